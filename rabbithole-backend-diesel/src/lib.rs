@@ -0,0 +1,346 @@
+//! The `Fetching` operations over a Diesel table, where the RSQL filter, the sort and the offset
+//! or page based pagination of a collection are run by the database, rather than in memory like
+//! `CollectionStore`:
+//! ```ignore
+//! impl DieselStore for HumanStore {
+//!     type Backend = Sqlite;
+//!     type Connection = SqliteConnection;
+//!     type Item = Human;
+//!     type Query = IntoBoxed<'static, humans::table, Sqlite>;
+//!     type Row = HumanRow;
+//!     type Table = humans::table;
+//!
+//!     const ID_COLUMN: &'static str = "humans.id";
+//!
+//!     fn query(&self) -> Self::Query { humans::table.into_boxed() }
+//!
+//!     fn column(&self, field: &str) -> Option<&'static str> {
+//!         match field {
+//!             "name" => Some("humans.name"),
+//!             _ => None,
+//!         }
+//!     }
+//!     ...
+//! }
+//!
+//! let service = JsonApiService::new(uri, version)?.resource(DieselService(HumanStore(conn)));
+//! ```
+//! NOTICE:
+//!   - The columns are the trusted SQL of `DieselStore::column`, and the values in the filters
+//!     are always bound, being a number, a boolean or a text as they are parsed
+//!   - The queries are run on the task of the request, as Diesel is blocking
+//!   - The cursor based pagination is still applied in memory
+
+use async_trait::async_trait;
+use diesel::backend::Backend;
+use diesel::dsl::sql;
+use diesel::expression::BoxableExpression;
+use diesel::query_dsl::methods::{FilterDsl, LimitDsl, OffsetDsl, ThenOrderDsl};
+use diesel::query_dsl::LoadQuery;
+use diesel::result::Error as DieselError;
+use diesel::sql_types::{BigInt, Bool, Double, Text};
+use diesel::{BoolExpressionMethods, Connection, QueryResult, RunQueryDsl};
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::document::Document;
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::filter::FilterQuery;
+use rabbithole::query::page::PageQuery;
+use rabbithole::query::sort::OrderType;
+use rabbithole::query::Query;
+use rabbithole::store;
+use rabbithole::RbhResult;
+use rsql_rs::ast::comparison;
+use rsql_rs::ast::constraint::Constraint;
+use rsql_rs::ast::expr::Expr;
+use rsql_rs::ast::Operator;
+use std::convert::TryFrom;
+
+/// A boolean expression on the table `QS`, like a translated filter
+pub type Predicate<QS, DB> = Box<dyn BoxableExpression<QS, DB, SqlType = Bool>>;
+
+/// A table of the resources of a type, where a row is selected by `query`
+pub trait DieselStore: Send + Sync {
+    type Item: SingleEntity + Send + Sync;
+    /// The table selected by `query`, which the filters are applied on
+    type Table: 'static;
+    type Backend: FilterBackend;
+    type Connection: Connection<Backend = Self::Backend>;
+    /// The query of all of the rows, which is usually `IntoBoxed<'static, table, Backend>`
+    type Query: FilterDsl<Predicate<Self::Table, Self::Backend>, Output = Self::Query>
+        + ThenOrderDsl<diesel::expression::SqlLiteral<Text>, Output = Self::Query>
+        + LimitDsl<Output = Self::Query>
+        + OffsetDsl<Output = Self::Query>
+        + RunQueryDsl<Self::Connection>
+        + for<'q> LoadQuery<'q, Self::Connection, Self::Row>;
+    type Row;
+
+    /// The column of the ids, like `humans.id`
+    const ID_COLUMN: &'static str;
+
+    fn query(&self) -> Self::Query;
+
+    /// The column of the attribute `field`, where the ones with no column can't be filtered, and
+    /// are ignored in the sort, like the fields which are not matched in the in-memory sort
+    fn column(&self, field: &str) -> Option<&'static str>;
+
+    /// Runs `f` with a connection, like the one from a pool
+    fn with_connection<R>(
+        &self, f: impl FnOnce(&mut Self::Connection) -> QueryResult<R>,
+    ) -> QueryResult<R>;
+
+    /// The items of the loaded rows, like loading their relationships with `belonging_to` if
+    /// `plan.includes` them
+    fn to_items(
+        &self, conn: &mut Self::Connection, rows: Vec<Self::Row>, plan: &FetchPlan<'_>,
+    ) -> QueryResult<Vec<Self::Item>>;
+
+    /// The predicate selecting the row of `id`, binding it as a text by default
+    fn find(&self, id: &str) -> Predicate<Self::Table, Self::Backend> {
+        Self::Backend::compare(Self::ID_COLUMN, "=", Value::Text(id.to_string()))
+    }
+}
+
+/// The backends which the filters can be built on, which are the ones of the `sqlite` and the
+/// `postgres` features
+pub trait FilterBackend: Backend + Sized {
+    /// The expression `<column> <operator> <value>` with `value` bound, where the `LIKE` patterns
+    /// are escaped by `\`
+    fn compare<QS: 'static>(column: &str, operator: &str, value: Value) -> Predicate<QS, Self>;
+
+    fn and<QS: 'static>(
+        left: Predicate<QS, Self>, right: Predicate<QS, Self>,
+    ) -> Predicate<QS, Self>;
+
+    fn or<QS: 'static>(
+        left: Predicate<QS, Self>, right: Predicate<QS, Self>,
+    ) -> Predicate<QS, Self>;
+}
+
+macro_rules! filter_backend {
+    ($backend:ty) => {
+        impl FilterBackend for $backend {
+            fn compare<QS: 'static>(
+                column: &str, operator: &str, value: Value,
+            ) -> Predicate<QS, Self> {
+                let fragment = sql::<Bool>(&format!("{} {} ", column, operator));
+                match value {
+                    Value::Text(v) if operator.ends_with("LIKE") => {
+                        Box::new(fragment.bind::<Text, _>(v).sql(" ESCAPE '\\'"))
+                    },
+                    Value::Text(v) => Box::new(fragment.bind::<Text, _>(v)),
+                    Value::Integer(v) => Box::new(fragment.bind::<BigInt, _>(v)),
+                    Value::Float(v) => Box::new(fragment.bind::<Double, _>(v)),
+                    Value::Bool(v) => Box::new(fragment.bind::<Bool, _>(v)),
+                }
+            }
+
+            fn and<QS: 'static>(
+                left: Predicate<QS, Self>, right: Predicate<QS, Self>,
+            ) -> Predicate<QS, Self> {
+                Box::new(left.and(right))
+            }
+
+            fn or<QS: 'static>(
+                left: Predicate<QS, Self>, right: Predicate<QS, Self>,
+            ) -> Predicate<QS, Self> {
+                Box::new(left.or(right))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "sqlite")]
+filter_backend!(diesel::sqlite::Sqlite);
+#[cfg(feature = "postgres")]
+filter_backend!(diesel::pg::Pg);
+
+/// A value in a filter, typed by how it's parsed as JSON, where the ones which are not JSON are
+/// texts, as RSQL strips the quotes
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Self {
+        match serde_json::from_str(value) {
+            Ok(serde_json::Value::Bool(v)) => Value::Bool(v),
+            Ok(serde_json::Value::Number(v)) => match v.as_i64() {
+                Some(v) => Value::Integer(v),
+                None => Value::Float(v.as_f64().unwrap_or_default()),
+            },
+            Ok(serde_json::Value::String(v)) => Value::Text(v),
+            _ => Value::Text(value.to_string()),
+        }
+    }
+}
+
+/// The `Fetching` service of a `DieselStore`
+pub struct DieselService<S>(pub S);
+
+impl<S: DieselStore> DieselService<S> {
+    /// The query of `plan`, with the filter, the sort and the pagination applied
+    pub fn collection_query(&self, plan: &FetchPlan<'_>) -> RbhResult<S::Query> {
+        let mut query = self.0.query();
+        if let Some(FilterQuery::Rsql(filter)) = &plan.query.filter {
+            for (ty_or_relat, expr) in filter.exprs() {
+                if ty_or_relat != &S::Item::ty() {
+                    return Err(error::Error::RsqlFilterOnRelatedNotImplemented(None));
+                }
+                query = query.filter(self.predicate(expr)?);
+            }
+        }
+        for (field, order) in plan.query.sort.fields() {
+            if let Some(column) = self.0.column(field) {
+                let order = if order == &OrderType::Desc { "DESC" } else { "ASC" };
+                query = query.then_order_by(sql::<Text>(&format!("{} {}", column, order)));
+            }
+        }
+        let (page_type, offset, limit) = match &plan.query.page {
+            Some(PageQuery::OffsetBased(page)) => ("OffsetBased", Some(page.offset), page.limit),
+            Some(PageQuery::PageBased(page)) => {
+                ("PageBased", page.number.checked_mul(page.size), page.size)
+            },
+            _ => return Ok(query),
+        };
+        let to_i64 = |value: Option<usize>| value.and_then(|value| i64::try_from(value).ok());
+        match (to_i64(offset), to_i64(Some(limit))) {
+            (Some(offset), Some(limit)) => Ok(query.offset(offset).limit(limit)),
+            _ => Err(error::Error::PageOutOfRange(page_type, None)),
+        }
+    }
+
+    /// The predicate of the RSQL `expr`, where `=in=` and `=out=` are expanded into `OR`s and
+    /// `AND`s, and the texts with `*` are matched with `LIKE`
+    fn predicate(&self, expr: &Expr) -> RbhResult<Predicate<S::Table, S::Backend>> {
+        match expr {
+            Expr::Item(constraint) => self.constraint(constraint),
+            Expr::Node(Operator::And, left, right) => {
+                Ok(S::Backend::and(self.predicate(left)?, self.predicate(right)?))
+            },
+            Expr::Node(Operator::Or, left, right) => {
+                Ok(S::Backend::or(self.predicate(left)?, self.predicate(right)?))
+            },
+        }
+    }
+
+    fn constraint(&self, constraint: &Constraint) -> RbhResult<Predicate<S::Table, S::Backend>> {
+        let Constraint { selector, comparison, arguments } = constraint;
+        let column =
+            self.0.column(selector).ok_or_else(|| error::Error::FieldNotExist(selector, None))?;
+        let compare = |arg: &str, equal: bool| -> Predicate<S::Table, S::Backend> {
+            let (operator, value) = match Value::from(arg) {
+                Value::Text(v) if v.contains('*') => {
+                    (if equal { "LIKE" } else { "NOT LIKE" }, Value::Text(like_pattern(&v)))
+                },
+                value => (if equal { "=" } else { "<>" }, value),
+            };
+            S::Backend::compare(column, operator, value)
+        };
+        let operator = if comparison == &comparison::GREATER_THAN as &comparison::Comparison {
+            ">"
+        } else if comparison == &comparison::GREATER_THAN_OR_EQUAL as &comparison::Comparison {
+            ">="
+        } else if comparison == &comparison::LESS_THAN as &comparison::Comparison {
+            "<"
+        } else if comparison == &comparison::LESS_THAN_OR_EQUAL as &comparison::Comparison {
+            "<="
+        } else if comparison == &comparison::EQUAL as &comparison::Comparison {
+            return Ok(compare(&arguments.0[0], true));
+        } else if comparison == &comparison::NOT_EQUAL as &comparison::Comparison {
+            return Ok(compare(&arguments.0[0], false));
+        } else if comparison == &comparison::IN as &comparison::Comparison {
+            return Ok(fold(arguments.0.iter().map(|arg| compare(arg, true)), S::Backend::or));
+        } else if comparison == &comparison::OUT as &comparison::Comparison {
+            return Ok(fold(arguments.0.iter().map(|arg| compare(arg, false)), S::Backend::and));
+        } else {
+            return Err(error::Error::UnsupportedRsqlComparison(
+                &comparison.symbols,
+                arguments.0.len(),
+                None,
+            ));
+        };
+        Ok(S::Backend::compare(column, operator, Value::from(arguments.0[0].as_str())))
+    }
+
+    fn load(&self, query: S::Query, plan: &FetchPlan<'_>) -> RbhResult<Vec<S::Item>> {
+        self.0
+            .with_connection(|conn| {
+                let rows = query.load::<S::Row>(conn)?;
+                self.0.to_items(conn, rows, plan)
+            })
+            .map_err(|err| to_error(&err))
+    }
+
+    fn load_single(&self, id: &str, plan: &FetchPlan<'_>) -> RbhResult<Option<S::Item>> {
+        let query = self.0.query().filter(self.0.find(id)).limit(1);
+        Ok(self.load(query, plan)?.into_iter().next())
+    }
+}
+
+/// The `LIKE` pattern of the RSQL `value`, where `*` is the only wildcard, as `%` and `_` are
+/// escaped by `\`, the `ESCAPE` character of `FilterBackend::compare`
+fn like_pattern(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_").replace('*', "%")
+}
+
+/// `items` is never empty, as a RSQL constraint has at least one argument
+fn fold<T>(items: impl Iterator<Item = T>, f: impl Fn(T, T) -> T) -> T {
+    let mut items = items;
+    let first = items.next().expect("at least one argument");
+    items.fold(first, f)
+}
+
+/// The JSON:API error of `err`, where the details of the database are not exposed
+pub fn to_error(err: &DieselError) -> error::Error {
+    let reason = match err {
+        DieselError::NotFound => "the row is not found".to_string(),
+        DieselError::DatabaseError(kind, _) => format!("{:?}", kind),
+        DieselError::DeserializationError(_) => "a row cannot be deserialized".to_string(),
+        _ => "the query cannot be run".to_string(),
+    };
+    error::Error::StorageFailed(&reason, None)
+}
+
+#[async_trait]
+impl<S: DieselStore> Fetching for DieselService<S> {
+    type Item = S::Item;
+
+    async fn vec_to_document(
+        &self, items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<Document, error::Error> {
+        store::storage_document(items, uri, query, request_path)
+    }
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        self.load(self.collection_query(plan)?, plan)
+    }
+
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        self.load_single(id, plan)
+    }
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, _request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        let item = self.load_single(id, &FetchPlan::new::<S::Item>(query))?;
+        store::relationship_of(item, related_field, uri)
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        let item = self.load_single(id, &FetchPlan::new::<S::Item>(query))?;
+        store::related_of(item, related_field, uri, query, request_path)
+    }
+}
@@ -0,0 +1,225 @@
+#![cfg(feature = "sqlite")]
+
+use diesel::dsl::IntoBoxed;
+use diesel::prelude::*;
+use diesel::sqlite::{Sqlite, SqliteConnection};
+use futures::executor::block_on;
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::Query;
+use rabbithole_backend_diesel::{DieselService, DieselStore, Value};
+use std::sync::Mutex;
+
+diesel::table! {
+    humans (id) {
+        id -> Text,
+        name -> Text,
+        age -> Integer,
+    }
+}
+
+diesel::table! {
+    dogs (id) {
+        id -> Text,
+        name -> Text,
+        master_id -> Text,
+    }
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub age: i32,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(
+    rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone, Queryable,
+)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[serde(skip)]
+    pub master_id: String,
+}
+
+#[derive(Queryable)]
+pub struct HumanRow {
+    pub id: String,
+    pub name: String,
+    pub age: i32,
+}
+
+struct HumanStore(Mutex<SqliteConnection>);
+
+impl DieselStore for HumanStore {
+    type Backend = Sqlite;
+    type Connection = SqliteConnection;
+    type Item = Human;
+    type Query = IntoBoxed<'static, humans::table, Sqlite>;
+    type Row = HumanRow;
+    type Table = humans::table;
+
+    const ID_COLUMN: &'static str = "humans.id";
+
+    fn query(&self) -> Self::Query { humans::table.into_boxed() }
+
+    fn column(&self, field: &str) -> Option<&'static str> {
+        match field {
+            "name" => Some("humans.name"),
+            "age" => Some("humans.age"),
+            _ => None,
+        }
+    }
+
+    fn with_connection<R>(
+        &self, f: impl FnOnce(&mut SqliteConnection) -> QueryResult<R>,
+    ) -> QueryResult<R> {
+        f(&mut self.0.lock().unwrap())
+    }
+
+    fn to_items(
+        &self, conn: &mut SqliteConnection, rows: Vec<HumanRow>, plan: &FetchPlan<'_>,
+    ) -> QueryResult<Vec<Human>> {
+        let ids: Vec<&str> = rows.iter().map(|row| row.id.as_str()).collect();
+        let dogs: Vec<Dog> = if plan.includes("dogs") || plan.selects("dogs") {
+            dogs::table.filter(dogs::master_id.eq_any(ids)).load(conn)?
+        } else {
+            vec![]
+        };
+        Ok(rows
+            .into_iter()
+            .map(|row| Human {
+                dogs: dogs.iter().filter(|dog| dog.master_id == row.id).cloned().collect(),
+                id: row.id,
+                name: row.name,
+                age: row.age,
+            })
+            .collect())
+    }
+}
+
+fn service() -> DieselService<HumanStore> {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    for stmt in &[
+        "CREATE TABLE humans (id TEXT PRIMARY KEY, name TEXT NOT NULL, age INTEGER NOT NULL)",
+        "CREATE TABLE dogs (id TEXT PRIMARY KEY, name TEXT NOT NULL, master_id TEXT NOT NULL)",
+        "INSERT INTO humans VALUES ('1', 'Alice', 30), ('2', 'Bob', 20), ('3', 'Carol', 40)",
+        "INSERT INTO dogs VALUES ('1', 'Dog of Alice', '1'), ('2', 'Dog of Bob', '2')",
+    ] {
+        diesel::sql_query(*stmt).execute(&mut conn).unwrap();
+    }
+    DieselService(HumanStore(Mutex::new(conn)))
+}
+
+fn query(query: &str) -> Query {
+    let query = percent_encode(query.as_bytes(), NON_ALPHANUMERIC);
+    let uri: http::Uri = format!("/people?{}", query).parse().unwrap();
+    Query::from_uri(&uri).unwrap()
+}
+
+fn names(service: &DieselService<HumanStore>, query: &str) -> Vec<String> {
+    let query = self::query(query);
+    let items = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    let doc = block_on(service.vec_to_document(
+        &items,
+        "http://localhost",
+        &query,
+        &"/people".parse().unwrap(),
+    ))
+    .unwrap();
+    let doc = serde_json::to_value(doc).unwrap();
+    doc["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap().to_string())
+        .collect()
+}
+
+#[test]
+fn collection_test() {
+    let service = service();
+    assert_eq!(names(&service, "sort=-name"), vec!["Carol", "Bob", "Alice"]);
+    assert_eq!(names(&service, "sort=age&page[offset]=1&page[limit]=1"), vec!["Alice"]);
+    assert_eq!(names(&service, "sort=name&page[number]=1&page[size]=2"), vec!["Carol"]);
+    assert_eq!(names(&service, "sort=name&filter[@type]=Rsql&filter[people]=age>25"), vec![
+        "Alice", "Carol"
+    ]);
+    assert_eq!(
+        names(&service, "sort=name&filter[@type]=Rsql&filter[people]=name==*o*;age=out=(40)"),
+        vec!["Bob"]
+    );
+    assert_eq!(
+        names(&service, "sort=name&filter[@type]=Rsql&filter[people]=name=in=(Alice,Bob),age==40"),
+        vec!["Alice", "Bob", "Carol"]
+    );
+
+    let query = self::query("filter[@type]=Rsql&filter[people]=color==red");
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0401"));
+
+    let query = self::query(&format!("page[number]={}&page[size]=2", usize::MAX));
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0111"));
+    assert_eq!(err.status.as_deref(), Some("400"));
+}
+
+#[test]
+fn like_test() {
+    let service = service();
+    for stmt in &[
+        "INSERT INTO humans VALUES ('4', 'a_b', 10), ('5', 'axbc', 10)",
+        "INSERT INTO humans VALUES ('6', '50%', 10), ('7', '500', 10)",
+    ] {
+        service.0.with_connection(|conn| diesel::sql_query(*stmt).execute(conn)).unwrap();
+    }
+
+    // `%` and `_` are matched as they are, where `*` is the only wildcard
+    assert_eq!(names(&service, "filter[@type]=Rsql&filter[people]=name==a_b*"), vec!["a_b"]);
+    assert_eq!(names(&service, "filter[@type]=Rsql&filter[people]=name==50%*"), vec!["50%"]);
+    assert_eq!(
+        names(&service, "sort=name&filter[@type]=Rsql&filter[people]=name!=*_*;age==10"),
+        vec!["50%", "500", "axbc"]
+    );
+}
+
+#[test]
+fn single_test() {
+    let service = service();
+    let query = Query::default();
+    let plan = FetchPlan::new::<Human>(&query);
+    let alice = block_on(service.fetch_single("1", &plan)).unwrap().unwrap();
+    assert_eq!(alice.name, "Alice");
+    assert_eq!(alice.dogs.len(), 1);
+    assert!(block_on(service.fetch_single("4", &plan)).unwrap().is_none());
+
+    let relationship = block_on(service.fetch_relationship(
+        "2",
+        "dogs",
+        "http://localhost",
+        &query,
+        &"/people/2/relationships/dogs".parse().unwrap(),
+    ))
+    .unwrap();
+    assert_eq!(serde_json::to_value(relationship.data).unwrap()[0]["id"], "2");
+    let err = block_on(service.fetch_relationship(
+        "4",
+        "dogs",
+        "http://localhost",
+        &query,
+        &"/people/4/relationships/dogs".parse().unwrap(),
+    ))
+    .err()
+    .unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0404"));
+
+    assert_eq!(Value::from("12"), Value::Integer(12));
+    assert_eq!(Value::from("Alice"), Value::Text("Alice".into()));
+}
@@ -0,0 +1,314 @@
+//! The `Fetching` operations over a SeaORM entity, where the RSQL filter, the sort and the offset
+//! or page based pagination of a collection are run by the database, like the ones of
+//! `rabbithole-backend-diesel`. The attributes are mapped to the columns of the same names by
+//! default, and the related models are loaded by `Related` only if they are rendered:
+//! ```ignore
+//! #[async_trait]
+//! impl SeaOrmStore for HumanStore {
+//!     type Entity = human::Entity;
+//!     type Item = Human;
+//!
+//!     fn connection(&self) -> &DatabaseConnection { &self.0 }
+//!
+//!     async fn to_items(
+//!         &self, models: Vec<human::Model>, plan: &FetchPlan<'_>,
+//!     ) -> Result<Vec<Human>, DbErr> {
+//!         let dogs = load_related(&models, dog::Entity, "dogs", plan, self.connection()).await?;
+//!         Ok(models.into_iter().zip(dogs).map(|(model, dogs)| Human::new(model, dogs)).collect())
+//!     }
+//! }
+//!
+//! let service = JsonApiService::new(uri, version)?.resource(SeaOrmService(HumanStore(db)));
+//! ```
+//! NOTICE:
+//!   - The values in the filters are bound, typed by the types of their columns
+//!   - The cursor based pagination is still applied in memory
+
+use async_trait::async_trait;
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::document::Document;
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::filter::FilterQuery;
+use rabbithole::query::page::PageQuery;
+use rabbithole::query::sort::OrderType;
+use rabbithole::query::Query;
+use rabbithole::store;
+use rabbithole::RbhResult;
+use rsql_rs::ast::comparison;
+use rsql_rs::ast::constraint::Constraint;
+use rsql_rs::ast::expr::Expr;
+use rsql_rs::ast::Operator;
+use sea_orm::sea_query::{self, LikeExpr, SimpleExpr};
+use sea_orm::{
+    ColumnTrait, ColumnType, Condition, ConnectionTrait, DatabaseConnection, DbErr, EntityTrait,
+    Iterable, LoaderTrait, ModelTrait, Order, PrimaryKeyToColumn, QueryFilter, QueryOrder,
+    QuerySelect, Related, Select, Value,
+};
+use std::str::FromStr;
+
+type Column<S> = <<S as SeaOrmStore>::Entity as EntityTrait>::Column;
+type Model<S> = <<S as SeaOrmStore>::Entity as EntityTrait>::Model;
+
+/// An entity of the resources of a type
+#[async_trait]
+pub trait SeaOrmStore: Send + Sync {
+    type Item: SingleEntity + Send + Sync;
+    type Entity: EntityTrait;
+
+    fn connection(&self) -> &DatabaseConnection;
+
+    /// The column of the attribute `field`, which is the column of the same name by default, where
+    /// the ones with no column can't be filtered, and are ignored in the sort
+    fn column(&self, field: &str) -> Option<Column<Self>> { Column::<Self>::from_str(field).ok() }
+
+    /// The condition selecting the model of `id` by the first column of the primary key, which is
+    /// `None` if `id` can't be a value of the column, like a text for an integer column
+    fn find(&self, id: &str) -> Option<Condition> {
+        let column = <Self::Entity as EntityTrait>::PrimaryKey::iter().next()?.into_column();
+        let value = value_of(&column, "id", id).ok()?;
+        Some(Condition::all().add(column.eq(value)))
+    }
+
+    /// The items of the loaded models, like with their related models loaded by `load_related`
+    async fn to_items(
+        &self, models: Vec<Model<Self>>, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, DbErr>;
+}
+
+/// The related models in `related` of each of the `models` of the to-many relationship `field`,
+/// loaded by one query, or the empty ones with no query if the relationship is neither included
+/// nor selected by `plan`
+pub async fn load_related<M, R, C>(
+    models: &[M], related: R, field: &str, plan: &FetchPlan<'_>, db: &C,
+) -> Result<Vec<Vec<R::Model>>, DbErr>
+where
+    M: ModelTrait + Sync,
+    M::Entity: Related<R>,
+    R: EntityTrait,
+    R::Model: Send + Sync,
+    C: ConnectionTrait,
+{
+    if plan.includes(field) || plan.selects(field) {
+        models.load_many(related, db).await
+    } else {
+        Ok(models.iter().map(|_| vec![]).collect())
+    }
+}
+
+/// The related model in `related` of each of the `models` of the to-one relationship `field`, like
+/// `load_related`
+pub async fn load_related_one<M, R, C>(
+    models: &[M], related: R, field: &str, plan: &FetchPlan<'_>, db: &C,
+) -> Result<Vec<Option<R::Model>>, DbErr>
+where
+    M: ModelTrait + Sync,
+    M::Entity: Related<R>,
+    R: EntityTrait,
+    R::Model: Send + Sync,
+    C: ConnectionTrait,
+{
+    if plan.includes(field) || plan.selects(field) {
+        models.load_one(related, db).await
+    } else {
+        Ok(models.iter().map(|_| None).collect())
+    }
+}
+
+/// The `Fetching` service of a `SeaOrmStore`
+pub struct SeaOrmService<S>(pub S);
+
+impl<S: SeaOrmStore> SeaOrmService<S> {
+    /// The select of `plan`, with the filter, the sort and the pagination applied
+    pub fn collection_select(&self, plan: &FetchPlan<'_>) -> RbhResult<Select<S::Entity>> {
+        let mut select = S::Entity::find();
+        if let Some(FilterQuery::Rsql(filter)) = &plan.query.filter {
+            for (ty_or_relat, expr) in filter.exprs() {
+                if ty_or_relat != &S::Item::ty() {
+                    return Err(error::Error::RsqlFilterOnRelatedNotImplemented(None));
+                }
+                select = select.filter(self.condition(expr)?);
+            }
+        }
+        for (field, order) in plan.query.sort.fields() {
+            if let Some(column) = self.0.column(field) {
+                let order = if order == &OrderType::Desc { Order::Desc } else { Order::Asc };
+                select = select.order_by(column, order);
+            }
+        }
+        let offset = match &plan.query.page {
+            Some(PageQuery::OffsetBased(page)) => Some((page.offset, page.limit)),
+            Some(PageQuery::PageBased(page)) => {
+                page.number.checked_mul(page.size).map(|offset| (offset, page.size))
+            },
+            _ => return Ok(select),
+        };
+        match offset {
+            Some((offset, limit)) => Ok(select.offset(offset as u64).limit(limit as u64)),
+            None => Err(error::Error::PageOutOfRange("PageBased", None)),
+        }
+    }
+
+    /// The condition of the RSQL `expr`, where the texts with `*` are matched with `LIKE`
+    fn condition(&self, expr: &Expr) -> RbhResult<Condition> {
+        match expr {
+            Expr::Item(constraint) => Ok(Condition::all().add(self.constraint(constraint)?)),
+            Expr::Node(Operator::And, left, right) => {
+                Ok(Condition::all().add(self.condition(left)?).add(self.condition(right)?))
+            },
+            Expr::Node(Operator::Or, left, right) => {
+                Ok(Condition::any().add(self.condition(left)?).add(self.condition(right)?))
+            },
+        }
+    }
+
+    fn constraint(&self, constraint: &Constraint) -> RbhResult<SimpleExpr> {
+        let Constraint { selector, comparison, arguments } = constraint;
+        let column =
+            self.0.column(selector).ok_or_else(|| error::Error::FieldNotExist(selector, None))?;
+        let arg = arguments.0[0].as_str();
+        let value = |arg: &str| value_of(&column, selector, arg);
+        let values = || arguments.0.iter().map(|arg| value(arg)).collect::<RbhResult<Vec<_>>>();
+        let expr = if comparison == &comparison::EQUAL as &comparison::Comparison {
+            match pattern(arg) {
+                Some(pattern) => sea_query::Expr::col((column.entity_name(), column)).like(pattern),
+                None => column.eq(value(arg)?),
+            }
+        } else if comparison == &comparison::NOT_EQUAL as &comparison::Comparison {
+            match pattern(arg) {
+                Some(pattern) => {
+                    sea_query::Expr::col((column.entity_name(), column)).not_like(pattern)
+                },
+                None => column.ne(value(arg)?),
+            }
+        } else if comparison == &comparison::GREATER_THAN as &comparison::Comparison {
+            column.gt(value(arg)?)
+        } else if comparison == &comparison::GREATER_THAN_OR_EQUAL as &comparison::Comparison {
+            column.gte(value(arg)?)
+        } else if comparison == &comparison::LESS_THAN as &comparison::Comparison {
+            column.lt(value(arg)?)
+        } else if comparison == &comparison::LESS_THAN_OR_EQUAL as &comparison::Comparison {
+            column.lte(value(arg)?)
+        } else if comparison == &comparison::IN as &comparison::Comparison {
+            column.is_in(values()?)
+        } else if comparison == &comparison::OUT as &comparison::Comparison {
+            column.is_not_in(values()?)
+        } else {
+            return Err(error::Error::UnsupportedRsqlComparison(
+                &comparison.symbols,
+                arguments.0.len(),
+                None,
+            ));
+        };
+        Ok(expr)
+    }
+
+    async fn load(
+        &self, select: Select<S::Entity>, plan: &FetchPlan<'_>,
+    ) -> RbhResult<Vec<S::Item>> {
+        let models = select.all(self.0.connection()).await.map_err(|err| to_error(&err))?;
+        self.0.to_items(models, plan).await.map_err(|err| to_error(&err))
+    }
+
+    async fn load_single(&self, id: &str, plan: &FetchPlan<'_>) -> RbhResult<Option<S::Item>> {
+        let condition = match self.0.find(id) {
+            Some(condition) => condition,
+            None => return Ok(None),
+        };
+        let select = S::Entity::find().filter(condition).limit(1);
+        Ok(self.load(select, plan).await?.into_iter().next())
+    }
+}
+
+/// A value compared with `column` of the attribute `field`, typed by the type of the column,
+/// where the values of the other types are typed by how they are parsed as JSON, like the texts
+/// which are not JSON, as RSQL strips the quotes
+fn value_of<C: ColumnTrait>(column: &C, field: &str, arg: &str) -> RbhResult<Value> {
+    let ty = column.def().get_column_type().clone();
+    let mismatch = || error::Error::FieldNotMatch(field, &format!("{:?}", ty), arg, None);
+    match ty {
+        ColumnType::Char(_) | ColumnType::String(_) | ColumnType::Text => Ok(arg.into()),
+        ColumnType::TinyInteger
+        | ColumnType::SmallInteger
+        | ColumnType::Integer
+        | ColumnType::BigInteger
+        | ColumnType::TinyUnsigned
+        | ColumnType::SmallUnsigned
+        | ColumnType::Unsigned
+        | ColumnType::BigUnsigned => arg.parse::<i64>().map(Into::into).map_err(|_| mismatch()),
+        ColumnType::Float | ColumnType::Double | ColumnType::Decimal(_) => {
+            arg.parse::<f64>().map(Into::into).map_err(|_| mismatch())
+        },
+        ColumnType::Boolean => arg.parse::<bool>().map(Into::into).map_err(|_| mismatch()),
+        _ => Ok(match serde_json::from_str(arg) {
+            Ok(serde_json::Value::Bool(v)) => v.into(),
+            Ok(serde_json::Value::Number(v)) => match v.as_i64() {
+                Some(v) => v.into(),
+                None => v.as_f64().unwrap_or_default().into(),
+            },
+            Ok(serde_json::Value::String(v)) => v.into(),
+            _ => arg.into(),
+        }),
+    }
+}
+
+/// The `LIKE` pattern of a value with `*`, which is the only wildcard, as `%` and `_` are escaped
+/// by `\`
+fn pattern(arg: &str) -> Option<LikeExpr> {
+    if arg.contains('*') {
+        let arg = arg.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+        Some(LikeExpr::new(arg.replace('*', "%")).escape('\\'))
+    } else {
+        None
+    }
+}
+
+/// The JSON:API error of `err`, where the details of the database are not exposed
+pub fn to_error(err: &DbErr) -> error::Error {
+    let reason = match err {
+        DbErr::RecordNotFound(_) => "the row is not found",
+        DbErr::ConnectionAcquire(_) | DbErr::Conn(_) => "the connection failed",
+        DbErr::Type(_) | DbErr::Json(_) => "a row cannot be deserialized",
+        _ => "the query cannot be run",
+    };
+    error::Error::StorageFailed(reason, None)
+}
+
+#[async_trait]
+impl<S: SeaOrmStore> Fetching for SeaOrmService<S> {
+    type Item = S::Item;
+
+    async fn vec_to_document(
+        &self, items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<Document, error::Error> {
+        store::storage_document(items, uri, query, request_path)
+    }
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        self.load(self.collection_select(plan)?, plan).await
+    }
+
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        self.load_single(id, plan).await
+    }
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, _request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        let item = self.load_single(id, &FetchPlan::new::<S::Item>(query)).await?;
+        store::relationship_of(item, related_field, uri)
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        let item = self.load_single(id, &FetchPlan::new::<S::Item>(query)).await?;
+        store::related_of(item, related_field, uri, query, request_path)
+    }
+}
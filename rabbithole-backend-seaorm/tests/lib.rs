@@ -0,0 +1,229 @@
+use async_trait::async_trait;
+use futures::executor::block_on;
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::Query;
+use rabbithole_backend_seaorm::{load_related, SeaOrmService, SeaOrmStore};
+use sea_orm::{DatabaseBackend, DatabaseConnection, DbErr, MockDatabase, Transaction};
+
+mod human {
+    use sea_orm::entity::prelude::*;
+    use std::convert::TryInto;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    #[sea_orm(table_name = "humans")]
+    pub struct Model {
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub id: String,
+        pub name: String,
+        pub age: i32,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {
+        #[sea_orm(has_many = "super::dog::Entity")]
+        Dog,
+    }
+
+    impl Related<super::dog::Entity> for Entity {
+        fn to() -> RelationDef { Relation::Dog.def() }
+    }
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+mod dog {
+    use sea_orm::entity::prelude::*;
+    use std::convert::TryInto;
+
+    #[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+    #[sea_orm(table_name = "dogs")]
+    pub struct Model {
+        #[sea_orm(primary_key, auto_increment = false)]
+        pub id: String,
+        pub name: String,
+        pub master_id: String,
+    }
+
+    #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+    pub enum Relation {
+        #[sea_orm(
+            belongs_to = "super::human::Entity",
+            from = "Column::MasterId",
+            to = "super::human::Column::Id"
+        )]
+        Human,
+    }
+
+    impl Related<super::human::Entity> for Entity {
+        fn to() -> RelationDef { Relation::Human.def() }
+    }
+
+    impl ActiveModelBehavior for ActiveModel {}
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub age: i32,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+}
+
+struct HumanStore(DatabaseConnection);
+
+#[async_trait]
+impl SeaOrmStore for HumanStore {
+    type Entity = human::Entity;
+    type Item = Human;
+
+    fn connection(&self) -> &DatabaseConnection { &self.0 }
+
+    async fn to_items(
+        &self, models: Vec<human::Model>, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Human>, DbErr> {
+        let dogs = load_related(&models, dog::Entity, "dogs", plan, self.connection()).await?;
+        Ok(models
+            .into_iter()
+            .zip(dogs)
+            .map(|(model, dogs)| Human {
+                id: model.id,
+                name: model.name,
+                age: model.age,
+                dogs: dogs.into_iter().map(|dog| Dog { id: dog.id, name: dog.name }).collect(),
+            })
+            .collect())
+    }
+}
+
+fn human(id: &str, name: &str, age: i32) -> human::Model {
+    human::Model { id: id.into(), name: name.into(), age }
+}
+
+fn query(query: &str) -> Query {
+    let query = percent_encode(query.as_bytes(), NON_ALPHANUMERIC);
+    let uri: http::Uri = format!("/people?{}", query).parse().unwrap();
+    Query::from_uri(&uri).unwrap()
+}
+
+fn log(service: SeaOrmService<HumanStore>) -> Vec<Transaction> {
+    service.0 .0.into_transaction_log()
+}
+
+#[test]
+fn collection_test() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![human("3", "Carol", 40), human("1", "Alice", 30)]])
+        .into_connection();
+    let service = SeaOrmService(HumanStore(db));
+    let query = query(
+        "filter[@type]=Rsql&filter[people]=age>25;name=in=(Alice,Carol),name==*o*&sort=-name&\
+         page[offset]=1&page[limit]=2&fields[people]=name&include=",
+    );
+    let items = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    let doc = block_on(service.vec_to_document(
+        &items,
+        "http://localhost",
+        &query,
+        &"/people".parse().unwrap(),
+    ))
+    .unwrap();
+    let doc = serde_json::to_value(doc).unwrap();
+    let names: Vec<&str> = doc["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Carol", "Alice"]);
+    assert_eq!(log(service), vec![Transaction::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"SELECT "humans"."id", "humans"."name", "humans"."age" FROM "humans" WHERE "humans"."age" > $1 AND ("humans"."name" IN ($2, $3) OR "humans"."name" LIKE $4 ESCAPE E'\\') ORDER BY "humans"."name" DESC LIMIT $5 OFFSET $6"#,
+        vec![25i64.into(), "Alice".into(), "Carol".into(), "%o%".into(), 2u64.into(), 1u64.into()],
+    )]);
+
+    let db = MockDatabase::new(DatabaseBackend::Postgres).into_connection();
+    let service = SeaOrmService(HumanStore(db));
+    let query = self::query("filter[@type]=Rsql&filter[people]=color==red");
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0401"));
+    let query = self::query("filter[@type]=Rsql&filter[people]=age==old");
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0402"));
+    let query = self::query(&format!("page[number]={}&page[size]=2", usize::MAX));
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0111"));
+}
+
+#[test]
+fn like_test() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([Vec::<human::Model>::new()])
+        .into_connection();
+    let service = SeaOrmService(HumanStore(db));
+    let query = query("filter[@type]=Rsql&filter[people]=name==a_b%*");
+    block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    assert_eq!(log(service), vec![Transaction::from_sql_and_values(
+        DatabaseBackend::Postgres,
+        r#"SELECT "humans"."id", "humans"."name", "humans"."age" FROM "humans" WHERE "humans"."name" LIKE $1 ESCAPE E'\\'"#,
+        vec![r"a\_b\%%".into()],
+    )]);
+}
+
+#[test]
+fn single_test() {
+    let db = MockDatabase::new(DatabaseBackend::Postgres)
+        .append_query_results([vec![human("1", "Alice", 30)]])
+        .append_query_results([vec![dog::Model {
+            id: "1".into(),
+            name: "Dog of Alice".into(),
+            master_id: "1".into(),
+        }]])
+        .append_query_results([Vec::<human::Model>::new()])
+        .into_connection();
+    let service = SeaOrmService(HumanStore(db));
+    let query = Query::default();
+    let plan = FetchPlan::new::<Human>(&query);
+    let alice = block_on(service.fetch_single("1", &plan)).unwrap().unwrap();
+    assert_eq!(alice.name, "Alice");
+    assert_eq!(alice.dogs[0].name, "Dog of Alice");
+
+    let err = block_on(service.fetch_relationship(
+        "4",
+        "dogs",
+        "http://localhost",
+        &query,
+        &"/people/4/relationships/dogs".parse().unwrap(),
+    ))
+    .err()
+    .unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0404"));
+    assert_eq!(log(service), vec![
+        Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "humans"."id", "humans"."name", "humans"."age" FROM "humans" WHERE "humans"."id" = $1 LIMIT $2"#,
+            vec!["1".into(), 1u64.into()],
+        ),
+        Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "dogs"."id", "dogs"."name", "dogs"."master_id" FROM "dogs" WHERE "dogs"."master_id" IN ($1)"#,
+            vec!["1".into()],
+        ),
+        Transaction::from_sql_and_values(
+            DatabaseBackend::Postgres,
+            r#"SELECT "humans"."id", "humans"."name", "humans"."age" FROM "humans" WHERE "humans"."id" = $1 LIMIT $2"#,
+            vec!["4".into(), 1u64.into()],
+        ),
+    ]);
+}
@@ -0,0 +1,316 @@
+//! The `Fetching` operations over an Elasticsearch or OpenSearch index, for the resource types
+//! whose filters are too slow on the primary storage. The RSQL filter, the sort and the offset or
+//! page based pagination of a collection are run by the search engine, which answers the ids and
+//! the sources of the hits, turned into the items by the store, like loading them by the ids from
+//! the primary storage:
+//! ```ignore
+//! #[async_trait]
+//! impl SearchStore for HumanStore {
+//!     type Item = Human;
+//!
+//!     fn client(&self) -> &dyn SearchClient { &self.client }
+//!
+//!     fn index(&self) -> &str { "people" }
+//!
+//!     async fn to_items(&self, hits: Vec<Hit>, _: &FetchPlan<'_>) -> RbhResult<Vec<Human>> {
+//!         from_sources(hits)
+//!     }
+//! }
+//!
+//! let client = HttpSearchClient::new("http://localhost:9200");
+//! let service = JsonApiService::new(uri, version)?.resource(SearchService(HumanStore { client }));
+//! ```
+//! NOTICE:
+//!   - The attributes are mapped to the fields of the same names, and `id` to `_id`
+//!   - The texts with `*` are matched with `wildcard` queries, and the others with `term` queries,
+//!     so the text fields should be mapped as `keyword`s
+//!   - The cursor based pagination is still applied in memory
+
+use async_trait::async_trait;
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::document::Document;
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::filter::FilterQuery;
+use rabbithole::query::page::PageQuery;
+use rabbithole::query::sort::OrderType;
+use rabbithole::query::Query;
+use rabbithole::store;
+use rabbithole::RbhResult;
+use rsql_rs::ast::comparison;
+use rsql_rs::ast::constraint::Constraint;
+use rsql_rs::ast::expr::Expr;
+use rsql_rs::ast::Operator;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+/// Runs the searches of the request bodies of the search API on an index
+#[async_trait]
+pub trait SearchClient: Send + Sync {
+    /// The response of `POST /<index>/_search` with `body`
+    async fn search(&self, index: &str, body: &Value) -> RbhResult<Value>;
+}
+
+/// The client of the search API of an Elasticsearch or OpenSearch cluster at `url`, like
+/// `http://localhost:9200`
+#[derive(Debug, Clone)]
+pub struct HttpSearchClient {
+    pub url: String,
+    client: reqwest::Client,
+}
+
+impl HttpSearchClient {
+    pub fn new(url: &str) -> Self { Self::with_client(url, Default::default()) }
+
+    /// The client with `client`, like the one with the credentials in its default headers
+    pub fn with_client(url: &str, client: reqwest::Client) -> Self {
+        Self { url: url.trim_end_matches('/').to_string(), client }
+    }
+}
+
+#[async_trait]
+impl SearchClient for HttpSearchClient {
+    async fn search(&self, index: &str, body: &Value) -> RbhResult<Value> {
+        let url = format!("{}/{}/_search", self.url, index);
+        let resp =
+            self.client.post(&url).json(body).send().await.map_err(|_| {
+                error::Error::StorageFailed("the search engine is unreachable", None)
+            })?;
+        let status = resp.status();
+        let resp: Value = resp
+            .json()
+            .await
+            .map_err(|_| error::Error::StorageFailed("the search cannot be read", None))?;
+        if !status.is_success() || resp.get("error").is_some() {
+            return Err(error::Error::StorageFailed("the search cannot be run", None));
+        }
+        Ok(resp)
+    }
+}
+
+/// A hit of a search, with its source if the store asks for the sources
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hit {
+    pub id: String,
+    pub source: Option<Value>,
+}
+
+/// An index of the resources of a type
+#[async_trait]
+pub trait SearchStore: Send + Sync {
+    type Item: SingleEntity + Send + Sync;
+
+    fn client(&self) -> &dyn SearchClient;
+
+    fn index(&self) -> &str;
+
+    /// The field of the attribute `field`, which is the field of the same name by default, and
+    /// `_id` for `id`, where the attributes with no field can't be filtered, and are ignored in
+    /// the sort
+    fn field(&self, field: &str) -> Option<String> {
+        Some(if field == "id" { "_id".into() } else { field.into() })
+    }
+
+    /// If the hits are answered with their sources, which is not needed by the stores loading the
+    /// items by the ids from the primary storage
+    fn sources(&self) -> bool { true }
+
+    /// The size of the searches of the collections with no pagination, as the search engines
+    /// answer only 10 hits by default, where 10000 is the default `index.max_result_window`
+    fn max_hits(&self) -> usize { 10_000 }
+
+    /// The items of the hits, in the order of the hits
+    async fn to_items(&self, hits: Vec<Hit>, plan: &FetchPlan<'_>) -> RbhResult<Vec<Self::Item>>;
+}
+
+/// The items deserialized from the sources of `hits`, with the ids of the hits as their `id`s
+pub fn from_sources<E: DeserializeOwned>(hits: Vec<Hit>) -> RbhResult<Vec<E>> {
+    hits.into_iter()
+        .map(|Hit { id, source }| {
+            let mut source = source.unwrap_or_else(|| json!({}));
+            if let Some(source) = source.as_object_mut() {
+                source.insert("id".into(), id.into());
+            }
+            serde_json::from_value(source)
+                .map_err(|_| error::Error::StorageFailed("a source cannot be deserialized", None))
+        })
+        .collect()
+}
+
+/// The `Fetching` service of a `SearchStore`
+pub struct SearchService<S>(pub S);
+
+impl<S: SearchStore> SearchService<S> {
+    /// The body of the search of `plan`, with the filter, the sort and the pagination applied
+    pub fn collection_search(&self, plan: &FetchPlan<'_>) -> RbhResult<Value> {
+        let mut filters = vec![];
+        if let Some(FilterQuery::Rsql(filter)) = &plan.query.filter {
+            for (ty_or_relat, expr) in filter.exprs() {
+                if ty_or_relat != &S::Item::ty() {
+                    return Err(error::Error::RsqlFilterOnRelatedNotImplemented(None));
+                }
+                filters.push(self.query(expr)?);
+            }
+        }
+        let mut sort = vec![];
+        for (field, order) in plan.query.sort.fields() {
+            if let Some(field) = self.0.field(field) {
+                let order = if order == &OrderType::Desc { "desc" } else { "asc" };
+                sort.push(json!({ field: { "order": order } }));
+            }
+        }
+        let mut body = json!({
+            "query": { "bool": { "filter": filters } },
+            "sort": sort,
+            "_source": self.0.sources(),
+        });
+        let (from, size) = match &plan.query.page {
+            Some(PageQuery::OffsetBased(page)) => (page.offset, page.limit),
+            Some(PageQuery::PageBased(page)) => match page.number.checked_mul(page.size) {
+                Some(from) => (from, page.size),
+                None => return Err(error::Error::PageOutOfRange("PageBased", None)),
+            },
+            _ => (0, self.0.max_hits()),
+        };
+        body["from"] = from.into();
+        body["size"] = size.into();
+        Ok(body)
+    }
+
+    /// The query of the RSQL `expr`
+    fn query(&self, expr: &Expr) -> RbhResult<Value> {
+        match expr {
+            Expr::Item(constraint) => self.constraint(constraint),
+            Expr::Node(Operator::And, left, right) => {
+                Ok(json!({ "bool": { "filter": [self.query(left)?, self.query(right)?] } }))
+            },
+            Expr::Node(Operator::Or, left, right) => Ok(json!({ "bool": {
+                "should": [self.query(left)?, self.query(right)?],
+                "minimum_should_match": 1,
+            } })),
+        }
+    }
+
+    fn constraint(&self, constraint: &Constraint) -> RbhResult<Value> {
+        let Constraint { selector, comparison, arguments } = constraint;
+        let field =
+            self.0.field(selector).ok_or_else(|| error::Error::FieldNotExist(selector, None))?;
+        let arg = arguments.0[0].as_str();
+        let values: Vec<Value> = arguments.0.iter().map(|arg| value(arg)).collect();
+        let range = |op: &str| json!({ "range": { &field: { op: value(arg) } } });
+        let query = if comparison == &comparison::EQUAL as &comparison::Comparison {
+            self.equal(&field, arg)
+        } else if comparison == &comparison::NOT_EQUAL as &comparison::Comparison {
+            not(self.equal(&field, arg))
+        } else if comparison == &comparison::GREATER_THAN as &comparison::Comparison {
+            range("gt")
+        } else if comparison == &comparison::GREATER_THAN_OR_EQUAL as &comparison::Comparison {
+            range("gte")
+        } else if comparison == &comparison::LESS_THAN as &comparison::Comparison {
+            range("lt")
+        } else if comparison == &comparison::LESS_THAN_OR_EQUAL as &comparison::Comparison {
+            range("lte")
+        } else if comparison == &comparison::IN as &comparison::Comparison {
+            json!({ "terms": { field: values } })
+        } else if comparison == &comparison::OUT as &comparison::Comparison {
+            not(json!({ "terms": { field: values } }))
+        } else {
+            return Err(error::Error::UnsupportedRsqlComparison(
+                &comparison.symbols,
+                arguments.0.len(),
+                None,
+            ));
+        };
+        Ok(query)
+    }
+
+    /// The texts with `*` are matched with `wildcard`, where `*` is the only wildcard, as `?` is
+    /// escaped by `\`
+    fn equal(&self, field: &str, arg: &str) -> Value {
+        if arg.contains('*') {
+            let pattern = arg.replace('\\', "\\\\").replace('?', "\\?");
+            json!({ "wildcard": { field: { "value": pattern } } })
+        } else {
+            json!({ "term": { field: value(arg) } })
+        }
+    }
+
+    async fn search(&self, body: &Value, plan: &FetchPlan<'_>) -> RbhResult<Vec<S::Item>> {
+        let resp = self.0.client().search(self.0.index(), body).await?;
+        self.0.to_items(hits(&resp)?, plan).await
+    }
+
+    async fn search_single(&self, id: &str, plan: &FetchPlan<'_>) -> RbhResult<Option<S::Item>> {
+        let body = json!({
+            "query": { "ids": { "values": [id] } },
+            "_source": self.0.sources(),
+            "size": 1,
+        });
+        Ok(self.search(&body, plan).await?.into_iter().next())
+    }
+}
+
+/// A value in a filter, typed by how it's parsed as JSON, like the texts which are not JSON, as
+/// RSQL strips the quotes
+fn value(arg: &str) -> Value {
+    match serde_json::from_str(arg) {
+        Ok(value @ Value::Bool(_))
+        | Ok(value @ Value::Number(_))
+        | Ok(value @ Value::String(_)) => value,
+        _ => arg.into(),
+    }
+}
+
+fn not(query: Value) -> Value { json!({ "bool": { "must_not": [query] } }) }
+
+/// The hits of the response `resp` of a search
+fn hits(resp: &Value) -> RbhResult<Vec<Hit>> {
+    let hits = resp["hits"]["hits"]
+        .as_array()
+        .ok_or_else(|| error::Error::StorageFailed("the search answers no hits", None))?;
+    hits.iter()
+        .map(|hit| match hit["_id"].as_str() {
+            Some(id) => Ok(Hit { id: id.into(), source: hit.get("_source").cloned() }),
+            None => Err(error::Error::StorageFailed("a hit has no id", None)),
+        })
+        .collect()
+}
+
+#[async_trait]
+impl<S: SearchStore> Fetching for SearchService<S> {
+    type Item = S::Item;
+
+    async fn vec_to_document(
+        &self, items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<Document, error::Error> {
+        store::storage_document(items, uri, query, request_path)
+    }
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        self.search(&self.collection_search(plan)?, plan).await
+    }
+
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        self.search_single(id, plan).await
+    }
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, _request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        let item = self.search_single(id, &FetchPlan::new::<S::Item>(query)).await?;
+        store::relationship_of(item, related_field, uri)
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        let item = self.search_single(id, &FetchPlan::new::<S::Item>(query)).await?;
+        store::related_of(item, related_field, uri, query, request_path)
+    }
+}
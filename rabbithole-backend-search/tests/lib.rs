@@ -0,0 +1,148 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::Query;
+use rabbithole::RbhResult;
+use rabbithole_backend_search::{from_sources, Hit, SearchClient, SearchService, SearchStore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub age: i32,
+}
+
+/// Answers `hits` to every search, recording the searches
+#[derive(Default)]
+struct MockClient {
+    hits: Vec<Value>,
+    searches: Mutex<Vec<(String, Value)>>,
+}
+
+#[async_trait]
+impl SearchClient for MockClient {
+    async fn search(&self, index: &str, body: &Value) -> RbhResult<Value> {
+        self.searches.lock().unwrap().push((index.to_string(), body.clone()));
+        Ok(json!({ "hits": { "hits": self.hits } }))
+    }
+}
+
+struct HumanStore(MockClient);
+
+#[async_trait]
+impl SearchStore for HumanStore {
+    type Item = Human;
+
+    fn client(&self) -> &dyn SearchClient { &self.0 }
+
+    fn index(&self) -> &str { "people" }
+
+    async fn to_items(&self, hits: Vec<Hit>, _: &FetchPlan<'_>) -> RbhResult<Vec<Human>> {
+        from_sources(hits)
+    }
+}
+
+fn service(hits: Vec<Value>) -> SearchService<HumanStore> {
+    SearchService(HumanStore(MockClient { hits, ..Default::default() }))
+}
+
+fn query(query: &str) -> Query {
+    let query = percent_encode(query.as_bytes(), NON_ALPHANUMERIC);
+    let uri: http::Uri = format!("/people?{}", query).parse().unwrap();
+    Query::from_uri(&uri).unwrap()
+}
+
+fn searches(service: SearchService<HumanStore>) -> Vec<(String, Value)> {
+    service.0 .0.searches.into_inner().unwrap()
+}
+
+#[test]
+fn collection_test() {
+    let service = service(vec![
+        json!({ "_id": "3", "_source": { "name": "Carol", "age": 40 } }),
+        json!({ "_id": "1", "_source": { "name": "Alice", "age": 30 } }),
+    ]);
+    let query = query(
+        "filter[@type]=Rsql&filter[people]=age>25;name=in=(Alice,Carol),name==*o*&sort=-name&\
+         page[offset]=1&page[limit]=2",
+    );
+    let items = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    let ids: Vec<&str> = items.iter().map(|human| human.id.as_str()).collect();
+    assert_eq!(ids, vec!["3", "1"]);
+    assert_eq!(items[0].name, "Carol");
+    assert_eq!(searches(service), vec![(
+        "people".to_string(),
+        json!({
+            "query": { "bool": { "filter": [{ "bool": { "filter": [
+                { "range": { "age": { "gt": 25 } } },
+                { "bool": {
+                    "should": [
+                        { "terms": { "name": ["Alice", "Carol"] } },
+                        { "wildcard": { "name": { "value": "*o*" } } },
+                    ],
+                    "minimum_should_match": 1,
+                } },
+            ] } }] } },
+            "sort": [{ "name": { "order": "desc" } }],
+            "_source": true,
+            "from": 1,
+            "size": 2,
+        })
+    )]);
+
+    let service = self::service(vec![]);
+    let query = self::query("filter[@type]=Rsql&filter[people]=id!=1");
+    block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    assert_eq!(
+        searches(service)[0].1,
+        json!({
+            "query": { "bool": { "filter": [
+                { "bool": { "must_not": [{ "term": { "_id": 1 } }] } },
+            ] } },
+            "sort": [],
+            "_source": true,
+            "from": 0,
+            "size": 10000,
+        })
+    );
+
+    let service = self::service(vec![]);
+    let query = self::query("filter[@type]=Rsql&filter[people]=name==a?b*");
+    block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).unwrap();
+    assert_eq!(
+        searches(service)[0].1["query"]["bool"]["filter"][0],
+        json!({ "wildcard": { "name": { "value": r"a\?b*" } } })
+    );
+    let service = self::service(vec![]);
+    let query = self::query(&format!("page[number]={}&page[size]=2", usize::MAX));
+    let err = block_on(service.fetch_collection(&FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0111"));
+}
+
+#[test]
+fn single_test() {
+    let service = service(vec![json!({ "_id": "1", "_source": { "name": "Alice", "age": 30 } })]);
+    let query = Query::default();
+    let alice = block_on(service.fetch_single("1", &FetchPlan::new::<Human>(&query)));
+    assert_eq!(alice.unwrap().unwrap().name, "Alice");
+    assert_eq!(searches(service), vec![(
+        "people".to_string(),
+        json!({
+            "query": { "ids": { "values": ["1"] } },
+            "_source": true,
+            "size": 1,
+        })
+    )]);
+
+    let service = self::service(vec![json!({ "_source": {} })]);
+    let err = block_on(service.fetch_single("1", &FetchPlan::new::<Human>(&query))).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0009"));
+}
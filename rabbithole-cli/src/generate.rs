@@ -0,0 +1,280 @@
+//! The files of a new server of a `Schema`, which is an actix app with:
+//!   - `src/entities.rs`: the entities with the derive annotations
+//!   - `src/services.rs`: a `Fetching` service of each entity, to be filled with the storage
+//!   - `src/main.rs`: the app mounting all of the services, with the settings in `config/app.toml`
+//!   - `tests/api_test.rs`: the ignored test stubs of the collections, until the services are done
+
+use crate::schema::{EntitySchema, FieldKind, Schema};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+/// A generated file, where `path` is relative to the root of the project
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeneratedFile {
+    pub path: PathBuf,
+    pub content: String,
+}
+
+impl GeneratedFile {
+    fn new(path: &str, content: String) -> Self { Self { path: path.into(), content } }
+}
+
+/// The files of the project `name` of `schema`
+pub fn generate(schema: &Schema, name: &str) -> Vec<GeneratedFile> {
+    let lib_name = name.replace('-', "_");
+    vec![
+        GeneratedFile::new("Cargo.toml", manifest(schema, name)),
+        GeneratedFile::new("config/app.toml", SETTINGS.to_string()),
+        GeneratedFile::new("src/lib.rs", LIB.to_string()),
+        GeneratedFile::new("src/entities.rs", entities(schema)),
+        GeneratedFile::new("src/services.rs", services(schema)),
+        GeneratedFile::new("src/main.rs", main(schema, &lib_name)),
+        GeneratedFile::new("tests/api_test.rs", tests(schema, &lib_name)),
+    ]
+}
+
+/// Writes `files` under `root`, where the existing files are kept unless `force`
+pub fn write(files: &[GeneratedFile], root: &Path, force: bool) -> io::Result<()> {
+    if !force {
+        if let Some(file) = files.iter().find(|file| root.join(&file.path).exists()) {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("`{}` exists already", root.join(&file.path).display()),
+            ));
+        }
+    }
+    for file in files {
+        let path = root.join(&file.path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(path, &file.content)?;
+    }
+    Ok(())
+}
+
+const SETTINGS: &str = r#"host = "localhost"
+port = 8080
+path = "/api"
+
+[jsonapi]
+version = "1.0"
+"#;
+
+const LIB: &str = r#"extern crate rabbithole_derive as rbh_derive;
+
+pub mod entities;
+pub mod services;
+"#;
+
+fn manifest(schema: &Schema, name: &str) -> String {
+    let uuid = if schema.uses("Uuid") {
+        "uuid = { version = \"~0.8\", features = [\"serde\", \"v4\"] }\n"
+    } else {
+        ""
+    };
+    format!(
+        r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2018"
+
+[dependencies]
+actix-web = "~4"
+async-trait = "~0.1"
+serde = {{ version = "~1.0", features = ["derive"] }}
+serde_json = "~1.0"
+{uuid}rabbithole = "~0.3"
+rabbithole-derive = "~0.3"
+rabbithole-endpoint-actix = "~0.3"
+"#,
+        name = name,
+        uuid = uuid
+    )
+}
+
+/// The collections of `std` imported by the entities if they are used
+const COLLECTIONS: &[&str] = &["BTreeMap", "BTreeSet", "HashMap", "HashSet"];
+
+fn entities(schema: &Schema) -> String {
+    let mut out = String::from("use serde::{Deserialize, Serialize};\n");
+    let collections: Vec<&str> =
+        COLLECTIONS.iter().copied().filter(|collection| schema.uses(collection)).collect();
+    match collections.as_slice() {
+        [] => {},
+        [collection] => {
+            let _ = writeln!(out, "use std::collections::{};", collection);
+        },
+        _ => {
+            let _ = writeln!(out, "use std::collections::{{{}}};", collections.join(", "));
+        },
+    }
+    if schema.uses("Uuid") {
+        out.push_str("use uuid::Uuid;\n");
+    }
+    for entity in &schema.entities {
+        out.push('\n');
+        out.push_str(
+            "#[derive(rbh_derive::EntityDecorator, rbh_derive::FromResource, Serialize, \
+             Deserialize, Clone, Debug)]\n",
+        );
+        let _ = writeln!(out, "#[entity(type = \"{}\")]", entity.ty);
+        let _ = writeln!(out, "pub struct {} {{", entity.name);
+        out.push_str("    #[entity(id)]\n");
+        for field in std::iter::once(&entity.id).chain(&entity.fields) {
+            let ty = match &field.kind {
+                FieldKind::Attribute(ty) => ty.clone(),
+                FieldKind::ToOne(ty) => {
+                    out.push_str("    #[entity(to_one)]\n");
+                    format!("Option<Box<{}>>", ty)
+                },
+                FieldKind::ToMany(ty) => {
+                    out.push_str("    #[entity(to_many)]\n");
+                    format!("Vec<{}>", ty)
+                },
+            };
+            let _ = writeln!(out, "    pub {}: {},", field.name, ty);
+        }
+        out.push_str("}\n");
+    }
+    out
+}
+
+fn service_name(entity: &EntitySchema) -> String { format!("{}Service", entity.name) }
+
+fn services(schema: &Schema) -> String {
+    let mut out = String::from(
+        r#"use crate::entities::*;
+use async_trait::async_trait;
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::Query;
+use rabbithole::store::{related_of, relationship_of};
+"#,
+    );
+    for entity in &schema.entities {
+        let _ = write!(
+            out,
+            r#"
+/// The service of `{name}`, where the relationships are answered by `fetch_single`
+#[derive(Default)]
+pub struct {service};
+
+#[async_trait]
+impl Fetching for {service} {{
+    type Item = {name};
+
+    async fn fetch_collection(
+        &self, _plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {{
+        todo!("load the `{ty}` matching `plan.query`")
+    }}
+
+    async fn fetch_single(
+        &self, _id: &str, _plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {{
+        todo!("load the `{ty}` with `id`")
+    }}
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, _request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {{
+        let item = self.fetch_single(id, &FetchPlan::new::<Self::Item>(query)).await?;
+        relationship_of(item, related_field, uri)
+    }}
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {{
+        let item = self.fetch_single(id, &FetchPlan::new::<Self::Item>(query)).await?;
+        related_of(item, related_field, uri, query, request_path)
+    }}
+}}
+"#,
+            name = entity.name,
+            service = service_name(entity),
+            ty = entity.ty,
+        );
+    }
+    out
+}
+
+fn main(schema: &Schema, lib_name: &str) -> String {
+    let resources: String = schema
+        .entities
+        .iter()
+        .map(|entity| format!("\n        .resource::<{}>()", service_name(entity)))
+        .collect();
+    format!(
+        r#"use actix_web::{{App, HttpServer}};
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use {lib_name}::services::*;
+
+pub fn api(settings: ActixSettingsModel) -> JsonApiApp {{
+    JsonApiApp::new(settings)
+        .expect("the settings are invalid"){resources}
+}}
+
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {{
+    // The settings in the file can be overridden by the environment variables, like `RBH__PORT`
+    let settings = ActixSettingsModel::load(Some("config/app.toml"), &[])
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidInput, err.to_string()))?;
+    let port = settings.port;
+    let api = api(settings);
+    HttpServer::new(move || App::new().service(api.scope()))
+        .bind(format!("[::]:{{}}", port))?
+        .run()
+        .await
+}}
+"#,
+        lib_name = lib_name,
+        resources = resources,
+    )
+}
+
+fn tests(schema: &Schema, lib_name: &str) -> String {
+    let resources: String = schema
+        .entities
+        .iter()
+        .map(|entity| format!("\n        .resource::<{}>()", service_name(entity)))
+        .collect();
+    let mut out = format!(
+        r#"use actix_web::http::StatusCode;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::testing;
+use {lib_name}::entities::*;
+use {lib_name}::services::*;
+
+fn api() -> JsonApiApp {{
+    let settings = ActixSettingsModel::load(Some("config/app.toml"), &[]).unwrap();
+    JsonApiApp::new(settings).unwrap(){resources}
+}}
+"#,
+        lib_name = lib_name,
+        resources = resources,
+    );
+    for entity in &schema.entities {
+        let _ = write!(
+            out,
+            r#"
+#[actix_web::test]
+#[ignore = "`{service}` is not implemented yet"]
+async fn {fn_name}_test() {{
+    let app = testing::init(&api()).await;
+    let (status, _doc) = app.get_collection::<{name}>("").await;
+    assert_eq!(status, StatusCode::OK);
+}}
+"#,
+            service = service_name(entity),
+            fn_name = entity.ty.replace(|c: char| !c.is_alphanumeric(), "_").to_lowercase(),
+            name = entity.name,
+        );
+    }
+    out
+}
@@ -0,0 +1,7 @@
+//! Scaffolding of the JSON:API servers, generating the entities, the services, the app and the
+//! test stubs of a short schema, like `cargo rabbithole new schema.txt my-server`
+#[macro_use]
+extern crate thiserror;
+
+pub mod generate;
+pub mod schema;
@@ -0,0 +1,64 @@
+use rabbithole::typescript;
+use rabbithole_cli::generate::{generate, write};
+use rabbithole_cli::schema::Schema;
+use std::path::PathBuf;
+use std::process;
+
+const USAGE: &str = "Usage:
+    cargo rabbithole new <SCHEMA> [DIR] [--force]
+    cargo rabbithole typescript <SCHEMA> [FILE]
+
+`new` generates a JSON:API server of the entities in the file SCHEMA into DIR (`.` by default),
+and `typescript` generates the TypeScript definitions of their resources into FILE (the standard
+output by default), where SCHEMA is like:
+    Human(people): id: Uuid, name: String, dogs: [Dog], best_friend: Human?
+    Dog(dogs): id: Uuid, name: String
+
+Options:
+    --force    Overwrites the existing files";
+
+fn main() {
+    // `cargo rabbithole` runs `cargo-rabbithole rabbithole ...`
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("rabbithole") {
+        args.remove(0);
+    }
+    let force = args.iter().any(|arg| arg == "--force");
+    args.retain(|arg| arg != "--force");
+
+    let (schema, dir) = match args.iter().map(String::as_str).collect::<Vec<_>>().as_slice() {
+        ["new", schema] => (read_schema(schema), PathBuf::from(".")),
+        ["new", schema, dir] => (read_schema(schema), PathBuf::from(dir)),
+        ["typescript", schema] => {
+            print!("{}", typescript::generate(&read_schema(schema).registry()));
+            return;
+        },
+        ["typescript", schema, file] => {
+            let definitions = typescript::generate(&read_schema(schema).registry());
+            if let Err(err) = std::fs::write(file, definitions) {
+                exit(&format!("Cannot write `{}`: {}", file, err));
+            }
+            return;
+        },
+        _ => exit(USAGE),
+    };
+    let root = dir.canonicalize().unwrap_or_else(|_| dir.clone());
+    let name = root.file_name().and_then(|name| name.to_str()).unwrap_or("server").to_string();
+
+    if let Err(err) = write(&generate(&schema, &name), &dir, force) {
+        exit(&format!("Cannot generate the project: {}", err));
+    }
+    println!("Generated `{}` in `{}`", name, dir.display());
+}
+
+fn read_schema(path: &str) -> Schema {
+    std::fs::read_to_string(path)
+        .unwrap_or_else(|err| exit(&format!("Cannot read `{}`: {}", path, err)))
+        .parse()
+        .unwrap_or_else(|err| exit(&format!("Invalid schema, {}", err)))
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}", message);
+    process::exit(1)
+}
@@ -0,0 +1,216 @@
+//! The short description of the entities, one per line, like:
+//! ```text
+//! # The first field is the id
+//! Human(people): id: Uuid, name: String, nickname: String?, dogs: [Dog], best_friend: Human?
+//! Dog(dogs): id: Uuid, name: String, tags: [String]
+//! ```
+//! where `[T]` and `T?` of the entities in the schema are the to-many and the to-one
+//! relationships, and they are `Vec<T>` and `Option<T>` of any other types
+
+use rabbithole::schema::{type_schema, Registry, ResourceSchema};
+use std::str::FromStr;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum SchemaError {
+    #[error("line {0}: expected `Name(type): field: Type, ...`")]
+    InvalidEntity(usize),
+    #[error("line {0}: expected `field: Type`, found `{1}`")]
+    InvalidField(usize, String),
+    #[error("line {0}: the entity `{1}` has no fields, where the first one is the id")]
+    MissingId(usize, String),
+    #[error("line {0}: the id `{1}` cannot be a relationship")]
+    RelationshipId(usize, String),
+    #[error("line {0}: duplicated {1} `{2}`")]
+    Duplicated(usize, &'static str, String),
+    #[error("the schema has no entities")]
+    Empty,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FieldKind {
+    /// An attribute of the type
+    Attribute(String),
+    /// A to-one relationship of the entity
+    ToOne(String),
+    /// A to-many relationship of the entity
+    ToMany(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub kind: FieldKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntitySchema {
+    /// The name of the struct
+    pub name: String,
+    /// The type of the resources
+    pub ty: String,
+    pub id: Field,
+    pub fields: Vec<Field>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub entities: Vec<EntitySchema>,
+}
+
+impl Schema {
+    /// Whether any of the fields is of `ty`, like `Uuid`
+    pub fn uses(&self, ty: &str) -> bool {
+        let fields = self.entities.iter().flat_map(|e| std::iter::once(&e.id).chain(&e.fields));
+        fields
+            .filter_map(|field| match &field.kind {
+                FieldKind::Attribute(field_ty) => Some(field_ty),
+                _ => None,
+            })
+            .any(|field_ty| {
+                field_ty.split(|c: char| !c.is_alphanumeric() && c != '_').any(|t| t == ty)
+            })
+    }
+
+    /// The schemas of the entities, like the ones generated by `EntityDecorator` for them
+    pub fn registry(&self) -> Registry {
+        let ty_of = |name: &str| {
+            self.entities.iter().find(|e| e.name == name).map(|e| e.ty.clone()).unwrap_or_default()
+        };
+        let mut registry = Registry::default();
+        for entity in &self.entities {
+            let mut schema = ResourceSchema::new(&entity.ty);
+            schema.name = entity.name.clone();
+            if let FieldKind::Attribute(ty) = &entity.id.kind {
+                schema.id = (entity.id.name.clone(), type_schema(ty));
+            }
+            for field in &entity.fields {
+                match &field.kind {
+                    FieldKind::Attribute(ty) => {
+                        schema.attributes.push((field.name.clone(), type_schema(ty)))
+                    },
+                    FieldKind::ToOne(name) => {
+                        schema.to_ones.push((field.name.clone(), ty_of(name)))
+                    },
+                    FieldKind::ToMany(name) => {
+                        schema.to_manys.push((field.name.clone(), ty_of(name)))
+                    },
+                }
+            }
+            registry.add(schema);
+        }
+        registry
+    }
+}
+
+impl FromStr for Schema {
+    type Err = SchemaError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = vec![];
+        for (no, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or_default().trim();
+            if !line.is_empty() {
+                lines.push((no + 1, parse_header(no + 1, line)?));
+            }
+        }
+        if lines.is_empty() {
+            return Err(SchemaError::Empty);
+        }
+
+        let names: Vec<&str> = lines.iter().map(|(_, (name, _, _))| *name).collect();
+        let mut entities: Vec<EntitySchema> = vec![];
+        for (no, (name, ty, fields)) in &lines {
+            if entities.iter().any(|e| e.name == *name) {
+                return Err(SchemaError::Duplicated(*no, "entity", name.to_string()));
+            } else if entities.iter().any(|e| e.ty == *ty) {
+                return Err(SchemaError::Duplicated(*no, "type", ty.to_string()));
+            }
+            let mut parsed: Vec<Field> = vec![];
+            for field in split_fields(fields) {
+                let field = parse_field(*no, field, &names)?;
+                if parsed.iter().any(|f| f.name == field.name) {
+                    return Err(SchemaError::Duplicated(*no, "field", field.name));
+                }
+                parsed.push(field);
+            }
+            if parsed.is_empty() {
+                return Err(SchemaError::MissingId(*no, name.to_string()));
+            }
+            let id = parsed.remove(0);
+            if !matches!(id.kind, FieldKind::Attribute(_)) {
+                return Err(SchemaError::RelationshipId(*no, id.name));
+            }
+            entities.push(EntitySchema {
+                name: name.to_string(),
+                ty: ty.to_string(),
+                id,
+                fields: parsed,
+            });
+        }
+        Ok(Schema { entities })
+    }
+}
+
+/// The name, the type and the fields of `Name(type): fields`
+fn parse_header(no: usize, line: &str) -> Result<(&str, &str, &str), SchemaError> {
+    let (header, fields) = line.split_once(':').ok_or(SchemaError::InvalidEntity(no))?;
+    let (name, ty) = header.trim().split_once('(').ok_or(SchemaError::InvalidEntity(no))?;
+    let ty = ty.strip_suffix(')').ok_or(SchemaError::InvalidEntity(no))?.trim();
+    let name = name.trim();
+    if !is_ident(name) || ty.is_empty() {
+        return Err(SchemaError::InvalidEntity(no));
+    }
+    Ok((name, ty, fields))
+}
+
+/// The fields split by the commas out of the brackets, like the one of `HashMap<String, i32>`
+fn split_fields(fields: &str) -> impl Iterator<Item = &str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut parts = vec![];
+    for (i, c) in fields.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&fields[start .. i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(&fields[start ..]);
+    parts.into_iter().map(str::trim).filter(|part| !part.is_empty())
+}
+
+fn parse_field(no: usize, field: &str, entities: &[&str]) -> Result<Field, SchemaError> {
+    let invalid = || SchemaError::InvalidField(no, field.to_string());
+    let (name, ty) = field.split_once(':').ok_or_else(invalid)?;
+    let (name, ty) = (name.trim(), ty.trim());
+    if !is_ident(name) || ty.is_empty() {
+        return Err(invalid());
+    }
+    let kind = if let Some(inner) = ty.strip_prefix('[').and_then(|ty| ty.strip_suffix(']')) {
+        let inner = inner.trim();
+        if entities.contains(&inner) {
+            FieldKind::ToMany(inner.to_string())
+        } else {
+            FieldKind::Attribute(format!("Vec<{}>", inner))
+        }
+    } else if let Some(inner) = ty.strip_suffix('?') {
+        let inner = inner.trim();
+        if entities.contains(&inner) {
+            FieldKind::ToOne(inner.to_string())
+        } else {
+            FieldKind::Attribute(format!("Option<{}>", inner))
+        }
+    } else {
+        FieldKind::Attribute(ty.to_string())
+    };
+    Ok(Field { name: name.to_string(), kind })
+}
+
+fn is_ident(s: &str) -> bool {
+    s.chars().next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
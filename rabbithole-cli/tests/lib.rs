@@ -0,0 +1,111 @@
+use rabbithole_cli::generate::{generate, write};
+use rabbithole_cli::schema::{Field, FieldKind, Schema, SchemaError};
+
+const SCHEMA: &str = r#"
+# The first field is the id
+Human(people): id: Uuid, name: String, nickname: String?, dogs: [Dog], best_friend: Human?
+Dog(dogs): id: u64, scores: HashMap<String, i32>, tags: [String]  # the tags
+"#;
+
+fn field(name: &str, kind: FieldKind) -> Field { Field { name: name.into(), kind } }
+
+#[test]
+fn schema_test() {
+    let schema: Schema = SCHEMA.parse().unwrap();
+    let human = &schema.entities[0];
+    assert_eq!((human.name.as_str(), human.ty.as_str()), ("Human", "people"));
+    assert_eq!(human.id, field("id", FieldKind::Attribute("Uuid".into())));
+    assert_eq!(human.fields, vec![
+        field("name", FieldKind::Attribute("String".into())),
+        field("nickname", FieldKind::Attribute("Option<String>".into())),
+        field("dogs", FieldKind::ToMany("Dog".into())),
+        field("best_friend", FieldKind::ToOne("Human".into())),
+    ]);
+    assert_eq!(schema.entities[1].fields, vec![
+        field("scores", FieldKind::Attribute("HashMap<String, i32>".into())),
+        field("tags", FieldKind::Attribute("Vec<String>".into())),
+    ]);
+    assert!(schema.uses("Uuid") && schema.uses("HashMap") && !schema.uses("HashSet"));
+
+    for (schema, err) in [
+        ("", SchemaError::Empty),
+        ("Human: id: String", SchemaError::InvalidEntity(1)),
+        ("\nHuman(people): id String", SchemaError::InvalidField(2, "id String".into())),
+        ("Human(people):", SchemaError::MissingId(1, "Human".into())),
+        ("Human(people): friend: Human?", SchemaError::RelationshipId(1, "friend".into())),
+        ("Human(people): id: u8, id: u8", SchemaError::Duplicated(1, "field", "id".into())),
+        (
+            "A(people): id: u8\nB(people): id: u8",
+            SchemaError::Duplicated(2, "type", "people".into()),
+        ),
+    ] {
+        assert_eq!(schema.parse::<Schema>().err(), Some(err), "{}", schema);
+    }
+}
+
+#[test]
+fn registry_test() {
+    let registry = SCHEMA.parse::<Schema>().unwrap().registry();
+    let human = registry.get("people").unwrap();
+    assert_eq!(human.name, "Human");
+    let attributes: Vec<&str> = human.attributes.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(attributes, vec!["name", "nickname"]);
+    assert_eq!(human.attributes[1].1, serde_json::json!({ "type": "string", "nullable": true }));
+    assert_eq!(human.to_ones, vec![("best_friend".to_string(), "people".to_string())]);
+    assert_eq!(human.to_manys, vec![("dogs".to_string(), "dogs".to_string())]);
+    let ts = rabbithole::typescript::generate(&registry);
+    assert!(ts.contains("export type HumanDocument = Document<Human | null, Human | Dog>;"));
+}
+
+#[test]
+fn generate_test() {
+    let schema: Schema = SCHEMA.parse().unwrap();
+    let files = generate(&schema, "my-server");
+    let paths: Vec<String> = files.iter().map(|file| file.path.display().to_string()).collect();
+    assert_eq!(paths, vec![
+        "Cargo.toml",
+        "config/app.toml",
+        "src/lib.rs",
+        "src/entities.rs",
+        "src/services.rs",
+        "src/main.rs",
+        "tests/api_test.rs",
+    ]);
+    for file in files.iter().filter(|file| file.path.extension().unwrap() == "rs") {
+        if let Err(err) = syn::parse_file(&file.content) {
+            panic!("`{}` is not valid: {}\n{}", file.path.display(), err, file.content);
+        }
+    }
+
+    let content =
+        |path: &str| &files.iter().find(|file| file.path.ends_with(path)).unwrap().content;
+    assert!(content("Cargo.toml").contains("name = \"my-server\""));
+    assert!(content("Cargo.toml").contains("uuid = "));
+    let entities = content("entities.rs");
+    assert!(entities.contains("use std::collections::HashMap;\nuse uuid::Uuid;\n"));
+    assert!(
+        entities.contains("#[entity(type = \"people\")]\npub struct Human {\n    #[entity(id)]\n")
+    );
+    assert!(entities.contains("    #[entity(to_one)]\n    pub best_friend: Option<Box<Human>>,\n"));
+    assert!(entities.contains("    #[entity(to_many)]\n    pub dogs: Vec<Dog>,\n"));
+    assert!(content("services.rs").contains("impl Fetching for DogService {"));
+    assert!(content("main.rs").contains("use my_server::services::*;"));
+    assert!(content("main.rs")
+        .contains(".resource::<HumanService>()\n        .resource::<DogService>()"));
+    assert!(content("api_test.rs").contains("async fn people_test() {"));
+}
+
+#[test]
+fn write_test() {
+    let schema: Schema = "Dog(dogs): id: String, name: String".parse().unwrap();
+    let files = generate(&schema, "dogs");
+    let root = std::env::temp_dir().join(format!("rabbithole-cli-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&root);
+
+    write(&files, &root, false).unwrap();
+    let entities = std::fs::read_to_string(root.join("src/entities.rs")).unwrap();
+    assert!(!entities.contains("uuid") && !entities.contains("collections"));
+    assert!(write(&files, &root, false).is_err());
+    write(&files, &root, true).unwrap();
+    std::fs::remove_dir_all(&root).unwrap();
+}
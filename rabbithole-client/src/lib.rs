@@ -0,0 +1,228 @@
+//! A typed client of the JSON:API servers, building the URLs and the headers of the requests from
+//! the entities, and the entities back from the documents with `FromResource`:
+//! ```ignore
+//! #[derive(rbh_derive::EntityDecorator, rbh_derive::FromResource, Serialize, Deserialize, Clone)]
+//! #[entity(type = "people")]
+//! pub struct Human { ... }
+//!
+//! let client = Client::new("http://localhost:8080/api");
+//! let humans: Vec<Human> = client.fetch("?sort=-name&page[limit]=10").await?;
+//! let alice = client.create(&alice).await?;
+//! client.update_relationship::<Human>("alice", "dogs", IdentifierData::Multiple(dogs.into())).await?;
+//!
+//! let mut dogs = client.fetch_all::<Dog>("?page[limit]=100");
+//! while let Some(dog) = dogs.try_next().await? { ... }
+//! ```
+//! where the error documents answered by the servers are returned as their first errors, and the
+//! relationship fields of the entities are filled with the resources in `included`, like by
+//! `?include=dogs`
+
+use async_trait::async_trait;
+use futures::stream::{self, Stream};
+use rabbithole::encoding::Encoding;
+use rabbithole::entity::{FromResource, SingleEntity};
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::model::resource::IdentifierData;
+use rabbithole::{RbhResult, JSON_API_HEADER};
+use std::collections::VecDeque;
+use std::sync::Arc;
+
+/// A response of the server
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+/// Sends the requests, answering the response, or the reason why there is no response
+#[async_trait]
+pub trait ClientTransport: Send + Sync {
+    async fn send(
+        &self, method: &str, url: &str, headers: &[(&str, String)], body: Option<Vec<u8>>,
+    ) -> Result<Response, String>;
+}
+
+/// The transport with `reqwest`
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport(pub reqwest::Client);
+
+#[async_trait]
+impl ClientTransport for HttpTransport {
+    async fn send(
+        &self, method: &str, url: &str, headers: &[(&str, String)], body: Option<Vec<u8>>,
+    ) -> Result<Response, String> {
+        let method =
+            reqwest::Method::from_bytes(method.as_bytes()).map_err(|err| err.to_string())?;
+        let mut req = self.0.request(method, url);
+        for (name, value) in headers {
+            req = req.header(*name, value);
+        }
+        if let Some(body) = body {
+            req = req.body(body);
+        }
+        let resp = req.send().await.map_err(|err| err.to_string())?;
+        let status = resp.status().as_u16();
+        let body = resp.bytes().await.map_err(|err| err.to_string())?;
+        Ok(Response { status, body: body.to_vec() })
+    }
+}
+
+/// The client of the resources under `uri`, like `http://localhost:8080/api`
+#[derive(Clone)]
+pub struct Client {
+    uri: String,
+    transport: Arc<dyn ClientTransport>,
+    max_pages: Option<usize>,
+    encoding: Encoding,
+}
+
+impl Client {
+    pub fn new(uri: &str) -> Self { Self::with_transport(uri, HttpTransport::default()) }
+
+    pub fn with_transport(uri: &str, transport: impl 'static + ClientTransport) -> Self {
+        Self {
+            uri: uri.trim_end_matches('/').to_string(),
+            transport: Arc::new(transport),
+            max_pages: None,
+            encoding: Encoding::Json,
+        }
+    }
+
+    /// Stops `fetch_all` after `max_pages` pages, which are unlimited by default
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = Some(max_pages);
+        self
+    }
+
+    /// Accepts the documents in `encoding`, like CBOR with the `cbor` feature of `rabbithole`,
+    /// while the bodies of the requests and the error documents are still in JSON
+    pub fn with_encoding(mut self, encoding: Encoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    pub fn uri(&self) -> &str { &self.uri }
+
+    /// The collection of `E`, where `query` is the query string of the request, like
+    /// `?sort=-name`, or empty
+    pub async fn fetch<E: FromResource>(&self, query: &str) -> RbhResult<Vec<E>> {
+        let url = format!("{}/{}{}", self.uri, E::ty(), query);
+        let doc = self.send("GET", &url, None).await?;
+        from_document(doc)
+    }
+
+    /// All of the collection of `E`, following the `next` links of the pages until the last one,
+    /// or `max_pages` of them. A page is only requested when the items of the previous one are
+    /// consumed, and the stream ends after the first error
+    pub fn fetch_all<'a, E: 'a + FromResource>(
+        &'a self, query: &str,
+    ) -> impl 'a + Stream<Item = RbhResult<E>> {
+        let url = format!("{}/{}{}", self.uri, E::ty(), query);
+        let state = (Some(url), VecDeque::new(), 0);
+        stream::unfold(state, move |(mut next, mut items, mut pages)| async move {
+            loop {
+                if let Some(item) = items.pop_front() {
+                    return Some((Ok(item), (next, items, pages)));
+                }
+                let url = next.take()?;
+                if self.max_pages.is_some_and(|max_pages| pages >= max_pages) {
+                    return None;
+                }
+                pages += 1;
+                let page = self.send("GET", &url, None).await.and_then(|doc| {
+                    next = doc.as_ref().and_then(|doc| next_link(&url, doc));
+                    from_document::<E>(doc)
+                });
+                match page {
+                    Ok(page) => items = page.into(),
+                    Err(err) => return Some((Err(err), (None, items, pages))),
+                }
+            }
+        })
+    }
+
+    /// The resource of `E` with `id`, or `None` if the server answers `null`
+    pub async fn fetch_one<E: FromResource>(&self, id: &str, query: &str) -> RbhResult<Option<E>> {
+        let url = format!("{}/{}/{}{}", self.uri, E::ty(), id, query);
+        let doc = self.send("GET", &url, None).await?;
+        Ok(from_document(doc)?.into_iter().next())
+    }
+
+    /// Creates `item`, answering the created resource, or `item` itself if the server answers
+    /// `204 No Content`
+    pub async fn create<E: FromResource>(&self, item: &E) -> RbhResult<E> {
+        let mut resource = item.to_resource(&self.uri, &Default::default()).unwrap_or_default();
+        resource.links = Default::default();
+        for relationship in resource.relationships.values_mut() {
+            relationship.links = Default::default();
+        }
+        let body = serde_json::json!({ "data": resource });
+        let url = format!("{}/{}", self.uri, E::ty());
+        match self.send("POST", &url, Some(body)).await? {
+            Some(doc) => {
+                Ok(from_document(Some(doc))?.into_iter().next().unwrap_or_else(|| item.clone()))
+            },
+            None => Ok(item.clone()),
+        }
+    }
+
+    /// Replaces the relationship `field` of the resource of `E` with `id` by `data`
+    pub async fn update_relationship<E: SingleEntity>(
+        &self, id: &str, field: &str, data: IdentifierData,
+    ) -> RbhResult<()> {
+        let url = format!("{}/{}/{}/relationships/{}", self.uri, E::ty(), id, field);
+        self.send("PATCH", &url, Some(serde_json::json!({ "data": data }))).await?;
+        Ok(())
+    }
+
+    /// Sends a request, answering the document of the response, or `None` if it has no body
+    async fn send(
+        &self, method: &str, url: &str, body: Option<serde_json::Value>,
+    ) -> RbhResult<Option<Document>> {
+        let mut headers = vec![("Accept", self.encoding.media_type().to_string())];
+        if body.is_some() {
+            headers.push(("Content-Type", JSON_API_HEADER.to_string()));
+        }
+        let body = body.map(|body| body.to_string().into_bytes());
+        let resp = self
+            .transport
+            .send(method, url, &headers, body)
+            .await
+            .map_err(|reason| error::Error::ServiceUnavailable(&reason, None))?;
+        let success = (200 .. 300).contains(&resp.status);
+        if resp.body.is_empty() && success {
+            return Ok(None);
+        }
+        let encoding = if success { self.encoding } else { Encoding::Json };
+        match encoding.decode::<Document>(&resp.body) {
+            Ok(Document { item: DocumentItem::Errors(errors), .. }) if !errors.is_empty() => {
+                Err(error::Error::from_errors(errors).unwrap_or_default())
+            },
+            Ok(doc) if success => Ok(Some(doc)),
+            Err(err) if success => Err(err),
+            _ => Err(error::Error {
+                status: Some(resp.status.to_string()),
+                detail: Some(String::from_utf8_lossy(&resp.body).to_string()),
+                ..Default::default()
+            }),
+        }
+    }
+}
+
+/// The URL of the `next` link of `doc`, resolved against `url` of the page if it's relative
+fn next_link(url: &str, doc: &Document) -> Option<String> {
+    let href = doc.links.as_ref()?.get("next")?.href();
+    let next = reqwest::Url::parse(url).ok()?.join(&href).ok()?.to_string();
+    Some(next).filter(|next| next != url)
+}
+
+/// The entities of the primary data of `doc`
+fn from_document<E: FromResource>(doc: Option<Document>) -> RbhResult<Vec<E>> {
+    match doc.map(|doc| doc.item) {
+        Some(DocumentItem::PrimaryData(Some((data, included)))) => {
+            data.data().iter().map(|resource| E::from_resource(resource, &included)).collect()
+        },
+        _ => Ok(vec![]),
+    }
+}
@@ -0,0 +1,312 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use async_trait::async_trait;
+use futures::{StreamExt, TryStreamExt};
+use rabbithole::entity::Entity;
+use rabbithole::model::error;
+use rabbithole::model::resource::{IdentifierData, ResourceIdentifier};
+use rabbithole::query::Query;
+use rabbithole_client::{Client, ClientTransport, Response};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+#[derive(
+    rbh_derive::EntityDecorator,
+    rbh_derive::FromResource,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_one)]
+    pub owner: Option<Box<Human>>,
+}
+
+#[derive(
+    rbh_derive::EntityDecorator,
+    rbh_derive::FromResource,
+    Serialize,
+    Deserialize,
+    Clone,
+    Debug,
+    PartialEq,
+)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: u32,
+    pub name: String,
+    pub nickname: Option<String>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+/// The method, the URL, the headers and the body of a request
+type Request = (String, String, Vec<(String, String)>, Option<serde_json::Value>);
+
+/// Answers the requests with `responses`, recording the requests
+#[derive(Clone, Default)]
+struct MockTransport {
+    responses: Arc<Mutex<VecDeque<Result<Response, String>>>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockTransport {
+    fn new(responses: Vec<Result<Response, String>>) -> Self {
+        Self { responses: Arc::new(Mutex::new(responses.into())), ..Default::default() }
+    }
+}
+
+#[async_trait]
+impl ClientTransport for MockTransport {
+    async fn send(
+        &self, method: &str, url: &str, headers: &[(&str, String)], body: Option<Vec<u8>>,
+    ) -> Result<Response, String> {
+        let headers = headers.iter().map(|(name, value)| (name.to_string(), value.clone()));
+        let body = body.map(|body| serde_json::from_slice(&body).unwrap());
+        self.requests.lock().unwrap().push((
+            method.to_string(),
+            url.to_string(),
+            headers.collect(),
+            body,
+        ));
+        self.responses.lock().unwrap().pop_front().unwrap()
+    }
+}
+
+fn ok(status: u16, body: impl ToString) -> Result<Response, String> {
+    Ok(Response { status, body: body.to_string().into_bytes() })
+}
+
+fn humans() -> Vec<Human> {
+    vec![
+        Human {
+            id: 1,
+            name: "Alice".into(),
+            nickname: Some("Ali".into()),
+            dogs: vec![Dog { id: "a".into(), name: "Rex".into(), owner: None }],
+        },
+        Human { id: 2, name: "Bob".into(), nickname: None, dogs: vec![] },
+    ]
+}
+
+#[tokio::test]
+async fn fetch_test() {
+    let doc = humans()
+        .to_document_automatically(
+            "http://localhost/api",
+            &Query::default(),
+            &"/people".parse().unwrap(),
+        )
+        .unwrap();
+    let single = humans()[1]
+        .to_document_automatically(
+            "http://localhost/api",
+            &Query::default(),
+            &"/people/2".parse().unwrap(),
+        )
+        .unwrap();
+    let transport = MockTransport::new(vec![
+        ok(200, serde_json::to_string(&doc).unwrap()),
+        ok(200, serde_json::to_string(&single).unwrap()),
+        ok(200, r#"{ "data": null }"#),
+    ]);
+    let client = Client::with_transport("http://localhost/api/", transport.clone());
+
+    let fetched: Vec<Human> = client.fetch("?sort=-name").await.unwrap();
+    assert_eq!(fetched, humans());
+    let bob: Option<Human> = client.fetch_one("2", "").await.unwrap();
+    assert_eq!(bob, Some(humans()[1].clone()));
+    assert_eq!(client.fetch_one::<Human>("3", "").await.unwrap(), None);
+
+    let requests = transport.requests.lock().unwrap();
+    let (method, url, headers, body) = &requests[0];
+    assert_eq!(method, "GET");
+    assert_eq!(url, "http://localhost/api/people?sort=-name");
+    assert_eq!(headers, &vec![("Accept".to_string(), rabbithole::JSON_API_HEADER.to_string())]);
+    assert_eq!(body, &None);
+    assert_eq!(requests[1].1, "http://localhost/api/people/2");
+}
+
+#[tokio::test]
+async fn hydration_test() {
+    let dog = |id: &str, owner: Option<&str>| {
+        serde_json::json!({
+            "type": "dogs",
+            "id": id,
+            "attributes": { "name": id },
+            "relationships": { "owner": { "data": owner.map(|owner| serde_json::json!({ "type": "people", "id": owner })) } },
+        })
+    };
+    let transport = MockTransport::new(vec![
+        ok(
+            200,
+            serde_json::json!({
+                "data": [dog("a", Some("1")), dog("c", Some("2"))],
+                "included": [
+                    {
+                        "type": "people",
+                        "id": "1",
+                        "attributes": { "name": "Alice" },
+                        "relationships": { "dogs": { "data": [
+                            { "type": "dogs", "id": "a" },
+                            { "type": "dogs", "id": "b" },
+                            { "type": "dogs", "id": "x" },
+                        ] } },
+                    },
+                    dog("b", Some("1")),
+                ],
+            }),
+        ),
+        ok(200, serde_json::json!({ "data": dog("a", Some("1")) })),
+    ]);
+    let client = Client::with_transport("http://localhost/api", transport);
+
+    let dogs: Vec<Dog> = client.fetch("?include=owner").await.unwrap();
+    let b = Dog { id: "b".into(), name: "b".into(), owner: None };
+    let alice = Human { id: 1, name: "Alice".into(), nickname: None, dogs: vec![b] };
+    assert_eq!(dogs, vec![
+        Dog { id: "a".into(), name: "a".into(), owner: Some(Box::new(alice)) },
+        Dog { id: "c".into(), name: "c".into(), owner: None },
+    ]);
+    let dog: Option<Dog> = client.fetch_one("a", "").await.unwrap();
+    assert_eq!(dog.unwrap().owner, None);
+}
+
+#[tokio::test]
+async fn create_test() {
+    let alice = humans()[0].clone();
+    let transport = MockTransport::new(vec![
+        ok(
+            201,
+            serde_json::json!({ "data": { "type": "people", "id": "3", "attributes": { "name": "Alice" } } }),
+        ),
+        ok(204, ""),
+        ok(204, ""),
+    ]);
+    let client = Client::with_transport("http://localhost/api", transport.clone());
+
+    let created = client.create(&alice).await.unwrap();
+    assert_eq!(created, Human { id: 3, name: "Alice".into(), nickname: None, dogs: vec![] });
+    assert_eq!(client.create(&alice).await.unwrap(), alice);
+    let dogs = IdentifierData::Multiple(vec![ResourceIdentifier::new("dogs", "b")].into());
+    client.update_relationship::<Human>("1", "dogs", dogs).await.unwrap();
+
+    let requests = transport.requests.lock().unwrap();
+    let (method, url, headers, body) = &requests[0];
+    assert_eq!(method, "POST");
+    assert_eq!(url, "http://localhost/api/people");
+    assert!(headers.contains(&("Content-Type".into(), rabbithole::JSON_API_HEADER.into())));
+    assert_eq!(
+        body.as_ref().unwrap(),
+        &serde_json::json!({ "data": {
+            "type": "people",
+            "id": "1",
+            "attributes": { "name": "Alice", "nickname": "Ali" },
+            "relationships": { "dogs": { "data": [{ "type": "dogs", "id": "a" }] } },
+        } })
+    );
+    let (method, url, _, body) = &requests[2];
+    assert_eq!(method, "PATCH");
+    assert_eq!(url, "http://localhost/api/people/1/relationships/dogs");
+    assert_eq!(
+        body.as_ref().unwrap(),
+        &serde_json::json!({ "data": [{ "type": "dogs", "id": "b" }] })
+    );
+}
+
+#[tokio::test]
+async fn errors_test() {
+    let transport = MockTransport::new(vec![
+        ok(404, serde_json::json!({ "errors": [error::Error::RouteNotFound(None)] })),
+        ok(502, "Bad Gateway"),
+        Err("connection refused".into()),
+        ok(
+            200,
+            serde_json::json!({ "data": { "type": "dogs", "id": "a", "attributes": { "name": "Rex" } } }),
+        ),
+        ok(
+            200,
+            serde_json::json!({ "data": { "type": "people", "id": "x", "attributes": { "name": "X" } } }),
+        ),
+        ok(200, serde_json::json!({ "data": { "type": "people", "id": "1", "attributes": {} } })),
+    ]);
+    let client = Client::with_transport("http://localhost/api", transport);
+
+    let err = client.fetch::<Human>("").await.unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0008"));
+    let err = client.fetch::<Human>("").await.unwrap_err();
+    assert_eq!(err.status.as_deref(), Some("502"));
+    assert_eq!(err.detail.as_deref(), Some("Bad Gateway"));
+    let err = client.fetch::<Human>("").await.unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0010"));
+    let err = client.fetch_one::<Human>("a", "").await.unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0402"));
+    let err = client.fetch_one::<Human>("x", "").await.unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0003"));
+    let err = client.fetch_one::<Human>("1", "").await.unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0401"));
+}
+
+/// A page of dogs, with the `next` link if any
+fn page(ids: &[&str], next: Option<&str>) -> Result<Response, String> {
+    let data: Vec<_> = ids
+        .iter()
+        .map(|id| serde_json::json!({ "type": "dogs", "id": id, "attributes": { "name": id } }))
+        .collect();
+    let links = match next {
+        Some(next) => serde_json::json!({ "next": next }),
+        None => serde_json::json!({}),
+    };
+    ok(200, serde_json::json!({ "data": data, "links": links }))
+}
+
+#[tokio::test]
+async fn fetch_all_test() {
+    let pages = || {
+        vec![
+            page(&["a", "b"], Some("http://localhost/api/dogs?page[offset]=2&page[limit]=2")),
+            page(&[], Some("/api/dogs?page[offset]=4&page[limit]=2")),
+            page(&["c"], None),
+        ]
+    };
+    let transport = MockTransport::new(pages());
+    let client = Client::with_transport("http://localhost/api", transport.clone());
+    let dogs: Vec<Dog> = client.fetch_all("?page[limit]=2").try_collect().await.unwrap();
+    let ids: Vec<&str> = dogs.iter().map(|dog| dog.id.as_str()).collect();
+    assert_eq!(ids, vec!["a", "b", "c"]);
+    let urls: Vec<String> =
+        transport.requests.lock().unwrap().iter().map(|(_, url, _, _)| url.clone()).collect();
+    assert_eq!(urls, vec![
+        "http://localhost/api/dogs?page[limit]=2",
+        "http://localhost/api/dogs?page[offset]=2&page[limit]=2",
+        "http://localhost/api/dogs?page[offset]=4&page[limit]=2",
+    ]);
+
+    let transport = MockTransport::new(pages());
+    let client = Client::with_transport("http://localhost/api", transport.clone());
+    let dogs: Vec<Dog> = client.fetch_all("").take(2).try_collect().await.unwrap();
+    assert_eq!(dogs.len(), 2);
+    assert_eq!(transport.requests.lock().unwrap().len(), 1);
+
+    let client = Client::with_transport("http://localhost/api", MockTransport::new(pages()))
+        .with_max_pages(2);
+    let dogs: Vec<Dog> = client.fetch_all("").try_collect().await.unwrap();
+    assert_eq!(dogs.len(), 2);
+
+    let transport = MockTransport::new(vec![
+        page(&["a"], Some("/api/dogs?page[offset]=1")),
+        ok(500, serde_json::json!({ "errors": [error::Error::InternalServerError(None)] })),
+    ]);
+    let client = Client::with_transport("http://localhost/api", transport);
+    let dogs: Vec<_> = client.fetch_all::<Dog>("").collect().await;
+    assert_eq!(dogs.len(), 2);
+    assert_eq!(dogs[1].as_ref().err().unwrap().code.as_deref(), Some("RBH-0006"));
+}
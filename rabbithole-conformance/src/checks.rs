@@ -0,0 +1,457 @@
+use crate::{Check, Conformance, Report, Response, Violation};
+use rabbithole::JSON_API_HEADER;
+use serde_json::{Map, Value};
+
+/// A path segment which is not expected to be a resource type of any API
+const UNKNOWN: &str = "__conformance_unknown__";
+
+/// The headers of the requests, where `Content-Type` is sent as well for the servers requiring it
+const HEADERS: &[(&str, &str)] = &[("Accept", JSON_API_HEADER), ("Content-Type", JSON_API_HEADER)];
+
+/// The members allowed in the error objects
+const ERROR_MEMBERS: &[&str] =
+    &["id", "links", "status", "code", "title", "detail", "source", "meta"];
+
+pub(crate) struct Runner<'a> {
+    conformance: &'a Conformance,
+    pub(crate) report: Report,
+}
+
+impl<'a> Runner<'a> {
+    pub(crate) fn new(conformance: &'a Conformance) -> Self {
+        Self { conformance, report: Default::default() }
+    }
+
+    pub(crate) async fn check_resource(&mut self, ty: &str) {
+        let url = format!("{}/{}", self.conformance.uri, ty);
+        let collection = self.check_content_negotiation(ty, &url).await;
+        self.check_pagination(ty, &url).await;
+        // The checks of a resource need any of them
+        let first = collection.and_then(|doc| doc["data"].as_array()?.first().cloned());
+        if let Some(resource) = first {
+            self.check_sparse_fieldsets(ty, &url, &resource).await;
+            self.check_relationships(ty, &url, &resource).await;
+        }
+    }
+
+    fn violation(&mut self, check: Check, url: &str, message: impl Into<String>) {
+        self.report.violations.push(Violation {
+            check,
+            url: url.to_string(),
+            message: message.into(),
+        });
+    }
+
+    async fn get(&mut self, check: Check, url: &str, headers: &[(&str, &str)]) -> Option<Response> {
+        self.report.requests += 1;
+        match self.conformance.transport.get(url, headers).await {
+            Ok(resp) => Some(resp),
+            Err(err) => {
+                self.violation(check, url, format!("no response: {}", err));
+                None
+            },
+        }
+    }
+
+    /// The document of `url` answered with `200 OK`, which is checked by the document structure,
+    /// and is a collection of `ty` if any
+    async fn fetch_document(&mut self, check: Check, url: &str, ty: Option<&str>) -> Option<Value> {
+        let resp = self.get(check, url, HEADERS).await?;
+        self.document_of(check, url, &resp, ty)
+    }
+
+    fn document_of(
+        &mut self, check: Check, url: &str, resp: &Response, ty: Option<&str>,
+    ) -> Option<Value> {
+        if resp.status != 200 {
+            self.violation(check, url, format!("answered {} instead of 200", resp.status));
+            return None;
+        }
+        if let Some(message) = content_type_violation(resp) {
+            self.violation(Check::ContentNegotiation, url, message);
+        }
+        let doc: Value = match serde_json::from_slice(&resp.body) {
+            Ok(doc) => doc,
+            Err(err) => {
+                self.violation(Check::DocumentStructure, url, format!("invalid JSON: {}", err));
+                return None;
+            },
+        };
+        let mut messages = document_violations(&doc);
+        if let Some(ty) = ty {
+            match doc["data"].as_array() {
+                Some(data) => {
+                    if data.iter().any(|resource| resource["type"] != ty) {
+                        messages.push(format!("the collection has resources other than `{}`", ty));
+                    }
+                },
+                None => messages.push("the primary data of a collection is not an array".into()),
+            }
+        }
+        for message in messages {
+            self.violation(Check::DocumentStructure, url, message);
+        }
+        Some(doc)
+    }
+
+    /// Checks the responses of the media types, answering the collection of `ty` if any
+    async fn check_content_negotiation(&mut self, ty: &str, url: &str) -> Option<Value> {
+        let check = Check::ContentNegotiation;
+        let collection = self.fetch_document(check, url, Some(ty)).await;
+
+        let with_params = format!("{}; charset=utf-8", JSON_API_HEADER);
+        let headers = [("Accept", with_params.as_str()), ("Content-Type", JSON_API_HEADER)];
+        if let Some(resp) = self.get(check, url, &headers).await {
+            let message = "the JSON:API media types of `Accept` all have parameters";
+            self.check_error(check, url, &resp, 406, message);
+        }
+        let headers = [("Accept", JSON_API_HEADER), ("Content-Type", with_params.as_str())];
+        if let Some(resp) = self.get(check, url, &headers).await {
+            let message = "the JSON:API media type of `Content-Type` has parameters";
+            self.check_error(check, url, &resp, 415, message);
+        }
+        collection
+    }
+
+    pub(crate) async fn check_error_shapes(&mut self) {
+        let check = Check::ErrorShape;
+        let unknown = format!("{}/{}", self.conformance.uri, UNKNOWN);
+        if let Some(resp) = self.get(check, &unknown, HEADERS).await {
+            self.check_error(check, &unknown, &resp, 404, "the resource type does not exist");
+        }
+    }
+
+    /// Checks that `resp` is an error document of `status`, since `reason`
+    fn check_error(&mut self, check: Check, url: &str, resp: &Response, status: u16, reason: &str) {
+        if resp.status != status {
+            let message =
+                format!("answered {} instead of {}, where {}", resp.status, status, reason);
+            self.violation(check, url, message);
+            return;
+        }
+        if let Some(message) = content_type_violation(resp) {
+            self.violation(Check::ContentNegotiation, url, message);
+        }
+        for message in error_violations(resp) {
+            self.violation(Check::ErrorShape, url, message);
+        }
+    }
+
+    /// Follows the `next` links from the first page until the last one, where each resource
+    /// should be in one page only
+    async fn check_pagination(&mut self, ty: &str, url: &str) {
+        let check = Check::Pagination;
+        let mut next = Some(format!("{}?{}", url, self.conformance.page_query));
+        let mut ids: Vec<String> = vec![];
+        let mut pages = 0;
+        while let Some(page_url) = next.take() {
+            if pages >= self.conformance.max_pages {
+                break;
+            }
+            pages += 1;
+            let doc = match self.fetch_document(check, &page_url, Some(ty)).await {
+                Some(doc) => doc,
+                None => break,
+            };
+            for id in doc["data"].as_array().into_iter().flatten().filter_map(|r| r["id"].as_str())
+            {
+                if ids.iter().any(|seen| seen == id) {
+                    self.violation(check, &page_url, format!("`{}` is in the previous pages", id));
+                } else {
+                    ids.push(id.to_string());
+                }
+            }
+            let links = doc.get("links").and_then(Value::as_object).cloned().unwrap_or_default();
+            for name in &["first", "last", "prev", "next"] {
+                let href = match links.get(*name) {
+                    None | Some(Value::Null) => continue,
+                    Some(link) => match link_href(link) {
+                        Some(href) => href,
+                        None => {
+                            let message = format!("the `{}` link is not a link", name);
+                            self.violation(check, &page_url, message);
+                            continue;
+                        },
+                    },
+                };
+                let href = match resolve(&page_url, href) {
+                    Some(href) => href,
+                    None => {
+                        let message = format!("the `{}` link `{}` is not a URL", name, href);
+                        self.violation(check, &page_url, message);
+                        continue;
+                    },
+                };
+                if *name == "next" {
+                    if href == page_url {
+                        self.violation(check, &page_url, "the `next` link is the page itself");
+                    } else {
+                        next = Some(href);
+                    }
+                } else if pages == 1 && (*name == "first" || *name == "last") {
+                    self.fetch_document(check, &href, Some(ty)).await;
+                }
+            }
+        }
+    }
+
+    /// Checks that only the field of `resource` in `fields[ty]` is in the resources of `ty`
+    async fn check_sparse_fieldsets(&mut self, ty: &str, url: &str, resource: &Value) {
+        let fields = fields_of(resource);
+        let field = match fields.first() {
+            Some(field) => field.clone(),
+            None => return,
+        };
+        let fields_url = format!("{}?fields[{}]={}", url, ty, field);
+        let doc = match self.fetch_document(Check::SparseFieldsets, &fields_url, Some(ty)).await {
+            Some(doc) => doc,
+            None => return,
+        };
+        let included = doc["included"].as_array().into_iter().flatten();
+        for resource in doc["data"].as_array().into_iter().flatten().chain(included) {
+            if resource["type"] != ty {
+                continue;
+            }
+            let extra: Vec<String> =
+                fields_of(resource).into_iter().filter(|f| f != &field).collect();
+            if !extra.is_empty() {
+                let message = format!(
+                    "`{}` of `{}` has the fields not requested: {}",
+                    resource["id"],
+                    ty,
+                    extra.join(", ")
+                );
+                self.violation(Check::SparseFieldsets, &fields_url, message);
+            }
+        }
+    }
+
+    /// Checks the `self` link of `resource`, and the `self` and `related` links of its
+    /// relationships
+    async fn check_relationships(&mut self, ty: &str, url: &str, resource: &Value) {
+        let check = Check::RelationshipEndpoints;
+        let id = resource["id"].as_str().unwrap_or_default();
+        let self_url = resource["links"]["self"]
+            .as_str()
+            .and_then(|href| resolve(url, href))
+            .unwrap_or_else(|| format!("{}/{}", url, id));
+        if let Some(doc) = self.fetch_document(check, &self_url, None).await {
+            if doc["data"]["type"] != ty || doc["data"]["id"] != id {
+                self.violation(check, &self_url, format!("the data is not `{}` of `{}`", id, ty));
+            }
+        }
+
+        let relationships = resource["relationships"].as_object().cloned().unwrap_or_default();
+        for (name, relationship) in relationships {
+            let links = &relationship["links"];
+            if let Some(href) = links.get("self").and_then(link_href) {
+                let href = resolve(url, href).unwrap_or_else(|| href.to_string());
+                if let Some(doc) = self.fetch_document(check, &href, None).await {
+                    if doc.get("data").is_none() || !is_linkage(&doc["data"]) {
+                        let message = format!("the data of `{}` is not a resource linkage", name);
+                        self.violation(check, &href, message);
+                    } else if relationship.get("data").is_some()
+                        && identifiers(&relationship["data"]) != identifiers(&doc["data"])
+                    {
+                        let message =
+                            format!("the data of `{}` is not the one in the resource", name);
+                        self.violation(check, &href, message);
+                    }
+                }
+            }
+            if let Some(href) = links.get("related").and_then(link_href) {
+                let href = resolve(url, href).unwrap_or_else(|| href.to_string());
+                if let Some(doc) = self.fetch_document(check, &href, None).await {
+                    if doc.get("data").is_none() {
+                        let message = format!("the related resources of `{}` have no data", name);
+                        self.violation(check, &href, message);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Why the `Content-Type` of `resp` is not the JSON:API media type, without parameters other than
+/// `ext` and `profile`
+fn content_type_violation(resp: &Response) -> Option<String> {
+    let content_type = match resp.header("content-type") {
+        Some(content_type) => content_type,
+        None => return Some("no `Content-Type`".into()),
+    };
+    let mut parts = content_type.split(';').map(str::trim);
+    let essence = parts.next().unwrap_or_default();
+    let params_allowed = parts.filter(|p| !p.is_empty()).all(|param| {
+        let name = param.split('=').next().unwrap_or_default().trim();
+        name.eq_ignore_ascii_case("ext") || name.eq_ignore_ascii_case("profile")
+    });
+    if essence.eq_ignore_ascii_case(JSON_API_HEADER) && params_allowed {
+        None
+    } else {
+        Some(format!("`Content-Type` is `{}` instead of `{}`", content_type, JSON_API_HEADER))
+    }
+}
+
+/// The violations of the top-level members and the resource objects of `doc`
+fn document_violations(doc: &Value) -> Vec<String> {
+    let doc = match doc.as_object() {
+        Some(doc) => doc,
+        None => return vec!["the document is not an object".into()],
+    };
+    let mut messages = vec![];
+    if !doc.contains_key("data") && !doc.contains_key("errors") && !doc.contains_key("meta") {
+        messages.push("the document has none of `data`, `errors` and `meta`".into());
+    }
+    if doc.contains_key("data") && doc.contains_key("errors") {
+        messages.push("the document has both `data` and `errors`".into());
+    }
+    if doc.contains_key("included") && !doc.contains_key("data") {
+        messages.push("the document has `included` without `data`".into());
+    }
+    let primary: Vec<&Value> = match doc.get("data") {
+        Some(Value::Array(data)) => data.iter().collect(),
+        Some(Value::Null) | None => vec![],
+        Some(data) => vec![data],
+    };
+    let included = doc.get("included").and_then(Value::as_array).into_iter().flatten();
+    for resource in primary.into_iter().chain(included) {
+        messages.extend(resource_violations(resource));
+    }
+    messages
+}
+
+fn resource_violations(resource: &Value) -> Vec<String> {
+    let object = match resource.as_object() {
+        Some(object) => object,
+        None => return vec![format!("`{}` is not a resource object", resource)],
+    };
+    let name = format!("the resource `{}` of `{}`", resource["id"], resource["type"]);
+    let mut messages = vec![];
+    if !object.get("type").is_some_and(Value::is_string) {
+        messages.push(format!("{} has no string `type`", name));
+    }
+    if !object.get("id").is_some_and(Value::is_string) {
+        messages.push(format!("{} has no string `id`", name));
+    }
+    for member in &["attributes", "relationships", "links", "meta"] {
+        if object.get(*member).is_some_and(|value| !value.is_object()) {
+            messages.push(format!("`{}` of {} is not an object", member, name));
+        }
+    }
+    let attributes = object.get("attributes").and_then(Value::as_object);
+    let relationships = object.get("relationships").and_then(Value::as_object);
+    for field in attributes.into_iter().chain(relationships).flat_map(Map::keys) {
+        if field == "type" || field == "id" {
+            messages.push(format!("{} has the field `{}`", name, field));
+        }
+    }
+    if let (Some(attributes), Some(relationships)) = (attributes, relationships) {
+        for field in attributes.keys().filter(|field| relationships.contains_key(*field)) {
+            messages
+                .push(format!("`{}` of {} is both an attribute and a relationship", field, name));
+        }
+    }
+    for (field, relationship) in relationships.into_iter().flatten() {
+        let has_member = ["links", "data", "meta"].iter().any(|m| relationship.get(*m).is_some());
+        if !has_member {
+            messages.push(format!(
+                "the relationship `{}` of {} has none of `links`, `data` and `meta`",
+                field, name
+            ));
+        } else if relationship.get("data").is_some_and(|data| !is_linkage(data)) {
+            messages.push(format!("the data of `{}` of {} is not a resource linkage", field, name));
+        }
+    }
+    messages
+}
+
+/// The violations of the error document of `resp`
+fn error_violations(resp: &Response) -> Vec<String> {
+    let doc: Value = match serde_json::from_slice(&resp.body) {
+        Ok(doc) => doc,
+        Err(err) => return vec![format!("invalid JSON: {}", err)],
+    };
+    let errors = match doc.get("errors").and_then(Value::as_array) {
+        Some(errors) if !errors.is_empty() => errors,
+        _ => return vec!["the document has no `errors`".into()],
+    };
+    let mut messages = vec![];
+    if doc.get("data").is_some() {
+        messages.push("the error document has `data`".into());
+    }
+    for error in errors {
+        let error = match error.as_object() {
+            Some(error) => error,
+            None => {
+                messages.push(format!("`{}` is not an error object", error));
+                continue;
+            },
+        };
+        for member in error.keys().filter(|member| !ERROR_MEMBERS.contains(&member.as_str())) {
+            messages.push(format!("the error has the unknown member `{}`", member));
+        }
+        for member in &["id", "status", "code", "title", "detail"] {
+            if error.get(*member).is_some_and(|value| !value.is_string()) {
+                messages.push(format!("`{}` of the error is not a string", member));
+            }
+        }
+        if let Some(status) = error.get("status").and_then(Value::as_str) {
+            if status != resp.status.to_string() {
+                messages.push(format!("the error has the status {} of {}", status, resp.status));
+            }
+        }
+        if error.get("source").is_some_and(|source| !source.is_object()) {
+            messages.push("`source` of the error is not an object".into());
+        }
+    }
+    messages
+}
+
+/// Whether `data` is `null`, a resource identifier, or an array of them
+fn is_linkage(data: &Value) -> bool {
+    let is_identifier = |identifier: &Value| {
+        identifier["type"].is_string()
+            && identifier["id"].is_string()
+            && identifier.get("attributes").is_none()
+            && identifier.get("relationships").is_none()
+    };
+    match data {
+        Value::Null => true,
+        Value::Array(identifiers) => identifiers.iter().all(is_identifier),
+        identifier => is_identifier(identifier),
+    }
+}
+
+/// The types and the ids of the resource linkage `data`, sorted
+fn identifiers(data: &Value) -> Vec<(String, String)> {
+    let data: Vec<&Value> = match data {
+        Value::Array(data) => data.iter().collect(),
+        Value::Null => vec![],
+        data => vec![data],
+    };
+    let mut identifiers: Vec<(String, String)> =
+        data.iter().map(|r| (r["type"].to_string(), r["id"].to_string())).collect();
+    identifiers.sort();
+    identifiers
+}
+
+/// The names of the attributes and the relationships of `resource`
+fn fields_of(resource: &Value) -> Vec<String> {
+    let attributes = resource["attributes"].as_object().into_iter().flat_map(Map::keys);
+    let relationships = resource["relationships"].as_object().into_iter().flat_map(Map::keys);
+    attributes.chain(relationships).cloned().collect()
+}
+
+/// The URL of a link, which is a string or a link object
+fn link_href(link: &Value) -> Option<&str> {
+    match link {
+        Value::String(href) => Some(href),
+        Value::Object(link) => link.get("href")?.as_str(),
+        _ => None,
+    }
+}
+
+/// `href` resolved against `base` if it's relative
+fn resolve(base: &str, href: &str) -> Option<String> {
+    Some(url::Url::parse(base).ok()?.join(href).ok()?.to_string())
+}
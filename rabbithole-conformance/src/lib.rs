@@ -0,0 +1,221 @@
+//! The JSON:API conformance checks of the fetching routes of an API, run against a running
+//! endpoint with `HttpTransport`, or an in-process `JsonApiService` with `ServiceTransport`:
+//! ```ignore
+//! let report = Conformance::new("http://localhost:8080/api", HttpTransport::default())
+//!     .resource("people")
+//!     .resource("dogs")
+//!     .run()
+//!     .await;
+//! report.assert_conformant();
+//! ```
+//! where each resource type is checked by:
+//!   - the content negotiation, like `406 Not Acceptable` and `415 Unsupported Media Type`
+//!   - the structure of the documents and the shape of the errors
+//!   - the pagination links, which are followed until the last page
+//!   - the sparse fieldsets
+//!   - the relationship endpoints, with the `self` and `related` links of a resource
+
+mod checks;
+
+use async_trait::async_trait;
+use rabbithole::service::JsonApiService;
+use std::fmt;
+use std::sync::Arc;
+
+/// A response of the API
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Response {
+    pub status: u16,
+    /// The headers, where the names are in lowercase
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// The value of the header `name` if any
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.iter().find(|(key, _)| key.eq_ignore_ascii_case(name)).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Sends the `GET` requests, answering the response, or the reason why there is no response
+#[async_trait]
+pub trait Transport: Send + Sync {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, String>;
+}
+
+/// The transport with `reqwest`, for a running endpoint
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport(pub reqwest::Client);
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, String> {
+        let mut req = self.0.get(url);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let resp = req.send().await.map_err(|err| err.to_string())?;
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).to_string())
+            })
+            .collect();
+        let body = resp.bytes().await.map_err(|err| err.to_string())?;
+        Ok(Response { status, headers, body: body.to_vec() })
+    }
+}
+
+/// The transport answering the requests by a `JsonApiService` in the same process, where only
+/// the paths and the queries of the URLs are used
+#[derive(Clone)]
+pub struct ServiceTransport(pub JsonApiService);
+
+#[async_trait]
+impl Transport for ServiceTransport {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, String> {
+        let url = url::Url::parse(url).map_err(|err| err.to_string())?;
+        let path = match url.query() {
+            Some(query) => format!("{}?{}", url.path(), query),
+            None => url.path().to_string(),
+        };
+        let mut req = http::Request::get(path);
+        for (name, value) in headers {
+            req = req.header(*name, *value);
+        }
+        let resp = self.0.handle(req.body(()).map_err(|err| err.to_string())?).await;
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                (name.as_str().to_string(), String::from_utf8_lossy(value.as_bytes()).to_string())
+            })
+            .collect();
+        Ok(Response { status: resp.status().as_u16(), headers, body: resp.into_body() })
+    }
+}
+
+/// The checks of the conformance
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Check {
+    ContentNegotiation,
+    DocumentStructure,
+    ErrorShape,
+    Pagination,
+    SparseFieldsets,
+    RelationshipEndpoints,
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Check::ContentNegotiation => "content negotiation",
+            Check::DocumentStructure => "document structure",
+            Check::ErrorShape => "error shape",
+            Check::Pagination => "pagination",
+            Check::SparseFieldsets => "sparse fieldsets",
+            Check::RelationshipEndpoints => "relationship endpoints",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A violation of the JSON:API specification found by `check` in the response of `url`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Violation {
+    pub check: Check,
+    pub url: String,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] GET {}: {}", self.check, self.url, self.message)
+    }
+}
+
+/// The violations found by `Conformance::run`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Report {
+    pub violations: Vec<Violation>,
+    /// The number of the requests sent
+    pub requests: usize,
+}
+
+impl Report {
+    pub fn is_conformant(&self) -> bool { self.violations.is_empty() }
+
+    /// The violations of `check`
+    pub fn of(&self, check: Check) -> impl Iterator<Item = &Violation> {
+        self.violations.iter().filter(move |violation| violation.check == check)
+    }
+
+    /// Panics with all of the violations if there are any, like in the tests of the CI
+    pub fn assert_conformant(&self) {
+        assert!(self.is_conformant(), "the API is not conformant to JSON:API:\n{}", self);
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} violations in {} requests", self.violations.len(), self.requests)?;
+        for violation in &self.violations {
+            writeln!(f, "  - {}", violation)?;
+        }
+        Ok(())
+    }
+}
+
+/// The conformance checks of the resource types under `uri`, like `http://localhost:8080/api`
+#[derive(Clone)]
+pub struct Conformance {
+    uri: String,
+    transport: Arc<dyn Transport>,
+    types: Vec<String>,
+    page_query: String,
+    max_pages: usize,
+}
+
+impl Conformance {
+    pub fn new(uri: &str, transport: impl 'static + Transport) -> Self {
+        Self {
+            uri: uri.trim_end_matches('/').to_string(),
+            transport: Arc::new(transport),
+            types: vec![],
+            page_query: "page[offset]=0&page[limit]=1".into(),
+            max_pages: 100,
+        }
+    }
+
+    /// Checks the resource type `ty`, whose collection is at `<uri>/<ty>`
+    pub fn resource(mut self, ty: &str) -> Self {
+        self.types.push(ty.to_string());
+        self
+    }
+
+    /// The query of the first page of the pagination checks, which is
+    /// `page[offset]=0&page[limit]=1` by default, where the pages should have one item at most
+    pub fn with_page_query(mut self, page_query: &str) -> Self {
+        self.page_query = page_query.to_string();
+        self
+    }
+
+    /// Stops following the `next` links after `max_pages` pages, which is 100 by default
+    pub fn with_max_pages(mut self, max_pages: usize) -> Self {
+        self.max_pages = max_pages;
+        self
+    }
+
+    /// Runs all of the checks of all of the resource types
+    pub async fn run(&self) -> Report {
+        let mut runner = checks::Runner::new(self);
+        runner.check_error_shapes().await;
+        for ty in &self.types {
+            runner.check_resource(ty).await;
+        }
+        runner.report
+    }
+}
@@ -0,0 +1,165 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use async_trait::async_trait;
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::service::JsonApiService;
+use rabbithole::store::CollectionStore;
+use rabbithole_conformance::{Check, Conformance, Response, ServiceTransport, Transport};
+use serde::{Deserialize, Serialize};
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+}
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_one)]
+    pub best_friend: Option<Box<Human>>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+fn dogs() -> Vec<Dog> {
+    ["a", "b", "c"].iter().map(|id| Dog { id: id.to_string(), name: id.to_uppercase() }).collect()
+}
+
+struct DogStore;
+
+#[async_trait]
+impl CollectionStore for DogStore {
+    type Item = Dog;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(dogs()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(dogs().into_iter().find(|dog| dog.id == id))
+    }
+}
+
+struct HumanStore;
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        let bob = Human { id: "bob".into(), name: "Bob".into(), best_friend: None, dogs: vec![] };
+        Ok(vec![
+            Human {
+                id: "alice".into(),
+                name: "Alice".into(),
+                best_friend: Some(Box::new(bob.clone())),
+                dogs: dogs()[.. 2].to_vec(),
+            },
+            bob,
+        ])
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.all().await?.into_iter().find(|human| human.id == id))
+    }
+}
+
+fn service() -> JsonApiService {
+    JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })
+        .unwrap()
+        .resource(HumanStore)
+        .resource(DogStore)
+}
+
+#[tokio::test]
+async fn conformant_test() {
+    let report = Conformance::new("http://localhost:8080/api", ServiceTransport(service()))
+        .resource("people")
+        .resource("dogs")
+        .run()
+        .await;
+    report.assert_conformant();
+    assert!(report.requests > 10, "{}", report.requests);
+}
+
+/// Breaks the responses of the service like a server which is not conformant
+struct BrokenTransport(ServiceTransport);
+
+#[async_trait]
+impl Transport for BrokenTransport {
+    async fn get(&self, url: &str, headers: &[(&str, &str)]) -> Result<Response, String> {
+        let mut resp = self.0.get(url, headers).await?;
+        if url.contains("__conformance_unknown__") {
+            resp.body = br#"{ "errors": [{ "status": 404, "message": "Not Found" }] }"#.to_vec();
+        } else if url.contains("fields[") {
+            // Ignores the sparse fieldsets
+            resp = self.0.get(url.split('?').next().unwrap(), headers).await?;
+        } else if let Some((base, rest)) = url.split_once("page[offset]=") {
+            // Repeats the first page as the second one, with an invalid `last` link
+            let offset: usize = rest.split('&').next().unwrap().parse().unwrap();
+            let page =
+                if offset == 1 { url.replace("[offset]=1", "[offset]=0") } else { url.into() };
+            resp = self.0.get(&page, headers).await?;
+            let mut doc: serde_json::Value = serde_json::from_slice(&resp.body).unwrap();
+            let page_url = |offset| format!("{}page[offset]={}&page[limit]=1", base, offset);
+            doc["links"] = serde_json::json!({ "first": page_url(0), "last": 42 });
+            if offset < 2 {
+                doc["links"]["next"] = serde_json::json!(page_url(offset + 1));
+            }
+            resp.body = serde_json::to_vec(&doc).unwrap();
+        }
+        if url.ends_with("/relationships/dogs") {
+            resp.body = br#"{ "data": [{ "type": "dogs", "id": "c" }] }"#.to_vec();
+        }
+        if resp.status == 200 {
+            resp.headers.retain(|(name, _)| name != "content-type");
+            resp.headers.push(("content-type".into(), "application/json".into()));
+        }
+        Ok(resp)
+    }
+}
+
+#[tokio::test]
+async fn violations_test() {
+    let transport = BrokenTransport(ServiceTransport(service()));
+    let report = Conformance::new("http://localhost:8080/api", transport)
+        .resource("people")
+        .resource("dogs")
+        .with_max_pages(5)
+        .run()
+        .await;
+    assert!(!report.is_conformant());
+    assert!(report.of(Check::ContentNegotiation).all(|v| v.message.contains("`Content-Type`")));
+    assert!(report.of(Check::ContentNegotiation).count() > 0);
+
+    let errors: Vec<&str> = report.of(Check::ErrorShape).map(|v| v.message.as_str()).collect();
+    assert!(errors.contains(&"`status` of the error is not a string"));
+    assert!(errors.contains(&"the error has the unknown member `message`"));
+
+    let pagination: Vec<&str> = report.of(Check::Pagination).map(|v| v.message.as_str()).collect();
+    assert_eq!(pagination, vec![
+        "the `last` link is not a link",
+        "`alice` is in the previous pages",
+        "the `last` link is not a link",
+        "the `last` link is not a link",
+        "the `last` link is not a link",
+        "`a` is in the previous pages",
+        "the `last` link is not a link",
+        "the `last` link is not a link",
+    ]);
+
+    // The names of the dogs are their only fields
+    let fields: Vec<_> = report.of(Check::SparseFieldsets).collect();
+    assert_eq!(fields.len(), 3, "{}", report);
+    assert!(fields[0].message.contains("has the fields not requested: best_friend, dogs"));
+
+    let relationships: Vec<_> = report.of(Check::RelationshipEndpoints).collect();
+    assert_eq!(relationships.len(), 1, "{}", report);
+    assert_eq!(relationships[0].message, "the data of `dogs` is not the one in the resource");
+    assert_eq!(relationships[0].url, "http://localhost:8080/api/people/alice/relationships/dogs");
+}
@@ -9,6 +9,8 @@ pub enum EntityDecoratorError {
     DuplicatedId,
     #[error("Invalid unit decorator {0}, the valid ones: [id, to_one, to_many]")]
     InvalidUnitDecorator(String),
+    #[error("Invalid deprecation argument {0}, the valid ones: [since, sunset, note]")]
+    InvalidDeprecation(String),
     #[error("Field without name")]
     FieldWithoutName,
 }
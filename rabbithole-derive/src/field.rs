@@ -1,5 +1,5 @@
 use crate::error::EntityDecoratorError;
-use crate::get_meta;
+use crate::{get_meta, is_deprecation};
 use quote::ToTokens;
 
 lazy_static! {
@@ -8,48 +8,80 @@ lazy_static! {
 }
 
 pub(crate) fn get_field_type(item: &syn::Field) -> syn::Result<FieldType> {
-    if let Some(syn::Meta::List(syn::MetaList { ref nested, .. })) = get_meta(&item.attrs)?.last() {
-        if let Some(syn::NestedMeta::Meta(ref meta_item)) = nested.last() {
-            match meta_item {
-                syn::Meta::Path(syn::Path { segments, .. }) => {
-                    if let Some(seg) = segments.last() {
-                        let field_ty = &seg.ident;
-                        if field_ty == "id" {
-                            return Ok(FieldType::Id);
-                        } else if field_ty == "to_many" {
-                            return Ok(FieldType::ToMany);
-                        } else if field_ty == "to_one" {
-                            return Ok(FieldType::ToOne);
-                        } else {
-                            return Err(syn::Error::new_spanned(
-                                field_ty,
-                                EntityDecoratorError::InvalidUnitDecorator(field_ty.to_string()),
-                            ));
-                        }
+    // `deprecated` is found by `get_deprecation`, and can be next to the other decorators
+    let decorator = get_meta(&item.attrs)?
+        .into_iter()
+        .filter_map(|meta| match meta {
+            syn::Meta::List(syn::MetaList { nested, .. }) => Some(nested),
+            _ => None,
+        })
+        .flatten()
+        .rfind(|nested| !is_deprecation(nested));
+    if let Some(syn::NestedMeta::Meta(ref meta_item)) = decorator {
+        match meta_item {
+            syn::Meta::Path(syn::Path { segments, .. }) => {
+                if let Some(seg) = segments.last() {
+                    let field_ty = &seg.ident;
+                    if field_ty == "id" {
+                        return Ok(FieldType::Id);
+                    } else if field_ty == "to_many" {
+                        return Ok(FieldType::ToMany);
+                    } else if field_ty == "to_one" {
+                        return Ok(FieldType::ToOne);
                     } else {
                         return Err(syn::Error::new_spanned(
-                            meta_item,
-                            EntityDecoratorError::InvalidUnitDecorator(
-                                meta_item.path().segments.to_token_stream().to_string(),
-                            ),
+                            field_ty,
+                            EntityDecoratorError::InvalidUnitDecorator(field_ty.to_string()),
                         ));
                     }
-                },
-                _ => {
+                } else {
                     return Err(syn::Error::new_spanned(
                         meta_item,
                         EntityDecoratorError::InvalidUnitDecorator(
                             meta_item.path().segments.to_token_stream().to_string(),
                         ),
-                    ))
-                },
-            }
+                    ));
+                }
+            },
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    meta_item,
+                    EntityDecoratorError::InvalidUnitDecorator(
+                        meta_item.path().segments.to_token_stream().to_string(),
+                    ),
+                ))
+            },
         }
     }
 
     Ok(FieldType::Plain)
 }
 
+/// The type of the related entities of a relationship field, like `Dog` of `Option<Box<Dog>>`
+pub(crate) fn get_related_type(ty: &syn::Type) -> &syn::Type {
+    match ty {
+        syn::Type::Reference(syn::TypeReference { elem, .. }) => get_related_type(elem),
+        syn::Type::Path(syn::TypePath { path, .. }) => {
+            let wrapped = path.segments.last().and_then(|seg| {
+                let wrapper = seg.ident.to_string();
+                let is_wrapper = VALID_TO_ONE_WRAPPER.contains(&wrapper.as_str())
+                    || VALID_TO_MANY_WRAPPER.contains(&wrapper.as_str());
+                match &seg.arguments {
+                    syn::PathArguments::AngleBracketed(args) if is_wrapper => {
+                        args.args.iter().find_map(|arg| match arg {
+                            syn::GenericArgument::Type(ty) => Some(ty),
+                            _ => None,
+                        })
+                    },
+                    _ => None,
+                }
+            });
+            wrapped.map_or(ty, get_related_type)
+        },
+        _ => ty,
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub(crate) enum FieldType {
     Id,
@@ -9,9 +9,9 @@ mod error;
 mod field;
 
 use crate::error::EntityDecoratorError;
-use crate::field::{get_field_type, FieldType};
+use crate::field::{get_field_type, get_related_type, FieldType};
 use proc_macro::TokenStream;
-use quote::{quote, TokenStreamExt};
+use quote::{quote, ToTokens, TokenStreamExt};
 use std::collections::HashSet;
 use syn::DeriveInput;
 
@@ -32,6 +32,23 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
     let (entity_type, backends) = get_entity_type(&ast)?;
 
     let (id, attrs, to_ones, to_manys) = get_fields(&ast)?;
+    let attr_types: Vec<&syn::Type> = attrs.iter().map(|attr| get_type(&ast, attr)).collect();
+    let id_type = get_type(&ast, id);
+    let to_one_types: Vec<&syn::Type> =
+        to_ones.iter().map(|field| get_related_type(get_type(&ast, field))).collect();
+    let to_many_types: Vec<&syn::Type> =
+        to_manys.iter().map(|field| get_related_type(get_type(&ast, field))).collect();
+
+    let attr_count = attrs.len();
+    let relat_count = to_ones.len() + to_manys.len();
+
+    let deprecation = match get_deprecation(&ast.attrs)? {
+        Some(deprecation) => quote!(Some(#deprecation)),
+        None => quote!(None),
+    };
+    let (deprecated_fields, deprecations): (Vec<_>, Vec<_>) =
+        get_deprecated_fields(&ast)?.into_iter().unzip();
+    let aliases = get_aliases(&ast.attrs)?;
 
     let mut res = quote! {
         impl #struct_lifetime rabbithole::entity::Entity for #decorated_struct#struct_lifetime {
@@ -66,6 +83,7 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
                 #(
                     if let Some(included_fields) = include_query {
                         if included_fields.contains(stringify!(#to_manys)) {
+                            included.reserve((&self.#to_manys).into_iter().size_hint().0);
                             for item in &self.#to_manys {
                                 if let Some(inc) = item.to_resource(uri, fields_query) {
                                     included.insert(inc.id.clone(), inc);
@@ -73,6 +91,7 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
                             }
                         }
                     } else {
+                        included.reserve((&self.#to_manys).into_iter().size_hint().0);
                         for item in &self.#to_manys {
                             if let Some(inc) = item.to_resource(uri, fields_query) {
                                 included.insert(inc.id.clone(), inc);
@@ -92,24 +111,74 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
             fn ty() -> std::string::String { #entity_type.to_string() }
             fn id(&self) -> std::string::String { self.#id.to_string() }
 
+            fn schema() -> rabbithole::schema::ResourceSchema {
+                rabbithole::schema::ResourceSchema {
+                    name: stringify!(#decorated_struct).to_string(),
+                    ty: #entity_type.to_string(),
+                    id: (stringify!(#id).to_string(), rabbithole::schema::type_schema(stringify!(#id_type))),
+                    attributes: vec![#( (stringify!(#attrs).to_string(), rabbithole::schema::type_schema(stringify!(#attr_types))), )*],
+                    to_ones: vec![#( (stringify!(#to_ones).to_string(), <#to_one_types as rabbithole::entity::SingleEntity>::ty()), )*],
+                    to_manys: vec![#( (stringify!(#to_manys).to_string(), <#to_many_types as rabbithole::entity::SingleEntity>::ty()), )*],
+                }
+            }
+
+            fn deprecation() -> std::option::Option<rabbithole::deprecation::Deprecation> { #deprecation }
+
+            fn deprecated_fields() -> std::collections::HashMap<String, rabbithole::deprecation::Deprecation> {
+                let mut fields = std::collections::HashMap::new();
+                #( fields.insert(stringify!(#deprecated_fields).to_string(), #deprecations); )*
+                fields
+            }
+
+            fn aliases() -> std::vec::Vec<String> { vec![#( #aliases.to_string() ),*] }
+
             fn attributes(&self) -> rabbithole::model::resource::Attributes {
-                let mut attr_map: std::collections::HashMap<String, serde_json::Value> = std::default::Default::default();
-                #(  if let Ok(json_value) = serde_json::to_value(self.#attrs.clone()) { attr_map.insert(stringify!(#attrs).to_string(), json_value); } )*
+                rabbithole::entity::SingleEntity::attributes_of(self, None)
+            }
+
+            fn attributes_of(&self, fields: std::option::Option<&std::collections::HashSet<String>>) -> rabbithole::model::resource::Attributes {
+                let mut attr_map: std::collections::HashMap<String, serde_json::Value> = std::collections::HashMap::with_capacity(#attr_count);
+                #(
+                    if fields.map_or(true, |fields| fields.contains(stringify!(#attrs))) {
+                        if let Ok(json_value) = serde_json::to_value(&self.#attrs) { attr_map.insert(stringify!(#attrs).to_string(), json_value); }
+                    }
+                )*
                 attr_map.into()
             }
 
+            fn attribute(&self, field: &str) -> std::option::Option<rabbithole::model::resource::AttributeField> {
+                match field {
+                    #( stringify!(#attrs) => serde_json::to_value(&self.#attrs).ok().map(std::convert::Into::into), )*
+                    _ => None,
+                }
+            }
+
+            fn has_attributes(&self, fields: std::option::Option<&std::collections::HashSet<String>>) -> bool {
+                let names: &[&str] = &[#( stringify!(#attrs), )*];
+                names.iter().any(|name| fields.map_or(true, |fields| fields.contains(*name)))
+            }
+
+            fn serialize_attributes<M: serde::ser::SerializeMap>(&self, fields: std::option::Option<&std::collections::HashSet<String>>, map: &mut M) -> std::result::Result<(), M::Error> {
+                #(
+                    if fields.map_or(true, |fields| fields.contains(stringify!(#attrs))) {
+                        map.serialize_entry(stringify!(#attrs), &self.#attrs)?;
+                    }
+                )*
+                Ok(())
+            }
+
             fn relationships(&self, uri: &str) -> rabbithole::model::relationship::Relationships {
-                let mut relat_map: rabbithole::model::relationship::Relationships = std::default::Default::default();
+                let mut relat_map: rabbithole::model::relationship::Relationships = std::collections::HashMap::with_capacity(#relat_count);
                 #(
                     if let Some(relat_id) = self.#to_ones.to_resource_identifier() {
                         let data = rabbithole::model::resource::IdentifierData::Single(Some(relat_id));
                         let relat = rabbithole::model::relationship::Relationship { data, links: self.to_relationship_links(stringify!(#to_ones), uri), ..std::default::Default::default() };
-                        relat_map.insert(stringify!(#to_ones).to_string(), relat);
+                        relat_map.insert(stringify!(#to_ones).into(), relat);
                     }
                 )*
 
                 #(
-                    let mut relat_ids: rabbithole::model::resource::ResourceIdentifiers = std::default::Default::default();
+                    let mut relat_ids = rabbithole::model::resource::ResourceIdentifiers::with_capacity((&self.#to_manys).into_iter().size_hint().0);
                     for item in &self.#to_manys {
                         if let Some(relat_id) = item.to_resource_identifier() {
                             relat_ids.push(relat_id);
@@ -117,7 +186,7 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
                     }
                     let data = rabbithole::model::resource::IdentifierData::Multiple(relat_ids);
                     let relat = rabbithole::model::relationship::Relationship { data, links: self.to_relationship_links(stringify!(#to_manys), uri), ..std::default::Default::default() };
-                    relat_map.insert(stringify!(#to_manys).to_string(), relat);
+                    relat_map.insert(stringify!(#to_manys).into(), relat);
                 )*
 
                 relat_map
@@ -141,6 +210,43 @@ fn inner_derive(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
     Ok(res)
 }
 
+/// Builds the entities back from their resources, with the same `entity` attributes of
+/// `EntityDecorator`
+#[proc_macro_derive(FromResource, attributes(entity))]
+pub fn derive_from_resource(input: TokenStream) -> TokenStream {
+    inner_derive_from_resource(input).unwrap_or_else(|err| err.to_compile_error()).into()
+}
+
+fn inner_derive_from_resource(input: TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let ast: DeriveInput = syn::parse(input)?;
+    let decorated_struct: &syn::Ident = &ast.ident;
+    let struct_lifetime = &ast.generics;
+
+    let (id, attrs, to_ones, to_manys) = get_fields(&ast)?;
+
+    Ok(quote! {
+        impl #struct_lifetime rabbithole::entity::FromResource for #decorated_struct#struct_lifetime {
+            fn from_included(
+                resource: &rabbithole::model::resource::Resource,
+                included: &rabbithole::model::document::Included,
+                ancestors: &[rabbithole::model::resource::ResourceIdentifier],
+            ) -> rabbithole::RbhResult<Self> {
+                use rabbithole::entity::FromRelationship;
+                rabbithole::entity::check_type::<Self>(resource)?;
+                let mut attributes = resource.attributes.get_json_value_map()?;
+                let mut ancestors = ancestors.to_vec();
+                ancestors.push(resource.id.clone());
+                Ok(Self {
+                    #id: rabbithole::entity::id_from_str(&resource.id.id)?,
+                    #( #attrs: rabbithole::entity::attribute_from_json(stringify!(#attrs), attributes.remove(stringify!(#attrs)))?, )*
+                    #( #to_ones: FromRelationship::from_relationship(resource.relationships.get(stringify!(#to_ones)), included, &ancestors)?, )*
+                    #( #to_manys: FromRelationship::from_relationship(resource.relationships.get(stringify!(#to_manys)), included, &ancestors)?, )*
+                })
+            }
+        }
+    })
+}
+
 fn get_meta(attrs: &[syn::Attribute]) -> syn::Result<Vec<syn::Meta>> {
     Ok(attrs
         .iter()
@@ -158,7 +264,11 @@ fn get_entity_type(ast: &syn::DeriveInput) -> syn::Result<(String, HashSet<Strin
 
     for meta in get_meta(&ast.attrs)? {
         if let syn::Meta::List(syn::MetaList { ref nested, .. }) = meta {
-            if let Some(syn::NestedMeta::Meta(ref meta_item)) = nested.last() {
+            for nested_meta in nested {
+                let meta_item = match nested_meta {
+                    syn::NestedMeta::Meta(meta_item) => meta_item,
+                    _ => continue,
+                };
                 match meta_item {
                     syn::Meta::NameValue(syn::MetaNameValue {
                         path,
@@ -201,6 +311,101 @@ fn get_entity_type(ast: &syn::DeriveInput) -> syn::Result<(String, HashSet<Strin
     }
 }
 
+/// If `nested` is the `deprecated` decorator, with or without its arguments
+pub(crate) fn is_deprecation(nested: &syn::NestedMeta) -> bool {
+    matches!(nested, syn::NestedMeta::Meta(meta) if meta.path().is_ident("deprecated"))
+}
+
+/// The `rabbithole::deprecation::Deprecation` of `#[entity(deprecated)]` or
+/// `#[entity(deprecated(since = "...", sunset = "...", note = "..."))]` in `attrs` if any
+fn get_deprecation(attrs: &[syn::Attribute]) -> syn::Result<Option<proc_macro2::TokenStream>> {
+    let mut deprecation = None;
+    for meta in get_meta(attrs)? {
+        let nested = match meta {
+            syn::Meta::List(syn::MetaList { nested, .. }) => nested,
+            _ => continue,
+        };
+        for meta_item in nested.iter().filter(|nested| is_deprecation(nested)) {
+            let (mut since, mut sunset, mut note) = (quote!(None), quote!(None), quote!(None));
+            if let syn::NestedMeta::Meta(syn::Meta::List(syn::MetaList { nested: args, .. })) =
+                meta_item
+            {
+                for arg in args {
+                    let invalid = || {
+                        syn::Error::new_spanned(
+                            arg,
+                            EntityDecoratorError::InvalidDeprecation(
+                                arg.to_token_stream().to_string(),
+                            ),
+                        )
+                    };
+                    let (path, value) = match arg {
+                        syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                            path,
+                            lit: syn::Lit::Str(lit_str),
+                            ..
+                        })) => (path, quote!(Some(#lit_str.to_string()))),
+                        _ => return Err(invalid()),
+                    };
+                    if path.is_ident("since") {
+                        since = value;
+                    } else if path.is_ident("sunset") {
+                        sunset = value;
+                    } else if path.is_ident("note") {
+                        note = value;
+                    } else {
+                        return Err(invalid());
+                    }
+                }
+            }
+            deprecation = Some(quote! {
+                rabbithole::deprecation::Deprecation { since: #since, sunset: #sunset, note: #note }
+            });
+        }
+    }
+    Ok(deprecation)
+}
+
+/// The former names of the resource type in `#[entity(alias = "...")]` of `attrs`, where the
+/// decorator can be repeated
+fn get_aliases(attrs: &[syn::Attribute]) -> syn::Result<Vec<String>> {
+    let mut aliases = vec![];
+    for meta in get_meta(attrs)? {
+        if let syn::Meta::List(syn::MetaList { nested, .. }) = meta {
+            for nested_meta in nested {
+                if let syn::NestedMeta::Meta(syn::Meta::NameValue(syn::MetaNameValue {
+                    path,
+                    lit: syn::Lit::Str(lit_str),
+                    ..
+                })) = nested_meta
+                {
+                    if path.is_ident("alias") {
+                        aliases.push(lit_str.value());
+                    }
+                }
+            }
+        }
+    }
+    Ok(aliases)
+}
+
+/// The names and the deprecations of the deprecated fields of the struct of `ast`
+fn get_deprecated_fields(
+    ast: &syn::DeriveInput,
+) -> syn::Result<Vec<(&syn::Ident, proc_macro2::TokenStream)>> {
+    let mut deprecated = vec![];
+    if let syn::Data::Struct(syn::DataStruct { fields, .. }) = &ast.data {
+        for field in fields {
+            if let (Some(ident), Some(deprecation)) =
+                (field.ident.as_ref(), get_deprecation(&field.attrs)?)
+            {
+                deprecated.push((ident, deprecation));
+            }
+        }
+    }
+    Ok(deprecated)
+}
+
 fn get_fields(ast: &syn::DeriveInput) -> syn::Result<FieldBundle> {
     if let syn::Data::Struct(syn::DataStruct {
         fields: syn::Fields::Named(syn::FieldsNamed { ref named, .. }),
@@ -236,3 +441,15 @@ fn get_fields(ast: &syn::DeriveInput) -> syn::Result<FieldBundle> {
     }
     Err(syn::Error::new_spanned(&ast.ident, EntityDecoratorError::InvalidEntityType))
 }
+
+/// The type of the field `ident` of the struct of `ast`, which is found by `get_fields`
+fn get_type<'a>(ast: &'a syn::DeriveInput, ident: &syn::Ident) -> &'a syn::Type {
+    match &ast.data {
+        syn::Data::Struct(syn::DataStruct { fields, .. }) => fields
+            .iter()
+            .find(|field| field.ident.as_ref() == Some(ident))
+            .map(|field| &field.ty)
+            .expect("the field is found by `get_fields`"),
+        _ => unreachable!("the fields are found by `get_fields`"),
+    }
+}
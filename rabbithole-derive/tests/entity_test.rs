@@ -1,7 +1,7 @@
 extern crate rabbithole_derive as rbh_derive;
 extern crate serde;
 
-use rabbithole::entity::Entity;
+use rabbithole::entity::{Entity, FromResource};
 use rabbithole::model::document::{Document, DocumentItem, PrimaryDataItem};
 use rabbithole::model::link::{Link, RawUri};
 use rabbithole::model::relationship::Relationship;
@@ -42,7 +42,7 @@ pub struct Dog<'a> {
     pub best_one: Option<Box<Dog<'a>>>,
 }
 
-#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[derive(rbh_derive::EntityDecorator, rbh_derive::FromResource, Serialize, Deserialize, Clone)]
 #[entity(type = "fleas")]
 pub struct Flea {
     #[entity(id)]
@@ -144,7 +144,7 @@ fn general_test() {
         .into(),
         relationships: HashMap::from_iter(vec![("only_flea".into(), Relationship {
             data: IdentifierData::Single(Some(ResourceIdentifier {
-                ty: "fleas".to_string(),
+                ty: "fleas".into(),
                 id: "1".to_string(),
             })),
             links: HashMap::from_iter(vec![
@@ -236,10 +236,13 @@ fn general_test() {
                 meta: Default::default(),
             }),
             ("fleas".into(), Relationship {
-                data: IdentifierData::Multiple(vec![
-                    ResourceIdentifier { ty: "fleas".to_string(), id: "a".to_string() },
-                    ResourceIdentifier { ty: "fleas".to_string(), id: "b".to_string() },
-                ]),
+                data: IdentifierData::Multiple(
+                    vec![
+                        ResourceIdentifier { ty: "fleas".into(), id: "a".to_string() },
+                        ResourceIdentifier { ty: "fleas".into(), id: "b".to_string() },
+                    ]
+                    .into(),
+                ),
                 links: HashMap::from_iter(vec![
                     (
                         "self".into(),
@@ -257,7 +260,7 @@ fn general_test() {
             }),
             ("master".into(), Relationship {
                 data: IdentifierData::Single(Some(ResourceIdentifier {
-                    ty: "humans".to_string(),
+                    ty: "humans".into(),
                     id: "number".to_string(),
                 })),
                 links: HashMap::from_iter(vec![
@@ -326,3 +329,17 @@ fn general_test() {
         }
     }
 }
+
+#[test]
+fn from_resource_test() {
+    use rabbithole::entity::SingleEntity;
+
+    let flea = Flea { id: "flea".into(), name: "Flea".into() };
+    let resource = flea.to_resource("http://localhost", &Default::default()).unwrap();
+    let built = Flea::from_resource(&resource, &Default::default()).unwrap();
+    assert_eq!((built.id, built.name), (flea.id, flea.name));
+
+    let resource = Resource { id: ResourceIdentifier::new("dogs", "flea"), ..resource };
+    let err = Flea::from_resource(&resource, &Default::default()).err().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0402"));
+}
@@ -9,12 +9,11 @@ use rabbithole::entity::{Entity, SingleEntity};
 use rabbithole::model::error;
 use rabbithole::model::link::RawUri;
 use rabbithole::model::relationship::Relationship;
-use rabbithole::operation::Fetching;
+use rabbithole::operation::{FetchPlan, Fetching};
 use rabbithole::query::Query;
+use rabbithole_endpoint_actix::app::JsonApiApp;
 use rabbithole_endpoint_actix::settings::ActixSettingsModel;
-use rabbithole_endpoint_actix::ActixSettings;
 use serde::{Deserialize, Serialize};
-use std::convert::TryInto;
 use uuid::Uuid;
 
 // rbh_derive::EntityDecorator to generate JSON:API data models
@@ -66,17 +65,25 @@ fn generate_masters(len: usize) -> Vec<Human> {
     masters
 }
 
+// The services behind the resources, shared by all of the requests
+#[derive(Default)]
+pub struct DogService;
+
 #[async_trait]
-impl Fetching for Dog {
+impl Fetching for DogService {
     type Item = Dog;
 
-    async fn fetch_collection(_query: &Query) -> Result<Vec<Self::Item>, error::Error> {
+    async fn fetch_collection(
+        &self, _plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
         let rand = rand::random::<usize>() % 5;
         let dogs = generate_dogs(rand);
         Ok(dogs)
     }
 
-    async fn fetch_single(id: &str, _query: &Query) -> Result<Option<Self::Item>, error::Error> {
+    async fn fetch_single(
+        &self, id: &str, _plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
         if id == "none" {
             Ok(None)
         } else {
@@ -86,29 +93,34 @@ impl Fetching for Dog {
     }
 
     async fn fetch_relationship(
-        _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
     ) -> Result<Relationship, error::Error> {
         Err(error::Error::FieldNotExist(related_field, None))
     }
 
     async fn fetch_related(
-        _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
     ) -> Result<serde_json::Value, error::Error> {
         Err(error::Error::FieldNotExist(related_field, None))
     }
 }
 
+#[derive(Default)]
+pub struct HumanService;
+
 #[async_trait]
-impl Fetching for Human {
+impl Fetching for HumanService {
     type Item = Human;
 
-    async fn fetch_collection(_: &Query) -> Result<Vec<Self::Item>, error::Error> {
+    async fn fetch_collection(&self, _: &FetchPlan<'_>) -> Result<Vec<Self::Item>, error::Error> {
         let rand = rand::random::<usize>() % 5 + 1;
         let masters = generate_masters(rand);
         Ok(masters)
     }
 
-    async fn fetch_single(id: &str, _query: &Query) -> Result<Option<Self::Item>, error::Error> {
+    async fn fetch_single(
+        &self, id: &str, _plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
         if id == "none" {
             Ok(None)
         } else {
@@ -118,7 +130,7 @@ impl Fetching for Human {
     }
 
     async fn fetch_relationship(
-        id: &str, related_field: &str, uri: &str, _query: &Query, _request_path: &RawUri,
+        &self, id: &str, related_field: &str, uri: &str, _query: &Query, _request_path: &RawUri,
     ) -> Result<Relationship, error::Error> {
         if related_field == "dogs" {
             if id == "none" {
@@ -134,7 +146,7 @@ impl Fetching for Human {
     }
 
     async fn fetch_related(
-        id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
     ) -> Result<serde_json::Value, error::Error> {
         if related_field == "dogs" {
             if id == "none" {
@@ -152,27 +164,26 @@ impl Fetching for Human {
     }
 }
 
-fn main() -> std::io::Result<()> {
+#[actix_web::main]
+async fn main() -> std::io::Result<()> {
     std::env::set_var("RUST_LOG", "actix_web=info");
     env_logger::init();
 
-    let mut settings = config::Config::default();
-    settings.merge(config::File::with_name("config/actix.config.example.toml")).unwrap();
-    let settings: ActixSettingsModel = settings.try_into().unwrap();
+    // The settings in the file can be overridden by the environment variables, like `RBH__PORT`
+    let settings = ActixSettingsModel::load(Some("config/actix.config.example.toml"), &[]).unwrap();
     let settings_port = settings.port;
+    // The services are created once and shared by all of the workers,
+    // and all of the resource types are mounted under `settings.path`
+    let api =
+        JsonApiApp::new(settings).unwrap().resource::<HumanService>().resource::<DogService>();
 
     HttpServer::new(move || {
         App::new()
-            .data::<ActixSettings<Human>>(settings.clone().try_into().unwrap())
-            .data::<ActixSettings<Dog>>(settings.clone().try_into().unwrap())
             .wrap(middleware::Logger::new(r#"%a "%r" %s %b "%{Referer}i" "%{Content-Type}i" %T"#))
-            .service(
-                web::scope(&settings.path)
-                    .service(Human::actix_service())
-                    .service(Dog::actix_service()),
-            )
+            .service(api.scope())
             .default_service(web::to(HttpResponse::NotFound))
     })
     .bind(format!("[::]:{}", settings_port))?
     .run()
+    .await
 }
@@ -0,0 +1,348 @@
+use actix_web::http::header;
+use actix_web::{web, HttpRequest, HttpResponse, Scope};
+use config::ConfigError;
+use rabbithole::cache::DocumentCache;
+use rabbithole::entity::SingleEntity;
+use rabbithole::handler;
+use rabbithole::model::error;
+use rabbithole::operation::{Fetching, Principal};
+use rabbithole::rule::registry::RuleRegistry;
+use rabbithole::schema::Registry;
+use rabbithole::JSON_API_HEADER;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::auth::{self, Authenticator};
+use crate::i18n::Localization;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::openapi::{self, OpenApiInfo};
+use crate::rate_limit::RateLimiter;
+use crate::settings::ActixSettingsModel;
+use crate::{base_uri, ActixSettings, ErrorHook};
+
+type ScopeFactory = Arc<dyn Fn(&JsonApiApp) -> Scope + Send + Sync>;
+
+/// All of the resource types of an API, mounted under the same `path` with the same settings,
+/// so the links of every type share the same base URI:
+/// ```ignore
+/// let api = JsonApiApp::new(settings)?.resource::<HumanService>().resource::<DogService>();
+/// HttpServer::new(move || App::new().service(api.scope()))
+/// ```
+#[derive(Clone)]
+pub struct JsonApiApp {
+    settings: ActixSettingsModel,
+    uri: url::Url,
+    prefix: String,
+    types: Vec<String>,
+    registry: Registry,
+    protected_types: Vec<String>,
+    /// The resource types of the API version, if it's limited
+    version_types: Option<Vec<String>>,
+    scopes: Vec<ScopeFactory>,
+    /// The rate limiters of the resource types, reporting the usage at `<path>/rate-limit`
+    rate_limiters: Vec<(String, RateLimiter)>,
+    error_hook: Option<ErrorHook>,
+    authenticator: Option<Arc<dyn Authenticator>>,
+    localization: Option<Arc<Localization>>,
+    cache: Option<Arc<dyn DocumentCache>>,
+    openapi: Option<OpenApiInfo>,
+    #[cfg(feature = "metrics")]
+    metrics: Option<Metrics>,
+}
+
+impl JsonApiApp {
+    pub fn new(settings: ActixSettingsModel) -> Result<Self, url::ParseError> {
+        let (uri, prefix) = base_uri(&settings)?;
+        Ok(Self {
+            settings,
+            uri,
+            prefix,
+            types: Vec::new(),
+            registry: Default::default(),
+            protected_types: Vec::new(),
+            version_types: None,
+            scopes: Vec::new(),
+            rate_limiters: Vec::new(),
+            error_hook: None,
+            authenticator: None,
+            localization: None,
+            cache: None,
+            openapi: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        })
+    }
+
+    /// The app of the API version `name` in `ActixSettingsModel::versions`, mounted at
+    /// `<path><version path>`, where the resource types not in the version are skipped
+    pub fn version(mut settings: ActixSettingsModel, name: &str) -> Result<Self, ConfigError> {
+        let version = settings
+            .versions
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ConfigError::NotFound(format!("versions.{}", name)))?;
+        settings.path = format!("{}{}", settings.path.trim_end_matches('/'), version.path);
+        let mut app = Self::new(settings).map_err(|err| {
+            ConfigError::Message(format!("invalid URL of version `{}`: {}", name, err))
+        })?;
+        app.version_types = version.resources;
+        Ok(app)
+    }
+
+    /// Mounts the resource type of the service `S`, created by `Default`
+    pub fn resource<S>(self) -> Self
+    where
+        S: 'static + Fetching + Default,
+    {
+        self.service(S::default())
+    }
+
+    /// Mounts the resource type of the service `S` like `resource`, rejecting the requests
+    /// without credentials
+    pub fn protected_resource<S>(self) -> Self
+    where
+        S: 'static + Fetching + Default,
+    {
+        self.protected_service(S::default())
+    }
+
+    /// Mounts the resource type of `service`
+    /// NOTICE:
+    ///   - Panics if the resource type is mounted already
+    ///   - Skips the resource type if it's not in the API version of the app
+    pub fn service<S>(self, service: impl Into<Arc<S>>) -> Self
+    where
+        S: 'static + Fetching,
+    {
+        self.mount(service.into(), false)
+    }
+
+    /// Mounts the resource type of `service` like `service`, rejecting the requests without
+    /// credentials
+    pub fn protected_service<S>(self, service: impl Into<Arc<S>>) -> Self
+    where
+        S: 'static + Fetching,
+    {
+        self.mount(service.into(), true)
+    }
+
+    fn mount<S>(mut self, service: Arc<S>, protected: bool) -> Self
+    where
+        S: 'static + Fetching,
+    {
+        let ty = S::Item::ty();
+        if self.version_types.as_ref().is_some_and(|types| !types.contains(&ty)) {
+            return self;
+        }
+        assert!(!self.types.contains(&ty), "resource type `{}` is mounted twice", ty);
+        if protected {
+            self.protected_types.push(ty.clone());
+        }
+        self.registry.add(S::Item::schema());
+        for alias in S::Item::aliases() {
+            self.settings.jsonapi.type_aliases.insert(alias, ty.clone());
+        }
+        // The rate limiter of the resource type is shared by all of the workers
+        let rate_limiter =
+            self.settings.rate_limit.clone().map(|rate_limit| {
+                RateLimiter::new(rate_limit, self.settings.trust_forwarded_headers)
+            });
+        if let Some(rate_limiter) = &rate_limiter {
+            self.rate_limiters.push((ty.clone(), rate_limiter.clone()));
+        }
+        self.types.push(ty);
+        self.scopes.push(Arc::new(move |app: &JsonApiApp| {
+            let mut settings = app.settings::<S>(service.clone());
+            settings.rate_limiter = rate_limiter.clone();
+            settings.protected = protected;
+            settings.scope()
+        }));
+        self
+    }
+
+    /// Runs `error_hook` on every error of all of the resource types before it's rendered
+    pub fn with_error_hook(
+        mut self, error_hook: impl Fn(&mut error::Error, &HttpRequest) + Send + Sync + 'static,
+    ) -> Self {
+        self.error_hook = Some(Arc::new(error_hook));
+        self
+    }
+
+    /// Authenticates the requests of all of the resource types by `authenticator`,
+    /// where only the protected ones reject the requests without credentials
+    pub fn with_authenticator(mut self, authenticator: impl Authenticator + 'static) -> Self {
+        self.authenticator = Some(Arc::new(authenticator));
+        self
+    }
+
+    /// Translates the errors of all of the resource types with `localization`
+    pub fn with_localization(mut self, localization: Localization) -> Self {
+        self.localization = Some(Arc::new(localization));
+        self
+    }
+
+    /// Checks the requests and the documents of all of the resource types with the custom rules
+    /// of `rules`
+    pub fn with_rules(mut self, rules: RuleRegistry) -> Self {
+        self.settings.jsonapi.rules = rules;
+        self
+    }
+
+    /// Answers the operations of all of the resource types with the documents in `cache`
+    pub fn with_cache(mut self, cache: Arc<dyn DocumentCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Serves the OpenAPI document of all of the resource types at `<path>/openapi.json`
+    pub fn with_openapi(mut self, title: &str, version: &str) -> Self {
+        self.openapi = Some(OpenApiInfo { title: title.into(), version: version.into() });
+        self
+    }
+
+    /// Records the requests of all of the resource types into `metrics`
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// The `ActixSettings` of a resource type with the shared settings
+    pub fn settings<S>(&self, service: impl Into<Arc<S>>) -> ActixSettings<S>
+    where
+        S: 'static + Fetching,
+    {
+        let mut settings = ActixSettings::with_base(
+            self.settings.clone(),
+            self.uri.clone(),
+            self.prefix.clone(),
+            service.into(),
+        );
+        settings.error_hook = self.error_hook.clone();
+        settings.authenticator = self.authenticator.clone();
+        settings.localization = self.localization.clone();
+        settings.cache = self.cache.clone();
+        #[cfg(feature = "metrics")]
+        let settings = match &self.metrics {
+            Some(metrics) => settings.with_metrics(metrics.clone()),
+            None => settings,
+        };
+        settings
+    }
+
+    /// The OpenAPI 3 document of the mounted resource types, with the routes of the operations
+    /// which are not disabled, described by the schemas of the entities. Without
+    /// `with_openapi`, the title is the base URI and the version is the JSON:API version
+    pub fn openapi(&self) -> serde_json::Value {
+        let info = self.openapi.clone().unwrap_or_else(|| OpenApiInfo {
+            title: self.uri.to_string(),
+            version: self.settings.jsonapi.version.to_string(),
+        });
+        openapi::document(
+            &info,
+            &self.uri,
+            &self.settings,
+            &self.registry,
+            &self.protected_types,
+            self.authenticator.is_some(),
+        )
+    }
+
+    /// All of the mounted resource types in a `<path>` scope, with a `/<ty>` scope for each type,
+    /// the `/openapi.json` route if it's enabled by `with_openapi`, the `/rate-limit` route if
+    /// `rate_limit` is set, and a `/<alias>` scope for each alias of the types, redirecting to the
+    /// routes of the type with `308 Permanent Redirect`. The paths only matched with the
+    /// tolerances of `route_matching`, like `/Humans/`, are answered by the routes of the type
+    pub fn scope(&self) -> Scope {
+        let mut scope = web::scope(&self.settings.path);
+        for (alias, ty) in &self.settings.jsonapi.type_aliases {
+            if !self.types.contains(ty) {
+                continue;
+            }
+            let path = self.settings.paths.get(ty).cloned().unwrap_or_else(|| format!("/{}", ty));
+            let (prefix, base) = (self.prefix.clone(), self.settings.path.clone());
+            scope = scope.service(web::scope(&format!("/{}", alias)).default_service(web::to(
+                move |req: HttpRequest| {
+                    let query = Some(req.query_string()).filter(|query| !query.is_empty());
+                    let location = handler::alias_location(
+                        &base,
+                        req.path(),
+                        query,
+                        path.trim_start_matches('/'),
+                    );
+                    let location = format!("{}{}", prefix, location);
+                    async move {
+                        HttpResponse::PermanentRedirect()
+                            .insert_header((header::LOCATION, location))
+                            .finish()
+                    }
+                },
+            )));
+        }
+        if !self.rate_limiters.is_empty() {
+            let rate_limiters = self.rate_limiters.clone();
+            let authenticator = self.authenticator.clone();
+            let jsonapi = self.settings.jsonapi.clone();
+            scope = scope.route(
+                "/rate-limit",
+                web::get().to(move |req: HttpRequest| {
+                    let rate_limiters = rate_limiters.clone();
+                    let authenticator = authenticator.clone();
+                    let jsonapi = jsonapi.clone();
+                    async move {
+                        let principal = auth::principal_of(authenticator.as_deref(), req.headers())
+                            .await
+                            .unwrap_or_default();
+                        let body = rate_limit_document(&rate_limiters, &req, principal.as_ref());
+                        let body = jsonapi.namespaced(body).to_string();
+                        HttpResponse::Ok().content_type(JSON_API_HEADER).body(body)
+                    }
+                }),
+            );
+        }
+        if self.openapi.is_some() {
+            let openapi = self.openapi();
+            scope = scope.route(
+                "/openapi.json",
+                web::get().to(move || {
+                    let openapi = openapi.clone();
+                    async move { HttpResponse::Ok().json(openapi) }
+                }),
+            );
+        }
+        self.scopes.iter().fold(scope, |scope, factory| scope.service(factory(self)))
+    }
+
+    pub fn path(&self) -> &str { &self.settings.path }
+
+    pub fn uri(&self) -> &url::Url { &self.uri }
+
+    /// The mounted resource types, in the mounting order
+    pub fn types(&self) -> &[String] { &self.types }
+}
+
+/// The meta document of the usage of the rate limits of the client of `req`, which is `principal`
+/// if the credentials are verified, by the resource types, which doesn't take any tokens:
+/// ```json
+/// { "meta": { "rateLimits": { "people": { "limit": 10, "remaining": 9, "reset": 1 } } } }
+/// ```
+fn rate_limit_document(
+    rate_limiters: &[(String, RateLimiter)], req: &HttpRequest, principal: Option<&Principal>,
+) -> serde_json::Value {
+    let usages: serde_json::Map<String, serde_json::Value> = rate_limiters
+        .iter()
+        .map(|(ty, limiter)| (ty.clone(), serde_json::json!(limiter.usage(req, principal))))
+        .collect();
+    serde_json::json!({ "meta": { "rateLimits": usages } })
+}
+
+impl fmt::Debug for JsonApiApp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonApiApp")
+            .field("settings", &self.settings)
+            .field("uri", &self.uri)
+            .field("types", &self.types)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,58 @@
+use actix_web::http::header::{self, HeaderMap};
+use async_trait::async_trait;
+use rabbithole::operation::Principal;
+use rabbithole::RbhResult;
+
+/// The header carrying the API key of a request
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The credentials of a request, taken from `Authorization: Bearer <token>` or `X-Api-Key`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Credentials {
+    Bearer(String),
+    ApiKey(String),
+}
+
+impl Credentials {
+    /// The credentials in the headers, where the bearer token goes first
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let bearer = headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| {
+                let (scheme, token) = value.trim().split_once(' ')?;
+                scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+            })
+            .filter(|token| !token.is_empty())
+            .map(|token| Credentials::Bearer(token.to_string()));
+        bearer.or_else(|| {
+            headers
+                .get(API_KEY_HEADER)
+                .and_then(|value| value.to_str().ok())
+                .filter(|key| !key.is_empty())
+                .map(|key| Credentials::ApiKey(key.to_string()))
+        })
+    }
+}
+
+/// Validates the credentials of the requests, like looking up the API keys or verifying the
+/// tokens, where the `Principal` is put into the `OperationContext` of the operations
+#[async_trait]
+pub trait Authenticator: Send + Sync {
+    /// Returns the principal of valid credentials, or an error like
+    /// `rabbithole::model::error::Error::Unauthenticated` otherwise
+    async fn authenticate(&self, credentials: &Credentials) -> RbhResult<Principal>;
+}
+
+/// The principal of the credentials in `headers` verified by `authenticator`, which is `None`
+/// without an authenticator or credentials, failing if the credentials are invalid
+pub async fn principal_of(
+    authenticator: Option<&dyn Authenticator>, headers: &HeaderMap,
+) -> RbhResult<Option<Principal>> {
+    match (authenticator, Credentials::from_headers(headers)) {
+        (Some(authenticator), Some(credentials)) => {
+            authenticator.authenticate(&credentials).await.map(Some)
+        },
+        _ => Ok(None),
+    }
+}
@@ -0,0 +1,3 @@
+//! The message catalogs of the errors, which are shared with the other endpoints in
+//! `rabbithole::i18n`
+pub use rabbithole::i18n::{builtin, Localization, Message};
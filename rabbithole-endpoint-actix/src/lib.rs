@@ -1,200 +1,784 @@
+pub mod app;
+pub mod auth;
+pub mod i18n;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod openapi;
+pub mod rate_limit;
 pub mod settings;
+pub mod testing;
 
-use actix_web::http::{header, HeaderMap, StatusCode};
-use actix_web::web;
-use actix_web::{HttpRequest, HttpResponse};
-use futures::{FutureExt, TryFutureExt};
+use actix_web::http::header::{self, HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::{Method, StatusCode};
+use actix_web::{guard, web};
+use actix_web::{HttpMessage, HttpRequest, HttpResponse, Resource, Route, Scope};
 use rabbithole::entity::SingleEntity;
 
-use crate::settings::{ActixSettingsModel, JsonApiSettings};
-use actix_web::dev::HttpResponseBuilder;
+use crate::auth::Authenticator;
+use crate::i18n::Localization;
+#[cfg(feature = "metrics")]
+use crate::metrics::Metrics;
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::settings::{ActixSettingsModel, JsonApiSettings, ResourceGuards};
 
+use rabbithole::cache::{CacheKey, DocumentCache};
+use rabbithole::encoding::Encoding;
+use rabbithole::handler::{self, OperationRequest, RouteMatching};
 use rabbithole::model::error;
-use rabbithole::model::version::JsonApiVersion;
-use rabbithole::operation::Fetching;
-use rabbithole::rule::RuleDispatcher;
-use rabbithole::JSON_API_HEADER;
-use serde::export::TryFrom;
-
+use rabbithole::model::link::RawUri;
+use rabbithole::operation::{authorize_included, Action, Fetching, OperationContext, Principal};
 use rabbithole::query::Query;
-use std::marker::PhantomData;
+use rabbithole::rule::registry::RuleRegistry;
+use rabbithole::RbhResult;
+
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::{Instant, SystemTime};
+use tracing::{info_span, Instrument, Span};
+
+fn operation_context(req: &HttpRequest) -> OperationContext {
+    let mut ctx = OperationContext::default();
+    for (name, value) in req.headers().iter() {
+        ctx.headers.append(name.clone(), value.clone());
+    }
+    ctx.principal = req.extensions().get::<Principal>().cloned();
+    ctx
+}
+
+pub use rabbithole::handler::OPERATIONS;
+
+/// The header carrying the correlation id of a request, echoed in the response
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// The path parameter of the type segment matched with the tolerances of `route_matching`
+const TYPE_SEGMENT: &str = "rbh_type";
+
+/// The `X-Request-Id` of the request if it's a reasonable one, or a new UUID
+fn request_id(req: &HttpRequest) -> String {
+    req.headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|id| !id.is_empty() && id.len() <= 128)
+        .map(ToString::to_string)
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+fn request_span(operation: &'static str, ty: &str, request_id: &str) -> Span {
+    info_span!("request", operation, ty, request_id)
+}
+
+/// A closure post-processing every error before it's rendered, like adding more `meta`,
+/// translating the messages or hiding the internals in production
+pub type ErrorHook = Arc<dyn Fn(&mut error::Error, &HttpRequest) + Send + Sync>;
+
+/// Renders the result of a handler, where the errors carry the request id in `meta.requestId`
+/// and go through the `error_hook`, and the response carries it in the `X-Request-Id` header.
+/// The `5xx` errors are redacted after the `error_hook` if `redact_errors` of `jsonapi` is enabled
+fn finish_response(
+    req: &HttpRequest, request_id: &str, jsonapi: &JsonApiSettings, error_hook: Option<&ErrorHook>,
+    resp: RbhResult<HttpResponse>,
+) -> HttpResponse {
+    let mut resp = resp.unwrap_or_else(|mut err| {
+        tracing::info!(status = ?err.status, code = ?err.code, detail = ?err.detail, "request failed");
+        err.for_each_mut(|err| {
+            err.meta
+                .get_or_insert_with(Default::default)
+                .insert(handler::CORRELATION_ID.into(), request_id.into());
+            if let Some(error_hook) = error_hook {
+                error_hook(err, req);
+            }
+        });
+        error_to_response(jsonapi.redacted(err))
+    });
+    if let Ok(value) = HeaderValue::from_str(request_id) {
+        resp.headers_mut().insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+    }
+    resp
+}
 
 fn error_to_response(err: error::Error) -> HttpResponse {
-    new_json_api_resp(
-        err.status.as_deref().and_then(|s| s.parse().ok()).unwrap_or(StatusCode::BAD_REQUEST),
-    )
-    .json(err)
+    from_handler_response(handler::error_response(err))
+}
+
+/// Converts a response of `rabbithole::handler` into the one of actix
+fn from_handler_response(resp: http::Response<Vec<u8>>) -> HttpResponse {
+    let (parts, body) = resp.into_parts();
+    let mut builder = HttpResponse::build(parts.status);
+    for (name, value) in parts.headers.iter() {
+        builder.append_header((name.clone(), value.clone()));
+    }
+    builder.body(body)
 }
 
-#[derive(Debug, Clone)]
+/// The base URI of the links and the path prefix of the external URL
+fn base_uri(settings: &ActixSettingsModel) -> Result<(url::Url, String), url::ParseError> {
+    let mut uri = match &settings.external_url {
+        Some(external_url) => external_url.parse::<url::Url>()?,
+        None => format!("http://{}:{}", settings.host, settings.port).parse::<url::Url>()?,
+    };
+    let prefix = uri.path().trim_end_matches('/').to_string();
+    uri.set_path(&format!("{}{}", prefix, settings.path));
+    Ok((uri, prefix))
+}
+
+/// The settings and the service of a resource type, shared by all of the handlers
 pub struct ActixSettings<T>
 where
     T: 'static + Fetching,
 {
     pub path: String,
+    /// The base of the links, which is `<external_url or http://host:port><path>`
     pub uri: url::Url,
     pub jsonapi: JsonApiSettings,
-    _data: PhantomData<T>,
+    /// If the `X-Forwarded-Proto/Host/Prefix` headers are used to build the links
+    pub trust_forwarded_headers: bool,
+    pub service: Arc<T>,
+    /// The path prefix of the external URL, prepended to the request paths in the links
+    prefix: String,
+    /// Post-processes the errors of the responses
+    pub error_hook: Option<ErrorHook>,
+    /// Rejects the requests over the rate limit with `429 Too Many Requests`
+    pub rate_limiter: Option<RateLimiter>,
+    /// Authenticates the requests with credentials
+    pub authenticator: Option<Arc<dyn Authenticator>>,
+    /// If the requests without credentials are rejected with `401 Unauthorized`
+    pub protected: bool,
+    /// The guards of the operations of the resource type, enforced by `ActixSettings::scope`
+    pub guards: ResourceGuards,
+    /// The paths of the resource types which are not `/<ty>`, used by `ActixSettings::scope`
+    /// and rewriting the links
+    pub paths: HashMap<String, String>,
+    /// Translates the errors into the locale negotiated with `Accept-Language`
+    pub localization: Option<Arc<Localization>>,
+    /// Answers the operations with the documents cached in it
+    pub cache: Option<Arc<dyn DocumentCache>>,
+    #[cfg(feature = "metrics")]
+    pub metrics: Option<Metrics>,
 }
 
-impl<T> TryFrom<ActixSettingsModel> for ActixSettings<T>
+impl<T> ActixSettings<T>
 where
-    T: 'static + Fetching + Send + Sync,
-    T::Item: Send + Sync,
+    T: 'static + Fetching,
 {
-    type Error = url::ParseError;
+    pub fn new(
+        settings: ActixSettingsModel, service: impl Into<Arc<T>>,
+    ) -> Result<Self, url::ParseError> {
+        let (uri, prefix) = base_uri(&settings)?;
+        Ok(Self::with_base(settings, uri, prefix, service.into()))
+    }
+
+    /// The settings with the base URI and the prefix given by `base_uri`
+    fn with_base(
+        settings: ActixSettingsModel, uri: url::Url, prefix: String, service: Arc<T>,
+    ) -> Self {
+        let trust_forwarded_headers = settings.trust_forwarded_headers;
+        let guards = settings.resources.get(&T::Item::ty()).cloned().unwrap_or_default();
+        let mut jsonapi = settings.jsonapi;
+        for alias in T::Item::aliases() {
+            jsonapi.type_aliases.insert(alias, T::Item::ty());
+        }
+        Self {
+            path: settings.path,
+            uri,
+            jsonapi,
+            trust_forwarded_headers,
+            service,
+            prefix,
+            error_hook: None,
+            rate_limiter: settings
+                .rate_limit
+                .map(|rate_limit| RateLimiter::new(rate_limit, trust_forwarded_headers)),
+            authenticator: None,
+            protected: false,
+            guards,
+            paths: settings.paths,
+            localization: None,
+            cache: None,
+            #[cfg(feature = "metrics")]
+            metrics: None,
+        }
+    }
+
+    /// Runs `error_hook` on every error before it's rendered
+    pub fn with_error_hook(
+        mut self, error_hook: impl Fn(&mut error::Error, &HttpRequest) + Send + Sync + 'static,
+    ) -> Self {
+        self.error_hook = Some(Arc::new(error_hook));
+        self
+    }
 
-    fn try_from(value: ActixSettingsModel) -> Result<Self, Self::Error> {
-        let ActixSettingsModel { host, port, path, jsonapi } = value;
-        let uri = format!("http://{}:{}", host, port).parse::<url::Url>().unwrap();
-        let uri = uri.join(&path).unwrap();
-        Ok(Self { path, uri, jsonapi, _data: PhantomData })
+    /// Authenticates the requests with credentials by `authenticator`,
+    /// and rejects the ones without credentials if the resource type is `protected`
+    pub fn with_authenticator(
+        mut self, authenticator: impl Authenticator + 'static, protected: bool,
+    ) -> Self {
+        self.authenticator = Some(Arc::new(authenticator));
+        self.protected = protected;
+        self
+    }
+
+    /// Translates the errors with `localization`, answering the `Content-Language` header
+    pub fn with_localization(mut self, localization: Localization) -> Self {
+        self.localization = Some(Arc::new(localization));
+        self
+    }
+
+    /// Checks the requests and the documents with the custom rules of `rules`
+    pub fn with_rules(mut self, rules: RuleRegistry) -> Self {
+        self.jsonapi.rules = rules;
+        self
+    }
+
+    /// Answers the operations with the documents in `cache`, which is invalidated by the
+    /// `ResourceChanged` events if it's subscribed to the `EventBus` of the services
+    pub fn with_cache(mut self, cache: Arc<dyn DocumentCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Records the requests of the resource type into `metrics`
+    #[cfg(feature = "metrics")]
+    pub fn with_metrics(mut self, metrics: Metrics) -> Self {
+        self.metrics = Some(metrics);
+        self
     }
 }
 
-impl<T> ActixSettings<T>
+impl<T> Clone for ActixSettings<T>
 where
-    T: 'static + Fetching + Send + Sync,
-    T::Item: Send + Sync,
+    T: 'static + Fetching,
 {
-    pub fn fetch_collection(
-        self, req: HttpRequest,
-    ) -> impl futures01::Future<Item = HttpResponse, Error = actix_web::Error> {
-        if let Err(err_resp) = check_header(&self.jsonapi.version, &req.headers()) {
-            return futures::future::ok(err_resp).boxed_local().compat();
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            uri: self.uri.clone(),
+            jsonapi: self.jsonapi.clone(),
+            trust_forwarded_headers: self.trust_forwarded_headers,
+            service: self.service.clone(),
+            prefix: self.prefix.clone(),
+            error_hook: self.error_hook.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            authenticator: self.authenticator.clone(),
+            protected: self.protected,
+            guards: self.guards.clone(),
+            paths: self.paths.clone(),
+            localization: self.localization.clone(),
+            cache: self.cache.clone(),
+            #[cfg(feature = "metrics")]
+            metrics: self.metrics.clone(),
         }
-        match Query::from_uri(req.uri()) {
-            Ok(query) => {
-                let fut = async move {
-                    let vec_res = T::fetch_collection(&query).await;
-                    match vec_res {
-                        Ok(vec) => {
-                            match T::vec_to_document(
-                                &vec,
-                                &self.uri.to_string(),
-                                &query,
-                                &req.uri().into(),
-                            )
-                            .await
-                            {
-                                Ok(doc) => Ok(HttpResponse::Ok().json(doc)),
-                                Err(err) => Ok(error_to_response(err)),
-                            }
-                        },
-                        Err(err) => Ok(error_to_response(err)),
-                    }
-                };
+    }
+}
 
-                fut.boxed_local().compat()
-            },
-            Err(err) => futures::future::ok(error_to_response(err)).boxed_local().compat(),
-        }
+impl<T> fmt::Debug for ActixSettings<T>
+where
+    T: 'static + Fetching,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ActixSettings")
+            .field("path", &self.path)
+            .field("uri", &self.uri)
+            .field("jsonapi", &self.jsonapi)
+            .finish_non_exhaustive()
     }
+}
 
-    pub fn fetch_single(
-        self, param: web::Path<String>, req: HttpRequest,
-    ) -> impl futures01::Future<Item = HttpResponse, Error = actix_web::Error> {
-        if let Err(err_resp) = check_header(&self.jsonapi.version, &req.headers()) {
-            return futures::future::ok(err_resp).boxed_local().compat();
-        }
-        match Query::from_uri(req.uri()) {
-            Ok(query) => {
-                let fut = async move {
-                    match T::fetch_single(&param.into_inner(), &query).await {
-                        Ok(item) => {
-                            match item.to_document_automatically(
-                                &self.uri.to_string(),
-                                &query,
-                                &req.uri().into(),
-                            ) {
-                                Ok(doc) => Ok(new_json_api_resp(StatusCode::OK).json(doc)),
-                                Err(err) => Ok(error_to_response(err)),
-                            }
-                        },
-                        Err(err) => Ok(error_to_response(err)),
-                    }
-                };
+impl<T> ActixSettings<T>
+where
+    T: 'static + Fetching,
+{
+    /// All of the routes of the resource type in a `/<ty>` scope, or the one in `paths`, carrying
+    /// the settings with it, except the ones disabled by the `guards`. The paths written with the
+    /// tolerances of `route_matching`, like `/Humans/`, are routed to the handlers as well, which
+    /// answer them with the canonical `self` links
+    pub fn scope(&self) -> Scope {
+        let matching = self.jsonapi.route_matching;
+        let enabled = |operation: &str| !self.guards.is_disabled(operation);
+        web::scope(&type_pattern(&self.type_path(), matching))
+            .app_data(web::Data::new(self.clone()))
+            .service(Self::enabled_resources(enabled, matching.trailing_slash))
+    }
 
-                fut.boxed_local().compat()
-            },
-            Err(err) => futures::future::ok(error_to_response(err)).boxed_local().compat(),
-        }
+    /// All of the routes of the resource type relative to `/<ty>`, whose handlers look for
+    /// `web::Data<ActixSettings<T>>` in the app data. Each path answers:
+    ///   - `GET` and `HEAD` (the same headers without the body)
+    ///   - `OPTIONS` with the `Allow` header
+    pub fn resources() -> Vec<Resource> { Self::enabled_resources(|_| true, false) }
+
+    /// The routes of the operations which are `enabled`, also matching the paths with a trailing
+    /// slash if `trailing_slash`
+    fn enabled_resources(enabled: impl Fn(&str) -> bool, trailing_slash: bool) -> Vec<Resource> {
+        let resource = |pattern: &str| match trailing_slash {
+            true => web::resource(vec![pattern.to_string(), format!("{}/", pattern)]),
+            false => web::resource(pattern),
+        };
+        let resources = vec![
+            ("fetch_collection", resource("").route(get_or_head().to(Self::collection_route))),
+            ("fetch_single", resource("/{id}").route(get_or_head().to(Self::single_route))),
+            (
+                "fetch_relationship",
+                resource("/{id}/relationships/{related_field}")
+                    .route(get_or_head().to(Self::relationship_route)),
+            ),
+            (
+                "fetch_related",
+                resource("/{id}/{related_field}").route(get_or_head().to(Self::related_route)),
+            ),
+        ];
+        resources
+            .into_iter()
+            .filter(|(operation, _)| enabled(operation))
+            .map(|(_, resource)| resource.default_service(web::to(other_methods)))
+            .collect()
+    }
+
+    async fn collection_route(req: HttpRequest, settings: web::Data<Self>) -> HttpResponse {
+        let method = req.method().clone();
+        strip_head_body(&method, settings.get_ref().clone().fetch_collection(req).await)
+    }
+
+    async fn single_route(
+        param: web::Path<IdParam>, req: HttpRequest, settings: web::Data<Self>,
+    ) -> HttpResponse {
+        let method = req.method().clone();
+        let param = web::Path::from(param.into_inner().id);
+        strip_head_body(&method, settings.get_ref().clone().fetch_single(param, req).await)
+    }
+
+    async fn relationship_route(
+        param: web::Path<FieldParams>, req: HttpRequest, settings: web::Data<Self>,
+    ) -> HttpResponse {
+        let method = req.method().clone();
+        let FieldParams { id, related_field } = param.into_inner();
+        let param = web::Path::from((id, related_field));
+        strip_head_body(&method, settings.get_ref().clone().fetch_relationship(param, req).await)
+    }
+
+    async fn related_route(
+        param: web::Path<FieldParams>, req: HttpRequest, settings: web::Data<Self>,
+    ) -> HttpResponse {
+        let method = req.method().clone();
+        let FieldParams { id, related_field } = param.into_inner();
+        let param = web::Path::from((id, related_field));
+        strip_head_body(&method, settings.get_ref().clone().fetch_related(param, req).await)
+    }
+
+    pub async fn fetch_collection(self, req: HttpRequest) -> HttpResponse {
+        self.respond("fetch_collection", &req, self.do_fetch_collection(&req)).await
+    }
+
+    pub async fn fetch_single(self, param: web::Path<String>, req: HttpRequest) -> HttpResponse {
+        self.respond("fetch_single", &req, self.do_fetch_single(&param, &req)).await
     }
 
-    pub fn fetch_relationship(
+    pub async fn fetch_relationship(
         self, param: web::Path<(String, String)>, req: HttpRequest,
-    ) -> impl futures01::Future<Item = HttpResponse, Error = actix_web::Error> {
-        if let Err(err_resp) = check_header(&self.jsonapi.version, &req.headers()) {
-            return futures::future::ok(err_resp).boxed_local().compat();
-        }
-        match Query::from_uri(req.uri()) {
-            Ok(query) => {
-                let (id, related_field) = param.into_inner();
-                let fut = async move {
-                    match T::fetch_relationship(
-                        &id,
-                        &related_field,
-                        &self.uri.to_string(),
-                        &query,
-                        &req.uri().into(),
-                    )
-                    .await
-                    {
-                        Ok(item) => Ok(new_json_api_resp(StatusCode::OK).json(item)),
-                        Err(err) => Ok(error_to_response(err)),
-                    }
-                };
+    ) -> HttpResponse {
+        let (id, related_field) = param.into_inner();
+        self.respond(
+            "fetch_relationship",
+            &req,
+            self.do_fetch_relationship(&id, &related_field, &req),
+        )
+        .await
+    }
 
-                fut.boxed_local().compat()
-            },
-            Err(err) => futures::future::ok(error_to_response(err)).boxed_local().compat(),
+    pub async fn fetch_related(
+        self, param: web::Path<(String, String)>, req: HttpRequest,
+    ) -> HttpResponse {
+        let (id, related_field) = param.into_inner();
+        self.respond("fetch_related", &req, self.do_fetch_related(&id, &related_field, &req)).await
+    }
+
+    /// Runs a handler body in the request span unless the client is over the rate limit,
+    /// recording the metrics and rendering the result, where a panic of the handler is logged
+    /// and rendered as a `500` error without its message
+    async fn respond(
+        &self, operation: &'static str, req: &HttpRequest,
+        handler: impl Future<Output = RbhResult<HttpResponse>>,
+    ) -> HttpResponse {
+        let request_id = request_id(req);
+        let span = request_span(operation, &T::Item::ty(), &request_id);
+        async {
+            let start = Instant::now();
+            let principal = auth::principal_of(self.authenticator.as_deref(), req.headers())
+                .instrument(info_span!("authenticate"))
+                .await;
+            let client = principal.as_ref().ok().and_then(Option::as_ref);
+            let rate_limit = self.rate_limiter.as_ref().map(|limiter| limiter.check(req, client));
+            let result = match rate_limit.and_then(|rate_limit| rate_limit.retry_after) {
+                Some(retry_after) => Err(error::Error::TooManyRequests(retry_after, None)),
+                None => self.authenticated(operation, req, principal, handler).await,
+            };
+            #[cfg(feature = "metrics")]
+            if let Some(metrics) = &self.metrics {
+                metrics.observe(&T::Item::ty(), operation, &result, start.elapsed());
+            }
+            let locale = self.localization.as_ref().map(|localization| {
+                let accept_language =
+                    handler::header_value(&req.headers().into(), header::ACCEPT_LANGUAGE);
+                localization.negotiate(accept_language.as_deref())
+            });
+            let result = result.map_err(|mut err| {
+                if let (Some(localization), Some(locale)) = (&self.localization, locale) {
+                    localization.translate(&mut err, locale);
+                }
+                err
+            });
+            let mut resp =
+                finish_response(req, &request_id, &self.jsonapi, self.error_hook.as_ref(), result);
+            if let Some(locale) = locale.and_then(|locale| HeaderValue::from_str(locale).ok()) {
+                resp.headers_mut().insert(header::CONTENT_LANGUAGE, locale);
+                resp.headers_mut()
+                    .insert(header::VARY, HeaderValue::from_static("Accept-Language"));
+            }
+            for (name, value) in rate_limit.iter().flat_map(RateLimit::headers) {
+                resp.headers_mut().insert(name, value);
+            }
+            if resp.status() == StatusCode::UNAUTHORIZED && self.authenticator.is_some() {
+                resp.headers_mut()
+                    .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static("Bearer"));
+            }
+            let mut headers = http::HeaderMap::from(resp.headers());
+            handler::apply_deprecation(T::Item::deprecation().as_ref(), &mut headers);
+            self.jsonapi.apply_cache_headers(&T::Item::ty(), resp.status(), &mut headers);
+            *resp.headers_mut() = headers.into();
+            let headers = req.headers().iter().filter_map(|(name, value)| {
+                value.to_str().ok().map(|value| (name.as_str(), value))
+            });
+            let status = resp.status().as_u16();
+            self.jsonapi.log_access(
+                req.method().as_str(),
+                req.uri(),
+                status,
+                start.elapsed(),
+                headers,
+            );
+            resp
         }
+        .instrument(span)
+        .await
     }
 
-    pub fn fetch_related(
-        self, param: web::Path<(String, String)>, req: HttpRequest,
-    ) -> impl futures01::Future<Item = HttpResponse, Error = actix_web::Error> {
-        if let Err(err_resp) = check_header(&self.jsonapi.version, &req.headers()) {
-            return futures::future::ok(err_resp).boxed_local().compat();
+    /// Runs `handler` after `authorize`, where a panic of it is logged and rendered as a `500`
+    /// error without its message
+    async fn authenticated(
+        &self, operation: &str, req: &HttpRequest, principal: RbhResult<Option<Principal>>,
+        handler: impl Future<Output = RbhResult<HttpResponse>>,
+    ) -> RbhResult<HttpResponse> {
+        self.authorize(operation, req, principal?)?;
+        AssertUnwindSafe(handler).catch_unwind().await.unwrap_or_else(|panic| {
+            let message = panic
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| panic.downcast_ref::<String>().map(String::as_str));
+            tracing::error!(panic = message, "handler panicked");
+            Err(error::Error::InternalServerError(None))
+        })
+    }
+
+    async fn do_fetch_collection(&self, req: &HttpRequest) -> RbhResult<HttpResponse> {
+        let op_req = self.operation_request(req)?;
+        let doc = self
+            .cached(&handler::Route::Collection, &op_req, async {
+                let vec = handler::fetch_collection(&*self.service, &op_req)
+                    .instrument(info_span!("operation"))
+                    .await?;
+                #[cfg(feature = "metrics")]
+                if let Some(metrics) = &self.metrics {
+                    metrics.observe_collection_size(&T::Item::ty(), vec.len());
+                }
+                let OperationRequest { context, query, base, request_path } = &op_req;
+                let doc = self.service.vec_to_document(&vec, base, query, request_path).await?;
+                let mut doc = self.to_json(&doc, base)?;
+                authorize_included(&*self.service, context, &Action::FetchCollection, &mut doc)
+                    .await;
+                Ok(doc)
+            })
+            .await?;
+        self.checked_response(req, &op_req.query, doc, None, None)
+    }
+
+    async fn do_fetch_single(&self, id: &str, req: &HttpRequest) -> RbhResult<HttpResponse> {
+        let op_req = self.operation_request(req)?;
+        // The cached documents are validated by their `Last-Modified` only, as the version of the
+        // resource is not kept with them
+        let key = CacheKey::new(&T::Item::ty(), &handler::Route::Single(id.to_string()), &op_req);
+        if let Some(cache) = &self.cache {
+            if let Some(doc) = cache.get(&key).await {
+                let last_modified = handler::last_modified_of(&doc);
+                return self.checked_response(req, &op_req.query, doc, None, last_modified);
+            }
+        }
+        let item = handler::fetch_single(&*self.service, id, &op_req)
+            .instrument(info_span!("operation"))
+            .await?;
+        let OperationRequest { context, query, base, request_path } = &op_req;
+        let headers = &context.headers;
+        // The versioned resources skip the rendering if the client has the latest one in the same
+        // encoding
+        let encoding = Encoding::negotiate(&handler::header_value(headers, header::ACCEPT));
+        let etag = item.version().map(|version| {
+            let content =
+                format!("{}\n{}\n{}\n{}", base, req.uri(), encoding.media_type(), version);
+            handler::etag_of(content.as_bytes())
+        });
+        let last_modified = item.last_modified();
+        if handler::is_fresh(headers, etag.as_deref(), last_modified) {
+            let resp = handler::not_modified(etag.as_deref(), last_modified);
+            return Ok(from_handler_response(resp));
         }
+        let doc = item.to_document_automatically(base, query, request_path)?;
+        let mut doc = self.to_json(&doc, base)?;
+        authorize_included(&*self.service, context, &Action::FetchSingle, &mut doc).await;
+        if let Some(cache) = &self.cache {
+            cache.put(key, doc.clone()).await;
+        }
+        self.checked_response(req, query, doc, etag, last_modified)
+    }
+
+    async fn do_fetch_relationship(
+        &self, id: &str, related_field: &str, req: &HttpRequest,
+    ) -> RbhResult<HttpResponse> {
+        let op_req = self.operation_request(req)?;
+        let route = handler::Route::Relationship(id.to_string(), related_field.to_string());
+        let doc = self
+            .cached(&route, &op_req, async {
+                let relationship =
+                    handler::fetch_relationship(&*self.service, id, related_field, &op_req)
+                        .instrument(info_span!("operation"))
+                        .await?;
+                self.to_json(&relationship, &op_req.base)
+            })
+            .await?;
+        json_response(req, &self.jsonapi, self.jsonapi.namespaced(doc), None, None)
+    }
 
-        match Query::from_uri(req.uri()) {
-            Ok(query) => {
-                let (id, related_field) = param.into_inner();
-                let fut = async move {
-                    match T::fetch_related(
-                        &id,
-                        &related_field,
-                        &self.uri.to_string(),
-                        &query,
-                        &req.uri().into(),
-                    )
-                    .await
-                    {
-                        Ok(item) => Ok(new_json_api_resp(StatusCode::OK).json(item)),
-                        Err(err) => Ok(error_to_response(err)),
+    async fn do_fetch_related(
+        &self, id: &str, related_field: &str, req: &HttpRequest,
+    ) -> RbhResult<HttpResponse> {
+        let op_req = self.operation_request(req)?;
+        let route = handler::Route::Related(id.to_string(), related_field.to_string());
+        let doc = self
+            .cached(&route, &op_req, async {
+                let related = handler::fetch_related(&*self.service, id, related_field, &op_req)
+                    .instrument(info_span!("operation"))
+                    .await?;
+                self.to_json(&related, &op_req.base)
+            })
+            .await?;
+        self.checked_response(req, &op_req.query, doc, None, None)
+    }
+
+    /// The document of `route` in the cache if any, or the one of `fetch`, which is cached if it
+    /// succeeds
+    async fn cached(
+        &self, route: &handler::Route, op_req: &OperationRequest,
+        fetch: impl Future<Output = RbhResult<serde_json::Value>>,
+    ) -> RbhResult<serde_json::Value> {
+        let key = CacheKey::new(&T::Item::ty(), route, op_req);
+        handler::cached(self.cache.as_deref(), key, fetch).await
+    }
+
+    /// The response of `json_response` after the document is checked by `handler::check_document`,
+    /// with the included resources sorted by the query
+    fn checked_response(
+        &self, req: &HttpRequest, query: &Query, mut doc: serde_json::Value, etag: Option<String>,
+        last_modified: Option<SystemTime>,
+    ) -> RbhResult<HttpResponse> {
+        handler::check_document(&self.jsonapi, &doc, query)?;
+        query.sort_included(&mut doc);
+        json_response(req, &self.jsonapi, self.jsonapi.namespaced(doc), etag, last_modified)
+    }
+
+    /// Checks the request by `handler::check_request`, and collects what the operations need from
+    /// it
+    fn operation_request(&self, req: &HttpRequest) -> RbhResult<OperationRequest> {
+        let mut context = operation_context(req);
+        let query = handler::check_request(
+            &self.jsonapi,
+            &handler::header_value(&context.headers, header::CONTENT_TYPE),
+            &handler::header_value(&context.headers, header::ACCEPT),
+            req.uri(),
+        )?;
+        let (base, request_path) = self.link_base(req);
+        let (tenant, base) = self.jsonapi.tenant_of(&base, req.uri(), &context.headers)?;
+        context.tenant = tenant;
+        Ok(OperationRequest { context, query, base: base.to_string(), request_path })
+    }
+
+    /// Puts the `Principal` of the verified credentials into the request extensions for the
+    /// `OperationContext`, failing if the credentials are missing when `operation` is protected,
+    /// or lacking the roles needed by `operation`
+    fn authorize(
+        &self, operation: &str, req: &HttpRequest, principal: Option<Principal>,
+    ) -> RbhResult<()> {
+        let roles = self.guards.roles(operation);
+        match principal {
+            Some(principal) => {
+                if let Some(roles) = roles {
+                    if !roles.iter().any(|role| principal.scopes.contains(role)) {
+                        let ty = T::Item::ty();
+                        return Err(error::Error::OperationForbidden(operation, &ty, roles, None));
                     }
-                };
-                fut.boxed_local().compat()
+                }
+                req.extensions_mut().insert(principal);
+                Ok(())
+            },
+            None if roles.is_some() || (self.protected && !self.guards.is_public(operation)) => {
+                Err(error::Error::Unauthenticated("no credentials are given", None))
             },
-            Err(err) => futures::future::ok(error_to_response(err)).boxed_local().compat(),
+            None => Ok(()),
         }
     }
+
+    /// The path of the resource type, which is `/<ty>` or the one in `paths`
+    fn type_path(&self) -> String {
+        let ty = T::Item::ty();
+        self.paths.get(&ty).cloned().unwrap_or_else(|| format!("/{}", ty))
+    }
+
+    /// The base of the links and the canonical request path seen by the clients, which are
+    /// different from the ones of the server behind a reverse proxy
+    fn link_base(&self, req: &HttpRequest) -> (url::Url, RawUri) {
+        let type_path = self.type_path();
+        let matching = self.jsonapi.route_matching;
+        let req_uri =
+            matching.canonical_uri(&self.path, req.uri(), type_path.trim_start_matches('/'));
+        let mut uri = self.uri.clone();
+        let mut prefix = self.prefix.as_str();
+        if self.trust_forwarded_headers {
+            let headers = req.headers();
+            if let Some(proto) = forwarded_header(headers, "x-forwarded-proto") {
+                let _ = uri.set_scheme(proto);
+            }
+            if let Some(host) = forwarded_header(headers, "x-forwarded-host") {
+                if let Ok(host) = format!("{}://{}", uri.scheme(), host).parse::<url::Url>() {
+                    let _ = uri.set_host(host.host_str());
+                    let _ = uri.set_port(host.port());
+                }
+            }
+            if let Some(forwarded_prefix) = forwarded_header(headers, "x-forwarded-prefix") {
+                prefix = forwarded_prefix.trim_end_matches('/');
+            }
+            uri.set_path(&format!("{}{}", prefix, self.path));
+        }
+        let request_path = if prefix.is_empty() {
+            req_uri.into()
+        } else {
+            format!("{}{}", prefix, req_uri).parse().unwrap_or_else(|_| req_uri.into())
+        };
+        (uri, request_path)
+    }
+
+    /// The JSON of `doc`, where the links of the resource types in `paths` are rewritten from
+    /// `<uri>/<ty>` to `<uri><path>`
+    fn to_json(&self, doc: &impl Serialize, uri: &str) -> RbhResult<serde_json::Value> {
+        let mut doc = handler::to_json(doc)?;
+        if !self.paths.is_empty() {
+            let paths: Vec<(String, String)> = self
+                .paths
+                .iter()
+                .map(|(ty, path)| (format!("{}/{}", uri, ty), format!("{}{}", uri, path)))
+                .collect();
+            rewrite_links(&mut doc, &paths, false);
+        }
+        Ok(doc)
+    }
+}
+
+/// The pattern of the scope of the type path `path`, whose type segment is matched ignoring the
+/// case with `case_insensitive_types` of `matching`, unless the path has many segments
+fn type_pattern(path: &str, matching: RouteMatching) -> String {
+    let segment = path.trim_start_matches('/');
+    if !matching.case_insensitive_types || segment.contains('/') {
+        return path.to_string();
+    }
+    let escaped: String = segment
+        .chars()
+        .flat_map(|c| match c.is_alphanumeric() || c == '_' || c == '-' {
+            true => vec![c],
+            false => vec!['\\', c],
+        })
+        .collect();
+    format!("/{{{}:(?i){}}}", TYPE_SEGMENT, escaped)
 }
 
-// TODO: If this check should be put into the main logic rather than web-framework specific?
-fn check_header(api_version: &JsonApiVersion, headers: &HeaderMap) -> Result<(), HttpResponse> {
-    let content_type = headers.get(header::CONTENT_TYPE).map(|r| r.to_str().unwrap().to_string());
-    let accept = headers.get(header::ACCEPT).map(|r| r.to_str().unwrap().to_string());
-    RuleDispatcher::ContentTypeMustBeJsonApi(api_version, &content_type)
-        .map_err(error_to_response)?;
-    RuleDispatcher::AcceptHeaderShouldBeJsonApi(api_version, &accept).map_err(error_to_response)?;
+/// The path parameters of the routes, taken by their names, as the type segment is a parameter
+/// of the scope with the tolerances of `route_matching`
+#[derive(Deserialize)]
+struct IdParam {
+    id: String,
+}
 
-    Ok(())
+#[derive(Deserialize)]
+struct FieldParams {
+    id: String,
+    related_field: String,
 }
 
-fn new_json_api_resp(status_code: StatusCode) -> HttpResponseBuilder {
-    let mut resp = HttpResponse::build(status_code);
-    resp.set_header(header::CONTENT_TYPE, JSON_API_HEADER);
-    resp
+fn get_or_head() -> Route { web::route().guard(guard::Any(guard::Get()).or(guard::Head())) }
+
+/// `OPTIONS`, and the methods of the operations which are not implemented, like `POST` and
+/// `DELETE`
+async fn other_methods(req: HttpRequest) -> HttpResponse {
+    from_handler_response(handler::other_method_response(req.method()))
+}
+
+fn strip_head_body(method: &Method, resp: HttpResponse) -> HttpResponse {
+    if method == Method::HEAD {
+        resp.drop_body().map_into_boxed_body()
+    } else {
+        resp
+    }
+}
+
+/// The first value of a `X-Forwarded-*` header
+fn forwarded_header<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    let value = headers.get(name)?.to_str().ok()?;
+    Some(value.split(',').next()?.trim()).filter(|value| !value.is_empty())
+}
+
+/// Replaces the prefixes of the strings in the `links` of `value` by `paths`, where only the
+/// whole path segments are matched
+fn rewrite_links(value: &mut serde_json::Value, paths: &[(String, String)], in_links: bool) {
+    match value {
+        serde_json::Value::String(link) if in_links => {
+            let rewritten = paths.iter().find_map(|(from, to)| {
+                let rest = link.strip_prefix(from.as_str())?;
+                (rest.is_empty() || rest.starts_with(&['/', '?'][..]))
+                    .then(|| format!("{}{}", to, rest))
+            });
+            if let Some(rewritten) = rewritten {
+                *link = rewritten;
+            }
+        },
+        serde_json::Value::Array(values) => {
+            values.iter_mut().for_each(|value| rewrite_links(value, paths, in_links))
+        },
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                rewrite_links(value, paths, in_links || key == "links");
+            }
+        },
+        _ => {},
+    }
+}
+
+/// The response of `handler::conditional_response`, which is `304 Not Modified` if the client has
+/// the same `ETag` or the resource is not modified since its `If-Modified-Since`
+fn json_response(
+    req: &HttpRequest, settings: &JsonApiSettings, doc: serde_json::Value, etag: Option<String>,
+    last_modified: Option<SystemTime>,
+) -> RbhResult<HttpResponse> {
+    let headers = http::HeaderMap::from(req.headers());
+    // The documents are full of `HashMap`s, so the keys are sorted by `serde_json::Value` first
+    // to keep the same document in the same body
+    handler::conditional_response(settings, &headers, &doc, etag, last_modified)
+        .map(from_handler_response)
 }
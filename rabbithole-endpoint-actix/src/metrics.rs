@@ -0,0 +1,102 @@
+use actix_web::{web, HttpResponse, Resource};
+use prometheus::{
+    Encoder, HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry, TextEncoder,
+};
+use rabbithole::RbhResult;
+use std::time::Duration;
+
+/// Prometheus metrics of the JSON:API endpoints, where all of the clones share the same
+/// collectors, so a single `Metrics` can be given to all of the `ActixSettings`.
+/// The metrics are labeled by the resource type and the operation:
+///   - `rabbithole_requests_total`: requests by the response status
+///   - `rabbithole_errors_total`: failed requests by the error code
+///   - `rabbithole_request_duration_seconds`: latency of the requests
+///   - `rabbithole_collection_size`: resource count of the collection responses
+#[derive(Clone)]
+pub struct Metrics {
+    registry: Registry,
+    requests: IntCounterVec,
+    errors: IntCounterVec,
+    latency: HistogramVec,
+    collection_size: HistogramVec,
+}
+
+impl Metrics {
+    /// Metrics in a new registry, which can be exposed by `Metrics::resource`
+    pub fn new() -> Self {
+        Self::with_registry(Registry::new()).expect("the collectors are registered only once")
+    }
+
+    /// Metrics in a user-provided registry, failing if the collectors are registered already
+    pub fn with_registry(registry: Registry) -> prometheus::Result<Self> {
+        let requests = IntCounterVec::new(
+            Opts::new("rabbithole_requests_total", "JSON:API requests by the response status"),
+            &["ty", "operation", "status"],
+        )?;
+        let errors = IntCounterVec::new(
+            Opts::new("rabbithole_errors_total", "Failed JSON:API requests by the error code"),
+            &["ty", "operation", "code"],
+        )?;
+        let latency = HistogramVec::new(
+            HistogramOpts::new(
+                "rabbithole_request_duration_seconds",
+                "Latency of the JSON:API requests",
+            ),
+            &["ty", "operation"],
+        )?;
+        let collection_size = HistogramVec::new(
+            HistogramOpts::new(
+                "rabbithole_collection_size",
+                "Resource count of the collection responses",
+            )
+            .buckets(vec![0.0, 1.0, 5.0, 10.0, 20.0, 50.0, 100.0, 500.0]),
+            &["ty"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(errors.clone()))?;
+        registry.register(Box::new(latency.clone()))?;
+        registry.register(Box::new(collection_size.clone()))?;
+        Ok(Self { registry, requests, errors, latency, collection_size })
+    }
+
+    pub fn registry(&self) -> &Registry { &self.registry }
+
+    /// `GET /metrics` in the Prometheus text format
+    pub fn resource(&self) -> Resource {
+        let registry = self.registry.clone();
+        web::resource("/metrics").route(web::get().to(move || {
+            let registry = registry.clone();
+            async move {
+                let mut buffer = Vec::new();
+                let encoder = TextEncoder::new();
+                match encoder.encode(&registry.gather(), &mut buffer) {
+                    Ok(_) => HttpResponse::Ok().content_type(encoder.format_type()).body(buffer),
+                    Err(err) => HttpResponse::InternalServerError().body(err.to_string()),
+                }
+            }
+        }))
+    }
+
+    pub(crate) fn observe(
+        &self, ty: &str, operation: &str, result: &RbhResult<HttpResponse>, elapsed: Duration,
+    ) {
+        let status = match result {
+            Ok(resp) => resp.status().as_str().to_string(),
+            Err(err) => err.status.clone().unwrap_or_default(),
+        };
+        self.requests.with_label_values(&[ty, operation, &status]).inc();
+        if let Err(err) = result {
+            let code = err.code.as_deref().unwrap_or_default();
+            self.errors.with_label_values(&[ty, operation, code]).inc();
+        }
+        self.latency.with_label_values(&[ty, operation]).observe(elapsed.as_secs_f64());
+    }
+
+    pub(crate) fn observe_collection_size(&self, ty: &str, size: usize) {
+        self.collection_size.with_label_values(&[ty]).observe(size as f64);
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self { Self::new() }
+}
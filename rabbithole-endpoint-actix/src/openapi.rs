@@ -0,0 +1,50 @@
+use rabbithole::openapi::{generate, path_of, OpenApiSettings};
+use rabbithole::schema::Registry;
+use serde_json::{json, Value};
+
+use crate::auth::API_KEY_HEADER;
+use crate::settings::ActixSettingsModel;
+use crate::OPERATIONS;
+
+pub use rabbithole::openapi::OpenApiInfo;
+
+/// The OpenAPI 3 document of the resource types in `registry` mounted with `settings`, without
+/// the disabled operations, where `protected` are the ones mounted as protected, and the security
+/// schemes are listed if `authenticated`
+pub(crate) fn document(
+    info: &OpenApiInfo, uri: &url::Url, settings: &ActixSettingsModel, registry: &Registry,
+    protected: &[String], authenticated: bool,
+) -> Value {
+    let mut openapi_settings = OpenApiSettings::new(info.clone(), uri.as_str());
+    for resource in registry.resources() {
+        openapi_settings.paths.insert(resource.ty.clone(), settings.resource_path(&resource.ty));
+    }
+    let mut doc = generate(registry, &openapi_settings);
+    if let Value::Object(paths) = &mut doc["paths"] {
+        for resource in registry.resources() {
+            let ty = &resource.ty;
+            let guards = settings.resources.get(ty).cloned().unwrap_or_default();
+            let is_protected = protected.contains(ty);
+            for operation in OPERATIONS.iter() {
+                let path = path_of(&settings.resource_path(ty), operation);
+                if guards.is_disabled(operation) {
+                    paths.remove(&path);
+                    continue;
+                }
+                let needs_credentials = guards.roles(operation).is_some()
+                    || (is_protected && !guards.is_public(operation));
+                if authenticated && needs_credentials {
+                    paths[&path]["get"]["security"] = json!([{ "bearer": [] }, { "apiKey": [] }]);
+                }
+            }
+        }
+    }
+
+    if authenticated {
+        doc["components"]["securitySchemes"] = json!({
+            "bearer": { "type": "http", "scheme": "bearer" },
+            "apiKey": { "type": "apiKey", "in": "header", "name": API_KEY_HEADER },
+        });
+    }
+    doc
+}
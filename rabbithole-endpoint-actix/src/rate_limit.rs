@@ -0,0 +1,123 @@
+use actix_web::http::header::{self, HeaderName, HeaderValue};
+use actix_web::HttpRequest;
+use rabbithole::operation::Principal;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use crate::settings::RateLimitSettings;
+
+/// The buckets are swept when there are more clients than this
+const SWEEP_THRESHOLD: usize = 10_000;
+
+pub const LIMIT_HEADER: &str = "ratelimit-limit";
+pub const REMAINING_HEADER: &str = "ratelimit-remaining";
+pub const RESET_HEADER: &str = "ratelimit-reset";
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+/// The usage of the rate limit by a client
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimit {
+    /// The requests allowed in a burst
+    pub limit: u32,
+    /// The requests left before the client is limited
+    pub remaining: u32,
+    /// The seconds until all of the requests of `limit` are available again
+    pub reset: u64,
+    /// The seconds to wait if the request is limited
+    #[serde(skip)]
+    pub retry_after: Option<u64>,
+}
+
+impl RateLimit {
+    /// The `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` headers, with
+    /// `Retry-After` if the request is limited
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = vec![
+            (HeaderName::from_static(LIMIT_HEADER), self.limit.into()),
+            (HeaderName::from_static(REMAINING_HEADER), self.remaining.into()),
+            (HeaderName::from_static(RESET_HEADER), self.reset.into()),
+        ];
+        if let Some(retry_after) = self.retry_after {
+            headers.push((header::RETRY_AFTER, retry_after.into()));
+        }
+        headers
+    }
+}
+
+/// Token buckets keyed by the clients, where all of the clones share the same buckets
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    settings: RateLimitSettings,
+    trust_forwarded_headers: bool,
+    buckets: Arc<Mutex<HashMap<String, Bucket>>>,
+}
+
+impl RateLimiter {
+    pub fn new(settings: RateLimitSettings, trust_forwarded_headers: bool) -> Self {
+        Self { settings, trust_forwarded_headers, buckets: Default::default() }
+    }
+
+    /// Takes a token of the client of `req`, which is `principal` if the credentials are verified,
+    /// where `retry_after` of the usage is the seconds to wait if there is none
+    pub fn check(&self, req: &HttpRequest, principal: Option<&Principal>) -> RateLimit {
+        self.update(req, principal, true)
+    }
+
+    /// The usage of the client of `req` without taking a token
+    pub fn usage(&self, req: &HttpRequest, principal: Option<&Principal>) -> RateLimit {
+        self.update(req, principal, false)
+    }
+
+    fn update(&self, req: &HttpRequest, principal: Option<&Principal>, take: bool) -> RateLimit {
+        let key = self.client_key(req, principal);
+        let now = Instant::now();
+        let burst = f64::from(self.settings.burst);
+        let mut buckets = self.buckets.lock().unwrap_or_else(|err| err.into_inner());
+        if buckets.len() >= SWEEP_THRESHOLD {
+            let rate = self.settings.rate;
+            buckets.retain(|_, bucket| {
+                bucket.tokens + now.duration_since(bucket.updated_at).as_secs_f64() * rate < burst
+            });
+        }
+        let bucket = buckets.entry(key).or_insert(Bucket { tokens: burst, updated_at: now });
+        let elapsed = now.duration_since(bucket.updated_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.settings.rate).min(burst);
+        bucket.updated_at = now;
+        let retry_after = if !take {
+            None
+        } else if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            None
+        } else {
+            Some(((1.0 - bucket.tokens) / self.settings.rate).ceil() as u64)
+        };
+        RateLimit {
+            limit: self.settings.burst,
+            remaining: bucket.tokens.floor() as u32,
+            reset: ((burst - bucket.tokens) / self.settings.rate).ceil() as u64,
+            retry_after,
+        }
+    }
+
+    /// The authenticated principal of the client if any, or its IP address, as the unverified
+    /// credentials could be made up for each request to get a new bucket
+    fn client_key(&self, req: &HttpRequest, principal: Option<&Principal>) -> String {
+        if let Some(principal) = principal {
+            return format!("principal:{}", principal.id);
+        }
+        let ip = if self.trust_forwarded_headers {
+            req.connection_info().realip_remote_addr().map(ToString::to_string)
+        } else {
+            req.peer_addr().map(|addr| addr.ip().to_string())
+        };
+        format!("ip:{}", ip.unwrap_or_default())
+    }
+}
@@ -1,5 +1,14 @@
-use rabbithole::model::version::JsonApiVersion;
+use config::{Config, ConfigError, Environment, File};
 use serde::Deserialize;
+use std::collections::HashMap;
+
+pub use rabbithole::handler::JsonApiSettings;
+
+use crate::OPERATIONS;
+
+/// The prefix of the environment variables overriding the settings, where `__` separates the
+/// nested keys, like `RBH__PORT` and `RBH__JSONAPI__VERSION`
+pub const ENV_PREFIX: &str = "RBH__";
 
 #[derive(Debug, Deserialize, Clone)]
 pub struct ActixSettingsModel {
@@ -7,9 +16,193 @@ pub struct ActixSettingsModel {
     pub port: u32,
     pub path: String,
     pub jsonapi: JsonApiSettings,
+    /// The URL where the server root is exposed to the clients, like `https://example.com/prefix`,
+    /// used in the links instead of `http://host:port`
+    #[serde(default)]
+    pub external_url: Option<String>,
+    /// If the `X-Forwarded-Proto/Host/Prefix` headers are used to build the links,
+    /// which should be enabled only behind a trusted reverse proxy
+    #[serde(default)]
+    pub trust_forwarded_headers: bool,
+    /// Limits the requests of each client per resource type, disabled if not given
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitSettings>,
+    /// The guards of the operations by the resource types, like `[resources.people]`
+    #[serde(default)]
+    pub resources: HashMap<String, ResourceGuards>,
+    /// The API versions by their names, like `[versions.v2]`, mounted by `JsonApiApp::version`
+    #[serde(default)]
+    pub versions: HashMap<String, ApiVersionSettings>,
+    /// The paths of the resource types in the routes and the links instead of `/<ty>`,
+    /// like `people = "/admin/humans"` in `[paths]`
+    #[serde(default)]
+    pub paths: HashMap<String, String>,
+}
+
+/// A token bucket for each client, refilled with `rate` tokens per second up to `burst` tokens,
+/// where each request takes a token. The clients are identified by the principals of the
+/// credentials verified by the `Authenticator`, or by their IP addresses otherwise
+#[derive(Debug, Deserialize, Clone)]
+pub struct RateLimitSettings {
+    pub rate: f64,
+    pub burst: u32,
 }
 
+/// An API version, mounted at `path` after the `path` of `ActixSettingsModel`, like `/api` + `/v2`:
+/// ```toml
+/// [versions.v2]
+/// path = "/v2"
+/// resources = ["people", "dogs"]
+/// ```
 #[derive(Debug, Deserialize, Clone)]
-pub struct JsonApiSettings {
-    pub version: JsonApiVersion,
+pub struct ApiVersionSettings {
+    pub path: String,
+    /// The resource types of the version, where the others are not mounted; all of the types are
+    /// mounted if not given
+    #[serde(default)]
+    pub resources: Option<Vec<String>>,
+}
+
+/// Which operations of a resource type are public, need roles or are disabled, where the operations
+/// are named as `OPERATIONS`:
+/// ```toml
+/// [resources.people]
+/// public = ["fetch_collection", "fetch_single"]
+/// disabled = ["fetch_related"]
+/// roles = { fetch_relationship = ["admin"] }
+/// ```
+/// NOTICE:
+///   - The public operations skip the protection of `ActixSettings::with_authenticator`
+///   - The roles are matched against `Principal::scopes`, where any of them is enough
+///   - The disabled operations are not registered, answering `404 Not Found`
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ResourceGuards {
+    #[serde(default)]
+    pub public: Vec<String>,
+    #[serde(default)]
+    pub roles: HashMap<String, Vec<String>>,
+    #[serde(default)]
+    pub disabled: Vec<String>,
+}
+
+impl ResourceGuards {
+    pub fn is_public(&self, operation: &str) -> bool {
+        self.public.iter().any(|op| op == operation)
+    }
+
+    pub fn is_disabled(&self, operation: &str) -> bool {
+        self.disabled.iter().any(|op| op == operation)
+    }
+
+    /// The roles needed by `operation`, if any
+    pub fn roles(&self, operation: &str) -> Option<&[String]> {
+        self.roles.get(operation).map(Vec::as_slice)
+    }
+}
+
+impl ActixSettingsModel {
+    /// Loads the settings from the layered sources, where the latter ones override the former:
+    ///   1. The config file `file` (with any extension supported by `config`), if given
+    ///   2. The environment variables with `ENV_PREFIX`
+    ///   3. The `overrides` like `("jsonapi.version", "1.1")`
+    pub fn load(file: Option<&str>, overrides: &[(&str, &str)]) -> Result<Self, ConfigError> {
+        let mut settings = Config::default();
+        if let Some(file) = file {
+            settings.merge(File::with_name(file))?;
+        }
+        // `config` appends a `_` to the prefix
+        settings.merge(
+            Environment::with_prefix(&ENV_PREFIX[.. ENV_PREFIX.len() - 1]).separator("__"),
+        )?;
+        for (key, value) in overrides {
+            settings.set(key, *value)?;
+        }
+        let settings: Self = settings.try_into()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// The path of the resource type `ty`, which is `/<ty>` if it's not in `paths`
+    pub fn resource_path(&self, ty: &str) -> String {
+        self.paths.get(ty).cloned().unwrap_or_else(|| format!("/{}", ty))
+    }
+
+    /// Checks the values which are well-typed but cannot be used
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.host.is_empty() {
+            return Err(invalid_value("host", "it must not be empty"));
+        }
+        if self.port > u32::from(u16::MAX) {
+            return Err(invalid_value("port", &format!("{} is not a valid port", self.port)));
+        }
+        if !self.path.is_empty() && !self.path.starts_with('/') {
+            return Err(invalid_value("path", &format!("`{}` must start with `/`", self.path)));
+        }
+        if let Some(external_url) = &self.external_url {
+            if let Err(err) = external_url.parse::<url::Url>() {
+                return Err(invalid_value("external_url", &format!("`{}`: {}", external_url, err)));
+            }
+        }
+        if let Some(rate_limit) = &self.rate_limit {
+            if !(rate_limit.rate > 0.0 && rate_limit.rate.is_finite()) {
+                return Err(invalid_value("rate_limit.rate", "it must be a positive number"));
+            }
+            if rate_limit.burst == 0 {
+                return Err(invalid_value("rate_limit.burst", "it must not be zero"));
+            }
+        }
+        for (name, version) in &self.versions {
+            if !version.path.starts_with('/') {
+                return Err(invalid_value(
+                    &format!("versions.{}.path", name),
+                    &format!("`{}` must start with `/`", version.path),
+                ));
+            }
+            let duplicated = self.versions.iter().find(|(other_name, other)| {
+                other_name.as_str() != name.as_str() && other.path == version.path
+            });
+            if let Some((other_name, _)) = duplicated {
+                return Err(invalid_value(
+                    &format!("versions.{}.path", name),
+                    &format!("`{}` is used by `{}` already", version.path, other_name),
+                ));
+            }
+        }
+        for (ty, path) in &self.paths {
+            if !path.starts_with('/') || path.ends_with('/') {
+                return Err(invalid_value(
+                    &format!("paths.{}", ty),
+                    &format!("`{}` must start with `/` and must not end with `/`", path),
+                ));
+            }
+            let duplicated = self
+                .paths
+                .iter()
+                .find(|(other_ty, other)| other_ty.as_str() != ty.as_str() && *other == path);
+            if let Some((other_ty, _)) = duplicated {
+                return Err(invalid_value(
+                    &format!("paths.{}", ty),
+                    &format!("`{}` is used by `{}` already", path, other_ty),
+                ));
+            }
+        }
+        for (ty, guards) in &self.resources {
+            let operations = guards.public.iter().map(|op| ("public", op));
+            let operations = operations.chain(guards.roles.keys().map(|op| ("roles", op)));
+            let operations = operations.chain(guards.disabled.iter().map(|op| ("disabled", op)));
+            for (key, operation) in operations {
+                if !OPERATIONS.contains(&operation.as_str()) {
+                    return Err(invalid_value(
+                        &format!("resources.{}.{}", ty, key),
+                        &format!("`{}` is not one of {:?}", operation, OPERATIONS),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid_value(key: &str, reason: &str) -> ConfigError {
+    ConfigError::Message(format!("invalid value for key `{}`: {}", key, reason))
 }
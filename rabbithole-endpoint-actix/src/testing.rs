@@ -0,0 +1,78 @@
+use actix_web::body::BoxBody;
+use actix_web::dev::{Service, ServiceResponse};
+use actix_web::http::{header, StatusCode};
+use actix_web::test::{self, TestRequest};
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::document::Document;
+use rabbithole::JSON_API_HEADER;
+
+use crate::app::JsonApiApp;
+
+/// An in-memory app of a `JsonApiApp` for the integration tests:
+/// ```ignore
+/// let api = JsonApiApp::new(settings)?.resource::<HumanService>();
+/// let app = testing::init(&api).await;
+/// let (status, doc) = app.get_collection::<Human>("?sort=-name").await;
+/// ```
+pub struct TestApp<S> {
+    service: S,
+    path: String,
+}
+
+/// Spins up the in-memory app of `api`
+pub async fn init(
+    api: &JsonApiApp,
+) -> TestApp<
+    impl Service<actix_http::Request, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>,
+> {
+    let service = test::init_service(actix_web::App::new().service(api.scope())).await;
+    TestApp { service, path: api.path().to_string() }
+}
+
+impl<S> TestApp<S>
+where
+    S: Service<actix_http::Request, Response = ServiceResponse<BoxBody>, Error = actix_web::Error>,
+{
+    /// A `GET` request of `uri`, which is relative to the `path` of the app,
+    /// with the JSON:API `Content-Type` and `Accept` headers
+    pub fn request(&self, uri: &str) -> TestRequest {
+        TestRequest::get()
+            .uri(&format!("{}/{}", self.path, uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+    }
+
+    pub async fn call(&self, req: TestRequest) -> ServiceResponse<BoxBody> {
+        test::call_service(&self.service, req.to_request()).await
+    }
+
+    /// Calls `req`, parsing the body into a `Document`
+    /// NOTICE:
+    ///   - Panics if the body is not a `Document`, like the one of a `304 Not Modified`
+    pub async fn document(&self, req: TestRequest) -> (StatusCode, Document) {
+        let resp = self.call(req).await;
+        let status = resp.status();
+        let body = test::read_body(resp).await;
+        let doc = serde_json::from_slice(&body).unwrap_or_else(|err| {
+            panic!("The body is not a document: {}, {}", err, String::from_utf8_lossy(&body))
+        });
+        (status, doc)
+    }
+
+    /// Fetches `uri`, which is relative to the `path` of the app
+    pub async fn get(&self, uri: &str) -> (StatusCode, Document) {
+        self.document(self.request(uri)).await
+    }
+
+    /// Fetches the collection of `E` with `query`, like `?sort=-name`
+    pub async fn get_collection<E: SingleEntity>(&self, query: &str) -> (StatusCode, Document) {
+        self.get(&format!("{}{}", E::ty(), query)).await
+    }
+
+    /// Fetches the resource `id` of `E` with `query`, like `?include=dogs`
+    pub async fn get_single<E: SingleEntity>(
+        &self, id: &str, query: &str,
+    ) -> (StatusCode, Document) {
+        self.get(&format!("{}/{}{}", E::ty(), id, query)).await
+    }
+}
@@ -0,0 +1,149 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::testing;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+#[actix_web::test]
+async fn json_api_app_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let api =
+        JsonApiApp::new(settings).unwrap().resource::<HumanService>().resource::<DogService>();
+    assert_eq!(api.types(), &["people".to_string(), "dogs".to_string()]);
+    assert_eq!(api.uri().as_str(), "http://localhost:1234/api/v1");
+
+    let app = testing::init(&api).await;
+    for (uri, status) in &[
+        ("people/1", StatusCode::OK),
+        ("people/1/dogs", StatusCode::OK),
+        ("dogs/1", StatusCode::OK),
+        ("cats/1", StatusCode::NOT_FOUND),
+    ] {
+        let resp = app.call(app.request(uri)).await;
+        assert_eq!(resp.status(), *status, "GET {}", uri);
+        if resp.status() == StatusCode::OK {
+            let body: serde_json::Value =
+                serde_json::from_slice(&test::read_body(resp).await).unwrap();
+            let link = body["links"]["self"].as_str().unwrap();
+            assert_eq!(link, format!("{}/{}", api.uri(), uri));
+        }
+    }
+
+    let (status, doc) = app.get_single::<Human>("1", "?include=dogs").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(matches!(doc.item, DocumentItem::PrimaryData(Some(_))));
+    let (status, doc) = app.get_collection::<Dog>("?sort=-name").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(matches!(doc.item, DocumentItem::PrimaryData(Some(_))));
+}
+
+#[actix_web::test]
+async fn lenient_header_rules_test() {
+    let settings =
+        ActixSettingsModel::load(Some(FILE), &[("jsonapi.header_rules", "lenient")]).unwrap();
+    let api = JsonApiApp::new(settings).unwrap().resource::<HumanService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    for (content_type, accept, status) in &[
+        (Some("application/json"), Some("application/json"), StatusCode::OK),
+        (None, None, StatusCode::OK),
+        (Some("text/plain"), None, StatusCode::UNSUPPORTED_MEDIA_TYPE),
+        (None, Some("text/html"), StatusCode::NOT_ACCEPTABLE),
+    ] {
+        let mut req = test::TestRequest::get().uri(&format!("{}/people/1", api.path()));
+        if let Some(content_type) = content_type {
+            req = req.insert_header((header::CONTENT_TYPE, *content_type));
+        }
+        if let Some(accept) = accept {
+            req = req.insert_header((header::ACCEPT, *accept));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), *status, "{:?} {:?}", content_type, accept);
+    }
+
+    let err = ActixSettingsModel::load(Some(FILE), &[("jsonapi.header_rules", "loose")]);
+    assert!(err.is_err());
+}
+
+#[actix_web::test]
+async fn route_matching_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[
+        ("jsonapi.route_matching.trailing_slash", "true"),
+        ("jsonapi.route_matching.case_insensitive_types", "true"),
+    ])
+    .unwrap();
+    let api = JsonApiApp::new(settings).unwrap().resource::<HumanService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    // The tolerated paths are answered by the routes of the type with the canonical `self` links
+    for (uri, canonical) in &[
+        ("/People", "/people"),
+        ("/people/1/", "/people/1"),
+        ("/PEOPLE/1/dogs/?sort=name", "/people/1/dogs?sort=name"),
+        ("/People/1/relationships/dogs/", "/relationships/dogs"),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}{}", api.path(), uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK, "GET {}", uri);
+        let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        let link = body["links"]["self"].as_str().unwrap();
+        assert!(link.contains(&format!("{}/people", api.path())), "GET {}: {}", uri, link);
+        assert!(link.ends_with(canonical), "GET {}: {}", uri, link);
+    }
+
+    let req = test::TestRequest::get().uri(&format!("{}/cats/", api.path())).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+}
+
+#[actix_web::test]
+async fn error_hook_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_error_hook(|err, req| {
+            err.detail = None;
+            err.meta.get_or_insert_with(Default::default).insert("path".into(), req.path().into());
+        })
+        .resource::<HumanService>();
+
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people/1/relationships/no_exist", api.path()))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    if let DocumentItem::Errors(errors) = body.item {
+        assert!(errors[0].detail.is_none());
+        let meta = errors[0].meta.as_ref().unwrap();
+        assert!(meta.contains_key("requestId"));
+        assert_eq!(
+            meta.get("path").and_then(|path| path.as_str()),
+            Some("/api/v1/people/1/relationships/no_exist")
+        );
+    } else {
+        unreachable!("Expect error document");
+    }
+}
+
+#[actix_web::test]
+#[should_panic(expected = "mounted twice")]
+async fn duplicated_resource_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    JsonApiApp::new(settings).unwrap().resource::<DogService>().resource::<DogService>();
+}
@@ -0,0 +1,80 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::error;
+use rabbithole::operation::Principal;
+use rabbithole::{RbhResult, JSON_API_HEADER};
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::auth::{Authenticator, Credentials, API_KEY_HEADER};
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+struct TokenAuthenticator;
+
+#[async_trait::async_trait]
+impl Authenticator for TokenAuthenticator {
+    async fn authenticate(&self, credentials: &Credentials) -> RbhResult<Principal> {
+        match credentials {
+            Credentials::Bearer(token) if token == "token" => {
+                Ok(Principal { id: "alice".into(), scopes: vec!["read".into()] })
+            },
+            Credentials::ApiKey(key) if key == "key" => Ok(Principal::default()),
+            _ => Err(error::Error::Unauthenticated("invalid credentials", None)),
+        }
+    }
+}
+
+#[actix_web::test]
+async fn authenticator_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_authenticator(TokenAuthenticator)
+        .protected_resource::<HumanService>()
+        .resource::<DogService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    for (uri, credentials, status) in &[
+        ("people/1", None, StatusCode::UNAUTHORIZED),
+        ("people/1", Some((header::AUTHORIZATION.as_str(), "Bearer token")), StatusCode::OK),
+        (
+            "people/1",
+            Some((header::AUTHORIZATION.as_str(), "Bearer bad")),
+            StatusCode::UNAUTHORIZED,
+        ),
+        ("people/1", Some((API_KEY_HEADER, "key")), StatusCode::OK),
+        ("dogs/1", None, StatusCode::OK),
+        ("dogs/1", Some((API_KEY_HEADER, "bad")), StatusCode::UNAUTHORIZED),
+    ] {
+        let mut req = test::TestRequest::get()
+            .uri(&format!("{}/{}", api.path(), uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER));
+        if let Some(credentials) = credentials {
+            req = req.insert_header(*credentials);
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), *status, "GET {} with {:?}", uri, credentials);
+        if *status == StatusCode::UNAUTHORIZED {
+            assert_eq!(resp.headers().get(header::WWW_AUTHENTICATE).unwrap(), "Bearer");
+        }
+    }
+}
+
+#[actix_web::test]
+async fn credentials_test() {
+    let mut headers = header::HeaderMap::new();
+    assert_eq!(Credentials::from_headers(&headers), None);
+    headers.insert(API_KEY_HEADER.parse().unwrap(), "key".parse().unwrap());
+    assert_eq!(Credentials::from_headers(&headers), Some(Credentials::ApiKey("key".into())));
+    headers.insert(header::AUTHORIZATION, "bearer  token ".parse().unwrap());
+    assert_eq!(Credentials::from_headers(&headers), Some(Credentials::Bearer("token".into())));
+    headers.insert(header::AUTHORIZATION, "Basic dXNlcg==".parse().unwrap());
+    assert_eq!(Credentials::from_headers(&headers), Some(Credentials::ApiKey("key".into())));
+}
@@ -0,0 +1,70 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web};
+use async_trait::async_trait;
+
+use crate::classes_init;
+
+use rabbithole::cache::{DocumentCache, LruDocumentCache};
+use rabbithole::model::error;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::ActixSettings;
+use std::sync::Arc;
+
+classes_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+/// The responses of the test services are random, so a fixed store is used
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+#[actix_web::test]
+async fn cache_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let humans = generate_masters(3);
+    let first = humans[0].id_code.to_string();
+    let cache = Arc::new(LruDocumentCache::new(8));
+    let store =
+        ActixSettings::new(settings.clone(), HumanStore(humans)).unwrap().with_cache(cache.clone());
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(store.scope())),
+    )
+    .await;
+
+    let uris = [
+        "people".to_string(),
+        format!("people/{}", first),
+        format!("people/{}/relationships/dogs", first),
+        format!("people/{}/dogs", first),
+    ];
+    for (count, uri) in uris.iter().enumerate() {
+        let mut bodies = Vec::new();
+        for _ in 0 .. 2 {
+            let req = test::TestRequest::get()
+                .uri(&format!("{}/{}", settings.path, uri))
+                .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+                .insert_header((header::ACCEPT, JSON_API_HEADER))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), StatusCode::OK, "GET {}", uri);
+            bodies.push(test::read_body(resp).await);
+        }
+        assert_eq!(bodies[0], bodies[1], "GET {}", uri);
+        assert_eq!(cache.len(), count + 1, "GET {}", uri);
+    }
+
+    cache.invalidate("dogs").await;
+    assert!(cache.is_empty());
+}
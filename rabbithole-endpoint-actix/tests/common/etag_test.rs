@@ -0,0 +1,122 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web};
+use async_trait::async_trait;
+
+use crate::classes_init;
+
+use rabbithole::model::error;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::ActixSettings;
+
+classes_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+/// The responses of the test services are random, so a fixed store is used
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+#[actix_web::test]
+async fn etag_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let humans = generate_masters(3);
+    let first = humans[0].id_code.to_string();
+    let store = ActixSettings::new(settings.clone(), HumanStore(humans)).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(store.scope())),
+    )
+    .await;
+
+    for uri in &["people".to_string(), format!("people/{}", first)] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/{}", settings.path, uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let etag = resp.headers().get(header::ETAG).unwrap().to_str().unwrap().to_string();
+        assert!(etag.starts_with('"') && etag.ends_with('"'), "{}", etag);
+
+        for (if_none_match, status) in &[
+            (etag.clone(), StatusCode::NOT_MODIFIED),
+            (format!("\"other\", W/{}", etag), StatusCode::NOT_MODIFIED),
+            ("*".to_string(), StatusCode::NOT_MODIFIED),
+            ("\"other\"".to_string(), StatusCode::OK),
+        ] {
+            let req = test::TestRequest::get()
+                .uri(&format!("{}/{}", settings.path, uri))
+                .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+                .insert_header((header::ACCEPT, JSON_API_HEADER))
+                .insert_header((header::IF_NONE_MATCH, if_none_match.as_str()))
+                .to_request();
+            let resp = test::call_service(&app, req).await;
+            assert_eq!(resp.status(), *status, "GET {} If-None-Match: {}", uri, if_none_match);
+            assert_eq!(resp.headers().get(header::ETAG).unwrap(), etag.as_str());
+            if *status == StatusCode::NOT_MODIFIED {
+                assert!(test::read_body(resp).await.is_empty());
+            }
+        }
+    }
+}
+
+#[actix_web::test]
+async fn cache_control_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[
+        ("jsonapi.cache_control.people.max_age", "60"),
+        ("jsonapi.cache_control.people.visibility", "public"),
+    ])
+    .unwrap();
+    let humans = generate_masters(1);
+    let first = humans[0].id_code.to_string();
+    let store = ActixSettings::new(settings.clone(), HumanStore(humans.clone())).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(store.scope())),
+    )
+    .await;
+
+    for (uri, cache_control) in &[
+        (format!("people/{}", first), Some("public, max-age=60")),
+        ("people?filter[@type]=Sql".into(), None),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/{}", settings.path, uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        let value = resp.headers().get(header::CACHE_CONTROL).map(|v| v.to_str().unwrap());
+        assert_eq!(value, *cache_control, "GET {}", uri);
+    }
+
+    // The `no-store` responses are left without the validators
+    let settings =
+        ActixSettingsModel::load(Some(FILE), &[("jsonapi.cache_control.people.no_store", "true")])
+            .unwrap();
+    let store = ActixSettings::new(settings.clone(), HumanStore(humans)).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(store.scope())),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people", settings.path))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get(header::CACHE_CONTROL).unwrap(), "no-store");
+    assert!(resp.headers().get(header::ETAG).is_none());
+}
@@ -0,0 +1,73 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::error;
+use rabbithole::operation::Principal;
+use rabbithole::{RbhResult, JSON_API_HEADER};
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::auth::{Authenticator, Credentials};
+use rabbithole_endpoint_actix::settings::{ActixSettingsModel, ResourceGuards};
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+struct RoleAuthenticator;
+
+#[async_trait::async_trait]
+impl Authenticator for RoleAuthenticator {
+    async fn authenticate(&self, credentials: &Credentials) -> RbhResult<Principal> {
+        match credentials {
+            Credentials::Bearer(role) => {
+                Ok(Principal { id: role.clone(), scopes: vec![role.clone()] })
+            },
+            _ => Err(error::Error::Unauthenticated("invalid credentials", None)),
+        }
+    }
+}
+
+#[actix_web::test]
+async fn resource_guards_test() {
+    let mut settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    settings.resources.insert("people".into(), ResourceGuards {
+        public: vec!["fetch_collection".into()],
+        roles: vec![("fetch_relationship".into(), vec!["admin".into()])].into_iter().collect(),
+        disabled: vec!["fetch_related".into()],
+    });
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_authenticator(RoleAuthenticator)
+        .protected_resource::<HumanService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    for (uri, role, status) in &[
+        ("people", None, StatusCode::OK),
+        ("people/1", None, StatusCode::UNAUTHORIZED),
+        ("people/1", Some("user"), StatusCode::OK),
+        ("people/1/relationships/dogs", None, StatusCode::UNAUTHORIZED),
+        ("people/1/relationships/dogs", Some("user"), StatusCode::FORBIDDEN),
+        ("people/1/relationships/dogs", Some("admin"), StatusCode::OK),
+        ("people/1/dogs", Some("admin"), StatusCode::NOT_FOUND),
+    ] {
+        let mut req = test::TestRequest::get()
+            .uri(&format!("{}/{}", api.path(), uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER));
+        if let Some(role) = role {
+            req = req.insert_header((header::AUTHORIZATION, format!("Bearer {}", role)));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.status(), *status, "GET {} as {:?}", uri, role);
+    }
+
+    let mut settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    settings.resources.insert("people".into(), ResourceGuards {
+        disabled: vec!["delete".into()],
+        ..Default::default()
+    });
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("`resources.people.disabled`"), "{}", err);
+}
@@ -0,0 +1,63 @@
+use actix_web::http::header;
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::i18n::{Localization, Message};
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+fn localization() -> Localization {
+    Localization::new("en")
+        .with_messages("zh", vec![("RBH-0401", Message::new("字段不存在", None))])
+        .with_messages("de-CH", vec![("RBH-0401", Message::new("Feld fehlt", Some("-".into())))])
+}
+
+#[actix_web::test]
+async fn localized_error_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_localization(localization())
+        .resource::<HumanService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    for (accept_language, locale, title) in &[
+        (Some("zh-CN, en;q=0.5"), "zh", "字段不存在"),
+        (Some("fr, en;q=0.1"), "en", "Field Not Exist"),
+        (None, "en", "Field Not Exist"),
+    ] {
+        let mut req = test::TestRequest::get()
+            .uri(&format!("{}/people/1/relationships/no_exist", api.path()))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER));
+        if let Some(accept_language) = accept_language {
+            req = req.insert_header((header::ACCEPT_LANGUAGE, *accept_language));
+        }
+        let resp = test::call_service(&app, req.to_request()).await;
+        assert_eq!(resp.headers().get(header::CONTENT_LANGUAGE).unwrap(), *locale);
+        let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        if let DocumentItem::Errors(errors) = body.item {
+            assert_eq!(errors[0].title.as_deref(), Some(*title));
+        } else {
+            unreachable!("Expect error document");
+        }
+    }
+}
+
+#[actix_web::test]
+async fn negotiate_test() {
+    let localization = localization();
+    assert_eq!(localization.negotiate(None), "en");
+    assert_eq!(localization.negotiate(Some("de-CH-1996")), "de-ch");
+    assert_eq!(localization.negotiate(Some("de")), "en");
+    assert_eq!(localization.negotiate(Some("en;q=0.5, zh-Hans")), "zh");
+    assert_eq!(localization.negotiate(Some("zh;q=0, *")), "en");
+}
@@ -0,0 +1,53 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web};
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::metrics::Metrics;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::ActixSettings;
+
+classes_init!();
+fetching_init!();
+
+#[actix_web::test]
+async fn metrics_test() {
+    let mut settings = config::Config::default();
+    settings.merge(config::File::with_name("config/actix.config.test.v1_0.toml")).unwrap();
+    let settings: ActixSettingsModel = settings.try_into().unwrap();
+    let metrics = Metrics::new();
+    let humans =
+        ActixSettings::new(settings.clone(), HumanService).unwrap().with_metrics(metrics.clone());
+
+    let app = test::init_service(
+        actix_web::App::new()
+            .service(web::scope(&settings.path).service(humans.scope()))
+            .service(metrics.resource()),
+    )
+    .await;
+
+    for uri in &["people", "people/1", "people/1/relationships/no_exist"] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/{}", settings.path, uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        test::call_service(&app, req).await;
+    }
+
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body = String::from_utf8(test::read_body(resp).await.to_vec()).unwrap();
+    assert!(body.contains(
+        r#"rabbithole_requests_total{operation="fetch_collection",status="200",ty="people"} 1"#
+    ));
+    assert!(body.contains(
+        r#"rabbithole_errors_total{code="RBH-0401",operation="fetch_relationship",ty="people"} 1"#
+    ));
+    assert!(body.contains(
+        r#"rabbithole_request_duration_seconds_count{operation="fetch_single",ty="people"} 1"#
+    ));
+    assert!(body.contains(r#"rabbithole_collection_size_count{ty="people"} 1"#));
+}
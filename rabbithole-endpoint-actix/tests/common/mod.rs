@@ -1,2 +1,18 @@
 /// https://jsonapi.org/format/#fetching-resources
+pub mod app_test;
+pub mod auth_test;
+pub mod cache_test;
+pub mod etag_test;
 pub mod fetching_test;
+pub mod guard_test;
+pub mod i18n_test;
+#[cfg(feature = "metrics")]
+pub mod metrics_test;
+pub mod openapi_test;
+pub mod panic_test;
+pub mod path_test;
+pub mod proxy_test;
+pub mod rate_limit_test;
+pub mod scope_test;
+pub mod settings_test;
+pub mod version_test;
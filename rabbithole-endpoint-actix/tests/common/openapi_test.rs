@@ -0,0 +1,79 @@
+use actix_web::http::StatusCode;
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::error;
+use rabbithole::operation::Principal;
+use rabbithole::RbhResult;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::auth::{Authenticator, Credentials};
+use rabbithole_endpoint_actix::settings::{ActixSettingsModel, ResourceGuards};
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+struct DenyingAuthenticator;
+
+#[async_trait::async_trait]
+impl Authenticator for DenyingAuthenticator {
+    async fn authenticate(&self, _: &Credentials) -> RbhResult<Principal> {
+        Err(error::Error::Unauthenticated("invalid credentials", None))
+    }
+}
+
+#[actix_web::test]
+async fn openapi_test() {
+    let mut settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    settings.resources.insert("people".into(), ResourceGuards {
+        public: vec!["fetch_collection".into()],
+        disabled: vec!["fetch_related".into()],
+        ..Default::default()
+    });
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_authenticator(DenyingAuthenticator)
+        .with_openapi("Pets", "2.1.0")
+        .protected_resource::<HumanService>()
+        .resource::<DogService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+
+    let req = test::TestRequest::get().uri(&format!("{}/openapi.json", api.path())).to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let doc: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    assert_eq!(doc, api.openapi());
+
+    assert_eq!(doc["info"]["title"], "Pets");
+    assert_eq!(doc["info"]["version"], "2.1.0");
+    assert_eq!(doc["servers"][0]["url"], api.uri().as_str());
+    let paths = doc["paths"].as_object().unwrap();
+    let mut keys: Vec<&str> = paths.keys().map(String::as_str).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec![
+        "/dogs",
+        "/dogs/{id}",
+        "/dogs/{id}/relationships/{related_field}",
+        "/dogs/{id}/{related_field}",
+        "/people",
+        "/people/{id}",
+        "/people/{id}/relationships/{related_field}",
+    ]);
+    assert_eq!(paths["/people/{id}"]["get"]["operationId"], "fetch_single_people");
+    assert!(paths["/people"]["get"].get("security").is_none());
+    assert!(paths["/people/{id}"]["get"]["security"].is_array());
+    assert!(paths["/dogs/{id}"]["get"].get("security").is_none());
+    assert!(doc["components"]["securitySchemes"]["bearer"].is_object());
+
+    let api = JsonApiApp::new(ActixSettingsModel::load(Some(FILE), &[]).unwrap())
+        .unwrap()
+        .resource::<DogService>();
+    let app = test::init_service(actix_web::App::new().service(api.scope())).await;
+    let req = test::TestRequest::get().uri(&format!("{}/openapi.json", api.path())).to_request();
+    assert_eq!(test::call_service(&app, req).await.status(), StatusCode::NOT_FOUND);
+    let doc = api.openapi();
+    assert_eq!(doc["info"]["version"], "1.0");
+    assert!(doc["components"].get("securitySchemes").is_none());
+}
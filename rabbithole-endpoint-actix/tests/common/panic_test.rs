@@ -0,0 +1,71 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web};
+use async_trait::async_trait;
+
+use crate::classes_init;
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::{ActixSettings, REQUEST_ID_HEADER};
+
+classes_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+/// A store panicking when listing the humans, but not when getting one of them
+struct PanickingStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for PanickingStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        panic!("connection string: secret")
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+#[actix_web::test]
+async fn panic_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    let humans = generate_masters(1);
+    let first = humans[0].id_code.to_string();
+    let store = ActixSettings::new(settings.clone(), PanickingStore(humans)).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(store.scope())),
+    )
+    .await;
+
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people", settings.path))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .insert_header((REQUEST_ID_HEADER, "panicking-request"))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), JSON_API_HEADER);
+    let body = test::read_body(resp).await;
+    assert!(!String::from_utf8_lossy(&body).contains("secret"));
+    let body: Document = serde_json::from_slice(&body).unwrap();
+    if let DocumentItem::Errors(errors) = body.item {
+        assert_eq!(errors[0].code.as_deref(), Some("RBH-0006"));
+        assert_eq!(errors[0].meta.as_ref().unwrap()["requestId"], "panicking-request");
+    } else {
+        unreachable!("Expect error document");
+    }
+
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people/{}", settings.path, first))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,59 @@
+use actix_web::http::StatusCode;
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::testing;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+#[actix_web::test]
+async fn resource_paths_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[
+        ("paths.people", "/admin/humans"),
+        ("paths.dogs", "/canines"),
+    ])
+    .unwrap();
+    assert_eq!(settings.resource_path("people"), "/admin/humans");
+    assert_eq!(settings.resource_path("cats"), "/cats");
+    let api =
+        JsonApiApp::new(settings).unwrap().resource::<HumanService>().resource::<DogService>();
+    let app = testing::init(&api).await;
+
+    for (uri, status) in &[
+        ("admin/humans/1", StatusCode::OK),
+        ("admin/humans/1/relationships/dogs", StatusCode::OK),
+        ("canines/1", StatusCode::OK),
+        ("people/1", StatusCode::NOT_FOUND),
+        ("dogs/1", StatusCode::NOT_FOUND),
+    ] {
+        let resp = app.call(app.request(uri)).await;
+        assert_eq!(resp.status(), *status, "GET {}", uri);
+    }
+
+    let resp = app.call(app.request("admin/humans/1")).await;
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    let uri = api.uri().as_str();
+    let id = body["data"]["id"].as_str().unwrap();
+    assert_eq!(body["links"]["self"], format!("{}/admin/humans/1", uri));
+    assert_eq!(body["data"]["links"]["self"], format!("{}/admin/humans/{}", uri, id));
+    assert_eq!(
+        body["data"]["relationships"]["dogs"]["links"]["self"],
+        format!("{}/admin/humans/{}/relationships/dogs", uri, id)
+    );
+    for included in body["included"].as_array().unwrap() {
+        let link = included["links"]["self"].as_str().unwrap();
+        assert!(link.starts_with(&format!("{}/canines/", uri)), "{}", link);
+    }
+
+    for (path, key) in &[("admin/", "paths.people"), ("/canines", "paths.people")] {
+        let err = ActixSettingsModel::load(Some(FILE), &[("paths.dogs", "/canines"), (key, path)])
+            .unwrap_err();
+        assert!(err.to_string().contains("`paths."), "{}", err);
+    }
+}
@@ -0,0 +1,108 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::{test, web};
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::ActixSettings;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+async fn self_link(settings: ActixSettingsModel, headers: &[(&str, &str)]) -> String {
+    let humans = ActixSettings::new(settings.clone(), HumanService).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(humans.scope())),
+    )
+    .await;
+    let mut req = test::TestRequest::get()
+        .uri(&format!("{}/people/1", settings.path))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER));
+    for header in headers {
+        req = req.insert_header(*header);
+    }
+    let resp = test::call_service(&app, req.to_request()).await;
+    let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    body["links"]["self"].as_str().unwrap().to_string()
+}
+
+#[actix_web::test]
+async fn external_url_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    assert_eq!(self_link(settings, &[]).await, "http://localhost:1234/api/v1/people/1");
+
+    let settings =
+        ActixSettingsModel::load(Some(FILE), &[("external_url", "https://example.com/svc/")])
+            .unwrap();
+    let humans = ActixSettings::new(settings.clone(), HumanService).unwrap();
+    assert_eq!(humans.uri.as_str(), "https://example.com/svc/api/v1");
+    assert_eq!(self_link(settings, &[]).await, "https://example.com/svc/api/v1/people/1");
+
+    let err = ActixSettingsModel::load(Some(FILE), &[("external_url", "not a url")]).unwrap_err();
+    assert!(err.to_string().contains("`external_url`"), "{}", err);
+}
+
+#[actix_web::test]
+async fn forwarded_headers_test() {
+    let forwarded = [
+        ("x-forwarded-proto", "https, http"),
+        ("x-forwarded-host", "example.com:8443"),
+        ("x-forwarded-prefix", "/svc"),
+    ];
+
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    assert_eq!(self_link(settings, &forwarded).await, "http://localhost:1234/api/v1/people/1");
+
+    let settings =
+        ActixSettingsModel::load(Some(FILE), &[("trust_forwarded_headers", "true")]).unwrap();
+    assert_eq!(
+        self_link(settings, &forwarded).await,
+        "https://example.com:8443/svc/api/v1/people/1"
+    );
+}
+
+#[actix_web::test]
+async fn tenant_test() {
+    let settings =
+        ActixSettingsModel::load(Some(FILE), &[("jsonapi.tenant", "subdomain")]).unwrap();
+    assert_eq!(
+        self_link(settings.clone(), &[("host", "acme.localhost:1234")]).await,
+        "http://acme.localhost:1234/api/v1/people/1"
+    );
+
+    // The tenants are looked for in the hosts seen by the clients
+    let settings = ActixSettingsModel::load(Some(FILE), &[
+        ("jsonapi.tenant", "subdomain"),
+        ("trust_forwarded_headers", "true"),
+    ])
+    .unwrap();
+    let headers = [("host", "acme.example.com"), ("x-forwarded-host", "example.com:8443")];
+    assert_eq!(
+        self_link(settings.clone(), &headers).await,
+        "http://acme.example.com:8443/api/v1/people/1"
+    );
+
+    let humans = ActixSettings::new(settings.clone(), HumanService).unwrap();
+    let app = test::init_service(
+        actix_web::App::new().service(web::scope(&settings.path).service(humans.scope())),
+    )
+    .await;
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people/1", settings.path))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    if let DocumentItem::Errors(errors) = body.item {
+        assert_eq!(errors[0].code.as_deref(), Some("RBH-0013"));
+    } else {
+        unreachable!("Expect error document");
+    }
+}
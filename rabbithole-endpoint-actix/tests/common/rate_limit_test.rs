@@ -0,0 +1,96 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::operation::Principal;
+use rabbithole::RbhResult;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::auth::{Authenticator, Credentials, API_KEY_HEADER};
+use rabbithole_endpoint_actix::rate_limit::{LIMIT_HEADER, REMAINING_HEADER, RESET_HEADER};
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::testing;
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+/// The API keys `a` and `b` are valid, being the ids of their principals
+struct KeyAuthenticator;
+
+#[async_trait::async_trait]
+impl Authenticator for KeyAuthenticator {
+    async fn authenticate(&self, credentials: &Credentials) -> RbhResult<Principal> {
+        match credentials {
+            Credentials::ApiKey(key) if key == "a" || key == "b" => {
+                Ok(Principal { id: key.clone(), scopes: vec![] })
+            },
+            _ => Err(error::Error::Unauthenticated("invalid credentials", None)),
+        }
+    }
+}
+
+#[actix_web::test]
+async fn rate_limit_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[
+        ("rate_limit.rate", "0.01"),
+        ("rate_limit.burst", "2"),
+    ])
+    .unwrap();
+    let api = JsonApiApp::new(settings)
+        .unwrap()
+        .with_authenticator(KeyAuthenticator)
+        .resource::<HumanService>()
+        .resource::<DogService>();
+    let app = testing::init(&api).await;
+
+    // The clients are the principals of the valid keys, where the invalid ones share the bucket of
+    // the IP address, so they can't be made up to get new buckets
+    for (uri, api_key, status, remaining) in &[
+        ("people/1", "a", StatusCode::OK, "1"),
+        ("people/1", "a", StatusCode::OK, "0"),
+        ("people/1", "a", StatusCode::TOO_MANY_REQUESTS, "0"),
+        ("people/1", "b", StatusCode::OK, "1"),
+        ("dogs/1", "a", StatusCode::OK, "1"),
+        ("people/1", "x", StatusCode::UNAUTHORIZED, "1"),
+        ("people/1", "y", StatusCode::UNAUTHORIZED, "0"),
+        ("people/1", "z", StatusCode::TOO_MANY_REQUESTS, "0"),
+    ] {
+        let resp = app.call(app.request(uri).insert_header((API_KEY_HEADER, *api_key))).await;
+        assert_eq!(resp.status(), *status, "GET {} with {}", uri, api_key);
+        assert_eq!(resp.headers().get(LIMIT_HEADER).unwrap(), "2");
+        assert_eq!(resp.headers().get(REMAINING_HEADER).unwrap(), remaining);
+        assert!(resp.headers().contains_key(RESET_HEADER));
+        if *status == StatusCode::TOO_MANY_REQUESTS {
+            assert_eq!(resp.headers().get(header::RETRY_AFTER).unwrap(), "100");
+            let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+            if let DocumentItem::Errors(errors) = body.item {
+                assert_eq!(errors[0].code.as_deref(), Some("RBH-0005"));
+            } else {
+                unreachable!("Expect error document");
+            }
+        }
+    }
+
+    // The usage of the client by the resource types, without taking any tokens
+    for _ in 0 .. 2 {
+        let resp = app.call(app.request("rate-limit").insert_header((API_KEY_HEADER, "a"))).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        let usages = &body["meta"]["rateLimits"];
+        assert_eq!(usages["people"]["limit"], 2);
+        assert_eq!(usages["people"]["remaining"], 0);
+        assert!(usages["people"]["reset"].as_u64().unwrap() > 100);
+        assert_eq!(usages["dogs"]["remaining"], 1);
+    }
+
+    let err = ActixSettingsModel::load(Some(FILE), &[
+        ("rate_limit.rate", "0"),
+        ("rate_limit.burst", "2"),
+    ])
+    .unwrap_err();
+    assert!(err.to_string().contains("`rate_limit.rate`"), "{}", err);
+}
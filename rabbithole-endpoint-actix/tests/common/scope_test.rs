@@ -0,0 +1,92 @@
+use actix_web::http::{header, Method, StatusCode};
+use actix_web::{test, web};
+
+use crate::{classes_init, fetching_init, init_app};
+
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+use rabbithole_endpoint_actix::ActixSettings;
+
+classes_init!();
+fetching_init!();
+
+#[actix_web::test]
+async fn scope_routes_test() {
+    let mut settings = config::Config::default();
+    settings.merge(config::File::with_name("config/actix.config.test.v1_0.toml")).unwrap();
+    let settings: ActixSettingsModel = settings.try_into().unwrap();
+    let humans = ActixSettings::new(settings.clone(), HumanService).unwrap();
+    let dogs = ActixSettings::new(settings.clone(), DogService).unwrap();
+
+    let app = test::init_service(
+        actix_web::App::new()
+            .service(web::scope(&settings.path).service(humans.scope()).service(dogs.scope())),
+    )
+    .await;
+
+    for (uri, status) in &[
+        ("people", StatusCode::OK),
+        ("people/1", StatusCode::OK),
+        ("people/1/relationships/dogs", StatusCode::OK),
+        ("people/1/dogs", StatusCode::OK),
+        ("dogs/1", StatusCode::OK),
+        ("dogs/1/relationships/master", StatusCode::NOT_FOUND),
+        ("cats/1", StatusCode::NOT_FOUND),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/{}", settings.path, uri))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), *status, "GET {}", uri);
+    }
+
+    for (method, uri) in &[
+        (Method::POST, "people"),
+        (Method::PATCH, "people/1"),
+        (Method::DELETE, "people/1/relationships/dogs"),
+        (Method::PUT, "dogs/1"),
+    ] {
+        let req = test::TestRequest::default()
+            .method(method.clone())
+            .uri(&format!("{}/{}", settings.path, uri))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED, "{} {}", method, uri);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, HEAD, OPTIONS");
+        let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+        if let DocumentItem::Errors(errors) = body.item {
+            assert_eq!(errors[0].code.as_deref(), Some("RBH-0007"));
+        } else {
+            unreachable!("Expect error document");
+        }
+    }
+}
+
+#[actix_web::test]
+async fn head_and_options_test() {
+    let (path, app) = init_app!(1, 0);
+
+    let req = test::TestRequest::default()
+        .method(Method::HEAD)
+        .uri(&format!("{}/people/1", path))
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), JSON_API_HEADER);
+    assert!(test::read_body(resp).await.is_empty());
+
+    for uri in &["people", "people/1", "people/1/relationships/dogs", "people/1/dogs"] {
+        let req = test::TestRequest::default()
+            .method(Method::OPTIONS)
+            .uri(&format!("{}/{}", path, uri))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::NO_CONTENT, "OPTIONS {}", uri);
+        assert_eq!(resp.headers().get(header::ALLOW).unwrap(), "GET, HEAD, OPTIONS");
+    }
+}
@@ -0,0 +1,32 @@
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole_endpoint_actix::settings::ActixSettingsModel;
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+#[test]
+fn layered_settings_test() {
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    assert_eq!(settings.port, 1234);
+    assert_eq!(settings.jsonapi.version, JsonApiVersion { major: 1, minor: 0 });
+
+    std::env::set_var("RBH__PORT", "4321");
+    std::env::set_var("RBH__JSONAPI__VERSION", "1.1");
+    let settings = ActixSettingsModel::load(Some(FILE), &[]).unwrap();
+    assert_eq!(settings.port, 4321);
+    assert_eq!(settings.jsonapi.version, JsonApiVersion { major: 1, minor: 1 });
+
+    let settings = ActixSettingsModel::load(Some(FILE), &[("port", "8080")]).unwrap();
+    assert_eq!(settings.port, 8080);
+
+    let err = ActixSettingsModel::load(Some(FILE), &[("port", "not a port")]).unwrap_err();
+    assert!(err.to_string().contains("port"), "{}", err);
+    let err = ActixSettingsModel::load(Some(FILE), &[("port", "70000")]).unwrap_err();
+    assert!(err.to_string().contains("`port`"), "{}", err);
+    let err = ActixSettingsModel::load(Some(FILE), &[("path", "api")]).unwrap_err();
+    assert!(err.to_string().contains("`path`"), "{}", err);
+
+    std::env::remove_var("RBH__PORT");
+    std::env::remove_var("RBH__JSONAPI__VERSION");
+    let err = ActixSettingsModel::load(None, &[]).unwrap_err();
+    assert!(err.to_string().contains("host"), "{}", err);
+}
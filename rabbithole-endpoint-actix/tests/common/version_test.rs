@@ -0,0 +1,66 @@
+use actix_web::http::{header, StatusCode};
+use actix_web::test;
+
+use crate::{classes_init, fetching_init};
+
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_actix::app::JsonApiApp;
+use rabbithole_endpoint_actix::settings::{ActixSettingsModel, ApiVersionSettings};
+
+classes_init!();
+fetching_init!();
+
+const FILE: &str = "config/actix.config.test.v1_0.toml";
+
+#[actix_web::test]
+async fn api_versions_test() {
+    let mut settings = ActixSettingsModel::load(Some(FILE), &[("path", "/api")]).unwrap();
+    settings.versions.insert("v1".into(), ApiVersionSettings {
+        path: "/v1".into(),
+        resources: Some(vec!["people".into()]),
+    });
+    settings
+        .versions
+        .insert("v2".into(), ApiVersionSettings { path: "/v2".into(), resources: None });
+    settings.validate().unwrap();
+
+    let v1 = JsonApiApp::version(settings.clone(), "v1")
+        .unwrap()
+        .resource::<HumanService>()
+        .resource::<DogService>();
+    let v2 = JsonApiApp::version(settings.clone(), "v2")
+        .unwrap()
+        .resource::<HumanService>()
+        .resource::<DogService>();
+    assert_eq!(v1.types(), &["people".to_string()]);
+    assert_eq!(v2.uri().as_str(), "http://localhost:1234/api/v2");
+    assert!(JsonApiApp::version(settings.clone(), "v3").is_err());
+
+    let app =
+        test::init_service(actix_web::App::new().service(v1.scope()).service(v2.scope())).await;
+    for (uri, status) in &[
+        ("/api/v1/people/1", StatusCode::OK),
+        ("/api/v1/dogs/1", StatusCode::NOT_FOUND),
+        ("/api/v2/people/1", StatusCode::OK),
+        ("/api/v2/dogs/1", StatusCode::OK),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(uri)
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, JSON_API_HEADER))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), *status, "GET {}", uri);
+        if resp.status() == StatusCode::OK {
+            let body: serde_json::Value =
+                serde_json::from_slice(&test::read_body(resp).await).unwrap();
+            assert_eq!(body["links"]["self"], format!("http://localhost:1234{}", uri));
+        }
+    }
+
+    settings
+        .versions
+        .insert("v3".into(), ApiVersionSettings { path: "/v2".into(), resources: None });
+    let err = settings.validate().unwrap_err();
+    assert!(err.to_string().contains("is used by"), "{}", err);
+}
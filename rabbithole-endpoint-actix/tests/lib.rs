@@ -7,12 +7,15 @@ macro_rules! fetching_init {
     () => {
         use rabbithole::entity::{Entity, SingleEntity};
 
+        #[derive(Default)]
+        pub struct DogService;
+
         #[async_trait::async_trait]
-        impl rabbithole::operation::Fetching for Dog {
+        impl rabbithole::operation::Fetching for DogService {
             type Item = Dog;
 
             async fn vec_to_document(
-                items: &[Self::Item], uri: &str, query: &rabbithole::query::Query,
+                &self, items: &[Self::Item], uri: &str, query: &rabbithole::query::Query,
                 request_path: &rabbithole::model::link::RawUri,
             ) -> Result<rabbithole::model::document::Document, rabbithole::model::error::Error>
             {
@@ -20,13 +23,13 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_collection(
-                _query: &rabbithole::query::Query,
+                &self, _plan: &rabbithole::operation::FetchPlan<'_>,
             ) -> Result<Vec<Self::Item>, rabbithole::model::error::Error> {
                 Ok(Default::default())
             }
 
             async fn fetch_single(
-                id: &str, _query: &rabbithole::query::Query,
+                &self, id: &str, _plan: &rabbithole::operation::FetchPlan<'_>,
             ) -> Result<Option<Self::Item>, rabbithole::model::error::Error> {
                 if id == "none" {
                     Ok(None)
@@ -37,7 +40,7 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_relationship(
-                _: &str, related_field: &str, _: &str, _: &rabbithole::query::Query,
+                &self, _: &str, related_field: &str, _: &str, _: &rabbithole::query::Query,
                 _: &rabbithole::model::link::RawUri,
             ) -> Result<
                 rabbithole::model::relationship::Relationship,
@@ -47,19 +50,22 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_related(
-                _: &str, related_field: &str, _: &str, _: &rabbithole::query::Query,
+                &self, _: &str, related_field: &str, _: &str, _: &rabbithole::query::Query,
                 _: &rabbithole::model::link::RawUri,
             ) -> Result<serde_json::Value, rabbithole::model::error::Error> {
                 Err(rabbithole::model::error::Error::FieldNotExist(related_field, None))
             }
         }
 
+        #[derive(Default)]
+        pub struct HumanService;
+
         #[async_trait::async_trait]
-        impl rabbithole::operation::Fetching for Human {
+        impl rabbithole::operation::Fetching for HumanService {
             type Item = Human;
 
             async fn vec_to_document(
-                items: &[Self::Item], uri: &str, query: &rabbithole::query::Query,
+                &self, items: &[Self::Item], uri: &str, query: &rabbithole::query::Query,
                 request_path: &rabbithole::model::link::RawUri,
             ) -> Result<rabbithole::model::document::Document, rabbithole::model::error::Error>
             {
@@ -67,7 +73,7 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_collection(
-                _: &rabbithole::query::Query,
+                &self, _: &rabbithole::operation::FetchPlan<'_>,
             ) -> Result<Vec<Self::Item>, rabbithole::model::error::Error> {
                 let rand = rand::random::<usize>() % 5 + 1;
                 let masters = generate_masters(rand);
@@ -75,7 +81,7 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_single(
-                id: &str, _query: &rabbithole::query::Query,
+                &self, id: &str, _plan: &rabbithole::operation::FetchPlan<'_>,
             ) -> Result<Option<Self::Item>, rabbithole::model::error::Error> {
                 if id == "none" {
                     Ok(None)
@@ -86,7 +92,7 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_relationship(
-                id: &str, related_field: &str, uri: &str, _query: &rabbithole::query::Query,
+                &self, id: &str, related_field: &str, uri: &str, _query: &rabbithole::query::Query,
                 _request_path: &rabbithole::model::link::RawUri,
             ) -> Result<
                 rabbithole::model::relationship::Relationship,
@@ -109,7 +115,7 @@ macro_rules! fetching_init {
             }
 
             async fn fetch_related(
-                id: &str, related_field: &str, uri: &str, query: &rabbithole::query::Query,
+                &self, id: &str, related_field: &str, uri: &str, query: &rabbithole::query::Query,
                 request_path: &rabbithole::model::link::RawUri,
             ) -> Result<serde_json::Value, rabbithole::model::error::Error> {
                 if id == "none" {
@@ -194,7 +200,6 @@ macro_rules! classes_init {
 #[macro_export]
 macro_rules! init_app {
     ($major:expr, $minor:expr) => {{
-        use std::convert::TryInto;
         let mut settings = config::Config::default();
         let version = format!("v{}_{}", $major, $minor);
         settings
@@ -207,19 +212,25 @@ macro_rules! init_app {
             settings.path.clone(),
             test::init_service(
                 actix_web::App::new()
-                    .data::<rabbithole_endpoint_actix::ActixSettings<Human>>(
-                        settings.clone().try_into().unwrap(),
-                    )
-                    .data::<rabbithole_endpoint_actix::ActixSettings<Dog>>(
-                        settings.clone().try_into().unwrap(),
-                    )
+                    .app_data(web::Data::new(
+                        rabbithole_endpoint_actix::ActixSettings::new(
+                            settings.clone(),
+                            HumanService,
+                        )
+                        .unwrap(),
+                    ))
+                    .app_data(web::Data::new(
+                        rabbithole_endpoint_actix::ActixSettings::new(settings.clone(), DogService)
+                            .unwrap(),
+                    ))
                     .service(
                         web::scope(&settings.path)
-                            .service(Human::actix_service())
-                            .service(Dog::actix_service()),
+                            .service(Human::actix_service::<HumanService>())
+                            .service(Dog::actix_service::<DogService>()),
                     )
                     .default_service(web::to(actix_web::HttpResponse::NotFound)),
-            ),
+            )
+            .await,
         )
     }};
 }
@@ -1,6 +1,4 @@
 use crate::{classes_init, fetching_init, init_app};
-use actix_web::dev::Service;
-use actix_web::dev::ServiceResponse;
 use actix_web::http::{header, StatusCode};
 use actix_web::{test, web};
 use rabbithole::JSON_API_HEADER;
@@ -8,45 +6,66 @@ use rabbithole::JSON_API_HEADER;
 classes_init!();
 fetching_init!();
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/#content-negotiation-servers
-fn invalid_accept_header_test() {
-    let (path, mut app) = init_app!(1, 0);
+async fn invalid_accept_header_test() {
+    let (path, app) = init_app!(1, 0);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
 }
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/#content-negotiation-servers
-fn invalid_content_type_test() {
-    let (path, mut app) = init_app!(1, 0);
+async fn invalid_content_type_test() {
+    let (path, app) = init_app!(1, 0);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::ACCEPT, JSON_API_HEADER)
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
 }
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/#content-negotiation-servers
-fn invalid_content_type_params_test() {
-    let (path, mut app) = init_app!(1, 0);
+async fn invalid_content_type_params_test() {
+    let (path, app) = init_app!(1, 0);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::ACCEPT, JSON_API_HEADER)
-        .header(
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .insert_header((
             header::CONTENT_TYPE,
             format!(r#"{}; profile="cursor-pagination""#, JSON_API_HEADER),
-        )
+        ))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
 }
+
+#[actix_web::test]
+/// https://jsonapi.org/format/#content-negotiation-servers
+async fn accept_header_list_test() {
+    let (path, app) = init_app!(1, 0);
+    for (accept, status) in &[
+        (format!("application/json, {}", JSON_API_HEADER), StatusCode::OK),
+        ("application/*".to_string(), StatusCode::OK),
+        (
+            format!(r#"{}; profile="cursor-pagination""#, JSON_API_HEADER),
+            StatusCode::NOT_ACCEPTABLE,
+        ),
+        ("application/json".to_string(), StatusCode::NOT_ACCEPTABLE),
+        ("not a media type".to_string(), StatusCode::NOT_ACCEPTABLE),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/people/1", path))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, accept.as_str()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), *status, "Accept: {}", accept);
+    }
+}
@@ -1,52 +1,96 @@
 use crate::{classes_init, fetching_init, init_app};
-use actix_web::dev::Service;
-use actix_web::dev::ServiceResponse;
 use actix_web::http::{header, StatusCode};
 use actix_web::{test, web};
+use rabbithole::model::document::{Document, DocumentItem};
 use rabbithole::JSON_API_HEADER;
 
 classes_init!();
 fetching_init!();
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/1.1/#content-negotiation-servers
-fn invalid_accept_header_test() {
-    let (path, mut app) = init_app!(1, 1);
+async fn invalid_accept_header_test() {
+    let (path, app) = init_app!(1, 1);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
 }
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/1.1/#content-negotiation-servers
-fn invalid_content_type_test() {
-    let (path, mut app) = init_app!(1, 1);
+async fn invalid_content_type_test() {
+    let (path, app) = init_app!(1, 1);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::ACCEPT, JSON_API_HEADER)
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
 }
 
-#[test]
+#[actix_web::test]
 /// https://jsonapi.org/format/1.1/#content-negotiation-servers
-fn invalid_content_type_params_test() {
-    let (path, mut app) = init_app!(1, 1);
+async fn invalid_content_type_params_test() {
+    let (path, app) = init_app!(1, 1);
     let req = test::TestRequest::get()
         .uri(&format!("{}/people/1", path))
-        .header(header::ACCEPT, format!(r#"{}; profile="cursor-pagination""#, JSON_API_HEADER))
-        .header(
+        .insert_header((
+            header::ACCEPT,
+            format!(r#"{}; profile="cursor-pagination""#, JSON_API_HEADER),
+        ))
+        .insert_header((
             header::CONTENT_TYPE,
             format!(r#"{}; profile="cursor-pagination""#, JSON_API_HEADER),
-        )
+        ))
         .to_request();
-    let future = test::run_on(|| app.call(req));
-    let resp: ServiceResponse = test::block_on(future).unwrap();
+    let resp = test::call_service(&app, req).await;
     assert_eq!(resp.status(), StatusCode::OK);
 }
+
+#[actix_web::test]
+/// https://jsonapi.org/format/1.1/#content-negotiation-servers
+async fn weighted_accept_header_test() {
+    let (path, app) = init_app!(1, 1);
+    for (accept, status) in &[
+        (format!("text/html, {}; q=0.8", JSON_API_HEADER), StatusCode::OK),
+        (format!("{}; q=0.5, */*; q=0.1", JSON_API_HEADER), StatusCode::OK),
+        // The wildcards don't accept JSON:API excluded by a more specific range
+        (format!("{}; q=0, */*; q=0.1", JSON_API_HEADER), StatusCode::NOT_ACCEPTABLE),
+        (format!("{}; q=0", JSON_API_HEADER), StatusCode::NOT_ACCEPTABLE),
+        (format!("{}; ext=unknown", JSON_API_HEADER), StatusCode::NOT_ACCEPTABLE),
+    ] {
+        let req = test::TestRequest::get()
+            .uri(&format!("{}/people/1", path))
+            .insert_header((header::CONTENT_TYPE, JSON_API_HEADER))
+            .insert_header((header::ACCEPT, accept.as_str()))
+            .to_request();
+        let resp = test::call_service(&app, req).await;
+        assert_eq!(resp.status(), *status, "Accept: {}", accept);
+    }
+}
+
+#[actix_web::test]
+/// https://jsonapi.org/format/1.1/#content-negotiation-servers
+async fn non_utf8_header_test() {
+    let (path, app) = init_app!(1, 1);
+    let req = test::TestRequest::get()
+        .uri(&format!("{}/people/1", path))
+        .insert_header((
+            header::CONTENT_TYPE,
+            header::HeaderValue::from_bytes(b"\xffjson").unwrap(),
+        ))
+        .insert_header((header::ACCEPT, JSON_API_HEADER))
+        .to_request();
+    let resp = test::call_service(&app, req).await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let body: Document = serde_json::from_slice(&test::read_body(resp).await).unwrap();
+    if let DocumentItem::Errors(errors) = body.item {
+        assert_eq!(errors[0].code, Some("RBH-0301".into()));
+    } else {
+        unreachable!("Expect error document");
+    }
+}
@@ -0,0 +1,192 @@
+use rabbithole::access_log::AccessLog;
+use rabbithole::handler::{self, RouteMatching};
+use rabbithole::model::meta::MetaNamespace;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::openapi::{self, OpenApiInfo};
+use rabbithole::operation::Fetching;
+use rabbithole::query::limits::QueryLimits;
+use rabbithole::rule::conformance::ConformanceLevel;
+use rabbithole::rule::profile::ProfileRegistry;
+use rabbithole::rule::registry::RuleRegistry;
+use rabbithole::rule::Strictness;
+use rabbithole::service::JsonApiService;
+use rabbithole::tenant::TenantSource;
+
+use poem::http::{Method, Uri};
+use poem::{async_trait, Endpoint, Request, Response};
+use std::sync::Arc;
+
+/// A poem endpoint serving the routes of all of the resource types added by `resource`, which are:
+///   - `GET /<ty>`
+///   - `GET /<ty>/<id>`
+///   - `GET /<ty>/<id>/relationships/<related_field>`
+///   - `GET /<ty>/<id>/<related_field>`
+///
+/// under the path of the base URI, where it's nested:
+/// ```ignore
+/// let jsonapi = JsonApiEndpoint::new("http://localhost:3000/api", version)?
+///     .resource(HumanService)
+///     .with_openapi("Pets", "1.0.0");
+/// // The spec of the poem-openapi routes with the JSON:API ones
+/// let api_service = OpenApiService::new(PetApi, "Pets", "1.0.0");
+/// let mut spec: serde_json::Value = serde_json::from_str(&api_service.spec())?;
+/// jsonapi.merge_openapi(&mut spec);
+/// let app = Route::new().nest("/api", jsonapi).nest("/pets", api_service);
+/// ```
+/// The other methods are answered with `405 Method Not Allowed`, and the unknown paths with
+/// `404 Not Found`, both as error documents
+#[derive(Clone)]
+pub struct JsonApiEndpoint {
+    service: JsonApiService,
+    /// Serves the OpenAPI document at `<path>/openapi.json` if given
+    openapi: Option<OpenApiInfo>,
+}
+
+impl JsonApiEndpoint {
+    /// The endpoint mounted at `uri`, which is the base of the links like `http://localhost/api`
+    pub fn new(uri: &str, version: JsonApiVersion) -> Result<Self, url::ParseError> {
+        Ok(Self { service: JsonApiService::new(uri, version)?, openapi: None })
+    }
+
+    pub fn with_header_rules(mut self, header_rules: Strictness) -> Self {
+        self.service = self.service.with_header_rules(header_rules);
+        self
+    }
+
+    /// Advertises and requires the profiles of `profiles` in JSON:API v1.1
+    pub fn with_profiles(mut self, profiles: ProfileRegistry) -> Self {
+        self.service = self.service.with_profiles(profiles);
+        self
+    }
+
+    /// Strips the internals of the `5xx` errors from the error documents if `redact_errors`
+    pub fn with_redacted_errors(mut self, redact_errors: bool) -> Self {
+        self.service = self.service.with_redacted_errors(redact_errors);
+        self
+    }
+
+    /// Checks the requests and the documents against the specification with `conformance`
+    pub fn with_conformance(mut self, conformance: ConformanceLevel) -> Self {
+        self.service = self.service.with_conformance(conformance);
+        self
+    }
+
+    /// Allows the implementation-specific query parameters of `parameters` in the `strict`
+    /// conformance level
+    pub fn with_query_parameters(mut self, parameters: Vec<String>) -> Self {
+        self.service = self.service.with_query_parameters(parameters);
+        self
+    }
+
+    /// Rejects the queries over the limits of `query_limits` with `400`
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.service = self.service.with_query_limits(query_limits);
+        self
+    }
+
+    /// Logs the requests with the secrets masked by `access_log`
+    pub fn with_access_log(mut self, access_log: AccessLog) -> Self {
+        self.service = self.service.with_access_log(access_log);
+        self
+    }
+
+    /// Nests the members of `meta` added by the crate and the application under `meta_namespace`
+    pub fn with_meta_namespace(mut self, meta_namespace: MetaNamespace) -> Self {
+        self.service = self.service.with_meta_namespace(meta_namespace);
+        self
+    }
+
+    /// Matches the paths with the tolerances of `route_matching`, like `/Humans/` for `/humans`
+    pub fn with_route_matching(mut self, route_matching: RouteMatching) -> Self {
+        self.service = self.service.with_route_matching(route_matching);
+        self
+    }
+
+    /// Extracts the tenants of the requests from `source`, scoping the links to them
+    pub fn with_tenant(mut self, source: TenantSource) -> Self {
+        self.service = self.service.with_tenant(source);
+        self
+    }
+
+    /// Checks the requests and the documents with the custom rules of `rules`
+    pub fn with_rules(mut self, rules: RuleRegistry) -> Self {
+        self.service = self.service.with_rules(rules);
+        self
+    }
+
+    /// Adds the resource type of `service`, replacing the one of the same type if any
+    pub fn resource<T>(mut self, service: impl Into<Arc<T>>) -> Self
+    where
+        T: 'static + Fetching,
+    {
+        self.service = self.service.resource(service);
+        self
+    }
+
+    /// Serves the OpenAPI document of all of the resource types at `<path>/openapi.json`
+    pub fn with_openapi(mut self, title: &str, version: &str) -> Self {
+        self.openapi = Some(OpenApiInfo { title: title.into(), version: version.into() });
+        self
+    }
+
+    /// The OpenAPI 3 document of all of the resource types, where if it's not enabled by
+    /// `with_openapi`, the title is the base URI and the version is the JSON:API version
+    pub fn openapi(&self) -> serde_json::Value {
+        let info = self.openapi.clone().unwrap_or_else(|| OpenApiInfo {
+            title: self.service.uri.to_string(),
+            version: self.service.jsonapi.version.to_string(),
+        });
+        self.service.openapi(&info)
+    }
+
+    /// Merges the paths and the schemas of the resource types into `spec`, like the one generated
+    /// by poem-openapi for the other routes, where the paths are prefixed by the path of the base
+    /// URI
+    pub fn merge_openapi(&self, spec: &mut serde_json::Value) {
+        let mut doc = self.openapi();
+        let prefix = self.base_path();
+        if let Some(paths) = doc["paths"].as_object() {
+            let paths =
+                paths.iter().map(|(path, item)| (format!("{}{}", prefix, path), item.clone()));
+            doc["paths"] = serde_json::Value::Object(paths.collect());
+        }
+        openapi::merge(spec, &doc);
+    }
+
+    fn base_path(&self) -> &str { self.service.uri.path().trim_end_matches('/') }
+
+    fn openapi_path(&self) -> String { format!("{}/openapi.json", self.base_path()) }
+}
+
+#[async_trait]
+impl Endpoint for JsonApiEndpoint {
+    type Output = Response;
+
+    async fn call(&self, req: Request) -> poem::Result<Self::Output> {
+        // The path of the nested endpoint is relative to the path of the base URI
+        let path = req.uri().path_and_query().map_or("/", |path| path.as_str());
+        let uri: Uri = format!("{}{}", self.base_path(), path).parse().unwrap_or_default();
+        if self.openapi.is_some() && uri.path() == self.openapi_path() {
+            if req.method() != Method::GET {
+                return Ok(from_http(handler::method_not_allowed(req.method(), "GET")));
+            }
+            return Ok(Response::builder()
+                .content_type("application/json")
+                .body(self.openapi().to_string()));
+        }
+
+        let mut http_req = poem::http::Request::new(());
+        *http_req.method_mut() = req.method().clone();
+        *http_req.uri_mut() = uri;
+        *http_req.headers_mut() = req.headers().clone();
+        Ok(from_http(self.service.handle(http_req).await))
+    }
+}
+
+/// Converts a response of `rabbithole::handler` into the one of poem
+fn from_http(resp: poem::http::Response<Vec<u8>>) -> Response {
+    let (parts, body) = resp.into_parts();
+    let mut resp = Response::builder().status(parts.status).body(body);
+    *resp.headers_mut() = parts.headers;
+    resp
+}
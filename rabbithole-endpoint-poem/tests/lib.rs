@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+use poem::http::{header, Method, StatusCode};
+use poem::test::{TestClient, TestResponse};
+use poem::{Endpoint, Route};
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::Strictness;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_poem::JsonApiEndpoint;
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id_code: uuid::Uuid,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: uuid::Uuid,
+    pub name: String,
+}
+
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+fn humans() -> Vec<Human> {
+    ["Alice", "Bob"]
+        .iter()
+        .map(|name| Human {
+            id_code: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            dogs: vec![Dog { id: uuid::Uuid::new_v4(), name: format!("Dog of {}", name) }],
+        })
+        .collect()
+}
+
+fn jsonapi(humans: Vec<Human>) -> JsonApiEndpoint {
+    JsonApiEndpoint::new("http://localhost:3000/api", JsonApiVersion { major: 1, minor: 0 })
+        .unwrap()
+        .resource(HumanStore(humans))
+}
+
+async fn call<E: Endpoint>(client: &TestClient<E>, method: Method, path: &str) -> TestResponse {
+    client
+        .request(method, path)
+        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .header(header::ACCEPT, JSON_API_HEADER)
+        .send()
+        .await
+}
+
+async fn body(resp: TestResponse) -> serde_json::Value {
+    serde_json::from_slice(&resp.0.into_body().into_vec().await.unwrap()).unwrap()
+}
+
+fn error_code(body: serde_json::Value) -> Option<String> {
+    let doc: Document = serde_json::from_value(body).unwrap();
+    match doc.item {
+        DocumentItem::Errors(errors) => errors[0].code.clone(),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn routes_test() {
+    let humans = humans();
+    let id = humans[0].id_code.to_string();
+    let client = TestClient::new(Route::new().nest("/api", jsonapi(humans)));
+
+    for (path, status) in &[
+        ("/api/people".to_string(), StatusCode::OK),
+        (format!("/api/people/{}", id), StatusCode::OK),
+        (format!("/api/people/{}/relationships/dogs", id), StatusCode::OK),
+        (format!("/api/people/{}/dogs", id), StatusCode::OK),
+        (format!("/api/people/{}/cats", id), StatusCode::NOT_FOUND),
+    ] {
+        let resp = call(&client, Method::GET, path).await;
+        resp.assert_status(*status);
+        resp.assert_header(header::CONTENT_TYPE, JSON_API_HEADER);
+    }
+
+    let body = body(call(&client, Method::GET, "/api/people?sort=-name").await).await;
+    assert_eq!(body["links"]["self"], "http://localhost:3000/api/people?sort=-name");
+    let names: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Bob", "Alice"]);
+}
+
+#[tokio::test]
+async fn errors_test() {
+    let client = TestClient::new(Route::new().nest("/api", jsonapi(humans())));
+
+    let resp = client.get("/api/people").send().await;
+    resp.assert_status(StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let resp = call(&client, Method::GET, "/api/cats").await;
+    resp.assert_status(StatusCode::NOT_FOUND);
+    assert_eq!(error_code(body(resp).await).as_deref(), Some("RBH-0008"));
+
+    let resp = call(&client, Method::DELETE, "/api/people/1").await;
+    resp.assert_status(StatusCode::METHOD_NOT_ALLOWED);
+    resp.assert_header(header::ALLOW, "GET, HEAD, OPTIONS");
+    assert_eq!(error_code(body(resp).await).as_deref(), Some("RBH-0007"));
+
+    let resp = call(&client, Method::HEAD, "/api/people").await;
+    resp.assert_status_is_ok();
+    resp.assert_header(header::CONTENT_TYPE, JSON_API_HEADER);
+    assert!(resp.0.into_body().into_vec().await.unwrap().is_empty());
+
+    let resp = call(&client, Method::OPTIONS, "/api/people").await;
+    resp.assert_status(StatusCode::NO_CONTENT);
+    resp.assert_header(header::ALLOW, "GET, HEAD, OPTIONS");
+
+    let jsonapi = jsonapi(humans()).with_header_rules(Strictness::Off);
+    let client = TestClient::new(Route::new().nest("/api", jsonapi));
+    client.get("/api/people").send().await.assert_status_is_ok();
+}
+
+#[tokio::test]
+async fn openapi_test() {
+    let jsonapi = jsonapi(humans()).with_openapi("Pets", "2.1.0");
+    let doc = jsonapi.openapi();
+    assert_eq!(doc["info"]["title"], "Pets");
+    assert_eq!(doc["servers"][0]["url"], "http://localhost:3000/api");
+    assert_eq!(doc["paths"]["/people/{id}"]["get"]["operationId"], "fetch_single_people");
+
+    let client = TestClient::new(Route::new().nest("/api", jsonapi.clone()));
+    let resp = client.get("/api/openapi.json").send().await;
+    resp.assert_status_is_ok();
+    assert_eq!(body(resp).await, doc);
+
+    // Like the spec of poem-openapi, with its own routes and schemas
+    let mut spec = serde_json::json!({
+        "openapi": "3.0.0",
+        "info": { "title": "Pets", "version": "2.1.0" },
+        "paths": { "/pets": { "get": { "operationId": "list_pets" } } },
+        "components": { "schemas": { "Pet": { "type": "object" } } },
+    });
+    jsonapi.merge_openapi(&mut spec);
+    assert_eq!(spec["paths"]["/pets"]["get"]["operationId"], "list_pets");
+    assert_eq!(spec["paths"]["/api/people"]["get"]["operationId"], "fetch_collection_people");
+    assert!(spec["components"]["schemas"]["Pet"].is_object());
+    assert!(spec["components"]["schemas"]["Document"].is_object());
+}
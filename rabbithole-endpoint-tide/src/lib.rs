@@ -0,0 +1,250 @@
+pub mod settings;
+
+use rabbithole::cache::{CacheKey, DocumentCache};
+use rabbithole::entity::SingleEntity;
+use rabbithole::handler::{self, JsonApiSettings, OperationRequest, Route};
+use rabbithole::model::error;
+use rabbithole::operation::{Fetching, OperationContext};
+use rabbithole::RbhResult;
+
+use std::convert::TryFrom;
+use std::sync::Arc;
+use std::time::Instant;
+use tide::http::headers::{HeaderName, ACCEPT, CONTENT_TYPE};
+use tide::{Body, Request, Response, StatusCode};
+
+use crate::settings::TideSettingsModel;
+
+/// The settings and the service of a resource type, shared by all of the endpoints
+pub struct TideSettings<T>
+where
+    T: 'static + Fetching,
+{
+    pub path: String,
+    /// The base of the links, which is `<external_url or http://host:port><path>`
+    pub uri: url::Url,
+    pub jsonapi: JsonApiSettings,
+    pub service: Arc<T>,
+    /// Answers the operations with the documents cached in it
+    pub cache: Option<Arc<dyn DocumentCache>>,
+}
+
+impl<T> Clone for TideSettings<T>
+where
+    T: 'static + Fetching,
+{
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            uri: self.uri.clone(),
+            jsonapi: self.jsonapi.clone(),
+            service: self.service.clone(),
+            cache: self.cache.clone(),
+        }
+    }
+}
+
+impl<T> TideSettings<T>
+where
+    T: 'static + Fetching,
+{
+    pub fn new(
+        settings: TideSettingsModel, service: impl Into<Arc<T>>,
+    ) -> Result<Self, url::ParseError> {
+        let mut uri = match &settings.external_url {
+            Some(external_url) => external_url.parse::<url::Url>()?,
+            None => format!("http://{}:{}", settings.host, settings.port).parse::<url::Url>()?,
+        };
+        uri.set_path(&format!("{}{}", uri.path().trim_end_matches('/'), settings.path));
+        Ok(Self {
+            path: settings.path,
+            uri,
+            jsonapi: settings.jsonapi,
+            service: service.into(),
+            cache: None,
+        })
+    }
+
+    /// Answers the operations with the documents in `cache`, which is invalidated by the
+    /// `ResourceChanged` events if it's subscribed to the `EventBus` of the services
+    pub fn with_cache(mut self, cache: Arc<dyn DocumentCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Registers all of the routes of the resource type at `<path>/<ty>` on `app`, which are:
+    ///   - `GET /<ty>`
+    ///   - `GET /<ty>/:id`
+    ///   - `GET /<ty>/:id/relationships/:related_field`
+    ///   - `GET /<ty>/:id/:related_field`
+    ///
+    /// with `HEAD` answering the headers of `GET` without the body and `OPTIONS` the `Allow`
+    /// header, where the other methods are answered with `405 Method Not Allowed`, and the paths
+    /// with a trailing slash are registered as well if `trailing_slash` of `route_matching` is set.
+    /// NOTICE: The router of `tide` matches the paths exactly, so `case_insensitive_types` of
+    /// `route_matching` is not supported, and rejected by `TideSettingsModel::validate`
+    pub fn register<S>(self, app: &mut tide::Server<S>)
+    where
+        S: 'static + Clone + Send + Sync,
+    {
+        let base = format!("{}/{}", self.path.trim_end_matches('/'), T::Item::ty());
+        let settings = Arc::new(self);
+
+        let suffixes: &[&str] =
+            if settings.jsonapi.route_matching.trailing_slash { &["", "/"] } else { &[""] };
+        for suffix in suffixes {
+            settings.serve(app.at(&format!("{}{}", base, suffix)), collection);
+            settings.serve(app.at(&format!("{}/:id{}", base, suffix)), single);
+            settings.serve(
+                app.at(&format!("{}/:id/relationships/:related_field{}", base, suffix)),
+                relationship,
+            );
+            settings.serve(app.at(&format!("{}/:id/:related_field{}", base, suffix)), related);
+        }
+    }
+
+    /// Answers `GET` and `HEAD` on `route` with the route of the operation given by `route_of`,
+    /// and the other methods with `other_methods`
+    fn serve<S>(
+        self: &Arc<Self>, mut route: tide::Route<'_, S>,
+        route_of: fn(&Request<S>) -> RbhResult<Route>,
+    ) where
+        S: 'static + Clone + Send + Sync,
+    {
+        let settings = self.clone();
+        let endpoint = move |req: Request<S>| {
+            let settings = settings.clone();
+            async move { Ok(settings.respond(&req, route_of(&req)).await) }
+        };
+        route.all(other_methods).get(endpoint.clone()).head(endpoint);
+    }
+
+    /// Answers `req` with `route`, logging it if `access_log` is set
+    async fn respond<S>(&self, req: &Request<S>, route: RbhResult<Route>) -> Response {
+        let started = Instant::now();
+        let mut resp = self.fetch(req, route).await.unwrap_or_else(handler::error_response);
+        handler::apply_deprecation(T::Item::deprecation().as_ref(), resp.headers_mut());
+        self.jsonapi.apply_cache_control(&T::Item::ty(), &mut resp);
+        let method = http_method(req);
+        handler::strip_head_body(&method, &mut resp);
+        let headers: Vec<(&str, &str)> = req
+            .iter()
+            .flat_map(|(name, values)| {
+                values.iter().map(move |value| (name.as_str(), value.as_str()))
+            })
+            .collect();
+        let status = resp.status().as_u16();
+        let uri = request_uri(req);
+        self.jsonapi.log_access(method.as_str(), &uri, status, started.elapsed(), headers);
+        into_response(resp)
+    }
+
+    async fn fetch<S>(
+        &self, req: &Request<S>, route: RbhResult<Route>,
+    ) -> RbhResult<http::Response<Vec<u8>>> {
+        let route = route?;
+        let uri = self.jsonapi.route_matching.canonical_uri(
+            &self.path,
+            &request_uri(req),
+            &T::Item::ty(),
+        );
+        let accept = header_value(req, ACCEPT);
+        let query =
+            handler::check_request(&self.jsonapi, &header_value(req, CONTENT_TYPE), &accept, &uri)?;
+        let mut context = operation_context(req);
+        let (tenant, base) = self.jsonapi.tenant_of(&self.uri, &uri, &context.headers)?;
+        context.tenant = tenant;
+        let req = OperationRequest {
+            context,
+            query,
+            base: base.as_str().trim_end_matches('/').to_string(),
+            request_path: uri.into(),
+        };
+        let key = CacheKey::new(&T::Item::ty(), &route, &req);
+        let fetched = handler::dispatch(&*self.service, &route, &req);
+        let (doc, last_modified) = handler::cached(self.cache.as_deref(), key, fetched)
+            .await
+            .and_then(|mut doc| {
+                handler::check_document(&self.jsonapi, &doc, &req.query)?;
+                req.query.sort_included(&mut doc);
+                let last_modified = handler::last_modified_of(&doc);
+                Ok((self.jsonapi.namespaced(doc), last_modified))
+            })
+            .map_err(|err| self.jsonapi.redacted(err))?;
+        let headers = &req.context.headers;
+        handler::conditional_response(&self.jsonapi, headers, &doc, None, last_modified)
+    }
+}
+
+/// The path and the query of the request
+fn request_uri<S>(req: &Request<S>) -> http::Uri {
+    let url = req.url();
+    let uri = match url.query() {
+        Some(query) => format!("{}?{}", url.path(), query),
+        None => url.path().to_string(),
+    };
+    uri.parse().unwrap_or_default()
+}
+
+fn operation_context<S>(req: &Request<S>) -> OperationContext {
+    let mut ctx = OperationContext::default();
+    for (name, values) in req.iter() {
+        for value in values {
+            let name = http::header::HeaderName::from_bytes(name.as_str().as_bytes());
+            let value = http::HeaderValue::from_str(value.as_str());
+            if let (Ok(name), Ok(value)) = (name, value) {
+                ctx.headers.append(name, value);
+            }
+        }
+    }
+    ctx
+}
+
+fn collection<S>(_: &Request<S>) -> RbhResult<Route> { Ok(Route::Collection) }
+
+fn single<S>(req: &Request<S>) -> RbhResult<Route> { Ok(Route::Single(param(req, "id")?)) }
+
+fn relationship<S>(req: &Request<S>) -> RbhResult<Route> {
+    Ok(Route::Relationship(param(req, "id")?, param(req, "related_field")?))
+}
+
+fn related<S>(req: &Request<S>) -> RbhResult<Route> {
+    Ok(Route::Related(param(req, "id")?, param(req, "related_field")?))
+}
+
+fn param<S>(req: &Request<S>, name: &str) -> RbhResult<String> {
+    req.param(name).map(ToString::to_string).map_err(|_| error::Error::InternalServerError(None))
+}
+
+/// All of the values of the header `name` joined by `, `, since a list header may be split into
+/// several lines
+fn header_value<S>(req: &Request<S>, name: HeaderName) -> Option<String> {
+    req.header(name)
+        .map(|values| values.iter().map(|value| value.as_str()).collect::<Vec<&str>>().join(", "))
+}
+
+/// The method of the request, which is `GET` if it's unknown to `http`
+fn http_method<S>(req: &Request<S>) -> http::Method {
+    http::Method::from_bytes(req.method().to_string().as_bytes()).unwrap_or_default()
+}
+
+/// `OPTIONS`, and the methods of the operations which are not implemented, like `POST` and
+/// `DELETE`
+async fn other_methods<S>(req: Request<S>) -> tide::Result {
+    Ok(into_response(handler::other_method_response(&http_method(&req))))
+}
+
+/// Converts a response of `rabbithole::handler` into the one of tide
+fn into_response(resp: http::Response<Vec<u8>>) -> Response {
+    let (parts, body) = resp.into_parts();
+    let status =
+        StatusCode::try_from(parts.status.as_u16()).unwrap_or(StatusCode::InternalServerError);
+    let mut resp = Response::new(status);
+    for (name, value) in parts.headers.iter() {
+        if let Ok(value) = value.to_str() {
+            resp.append_header(name.as_str(), value);
+        }
+    }
+    resp.set_body(Body::from_bytes(body));
+    resp
+}
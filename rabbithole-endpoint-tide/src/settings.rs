@@ -0,0 +1,74 @@
+use config::{Config, ConfigError, Environment, File};
+use serde::Deserialize;
+
+pub use rabbithole::handler::JsonApiSettings;
+
+/// The prefix of the environment variables overriding the settings, where `__` separates the
+/// nested keys, like `RBH__PORT` and `RBH__JSONAPI__VERSION`
+pub const ENV_PREFIX: &str = "RBH__";
+
+/// The settings of the tide endpoint, in the same shape as the ones of the actix endpoint
+#[derive(Debug, Deserialize, Clone)]
+pub struct TideSettingsModel {
+    pub host: String,
+    pub port: u32,
+    pub path: String,
+    pub jsonapi: JsonApiSettings,
+    /// The URL where the server root is exposed to the clients, like `https://example.com/prefix`,
+    /// used in the links instead of `http://host:port`
+    #[serde(default)]
+    pub external_url: Option<String>,
+}
+
+impl TideSettingsModel {
+    /// Loads the settings from the layered sources, where the latter ones override the former:
+    ///   1. The config file `file` (with any extension supported by `config`), if given
+    ///   2. The environment variables with `ENV_PREFIX`
+    ///   3. The `overrides` like `("jsonapi.version", "1.1")`
+    pub fn load(file: Option<&str>, overrides: &[(&str, &str)]) -> Result<Self, ConfigError> {
+        let mut settings = Config::default();
+        if let Some(file) = file {
+            settings.merge(File::with_name(file))?;
+        }
+        // `config` appends a `_` to the prefix
+        settings.merge(
+            Environment::with_prefix(&ENV_PREFIX[.. ENV_PREFIX.len() - 1]).separator("__"),
+        )?;
+        for (key, value) in overrides {
+            settings.set(key, *value)?;
+        }
+        let settings: Self = settings.try_into()?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    /// Checks the values which are well-typed but cannot be used, like `case_insensitive_types` of
+    /// `route_matching`, as the router of `tide` matches the paths exactly
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.host.is_empty() {
+            return Err(invalid_value("host", "it must not be empty"));
+        }
+        if self.port > u32::from(u16::MAX) {
+            return Err(invalid_value("port", &format!("{} is not a valid port", self.port)));
+        }
+        if !self.path.is_empty() && !self.path.starts_with('/') {
+            return Err(invalid_value("path", &format!("`{}` must start with `/`", self.path)));
+        }
+        if let Some(external_url) = &self.external_url {
+            if let Err(err) = external_url.parse::<url::Url>() {
+                return Err(invalid_value("external_url", &format!("`{}`: {}", external_url, err)));
+            }
+        }
+        if self.jsonapi.route_matching.case_insensitive_types {
+            return Err(invalid_value(
+                "jsonapi.route_matching.case_insensitive_types",
+                "the router of tide matches the type segments exactly",
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn invalid_value(key: &str, reason: &str) -> ConfigError {
+    ConfigError::Message(format!("invalid value for key `{}`: {}", key, reason))
+}
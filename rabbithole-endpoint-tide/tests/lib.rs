@@ -0,0 +1,181 @@
+use async_trait::async_trait;
+use rabbithole::cache::{DocumentCache, LruDocumentCache};
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_tide::settings::TideSettingsModel;
+use rabbithole_endpoint_tide::TideSettings;
+use std::sync::Arc;
+use tide::http::{Method, Request, Response, StatusCode, Url};
+
+const FILE: &str = "config/tide.config.test.toml";
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id_code: uuid::Uuid,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: uuid::Uuid,
+    pub name: String,
+}
+
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+fn humans() -> Vec<Human> {
+    ["Alice", "Bob"]
+        .iter()
+        .map(|name| Human {
+            id_code: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            dogs: vec![Dog { id: uuid::Uuid::new_v4(), name: format!("Dog of {}", name) }],
+        })
+        .collect()
+}
+
+fn app(settings: TideSettingsModel, humans: Vec<Human>) -> tide::Server<()> {
+    let mut app = tide::new();
+    TideSettings::new(settings, HumanStore(humans)).unwrap().register(&mut app);
+    app
+}
+
+async fn call(app: &tide::Server<()>, method: Method, path: &str, json_api: bool) -> Response {
+    let mut req = Request::new(method, Url::parse(&format!("http://localhost{}", path)).unwrap());
+    if json_api {
+        req.insert_header("Content-Type", JSON_API_HEADER);
+        req.insert_header("Accept", JSON_API_HEADER);
+    }
+    app.respond(req).await.unwrap()
+}
+
+async fn error_code(resp: &mut Response) -> Option<String> {
+    let doc: Document = serde_json::from_str(&resp.body_string().await.unwrap()).unwrap();
+    match doc.item {
+        DocumentItem::Errors(errors) => errors[0].code.clone(),
+        _ => None,
+    }
+}
+
+#[async_std::test]
+async fn routes_test() {
+    let humans = humans();
+    let id = humans[0].id_code.to_string();
+    let app = app(TideSettingsModel::load(Some(FILE), &[]).unwrap(), humans);
+
+    for (path, status) in &[
+        ("/api/v1/people".to_string(), StatusCode::Ok),
+        (format!("/api/v1/people/{}", id), StatusCode::Ok),
+        (format!("/api/v1/people/{}/relationships/dogs", id), StatusCode::Ok),
+        (format!("/api/v1/people/{}/dogs", id), StatusCode::Ok),
+        (format!("/api/v1/people/{}/cats", id), StatusCode::NotFound),
+    ] {
+        let resp = call(&app, Method::Get, path, true).await;
+        assert_eq!(resp.status(), *status, "GET {}", path);
+        assert_eq!(resp["Content-Type"], JSON_API_HEADER);
+    }
+
+    let mut resp = call(&app, Method::Get, "/api/v1/people?sort=-name", true).await;
+    let body: serde_json::Value = serde_json::from_str(&resp.body_string().await.unwrap()).unwrap();
+    assert_eq!(body["links"]["self"], "http://localhost:1234/api/v1/people?sort=-name");
+    let names: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Bob", "Alice"]);
+
+    let mut resp = call(&app, Method::Get, &format!("/api/v1/people/{}", id), true).await;
+    let body: serde_json::Value = serde_json::from_str(&resp.body_string().await.unwrap()).unwrap();
+    assert_eq!(
+        body["data"]["links"]["self"],
+        format!("http://localhost:1234/api/v1/people/{}", id)
+    );
+}
+
+#[async_std::test]
+async fn conditional_test() {
+    let app = app(TideSettingsModel::load(Some(FILE), &[]).unwrap(), humans());
+    let resp = call(&app, Method::Get, "/api/v1/people", true).await;
+    let etag = resp["ETag"].as_str().to_string();
+
+    let url = Url::parse("http://localhost/api/v1/people").unwrap();
+    let mut req = Request::new(Method::Get, url);
+    req.insert_header("Content-Type", JSON_API_HEADER);
+    req.insert_header("Accept", JSON_API_HEADER);
+    req.insert_header("If-None-Match", etag.as_str());
+    let resp: Response = app.respond(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NotModified);
+    assert_eq!(resp["ETag"], etag.as_str());
+}
+
+#[async_std::test]
+async fn cache_test() {
+    let cache = Arc::new(LruDocumentCache::new(8));
+    let mut app = tide::new();
+    TideSettings::new(TideSettingsModel::load(Some(FILE), &[]).unwrap(), HumanStore(humans()))
+        .unwrap()
+        .with_cache(cache.clone())
+        .register(&mut app);
+    let mut resp = call(&app, Method::Get, "/api/v1/people", true).await;
+    assert_eq!(resp.status(), StatusCode::Ok);
+    assert_eq!(cache.len(), 1);
+
+    let mut cached = call(&app, Method::Get, "/api/v1/people", true).await;
+    assert_eq!(cached.body_string().await.unwrap(), resp.body_string().await.unwrap());
+    cache.invalidate("dogs").await;
+    assert!(cache.is_empty());
+}
+
+#[async_std::test]
+async fn errors_test() {
+    let app = app(TideSettingsModel::load(Some(FILE), &[]).unwrap(), humans());
+
+    let resp = call(&app, Method::Get, "/api/v1/people", false).await;
+    assert_eq!(resp.status(), StatusCode::UnsupportedMediaType);
+
+    let mut resp = call(&app, Method::Delete, "/api/v1/people/1", true).await;
+    assert_eq!(resp.status(), StatusCode::MethodNotAllowed);
+    assert_eq!(resp["Allow"], "GET, HEAD, OPTIONS");
+    assert_eq!(error_code(&mut resp).await.as_deref(), Some("RBH-0007"));
+
+    let mut resp = call(&app, Method::Head, "/api/v1/people", true).await;
+    assert_eq!(resp.status(), StatusCode::Ok);
+    assert_eq!(resp["Content-Type"], JSON_API_HEADER);
+    assert!(resp.body_string().await.unwrap().is_empty());
+
+    let resp = call(&app, Method::Options, "/api/v1/people", true).await;
+    assert_eq!(resp.status(), StatusCode::NoContent);
+    assert_eq!(resp["Allow"], "GET, HEAD, OPTIONS");
+
+    let settings = TideSettingsModel::load(Some(FILE), &[("jsonapi.header_rules", "off")]).unwrap();
+    let app = self::app(settings, humans());
+    let resp = call(&app, Method::Get, "/api/v1/people", false).await;
+    assert_eq!(resp.status(), StatusCode::Ok);
+
+    let err = TideSettingsModel::load(Some(FILE), &[("path", "api")]).unwrap_err();
+    assert!(err.to_string().contains("`path`"), "{}", err);
+    let key = "jsonapi.route_matching.case_insensitive_types";
+    let err = TideSettingsModel::load(Some(FILE), &[(key, "true")]).unwrap_err();
+    assert!(err.to_string().contains(key), "{}", err);
+}
@@ -0,0 +1,333 @@
+use rabbithole::access_log::AccessLog;
+use rabbithole::cache::{CacheKey, DocumentCache};
+use rabbithole::entity::SingleEntity;
+use rabbithole::handler::{self, JsonApiSettings, OperationRequest, Route, RouteMatching};
+use rabbithole::model::error;
+use rabbithole::model::meta::MetaNamespace;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::operation::{Fetching, OperationContext};
+use rabbithole::query::limits::QueryLimits;
+use rabbithole::rule::conformance::ConformanceLevel;
+use rabbithole::rule::profile::ProfileRegistry;
+use rabbithole::rule::registry::RuleRegistry;
+use rabbithole::rule::Strictness;
+use rabbithole::tenant::TenantSource;
+use rabbithole::RbhResult;
+
+use std::convert::Infallible;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Instant;
+use warp::filters::BoxedFilter;
+use warp::http::{header, HeaderMap, Method, Uri};
+use warp::path::FullPath;
+use warp::reply::Response;
+use warp::{Filter, Rejection};
+
+/// The settings of the resource types served by `jsonapi_resource`
+#[derive(Clone)]
+pub struct WarpSettings {
+    /// The base of the links, which is where the filters are mounted, like `http://localhost/api`
+    pub uri: url::Url,
+    pub jsonapi: JsonApiSettings,
+    /// Answers the operations with the documents cached in it
+    pub cache: Option<Arc<dyn DocumentCache>>,
+}
+
+impl fmt::Debug for WarpSettings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WarpSettings")
+            .field("uri", &self.uri)
+            .field("jsonapi", &self.jsonapi)
+            .field("cache", &self.cache.is_some())
+            .finish()
+    }
+}
+
+impl WarpSettings {
+    pub fn new(uri: &str, version: JsonApiVersion) -> Result<Self, url::ParseError> {
+        Ok(Self { uri: uri.parse()?, jsonapi: JsonApiSettings::new(version), cache: None })
+    }
+
+    pub fn with_header_rules(mut self, header_rules: Strictness) -> Self {
+        self.jsonapi.header_rules = header_rules;
+        self
+    }
+
+    /// Advertises and requires the profiles of `profiles` in JSON:API v1.1
+    pub fn with_profiles(mut self, profiles: ProfileRegistry) -> Self {
+        self.jsonapi.profiles = profiles;
+        self
+    }
+
+    /// Strips the internals of the `5xx` errors from the error documents if `redact_errors`
+    pub fn with_redacted_errors(mut self, redact_errors: bool) -> Self {
+        self.jsonapi.redact_errors = redact_errors;
+        self
+    }
+
+    /// Checks the requests and the documents against the specification with `conformance`
+    pub fn with_conformance(mut self, conformance: ConformanceLevel) -> Self {
+        self.jsonapi.conformance = conformance;
+        self
+    }
+
+    /// Allows the implementation-specific query parameters of `parameters` in the `strict`
+    /// conformance level
+    pub fn with_query_parameters(mut self, parameters: Vec<String>) -> Self {
+        self.jsonapi.query_parameters = parameters;
+        self
+    }
+
+    /// Rejects the queries over the limits of `query_limits` with `400`
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.jsonapi.query_limits = query_limits;
+        self
+    }
+
+    /// Logs the requests with the secrets masked by `access_log`
+    pub fn with_access_log(mut self, access_log: AccessLog) -> Self {
+        self.jsonapi.access_log = Some(access_log);
+        self
+    }
+
+    /// Nests the members of `meta` added by the crate and the application under `meta_namespace`
+    pub fn with_meta_namespace(mut self, meta_namespace: MetaNamespace) -> Self {
+        self.jsonapi.meta_namespace = Some(meta_namespace);
+        self
+    }
+
+    /// Matches the type segments with the tolerances of `route_matching`, like `/Humans` for
+    /// `/humans`
+    pub fn with_route_matching(mut self, route_matching: RouteMatching) -> Self {
+        self.jsonapi.route_matching = route_matching;
+        self
+    }
+
+    /// Extracts the tenants of the requests from `source`, scoping the links to them
+    pub fn with_tenant(mut self, source: TenantSource) -> Self {
+        self.jsonapi.tenant = Some(source);
+        self
+    }
+
+    /// Checks the requests and the documents with the custom rules of `rules`
+    pub fn with_rules(mut self, rules: RuleRegistry) -> Self {
+        self.jsonapi.rules = rules;
+        self
+    }
+
+    /// Answers the operations with the documents in `cache`, which is invalidated by the
+    /// `ResourceChanged` events if it's subscribed to the `EventBus` of the services
+    pub fn with_cache(mut self, cache: Arc<dyn DocumentCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+}
+
+/// The parts of a request used by the operations
+struct Request {
+    method: Method,
+    uri: Uri,
+    headers: HeaderMap,
+}
+
+impl Request {
+    fn new(method: Method, path: FullPath, query: String, headers: HeaderMap) -> Self {
+        let uri = if query.is_empty() {
+            path.as_str().parse()
+        } else {
+            format!("{}?{}", path.as_str(), query).parse()
+        };
+        Self { method, uri: uri.unwrap_or_default(), headers }
+    }
+}
+
+struct Resource<T> {
+    service: Arc<T>,
+    settings: WarpSettings,
+}
+
+/// All of the routes of the resource type of `service` under `/<ty>`, which are:
+///   - `GET /<ty>`
+///   - `GET /<ty>/<id>`
+///   - `GET /<ty>/<id>/relationships/<related_field>`
+///   - `GET /<ty>/<id>/<related_field>`
+///
+/// with `HEAD` answering the headers of `GET` without the body and `OPTIONS` the `Allow` header.
+/// The type segment is matched with the tolerances of `route_matching`, while a trailing slash is
+/// always ignored by the path filters of `warp`, and the `self` links are canonical either way.
+/// The errors of the operations and the other methods are answered as error documents, while the
+/// rejections, like the ones of the unknown paths, are left to `recover`:
+/// ```ignore
+/// let routes = warp::path("api")
+///     .and(jsonapi_resource(HumanService, settings.clone()).or(jsonapi_resource(DogService, settings)))
+///     .recover(rabbithole_endpoint_warp::recover);
+/// ```
+pub fn jsonapi_resource<T>(
+    service: impl Into<Arc<T>>, settings: WarpSettings,
+) -> BoxedFilter<(Response,)>
+where
+    T: 'static + Fetching,
+{
+    let matching = settings.jsonapi.route_matching;
+    let resource = Arc::new(Resource { service: service.into(), settings });
+    let resource = warp::any().map(move || resource.clone());
+    let query = warp::query::raw().or(warp::any().map(String::new)).unify();
+    let request = warp::get()
+        .or(warp::head())
+        .unify()
+        .and(warp::method())
+        .and(warp::path::full())
+        .and(query)
+        .and(warp::header::headers_cloned())
+        .map(Request::new);
+    let base = type_segment(T::Item::ty(), matching).and(resource).and(request);
+
+    let collection = base.clone().and(warp::path::end()).then(
+        |resource: Arc<Resource<T>>, req: Request| async move {
+            resource.respond(Route::Collection, &req).await
+        },
+    );
+    let single = base.clone().and(warp::path::param::<String>()).and(warp::path::end()).then(
+        |resource: Arc<Resource<T>>, req: Request, id: String| async move {
+            resource.respond(Route::Single(id), &req).await
+        },
+    );
+    let relationship = base
+        .clone()
+        .and(warp::path::param::<String>())
+        .and(warp::path("relationships"))
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .then(|resource: Arc<Resource<T>>, req: Request, id: String, related_field: String| {
+            async move { resource.respond(Route::Relationship(id, related_field), &req).await }
+        });
+    let related = base
+        .and(warp::path::param::<String>())
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .then(|resource: Arc<Resource<T>>, req: Request, id: String, related_field: String| {
+            async move { resource.respond(Route::Related(id, related_field), &req).await }
+        });
+
+    let other_methods = type_segment(T::Item::ty(), matching)
+        .and(warp::path::tail())
+        .and(warp::method())
+        .and_then(|_, method: Method| async move {
+            if handler::is_fetching(&method) {
+                Err(warp::reject::not_found())
+            } else {
+                Ok(handler::other_method_response(&method).map(Into::into))
+            }
+        });
+
+    collection
+        .or(single)
+        .unify()
+        .or(relationship)
+        .unify()
+        .or(related)
+        .unify()
+        .or(other_methods)
+        .unify()
+        .boxed()
+}
+
+impl<T> Resource<T>
+where
+    T: 'static + Fetching,
+{
+    /// Answers `req` with `route`, logging it if `access_log` is set
+    async fn respond(&self, route: Route, req: &Request) -> Response {
+        let started = Instant::now();
+        let mut resp = self.fetch(route, req).await.unwrap_or_else(handler::error_response);
+        handler::apply_deprecation(T::Item::deprecation().as_ref(), resp.headers_mut());
+        self.settings.jsonapi.apply_cache_control(&T::Item::ty(), &mut resp);
+        handler::strip_head_body(&req.method, &mut resp);
+        let headers = req
+            .headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+        let status = resp.status().as_u16();
+        let method = req.method.as_str();
+        self.settings.jsonapi.log_access(method, &req.uri, status, started.elapsed(), headers);
+        resp.map(Into::into)
+    }
+
+    async fn fetch(&self, route: Route, req: &Request) -> RbhResult<warp::http::Response<Vec<u8>>> {
+        let uri = self.settings.jsonapi.route_matching.canonical_uri(
+            self.settings.uri.path(),
+            &req.uri,
+            &T::Item::ty(),
+        );
+        let query = handler::check_request(
+            &self.settings.jsonapi,
+            &handler::header_value(&req.headers, header::CONTENT_TYPE),
+            &handler::header_value(&req.headers, header::ACCEPT),
+            &uri,
+        )?;
+        let (tenant, base) =
+            self.settings.jsonapi.tenant_of(&self.settings.uri, &req.uri, &req.headers)?;
+        let req = OperationRequest {
+            context: OperationContext {
+                headers: req.headers.clone(),
+                tenant,
+                ..Default::default()
+            },
+            query,
+            base: base.as_str().trim_end_matches('/').to_string(),
+            request_path: uri.into(),
+        };
+        let key = CacheKey::new(&T::Item::ty(), &route, &req);
+        let fetched = handler::dispatch(&*self.service, &route, &req);
+        let (doc, last_modified) = handler::cached(self.settings.cache.as_deref(), key, fetched)
+            .await
+            .and_then(|mut doc| {
+                handler::check_document(&self.settings.jsonapi, &doc, &req.query)?;
+                req.query.sort_included(&mut doc);
+                let last_modified = handler::last_modified_of(&doc);
+                Ok((self.settings.jsonapi.namespaced(doc), last_modified))
+            })
+            .map_err(|err| self.settings.jsonapi.redacted(err))?;
+        handler::conditional_response(
+            &self.settings.jsonapi,
+            &req.context.headers,
+            &doc,
+            None,
+            last_modified,
+        )
+    }
+}
+
+/// The filter of the type segment `ty`, matched with the tolerances of `matching`
+fn type_segment(ty: String, matching: RouteMatching) -> BoxedFilter<()> {
+    warp::path::param::<String>()
+        .and_then(move |segment: String| {
+            let matched = matching.matches_type(&segment, &ty);
+            async move {
+                match matched {
+                    true => Ok(()),
+                    false => Err(warp::reject::not_found()),
+                }
+            }
+        })
+        .untuple_one()
+        .boxed()
+}
+
+/// Converts the rejections of the filters into error documents, like the `404 Not Found` of the
+/// unknown paths, to be used with `Filter::recover`
+pub async fn recover(rejection: Rejection) -> Result<Response, Infallible> {
+    // A method mismatch of one route is reported when no other route matches the request,
+    // while the other methods of the JSON:API routes are answered by `jsonapi_resource` itself
+    let err = if rejection.is_not_found()
+        || rejection.find::<warp::reject::MethodNotAllowed>().is_some()
+    {
+        error::Error::RouteNotFound(None)
+    } else {
+        error::Error::InternalServerError(None)
+    };
+    Ok(error_response(err))
+}
+
+fn error_response(err: error::Error) -> Response { handler::error_response(err).map(Into::into) }
@@ -0,0 +1,204 @@
+use async_trait::async_trait;
+use rabbithole::cache::{DocumentCache, LruDocumentCache};
+use rabbithole::handler::RouteMatching;
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::Strictness;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_endpoint_warp::{jsonapi_resource, recover, WarpSettings};
+use std::sync::Arc;
+use warp::http::{header, StatusCode};
+use warp::Filter;
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id_code: uuid::Uuid,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: uuid::Uuid,
+    pub name: String,
+}
+
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+fn humans() -> Vec<Human> {
+    ["Alice", "Bob"]
+        .iter()
+        .map(|name| Human {
+            id_code: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            dogs: vec![Dog { id: uuid::Uuid::new_v4(), name: format!("Dog of {}", name) }],
+        })
+        .collect()
+}
+
+fn settings() -> WarpSettings {
+    WarpSettings::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 }).unwrap()
+}
+
+fn request(method: &str, path: &str) -> warp::test::RequestBuilder {
+    warp::test::request()
+        .method(method)
+        .path(path)
+        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .header(header::ACCEPT, JSON_API_HEADER)
+}
+
+fn error_code(body: &[u8]) -> Option<String> {
+    let doc: Document = serde_json::from_slice(body).unwrap();
+    match doc.item {
+        DocumentItem::Errors(errors) => errors[0].code.clone(),
+        _ => None,
+    }
+}
+
+#[tokio::test]
+async fn routes_test() {
+    let humans = humans();
+    let id = humans[0].id_code.to_string();
+    let api = warp::path("api").and(jsonapi_resource(HumanStore(humans), settings()));
+
+    for (path, status) in &[
+        ("/api/people".to_string(), StatusCode::OK),
+        ("/api/people?sort=-name".to_string(), StatusCode::OK),
+        (format!("/api/people/{}", id), StatusCode::OK),
+        (format!("/api/people/{}/relationships/dogs", id), StatusCode::OK),
+        (format!("/api/people/{}/dogs", id), StatusCode::OK),
+        (format!("/api/people/{}/cats", id), StatusCode::NOT_FOUND),
+    ] {
+        let resp = request("GET", path).reply(&api).await;
+        assert_eq!(resp.status(), *status, "GET {}", path);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], JSON_API_HEADER);
+    }
+
+    let resp = request("GET", "/api/people?sort=-name").reply(&api).await;
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["links"]["self"], "http://localhost:8080/api/people?sort=-name");
+    let names: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Bob", "Alice"]);
+
+    let resp = request("GET", &format!("/api/people/{}", id)).reply(&api).await;
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["data"]["links"]["self"], format!("http://localhost:8080/api/people/{}", id));
+}
+
+#[tokio::test]
+async fn conditional_test() {
+    let api = warp::path("api").and(jsonapi_resource(HumanStore(humans()), settings()));
+    let resp = request("GET", "/api/people").reply(&api).await;
+    let etag = resp.headers()[header::ETAG].clone();
+
+    let resp =
+        request("GET", "/api/people").header(header::IF_NONE_MATCH, etag.clone()).reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()[header::ETAG], etag);
+    assert!(resp.body().is_empty());
+}
+
+#[tokio::test]
+async fn cache_test() {
+    let cache = Arc::new(LruDocumentCache::new(8));
+    let settings = settings().with_cache(cache.clone());
+    let api = warp::path("api").and(jsonapi_resource(HumanStore(humans()), settings));
+    let resp = request("GET", "/api/people").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(cache.len(), 1);
+
+    let cached = request("GET", "/api/people").reply(&api).await;
+    assert_eq!(cached.body(), resp.body());
+    cache.invalidate("dogs").await;
+    assert!(cache.is_empty());
+}
+
+#[tokio::test]
+async fn route_matching_test() {
+    let humans = humans();
+    let lenient = settings().with_route_matching(RouteMatching::lenient());
+    let api = warp::path("api").and(jsonapi_resource(HumanStore(humans), lenient)).recover(recover);
+
+    for (path, link) in &[
+        ("/api/People/", "http://localhost:8080/api/people"),
+        ("/api/PEOPLE?sort=-name", "http://localhost:8080/api/people?sort=-name"),
+    ] {
+        let resp = request("GET", path).reply(&api).await;
+        assert_eq!(resp.status(), StatusCode::OK, "GET {}", path);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["links"]["self"], *link, "GET {}", path);
+    }
+
+    let api =
+        warp::path("api").and(jsonapi_resource(HumanStore(vec![]), settings())).recover(recover);
+    let resp = request("GET", "/api/People").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn errors_test() {
+    let api =
+        warp::path("api").and(jsonapi_resource(HumanStore(humans()), settings())).recover(recover);
+
+    let resp = warp::test::request()
+        .path("/api/people")
+        .header(header::ACCEPT, JSON_API_HEADER)
+        .reply(&api)
+        .await;
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    let resp = warp::test::request()
+        .path("/api/people")
+        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .reply(&api)
+        .await;
+    assert_eq!(resp.status(), StatusCode::NOT_ACCEPTABLE);
+
+    let resp = request("GET", "/api/people/none").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp = request("GET", "/api/cats").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0008"));
+
+    let resp = request("DELETE", "/api/people/1").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+    assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0007"));
+
+    let resp = request("HEAD", "/api/people").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], JSON_API_HEADER);
+    assert!(resp.body().is_empty());
+
+    let resp = request("OPTIONS", "/api/people").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+
+    let settings = settings().with_header_rules(Strictness::Off);
+    let api = warp::path("api").and(jsonapi_resource(HumanStore(humans()), settings));
+    let resp = warp::test::request().path("/api/people").reply(&api).await;
+    assert_eq!(resp.status(), StatusCode::OK);
+}
@@ -0,0 +1,375 @@
+//! A GraphQL schema over the `Fetching` services, so a service layer serves both of the protocols.
+//! Each resource type `<ty>` gets an object type with its `id`, `type`, `attributes`,
+//! `relationships` and `links`, and two query fields:
+//!   - `<ty>(filter: String, sort: String, page: Page, fields: [String!]): [<Ty>!]!`, where
+//!     `filter` is an RSQL expression, and the others are the same as the JSON:API parameters
+//!   - `<ty>ById(id: ID!, fields: [String!]): <Ty>`
+//!
+//! The attributes, the relationships and the links are `JSON` scalars, since the entities carry
+//! no schema of their attributes. Mutations are not mapped, as there are no mutation operations
+//! to map yet:
+//! ```ignore
+//! let schema = GraphQlSchema::new("http://localhost/api").resource(HumanService).finish();
+//! let ctx = GraphQlContext { operation: OperationContext { headers, ..Default::default() } };
+//! let (value, errors) =
+//!     juniper::execute("{ people(sort: \"-name\") { id attributes } }", None, &schema, &Variables::new(), &ctx)
+//!         .await?;
+//! ```
+
+use juniper::meta::MetaType;
+use juniper::{
+    Arguments, BoxFuture, DefaultScalarValue, EmptyMutation, EmptySubscription, ExecutionResult,
+    Executor, FieldError, FieldResult, GraphQLInputObject, GraphQLScalar, GraphQLType,
+    GraphQLValue, GraphQLValueAsync, Registry, RootNode, Value, ID,
+};
+use percent_encoding::{utf8_percent_encode, NON_ALPHANUMERIC};
+use rabbithole::entity::SingleEntity;
+use rabbithole::handler::{self, OperationRequest};
+use rabbithole::model::error;
+use rabbithole::operation::{Fetching, OperationContext};
+use rabbithole::query::Query;
+use rabbithole::RbhResult;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+/// The schema built by `GraphQlSchema::finish`
+pub type Schema =
+    RootNode<'static, QueryRoot, EmptyMutation<GraphQlContext>, EmptySubscription<GraphQlContext>>;
+
+/// The context of a GraphQL request, carrying the `OperationContext` of the operations
+#[derive(Debug, Clone, Default)]
+pub struct GraphQlContext {
+    pub operation: OperationContext,
+}
+
+impl juniper::Context for GraphQlContext {}
+
+/// Builds the GraphQL schema of the resource types added by `resource`
+#[derive(Clone)]
+pub struct GraphQlSchema {
+    /// The base of the links in the resources, like `http://localhost/api`
+    uri: String,
+    resources: Vec<Arc<dyn Resource>>,
+}
+
+impl GraphQlSchema {
+    pub fn new(uri: &str) -> Self {
+        Self { uri: uri.trim_end_matches('/').to_string(), resources: Default::default() }
+    }
+
+    /// Adds the resource type of `service`
+    pub fn resource<T>(mut self, service: impl Into<Arc<T>>) -> Self
+    where
+        T: 'static + Fetching,
+    {
+        self.resources.push(Arc::new(FetchingResource(service.into())));
+        self
+    }
+
+    pub fn finish(self) -> Schema {
+        RootNode::new_with_info(
+            QueryRoot,
+            EmptyMutation::new(),
+            EmptySubscription::new(),
+            self,
+            (),
+            (),
+        )
+    }
+
+    /// The resource type of the query field `field_name`, and if it's the single one
+    fn field(&self, field_name: &str) -> Option<(&Arc<dyn Resource>, bool)> {
+        self.resources.iter().find_map(|resource| {
+            let name = field_name_of(&resource.ty());
+            if field_name == name {
+                Some((resource, false))
+            } else if field_name.strip_suffix("ById") == Some(&name) {
+                Some((resource, true))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// The pagination of a collection, the same as the `page` parameters of JSON:API
+#[derive(Debug, Clone, Default, GraphQLInputObject)]
+pub struct Page {
+    pub offset: Option<i32>,
+    pub limit: Option<i32>,
+    pub number: Option<i32>,
+    pub size: Option<i32>,
+    pub cursor: Option<String>,
+}
+
+/// Any JSON value, like the attributes of a resource
+#[derive(Debug, Clone, GraphQLScalar)]
+#[graphql(name = "JSON", with = json_scalar, parse_token(String))]
+pub struct Json(pub serde_json::Value);
+
+mod json_scalar {
+    use super::Json;
+    use juniper::{InputValue, ScalarValue, Value};
+    use std::convert::TryFrom;
+
+    pub(super) fn to_output<S: ScalarValue>(v: &Json) -> Value<S> { to_value(&v.0) }
+
+    /// The input is a string of the JSON
+    pub(super) fn from_input<S: ScalarValue>(v: &InputValue<S>) -> Result<Json, String> {
+        let json =
+            v.as_string_value().ok_or_else(|| format!("Expected a JSON string, found: {}", v))?;
+        serde_json::from_str(json).map(Json).map_err(|err| err.to_string())
+    }
+
+    pub(super) fn to_value<S: ScalarValue>(json: &serde_json::Value) -> Value<S> {
+        match json {
+            serde_json::Value::Null => Value::null(),
+            serde_json::Value::Bool(b) => Value::scalar(*b),
+            serde_json::Value::Number(n) => {
+                match n.as_i64().filter(|n| i32::try_from(*n).is_ok()) {
+                    Some(n) => Value::scalar(n as i32),
+                    None => Value::scalar(n.as_f64().unwrap_or_default()),
+                }
+            },
+            serde_json::Value::String(s) => Value::scalar(s.clone()),
+            serde_json::Value::Array(values) => Value::list(values.iter().map(to_value).collect()),
+            serde_json::Value::Object(map) => Value::object(
+                map.iter().map(|(key, value)| (key.as_str(), to_value(value))).collect(),
+            ),
+        }
+    }
+}
+
+/// The `Query` type with the query fields of all of the resource types
+pub struct QueryRoot;
+
+impl GraphQLType<DefaultScalarValue> for QueryRoot {
+    fn name(_: &GraphQlSchema) -> Option<&str> { Some("Query") }
+
+    fn meta<'r>(
+        info: &GraphQlSchema, registry: &mut Registry<'r, DefaultScalarValue>,
+    ) -> MetaType<'r, DefaultScalarValue>
+    where
+        DefaultScalarValue: 'r,
+    {
+        let mut fields = Vec::new();
+        for resource in &info.resources {
+            let ty = resource.ty();
+            let type_name = type_name_of(&ty);
+            let field_name = field_name_of(&ty);
+            fields.push(
+                registry
+                    .field::<Vec<ResourceObject>>(&field_name, &type_name)
+                    .argument(registry.arg::<Option<String>>("filter", &()))
+                    .argument(registry.arg::<Option<String>>("sort", &()))
+                    .argument(registry.arg::<Option<Page>>("page", &()))
+                    .argument(registry.arg::<Option<Vec<String>>>("fields", &())),
+            );
+            fields.push(
+                registry
+                    .field::<Option<ResourceObject>>(&format!("{}ById", field_name), &type_name)
+                    .argument(registry.arg::<ID>("id", &()))
+                    .argument(registry.arg::<Option<Vec<String>>>("fields", &())),
+            );
+        }
+        registry.build_object_type::<Self>(info, &fields).into_meta()
+    }
+}
+
+impl GraphQLValue<DefaultScalarValue> for QueryRoot {
+    type Context = GraphQlContext;
+    type TypeInfo = GraphQlSchema;
+
+    fn type_name<'i>(&self, info: &'i GraphQlSchema) -> Option<&'i str> {
+        <Self as GraphQLType>::name(info)
+    }
+}
+
+impl GraphQLValueAsync<DefaultScalarValue> for QueryRoot {
+    fn resolve_field_async<'a>(
+        &'a self, info: &'a GraphQlSchema, field_name: &'a str, args: &'a Arguments,
+        executor: &'a Executor<GraphQlContext>,
+    ) -> BoxFuture<'a, ExecutionResult> {
+        Box::pin(async move {
+            let (resource, single) = info
+                .field(field_name)
+                .ok_or_else(|| FieldError::from(format!("Unknown field: {}", field_name)))?;
+            let type_name = type_name_of(&resource.ty());
+            let req = operation_request(&resource.ty(), &info.uri, args, executor.context())?;
+            if single {
+                let id = args.get::<ID>("id")?.map(|id| id.to_string()).unwrap_or_default();
+                let item = resource.fetch_single(&id, &req).await.map_err(into_error)?;
+                executor.resolve_async(&type_name, &item.map(ResourceObject)).await
+            } else {
+                let items = resource.fetch_collection(&req).await.map_err(into_error)?;
+                let items: Vec<ResourceObject> = items.into_iter().map(ResourceObject).collect();
+                executor.resolve_async(&type_name, &items).await
+            }
+        })
+    }
+}
+
+/// A resource in its JSON:API JSON, whose type info is the name of its GraphQL type
+pub struct ResourceObject(serde_json::Value);
+
+impl GraphQLType<DefaultScalarValue> for ResourceObject {
+    fn name(info: &String) -> Option<&str> { Some(info) }
+
+    fn meta<'r>(
+        info: &String, registry: &mut Registry<'r, DefaultScalarValue>,
+    ) -> MetaType<'r, DefaultScalarValue>
+    where
+        DefaultScalarValue: 'r,
+    {
+        let fields = [
+            registry.field::<ID>("id", &()),
+            registry.field::<String>("type", &()),
+            registry.field::<Json>("attributes", &()),
+            registry.field::<Json>("relationships", &()),
+            registry.field::<Json>("links", &()),
+        ];
+        registry.build_object_type::<Self>(info, &fields).into_meta()
+    }
+}
+
+impl GraphQLValue<DefaultScalarValue> for ResourceObject {
+    type Context = GraphQlContext;
+    type TypeInfo = String;
+
+    fn type_name<'i>(&self, info: &'i String) -> Option<&'i str> { Some(info) }
+
+    fn resolve_field(
+        &self, _: &String, field_name: &str, _: &Arguments, _: &Executor<GraphQlContext>,
+    ) -> ExecutionResult {
+        let value = self.0.get(field_name).cloned().unwrap_or_else(|| serde_json::json!({}));
+        Ok(json_scalar::to_value(&value))
+    }
+}
+
+impl GraphQLValueAsync<DefaultScalarValue> for ResourceObject {
+    fn resolve_field_async<'a>(
+        &'a self, info: &'a String, field_name: &'a str, args: &'a Arguments,
+        executor: &'a Executor<GraphQlContext>,
+    ) -> BoxFuture<'a, ExecutionResult> {
+        Box::pin(std::future::ready(self.resolve_field(info, field_name, args, executor)))
+    }
+}
+
+/// The request of the operations, where the arguments of the field are mapped to the query like
+/// `filter[@type]=Rsql&filter[<ty>]=<filter>&sort=<sort>&page[<key>]=<value>&fields[<ty>]=<fields>`
+fn operation_request(
+    ty: &str, uri: &str, args: &Arguments, ctx: &GraphQlContext,
+) -> FieldResult<OperationRequest> {
+    let mut params = Vec::new();
+    if let Some(filter) = args.get::<String>("filter")? {
+        params.push(("filter[@type]".to_string(), "Rsql".to_string()));
+        params.push((format!("filter[{}]", ty), filter));
+    }
+    if let Some(sort) = args.get::<String>("sort")? {
+        params.push(("sort".to_string(), sort));
+    }
+    if let Some(page) = args.get::<Page>("page")? {
+        let numbers = [
+            ("offset", page.offset),
+            ("limit", page.limit),
+            ("number", page.number),
+            ("size", page.size),
+        ];
+        for (key, value) in numbers.iter().filter_map(|(key, value)| Some((key, (*value)?))) {
+            params.push((format!("page[{}]", key), value.to_string()));
+        }
+        if let Some(cursor) = page.cursor {
+            params.push(("page[cursor]".to_string(), cursor));
+        }
+    }
+    if let Some(fields) = args.get::<Vec<String>>("fields")? {
+        params.push((format!("fields[{}]", ty), fields.join(",")));
+    }
+
+    let query: Vec<String> = params
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, utf8_percent_encode(value, NON_ALPHANUMERIC)))
+        .collect();
+    let request_path: http::Uri =
+        format!("/{}?{}", ty, query.join("&")).parse().map_err(|err| format!("{}", err))?;
+    Ok(OperationRequest {
+        context: ctx.operation.clone(),
+        query: Query::from_uri(&request_path).map_err(into_error)?,
+        base: uri.to_string(),
+        request_path: request_path.into(),
+    })
+}
+
+/// The GraphQL error of `err`, carrying its `status` and `code` in the extensions
+fn into_error(err: error::Error) -> FieldError {
+    let message = err.detail.clone().or_else(|| err.title.clone()).unwrap_or_default();
+    let mut extensions = juniper::Object::with_capacity(2);
+    if let Some(status) = err.status {
+        extensions.add_field("status", Value::scalar(status));
+    }
+    if let Some(code) = err.code {
+        extensions.add_field("code", Value::scalar(code));
+    }
+    FieldError::new(message, Value::Object(extensions))
+}
+
+/// `people` -> `People`, `dog-owners` -> `DogOwners`
+fn type_name_of(ty: &str) -> String {
+    ty.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word[.. 1].to_ascii_uppercase() + &word[1 ..])
+        .collect()
+}
+
+/// `people` -> `people`, `dog-owners` -> `dogOwners`
+fn field_name_of(ty: &str) -> String {
+    let type_name = type_name_of(ty);
+    match type_name.chars().next() {
+        Some(first) => first.to_ascii_lowercase().to_string() + &type_name[1 ..],
+        None => type_name,
+    }
+}
+
+/// The operations of a resource type, without the type of its service, returning the JSON of
+/// the resources, where a collection is filtered, sorted and paged by `Fetching::vec_to_document`
+#[async_trait]
+trait Resource: Send + Sync {
+    fn ty(&self) -> String;
+
+    async fn fetch_collection(&self, req: &OperationRequest) -> RbhResult<Vec<serde_json::Value>>;
+
+    async fn fetch_single(
+        &self, id: &str, req: &OperationRequest,
+    ) -> RbhResult<Option<serde_json::Value>>;
+}
+
+struct FetchingResource<T>(Arc<T>);
+
+#[async_trait]
+impl<T> Resource for FetchingResource<T>
+where
+    T: 'static + Fetching,
+{
+    fn ty(&self) -> String { T::Item::ty() }
+
+    async fn fetch_collection(&self, req: &OperationRequest) -> RbhResult<Vec<serde_json::Value>> {
+        let items = handler::fetch_collection(&*self.0, req).await?;
+        let doc = self.0.vec_to_document(&items, &req.base, &req.query, &req.request_path).await?;
+        match handler::to_json(&doc)?.get_mut("data").map(serde_json::Value::take) {
+            Some(serde_json::Value::Array(data)) => Ok(data),
+            _ => Ok(vec![]),
+        }
+    }
+
+    async fn fetch_single(
+        &self, id: &str, req: &OperationRequest,
+    ) -> RbhResult<Option<serde_json::Value>> {
+        let item = handler::fetch_single(&*self.0, id, req).await?;
+        item.as_ref().map(|item| to_json(item, req)).transpose()
+    }
+}
+
+fn to_json<E: SingleEntity>(item: &E, req: &OperationRequest) -> RbhResult<serde_json::Value> {
+    handler::to_json(&item.to_resource(&req.base, &req.query.fields))
+}
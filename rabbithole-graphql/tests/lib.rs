@@ -0,0 +1,127 @@
+use async_trait::async_trait;
+use juniper::{DefaultScalarValue, ExecutionError, Variables};
+use rabbithole::model::error;
+use rabbithole::store::CollectionStore;
+use rabbithole_graphql::{GraphQlContext, GraphQlSchema, Schema};
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id_code: uuid::Uuid,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rabbithole_derive::EntityDecorator, serde::Serialize, serde::Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: uuid::Uuid,
+    pub name: String,
+}
+
+struct HumanStore(Vec<Human>);
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(self.0.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.0.iter().find(|human| human.id_code.to_string() == id).cloned())
+    }
+}
+
+fn humans() -> Vec<Human> {
+    ["Alice", "Bob"]
+        .iter()
+        .map(|name| Human {
+            id_code: uuid::Uuid::new_v4(),
+            name: name.to_string(),
+            dogs: vec![Dog { id: uuid::Uuid::new_v4(), name: format!("Dog of {}", name) }],
+        })
+        .collect()
+}
+
+fn schema(humans: Vec<Human>) -> Schema {
+    GraphQlSchema::new("http://localhost/api").resource(HumanStore(humans)).finish()
+}
+
+async fn execute(
+    schema: &Schema, query: &str,
+) -> (serde_json::Value, Vec<ExecutionError<DefaultScalarValue>>) {
+    let ctx = GraphQlContext::default();
+    let (value, errors) =
+        juniper::execute(query, None, schema, &Variables::new(), &ctx).await.unwrap();
+    (serde_json::to_value(&value).unwrap(), errors)
+}
+
+fn names(value: &serde_json::Value) -> Vec<&str> {
+    value["people"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect()
+}
+
+#[tokio::test]
+async fn query_test() {
+    let humans = humans();
+    let id = humans[0].id_code.to_string();
+    let schema = schema(humans);
+
+    let (value, errors) =
+        execute(&schema, r#"{ people(sort: "-name") { id type attributes links } }"#).await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(names(&value), vec!["Bob", "Alice"]);
+    assert_eq!(value["people"][0]["type"], "people");
+
+    let (value, errors) =
+        execute(&schema, r#"{ people(filter: "name==Ali*") { attributes } }"#).await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(names(&value), vec!["Alice"]);
+
+    let (value, errors) = execute(
+        &schema,
+        r#"{ people(sort: "name", page: { offset: 1, limit: 1 }) { attributes } }"#,
+    )
+    .await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(names(&value), vec!["Bob"]);
+
+    let query = format!(r#"{{ peopleById(id: "{}") {{ id relationships links }} }}"#, id);
+    let (value, errors) = execute(&schema, &query).await;
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(value["peopleById"]["id"], id);
+    assert_eq!(value["peopleById"]["links"]["self"], format!("http://localhost/api/people/{}", id));
+    assert!(value["peopleById"]["relationships"]["dogs"].is_object());
+
+    let (value, _) = execute(&schema, r#"{ peopleById(id: "unknown") { id } }"#).await;
+    assert!(value["peopleById"].is_null());
+}
+
+#[tokio::test]
+async fn errors_test() {
+    let schema = schema(humans());
+
+    let (_, errors) = execute(&schema, r#"{ people(filter: "name=Alice") { id } }"#).await;
+    assert_eq!(errors.len(), 1);
+    let extensions = serde_json::to_value(errors[0].error().extensions()).unwrap();
+    assert!(extensions["code"].as_str().unwrap().starts_with("RBH-"), "{}", extensions);
+
+    let (value, _) = execute(&schema, r#"{ __type(name: "People") { fields { name } } }"#).await;
+    let fields: Vec<&str> = value["__type"]["fields"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|field| field["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(fields, vec!["id", "type", "attributes", "relationships", "links"]);
+    let ctx = GraphQlContext::default();
+    let result = juniper::execute("{ dogs { id } }", None, &schema, &Variables::new(), &ctx).await;
+    assert!(result.is_err());
+}
@@ -0,0 +1,49 @@
+use serde_json::{json, Value};
+
+/// The fake value of the JSON schema `schema` of `field` of the `index`-th resource, where the
+/// schema is like the ones of `rabbithole::schema::type_schema`:
+///   - the strings are like `name 1`, or the values of their formats, like `uuid` and `date`
+///   - the numbers are the ones after `index`, and the booleans are alternate
+///   - the arrays have one item, the maps are empty, and the nullable values are `null` for every
+///     fifth resource
+///   - the values of the unknown types are `null`
+pub fn fake_value(schema: &Value, field: &str, index: usize) -> Value {
+    let nth = index + 1;
+    if schema.get("nullable") == Some(&Value::Bool(true)) && nth.is_multiple_of(5) {
+        return Value::Null;
+    }
+    match schema.get("type").and_then(Value::as_str) {
+        Some("string") => {
+            let (month, day, hour) = (index / 28 % 12 + 1, index % 28 + 1, index % 24);
+            match schema.get("format").and_then(Value::as_str) {
+                Some("uuid") => json!(format!("00000000-0000-4000-8000-{:012x}", nth)),
+                Some("date") => json!(format!("2020-{:02}-{:02}", month, day)),
+                Some("date-time") => {
+                    json!(format!("2020-{:02}-{:02}T{:02}:00:00Z", month, day, hour))
+                },
+                _ => json!(format!("{} {}", field, nth)),
+            }
+        },
+        Some("integer") => {
+            let minimum = schema.get("minimum").and_then(Value::as_i64).unwrap_or(i64::MIN);
+            json!((nth as i64).max(minimum))
+        },
+        Some("number") => json!(nth as f64 * 1.5),
+        Some("boolean") => json!(index.is_multiple_of(2)),
+        Some("array") => {
+            let items = schema.get("items").unwrap_or(&Value::Null);
+            json!([fake_value(items, field, index)])
+        },
+        Some("object") => json!({}),
+        _ => Value::Null,
+    }
+}
+
+/// The fake id of the `index`-th resource, which is a valid path segment, like `1` of the
+/// strings and the integers, or the values of their formats
+pub(crate) fn fake_id(schema: &Value, index: usize) -> Value {
+    match schema.get("type").and_then(Value::as_str) {
+        Some("string") if schema.get("format").is_none() => json!((index + 1).to_string()),
+        _ => fake_value(schema, "id", index),
+    }
+}
@@ -0,0 +1,191 @@
+//! A mock JSON:API server of the entities, serving fake but schema-valid resources, so the
+//! frontends can be developed before the real backend exists:
+//! ```ignore
+//! Mock::new("http://localhost:8080/api")?
+//!     .entity(MockStore::<Human>::fake(20)?.with_fixtures("fixtures/people.json")?)
+//!     .entity(MockStore::<Dog>::fake(50)?)
+//!     .serve(([127, 0, 0, 1], 8080).into())
+//!     .await?;
+//! ```
+//! where:
+//!   - the attributes of the fakes are generated from `SingleEntity::schema`, and are the same
+//!     across the restarts, while their relationships are empty
+//!   - the fixtures are the entities in JSON, like the ones serialized by `serde`, replacing the
+//!     fakes of the same ids, where the related resources are linked
+//!   - the collections are filtered, sorted and paged in memory like any `CollectionStore`
+
+mod fake;
+
+pub use fake::fake_value;
+
+use async_trait::async_trait;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Server};
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::Strictness;
+use rabbithole::service::JsonApiService;
+use rabbithole::store::CollectionStore;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum MockError {
+    #[error(
+        "cannot fake the resources of `{0}`: {1}, where they can be built by \
+         `MockStore::generate` or the fixtures"
+    )]
+    Fake(String, serde_json::Error),
+    #[error("cannot read the fixtures `{0}`: {1}")]
+    Fixtures(String, std::io::Error),
+    #[error("invalid fixtures `{0}`: {1}")]
+    InvalidFixtures(String, serde_json::Error),
+}
+
+/// The resources of `E` served by the mock server
+#[derive(Debug, Clone)]
+pub struct MockStore<E> {
+    items: Vec<E>,
+}
+
+impl<E: SingleEntity> MockStore<E> {
+    pub fn new(items: Vec<E>) -> Self { Self { items } }
+
+    /// `count` resources built by `f` of their indexes, for the entities which cannot be faked
+    pub fn generate(count: usize, f: impl FnMut(usize) -> E) -> Self {
+        Self { items: (0 .. count).map(f).collect() }
+    }
+
+    /// Adds `items`, replacing the ones of the same ids, where the new ones are put first
+    pub fn with_items(mut self, mut items: Vec<E>) -> Self {
+        self.items.retain(|item| items.iter().all(|new| new.id() != item.id()));
+        items.append(&mut self.items);
+        self.items = items;
+        self
+    }
+
+    pub fn items(&self) -> &[E] { &self.items }
+}
+
+impl<E: SingleEntity + DeserializeOwned> MockStore<E> {
+    /// `count` fake resources, whose ids are `1`, `2`, ..., or the values of their formats, and
+    /// attributes are the values of `fake_value` of the `i`-th resource, where the relationships
+    /// are empty and the attributes of the unknown types are `null`
+    pub fn fake(count: usize) -> Result<Self, MockError> {
+        let schema = E::schema();
+        let items = (0 .. count)
+            .map(|index| {
+                let mut json = Map::new();
+                let (id, id_schema) = &schema.id;
+                json.insert(id.clone(), fake::fake_id(id_schema, index));
+                for (field, field_schema) in &schema.attributes {
+                    json.insert(field.clone(), fake_value(field_schema, field, index));
+                }
+                for (field, _) in &schema.to_ones {
+                    json.insert(field.clone(), Value::Null);
+                }
+                for (field, _) in &schema.to_manys {
+                    json.insert(field.clone(), Value::Array(vec![]));
+                }
+                serde_json::from_value(Value::Object(json))
+                    .map_err(|err| MockError::Fake(schema.ty.clone(), err))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { items })
+    }
+
+    /// Adds the entities in the JSON array of the file `path`, replacing the ones of the same ids
+    pub fn with_fixtures(self, path: impl AsRef<Path>) -> Result<Self, MockError> {
+        let path = path.as_ref();
+        let content = std::fs::read(path)
+            .map_err(|err| MockError::Fixtures(path.display().to_string(), err))?;
+        let fixtures: Vec<E> = serde_json::from_slice(&content)
+            .map_err(|err| MockError::InvalidFixtures(path.display().to_string(), err))?;
+        Ok(self.with_items(fixtures))
+    }
+}
+
+#[async_trait]
+impl<E> CollectionStore for MockStore<E>
+where
+    E: SingleEntity + Send + Sync,
+{
+    type Item = E;
+
+    async fn all(&self) -> Result<Vec<E>, error::Error> { Ok(self.items.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<E>, error::Error> {
+        Ok(self.items.iter().find(|item| item.id() == id).cloned())
+    }
+}
+
+/// The mock server of the entities added by `entity`, where:
+///   - the header rules are `Strictness::Lenient`, for the plain `fetch` calls
+///   - the responses allow any origin, and the CORS preflight requests are answered with
+///     `204 No Content`
+#[derive(Clone)]
+pub struct Mock {
+    service: JsonApiService,
+}
+
+impl Mock {
+    /// The mock server whose links are under `uri`, like `http://localhost:8080/api`
+    pub fn new(uri: &str) -> Result<Self, url::ParseError> {
+        let service = JsonApiService::new(uri, JsonApiVersion { major: 1, minor: 0 })?
+            .with_header_rules(Strictness::Lenient);
+        Ok(Self { service })
+    }
+
+    /// Serves the resources of `store`, replacing the ones of the same type if any
+    pub fn entity<E>(mut self, store: MockStore<E>) -> Self
+    where
+        E: 'static + SingleEntity + Send + Sync,
+    {
+        self.service = self.service.resource(store);
+        self
+    }
+
+    pub fn service(&self) -> &JsonApiService { &self.service }
+
+    /// Answers `req` like the server
+    pub async fn handle<B>(&self, req: http::Request<B>) -> http::Response<Vec<u8>> {
+        let mut resp = if req.method() == http::Method::OPTIONS {
+            let mut resp = http::Response::new(vec![]);
+            *resp.status_mut() = http::StatusCode::NO_CONTENT;
+            let headers = resp.headers_mut();
+            headers.insert(
+                http::header::ACCESS_CONTROL_ALLOW_METHODS,
+                http::HeaderValue::from_static("GET, OPTIONS"),
+            );
+            headers.insert(
+                http::header::ACCESS_CONTROL_ALLOW_HEADERS,
+                http::HeaderValue::from_static("Accept, Content-Type, Authorization"),
+            );
+            resp
+        } else {
+            self.service.handle(req).await
+        };
+        resp.headers_mut()
+            .insert(http::header::ACCESS_CONTROL_ALLOW_ORIGIN, http::HeaderValue::from_static("*"));
+        resp
+    }
+
+    /// Serves the routes on `addr` until the server fails
+    pub async fn serve(self, addr: SocketAddr) -> Result<(), hyper::Error> {
+        let make_service = make_service_fn(move |_| {
+            let mock = self.clone();
+            async move {
+                Ok::<_, Infallible>(service_fn(move |req| {
+                    let mock = mock.clone();
+                    async move { Ok::<_, Infallible>(mock.handle(req).await.map(Body::from)) }
+                }))
+            }
+        });
+        Server::bind(&addr).serve(make_service).await
+    }
+}
@@ -0,0 +1,140 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use rabbithole_mock::{fake_value, Mock, MockError, MockStore};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "dogs")]
+pub struct Dog {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub good: bool,
+}
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub age: u32,
+    pub nickname: Option<String>,
+    #[entity(to_one)]
+    pub best_friend: Option<Box<Human>>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "cats")]
+pub struct Cat {
+    #[entity(id)]
+    pub id: String,
+    pub color: Color,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Color {
+    Black,
+    White,
+}
+
+fn mock() -> Mock {
+    let people = MockStore::<Human>::fake(12).unwrap().with_fixtures("tests/fixtures/people.json");
+    Mock::new("http://localhost:8080/api")
+        .unwrap()
+        .entity(people.unwrap())
+        .entity(MockStore::<Dog>::fake(3).unwrap())
+}
+
+async fn get(mock: &Mock, path: &str) -> (u16, Value) {
+    let resp = mock.handle(http::Request::get(path).body(()).unwrap()).await;
+    assert_eq!(resp.headers()["access-control-allow-origin"], "*");
+    (resp.status().as_u16(), serde_json::from_slice(resp.body()).unwrap())
+}
+
+#[test]
+fn fake_test() {
+    let dogs = MockStore::<Dog>::fake(2).unwrap();
+    assert_eq!(
+        serde_json::to_value(dogs.items()).unwrap(),
+        json!([
+            { "id": "1", "name": "name 1", "good": true },
+            { "id": "2", "name": "name 2", "good": false },
+        ])
+    );
+
+    let people = MockStore::<Human>::fake(5).unwrap();
+    let nicknames: Vec<_> = people.items().iter().map(|human| human.nickname.clone()).collect();
+    assert_eq!(nicknames[0].as_deref(), Some("nickname 1"));
+    assert_eq!(nicknames[4], None);
+    assert!(people
+        .items()
+        .iter()
+        .all(|human| human.best_friend.is_none() && human.dogs.is_empty()));
+
+    // The fixtures replace the fakes of the same ids
+    let people = people.with_fixtures("tests/fixtures/people.json").unwrap();
+    let ids: Vec<&str> = people.items().iter().map(|human| human.id.as_str()).collect();
+    assert_eq!(ids, vec!["alice", "3", "1", "2", "4", "5"]);
+
+    match MockStore::<Cat>::fake(1) {
+        Err(MockError::Fake(ty, _)) => assert_eq!(ty, "cats"),
+        _ => panic!("the colors cannot be faked"),
+    }
+    let cats = MockStore::generate(2, |i| Cat { id: i.to_string(), color: Color::Black });
+    assert_eq!(cats.items().len(), 2);
+    assert!(matches!(
+        MockStore::<Dog>::fake(0).unwrap().with_fixtures("tests/fixtures/unknown.json"),
+        Err(MockError::Fixtures(..))
+    ));
+}
+
+#[test]
+fn fake_value_test() {
+    let uuid = json!({ "type": "string", "format": "uuid" });
+    assert_eq!(fake_value(&uuid, "id", 0), "00000000-0000-4000-8000-000000000001");
+    let date = json!({ "type": "string", "format": "date-time" });
+    assert_eq!(fake_value(&date, "born", 30), "2020-02-03T06:00:00Z");
+    let scores = json!({ "type": "array", "items": { "type": "integer", "minimum": 0 } });
+    assert_eq!(fake_value(&scores, "scores", 2), json!([3]));
+    assert_eq!(fake_value(&json!({ "type": "number", "nullable": true }), "x", 1), json!(3.0));
+    assert_eq!(fake_value(&json!({}), "gender", 1), Value::Null);
+}
+
+#[tokio::test]
+async fn mock_test() {
+    let mock = mock();
+
+    // No JSON:API headers are needed
+    let (status, doc) = get(&mock, "/api/people?sort=-age&page[offset]=0&page[limit]=2").await;
+    assert_eq!(status, 200);
+    let ids: Vec<&Value> = doc["data"].as_array().unwrap().iter().map(|r| &r["id"]).collect();
+    assert_eq!(ids, vec!["alice", "12"]);
+    let included = doc["included"].as_array().unwrap();
+    assert!(included.iter().any(|r| r["type"] == "people" && r["id"] == "bob"));
+
+    let (_, doc) = get(&mock, "/api/people?filter[@type]=Rsql&filter[people]=age=lt=3").await;
+    let names: Vec<&Value> =
+        doc["data"].as_array().unwrap().iter().map(|r| &r["attributes"]["name"]).collect();
+    assert_eq!(names, vec!["name 1", "name 2"]);
+
+    let (status, doc) = get(&mock, "/api/dogs/2").await;
+    assert_eq!(status, 200);
+    assert_eq!(doc["data"]["attributes"], json!({ "name": "name 2", "good": false }));
+
+    let (status, doc) = get(&mock, "/api/people/alice/relationships/dogs").await;
+    assert_eq!(status, 200);
+    assert_eq!(doc["data"], json!([{ "type": "dogs", "id": "1" }]));
+
+    let (status, _) = get(&mock, "/api/cats").await;
+    assert_eq!(status, 404);
+
+    let preflight = http::Request::options("/api/people").body(()).unwrap();
+    let resp = mock.handle(preflight).await;
+    assert_eq!(resp.status(), 204);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "*");
+    assert_eq!(resp.headers()["access-control-allow-methods"], "GET, OPTIONS");
+}
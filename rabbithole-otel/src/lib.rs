@@ -0,0 +1,134 @@
+//! The OpenTelemetry spans of the JSON:API operations, for any of the endpoints. `TracedService`
+//! puts a span around each of the operations of a service, and with the `tracing` feature of
+//! `rabbithole`, enabled by this crate, the query parsing, the filter evaluation and the document
+//! serialization are in their spans too, all of them exported by the OpenTelemetry `layer`:
+//! ```ignore
+//! let tracer = opentelemetry_otlp::new_pipeline().tracing().install_batch(runtime::Tokio)?;
+//! tracing_subscriber::registry().with(rabbithole_otel::layer(tracer)).init();
+//!
+//! let service = JsonApiService::new(uri, version)?.resource(TracedService(HumanService));
+//! ```
+//! The spans carry the attributes:
+//!   - `rabbithole.resource_type`: the type of the resources
+//!   - `rabbithole.operation`: the operation, one of `handler::OPERATIONS`
+//!   - `rabbithole.result_count`: the number of the fetched or the filtered resources
+//!   - `otel.status_code`: `ERROR` if the operation failed
+
+use async_trait::async_trait;
+use rabbithole::entity::SingleEntity;
+use rabbithole::model::document::Document;
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::model::resource::Resource;
+use rabbithole::operation::{Action, Decision, FetchPlan, Fetching, OperationContext};
+use rabbithole::query::Query;
+use std::future::Future;
+use tracing::{field, info_span, Instrument, Span};
+use tracing_opentelemetry::{OpenTelemetryLayer, PreSampledTracer};
+use tracing_subscriber::registry::LookupSpan;
+
+pub use rabbithole::trace::{OPERATION, RESOURCE_TYPE, RESULT_COUNT};
+
+/// The layer exporting the spans with `tracer`, to be added to a `tracing_subscriber::Registry`
+pub fn layer<S, T>(tracer: T) -> OpenTelemetryLayer<S, T>
+where
+    S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+    T: 'static + opentelemetry::trace::Tracer + PreSampledTracer,
+{
+    tracing_opentelemetry::layer().with_tracer(tracer)
+}
+
+/// A service with a span around each of its operations
+pub struct TracedService<T>(pub T);
+
+impl<T: Fetching> TracedService<T> {
+    fn span(operation: &str) -> Span {
+        info_span!(
+            "rabbithole.operation",
+            rabbithole.resource_type = T::Item::ty().as_str(),
+            rabbithole.operation = operation,
+            rabbithole.result_count = field::Empty,
+            otel.status_code = field::Empty,
+        )
+    }
+
+    /// Runs `fut` in the span of `operation`, recording the number of the answered resources of
+    /// `count`, or the failure
+    async fn trace<F, R>(operation: &str, fut: F, count: impl FnOnce(&R) -> usize) -> F::Output
+    where
+        F: Future<Output = Result<R, error::Error>>,
+    {
+        let span = Self::span(operation);
+        let result = fut.instrument(span.clone()).await;
+        match &result {
+            Ok(result) => span.record(RESULT_COUNT, count(result) as i64),
+            Err(_) => span.record("otel.status_code", "ERROR"),
+        };
+        result
+    }
+}
+
+/// The number of the resources in `data` of a document
+fn data_count(doc: &serde_json::Value) -> usize {
+    match &doc["data"] {
+        serde_json::Value::Array(data) => data.len(),
+        serde_json::Value::Null => 0,
+        _ => 1,
+    }
+}
+
+#[async_trait]
+impl<T: Fetching> Fetching for TracedService<T> {
+    type Item = T::Item;
+
+    async fn vec_to_document(
+        &self, items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<Document, error::Error> {
+        let span = info_span!(
+            "rabbithole.render_document",
+            rabbithole.resource_type = T::Item::ty().as_str(),
+            rabbithole.result_count = items.len() as i64,
+        );
+        self.0.vec_to_document(items, uri, query, request_path).instrument(span).await
+    }
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        Self::trace("fetch_collection", self.0.fetch_collection(plan), Vec::len).await
+    }
+
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        Self::trace("fetch_single", self.0.fetch_single(id, plan), |item| item.iter().count()).await
+    }
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        let fut = self.0.fetch_relationship(id, related_field, uri, query, request_path);
+        Self::trace("fetch_relationship", fut, |relationship| {
+            serde_json::to_value(relationship).map(|doc| data_count(&doc)).unwrap_or_default()
+        })
+        .await
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        let fut = self.0.fetch_related(id, related_field, uri, query, request_path);
+        Self::trace("fetch_related", fut, data_count).await
+    }
+
+    async fn can(&self, ctx: &OperationContext, action: &Action, item: &Self::Item) -> Decision {
+        self.0.can(ctx, action, item).await
+    }
+
+    async fn can_include(
+        &self, ctx: &OperationContext, action: &Action, resource: &Resource,
+    ) -> Decision {
+        self.0.can_include(ctx, action, resource).await
+    }
+}
@@ -0,0 +1,104 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use async_trait::async_trait;
+use futures::executor::block_on;
+use opentelemetry::trace::{Status, TracerProvider as _};
+use opentelemetry::{Key, Value};
+use opentelemetry_sdk::export::trace::SpanData;
+use opentelemetry_sdk::testing::trace::InMemorySpanExporter;
+use opentelemetry_sdk::trace::TracerProvider;
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::service::JsonApiService;
+use rabbithole::store::CollectionStore;
+use rabbithole::JSON_API_HEADER;
+use rabbithole_otel::{TracedService, OPERATION, RESOURCE_TYPE, RESULT_COUNT};
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::layer::SubscriberExt;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+}
+
+struct HumanStore;
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        Ok(vec![Human { id: "alice".into(), name: "Alice".into() }, Human {
+            id: "bob".into(),
+            name: "Bob".into(),
+        }])
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.all().await?.into_iter().find(|human| human.id == id))
+    }
+}
+
+/// The spans of the requests of `paths`, ended in the order of their ends
+fn spans(paths: &[&str]) -> Vec<SpanData> {
+    let exporter = InMemorySpanExporter::default();
+    let provider = TracerProvider::builder().with_simple_exporter(exporter.clone()).build();
+    let subscriber =
+        tracing_subscriber::registry().with(rabbithole_otel::layer(provider.tracer("test")));
+    let service =
+        JsonApiService::new("http://localhost/api", JsonApiVersion { major: 1, minor: 0 })
+            .unwrap()
+            .resource(TracedService(HumanStore));
+    tracing::subscriber::with_default(subscriber, || {
+        for path in paths {
+            let req = http::Request::builder()
+                .uri(*path)
+                .header(http::header::CONTENT_TYPE, JSON_API_HEADER)
+                .header(http::header::ACCEPT, JSON_API_HEADER)
+                .body(())
+                .unwrap();
+            block_on(service.handle(req));
+        }
+    });
+    provider.force_flush();
+    exporter.get_finished_spans().unwrap()
+}
+
+fn attribute<'a>(span: &'a SpanData, key: &str) -> Option<&'a Value> {
+    span.attributes.iter().find(|kv| kv.key == Key::from(key.to_string())).map(|kv| &kv.value)
+}
+
+#[test]
+fn spans_test() {
+    let query = percent_encode(b"filter[@type]=Rsql&filter[people]=name==Bo*", NON_ALPHANUMERIC);
+    let spans = spans(&[&format!("/api/people?{}", query), "/api/people/carol/relationships/x"]);
+    let names: Vec<&str> = spans.iter().map(|span| span.name.as_ref()).collect();
+    assert_eq!(names, vec![
+        "rabbithole.check_headers",
+        "rabbithole.parse_query",
+        "rabbithole.filter",
+        "rabbithole.operation",
+        "rabbithole.render_document",
+        "rabbithole.serialize_document",
+        "rabbithole.serialize_document",
+        "rabbithole.check_headers",
+        "rabbithole.parse_query",
+        "rabbithole.operation",
+        "rabbithole.operation",
+        "rabbithole.serialize_document",
+    ]);
+
+    assert_eq!(attribute(&spans[2], RESULT_COUNT), Some(&Value::I64(1)));
+    assert_eq!(attribute(&spans[3], RESOURCE_TYPE), Some(&Value::from("people")));
+    assert_eq!(attribute(&spans[3], OPERATION), Some(&Value::from("fetch_collection")));
+    assert_eq!(attribute(&spans[3], RESULT_COUNT), Some(&Value::I64(1)));
+    assert_eq!(attribute(&spans[4], RESULT_COUNT), Some(&Value::I64(1)));
+    assert_eq!(attribute(&spans[9], OPERATION), Some(&Value::from("fetch_single")));
+    assert_eq!(attribute(&spans[9], RESULT_COUNT), Some(&Value::I64(0)));
+    assert_eq!(attribute(&spans[10], OPERATION), Some(&Value::from("fetch_relationship")));
+    assert!(matches!(spans[10].status, Status::Error { .. }));
+}
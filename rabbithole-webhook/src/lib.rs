@@ -0,0 +1,278 @@
+//! The webhooks of the changes of the resources, posting a JSON:API document of the changed
+//! resource to the endpoints subscribed to its type and the kind of the change, signed with
+//! HMAC-SHA256 and retried with an exponential backoff. The webhooks subscribe to the `EventBus`
+//! of the `ResourceChanged` events published by the services changing the data:
+//! ```ignore
+//! let webhooks = Webhooks::new(vec![WebhookEndpoint::new("https://example.com/hooks")
+//!     .with_types(&["people"])
+//!     .with_secret("secret")]);
+//! let events = EventBus::new().subscribe(cache.clone()).subscribe(webhooks);
+//! let event = ResourceChanged::new(ChangeKind::Created, &human, "http://localhost/api");
+//! tokio::spawn(async move { events.publish(&event).await });
+//! ```
+//! where the receivers check the `X-Rabbithole-Signature` header, which is `sha256=<hex>` of the
+//! HMAC of the body with the secret
+
+#[macro_use]
+extern crate serde_derive;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use rabbithole::entity::SingleEntity;
+use rabbithole::event::{EventSubscriber, ResourceChanged};
+use rabbithole::model::resource::ResourceIdentifier;
+use rabbithole::JSON_API_HEADER;
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const EVENT_HEADER: &str = "X-Rabbithole-Event";
+pub const SIGNATURE_HEADER: &str = "X-Rabbithole-Signature";
+
+pub use rabbithole::event::ChangeKind as EventKind;
+
+/// A change of a resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceEvent {
+    pub kind: EventKind,
+    pub ty: String,
+    pub id: String,
+    /// The document posted to the endpoints, where `data` is the resource, or its identifier if
+    /// it's deleted, and `meta.event` is the kind
+    pub payload: serde_json::Value,
+}
+
+impl ResourceEvent {
+    /// The event of `item`, rendered with all of its fields, and the links under `uri`
+    pub fn new<E: SingleEntity>(kind: EventKind, item: &E, uri: &str) -> Self {
+        Self::from(&ResourceChanged::new(kind, item, uri))
+    }
+}
+
+impl From<&ResourceChanged> for ResourceEvent {
+    fn from(event: &ResourceChanged) -> Self {
+        let data = if event.kind == EventKind::Deleted {
+            serde_json::to_value(ResourceIdentifier::new(&event.ty, &event.id))
+        } else {
+            serde_json::to_value(&event.resource)
+        };
+        Self {
+            kind: event.kind,
+            ty: event.ty.clone(),
+            id: event.id.clone(),
+            payload: serde_json::json!({
+                "data": data.unwrap_or_default(),
+                "meta": { "event": event.kind.as_str() },
+            }),
+        }
+    }
+}
+
+/// An endpoint receiving the events, of all of the types and the kinds by default
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WebhookEndpoint {
+    pub url: String,
+    /// The key of the signatures, where the events are not signed with no secret
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub types: Option<Vec<String>>,
+    #[serde(default)]
+    pub kinds: Option<Vec<EventKind>>,
+}
+
+impl WebhookEndpoint {
+    pub fn new(url: &str) -> Self {
+        Self { url: url.to_string(), secret: None, types: None, kinds: None }
+    }
+
+    pub fn with_secret(mut self, secret: &str) -> Self {
+        self.secret = Some(secret.to_string());
+        self
+    }
+
+    pub fn with_types(mut self, types: &[&str]) -> Self {
+        self.types = Some(types.iter().map(ToString::to_string).collect());
+        self
+    }
+
+    pub fn with_kinds(mut self, kinds: &[EventKind]) -> Self {
+        self.kinds = Some(kinds.to_vec());
+        self
+    }
+
+    pub fn subscribes(&self, event: &ResourceEvent) -> bool {
+        self.types.as_ref().is_none_or(|types| types.contains(&event.ty))
+            && self.kinds.as_ref().is_none_or(|kinds| kinds.contains(&event.kind))
+    }
+}
+
+/// How the failed deliveries are retried, where the `n`th retry waits for
+/// `initial_backoff * 2^(n - 1)`, up to `max_backoff`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct RetryPolicy {
+    /// All of the attempts of a delivery, including the first one
+    pub max_attempts: usize,
+    #[serde(with = "millis")]
+    pub initial_backoff: Duration,
+    #[serde(with = "millis")]
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The wait before the `retry`th retry, counting from 1
+    pub fn backoff(&self, retry: usize) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1) as u32);
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+mod millis {
+    use serde::{Deserialize, Deserializer};
+    use std::time::Duration;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        Ok(Duration::from_millis(u64::deserialize(deserializer)?))
+    }
+}
+
+/// Posts the bodies of the deliveries, answering the status of the response, or the reason why
+/// there is no response
+#[async_trait]
+pub trait WebhookTransport: Send + Sync {
+    async fn post(&self, url: &str, headers: &[(&str, String)], body: &[u8])
+        -> Result<u16, String>;
+}
+
+/// The transport with `reqwest`
+#[derive(Debug, Clone, Default)]
+pub struct HttpTransport(pub reqwest::Client);
+
+#[async_trait]
+impl WebhookTransport for HttpTransport {
+    async fn post(
+        &self, url: &str, headers: &[(&str, String)], body: &[u8],
+    ) -> Result<u16, String> {
+        let mut req = self.0.post(url).body(body.to_vec());
+        for (name, value) in headers {
+            req = req.header(*name, value);
+        }
+        req.send().await.map(|resp| resp.status().as_u16()).map_err(|err| err.to_string())
+    }
+}
+
+/// The result of the delivery of an event to an endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delivery {
+    pub url: String,
+    pub attempts: usize,
+    /// The status of the last response, if any
+    pub status: Option<u16>,
+    pub delivered: bool,
+}
+
+/// The endpoints of the events
+#[derive(Clone)]
+pub struct Webhooks {
+    pub endpoints: Vec<WebhookEndpoint>,
+    pub retry: RetryPolicy,
+    transport: Arc<dyn WebhookTransport>,
+}
+
+impl Webhooks {
+    pub fn new(endpoints: Vec<WebhookEndpoint>) -> Self {
+        Self::with_transport(endpoints, HttpTransport::default())
+    }
+
+    pub fn with_transport(
+        endpoints: Vec<WebhookEndpoint>, transport: impl 'static + WebhookTransport,
+    ) -> Self {
+        Self { endpoints, retry: Default::default(), transport: Arc::new(transport) }
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Delivers `event` to all of the endpoints subscribed to it, one after another
+    pub async fn publish(&self, event: &ResourceEvent) -> Vec<Delivery> {
+        let body = event.payload.to_string().into_bytes();
+        let mut deliveries = vec![];
+        for endpoint in self.endpoints.iter().filter(|endpoint| endpoint.subscribes(event)) {
+            deliveries.push(self.deliver(endpoint, event, &body).await);
+        }
+        deliveries
+    }
+
+    /// Posts `body` to `endpoint` until it's answered with a `2xx`, retrying the failed requests,
+    /// the `5xx` and the `429 Too Many Requests`
+    async fn deliver(
+        &self, endpoint: &WebhookEndpoint, event: &ResourceEvent, body: &[u8],
+    ) -> Delivery {
+        let mut headers = vec![
+            ("Content-Type", JSON_API_HEADER.to_string()),
+            (EVENT_HEADER, format!("{}.{}", event.ty, event.kind.as_str())),
+        ];
+        if let Some(secret) = &endpoint.secret {
+            headers.push((SIGNATURE_HEADER, signature(secret, body)));
+        }
+        let mut delivery =
+            Delivery { url: endpoint.url.clone(), attempts: 0, status: None, delivered: false };
+        while delivery.attempts < self.retry.max_attempts.max(1) {
+            if delivery.attempts > 0 {
+                tokio::time::sleep(self.retry.backoff(delivery.attempts)).await;
+            }
+            delivery.attempts += 1;
+            match self.transport.post(&endpoint.url, &headers, body).await {
+                Ok(status) => {
+                    delivery.status = Some(status);
+                    if (200 .. 300).contains(&status) {
+                        delivery.delivered = true;
+                        break;
+                    } else if status != 429 && status < 500 {
+                        break;
+                    }
+                },
+                Err(reason) => {
+                    log::warn!("Failed to deliver the webhook to `{}`: {}", endpoint.url, reason)
+                },
+            }
+        }
+        delivery
+    }
+}
+
+/// Publishes the changes of the `EventBus`, where the failed deliveries are logged
+#[async_trait]
+impl EventSubscriber for Webhooks {
+    async fn on_change(&self, event: &ResourceChanged) {
+        for delivery in self.publish(&ResourceEvent::from(event)).await {
+            if !delivery.delivered {
+                log::warn!(
+                    "The webhook to `{}` is not delivered after {} attempts",
+                    delivery.url,
+                    delivery.attempts
+                );
+            }
+        }
+    }
+}
+
+/// The value of `SIGNATURE_HEADER` of `body`
+pub fn signature(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC can take a key of any size");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,129 @@
+extern crate rabbithole_derive as rbh_derive;
+
+use async_trait::async_trait;
+use rabbithole::event::{EventBus, EventPublisher, ResourceChanged};
+use rabbithole_webhook::{
+    signature, Delivery, EventKind, ResourceEvent, RetryPolicy, WebhookEndpoint, WebhookTransport,
+    Webhooks, EVENT_HEADER, SIGNATURE_HEADER,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+}
+
+/// The URL, the headers and the body of a request
+type Request = (String, Vec<(String, String)>, Vec<u8>);
+
+/// Answers the requests with `statuses`, and `200` after them, recording the requests
+#[derive(Clone, Default)]
+struct MockTransport {
+    statuses: Arc<Mutex<VecDeque<Result<u16, String>>>>,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockTransport {
+    fn new(statuses: Vec<Result<u16, String>>) -> Self {
+        Self { statuses: Arc::new(Mutex::new(statuses.into())), ..Default::default() }
+    }
+}
+
+#[async_trait]
+impl WebhookTransport for MockTransport {
+    async fn post(
+        &self, url: &str, headers: &[(&str, String)], body: &[u8],
+    ) -> Result<u16, String> {
+        let headers = headers.iter().map(|(name, value)| (name.to_string(), value.clone()));
+        self.requests.lock().unwrap().push((url.to_string(), headers.collect(), body.to_vec()));
+        self.statuses.lock().unwrap().pop_front().unwrap_or(Ok(200))
+    }
+}
+
+fn retry() -> RetryPolicy {
+    RetryPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(1),
+        max_backoff: Duration::from_millis(2),
+    }
+}
+
+fn alice() -> Human { Human { id: "alice".into(), name: "Alice".into() } }
+
+#[test]
+fn event_test() {
+    let event = ResourceEvent::new(EventKind::Updated, &alice(), "http://localhost/api");
+    assert_eq!(event.payload["data"]["attributes"]["name"], "Alice");
+    assert_eq!(event.payload["meta"]["event"], "updated");
+
+    let event = ResourceEvent::new(EventKind::Deleted, &alice(), "http://localhost/api");
+    assert_eq!(event.payload["data"], serde_json::json!({ "type": "people", "id": "alice" }));
+
+    let endpoint = WebhookEndpoint::new("http://hooks").with_kinds(&[EventKind::Created]);
+    assert!(!endpoint.subscribes(&event));
+    let endpoint = WebhookEndpoint::new("http://hooks").with_types(&["dogs"]);
+    assert!(!endpoint.subscribes(&event));
+    assert!(WebhookEndpoint::new("http://hooks").subscribes(&event));
+
+    let endpoint: WebhookEndpoint =
+        serde_json::from_str(r#"{ "url": "http://hooks", "kinds": ["deleted"] }"#).unwrap();
+    assert!(endpoint.subscribes(&event));
+    assert_eq!(retry().backoff(1), Duration::from_millis(1));
+    assert_eq!(retry().backoff(3), Duration::from_millis(2));
+}
+
+#[tokio::test]
+async fn publish_test() {
+    let transport = MockTransport::new(vec![Err("refused".into()), Ok(503), Ok(404)]);
+    let webhooks = Webhooks::with_transport(
+        vec![
+            WebhookEndpoint::new("http://first").with_secret("secret"),
+            WebhookEndpoint::new("http://second"),
+            WebhookEndpoint::new("http://dogs").with_types(&["dogs"]),
+        ],
+        transport.clone(),
+    )
+    .with_retry(retry());
+    let event = ResourceEvent::new(EventKind::Created, &alice(), "http://localhost/api");
+    let deliveries = webhooks.publish(&event).await;
+    assert_eq!(deliveries, vec![
+        Delivery { url: "http://first".into(), attempts: 3, status: Some(404), delivered: false },
+        Delivery { url: "http://second".into(), attempts: 1, status: Some(200), delivered: true },
+    ]);
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 4);
+    let (url, headers, body) = &requests[0];
+    assert_eq!(url, "http://first");
+    assert_eq!(body, &event.payload.to_string().into_bytes());
+    assert!(headers.contains(&(EVENT_HEADER.into(), "people.created".into())));
+    assert!(headers.contains(&(SIGNATURE_HEADER.into(), signature("secret", body))));
+    let (_, headers, _) = &requests[3];
+    assert!(!headers.iter().any(|(name, _)| name == SIGNATURE_HEADER));
+    assert_eq!(
+        signature("key", b"The quick brown fox jumps over the lazy dog"),
+        "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+    );
+}
+
+#[tokio::test]
+async fn event_bus_test() {
+    let transport = MockTransport::new(vec![]);
+    let webhooks =
+        Webhooks::with_transport(vec![WebhookEndpoint::new("http://hooks")], transport.clone());
+    let events = EventBus::new().subscribe(webhooks);
+    let changed = ResourceChanged::new(EventKind::Deleted, &alice(), "http://localhost/api");
+    events.publish(&changed).await;
+
+    let requests = transport.requests.lock().unwrap();
+    assert_eq!(requests.len(), 1);
+    let (_, headers, body) = &requests[0];
+    assert_eq!(body, &ResourceEvent::from(&changed).payload.to_string().into_bytes());
+    assert!(headers.contains(&(EVENT_HEADER.into(), "people.deleted".into())));
+}
@@ -0,0 +1,28 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use rabbithole::query::Query;
+
+fn uri(query: &str) -> http::Uri {
+    format!("/people?{}", percent_encode(query.as_bytes(), NON_ALPHANUMERIC)).parse().unwrap()
+}
+
+fn query_bench(c: &mut Criterion) {
+    let empty: http::Uri = "/people".parse().unwrap();
+    c.bench_function("from_uri without query", |b| b.iter(|| Query::from_uri(black_box(&empty))));
+
+    let plain: http::Uri = "/people?include=dogs&sort=-name,age&page[offset]=10&page[limit]=20"
+        .parse()
+        .unwrap();
+    c.bench_function("from_uri with plain query", |b| b.iter(|| Query::from_uri(black_box(&plain))));
+
+    let encoded = uri(
+        "include=dogs,dogs.master&fields[people]=name,dogs&fields[dogs]=name,age&sort=-name\
+         &page[number]=2&page[size]=20",
+    );
+    c.bench_function("from_uri with encoded query", |b| {
+        b.iter(|| Query::from_uri(black_box(&encoded)))
+    });
+}
+
+criterion_group!(benches, query_bench);
+criterion_main!(benches);
@@ -0,0 +1,144 @@
+//! The access logs of the requests, written by the endpoints with `log` under the target
+//! `rabbithole::access` when `access_log` of `JsonApiSettings` is set, like:
+//! ```text
+//! method=GET path=/api/people query="filter[people]=password==***" status=200 elapsed_ms=3
+//! ```
+//! where the secrets are masked:
+//!   - the arguments of the constraints on `masked_attributes` in the filters of the query
+//!   - the values of `masked_headers`, when the request headers are logged with `log_headers`
+
+use http::Uri;
+use percent_encoding::percent_decode_str;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// The target of the access logs
+pub const TARGET: &str = "rabbithole::access";
+/// What the masked values are replaced with
+pub const MASK: &str = "***";
+
+lazy_static! {
+    /// A constraint of RSQL with its selector, operator and argument(s)
+    static ref CONSTRAINT: Regex = Regex::new(
+        r#"(?P<selector>[^;,()=!<>~\s]+)(?P<op>\s*(?:==|!=|=[a-zA-Z-]*=)\s*)(?P<args>"[^"]*"|'[^']*'|\([^)]*\)|[^;,()]*)"#
+    )
+    .unwrap();
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AccessLog {
+    /// If the request headers are logged
+    #[serde(default)]
+    pub log_headers: bool,
+    /// The attributes whose values are masked in the filters of the queries
+    #[serde(default)]
+    pub masked_attributes: Vec<String>,
+    /// The headers whose values are masked, compared case-insensitively, which are the
+    /// credentials and the cookies by default
+    #[serde(default = "default_masked_headers")]
+    pub masked_headers: Vec<String>,
+}
+
+fn default_masked_headers() -> Vec<String> {
+    vec!["authorization".into(), "proxy-authorization".into(), "cookie".into(), "x-api-key".into()]
+}
+
+impl Default for AccessLog {
+    fn default() -> Self {
+        Self {
+            log_headers: false,
+            masked_attributes: vec![],
+            masked_headers: default_masked_headers(),
+        }
+    }
+}
+
+/// A request answered by an endpoint
+#[derive(Debug)]
+pub struct AccessRecord<'a> {
+    pub method: &'a str,
+    pub uri: &'a Uri,
+    pub status: u16,
+    pub elapsed: Duration,
+    /// The request headers, only used with `log_headers`
+    pub headers: Vec<(&'a str, &'a str)>,
+}
+
+impl AccessLog {
+    /// Logs the request headers as well
+    pub fn with_headers(mut self) -> Self {
+        self.log_headers = true;
+        self
+    }
+
+    pub fn mask_attribute(mut self, name: &str) -> Self {
+        self.masked_attributes.push(name.to_string());
+        self
+    }
+
+    pub fn mask_header(mut self, name: &str) -> Self {
+        self.masked_headers.push(name.to_ascii_lowercase());
+        self
+    }
+
+    /// The line of `record`, with the secrets masked
+    pub fn format(&self, record: &AccessRecord) -> String {
+        let mut line = format!("method={} path={}", record.method, record.uri.path());
+        if let Some(query) = record.uri.query() {
+            let _ = write!(line, " query={:?}", self.mask_query(query));
+        }
+        let _ = write!(line, " status={} elapsed_ms={}", record.status, record.elapsed.as_millis());
+        if self.log_headers {
+            for (name, value) in &record.headers {
+                let masked =
+                    self.masked_headers.iter().any(|masked| masked.eq_ignore_ascii_case(name));
+                let value = if masked { MASK } else { value };
+                let _ = write!(line, " {}={:?}", name.to_ascii_lowercase(), value);
+            }
+        }
+        line
+    }
+
+    /// Writes the line of `record`, where the `5xx` responses are logged as warnings
+    pub fn log(&self, record: &AccessRecord) {
+        let level = if record.status >= 500 { log::Level::Warn } else { log::Level::Info };
+        log::log!(target: TARGET, level, "{}", self.format(record));
+    }
+
+    /// The decoded query, where the arguments on `masked_attributes` in the filters are masked,
+    /// including the ones on their nested fields like `password.hash`
+    fn mask_query(&self, query: &str) -> String {
+        let decode = |part: &str| percent_decode_str(part).decode_utf8_lossy().into_owned();
+        let pairs: Vec<String> = query
+            .split('&')
+            .map(|pair| match pair.split_once('=') {
+                Some((key, value)) => {
+                    let (key, value) = (decode(key), decode(value));
+                    if !key.starts_with("filter[") || self.masked_attributes.is_empty() {
+                        return format!("{}={}", key, value);
+                    }
+                    let value = CONSTRAINT.replace_all(&value, |caps: &Captures| {
+                        if self.is_masked(&caps["selector"]) {
+                            format!("{}{}{}", &caps["selector"], &caps["op"], MASK)
+                        } else {
+                            caps[0].to_string()
+                        }
+                    });
+                    format!("{}={}", key, value)
+                },
+                None => decode(pair),
+            })
+            .collect();
+        pairs.join("&")
+    }
+
+    fn is_masked(&self, selector: &str) -> bool {
+        self.masked_attributes.iter().any(|name| {
+            selector
+                .strip_prefix(name.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+        })
+    }
+}
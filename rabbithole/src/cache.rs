@@ -0,0 +1,246 @@
+//! The caches of the rendered documents, consulted by `handler::cached` before running the
+//! fetching operations. A cached document is dropped by `DocumentCache::invalidate` when any
+//! resource of its types changes, where a cache subscribed to the `EventBus` of the services is
+//! invalidated by the `ResourceChanged` events they publish:
+//! ```ignore
+//! let cache: Arc<dyn DocumentCache> = Arc::new(LruDocumentCache::new(1024));
+//! let events = EventBus::new().subscribe(cache.clone());
+//! let service = JsonApiService::new("http://localhost/api", version)?.with_cache(cache);
+//! ```
+
+use crate::event::{EventSubscriber, ResourceChanged};
+use crate::handler::{OperationRequest, Route};
+
+use async_trait::async_trait;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Mutex;
+
+/// What a document is cached by: the resource type and the id of the route, the base of the links
+/// with the path and the query with the fieldsets, the principal, as the documents are authorized
+/// per principal, and the tenant, as the data is partitioned per tenant
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub ty: String,
+    pub id: Option<String>,
+    /// The base of the links, which differs by the `X-Forwarded-*` headers behind a reverse proxy
+    pub base: String,
+    /// The path and the query of the request, like `/people/1?fields[people]=name`
+    pub request: String,
+    pub principal: Option<String>,
+    pub tenant: Option<String>,
+}
+
+impl CacheKey {
+    pub fn new(ty: &str, route: &Route, req: &OperationRequest) -> Self {
+        let id = match route {
+            Route::Collection => None,
+            Route::Single(id) | Route::Relationship(id, _) | Route::Related(id, _) => {
+                Some(id.clone())
+            },
+        };
+        Self {
+            ty: ty.to_string(),
+            id,
+            base: req.base.clone(),
+            request: req.request_path.to_string(),
+            principal: req.context.principal.as_ref().map(|principal| principal.id.clone()),
+            tenant: req.context.tenant.clone(),
+        }
+    }
+}
+
+impl std::fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}:{}:{} {}",
+            self.tenant.as_deref().unwrap_or_default(),
+            self.ty,
+            self.id.as_deref().unwrap_or_default(),
+            self.principal.as_deref().unwrap_or_default(),
+            self.base,
+            self.request
+        )
+    }
+}
+
+/// A cache of the JSON documents, where the failures of the storage of the cache are not errors
+/// of the requests, but only misses
+#[async_trait]
+pub trait DocumentCache: Send + Sync {
+    async fn get(&self, key: &CacheKey) -> Option<serde_json::Value>;
+
+    /// Caches `doc`, which is dropped when any type of `types_of(key, doc)` is invalidated
+    async fn put(&self, key: CacheKey, doc: serde_json::Value);
+
+    /// Drops all of the documents with resources of `ty`, in `data` or in `included`
+    async fn invalidate(&self, ty: &str);
+}
+
+/// Drops the documents of the type of the changed resource
+#[async_trait]
+impl EventSubscriber for dyn DocumentCache {
+    async fn on_change(&self, event: &ResourceChanged) { self.invalidate(&event.ty).await }
+}
+
+/// The types a document depends on: the type of the route, and the types of the resources and
+/// the resource identifiers in `data` and `included`
+pub fn types_of(key: &CacheKey, doc: &serde_json::Value) -> BTreeSet<String> {
+    let mut types = BTreeSet::new();
+    types.insert(key.ty.clone());
+    let resources = doc.get("data").into_iter().chain(doc.get("included"));
+    for resource in resources {
+        let resources = match resource {
+            serde_json::Value::Array(resources) => resources.iter().collect(),
+            resource => vec![resource],
+        };
+        for ty in resources.into_iter().filter_map(|r| r.get("type")?.as_str()) {
+            types.insert(ty.to_string());
+        }
+    }
+    types
+}
+
+/// An in-memory cache keeping the `capacity` least recently used documents
+pub struct LruDocumentCache {
+    capacity: usize,
+    inner: Mutex<LruInner>,
+}
+
+#[derive(Default)]
+struct LruInner {
+    tick: u64,
+    /// The documents with their types and the tick of their last use
+    entries: HashMap<CacheKey, (serde_json::Value, BTreeSet<String>, u64)>,
+    /// The keys by the ticks of their last use, the least recently used first
+    recency: BTreeMap<u64, CacheKey>,
+}
+
+impl LruInner {
+    fn touch(&mut self, key: &CacheKey) -> Option<serde_json::Value> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (doc, _, last) = self.entries.get_mut(key)?;
+        self.recency.remove(last);
+        *last = tick;
+        self.recency.insert(tick, key.clone());
+        Some(doc.clone())
+    }
+
+    fn remove(&mut self, key: &CacheKey) {
+        if let Some((_, _, last)) = self.entries.remove(key) {
+            self.recency.remove(&last);
+        }
+    }
+}
+
+impl LruDocumentCache {
+    pub fn new(capacity: usize) -> Self { Self { capacity, inner: Default::default() } }
+
+    pub fn len(&self) -> usize { self.inner.lock().map(|inner| inner.entries.len()).unwrap_or(0) }
+
+    pub fn is_empty(&self) -> bool { self.len() == 0 }
+}
+
+#[async_trait]
+impl DocumentCache for LruDocumentCache {
+    async fn get(&self, key: &CacheKey) -> Option<serde_json::Value> {
+        self.inner.lock().ok()?.touch(key)
+    }
+
+    async fn put(&self, key: CacheKey, doc: serde_json::Value) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut inner = match self.inner.lock() {
+            Ok(inner) => inner,
+            Err(_) => return,
+        };
+        inner.remove(&key);
+        while inner.entries.len() >= self.capacity {
+            let oldest = match inner.recency.values().next() {
+                Some(oldest) => oldest.clone(),
+                None => break,
+            };
+            inner.remove(&oldest);
+        }
+        inner.tick += 1;
+        let tick = inner.tick;
+        let types = types_of(&key, &doc);
+        inner.recency.insert(tick, key.clone());
+        inner.entries.insert(key, (doc, types, tick));
+    }
+
+    async fn invalidate(&self, ty: &str) {
+        if let Ok(mut inner) = self.inner.lock() {
+            let keys: Vec<CacheKey> = inner
+                .entries
+                .iter()
+                .filter(|(_, (_, types, _))| types.contains(ty))
+                .map(|(key, _)| key.clone())
+                .collect();
+            for key in &keys {
+                inner.remove(key);
+            }
+        }
+    }
+}
+
+/// A cache in Redis, shared by the instances of a service, where a document is kept for `ttl`
+/// seconds at `<prefix>doc:<key>`, and the keys of the documents of a type are in the set
+/// `<prefix>type:<ty>`
+#[cfg(feature = "redis-cache")]
+pub struct RedisDocumentCache {
+    connection: redis::aio::ConnectionManager,
+    prefix: String,
+    ttl: usize,
+}
+
+#[cfg(feature = "redis-cache")]
+impl RedisDocumentCache {
+    pub fn new(connection: redis::aio::ConnectionManager, prefix: &str, ttl: usize) -> Self {
+        Self { connection, prefix: prefix.to_string(), ttl }
+    }
+
+    fn doc_key(&self, key: &CacheKey) -> String { format!("{}doc:{}", self.prefix, key) }
+
+    fn type_key(&self, ty: &str) -> String { format!("{}type:{}", self.prefix, ty) }
+}
+
+#[cfg(feature = "redis-cache")]
+#[async_trait]
+impl DocumentCache for RedisDocumentCache {
+    async fn get(&self, key: &CacheKey) -> Option<serde_json::Value> {
+        let mut connection = self.connection.clone();
+        let doc: Option<String> =
+            redis::cmd("GET").arg(self.doc_key(key)).query_async(&mut connection).await.ok()?;
+        serde_json::from_str(&doc?).ok()
+    }
+
+    async fn put(&self, key: CacheKey, doc: serde_json::Value) {
+        let mut connection = self.connection.clone();
+        let doc_key = self.doc_key(&key);
+        let mut pipe = redis::pipe();
+        pipe.cmd("SET").arg(&doc_key).arg(doc.to_string()).arg("EX").arg(self.ttl).ignore();
+        for ty in types_of(&key, &doc) {
+            pipe.cmd("SADD").arg(self.type_key(&ty)).arg(&doc_key).ignore();
+        }
+        if let Err(err) = pipe.query_async::<_, ()>(&mut connection).await {
+            log::warn!("Failed to cache the document of `{}`: {}", key, err);
+        }
+    }
+
+    async fn invalidate(&self, ty: &str) {
+        let mut connection = self.connection.clone();
+        let type_key = self.type_key(ty);
+        let result: redis::RedisResult<()> = async {
+            let keys: Vec<String> =
+                redis::cmd("SMEMBERS").arg(&type_key).query_async(&mut connection).await?;
+            redis::cmd("DEL").arg(&type_key).arg(keys).query_async(&mut connection).await
+        }
+        .await;
+        if let Err(err) = result {
+            log::warn!("Failed to invalidate the documents of `{}`: {}", ty, err);
+        }
+    }
+}
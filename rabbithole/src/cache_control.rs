@@ -0,0 +1,101 @@
+//! The `Cache-Control` policies of the resource types, set by `cache_control` in
+//! `JsonApiSettings` like:
+//! ```toml
+//! [jsonapi.cache_control.dogs]
+//! max_age = 60
+//! s_maxage = 300
+//! visibility = "public"
+//!
+//! [jsonapi.cache_control.people]
+//! no_store = true
+//! ```
+//! The policy of a type is the `Cache-Control` header of the successful and the `304 Not Modified`
+//! responses of its routes, while the errors are left without one
+//! NOTICE:
+//!   - The `no-store` responses are never revalidated, so the endpoints leave the `ETag` and the
+//!     `Last-Modified` headers out of them
+//!   - The protected resource types should be `private`, as the shared caches ignore who asks
+
+use http::HeaderValue;
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Visibility {
+    /// Cacheable by the shared caches, like the CDNs
+    Public,
+    /// Only cacheable by the clients
+    Private,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct CachePolicy {
+    /// The seconds the responses are fresh for
+    #[serde(default)]
+    pub max_age: Option<u64>,
+    /// The seconds the responses are fresh for in the shared caches, overriding `max_age`
+    #[serde(default)]
+    pub s_maxage: Option<u64>,
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// If the stale responses must be revalidated before they're used
+    #[serde(default)]
+    pub must_revalidate: bool,
+    /// If the responses must not be stored at all, which overrides the others
+    #[serde(default)]
+    pub no_store: bool,
+}
+
+impl CachePolicy {
+    pub fn public(max_age: u64) -> Self {
+        Self { max_age: Some(max_age), visibility: Some(Visibility::Public), ..Default::default() }
+    }
+
+    pub fn private(max_age: u64) -> Self {
+        Self { max_age: Some(max_age), visibility: Some(Visibility::Private), ..Default::default() }
+    }
+
+    pub fn no_store() -> Self { Self { no_store: true, ..Default::default() } }
+
+    pub fn with_s_maxage(mut self, s_maxage: u64) -> Self {
+        self.s_maxage = Some(s_maxage);
+        self
+    }
+
+    pub fn with_must_revalidate(mut self) -> Self {
+        self.must_revalidate = true;
+        self
+    }
+
+    /// The value of the `Cache-Control` header, like `public, max-age=60`, which is `no-cache`
+    /// if nothing is set
+    pub fn header_value(&self) -> String {
+        if self.no_store {
+            return "no-store".to_string();
+        }
+        let mut directives = vec![];
+        match self.visibility {
+            Some(Visibility::Public) => directives.push("public".to_string()),
+            Some(Visibility::Private) => directives.push("private".to_string()),
+            None => {},
+        }
+        if let Some(max_age) = self.max_age {
+            directives.push(format!("max-age={}", max_age));
+        }
+        if let Some(s_maxage) = self.s_maxage {
+            directives.push(format!("s-maxage={}", s_maxage));
+        }
+        if self.must_revalidate {
+            directives.push("must-revalidate".to_string());
+        }
+        if directives.is_empty() {
+            return "no-cache".to_string();
+        }
+        directives.join(", ")
+    }
+
+    pub fn header(&self) -> HeaderValue {
+        HeaderValue::from_str(&self.header_value())
+            .unwrap_or_else(|_| HeaderValue::from_static("no-cache"))
+    }
+}
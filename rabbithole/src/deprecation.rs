@@ -0,0 +1,91 @@
+//! The deprecations of the resource types and of their fields, declared by `EntityDecorator`:
+//! ```ignore
+//! #[derive(EntityDecorator)]
+//! #[entity(type = "dogs")]
+//! #[entity(deprecated(since = "2023-01-01", sunset = "2024-01-01", note = "use `pets`"))]
+//! pub struct Dog {
+//!     #[entity(id)]
+//!     pub id: Uuid,
+//!     #[entity(deprecated(note = "use `birthday`"))]
+//!     pub age: i32,
+//! }
+//! ```
+//! The deprecated resource types are announced with the `Deprecation` (RFC 9745) and `Sunset`
+//! (RFC 8594) headers of their routes, and the deprecated fields are noted in the `deprecated`
+//! meta of the resources having them
+//! NOTICE:
+//!   - The dates are `YYYY-MM-DD` in UTC or HTTP-dates, and the invalid ones are left out of the
+//!     headers, where `Deprecation` falls back to `true`
+
+use http::{HeaderName, HeaderValue};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEPRECATION_HEADER: &str = "deprecation";
+pub const SUNSET_HEADER: &str = "sunset";
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Deprecation {
+    /// When the resource type or the field was deprecated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub since: Option<String>,
+    /// When the resource type or the field is going to be removed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset: Option<String>,
+    /// What to use instead, or why it's deprecated
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl Deprecation {
+    /// The value of the `Deprecation` header, which is the epoch seconds of `since` like
+    /// `@1672531200`, or `true` without a valid `since`
+    pub fn deprecation_header(&self) -> String {
+        match self.since.as_deref().and_then(parse_date) {
+            Some(since) => {
+                format!("@{}", since.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs())
+            },
+            None => "true".to_string(),
+        }
+    }
+
+    /// The value of the `Sunset` header, which is the HTTP-date of `sunset`
+    pub fn sunset_header(&self) -> Option<String> {
+        self.sunset.as_deref().and_then(parse_date).map(httpdate::fmt_http_date)
+    }
+
+    /// The `Deprecation` and the `Sunset` headers of the responses
+    pub fn headers(&self) -> Vec<(HeaderName, HeaderValue)> {
+        let mut headers = Vec::with_capacity(2);
+        if let Ok(value) = HeaderValue::from_str(&self.deprecation_header()) {
+            headers.push((HeaderName::from_static(DEPRECATION_HEADER), value));
+        }
+        if let Some(value) = self.sunset_header().and_then(|date| HeaderValue::from_str(&date).ok())
+        {
+            headers.push((HeaderName::from_static(SUNSET_HEADER), value));
+        }
+        headers
+    }
+}
+
+/// The time of an HTTP-date or the midnight of a `YYYY-MM-DD` date in UTC
+fn parse_date(date: &str) -> Option<SystemTime> {
+    let date = date.trim();
+    if let Ok(time) = httpdate::parse_http_date(date) {
+        return Some(time);
+    }
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    if year < 1970 || !(1 ..= 12).contains(&month) || !(1 ..= 31).contains(&day) {
+        return None;
+    }
+    // The days since the epoch in the proleptic Gregorian calendar, counting the years from March
+    // so the leap days are at the end of them
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+    Some(UNIX_EPOCH + Duration::from_secs(days as u64 * 86_400))
+}
@@ -0,0 +1,142 @@
+//! The wire formats of the documents, where the same `Document` is encoded as JSON, as CBOR for
+//! the service-to-service traffic with the `cbor` feature, or converted into HAL or Siren by
+//! `rabbithole::hypermedia`. The encoding of the responses is negotiated from the `Accept` of the
+//! requests:
+//! ```ignore
+//! let encoding = Encoding::negotiate(&accept);
+//! let body = encoding.encode(&doc)?;
+//! let doc: Document = Encoding::of_content_type(&content_type).decode(&body)?;
+//! ```
+//! NOTICE:
+//!   - The error documents are always in JSON, so the clients can read them without CBOR
+//!   - Only the JSON and the CBOR ones are decoded, while HAL and Siren are for the responses
+
+use crate::hypermedia::{self, HAL_HEADER, SIREN_HEADER};
+use crate::model::error;
+use crate::rule::media_type::MediaType;
+use crate::{RbhResult, JSON_API_HEADER};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+pub const JSON_API_CBOR_HEADER: &str = "application/vnd.api+cbor";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Encoding {
+    /// `application/vnd.api+json`
+    #[default]
+    Json,
+    /// `application/vnd.api+cbor`
+    Cbor,
+    /// `application/hal+json`
+    Hal,
+    /// `application/vnd.siren+json`
+    Siren,
+}
+
+impl Encoding {
+    pub fn media_type(self) -> &'static str {
+        match self {
+            Encoding::Json => JSON_API_HEADER,
+            Encoding::Cbor => JSON_API_CBOR_HEADER,
+            Encoding::Hal => HAL_HEADER,
+            Encoding::Siren => SIREN_HEADER,
+        }
+    }
+
+    /// The encoding of the essence of a media type, where CBOR needs the `cbor` feature
+    pub fn of_essence(essence: &str) -> Option<Self> {
+        match essence {
+            JSON_API_HEADER => Some(Encoding::Json),
+            JSON_API_CBOR_HEADER if cfg!(feature = "cbor") => Some(Encoding::Cbor),
+            HAL_HEADER => Some(Encoding::Hal),
+            SIREN_HEADER => Some(Encoding::Siren),
+            _ => None,
+        }
+    }
+
+    /// If the documents in the encoding are JSON:API ones, where the media type parameters like
+    /// `profile` are allowed
+    pub fn is_json_api(self) -> bool { matches!(self, Encoding::Json | Encoding::Cbor) }
+
+    /// The encoding of a request body by its `Content-Type`, which is JSON if it's not CBOR
+    pub fn of_content_type(content_type: &Option<String>) -> Self {
+        match content_type.as_deref().and_then(MediaType::parse) {
+            Some(media_type) if media_type.essence == JSON_API_CBOR_HEADER => Encoding::Cbor,
+            _ => Encoding::Json,
+        }
+    }
+
+    /// The encoding of the responses to a request with `accept`, which is the one of the first
+    /// media range with the highest quality value, where CBOR needs the `cbor` feature.
+    /// The wildcards stand for JSON with its quality value by `MediaType::quality`, so they don't
+    /// choose JSON when a more specific range excludes it
+    pub fn negotiate(accept: &Option<String>) -> Self {
+        let media_types = accept.as_deref().map(MediaType::parse_list).unwrap_or_default();
+        let mut preferred: Option<(Self, f32)> = None;
+        for media_type in media_types.iter().filter(|media_type| media_type.accepts_json_api()) {
+            let candidate = match Self::of_essence(&media_type.essence) {
+                Some(encoding) => (encoding, media_type.weight),
+                None => (Encoding::Json, MediaType::quality(&media_types, JSON_API_HEADER)),
+            };
+            if preferred.is_none_or(|(_, weight)| candidate.1 > weight) {
+                preferred = Some(candidate);
+            }
+        }
+        preferred.map(|(encoding, _)| encoding).unwrap_or_default()
+    }
+
+    pub fn encode<T: Serialize + ?Sized>(self, value: &T) -> RbhResult<Vec<u8>> {
+        match self {
+            Encoding::Json => encode_json(value),
+            Encoding::Cbor => encode_cbor(value),
+            Encoding::Hal => encode_json(&hypermedia::to_hal(&to_json_value(value)?)),
+            Encoding::Siren => encode_json(&hypermedia::to_siren(&to_json_value(value)?)),
+        }
+    }
+
+    pub fn decode<T: DeserializeOwned>(self, content: &[u8]) -> RbhResult<T> {
+        match self {
+            Encoding::Json => {
+                serde_json::from_slice(content).map_err(|err| error::Error::InvalidJson(&err, None))
+            },
+            Encoding::Cbor => decode_cbor(content),
+            Encoding::Hal | Encoding::Siren => Err(error::Error::InvalidContentType(
+                &format!("`{}` or `{}`", JSON_API_HEADER, JSON_API_CBOR_HEADER),
+                self.media_type(),
+                None,
+            )),
+        }
+    }
+}
+
+fn encode_json<T: Serialize + ?Sized>(value: &T) -> RbhResult<Vec<u8>> {
+    serde_json::to_vec(value).map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+fn to_json_value<T: Serialize + ?Sized>(value: &T) -> RbhResult<serde_json::Value> {
+    serde_json::to_value(value).map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+#[cfg(feature = "cbor")]
+fn encode_cbor<T: Serialize + ?Sized>(value: &T) -> RbhResult<Vec<u8>> {
+    let mut content = Vec::new();
+    ciborium::ser::into_writer(value, &mut content)
+        .map_err(|err| error::Error::InvalidCbor(&err.to_string(), None))?;
+    Ok(content)
+}
+
+#[cfg(not(feature = "cbor"))]
+fn encode_cbor<T: Serialize + ?Sized>(_value: &T) -> RbhResult<Vec<u8>> {
+    Err(error::Error::CborNotImplemented(None))
+}
+
+#[cfg(feature = "cbor")]
+fn decode_cbor<T: DeserializeOwned>(content: &[u8]) -> RbhResult<T> {
+    ciborium::de::from_reader(content)
+        .map_err(|err| error::Error::InvalidCbor(&err.to_string(), None))
+}
+
+#[cfg(not(feature = "cbor"))]
+fn decode_cbor<T: DeserializeOwned>(_content: &[u8]) -> RbhResult<T> {
+    Err(error::Error::CborNotImplemented(None))
+}
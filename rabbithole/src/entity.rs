@@ -1,17 +1,26 @@
+use crate::deprecation::Deprecation;
 use crate::model::document::{Document, Included};
 use crate::model::link::{Link, Links, RawUri};
-use crate::model::relationship::{RelationshipLinks, Relationships};
-use crate::model::resource::{Attributes, Resource, ResourceIdentifier};
-use serde::Serialize;
+use crate::model::relationship::{Relationship, RelationshipLinks, Relationships};
+use crate::model::resource::{AttributeField, Attributes, Resource, ResourceIdentifier};
+use crate::model::Meta;
+use serde::de::DeserializeOwned;
+use serde::ser::{SerializeMap, SerializeStruct};
+use serde::{Serialize, Serializer};
 
 use crate::model::error;
 use crate::query::*;
+use crate::schema::ResourceSchema;
+use std::any::Any;
 use std::cmp::Ordering;
 
 use crate::RbhResult;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
 use std::iter::FromIterator;
 use std::ops::Deref;
+use std::sync::Arc;
+use std::time::SystemTime;
 
 pub trait SingleEntity: Entity {
     #[doc(hidden)]
@@ -23,6 +32,75 @@ pub trait SingleEntity: Entity {
     #[doc(hidden)]
     fn relationships(&self, uri: &str) -> Relationships;
 
+    /// The attributes in `fields` only, or all of them if `None`, where the derived entities
+    /// serialize the fields in `fields` only
+    #[doc(hidden)]
+    fn attributes_of(&self, fields: Option<&HashSet<String>>) -> Attributes {
+        match fields {
+            Some(fields) => self.attributes().retain(fields),
+            None => self.attributes(),
+        }
+    }
+
+    /// The attribute `field`, where the derived entities serialize this field only
+    #[doc(hidden)]
+    fn attribute(&self, field: &str) -> Option<AttributeField> {
+        self.attributes().get_field(field).ok().cloned()
+    }
+
+    /// Whether any attribute is in `fields`, or there is any attribute if `None`
+    #[doc(hidden)]
+    fn has_attributes(&self, fields: Option<&HashSet<String>>) -> bool {
+        !self.attributes_of(fields).is_empty()
+    }
+
+    /// Writes the attributes in `fields`, or all of them if `None`, into `map`, where the derived
+    /// entities serialize the fields straight into `map` without any JSON value in between
+    #[doc(hidden)]
+    fn serialize_attributes<M: SerializeMap>(
+        &self, fields: Option<&HashSet<String>>, map: &mut M,
+    ) -> Result<(), M::Error> {
+        for (name, value) in self.attributes_of(fields).iter() {
+            map.serialize_entry(name, value)?;
+        }
+        Ok(())
+    }
+
+    /// The schema of the resource type, which has no attributes or relationships unless it's
+    /// generated by `EntityDecorator`
+    fn schema() -> ResourceSchema { ResourceSchema::new(&Self::ty()) }
+
+    /// The version of the resource, like a revision number or an update timestamp, which lets the
+    /// endpoints generate the `ETag` without rendering the document
+    /// NOTICE:
+    ///   - The version should change whenever anything in the document of the resource changes,
+    ///     including the included resources
+    fn version(&self) -> Option<String> { None }
+
+    /// The time the resource was last modified, which becomes the `Last-Modified` header of the
+    /// single resource responses and the `lastModified` in the meta of the resource, so the
+    /// clients can revalidate their copies with `If-Modified-Since`
+    fn last_modified(&self) -> Option<SystemTime> { None }
+
+    /// The deprecation of the resource type, which the endpoints announce with the `Deprecation`
+    /// and the `Sunset` headers of its routes
+    fn deprecation() -> Option<Deprecation> { None }
+
+    /// The deprecations of the fields by their names, which are noted in the `deprecated` meta of
+    /// the resources having any of them
+    fn deprecated_fields() -> HashMap<String, Deprecation> { HashMap::new() }
+
+    /// The former names of the resource type, like `person` of `people`, whose routes are
+    /// redirected to the ones of the type, and which are accepted in `fields[<ty>]` and
+    /// `filter[<ty>]`. They are noted in the `aliases` meta of the resources
+    fn aliases() -> Vec<String> { vec![] }
+
+    /// The content hash of the resource with all of its fields, whose links are relative to the
+    /// root, so the hash doesn't depend on the base URI of the endpoints
+    fn content_hash(&self) -> String {
+        crate::hash::content_hash(&self.to_resource("", &Default::default()))
+    }
+
     #[doc(hidden)]
     fn links(&self, uri: &str) -> Links {
         let slf = format!(
@@ -47,25 +125,28 @@ pub trait SingleEntity: Entity {
     }
 
     fn to_resource_identifier(&self) -> Option<ResourceIdentifier> {
-        Some(ResourceIdentifier { ty: <Self as SingleEntity>::ty(), id: self.id() })
+        Some(ResourceIdentifier { ty: <Self as SingleEntity>::ty().into(), id: self.id() })
     }
 
     fn to_resource(&self, uri: &str, fields_query: &FieldsQuery) -> Option<Resource> {
-        let mut attributes = self.attributes();
+        let ty = <Self as SingleEntity>::ty();
+        let fields = fields_query.get(&ty);
+        let attributes = self.attributes_of(fields);
         let mut relationships = self.relationships(uri);
-        for (k, vs) in fields_query.iter() {
-            if &<Self as SingleEntity>::ty() == k {
-                attributes = attributes.retain(vs);
-                relationships.retain(|k, _| vs.contains(k));
-            }
+        if let Some(fields) = fields {
+            relationships.retain(|k, _| fields.contains(k.as_str()));
         }
 
+        let meta = resource_meta(self, |name| {
+            attributes.get_field(name).is_ok() || relationships.contains_key(name)
+        });
+
         Some(Resource {
-            id: ResourceIdentifier { id: self.id(), ty: Self::ty() },
+            id: ResourceIdentifier { id: self.id(), ty: ty.into() },
             attributes,
             relationships,
             links: self.links(uri),
-            ..Default::default()
+            meta,
         })
     }
 
@@ -91,7 +172,280 @@ pub trait SingleEntity: Entity {
     }
 
     fn cmp_field(&self, field: &str, other: &Self) -> Result<Ordering, error::Error> {
-        self.attributes().cmp(field, &other.attributes())
+        let not_exist = || error::Error::FieldNotExist(field, None);
+        let this = self.attribute(field).ok_or_else(not_exist)?;
+        this.cmp_with(&other.attribute(field).ok_or_else(not_exist)?, field)
+    }
+}
+
+/// The `meta` of the resource object of `entity`, where only the deprecated fields which are
+/// `present` in the resource object are listed
+fn resource_meta<E: SingleEntity>(entity: &E, present: impl Fn(&str) -> bool) -> Meta {
+    let mut meta = Meta::new();
+    if let Some(last_modified) = entity.last_modified() {
+        meta.insert("lastModified".into(), httpdate::fmt_http_date(last_modified).into());
+    }
+    let deprecated: serde_json::Map<String, serde_json::Value> = E::deprecated_fields()
+        .into_iter()
+        .filter(|(name, _)| present(name))
+        .filter_map(|(name, deprecation)| Some((name, serde_json::to_value(deprecation).ok()?)))
+        .collect();
+    if !deprecated.is_empty() {
+        meta.insert("deprecated".into(), deprecated.into());
+    }
+    let aliases = E::aliases();
+    if !aliases.is_empty() {
+        meta.insert("aliases".into(), aliases.into());
+    }
+    meta
+}
+
+/// The resource object of an entity, serialized as the one of `SingleEntity::to_resource` but
+/// without building the `Resource`, where the attributes are written straight from the fields of
+/// the entity instead of being cloned into JSON values first, like for the large collections
+pub struct ResourceView<'a, E> {
+    entity: &'a E,
+    uri: &'a str,
+    fields: Option<&'a HashSet<String>>,
+}
+
+impl<'a, E: SingleEntity> ResourceView<'a, E> {
+    pub fn new(entity: &'a E, uri: &'a str, fields_query: &'a FieldsQuery) -> Self {
+        Self { entity, uri, fields: fields_query.get(&E::ty()) }
+    }
+}
+
+impl<'a, E: SingleEntity> Serialize for ResourceView<'a, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        struct AttributesView<'a, E>(&'a ResourceView<'a, E>);
+
+        impl<'a, E: SingleEntity> Serialize for AttributesView<'a, E> {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                let mut map = serializer.serialize_map(None)?;
+                self.0.entity.serialize_attributes(self.0.fields, &mut map)?;
+                map.end()
+            }
+        }
+
+        let mut relationships = self.entity.relationships(self.uri);
+        if let Some(fields) = self.fields {
+            relationships.retain(|k, _| fields.contains(k.as_str()));
+        }
+        let links = self.entity.links(self.uri);
+        let has_attributes = self.entity.has_attributes(self.fields);
+        let meta = resource_meta(self.entity, |name| {
+            relationships.contains_key(name)
+                || (self.fields.map_or(true, |fields| fields.contains(name))
+                    && self.entity.attribute(name).is_some())
+        });
+        let len = 2
+            + has_attributes as usize
+            + !relationships.is_empty() as usize
+            + !links.is_empty() as usize
+            + !meta.is_empty() as usize;
+        let mut state = serializer.serialize_struct("Resource", len)?;
+        state.serialize_field("type", &E::ty())?;
+        state.serialize_field("id", &self.entity.id())?;
+        if has_attributes {
+            state.serialize_field("attributes", &AttributesView(self))?;
+        }
+        if !relationships.is_empty() {
+            state.serialize_field("relationships", &relationships)?;
+        }
+        if !links.is_empty() {
+            state.serialize_field("links", &links)?;
+        }
+        if !meta.is_empty() {
+            state.serialize_field("meta", &meta)?;
+        }
+        state.end()
+    }
+}
+
+/// The document of the entities, serialized as the one of `to_document_automatically` but with
+/// the `ResourceView`s of the entities as the primary data, so only the included resources are
+/// built as `Resource`s, and the JSON values of the attributes are left to the filters and the
+/// sorting which ask for them
+pub struct DocumentView<'a, E> {
+    data: Option<Vec<&'a E>>,
+    is_single: bool,
+    uri: &'a str,
+    fields_query: &'a FieldsQuery,
+    included: Included,
+    links: Option<Links>,
+}
+
+impl<'a, E: SingleEntity> DocumentView<'a, E> {
+    /// The document of `entity`, which is the `null` document without any link if it's `None`
+    pub fn single(
+        entity: Option<&'a E>, uri: &'a str, query: &'a Query, request_path: &RawUri,
+    ) -> RbhResult<Self> {
+        let (included, links) = match entity {
+            Some(entity) => (
+                entity.included(uri, &query.include, &query.fields)?,
+                Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())])),
+            ),
+            None => Default::default(),
+        };
+        let data = entity.map(|entity| vec![entity]);
+        Ok(Self { data, is_single: true, uri, fields_query: &query.fields, included, links })
+    }
+
+    /// The document of `entities`, sorted and paged by `query` like `to_document_automatically`
+    pub fn collection(
+        entities: &'a [E], uri: &'a str, query: &'a Query, request_path: &RawUri,
+    ) -> RbhResult<Self> {
+        let indices = query.sort.sorted_indices(entities);
+        let indices = match &query.page {
+            Some(page) => page.page_indices(entities, &indices),
+            None => indices.as_slice(),
+        };
+        Ok(Self {
+            data: Some(indices.iter().map(|&i| &entities[i]).collect()),
+            is_single: false,
+            uri,
+            fields_query: &query.fields,
+            included: entities.included(uri, &query.include, &query.fields)?,
+            links: Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())])),
+        })
+    }
+}
+
+impl<'a, E: SingleEntity> Serialize for DocumentView<'a, E> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let view = |entity: &&'a E| ResourceView::new(*entity, self.uri, self.fields_query);
+        let len = 1 + !self.included.is_empty() as usize + self.links.is_some() as usize;
+        let mut state = serializer.serialize_struct("Document", len)?;
+        match &self.data {
+            Some(data) if self.is_single => {
+                state.serialize_field("data", &data.first().map(view))?
+            },
+            Some(data) => {
+                state.serialize_field("data", &data.iter().map(view).collect::<Vec<_>>())?
+            },
+            None => state.serialize_field("data", &serde_json::Value::Null)?,
+        }
+        if !self.included.is_empty() {
+            let mut included: Vec<_> = self.included.iter().collect();
+            included.sort_by(|(a, _), (b, _)| (&a.ty, &a.id).cmp(&(&b.ty, &b.id)));
+            let included: Vec<&Resource> = included.into_iter().map(|(_, r)| r).collect();
+            state.serialize_field("included", &included)?;
+        }
+        if let Some(links) = &self.links {
+            state.serialize_field("links", links)?;
+        }
+        state.end()
+    }
+}
+
+/// The entities built back from their resources, like the ones answered to the clients, which is
+/// derived by `FromResource` of `rabbithole-derive`, where the relationship fields are hydrated by
+/// the related resources in `included`, matched by their types and ids
+/// NOTICE:
+///   - The relationship fields have to be one of `Option<T>`, `Option<Box<T>>`, `Vec<T>` and
+///     `HashSet<T>`, which are left `None` or empty for the related resources not in `included`
+///   - A resource is not hydrated inside itself again, like a dog inside its master inside the
+///     dog, to break the cycles of the relationships
+pub trait FromResource: SingleEntity + Sized {
+    /// Builds the entity from `resource`, where the related resources are looked up in `included`
+    fn from_resource(resource: &Resource, included: &Included) -> RbhResult<Self> {
+        Self::from_included(resource, included, &[])
+    }
+
+    /// Builds the entity from `resource`, inside the resources of `ancestors`, which are not
+    /// looked up again
+    #[doc(hidden)]
+    fn from_included(
+        resource: &Resource, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self>;
+}
+
+impl<T: FromResource> FromResource for Box<T> {
+    fn from_included(
+        resource: &Resource, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self> {
+        T::from_included(resource, included, ancestors).map(Box::new)
+    }
+}
+
+/// The relationship fields of a derived `FromResource`, built from the related resources in
+/// `included`
+#[doc(hidden)]
+pub trait FromRelationship: Sized {
+    fn from_relationship(
+        relationship: Option<&Relationship>, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self>;
+}
+
+/// The entities of the related resources of `relationship` found in `included`
+fn related<'a, T: FromResource>(
+    relationship: Option<&'a Relationship>, included: &'a Included,
+    ancestors: &'a [ResourceIdentifier],
+) -> impl 'a + Iterator<Item = RbhResult<T>> {
+    relationship
+        .map(|relationship| relationship.data.data())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(move |id| !ancestors.contains(id))
+        .filter_map(move |id| included.get(&id))
+        .map(move |resource| T::from_included(resource, included, ancestors))
+}
+
+impl<T: FromResource> FromRelationship for Option<T> {
+    fn from_relationship(
+        relationship: Option<&Relationship>, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self> {
+        related(relationship, included, ancestors).next().transpose()
+    }
+}
+
+impl<T: FromResource> FromRelationship for Vec<T> {
+    fn from_relationship(
+        relationship: Option<&Relationship>, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self> {
+        related(relationship, included, ancestors).collect()
+    }
+}
+
+impl<T: FromResource + Eq + Hash> FromRelationship for HashSet<T> {
+    fn from_relationship(
+        relationship: Option<&Relationship>, included: &Included, ancestors: &[ResourceIdentifier],
+    ) -> RbhResult<Self> {
+        related(relationship, included, ancestors).collect()
+    }
+}
+
+/// The id field of a derived `FromResource`, which is tried as a JSON string first, and as any
+/// other JSON value then, like a number
+#[doc(hidden)]
+pub fn id_from_str<T: DeserializeOwned>(id: &str) -> RbhResult<T> {
+    serde_json::from_value(serde_json::Value::String(id.to_string()))
+        .or_else(|_| serde_json::from_str(id))
+        .map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+/// An attribute field of a derived `FromResource`, where a missing attribute is taken as `null`,
+/// like for the `Option` fields left out by the sparse fieldsets
+#[doc(hidden)]
+pub fn attribute_from_json<T: DeserializeOwned>(
+    field: &str, value: Option<serde_json::Value>,
+) -> RbhResult<T> {
+    match value {
+        Some(value) => {
+            serde_json::from_value(value).map_err(|err| error::Error::InvalidJson(&err, None))
+        },
+        None => serde_json::from_value(serde_json::Value::Null)
+            .map_err(|_| error::Error::FieldNotExist(field, None)),
+    }
+}
+
+/// Checks the type of a resource given to a derived `FromResource`
+#[doc(hidden)]
+pub fn check_type<E: SingleEntity>(resource: &Resource) -> RbhResult<()> {
+    if resource.id.ty == E::ty() {
+        Ok(())
+    } else {
+        Err(error::Error::FieldNotMatch("type", &E::ty(), &resource.id.ty, None))
     }
 }
 
@@ -122,14 +476,49 @@ pub trait Entity: Serialize + Clone {
 impl<T: SingleEntity> SingleEntity for Option<T> {
     fn ty() -> String { T::ty() }
 
+    fn schema() -> ResourceSchema { T::schema() }
+
+    fn deprecation() -> Option<Deprecation> { T::deprecation() }
+
+    fn deprecated_fields() -> HashMap<String, Deprecation> { T::deprecated_fields() }
+
+    fn aliases() -> Vec<String> { T::aliases() }
+
     fn id(&self) -> String { self.as_ref().map(SingleEntity::id).unwrap() }
 
     fn attributes(&self) -> Attributes { self.as_ref().map(SingleEntity::attributes).unwrap() }
 
+    fn attributes_of(&self, fields: Option<&HashSet<String>>) -> Attributes {
+        self.as_ref().map(|op| op.attributes_of(fields)).unwrap()
+    }
+
+    fn attribute(&self, field: &str) -> Option<AttributeField> {
+        self.as_ref().and_then(|op| op.attribute(field))
+    }
+
+    fn has_attributes(&self, fields: Option<&HashSet<String>>) -> bool {
+        self.as_ref().is_some_and(|op| op.has_attributes(fields))
+    }
+
+    fn serialize_attributes<M: SerializeMap>(
+        &self, fields: Option<&HashSet<String>>, map: &mut M,
+    ) -> Result<(), M::Error> {
+        match self {
+            Some(op) => op.serialize_attributes(fields, map),
+            None => Ok(()),
+        }
+    }
+
     fn relationships(&self, uri: &str) -> Relationships {
         self.as_ref().map(|op| op.relationships(uri)).unwrap()
     }
 
+    fn version(&self) -> Option<String> { self.as_ref().and_then(SingleEntity::version) }
+
+    fn last_modified(&self) -> Option<SystemTime> {
+        self.as_ref().and_then(SingleEntity::last_modified)
+    }
+
     fn to_document_automatically(
         &self, uri: &str, query: &Query, request_path: &RawUri,
     ) -> RbhResult<Document> {
@@ -170,11 +559,39 @@ impl<T: Entity> Entity for Option<T> {
 impl<T: SingleEntity> SingleEntity for Box<T> {
     fn ty() -> String { T::ty() }
 
+    fn schema() -> ResourceSchema { T::schema() }
+
+    fn deprecation() -> Option<Deprecation> { T::deprecation() }
+
+    fn deprecated_fields() -> HashMap<String, Deprecation> { T::deprecated_fields() }
+
+    fn aliases() -> Vec<String> { T::aliases() }
+
     fn id(&self) -> String { self.as_ref().id() }
 
     fn attributes(&self) -> Attributes { self.as_ref().attributes() }
 
+    fn attributes_of(&self, fields: Option<&HashSet<String>>) -> Attributes {
+        self.as_ref().attributes_of(fields)
+    }
+
+    fn attribute(&self, field: &str) -> Option<AttributeField> { self.as_ref().attribute(field) }
+
+    fn has_attributes(&self, fields: Option<&HashSet<String>>) -> bool {
+        self.as_ref().has_attributes(fields)
+    }
+
+    fn serialize_attributes<M: SerializeMap>(
+        &self, fields: Option<&HashSet<String>>, map: &mut M,
+    ) -> Result<(), M::Error> {
+        self.as_ref().serialize_attributes(fields, map)
+    }
+
     fn relationships(&self, uri: &str) -> Relationships { self.as_ref().relationships(uri) }
+
+    fn version(&self) -> Option<String> { self.as_ref().version() }
+
+    fn last_modified(&self) -> Option<SystemTime> { self.as_ref().last_modified() }
 }
 
 impl<T: Entity> Entity for Box<T> {
@@ -191,17 +608,97 @@ impl<T: Entity> Entity for Box<T> {
     }
 }
 
+impl<T: SingleEntity> SingleEntity for Arc<T> {
+    fn ty() -> String { T::ty() }
+
+    fn schema() -> ResourceSchema { T::schema() }
+
+    fn deprecation() -> Option<Deprecation> { T::deprecation() }
+
+    fn deprecated_fields() -> HashMap<String, Deprecation> { T::deprecated_fields() }
+
+    fn aliases() -> Vec<String> { T::aliases() }
+
+    fn id(&self) -> String { self.as_ref().id() }
+
+    fn attributes(&self) -> Attributes { self.as_ref().attributes() }
+
+    fn attributes_of(&self, fields: Option<&HashSet<String>>) -> Attributes {
+        self.as_ref().attributes_of(fields)
+    }
+
+    fn attribute(&self, field: &str) -> Option<AttributeField> { self.as_ref().attribute(field) }
+
+    fn has_attributes(&self, fields: Option<&HashSet<String>>) -> bool {
+        self.as_ref().has_attributes(fields)
+    }
+
+    fn serialize_attributes<M: SerializeMap>(
+        &self, fields: Option<&HashSet<String>>, map: &mut M,
+    ) -> Result<(), M::Error> {
+        self.as_ref().serialize_attributes(fields, map)
+    }
+
+    fn relationships(&self, uri: &str) -> Relationships { self.as_ref().relationships(uri) }
+
+    fn version(&self) -> Option<String> { self.as_ref().version() }
+
+    fn last_modified(&self) -> Option<SystemTime> { self.as_ref().last_modified() }
+}
+
+impl<T: Entity> Entity for Arc<T> {
+    fn included(
+        &self, uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+    ) -> RbhResult<Included> {
+        self.as_ref().included(uri, include_query, fields_query)
+    }
+
+    fn to_document_automatically(
+        &self, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> RbhResult<Document> {
+        self.as_ref().to_document_automatically(uri, query, request_path)
+    }
+}
+
 impl<T: SingleEntity> SingleEntity for &T
 where
     Self: Clone,
 {
     fn ty() -> String { T::ty() }
 
+    fn schema() -> ResourceSchema { T::schema() }
+
+    fn deprecation() -> Option<Deprecation> { T::deprecation() }
+
+    fn deprecated_fields() -> HashMap<String, Deprecation> { T::deprecated_fields() }
+
+    fn aliases() -> Vec<String> { T::aliases() }
+
     fn id(&self) -> String { self.deref().id() }
 
     fn attributes(&self) -> Attributes { self.deref().attributes() }
 
+    fn attributes_of(&self, fields: Option<&HashSet<String>>) -> Attributes {
+        T::attributes_of(self, fields)
+    }
+
+    fn attribute(&self, field: &str) -> Option<AttributeField> { T::attribute(self, field) }
+
+    fn has_attributes(&self, fields: Option<&HashSet<String>>) -> bool {
+        T::has_attributes(self, fields)
+    }
+
+    fn serialize_attributes<M: SerializeMap>(
+        &self, fields: Option<&HashSet<String>>, map: &mut M,
+    ) -> Result<(), M::Error> {
+        T::serialize_attributes(self, fields, map)
+    }
+
     fn relationships(&self, uri: &str) -> Relationships { self.deref().relationships(uri) }
+
+    fn version(&self) -> Option<String> { T::version(self) }
+
+    fn last_modified(&self) -> Option<SystemTime> { T::last_modified(self) }
 }
 
 impl<T: Entity> Entity for &T
@@ -229,17 +726,24 @@ impl<T: SingleEntity> Entity for &[T] {
             .iter()
             .map(|e| e.included(uri, include_query, fields_query))
             .collect::<RbhResult<Vec<Included>>>()?;
-        Ok(includes.into_iter().flat_map(|s| s.into_iter()).collect())
+        let mut included = Included::with_capacity(includes.iter().map(Included::len).sum());
+        for inc in includes {
+            included.extend(inc);
+        }
+        Ok(included)
     }
 
     fn to_document_automatically(
         &self, uri: &str, query: &Query, request_path: &RawUri,
     ) -> RbhResult<Document> {
-        let mut entities = self.to_vec();
-        query.sort.sort::<T>(entities.as_mut());
-        let entities =
-            if let Some(page) = &query.page { page.page(&entities) } else { entities.as_slice() };
-        let resources = entities.iter().filter_map(|e| e.to_resource(uri, &query.fields)).collect();
+        let entities: &[T] = self;
+        let indices = query.sort.sorted_indices(entities);
+        let indices = match &query.page {
+            Some(page) => page.page_indices(entities, &indices),
+            None => indices.as_slice(),
+        };
+        let resources =
+            indices.iter().filter_map(|&i| entities[i].to_resource(uri, &query.fields)).collect();
         Ok(Document::multiple_resources(
             resources,
             self.included(uri, &query.include, &query.fields)?,
@@ -261,3 +765,158 @@ impl<T: SingleEntity> Entity for Vec<T> {
         self.as_slice().to_document_automatically(uri, query, request_path)
     }
 }
+
+/// The collections shared by the services, like the cached ones, which are rendered without
+/// being cloned
+impl<T: SingleEntity> Entity for Arc<[T]> {
+    fn included(
+        &self, uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+    ) -> RbhResult<Included> {
+        self.as_ref().included(uri, include_query, fields_query)
+    }
+
+    fn to_document_automatically(
+        &self, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> RbhResult<Document> {
+        self.as_ref().to_document_automatically(uri, query, request_path)
+    }
+}
+
+/// The entities of an iterator, like `EntityIter(humans.iter().filter(|human| human.active))`,
+/// which are rendered by the services without being collected first. The iterator is cloned for
+/// each pass over the entities, so it should be a cheap one, like the ones of the references
+#[derive(Debug, Clone)]
+pub struct EntityIter<I>(pub I);
+
+impl<I> Serialize for EntityIter<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: Serialize,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.0.clone())
+    }
+}
+
+impl<I> Entity for EntityIter<I>
+where
+    I: IntoIterator + Clone,
+    I::Item: SingleEntity,
+{
+    fn included(
+        &self, uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+    ) -> RbhResult<Included> {
+        let mut included = Included::new();
+        for entity in self.0.clone() {
+            included.extend(entity.included(uri, include_query, fields_query)?);
+        }
+        Ok(included)
+    }
+
+    /// Collects the entities to be sorted and paged
+    fn to_document_automatically(
+        &self, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> RbhResult<Document> {
+        let entities: Vec<I::Item> = self.0.clone().into_iter().collect();
+        entities.to_document_automatically(uri, query, request_path)
+    }
+}
+
+/// The object-safe facade of `SingleEntity`, implemented for all of the entities, so the entities
+/// of different types can be kept together as `Box<dyn DynEntity>`, like in the caches and the
+/// registries, and rendered into documents at runtime
+pub trait DynEntity {
+    fn dyn_ty(&self) -> String;
+
+    fn dyn_id(&self) -> String;
+
+    fn dyn_version(&self) -> Option<String>;
+
+    fn dyn_last_modified(&self) -> Option<SystemTime>;
+
+    fn dyn_to_resource(&self, uri: &str, fields_query: &FieldsQuery) -> Option<Resource>;
+
+    fn dyn_included(
+        &self, uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+    ) -> RbhResult<Included>;
+
+    fn dyn_clone(&self) -> Box<dyn DynEntity>;
+
+    /// The entity itself, which can be downcast to its type
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: SingleEntity + 'static> DynEntity for T {
+    fn dyn_ty(&self) -> String { T::ty() }
+
+    fn dyn_id(&self) -> String { self.id() }
+
+    fn dyn_version(&self) -> Option<String> { self.version() }
+
+    fn dyn_last_modified(&self) -> Option<SystemTime> { self.last_modified() }
+
+    fn dyn_to_resource(&self, uri: &str, fields_query: &FieldsQuery) -> Option<Resource> {
+        self.to_resource(uri, fields_query)
+    }
+
+    fn dyn_included(
+        &self, uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+    ) -> RbhResult<Included> {
+        self.included(uri, include_query, fields_query)
+    }
+
+    fn dyn_clone(&self) -> Box<dyn DynEntity> { Box::new(self.clone()) }
+
+    fn as_any(&self) -> &dyn Any { self }
+}
+
+impl Clone for Box<dyn DynEntity> {
+    fn clone(&self) -> Self { self.as_ref().dyn_clone() }
+}
+
+impl dyn DynEntity {
+    /// The entity as `E`, if it's of the type
+    pub fn downcast_ref<E: SingleEntity + 'static>(&self) -> Option<&E> {
+        self.as_any().downcast_ref()
+    }
+
+    /// The document of the single resource, like `SingleEntity::to_document_automatically`
+    pub fn to_document(
+        &self, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> RbhResult<Document> {
+        Ok(Document::single_resource(
+            self.dyn_to_resource(uri, &query.fields).unwrap(),
+            self.dyn_included(uri, &query.include, &query.fields)?,
+            Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())])),
+        ))
+    }
+}
+
+/// The document of the resources of `entities`, which may be of different types, in their order
+/// NOTICE:
+///   - The entities are paged by `query`, but not sorted, as the fields of different types can't
+///     be compared
+pub fn dyn_document(
+    entities: &[Box<dyn DynEntity>], uri: &str, query: &Query, request_path: &RawUri,
+) -> RbhResult<Document> {
+    let entities = match &query.page {
+        Some(page) => {
+            let (start, end) = page.range(entities.len(), |id| {
+                entities.iter().position(|entity| entity.dyn_id() == id)
+            });
+            &entities[start .. end]
+        },
+        None => entities,
+    };
+    let mut included = Included::new();
+    for entity in entities {
+        included.extend(entity.dyn_included(uri, &query.include, &query.fields)?);
+    }
+    let resources =
+        entities.iter().filter_map(|entity| entity.dyn_to_resource(uri, &query.fields)).collect();
+    Ok(Document::multiple_resources(
+        resources,
+        included,
+        Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())])),
+    ))
+}
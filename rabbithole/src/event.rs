@@ -0,0 +1,103 @@
+//! The events of the changes of the resources, published through an `EventPublisher` after the
+//! changes succeed, so the document caches, the webhooks and the other subscribers share one
+//! mechanism. As there are no mutation operations yet, the events are published by the services
+//! changing the data:
+//! ```ignore
+//! let cache: Arc<dyn DocumentCache> = Arc::new(LruDocumentCache::new(1024));
+//! let events = EventBus::new().subscribe(cache.clone()).subscribe(webhooks).subscribe(AuditLog);
+//! // After `human` is saved
+//! let event = ResourceChanged::new(ChangeKind::Updated, &human, "http://localhost/api");
+//! events.publish(&event).await;
+//! ```
+
+use crate::entity::SingleEntity;
+use crate::model::resource::Resource;
+
+use async_trait::async_trait;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Deleted => "deleted",
+        }
+    }
+}
+
+/// A change of a resource
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResourceChanged {
+    pub ty: String,
+    pub id: String,
+    pub kind: ChangeKind,
+    /// The resource after the change with all of its fields, which is `None` if it's deleted
+    pub resource: Option<Resource>,
+}
+
+impl ResourceChanged {
+    /// The change of `item`, rendered with the links under `uri`
+    pub fn new<E: SingleEntity>(kind: ChangeKind, item: &E, uri: &str) -> Self {
+        let resource = match kind {
+            ChangeKind::Deleted => None,
+            _ => item.to_resource(uri, &Default::default()),
+        };
+        Self { ty: E::ty(), id: item.id(), kind, resource }
+    }
+}
+
+/// Reacts to the changes of the resources, like dropping the cached documents
+#[async_trait]
+pub trait EventSubscriber: Send + Sync {
+    async fn on_change(&self, event: &ResourceChanged);
+}
+
+/// Where the services publish the changes of the resources after they succeed
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, event: &ResourceChanged);
+}
+
+/// The publisher delivering every event to all of its subscribers, one after another in the order
+/// they subscribed, where the slow subscribers, like the webhooks with their retries, hold the
+/// publisher until they finish, so the publishing may be spawned
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Vec<Arc<dyn EventSubscriber>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self { Default::default() }
+
+    pub fn subscribe(mut self, subscriber: impl 'static + EventSubscriber) -> Self {
+        self.subscribers.push(Arc::new(subscriber));
+        self
+    }
+
+    pub fn len(&self) -> usize { self.subscribers.len() }
+
+    pub fn is_empty(&self) -> bool { self.subscribers.is_empty() }
+}
+
+#[async_trait]
+impl EventPublisher for EventBus {
+    async fn publish(&self, event: &ResourceChanged) {
+        for subscriber in &self.subscribers {
+            subscriber.on_change(event).await;
+        }
+    }
+}
+
+#[async_trait]
+impl<T: EventSubscriber + ?Sized> EventSubscriber for Arc<T> {
+    async fn on_change(&self, event: &ResourceChanged) { (**self).on_change(event).await }
+}
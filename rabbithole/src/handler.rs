@@ -0,0 +1,692 @@
+//! The framework-independent parts of the endpoints: the routes, the header checks, the query
+//! parsing, the operations with their authorization and the rendering of the documents and the
+//! errors into `http::Response`s, so an endpoint only translates the requests and the responses
+//! of its web framework
+
+use crate::access_log::{AccessLog, AccessRecord};
+use crate::cache::{CacheKey, DocumentCache};
+use crate::cache_control::CachePolicy;
+use crate::deprecation::Deprecation;
+use crate::encoding::Encoding;
+use crate::entity::SingleEntity;
+use crate::model::document::Document;
+use crate::model::error;
+use crate::model::link::RawUri;
+use crate::model::meta::MetaNamespace;
+use crate::model::relationship::Relationship;
+use crate::model::version::JsonApiVersion;
+use crate::operation::{
+    authorize_collection, authorize_included, authorize_parent, authorize_resources,
+    authorize_single, Action, FetchPlan, Fetching, OperationContext,
+};
+use crate::query::limits::QueryLimits;
+use crate::query::Query;
+use crate::rule::conformance::ConformanceLevel;
+use crate::rule::profile::ProfileRegistry;
+use crate::rule::registry::{RuleRegistry, RuleRequest};
+use crate::rule::{RuleDispatcher, Strictness};
+use crate::tenant::TenantSource;
+use crate::trace;
+use crate::{RbhResult, JSON_API_HEADER};
+
+use http::{header, HeaderMap, HeaderValue, Method, Response, StatusCode, Uri};
+use httpdate::HttpDate;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::SystemTime;
+
+/// The names of the operations, used in the settings, the tracing spans and the metrics
+pub const OPERATIONS: [&str; 4] =
+    ["fetch_collection", "fetch_single", "fetch_relationship", "fetch_related"];
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct JsonApiSettings {
+    pub version: JsonApiVersion,
+    /// How the `Content-Type` and `Accept` rules are applied: `strict`, `lenient` or `off`
+    #[serde(default)]
+    pub header_rules: Strictness,
+    /// The profiles advertised in the `Content-Type` of the documents, and required from the
+    /// requests, which are only used in JSON:API v1.1
+    #[serde(default)]
+    pub profiles: ProfileRegistry,
+    /// If the internals of the `5xx` errors are stripped from the error documents, which should
+    /// be enabled in production
+    #[serde(default)]
+    pub redact_errors: bool,
+    /// How closely the requests and the documents are checked against the specification:
+    /// `strict`, `standard` or `lenient`
+    #[serde(default)]
+    pub conformance: ConformanceLevel,
+    /// The implementation-specific query parameters, like `camelCase` and its family
+    /// `camelCase[name]`, which are allowed by the `strict` conformance level
+    #[serde(default)]
+    pub query_parameters: Vec<String>,
+    /// The limits of the complexity of the queries, like the number of the included paths
+    #[serde(default)]
+    pub query_limits: QueryLimits,
+    /// Logs the requests with their statuses and timings, masking the secrets
+    #[serde(default)]
+    pub access_log: Option<AccessLog>,
+    /// The former names of the resource types mapped to the types, like `person = "people"`,
+    /// where the aliases of `SingleEntity::aliases` are added by the endpoints
+    #[serde(default)]
+    pub type_aliases: HashMap<String, String>,
+    /// Nests the members of `meta` added by the crate and the application under a namespace,
+    /// like `meta.rabbithole.lastModified`
+    #[serde(default)]
+    pub meta_namespace: Option<MetaNamespace>,
+    /// The `Cache-Control` policies of the responses by the resource types
+    #[serde(default)]
+    pub cache_control: HashMap<String, CachePolicy>,
+    /// The tolerances of the routes to how the clients write the paths, like `/Humans/` for
+    /// `/humans`
+    #[serde(default)]
+    pub route_matching: RouteMatching,
+    /// Where the tenants of the requests are extracted from, like the subdomains, if the API is
+    /// multi-tenant
+    #[serde(default)]
+    pub tenant: Option<TenantSource>,
+    /// The custom rules of the requests and of the documents, checked after the built-in ones
+    #[serde(skip)]
+    pub rules: RuleRegistry,
+}
+
+impl JsonApiSettings {
+    pub fn new(version: JsonApiVersion) -> Self {
+        Self {
+            version,
+            header_rules: Default::default(),
+            profiles: Default::default(),
+            redact_errors: false,
+            conformance: Default::default(),
+            query_parameters: vec![],
+            query_limits: Default::default(),
+            access_log: None,
+            type_aliases: HashMap::new(),
+            meta_namespace: None,
+            cache_control: HashMap::new(),
+            route_matching: Default::default(),
+            tenant: None,
+            rules: Default::default(),
+        }
+    }
+
+    /// `err` redacted by `redact_error` if `redact_errors` is enabled, with its `meta` namespaced
+    /// by `meta_namespace` if it's set
+    pub fn redacted(&self, mut err: error::Error) -> error::Error {
+        if self.redact_errors {
+            redact_error(&mut err);
+        }
+        if let Some(namespace) = &self.meta_namespace {
+            namespace.apply_error(&mut err);
+        }
+        err
+    }
+
+    /// The `Cache-Control` policy of a response of `ty` with `status`, which is only applied to
+    /// the successful and the `304 Not Modified` responses
+    pub fn cache_policy(&self, ty: &str, status: StatusCode) -> Option<&CachePolicy> {
+        match status.is_success() || status == StatusCode::NOT_MODIFIED {
+            true => self.cache_control.get(ty),
+            false => None,
+        }
+    }
+
+    /// Puts the `Cache-Control` header of the policy of `ty` into `resp`, leaving out the
+    /// validators of the `no-store` responses
+    pub fn apply_cache_control<B>(&self, ty: &str, resp: &mut Response<B>) {
+        let status = resp.status();
+        self.apply_cache_headers(ty, status, resp.headers_mut());
+    }
+
+    /// Same as `apply_cache_control`, for the endpoints with their own response types, which
+    /// answer `status` with `headers`
+    pub fn apply_cache_headers(&self, ty: &str, status: StatusCode, headers: &mut HeaderMap) {
+        if let Some(policy) = self.cache_policy(ty, status) {
+            headers.insert(header::CACHE_CONTROL, policy.header());
+            if policy.no_store {
+                headers.remove(header::ETAG);
+                headers.remove(header::LAST_MODIFIED);
+            }
+        }
+    }
+
+    /// The JSON of a document with its `meta` namespaced by `meta_namespace` if it's set
+    pub fn namespaced(&self, mut doc: serde_json::Value) -> serde_json::Value {
+        if let Some(namespace) = &self.meta_namespace {
+            namespace.apply(&mut doc);
+        }
+        doc
+    }
+
+    /// Logs the request of `method` and `uri` answered with `status` after `elapsed` if
+    /// `access_log` is set, where `headers` are the request headers
+    pub fn log_access<'a>(
+        &self, method: &str, uri: &Uri, status: u16, elapsed: std::time::Duration,
+        headers: impl IntoIterator<Item = (&'a str, &'a str)>,
+    ) {
+        if let Some(access_log) = &self.access_log {
+            let headers =
+                if access_log.log_headers { headers.into_iter().collect() } else { vec![] };
+            access_log.log(&AccessRecord { method, uri, status, elapsed, headers });
+        }
+    }
+
+    /// The tenant of a request to `uri` with `headers` and the base of its links, for the API based
+    /// at `base`, which are `None` and `base` itself if `tenant` is not set
+    pub fn tenant_of(
+        &self, base: &url::Url, uri: &Uri, headers: &HeaderMap,
+    ) -> RbhResult<(Option<String>, url::Url)> {
+        match &self.tenant {
+            Some(source) => {
+                let tenant = source.extract(base, uri, headers)?;
+                let base = source.base(base, &tenant);
+                Ok((Some(tenant), base))
+            },
+            None => Ok((None, base.clone())),
+        }
+    }
+
+    /// If the media type parameters like `profile` are allowed in the JSON:API version
+    fn has_profiles(&self) -> bool { matches!(self.version, JsonApiVersion { major: 1, minor: 1 }) }
+
+    /// The `Content-Type` of the documents, with the profiles in JSON:API v1.1
+    pub fn content_type(&self) -> String { self.content_type_of(Encoding::Json) }
+
+    /// The `Content-Type` of the documents in `encoding`, with the profiles in JSON:API v1.1
+    pub fn content_type_of(&self, encoding: Encoding) -> String {
+        if self.has_profiles() && encoding.is_json_api() {
+            self.profiles.media_type(encoding.media_type())
+        } else {
+            encoding.media_type().to_string()
+        }
+    }
+}
+
+/// The operation of a path under `/<ty>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// `/<ty>`
+    Collection,
+    /// `/<ty>/<id>`
+    Single(String),
+    /// `/<ty>/<id>/relationships/<related_field>`
+    Relationship(String, String),
+    /// `/<ty>/<id>/<related_field>`
+    Related(String, String),
+}
+
+impl Route {
+    /// The route of the (decoded) path segments after `/<ty>`, if it's one of the operations
+    pub fn from_segments(segments: &[&str]) -> Option<Self> {
+        match segments {
+            [] => Some(Route::Collection),
+            [id] => Some(Route::Single(id.to_string())),
+            [id, "relationships", related_field] => {
+                Some(Route::Relationship(id.to_string(), related_field.to_string()))
+            },
+            [id, related_field] => Some(Route::Related(id.to_string(), related_field.to_string())),
+            _ => None,
+        }
+    }
+}
+
+/// The tolerances of the routes to how the clients write the paths, where the `self` links are
+/// canonical whichever way the path is written
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+pub struct RouteMatching {
+    /// If a trailing slash is ignored, like `/humans/` for `/humans`
+    #[serde(default)]
+    pub trailing_slash: bool,
+    /// If the type segments are matched ignoring the ASCII case, like `/Humans` for `/humans`
+    #[serde(default)]
+    pub case_insensitive_types: bool,
+}
+
+impl RouteMatching {
+    /// Both of the tolerances
+    pub fn lenient() -> Self { Self { trailing_slash: true, case_insensitive_types: true } }
+
+    pub fn is_strict(&self) -> bool { !self.trailing_slash && !self.case_insensitive_types }
+
+    /// If the path segment `segment` matches the type segment `ty`
+    pub fn matches_type(&self, segment: &str, ty: &str) -> bool {
+        segment == ty || (self.case_insensitive_types && segment.eq_ignore_ascii_case(ty))
+    }
+
+    /// The type segment of `types` matched by the path segment `segment`, where the exact one is
+    /// preferred to the ones only matched with the tolerances
+    pub fn find_type<'a>(
+        &self, segment: &str, types: impl IntoIterator<Item = &'a str>,
+    ) -> Option<&'a str> {
+        let types: Vec<&str> =
+            types.into_iter().filter(|ty| self.matches_type(segment, ty)).collect();
+        types.iter().find(|ty| **ty == segment).or_else(|| types.first()).copied()
+    }
+
+    /// `path` without its trailing slash if it's ignored, where the root `/` is left as it is
+    pub fn trim_path<'a>(&self, path: &'a str) -> &'a str {
+        match path.strip_suffix('/') {
+            Some(trimmed) if self.trailing_slash && !trimmed.is_empty() => trimmed,
+            _ => path,
+        }
+    }
+
+    /// The canonical location of `path` after `prefix` if it's only a route of one of the type
+    /// segments of `types` with the tolerances, like `/api/Humans/?include=dogs` into
+    /// `/api/humans?include=dogs`
+    pub fn canonical_location<'a>(
+        &self, prefix: &str, path: &str, query: Option<&str>,
+        types: impl IntoIterator<Item = &'a str>,
+    ) -> Option<String> {
+        let prefix = prefix.trim_end_matches('/');
+        let trimmed = self.trim_path(path);
+        let segment = trimmed.strip_prefix(prefix)?.strip_prefix('/')?.split('/').next()?;
+        let ty = self.find_type(segment, types)?;
+        if trimmed == path && segment == ty {
+            return None;
+        }
+        Some(alias_location(prefix, trimmed, query, ty))
+    }
+
+    /// The canonical path and query of `uri`, a request of the type segment `ty` after `prefix`,
+    /// for the `self` links
+    pub fn canonical_uri(&self, prefix: &str, uri: &Uri, ty: &str) -> Uri {
+        self.canonical_location(prefix, uri.path(), uri.query(), std::iter::once(ty))
+            .and_then(|location| location.parse().ok())
+            .unwrap_or_else(|| uri.clone())
+    }
+}
+
+/// What the operations need from a request
+#[derive(Debug)]
+pub struct OperationRequest {
+    pub context: OperationContext,
+    pub query: Query,
+    /// The base of the links, like `http://localhost/api`
+    pub base: String,
+    /// The path and the query of the request as seen by the clients
+    pub request_path: RawUri,
+}
+
+/// Checks the `Content-Type` and `Accept` headers, with all of their values joined by `, `, and
+/// the required profiles in them unless the header rules are off, where the header rules are
+/// adjusted by the conformance level
+pub fn check_headers(
+    settings: &JsonApiSettings, content_type: &Option<String>, accept: &Option<String>,
+) -> RbhResult<()> {
+    trace::check_headers_span().in_scope(|| {
+        let header_rules = settings.conformance.header_rules(settings.header_rules);
+        RuleDispatcher::check_headers(&settings.version, header_rules, content_type, accept)?;
+        if header_rules != Strictness::Off && settings.has_profiles() {
+            settings.profiles.check(content_type, accept)?;
+        }
+        Ok(())
+    })
+}
+
+/// Checks the headers like `check_headers`, and parses the query of `uri`, which is checked like
+/// `check_query`
+pub fn check_request(
+    settings: &JsonApiSettings, content_type: &Option<String>, accept: &Option<String>, uri: &Uri,
+) -> RbhResult<Query> {
+    check_headers(settings, content_type, accept)?;
+    let mut query = Query::from_uri(uri)?;
+    query.resolve_aliases(&settings.type_aliases);
+    check_query(settings, content_type, accept, uri, &query)?;
+    Ok(query)
+}
+
+/// Checks the parsed `query` against the query limits, its parameters and member names by the
+/// conformance level, and then the request with the custom request rules
+pub fn check_query(
+    settings: &JsonApiSettings, content_type: &Option<String>, accept: &Option<String>, uri: &Uri,
+    query: &Query,
+) -> RbhResult<()> {
+    settings.query_limits.check(query)?;
+    settings.conformance.check_query(uri.query(), query, &settings.query_parameters)?;
+    let req = RuleRequest { content_type, accept, uri, query };
+    settings.rules.check_request(&settings.version, &req)
+}
+
+/// Checks the JSON of the document answering `query` by the conformance level, like the full
+/// linkage of the included resources, and then with the custom response rules
+pub fn check_document(
+    settings: &JsonApiSettings, doc: &serde_json::Value, query: &Query,
+) -> RbhResult<()> {
+    settings.conformance.check_document(doc, query)?;
+    settings.rules.check_response(&settings.version, doc, query)
+}
+
+/// The items of the collection allowed by `Fetching::can`
+pub async fn fetch_collection<T: Fetching>(
+    service: &T, req: &OperationRequest,
+) -> RbhResult<Vec<T::Item>> {
+    let plan = FetchPlan::new::<T::Item>(&req.query).with_tenant(req.context.tenant.as_deref());
+    let vec = service.fetch_collection(&plan).await?;
+    Ok(authorize_collection(service, &req.context, vec).await)
+}
+
+/// The resource of `id`, failing if it's not allowed by `Fetching::can`
+pub async fn fetch_single<T: Fetching>(
+    service: &T, id: &str, req: &OperationRequest,
+) -> RbhResult<Option<T::Item>> {
+    let plan = FetchPlan::new::<T::Item>(&req.query).with_tenant(req.context.tenant.as_deref());
+    let item = service.fetch_single(id, &plan).await?;
+    if let Some(item) = &item {
+        authorize_single(service, &req.context, &Action::FetchSingle, item).await?;
+    }
+    Ok(item)
+}
+
+/// The relationship of the resource of `id`, failing if the resource is not allowed
+pub async fn fetch_relationship<T: Fetching>(
+    service: &T, id: &str, related_field: &str, req: &OperationRequest,
+) -> RbhResult<Relationship> {
+    let action = Action::FetchRelationship(related_field.to_string());
+    authorize_parent(service, &req.context, id, &action, &req.query).await?;
+    service.fetch_relationship(id, related_field, &req.base, &req.query, &req.request_path).await
+}
+
+/// The related resources of the resource of `id`, failing if the resource is not allowed, where
+/// the related and the included resources not allowed by `Fetching::can_include` are dropped
+pub async fn fetch_related<T: Fetching>(
+    service: &T, id: &str, related_field: &str, req: &OperationRequest,
+) -> RbhResult<serde_json::Value> {
+    let action = Action::FetchRelated(related_field.to_string());
+    authorize_parent(service, &req.context, id, &action, &req.query).await?;
+    let mut doc =
+        service.fetch_related(id, related_field, &req.base, &req.query, &req.request_path).await?;
+    if let Some(data) = doc.get_mut("data") {
+        authorize_resources(service, &req.context, &action, data).await;
+    }
+    authorize_included(service, &req.context, &action, &mut doc).await;
+    Ok(doc)
+}
+
+/// Runs the operation of `route`, returning the JSON of its document, where the included
+/// resources not allowed by `Fetching::can_include` are dropped
+pub async fn dispatch<T: Fetching>(
+    service: &T, route: &Route, req: &OperationRequest,
+) -> RbhResult<serde_json::Value> {
+    match route {
+        Route::Collection => {
+            let vec = fetch_collection(service, req).await?;
+            let doc = service.vec_to_document(&vec, &req.base, &req.query, &req.request_path);
+            let mut doc = to_json(&doc.await?)?;
+            authorize_included(service, &req.context, &Action::FetchCollection, &mut doc).await;
+            Ok(doc)
+        },
+        Route::Single(id) => {
+            let item = fetch_single(service, id, req).await?;
+            let doc = item.to_document_automatically(&req.base, &req.query, &req.request_path)?;
+            let mut doc = to_json(&doc)?;
+            authorize_included(service, &req.context, &Action::FetchSingle, &mut doc).await;
+            Ok(doc)
+        },
+        Route::Relationship(id, related_field) => {
+            to_json(&fetch_relationship(service, id, related_field, req).await?)
+        },
+        Route::Related(id, related_field) => fetch_related(service, id, related_field, req).await,
+    }
+}
+
+/// Runs the operation of `route` like `dispatch`, answering with the document in `cache` if any,
+/// and caching the document of a successful operation
+pub async fn dispatch_cached<T: Fetching>(
+    cache: &dyn DocumentCache, service: &T, route: &Route, req: &OperationRequest,
+) -> RbhResult<serde_json::Value> {
+    let key = CacheKey::new(&T::Item::ty(), route, req);
+    cached(Some(cache), key, dispatch(service, route, req)).await
+}
+
+/// The document of `key` in `cache` if any, or the one of `fetch`, which is cached if it succeeds,
+/// for the endpoints running the operations by themselves
+pub async fn cached(
+    cache: Option<&dyn DocumentCache>, key: CacheKey,
+    fetch: impl Future<Output = RbhResult<serde_json::Value>>,
+) -> RbhResult<serde_json::Value> {
+    let cache = match cache {
+        Some(cache) => cache,
+        None => return fetch.await,
+    };
+    if let Some(doc) = cache.get(&key).await {
+        return Ok(doc);
+    }
+    let doc = fetch.await?;
+    cache.put(key, doc.clone()).await;
+    Ok(doc)
+}
+
+pub fn to_json(doc: &impl Serialize) -> RbhResult<serde_json::Value> {
+    trace::serialize_span()
+        .in_scope(|| serde_json::to_value(doc))
+        .map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+/// The status of `err`, which is `400 Bad Request` if it has none
+pub fn error_status(err: &error::Error) -> StatusCode {
+    err.status.as_deref().and_then(|s| s.parse().ok()).unwrap_or(StatusCode::BAD_REQUEST)
+}
+
+/// A `200 OK` response of `doc` with the JSON:API `Content-Type`
+pub fn json_response(doc: &impl Serialize) -> RbhResult<Response<Vec<u8>>> {
+    let body = trace::serialize_span()
+        .in_scope(|| serde_json::to_vec(doc))
+        .map_err(|err| error::Error::InvalidJson(&err, None))?;
+    let mut resp = Response::new(body);
+    resp.headers_mut().insert(header::CONTENT_TYPE, HeaderValue::from_static(JSON_API_HEADER));
+    Ok(resp)
+}
+
+/// A `200 OK` response of `doc` in the encoding negotiated from `accept`, with the `Content-Type`
+/// of `settings`
+pub fn document_response(
+    settings: &JsonApiSettings, accept: &Option<String>, doc: &impl Serialize,
+) -> RbhResult<Response<Vec<u8>>> {
+    let encoding = Encoding::negotiate(accept);
+    let body = trace::serialize_span().in_scope(|| encoding.encode(doc))?;
+    let mut resp = Response::new(body);
+    let content_type = HeaderValue::from_str(&settings.content_type_of(encoding))
+        .unwrap_or_else(|_| HeaderValue::from_static(encoding.media_type()));
+    resp.headers_mut().insert(header::CONTENT_TYPE, content_type);
+    Ok(resp)
+}
+
+/// The response of `doc` like `document_response`, with its `ETag`, which is generated from the
+/// body if not given, and the `Last-Modified` of `last_modified` if any, or `304 Not Modified` if
+/// the client with `headers` already has it as `is_fresh`
+pub fn conditional_response(
+    settings: &JsonApiSettings, headers: &HeaderMap, doc: &impl Serialize, etag: Option<String>,
+    last_modified: Option<SystemTime>,
+) -> RbhResult<Response<Vec<u8>>> {
+    let mut resp = document_response(settings, &header_value(headers, header::ACCEPT), doc)?;
+    let etag = etag.unwrap_or_else(|| etag_of(resp.body()));
+    if is_fresh(headers, Some(&etag), last_modified) {
+        return Ok(not_modified(Some(&etag), last_modified));
+    }
+    apply_validators(resp.headers_mut(), Some(&etag), last_modified);
+    Ok(resp)
+}
+
+/// A strong `ETag` of `content`
+pub fn etag_of(content: &[u8]) -> String { format!("\"{}\"", crate::hash::digest(content)) }
+
+/// If any entity tag in the `If-None-Match` of `headers` matches `etag` with the weak comparison
+pub fn if_none_match(headers: &HeaderMap, etag: &str) -> bool {
+    header_value(headers, header::IF_NONE_MATCH).is_some_and(|tags| {
+        tags.split(',').map(str::trim).any(|tag| tag == "*" || tag.trim_start_matches("W/") == etag)
+    })
+}
+
+/// If the client with `headers` has the resource of `etag` and `last_modified`, where
+/// `If-Modified-Since` is ignored if there is `If-None-Match`, as RFC 7232
+pub fn is_fresh(
+    headers: &HeaderMap, etag: Option<&str>, last_modified: Option<SystemTime>,
+) -> bool {
+    if headers.contains_key(header::IF_NONE_MATCH) {
+        return etag.is_some_and(|etag| if_none_match(headers, etag));
+    }
+    let if_modified_since = header_value(headers, header::IF_MODIFIED_SINCE);
+    last_modified.is_some_and(|time| !modified_since(time, &if_modified_since))
+}
+
+/// The `304 Not Modified` response with the `ETag` and the `Last-Modified` of the resource
+pub fn not_modified(etag: Option<&str>, last_modified: Option<SystemTime>) -> Response<Vec<u8>> {
+    let mut resp = Response::new(vec![]);
+    *resp.status_mut() = StatusCode::NOT_MODIFIED;
+    apply_validators(resp.headers_mut(), etag, last_modified);
+    resp
+}
+
+/// Puts the `ETag` and the `Last-Modified` headers of the resource into `headers`
+pub fn apply_validators(
+    headers: &mut HeaderMap, etag: Option<&str>, last_modified: Option<SystemTime>,
+) {
+    if let Some(etag) = etag.and_then(|etag| HeaderValue::from_str(etag).ok()) {
+        headers.insert(header::ETAG, etag);
+    }
+    if let Some(date) = last_modified.and_then(|time| HeaderValue::from_str(&http_date(time)).ok())
+    {
+        headers.insert(header::LAST_MODIFIED, date);
+    }
+}
+
+/// The `lastModified` in the `meta` of the primary resource of the JSON of a single resource
+/// document, before it's namespaced by `meta_namespace`
+pub fn last_modified_of(doc: &serde_json::Value) -> Option<SystemTime> {
+    let date = doc.get("data")?.get("meta")?.get("lastModified")?.as_str()?;
+    httpdate::parse_http_date(date).ok()
+}
+
+/// Puts the `Deprecation` and the `Sunset` headers of `deprecation`, the one of a resource type,
+/// into `headers` if any, for all of the responses of the routes of the type
+pub fn apply_deprecation(deprecation: Option<&Deprecation>, headers: &mut HeaderMap) {
+    for (name, value) in deprecation.iter().flat_map(|deprecation| deprecation.headers()) {
+        headers.insert(name, value);
+    }
+}
+
+/// The member of `meta` correlating an error document with the logs, which is the `X-Request-Id`
+/// in the actix endpoint
+pub const CORRELATION_ID: &str = "requestId";
+
+/// Strips the internals of a `5xx` error, like the error strings of the backends or the file and
+/// the line in `meta`, leaving its `id`, `status`, `code` and `title` with the correlation id in
+/// `meta`, which is generated if missing. The full error is logged with the correlation id
+pub fn redact_error(err: &mut error::Error) {
+    if !error_status(err).is_server_error() {
+        return;
+    }
+    let correlation_id = err
+        .meta
+        .as_ref()
+        .and_then(|meta| meta.get(CORRELATION_ID))
+        .cloned()
+        .unwrap_or_else(|| uuid::Uuid::new_v4().to_string().into());
+    log::error!("Redacted error, {} = {}: {:?}", CORRELATION_ID, correlation_id, err);
+    *err = error::Error {
+        id: err.id.take(),
+        status: err.status.take(),
+        code: err.code.take(),
+        title: err.title.take(),
+        meta: Some(std::iter::once((CORRELATION_ID.to_string(), correlation_id)).collect()),
+        ..Default::default()
+    };
+}
+
+/// The error document of `err` and the ones in its `others`, with the status of `err`
+pub fn error_response(err: error::Error) -> Response<Vec<u8>> {
+    let status = error_status(&err);
+    let mut resp = json_response(&Document::errors(err.into_errors())).unwrap_or_default();
+    *resp.status_mut() = status;
+    resp
+}
+
+/// The answer to the methods of the operations which are not implemented, like `POST` and
+/// `DELETE`, where `allowed` is the `Allow` header like `GET, HEAD`
+pub fn method_not_allowed(method: &Method, allowed: &'static str) -> Response<Vec<u8>> {
+    let mut resp = error_response(error::Error::MethodNotAllowed(method.as_str(), allowed, None));
+    resp.headers_mut().insert(header::ALLOW, HeaderValue::from_static(allowed));
+    resp
+}
+
+/// The methods answered on every route, as only `Fetching` operations are supported now
+pub const ALLOWED_METHODS: &str = "GET, HEAD, OPTIONS";
+
+/// If `method` runs the fetching operations, which are `GET` and `HEAD`
+pub fn is_fetching(method: &Method) -> bool { method == Method::GET || method == Method::HEAD }
+
+/// The answer to the methods of the routes other than `is_fetching`, which is `204 No Content`
+/// with the `Allow` header to `OPTIONS`, and `405 Method Not Allowed` to the others
+pub fn other_method_response(method: &Method) -> Response<Vec<u8>> {
+    if method != Method::OPTIONS {
+        return method_not_allowed(method, ALLOWED_METHODS);
+    }
+    let mut resp = Response::new(vec![]);
+    *resp.status_mut() = StatusCode::NO_CONTENT;
+    resp.headers_mut().insert(header::ALLOW, HeaderValue::from_static(ALLOWED_METHODS));
+    resp
+}
+
+/// Strips the body of the response to `HEAD`, leaving the headers of the one to `GET`
+pub fn strip_head_body(method: &Method, resp: &mut Response<Vec<u8>>) {
+    if method == Method::HEAD {
+        resp.body_mut().clear();
+    }
+}
+
+/// The `308 Permanent Redirect` of the routes of a type alias to `location`, which keeps the
+/// method of the request
+pub fn redirect_response(location: &str) -> Response<Vec<u8>> {
+    let mut resp = Response::new(vec![]);
+    *resp.status_mut() = StatusCode::PERMANENT_REDIRECT;
+    if let Ok(location) = HeaderValue::from_str(location) {
+        resp.headers_mut().insert(header::LOCATION, location);
+    }
+    resp
+}
+
+/// The location of the route of `ty` redirected from `path`, a path of one of its aliases after
+/// `prefix`, like `/api/person/1?include=dogs` into `/api/people/1?include=dogs`
+pub fn alias_location(prefix: &str, path: &str, query: Option<&str>, ty: &str) -> String {
+    let rest = path.strip_prefix(prefix).unwrap_or(path).trim_start_matches('/');
+    let mut location = match rest.split_once('/') {
+        Some((_, rest)) => format!("{}/{}/{}", prefix, ty, rest),
+        None => format!("{}/{}", prefix, ty),
+    };
+    if let Some(query) = query {
+        location.push('?');
+        location.push_str(query);
+    }
+    location
+}
+
+/// The HTTP-date of `time` for the `Last-Modified` header, like `Sun, 06 Nov 1994 08:49:37 GMT`
+pub fn http_date(time: SystemTime) -> String { httpdate::fmt_http_date(time) }
+
+/// If the resource last modified at `last_modified` is newer than the `If-Modified-Since` date in
+/// whole seconds, where a missing or invalid date counts as modified, as RFC 7232 ignores it
+pub fn modified_since(last_modified: SystemTime, if_modified_since: &Option<String>) -> bool {
+    match if_modified_since.as_deref().and_then(|date| date.trim().parse::<HttpDate>().ok()) {
+        Some(since) => HttpDate::from(last_modified) > since,
+        None => true,
+    }
+}
+
+/// All of the values of the header `name` joined by `, `, since a list header may be split into
+/// several lines, where the non-UTF-8 bytes are replaced rather than rejected, leaving the invalid
+/// values to the rules
+pub fn header_value(headers: &HeaderMap, name: header::HeaderName) -> Option<String> {
+    let values: Vec<_> = headers
+        .get_all(name)
+        .iter()
+        .map(|value| String::from_utf8_lossy(value.as_bytes()))
+        .collect();
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.join(", "))
+    }
+}
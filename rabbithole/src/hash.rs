@@ -0,0 +1,35 @@
+//! The content hashes of the entities and the documents, shared by the `ETag`s of the endpoints,
+//! the document caches and the change detection, where the same content always has the same hash:
+//! ```ignore
+//! let etag = format!("\"{}\"", doc.content_hash());
+//! ```
+//! The hashes are the hex SHA-1 digests of the canonical JSON, where the keys of the objects are
+//! sorted and the included resources are in the order of their types and ids, so they don't
+//! depend on the order of the fields or of the `HashMap`s
+
+use serde::Serialize;
+use serde_json::Value;
+use sha1::{Digest, Sha1};
+
+/// The hex SHA-1 digest of `content`
+pub fn digest(content: &[u8]) -> String {
+    Sha1::digest(content).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// The content hash of `value`, like a `Document`, a `Resource` or the JSON of a response body
+pub fn content_hash<T: Serialize + ?Sized>(value: &T) -> String {
+    digest(&serde_json::to_vec(&canonical(value)).unwrap())
+}
+
+/// The canonical JSON of `value`, where the included resources of a document are in the order of
+/// their types and ids. The keys of the objects are sorted already, as the objects of `serde_json`
+/// are `BTreeMap`s without its `preserve_order` feature
+pub fn canonical<T: Serialize + ?Sized>(value: &T) -> Value {
+    let mut value = serde_json::to_value(value).expect("the content is not JSON");
+    if let Some(Value::Array(included)) = value.get_mut("included") {
+        included.sort_by(|a, b| {
+            (a["type"].as_str(), a["id"].as_str()).cmp(&(b["type"].as_str(), b["id"].as_str()))
+        });
+    }
+    value
+}
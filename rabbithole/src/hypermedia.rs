@@ -0,0 +1,258 @@
+//! The conversions of the JSON:API documents into the other hypermedia formats, HAL and Siren,
+//! for the consumers standardized on them, reusing the links, the relationships and the included
+//! resources of the documents:
+//!   - HAL: the attributes are the properties, the links and the `related` links of the
+//!     relationships are in `_links`, and the included resources are in `_embedded`
+//!   - Siren: the type is the `class`, the attributes are the `properties`, and the related
+//!     resources are the sub-entities, where the included ones are embedded entirely
+//!
+//! They are negotiated from `Accept` like the other encodings in `rabbithole::encoding`
+//! NOTICE:
+//!   - The error documents are kept in JSON:API, as there is no error in HAL or Siren
+//!   - The included resources are embedded into the primary data only, without the nested ones
+
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+pub const HAL_HEADER: &str = "application/hal+json";
+pub const SIREN_HEADER: &str = "application/vnd.siren+json";
+
+/// The included resources of a document by their types and ids
+type IncludedMap<'a> = HashMap<(&'a str, &'a str), &'a Value>;
+
+/// The HAL representation of the JSON of a document
+pub fn to_hal(doc: &Value) -> Value {
+    if doc.get("errors").is_some() {
+        return doc.clone();
+    }
+    let included = included_of(doc);
+    let mut res = match doc.get("data") {
+        Some(Value::Array(resources)) => {
+            // The resources are embedded by their types
+            let mut embedded: Map<String, Value> = Map::new();
+            for resource in resources {
+                let ty = resource.get("type").and_then(Value::as_str).unwrap_or("items");
+                let items = embedded.entry(ty).or_insert_with(|| Value::Array(vec![]));
+                if let Value::Array(items) = items {
+                    items.push(Value::Object(hal_resource(resource, &included, true)));
+                }
+            }
+            let mut res = Map::new();
+            res.insert("_embedded".into(), Value::Object(embedded));
+            res
+        },
+        Some(resource @ Value::Object(_)) => hal_resource(resource, &included, true),
+        _ => Map::new(),
+    };
+
+    let links = links_of(doc);
+    if !links.is_empty() {
+        let hal_links = res.entry("_links").or_insert_with(|| json!({}));
+        if let Value::Object(hal_links) = hal_links {
+            for (name, href) in links {
+                hal_links.entry(name).or_insert_with(|| json!({ "href": href }));
+            }
+        }
+    }
+    if let Some(meta) = doc.get("meta") {
+        res.insert("meta".into(), meta.clone());
+    }
+    Value::Object(res)
+}
+
+/// The Siren representation of the JSON of a document
+pub fn to_siren(doc: &Value) -> Value {
+    if doc.get("errors").is_some() {
+        return doc.clone();
+    }
+    let included = included_of(doc);
+    let mut res = match doc.get("data") {
+        Some(Value::Array(resources)) => {
+            let entities = resources
+                .iter()
+                .map(|resource| {
+                    let mut entity = siren_entity(resource, &included, true);
+                    entity.insert("rel".into(), json!(["item"]));
+                    Value::Object(entity)
+                })
+                .collect();
+            let mut res = Map::new();
+            res.insert("class".into(), json!(["collection"]));
+            res.insert("entities".into(), Value::Array(entities));
+            res
+        },
+        Some(resource @ Value::Object(_)) => siren_entity(resource, &included, true),
+        _ => Map::new(),
+    };
+
+    let links = links_of(doc);
+    if !links.is_empty() {
+        let siren_links = res.entry("links").or_insert_with(|| json!([]));
+        if let Value::Array(siren_links) = siren_links {
+            for (name, href) in links {
+                let exists = siren_links.iter().any(|link| link["rel"][0] == name.as_str());
+                if !exists {
+                    siren_links.push(json!({ "rel": [name], "href": href }));
+                }
+            }
+        }
+    }
+    if let Some(meta) = doc.get("meta") {
+        let properties = res.entry("properties").or_insert_with(|| json!({}));
+        if let Value::Object(properties) = properties {
+            properties.insert("meta".into(), meta.clone());
+        }
+    }
+    Value::Object(res)
+}
+
+fn hal_resource(resource: &Value, included: &IncludedMap, embed: bool) -> Map<String, Value> {
+    let mut res = Map::new();
+    let mut links = Map::new();
+    for (name, href) in links_of(resource) {
+        links.insert(name, json!({ "href": href }));
+    }
+    if let Some(id) = resource.get("id") {
+        res.insert("id".into(), id.clone());
+    }
+    if let Some(Value::Object(attributes)) = resource.get("attributes") {
+        res.extend(attributes.clone());
+    }
+
+    let mut embedded = Map::new();
+    if let Some(Value::Object(relationships)) = resource.get("relationships") {
+        for (name, relationship) in relationships {
+            if let Some(href) = related_href(relationship) {
+                links.insert(name.clone(), json!({ "href": href }));
+            }
+            if !embed {
+                continue;
+            }
+            let related: Vec<Value> = identifiers(relationship)
+                .into_iter()
+                .filter_map(|key| included.get(&key))
+                .map(|related| Value::Object(hal_resource(related, included, false)))
+                .collect();
+            match relationship.get("data") {
+                Some(Value::Array(_)) if !related.is_empty() => {
+                    embedded.insert(name.clone(), Value::Array(related));
+                },
+                Some(Value::Object(_)) => {
+                    if let Some(related) = related.into_iter().next() {
+                        embedded.insert(name.clone(), related);
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    if !links.is_empty() {
+        res.insert("_links".into(), Value::Object(links));
+    }
+    if !embedded.is_empty() {
+        res.insert("_embedded".into(), Value::Object(embedded));
+    }
+    res
+}
+
+fn siren_entity(resource: &Value, included: &IncludedMap, embed: bool) -> Map<String, Value> {
+    let mut res = Map::new();
+    if let Some(ty) = resource.get("type") {
+        res.insert("class".into(), json!([ty]));
+    }
+    let mut properties = Map::new();
+    if let Some(id) = resource.get("id") {
+        properties.insert("id".into(), id.clone());
+    }
+    if let Some(Value::Object(attributes)) = resource.get("attributes") {
+        properties.extend(attributes.clone());
+    }
+    res.insert("properties".into(), Value::Object(properties));
+
+    let mut links: Vec<Value> = links_of(resource)
+        .into_iter()
+        .map(|(name, href)| json!({ "rel": [name], "href": href }))
+        .collect();
+    let mut entities = Vec::new();
+    if let Some(Value::Object(relationships)) = resource.get("relationships") {
+        for (name, relationship) in relationships {
+            if let Some(href) = related_href(relationship) {
+                links.push(json!({ "rel": [name], "href": href }));
+            }
+            if !embed {
+                continue;
+            }
+            // The included resources are embedded entirely, and the others by their identifiers
+            for (ty, id) in identifiers(relationship) {
+                let mut entity = match included.get(&(ty, id)) {
+                    Some(related) => siren_entity(related, included, false),
+                    None => {
+                        let mut entity = Map::new();
+                        entity.insert("class".into(), json!([ty]));
+                        entity.insert("properties".into(), json!({ "id": id }));
+                        entity
+                    },
+                };
+                entity.insert("rel".into(), json!([name]));
+                entities.push(Value::Object(entity));
+            }
+        }
+    }
+
+    if !entities.is_empty() {
+        res.insert("entities".into(), Value::Array(entities));
+    }
+    if !links.is_empty() {
+        res.insert("links".into(), Value::Array(links));
+    }
+    res
+}
+
+fn included_of(doc: &Value) -> IncludedMap<'_> {
+    let mut included = HashMap::new();
+    if let Some(Value::Array(resources)) = doc.get("included") {
+        for resource in resources {
+            if let Some(key) = identifier_of(resource) {
+                included.insert(key, resource);
+            }
+        }
+    }
+    included
+}
+
+fn identifier_of(resource: &Value) -> Option<(&str, &str)> {
+    Some((resource.get("type")?.as_str()?, resource.get("id")?.as_str()?))
+}
+
+/// The identifiers of the data of a relationship
+fn identifiers(relationship: &Value) -> Vec<(&str, &str)> {
+    match relationship.get("data") {
+        Some(Value::Array(data)) => data.iter().filter_map(identifier_of).collect(),
+        Some(data @ Value::Object(_)) => identifier_of(data).into_iter().collect(),
+        _ => vec![],
+    }
+}
+
+/// The names and the `href`s of the `links` of a document or a resource
+fn links_of(value: &Value) -> Vec<(String, &str)> {
+    match value.get("links") {
+        Some(Value::Object(links)) => {
+            links.iter().filter_map(|(name, link)| Some((name.clone(), href(link)?))).collect()
+        },
+        _ => vec![],
+    }
+}
+
+fn related_href(relationship: &Value) -> Option<&str> {
+    href(relationship.get("links")?.get("related")?)
+}
+
+/// The `href` of a link, which is a string or a link object
+fn href(link: &Value) -> Option<&str> {
+    match link {
+        Value::String(href) => Some(href),
+        Value::Object(link) => link.get("href")?.as_str(),
+        _ => None,
+    }
+}
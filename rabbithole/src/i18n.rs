@@ -0,0 +1,146 @@
+//! The message catalogs of the errors by their codes like `RBH-0405`, where the built-in messages
+//! are in English, and the applications register the ones of the other locales, or override the
+//! built-in ones:
+//! ```ignore
+//! let localization = Localization::new("en").with_messages("zh", vec![(
+//!     "RBH-0405",
+//!     Message::new("资源不存在", Some("资源 `{ty}/{id}` 不存在".into())),
+//! )]);
+//! let locale = localization.negotiate(accept_language.as_deref());
+//! localization.translate(&mut err, locale);
+//! ```
+//! The `{name}`s in the details are replaced with the arguments of the errors, which are the names
+//! of the parameters of the error constructors
+
+use crate::model::error;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref BUILTIN: HashMap<&'static str, Message> = error::Error::MESSAGES
+        .iter()
+        .map(|(code, title, detail)| {
+            (*code, Message::new(*title, Some(detail.replace(":?}", "}"))))
+        })
+        .collect();
+}
+
+/// The built-in message of the error `code`
+pub fn builtin(code: &str) -> Option<&'static Message> { BUILTIN.get(code) }
+
+/// A translation of an error, where `detail` replaces the original one if given
+#[derive(Debug, Clone, PartialEq)]
+pub struct Message {
+    pub title: String,
+    pub detail: Option<String>,
+}
+
+impl Message {
+    pub fn new(title: impl Into<String>, detail: Option<String>) -> Self {
+        Self { title: title.into(), detail }
+    }
+
+    /// The detail with the `{name}`s replaced with the arguments in `params`
+    pub fn render_detail(&self, params: &[(&str, String)]) -> Option<String> {
+        let mut detail = self.detail.clone()?;
+        for (name, value) in params {
+            detail = detail.replace(&format!("{{{}}}", name), value);
+        }
+        Some(detail)
+    }
+}
+
+/// The message catalogs of the locales, translating the errors by their codes.
+/// The locale of a request is negotiated with `Accept-Language`, falling back to `default_locale`,
+/// whose errors are left as they are if it has no catalog
+#[derive(Debug, Clone)]
+pub struct Localization {
+    default_locale: String,
+    catalogs: HashMap<String, HashMap<String, Message>>,
+}
+
+impl Localization {
+    pub fn new(default_locale: &str) -> Self {
+        Self { default_locale: default_locale.to_ascii_lowercase(), catalogs: HashMap::new() }
+    }
+
+    /// Adds the messages of `locale` by the error codes
+    pub fn with_messages<C: Into<String>>(
+        mut self, locale: &str, messages: impl IntoIterator<Item = (C, Message)>,
+    ) -> Self {
+        let catalog = self.catalogs.entry(locale.to_ascii_lowercase()).or_default();
+        catalog.extend(messages.into_iter().map(|(code, message)| (code.into(), message)));
+        self
+    }
+
+    pub fn default_locale(&self) -> &str { &self.default_locale }
+
+    /// The best locale for the `Accept-Language` header, where each language range is looked up
+    /// by removing the subtags from the end, like `zh-hant-tw`, `zh-hant` and `zh`
+    pub fn negotiate(&self, accept_language: Option<&str>) -> &str {
+        let mut ranges: Vec<(&str, f32)> = accept_language
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';');
+                let tag = parts.next()?.trim();
+                let weight = parts
+                    .filter_map(|param| param.trim().strip_prefix("q="))
+                    .find_map(|q| q.trim().parse::<f32>().ok())
+                    .unwrap_or(1.0);
+                Some((tag, weight)).filter(|(tag, weight)| !tag.is_empty() && *weight > 0.0)
+            })
+            .collect();
+        // A stable sort keeps the order of the header for the same weights
+        ranges.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (range, _) in ranges {
+            if range == "*" {
+                break;
+            }
+            let mut range = range.to_ascii_lowercase();
+            loop {
+                if let Some(locale) = self.find_locale(&range) {
+                    return locale;
+                }
+                match range.rfind('-') {
+                    Some(idx) => range.truncate(idx),
+                    None => break,
+                }
+            }
+        }
+        &self.default_locale
+    }
+
+    fn find_locale(&self, locale: &str) -> Option<&str> {
+        if locale == self.default_locale {
+            return Some(&self.default_locale);
+        }
+        self.catalogs.get_key_value(locale).map(|(locale, _)| locale.as_str())
+    }
+
+    /// The message of the error `code` in `locale`, which is looked up by removing the subtags
+    /// from the end like `negotiate`, so `de-ch` only overrides some of the messages of `de`
+    pub fn message(&self, code: &str, locale: &str) -> Option<&Message> {
+        let mut locale = locale;
+        loop {
+            if let Some(message) = self.catalogs.get(locale).and_then(|catalog| catalog.get(code)) {
+                return Some(message);
+            }
+            locale = &locale[.. locale.rfind('-')?];
+        }
+    }
+
+    /// Translates the title and the detail of `err` and of its `others` into `locale`, if there
+    /// are the messages
+    pub fn translate(&self, err: &mut error::Error, locale: &str) {
+        err.for_each_mut(|err| {
+            let message = err.code.as_deref().and_then(|code| self.message(code, locale));
+            if let Some(message) = message {
+                err.title = Some(message.title.clone());
+                if let Some(detail) = message.render_detail(&err.params) {
+                    err.detail = Some(detail);
+                }
+            }
+        });
+    }
+}
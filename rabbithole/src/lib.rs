@@ -1,5 +1,4 @@
 #![feature(associated_type_defaults)]
-#![feature(step_trait)]
 
 #[macro_use]
 extern crate serde_derive;
@@ -12,8 +11,30 @@ pub type RbhResult<T> = Result<T, Error>;
 pub type RbhOptionRes<T> = Result<Option<T>, Error>;
 pub const JSON_API_HEADER: &str = "application/vnd.api+json";
 
+pub mod access_log;
+pub mod cache;
+pub mod cache_control;
+pub mod deprecation;
+pub mod encoding;
 pub mod entity;
+pub mod event;
+pub mod handler;
+pub mod hash;
+pub mod hypermedia;
+pub mod i18n;
 pub mod model;
+pub mod openapi;
 pub mod operation;
+#[cfg(feature = "parallel")]
+pub mod parallel;
+pub mod postman;
 pub mod query;
 pub mod rule;
+pub mod schema;
+pub mod service;
+pub mod store;
+pub mod stream;
+pub mod tenant;
+pub mod testing;
+pub mod trace;
+pub mod typescript;
@@ -62,6 +62,10 @@ impl Default for Document {
 impl Document {
     pub fn null(links: Option<Links>) -> Self { Self { links, ..Default::default() } }
 
+    pub fn errors(errors: Errors) -> Self {
+        Self { item: DocumentItem::Errors(errors), ..Default::default() }
+    }
+
     pub fn single_resource(resource: Resource, included: Included, links: Option<Links>) -> Self {
         Self {
             item: DocumentItem::PrimaryData(Some((
@@ -84,19 +88,47 @@ impl Document {
     }
 }
 
+impl Document {
+    /// The content hash of the document, which is the same for the same content whatever the
+    /// order of its fields and included resources
+    pub fn content_hash(&self) -> String { crate::hash::content_hash(self) }
+
+    /// The HAL representation of the document
+    pub fn to_hal(&self) -> Value {
+        crate::hypermedia::to_hal(&serde_json::to_value(self).expect("the document is not JSON"))
+    }
+
+    /// The Siren representation of the document
+    pub fn to_siren(&self) -> Value {
+        crate::hypermedia::to_siren(&serde_json::to_value(self).expect("the document is not JSON"))
+    }
+}
+
 impl Serialize for Document {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
-        let mut state = serializer.serialize_struct("Document", 4)?;
+        // The fields are counted, as the binary encodings like CBOR write the lengths of the maps
+        let has_included = matches!(
+            &self.item, DocumentItem::PrimaryData(Some((_, included))) if !included.is_empty()
+        );
+        let len = 1
+            + has_included as usize
+            + self.links.is_some() as usize
+            + self.meta.is_some() as usize
+            + self.jsonapi.is_some() as usize;
+        let mut state = serializer.serialize_struct("Document", len)?;
         match self.item {
             DocumentItem::PrimaryData(Some((ref data, ref included))) => {
                 state.serialize_field("data", data)?;
                 if !included.is_empty() {
+                    // Sorted by the identifiers, so the same document is always serialized the same
+                    let mut included: Vec<_> = included.iter().collect();
+                    included.sort_by(|(a, _), (b, _)| (&a.ty, &a.id).cmp(&(&b.ty, &b.id)));
                     state.serialize_field(
                         "included",
-                        &included.values().collect::<Vec<&Resource>>(),
+                        &included.into_iter().map(|(_, res)| res).collect::<Vec<&Resource>>(),
                     )?;
                 }
             },
@@ -1,9 +1,11 @@
 use crate::model::link::{Link, Links, RawUri};
 use crate::model::Meta;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 use std::fmt;
 
-pub type Errors = Vec<Error>;
+/// The errors of a document, which are mostly a single one, kept inline
+pub type Errors = SmallVec<[Error; 1]>;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct ErrorLinks {
@@ -63,6 +65,35 @@ pub struct Error {
     pub source: ErrorSource,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub meta: Option<Meta>,
+    /// The arguments of `detail` by their names, which render the translated details again
+    #[serde(skip)]
+    pub params: Vec<(&'static str, String)>,
+    /// The other errors of the same request, like the other invalid query parameters, which are
+    /// rendered after this one in the error document
+    #[serde(skip)]
+    pub others: Vec<Error>,
+}
+
+impl Error {
+    /// The first one of `errors` with the rest of them in `others`, or `None` if there's no error
+    pub fn from_errors(errors: impl IntoIterator<Item = Error>) -> Option<Error> {
+        let mut errors = errors.into_iter();
+        let mut first = errors.next()?;
+        first.others.extend(errors);
+        Some(first)
+    }
+
+    /// The error and the ones in `others`, as they're rendered in the error document
+    pub fn into_errors(mut self) -> Errors {
+        let others = std::mem::take(&mut self.others);
+        std::iter::once(self).chain(others).collect()
+    }
+
+    /// Runs `f` on the error and then on the ones in `others`
+    pub fn for_each_mut(&mut self, mut f: impl FnMut(&mut Error)) {
+        f(self);
+        self.others.iter_mut().for_each(f);
+    }
 }
 
 impl fmt::Display for Error {
@@ -76,6 +107,41 @@ impl fmt::Display for Error {
     }
 }
 
+impl std::error::Error for Error {}
+
+/// The arguments of the error details, rendered as they are in the details
+pub(crate) trait DetailParam {
+    fn render(&self) -> String;
+}
+
+macro_rules! detail_params {
+    ( display: [$($display:ty,)*], debug: [$($debug:ty,)*] ) => {
+        $(
+            impl DetailParam for $display {
+                fn render(&self) -> String { self.to_string() }
+            }
+        )*
+        $(
+            impl DetailParam for $debug {
+                fn render(&self) -> String { format!("{:?}", self) }
+            }
+        )*
+    };
+}
+
+detail_params! {
+    display: [
+        &str,
+        String,
+        u64,
+        usize,
+        &std::string::FromUtf8Error,
+        &std::str::Utf8Error,
+        &serde_json::Error,
+    ],
+    debug: [&[&str], &[String], &crate::operation::Action,]
+}
+
 /// Rabbithole Error Code:
 ///   1. Magic Word(0..4): Fixed "RBH-", to indicate User that this error is from Rabbithole Server,
 ///                        rather than an application-specific error
@@ -99,6 +165,7 @@ macro_rules! rabbithole_errors_inner {
                     title: Some($title.into()),
                     detail: Some(format!($detail, $($param_arg = $param_arg),*)),
                     source: error_source.unwrap_or(Default::default()),
+                    params: vec![$((stringify!($param_arg), DetailParam::render(&$param_arg)),)*],
                     ..Default::default()
                 }
             }
@@ -109,6 +176,11 @@ macro_rules! rabbithole_errors_inner {
 macro_rules! rabbithole_errors {
     ( $(ty: $ty:ident, status: $status:expr, code: $code:expr, title: $title:expr, detail: $detail:expr, param: [$($param_arg:ident: $param_ty:ty,)*];)* ) => {
         impl Error {
+            /// The built-in messages of the errors as `(code, title, detail)`, where the details
+            /// are the templates of `format!` with the arguments of the errors
+            pub const MESSAGES: &'static [(&'static str, &'static str, &'static str)] =
+                &[$(($code, $title, $detail),)*];
+
             rabbithole_errors_inner!($(ty: $ty, status: $status, code: $code, title: $title, detail: $detail, param: [$($param_arg: $param_ty,)*];)*);
         }
     };
@@ -144,6 +216,69 @@ rabbithole_errors! {
     detail: "The relationship path in Query `{relat_path}` is not supported yet",
     param: [relat_path: &str,];
 
+    ty: TooManyRequests,
+    status: http::StatusCode::TOO_MANY_REQUESTS,
+    code: "RBH-0005",
+    title: "Too Many Requests",
+    detail: "The rate limit is exceeded, please retry after {retry_after} second(s)",
+    param: [retry_after: u64,];
+
+    ty: InternalServerError,
+    status: http::StatusCode::INTERNAL_SERVER_ERROR,
+    code: "RBH-0006",
+    title: "Internal Server Error",
+    detail: "An unexpected error occurred when handling the request",
+    param: [];
+
+    ty: MethodNotAllowed,
+    status: http::StatusCode::METHOD_NOT_ALLOWED,
+    code: "RBH-0007",
+    title: "Method Not Allowed",
+    detail: "The method `{method}` is not supported, the allowed ones are: {allowed}",
+    param: [method: &str, allowed: &str,];
+
+    ty: RouteNotFound,
+    status: http::StatusCode::NOT_FOUND,
+    code: "RBH-0008",
+    title: "Route Not Found",
+    detail: "No route matches the path of the request",
+    param: [];
+
+    ty: StorageFailed,
+    status: http::StatusCode::INTERNAL_SERVER_ERROR,
+    code: "RBH-0009",
+    title: "Storage Failed",
+    detail: "The storage failed to run the query: {reason}",
+    param: [reason: &str,];
+
+    ty: ServiceUnavailable,
+    status: http::StatusCode::SERVICE_UNAVAILABLE,
+    code: "RBH-0010",
+    title: "Service Unavailable",
+    detail: "The operation cannot be served now: {reason}",
+    param: [reason: &str,];
+
+    ty: InvalidCbor,
+    status: http::StatusCode::NOT_ACCEPTABLE,
+    code: "RBH-0011",
+    title: "Invalid CBOR Content",
+    detail: "An error found when encoding or decoding CBOR: {reason}",
+    param: [reason: &str,];
+
+    ty: RuleViolated,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0012",
+    title: "Rule Violated",
+    detail: "The rule `{rule}` is violated: {reason}",
+    param: [rule: &str, reason: &str,];
+
+    ty: InvalidTenant,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0013",
+    title: "Invalid Tenant",
+    detail: "The tenant of the request is missing or invalid in the {from}",
+    param: [from: &str,];
+
     ty: InvalidPaginationType,
     status: http::StatusCode::NOT_ACCEPTABLE,
     code: "RBH-0101",
@@ -186,6 +321,41 @@ rabbithole_errors! {
     detail: "Comparison `{comparison:?}` with {param_cnt} parameter(s) is not supported now",
     param: [comparison: &[String], param_cnt: usize,];
 
+    ty: UnknownQueryParameter,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0107",
+    title: "Unknown Query Parameter",
+    detail: "The query parameter `{parameter}` is not supported",
+    param: [parameter: &str,];
+
+    ty: InvalidMemberName,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0108",
+    title: "Invalid Member Name",
+    detail: "`{name}` in the query parameter `{parameter}` is not a valid member name",
+    param: [name: &str, parameter: &str,];
+
+    ty: InvalidQueryParameterName,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0109",
+    title: "Invalid Query Parameter Name",
+    detail: "The query parameter `{parameter}` is neither defined by JSON:API nor a valid implementation-specific one",
+    param: [parameter: &str,];
+
+    ty: QueryTooComplex,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0110",
+    title: "Query Too Complex",
+    detail: "The query parameter `{parameter}` is over the limit of {limit} {what}",
+    param: [parameter: &str, limit: usize, what: &str,];
+
+    ty: PageOutOfRange,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0111",
+    title: "Page Out of Range",
+    detail: "The page of pagination type `[{page_type}]` is out of the range of the storage",
+    param: [page_type: &str,];
+
     ty: InvalidJsonApiVersion,
     status: http::StatusCode::NOT_ACCEPTABLE,
     code: "RBH-0201",
@@ -193,6 +363,27 @@ rabbithole_errors! {
     detail: "A invalid JSON:API version: {invalid_version}",
     param: [invalid_version: String,];
 
+    ty: InvalidJsonPatch,
+    status: http::StatusCode::UNPROCESSABLE_ENTITY,
+    code: "RBH-0202",
+    title: "Invalid JSON Patch",
+    detail: "The JSON Patch cannot be applied: {reason}",
+    param: [reason: &str,];
+
+    ty: JsonPatchTestFailed,
+    status: http::StatusCode::CONFLICT,
+    code: "RBH-0203",
+    title: "JSON Patch Test Failed",
+    detail: "The value of `{path}` is not the one tested in the JSON Patch",
+    param: [path: &str,];
+
+    ty: InvalidDocumentMember,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0204",
+    title: "Invalid Document Member",
+    detail: "The member `{pointer}` of the document is invalid: {reason}",
+    param: [pointer: &str, reason: &str,];
+
     ty: InvalidContentType,
     status: http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
     code: "RBH-0301",
@@ -207,6 +398,27 @@ rabbithole_errors! {
     detail: "The `Accept` header of Request must be {header_hint}, but {invalid_header} found",
     param: [header_hint: &str, invalid_header: &str,];
 
+    ty: Unauthenticated,
+    status: http::StatusCode::UNAUTHORIZED,
+    code: "RBH-0303",
+    title: "Unauthenticated",
+    detail: "The request is not authenticated: {reason}",
+    param: [reason: &str,];
+
+    ty: OperationForbidden,
+    status: http::StatusCode::FORBIDDEN,
+    code: "RBH-0304",
+    title: "Operation Forbidden",
+    detail: "The operation `{operation}` on `{ty}` needs one of the roles: {roles:?}",
+    param: [operation: &str, ty: &str, roles: &[String],];
+
+    ty: ProfileRequired,
+    status: http::StatusCode::BAD_REQUEST,
+    code: "RBH-0305",
+    title: "Profile Required",
+    detail: "The request must apply the profile `{profile}` in the `profile` parameter of its `Content-Type` or `Accept` header",
+    param: [profile: &str,];
+
     ty: FieldNotExist,
     status: http::StatusCode::NOT_FOUND,
     code: "RBH-0401",
@@ -221,6 +433,13 @@ rabbithole_errors! {
     detail: "The type of `{field}` is not match: comparing `{slf}` and `{other}`",
     param: [field: &str, slf: &str, other: &str,];
 
+    ty: ActionForbidden,
+    status: http::StatusCode::FORBIDDEN,
+    code: "RBH-0403",
+    title: "Action Forbidden",
+    detail: "The action `{action:?}` on resource `{ty}/{id}` is forbidden",
+    param: [action: &crate::operation::Action, ty: &str, id: &str,];
+
     ty: ParentResourceNotExist,
     status: http::StatusCode::NOT_FOUND,
     code: "RBH-0404",
@@ -228,6 +447,20 @@ rabbithole_errors! {
     detail: "The parent resource of the relationship `{target_relat}` does not exist",
     param: [target_relat: &str,];
 
+    ty: ResourceNotExist,
+    status: http::StatusCode::NOT_FOUND,
+    code: "RBH-0405",
+    title: "Resource Not Exist",
+    detail: "The resource `{ty}/{id}` does not exist",
+    param: [ty: &str, id: &str,];
+
+    ty: IncompleteLinkage,
+    status: http::StatusCode::INTERNAL_SERVER_ERROR,
+    code: "RBH-0406",
+    title: "Incomplete Linkage",
+    detail: "The included resource `{ty}/{id}` is not related from the primary data",
+    param: [ty: &str, id: &str,];
+
     ty: CursorPaginationNotImplemented,
     status: http::StatusCode::NOT_IMPLEMENTED,
     code: "RBH-9901",
@@ -248,4 +481,11 @@ rabbithole_errors! {
     title: "RSQL Filter on Related Field is not Implemented",
     detail: "The auto-generated RSQL Filter cannot handle related fields, please implement it manually",
     param: [];
+
+    ty: CborNotImplemented,
+    status: http::StatusCode::NOT_IMPLEMENTED,
+    code: "RBH-9904",
+    title: "CBOR Encoding is not Implemented",
+    detail: "Please use `#[feature(cbor)]` to unlock it",
+    param: [];
 }
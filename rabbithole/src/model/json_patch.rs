@@ -0,0 +1,206 @@
+//! The JSON Patch (RFC 6902) bodies of `application/json-patch+json`, updating the attributes and
+//! the relationships of a resource with the operations on `/data/attributes/...` and
+//! `/data/relationships/...`, including the edits of the array elements. They are translated into
+//! the `Patch`es of the changed attributes and relationships:
+//! ```ignore
+//! let json_patch: JsonPatch = serde_json::from_slice(&body)?;
+//! let patches = json_patch.to_patches(&current)?;
+//! ```
+//! NOTICE:
+//!   - The operations are applied all or none, so a failed `test` changes nothing
+
+use crate::model::error;
+use crate::model::patch::{Patch, PatchData};
+use crate::model::relationship::Relationships;
+use crate::model::resource::{AttributeField, Attributes, Resource};
+use crate::RbhResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+pub const JSON_PATCH_HEADER: &str = "application/json-patch+json";
+
+/// The paths allowed in the operations
+const ALLOWED_PATHS: [&str; 2] = ["/data/attributes/", "/data/relationships/"];
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "lowercase")]
+pub enum Operation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct JsonPatch(pub Vec<Operation>);
+
+impl JsonPatch {
+    /// The resource with the operations applied, which fails if any of them fails
+    pub fn apply(&self, resource: &Resource) -> RbhResult<Resource> {
+        let mut doc = json!({
+            "data": {
+                "attributes": to_value(&resource.attributes)?,
+                "relationships": to_value(&resource.relationships)?,
+            }
+        });
+        for operation in &self.0 {
+            operation.apply(&mut doc)?;
+        }
+
+        let mut updated = resource.clone();
+        updated.attributes = from_value(doc["data"]["attributes"].take())?;
+        updated.relationships = from_value(doc["data"]["relationships"].take())?;
+        if let Some(name) =
+            resource.relationships.keys().find(|name| !updated.relationships.contains_key(*name))
+        {
+            return Err(error::Error::InvalidJsonPatch(
+                &format!("the relationship `{}` cannot be removed", name),
+                None,
+            ));
+        }
+        Ok(updated)
+    }
+
+    /// The `Patch`es of the attributes and the relationships changed by the operations, where the
+    /// removed attributes are `null`
+    pub fn to_patches(&self, resource: &Resource) -> RbhResult<Vec<Patch>> {
+        let updated = self.apply(resource)?;
+        let mut attributes: HashMap<String, AttributeField> = HashMap::new();
+        for (name, value) in updated.attributes.iter() {
+            if resource.attributes.get_field(name).ok() != Some(value) {
+                attributes.insert(name.to_string(), value.clone());
+            }
+        }
+        for (name, _) in resource.attributes.iter() {
+            if updated.attributes.get_field(name).is_err() {
+                attributes.insert(name.to_string(), Value::Null.into());
+            }
+        }
+        let relationships: Relationships = updated
+            .relationships
+            .into_iter()
+            .filter(|(name, relationship)| resource.relationships.get(name) != Some(relationship))
+            .collect();
+
+        let mut patches = Vec::new();
+        if !attributes.is_empty() {
+            patches.push(Patch {
+                resource_type: resource.id.ty.to_string(),
+                resource_id: resource.id.id.clone(),
+                item: PatchData::Attributes(Attributes::from(attributes)),
+            });
+        }
+        if !relationships.is_empty() {
+            patches.push(Patch {
+                resource_type: resource.id.ty.to_string(),
+                resource_id: resource.id.id.clone(),
+                item: PatchData::Relationships(relationships),
+            });
+        }
+        Ok(patches)
+    }
+}
+
+impl Operation {
+    fn apply(&self, doc: &mut Value) -> RbhResult<()> {
+        match self {
+            Operation::Add { path, value } => add(doc, allowed(path)?, value.clone()),
+            Operation::Remove { path } => remove(doc, allowed(path)?).map(|_| ()),
+            Operation::Replace { path, value } => {
+                let target = doc.pointer_mut(allowed(path)?).ok_or_else(|| not_found(path))?;
+                *target = value.clone();
+                Ok(())
+            },
+            Operation::Move { from, path } => {
+                if path.starts_with(&format!("{}/", from)) {
+                    return Err(error::Error::InvalidJsonPatch(
+                        &format!("`{}` cannot be moved into its child `{}`", from, path),
+                        None,
+                    ));
+                }
+                let value = remove(doc, allowed(from)?)?;
+                add(doc, allowed(path)?, value)
+            },
+            Operation::Copy { from, path } => {
+                let value = doc.pointer(allowed(from)?).cloned().ok_or_else(|| not_found(from))?;
+                add(doc, allowed(path)?, value)
+            },
+            Operation::Test { path, value } => {
+                if doc.pointer(allowed(path)?) == Some(value) {
+                    Ok(())
+                } else {
+                    Err(error::Error::JsonPatchTestFailed(path, None))
+                }
+            },
+        }
+    }
+}
+
+fn allowed(path: &str) -> RbhResult<&str> {
+    if ALLOWED_PATHS.iter().any(|allowed| path.starts_with(allowed)) {
+        Ok(path)
+    } else {
+        Err(error::Error::InvalidJsonPatch(
+            &format!("`{}` is not under `/data/attributes` or `/data/relationships`", path),
+            None,
+        ))
+    }
+}
+
+/// The pointer of the parent and the unescaped last token of `path`
+fn split_last(path: &str) -> (&str, String) {
+    let (parent, last) = path.rsplit_once('/').unwrap_or(("", path));
+    (parent, last.replace("~1", "/").replace("~0", "~"))
+}
+
+fn add(doc: &mut Value, path: &str, value: Value) -> RbhResult<()> {
+    let (parent, last) = split_last(path);
+    match doc.pointer_mut(parent) {
+        Some(Value::Object(object)) => {
+            object.insert(last, value);
+            Ok(())
+        },
+        Some(Value::Array(array)) if last == "-" => {
+            array.push(value);
+            Ok(())
+        },
+        Some(Value::Array(array)) => match last.parse::<usize>() {
+            Ok(index) if index <= array.len() => {
+                array.insert(index, value);
+                Ok(())
+            },
+            _ => Err(not_found(path)),
+        },
+        _ => Err(not_found(path)),
+    }
+}
+
+fn remove(doc: &mut Value, path: &str) -> RbhResult<Value> {
+    let (parent, last) = split_last(path);
+    let removed = match doc.pointer_mut(parent) {
+        Some(Value::Object(object)) => object.remove(&last),
+        Some(Value::Array(array)) => match last.parse::<usize>() {
+            Ok(index) if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| not_found(path))
+}
+
+fn not_found(path: &str) -> error::Error {
+    error::Error::InvalidJsonPatch(&format!("`{}` does not exist", path), None)
+}
+
+fn to_value<T: Serialize>(value: &T) -> RbhResult<Value> {
+    serde_json::to_value(value).map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+fn from_value<T: serde::de::DeserializeOwned>(value: Value) -> RbhResult<T> {
+    serde_json::from_value(value)
+        .map_err(|err| error::Error::InvalidJsonPatch(&err.to_string(), None))
+}
@@ -0,0 +1,218 @@
+//! The lenient parsing of the incoming documents, which checks all of the members of a document
+//! instead of failing at the first invalid one as serde does, and reports each problem as an
+//! `InvalidDocumentMember` error with the JSON pointer of the member, like:
+//! ```json
+//! {
+//!   "errors": [
+//!     { "code": "RBH-0204", "source": { "pointer": "/data/0/id" }, "detail": "..." },
+//!     { "code": "RBH-0204", "source": { "pointer": "/data/1/relationships/dogs/data" }, "detail": "..." }
+//!   ]
+//! }
+//! ```
+//! ```ignore
+//! let doc: Document = lenient::from_slice(&body)?;
+//! ```
+//! NOTICE:
+//!   - The checks cover the structure the `Document` model needs, like the `type` and the `id` of
+//!     the resources and the linkages of the relationships, but not the names of the members
+
+use crate::model::document::Document;
+use crate::model::error::{self, ErrorSource, Errors};
+use crate::model::link::Link;
+use crate::model::JsonApiInfo;
+use crate::RbhResult;
+use serde_json::{Map, Value};
+
+/// Parses the document of `content`, with all of the problems of its members if it's invalid
+pub fn from_slice(content: &[u8]) -> RbhResult<Document> {
+    let value: Value =
+        serde_json::from_slice(content).map_err(|err| error::Error::InvalidJson(&err, None))?;
+    from_value(value)
+}
+
+/// Parses the document of `value`, with all of the problems of its members if it's invalid
+pub fn from_value(value: Value) -> RbhResult<Document> {
+    let mut problems = Problems::default();
+    if let Value::Object(doc) = &value {
+        problems.check_document(doc);
+    }
+    if let Some(err) = error::Error::from_errors(problems.0) {
+        return Err(err);
+    }
+    serde_json::from_value(value).map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+/// The JSON pointer of `member` in the object of `pointer`
+fn member(pointer: &str, member: &str) -> String {
+    format!("{}/{}", pointer, member.replace('~', "~0").replace('/', "~1"))
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "an array",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[derive(Default)]
+struct Problems(Vec<error::Error>);
+
+impl Problems {
+    fn push(&mut self, pointer: &str, reason: &str) {
+        let source = ErrorSource { pointer: pointer.parse().ok(), parameter: None };
+        self.0.push(error::Error::InvalidDocumentMember(pointer, reason, Some(source)));
+    }
+
+    fn expect_object<'a>(
+        &mut self, pointer: &str, value: &'a Value,
+    ) -> Option<&'a Map<String, Value>> {
+        match value {
+            Value::Object(object) => Some(object),
+            _ => {
+                self.push(pointer, &format!("expected an object, but {} found", type_name(value)));
+                None
+            },
+        }
+    }
+
+    fn expect_string(&mut self, object: &Map<String, Value>, pointer: &str, name: &str) {
+        match object.get(name) {
+            Some(Value::String(_)) => {},
+            Some(value) => self.push(
+                &member(pointer, name),
+                &format!("expected a string, but {} found", type_name(value)),
+            ),
+            None => self.push(pointer, &format!("the member `{}` is missing", name)),
+        }
+    }
+
+    fn check_document(&mut self, doc: &Map<String, Value>) {
+        match doc.get("data") {
+            Some(Value::Null) | None => {},
+            Some(Value::Array(resources)) => {
+                for (i, resource) in resources.iter().enumerate() {
+                    self.check_resource(&format!("/data/{}", i), resource);
+                }
+            },
+            Some(resource) => self.check_resource("/data", resource),
+        }
+        match doc.get("included") {
+            Some(Value::Array(resources)) => {
+                for (i, resource) in resources.iter().enumerate() {
+                    self.check_resource(&format!("/included/{}", i), resource);
+                }
+            },
+            Some(value) => self
+                .push("/included", &format!("expected an array, but {} found", type_name(value))),
+            None => {},
+        }
+        if doc.contains_key("included") && !doc.contains_key("data") {
+            self.push("/included", "the member `included` cannot exist without `data`");
+        }
+        if let Some(errors) = doc.get("errors") {
+            if doc.contains_key("data") {
+                self.push("/errors", "the members `data` and `errors` cannot exist together");
+            }
+            if let Err(err) = serde_json::from_value::<Errors>(errors.clone()) {
+                self.push("/errors", &err.to_string());
+            }
+        }
+        if let Some(jsonapi) = doc.get("jsonapi") {
+            if let Err(err) = serde_json::from_value::<JsonApiInfo>(jsonapi.clone()) {
+                self.push("/jsonapi", &err.to_string());
+            }
+        }
+        self.check_links_and_meta("", doc);
+    }
+
+    fn check_resource(&mut self, pointer: &str, value: &Value) {
+        let resource = match self.expect_object(pointer, value) {
+            Some(resource) => resource,
+            None => return,
+        };
+        self.expect_string(resource, pointer, "type");
+        self.expect_string(resource, pointer, "id");
+        if let Some(attributes) = resource.get("attributes") {
+            self.expect_object(&member(pointer, "attributes"), attributes);
+        }
+        if let Some(relationships) = resource.get("relationships") {
+            let pointer = member(pointer, "relationships");
+            if let Some(relationships) = self.expect_object(&pointer, relationships) {
+                for (name, relationship) in relationships {
+                    self.check_relationship(&member(&pointer, name), relationship);
+                }
+            }
+        }
+        self.check_links_and_meta(pointer, resource);
+    }
+
+    fn check_relationship(&mut self, pointer: &str, value: &Value) {
+        let relationship = match self.expect_object(pointer, value) {
+            Some(relationship) => relationship,
+            None => return,
+        };
+        let data_pointer = member(pointer, "data");
+        match relationship.get("data") {
+            Some(Value::Null) => {},
+            Some(Value::Array(identifiers)) => {
+                for (i, identifier) in identifiers.iter().enumerate() {
+                    self.check_identifier(&format!("{}/{}", data_pointer, i), identifier);
+                }
+            },
+            Some(identifier @ Value::Object(_)) => self.check_identifier(&data_pointer, identifier),
+            Some(value) => self.push(
+                &data_pointer,
+                &format!("expected null, an object or an array, but {} found", type_name(value)),
+            ),
+            None => self.push(pointer, "the member `data` is missing"),
+        }
+        self.check_links_and_meta(pointer, relationship);
+    }
+
+    fn check_identifier(&mut self, pointer: &str, value: &Value) {
+        if let Some(identifier) = self.expect_object(pointer, value) {
+            self.expect_string(identifier, pointer, "type");
+            self.expect_string(identifier, pointer, "id");
+        }
+    }
+
+    fn check_links_and_meta(&mut self, pointer: &str, object: &Map<String, Value>) {
+        if let Some(links) = object.get("links") {
+            let pointer = member(pointer, "links");
+            if let Some(links) = self.expect_object(&pointer, links) {
+                for (name, link) in links {
+                    self.check_link(&member(&pointer, name), link);
+                }
+            }
+        }
+        if let Some(meta) = object.get("meta") {
+            self.expect_object(&member(pointer, "meta"), meta);
+        }
+    }
+
+    fn check_link(&mut self, pointer: &str, value: &Value) {
+        match value {
+            Value::String(_) => {},
+            Value::Object(link) if !link.contains_key("href") => {
+                return self.push(pointer, "the member `href` is missing");
+            },
+            Value::Object(_) => {},
+            _ => {
+                return self.push(
+                    pointer,
+                    &format!("expected a string or an object, but {} found", type_name(value)),
+                );
+            },
+        }
+        if serde_json::from_value::<Link>(value.clone()).is_err() {
+            self.push(
+                pointer,
+                "expected a URI, or a link object with the URI of `href` and `meta`",
+            );
+        }
+    }
+}
@@ -3,7 +3,6 @@ use core::fmt;
 use serde::de::Visitor;
 
 use http::Uri;
-use serde::export::Formatter;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::collections::HashMap;
 
@@ -21,6 +20,10 @@ impl RawUri {
     }
 }
 
+impl fmt::Display for RawUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+}
+
 impl FromStr for RawUri {
     type Err = http::uri::InvalidUri;
 
@@ -49,13 +52,235 @@ impl From<&http::Uri> for RawUri {
 #[serde(untagged)]
 pub enum Link {
     Raw(RawUri),
-    Object { href: RawUri, meta: Meta },
+    /// A link object of a URI template, whose `meta` notes it's `templated` with its `variables`
+    Template {
+        href: UriTemplate,
+        #[serde(deserialize_with = "templated")]
+        meta: Meta,
+    },
+    Object {
+        href: RawUri,
+        meta: Meta,
+    },
+}
+
+/// The `meta` of the template links, which are told from the link objects by `templated`, as
+/// most of the templates are valid URIs as well
+fn templated<'de, D>(deserializer: D) -> Result<Meta, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let meta = Meta::deserialize(deserializer)?;
+    match meta.get("templated") {
+        Some(serde_json::Value::Bool(true)) => Ok(meta),
+        _ => Err(serde::de::Error::custom("The link is not templated")),
+    }
 }
 
 impl Link {
     pub fn slf(url: &str, link: RawUri) -> (String, Link) {
         ("self".into(), link.append_to(url).into())
     }
+
+    /// The link object of `template`, like:
+    /// ```json
+    /// {
+    ///   "href": "http://localhost/people/1/dogs{?page[limit],page[offset]}",
+    ///   "meta": { "templated": true, "variables": ["page[limit]", "page[offset]"] }
+    /// }
+    /// ```
+    pub fn template(template: UriTemplate) -> Link {
+        let mut meta = Meta::new();
+        meta.insert("templated".into(), true.into());
+        meta.insert("variables".into(), template.variables().into());
+        Link::Template { href: template, meta }
+    }
+
+    /// The URI of the link, where the templates are expanded without any variables
+    pub fn href(&self) -> String {
+        match self {
+            Link::Raw(href) | Link::Object { href, .. } => href.to_string(),
+            Link::Template { href, .. } => href.expand(&HashMap::new()),
+        }
+    }
+}
+
+/// The characters allowed in the reserved expansions, `{+var}` and `{#var}`, besides the
+/// unreserved ones
+const RESERVED: &str = ":/?#[]@!$&'()*+,;=";
+
+/// A URI template of RFC 6570 with the expressions up to the level 3, like
+/// `/people/1/dogs{?page[limit],page[offset]}`, where the variable names may have `[` and `]` for
+/// the families of the query parameters
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct UriTemplate(String);
+
+/// The error of an invalid URI template, with the reason
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct InvalidUriTemplate(pub String);
+
+impl fmt::Display for InvalidUriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Invalid URI template: {}", self.0)
+    }
+}
+
+impl std::error::Error for InvalidUriTemplate {}
+
+/// A part of a URI template, which is a literal or an expression with its operator and variables
+enum Part<'a> {
+    Literal(&'a str),
+    Expression(Option<char>, Vec<&'a str>),
+}
+
+impl UriTemplate {
+    fn parts(&self) -> Vec<Part<'_>> {
+        let mut parts = vec![];
+        let mut rest = self.0.as_str();
+        while let Some(start) = rest.find('{') {
+            let end = start + rest[start ..].find('}').unwrap_or(rest.len() - start);
+            if start > 0 {
+                parts.push(Part::Literal(&rest[.. start]));
+            }
+            let expression = &rest[start + 1 .. end.min(rest.len())];
+            let op = expression.chars().next().filter(|c| "+#./;?&".contains(*c));
+            let varlist = &expression[op.map_or(0, char::len_utf8) ..];
+            parts.push(Part::Expression(op, varlist.split(',').collect()));
+            rest = &rest[(end + 1).min(rest.len()) ..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest));
+        }
+        parts
+    }
+
+    /// The names of the variables, in the order they appear
+    pub fn variables(&self) -> Vec<String> {
+        let mut variables: Vec<String> = vec![];
+        for part in self.parts() {
+            if let Part::Expression(_, names) = part {
+                for name in names {
+                    if !variables.iter().any(|v| v == name) {
+                        variables.push(name.to_string());
+                    }
+                }
+            }
+        }
+        variables
+    }
+
+    /// The URI of the template with the values of `variables`, where the undefined ones are left
+    /// out of the expansion, like `/people/1/dogs?page[limit]=10` of
+    /// `/people/1/dogs{?page[limit],page[offset]}` with `page[limit]` only
+    pub fn expand(&self, variables: &HashMap<&str, String>) -> String {
+        let mut uri = String::with_capacity(self.0.len());
+        for part in self.parts() {
+            let (op, names) = match part {
+                Part::Literal(literal) => {
+                    uri.push_str(literal);
+                    continue;
+                },
+                Part::Expression(op, names) => (op, names),
+            };
+            let (first, sep, named, ifemp, reserved) = match op {
+                None => ("", ",", false, "", false),
+                Some('+') => ("", ",", false, "", true),
+                Some('#') => ("#", ",", false, "", true),
+                Some('.') => (".", ".", false, "", false),
+                Some('/') => ("/", "/", false, "", false),
+                Some(';') => (";", ";", true, "", false),
+                Some('?') => ("?", "&", true, "=", false),
+                _ => ("&", "&", true, "=", false),
+            };
+            let values: Vec<String> = names
+                .into_iter()
+                .filter_map(|name| {
+                    let value = encode(variables.get(name)?, reserved);
+                    Some(match (named, value.is_empty()) {
+                        (false, _) => value,
+                        (true, true) => format!("{}{}", name, ifemp),
+                        (true, false) => format!("{}={}", name, value),
+                    })
+                })
+                .collect();
+            if !values.is_empty() {
+                uri.push_str(first);
+                uri.push_str(&values.join(sep));
+            }
+        }
+        uri
+    }
+}
+
+/// Percent-encodes `value` except the unreserved characters, and the reserved ones if `reserved`
+fn encode(value: &str, reserved: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c.is_ascii_alphanumeric() || "-._~".contains(c) || (reserved && RESERVED.contains(c)) {
+            encoded.push(c);
+        } else {
+            let mut buf = [0; 4];
+            for byte in c.encode_utf8(&mut buf).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        }
+    }
+    encoded
+}
+
+impl FromStr for UriTemplate {
+    type Err = InvalidUriTemplate;
+
+    /// Checks the expressions of `s`, which are closed, not nested, and have valid variable names
+    /// without the modifiers of the level 4
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut rest = s;
+        while let Some(start) = rest.find(['{', '}']) {
+            if rest[start ..].starts_with('}') {
+                return Err(InvalidUriTemplate(format!("unopened `}}` in `{}`", s)));
+            }
+            let end = match rest[start + 1 ..].find(['{', '}']) {
+                Some(end) if rest[start + 1 + end ..].starts_with('}') => start + 1 + end,
+                _ => return Err(InvalidUriTemplate(format!("unclosed `{{` in `{}`", s))),
+            };
+            let expression = &rest[start + 1 .. end];
+            let varlist = expression.trim_start_matches(|c: char| "+#./;?&".contains(c));
+            if expression.len() - varlist.len() > 1 {
+                return Err(InvalidUriTemplate(format!("multiple operators in `{}`", expression)));
+            }
+            let is_varchar = |c: char| c.is_ascii_alphanumeric() || "_.%[]".contains(c);
+            if let Some(name) =
+                varlist.split(',').find(|name| name.is_empty() || !name.chars().all(is_varchar))
+            {
+                return Err(InvalidUriTemplate(format!("invalid variable `{}`", name)));
+            }
+            rest = &rest[end + 1 ..];
+        }
+        Ok(UriTemplate(s.to_string()))
+    }
+}
+
+impl fmt::Display for UriTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+impl Serialize for UriTemplate {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for UriTemplate {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let template = String::deserialize(deserializer)?;
+        template.parse().map_err(serde::de::Error::custom)
+    }
 }
 
 impl Serialize for RawUri {
@@ -71,7 +296,7 @@ struct RawUriVisitor;
 impl<'de> Visitor<'de> for RawUriVisitor {
     type Value = RawUri;
 
-    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
         formatter.write_str("URI valid String")
     }
 
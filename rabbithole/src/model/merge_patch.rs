@@ -0,0 +1,100 @@
+//! The JSON Merge Patch (RFC 7396) bodies of `application/merge-patch+json`, which are merged into
+//! the attributes of a resource directly, as a lighter alternative to the resource documents for
+//! the simple updates:
+//!   - the `null` members remove the attributes
+//!   - the objects are merged recursively, with the same `null` removal in them
+//!   - the other values, including the arrays, replace the attributes entirely
+//!
+//! ```ignore
+//! let merge_patch: MergePatch = serde_json::from_slice(&body)?;
+//! let patch = merge_patch.to_patch(&current)?;
+//! ```
+
+use crate::model::error;
+use crate::model::patch::{Patch, PatchData};
+use crate::model::resource::{AttributeField, Attributes, Resource};
+use crate::RbhResult;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub const MERGE_PATCH_HEADER: &str = "application/merge-patch+json";
+
+/// The members of the attributes to merge, where a body other than an object is rejected
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct MergePatch(pub Map<String, Value>);
+
+impl MergePatch {
+    /// The attributes merged with the patch
+    pub fn apply(&self, attributes: &Attributes) -> RbhResult<Attributes> {
+        let mut attributes = attributes.get_json_value_map()?;
+        for (name, patch) in &self.0 {
+            if patch.is_null() {
+                attributes.remove(name);
+            } else {
+                merge(attributes.entry(name.clone()).or_insert(Value::Null), patch);
+            }
+        }
+        Ok(attributes.into())
+    }
+
+    /// The `Patch` of the attributes changed by the merge, where the removed attributes are `null`,
+    /// or `None` if nothing is changed
+    pub fn to_patch(&self, resource: &Resource) -> RbhResult<Option<Patch>> {
+        let current = resource.attributes.get_json_value_map()?;
+        let mut changed: HashMap<String, AttributeField> = HashMap::new();
+        for (name, patch) in &self.0 {
+            let old = current.get(name);
+            if patch.is_null() {
+                if old.is_some() {
+                    changed.insert(name.clone(), Value::Null.into());
+                }
+                continue;
+            }
+            let mut new = old.cloned().unwrap_or(Value::Null);
+            merge(&mut new, patch);
+            if old != Some(&new) {
+                changed.insert(name.clone(), new.into());
+            }
+        }
+
+        if changed.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Patch {
+            resource_type: resource.id.ty.to_string(),
+            resource_id: resource.id.id.clone(),
+            item: PatchData::Attributes(Attributes::from(changed)),
+        }))
+    }
+}
+
+impl std::str::FromStr for MergePatch {
+    type Err = error::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s).map_err(|err| error::Error::InvalidJson(&err, None))
+    }
+}
+
+/// Merges `patch` into `target` as RFC 7396
+fn merge(target: &mut Value, patch: &Value) {
+    match patch {
+        Value::Object(patch) => {
+            if !target.is_object() {
+                *target = Value::Object(Map::new());
+            }
+            if let Value::Object(target) = target {
+                for (name, patch) in patch {
+                    if patch.is_null() {
+                        target.remove(name);
+                    } else {
+                        merge(target.entry(name.clone()).or_insert(Value::Null), patch);
+                    }
+                }
+            }
+        },
+        _ => *target = patch.clone(),
+    }
+}
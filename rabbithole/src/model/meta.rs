@@ -0,0 +1,91 @@
+//! The namespace of the implementation-specific members of `meta`, set by `meta_namespace` in
+//! `JsonApiSettings`, which nests the members added by the crate and the ones of `members` under
+//! the namespace, like `{ "meta": { "rabbithole": { "lastModified": "..." } } }`, so they don't
+//! collide with the members of the applications.
+//! NOTICE:
+//!   - Only the `meta` of the top level, of the resources in `data` and `included`, and of the
+//!     errors are namespaced, and the other members are left as they are
+
+use crate::handler::CORRELATION_ID;
+use crate::model::error;
+use crate::model::Meta;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// The members of `meta` added by the crate
+pub const MEMBERS: [&str; 5] =
+    ["lastModified", "deprecated", "aliases", CORRELATION_ID, "rateLimits"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct MetaNamespace {
+    /// The member the others are nested under, like `rabbithole`
+    pub name: String,
+    /// The members added by the application, nested along with the ones in `MEMBERS`
+    #[serde(default)]
+    pub members: Vec<String>,
+}
+
+impl MetaNamespace {
+    pub fn new(name: &str) -> Self { Self { name: name.to_string(), members: vec![] } }
+
+    pub fn with_member(mut self, member: &str) -> Self {
+        self.members.push(member.to_string());
+        self
+    }
+
+    fn is_namespaced(&self, member: &str) -> bool {
+        MEMBERS.contains(&member) || self.members.iter().any(|m| m == member)
+    }
+
+    /// Nests the namespaced members of the `meta` objects in the JSON of a document
+    pub fn apply(&self, doc: &mut Value) {
+        self.nest_value(doc);
+        for member in &["data", "included"] {
+            match doc.get_mut(member) {
+                Some(Value::Array(resources)) => {
+                    resources.iter_mut().for_each(|r| self.nest_value(r))
+                },
+                Some(resource @ Value::Object(_)) => self.nest_value(resource),
+                _ => {},
+            }
+        }
+        if let Some(Value::Array(errors)) = doc.get_mut("errors") {
+            errors.iter_mut().for_each(|err| self.nest_value(err));
+        }
+    }
+
+    /// Nests the namespaced members of the `meta` of `err` and its `others`
+    pub fn apply_error(&self, err: &mut error::Error) {
+        err.for_each_mut(|err| {
+            if let Some(meta) = &mut err.meta {
+                self.nest(meta);
+            }
+        });
+    }
+
+    /// Nests the namespaced members of `meta`, merging them into the namespace if it's there
+    pub fn nest(&self, meta: &mut Meta) {
+        let members: Vec<String> = meta.keys().filter(|k| self.is_namespaced(k)).cloned().collect();
+        if members.is_empty() {
+            return;
+        }
+        let mut nested = match meta.remove(&self.name) {
+            Some(Value::Object(nested)) => nested,
+            _ => Map::new(),
+        };
+        for member in members {
+            if let Some(value) = meta.remove(&member) {
+                nested.insert(member, value);
+            }
+        }
+        meta.insert(self.name.clone(), Value::Object(nested));
+    }
+
+    fn nest_value(&self, value: &mut Value) {
+        if let Some(Value::Object(meta)) = value.get_mut("meta") {
+            let mut nested: Meta = std::mem::take(meta).into_iter().collect();
+            self.nest(&mut nested);
+            *meta = nested.into_iter().collect();
+        }
+    }
+}
@@ -1,6 +1,11 @@
 pub mod document;
 pub mod error;
+pub mod json_patch;
+pub mod lenient;
 pub mod link;
+pub mod merge_patch;
+pub mod meta;
+pub mod name;
 pub mod pagination;
 pub mod patch;
 pub mod relationship;
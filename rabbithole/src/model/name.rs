@@ -0,0 +1,122 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::borrow::Borrow;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::{Arc, Mutex};
+
+lazy_static! {
+    static ref INTERNED_NAMES: Mutex<HashSet<Arc<str>>> = Default::default();
+}
+
+/// The interned names of the resource types and of the attribute and relationship fields, where
+/// the names with the same content share the same `Arc<str>`, so the cloning of them in the
+/// identifiers and relationships of the large documents is only a reference count
+#[derive(Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct Name(Arc<str>);
+
+impl Name {
+    pub fn new(name: &str) -> Self {
+        let mut names = INTERNED_NAMES.lock().unwrap();
+        if let Some(interned) = names.get(name) {
+            return Self(interned.clone());
+        }
+        let interned: Arc<str> = Arc::from(name);
+        names.insert(interned.clone());
+        Self(interned)
+    }
+
+    pub fn as_str(&self) -> &str { &self.0 }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str { &self.0 }
+}
+
+impl AsRef<str> for Name {
+    fn as_ref(&self) -> &str { &self.0 }
+}
+
+impl Borrow<str> for Name {
+    fn borrow(&self) -> &str { &self.0 }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+}
+
+impl fmt::Debug for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { self.0.fmt(f) }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self { Self::new(name) }
+}
+
+impl From<&String> for Name {
+    fn from(name: &String) -> Self { Self::new(name) }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self { Self::new(&name) }
+}
+
+impl From<Name> for String {
+    fn from(name: Name) -> Self { name.0.to_string() }
+}
+
+impl PartialEq<str> for Name {
+    fn eq(&self, other: &str) -> bool { &*self.0 == other }
+}
+
+impl PartialEq<&str> for Name {
+    fn eq(&self, other: &&str) -> bool { &*self.0 == *other }
+}
+
+impl PartialEq<String> for Name {
+    fn eq(&self, other: &String) -> bool { &*self.0 == other.as_str() }
+}
+
+impl PartialEq<Name> for str {
+    fn eq(&self, other: &Name) -> bool { self == &*other.0 }
+}
+
+impl PartialEq<Name> for &str {
+    fn eq(&self, other: &Name) -> bool { *self == &*other.0 }
+}
+
+impl PartialEq<Name> for String {
+    fn eq(&self, other: &Name) -> bool { self.as_str() == &*other.0 }
+}
+
+impl Serialize for Name {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for Name {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name: std::borrow::Cow<'de, str> = Deserialize::deserialize(deserializer)?;
+        Ok(Self::new(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::name::Name;
+    use std::sync::Arc;
+
+    #[test]
+    fn interned_test() {
+        let a = Name::from("people");
+        let b = Name::from("people".to_string());
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, "people");
+        assert_eq!(serde_json::to_string(&a).unwrap(), r#""people""#);
+        let c: Name = serde_json::from_str(r#""people""#).unwrap();
+        assert!(Arc::ptr_eq(&a.0, &c.0));
+    }
+}
@@ -1,11 +1,12 @@
 use crate::model::link::{Link, Links};
+use crate::model::name::Name;
 use crate::model::resource::IdentifierData;
 use crate::model::Meta;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::iter::FromIterator;
 
-pub type Relationships = HashMap<String, Relationship>;
+pub type Relationships = HashMap<Name, Relationship>;
 lazy_static! {
     static ref INVALID_RELAT_FIELDS: HashSet<&'static str> = HashSet::from_iter(vec!["type", "id"]);
 }
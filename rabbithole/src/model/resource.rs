@@ -1,8 +1,11 @@
 use crate::model::link::Links;
+use crate::model::name::Name;
 use crate::model::relationship::Relationships;
 use crate::model::{error, Meta};
 
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::collections::HashSet;
@@ -13,7 +16,8 @@ use std::hash::Hash;
 use std::iter::FromIterator;
 use std::str::FromStr;
 
-pub type ResourceIdentifiers = Vec<ResourceIdentifier>;
+/// The identifiers of the to-many relationships, which are mostly only a few, kept inline
+pub type ResourceIdentifiers = SmallVec<[ResourceIdentifier; 2]>;
 pub type Resources = Vec<Resource>;
 
 lazy_static! {
@@ -32,6 +36,34 @@ impl AttributeField {
         })
     }
 
+    /// Compares with the value of `field` in another resource
+    pub fn cmp_with(&self, other: &Self, field: &str) -> RbhResult<Ordering> {
+        self.partial_cmp(other).ok_or_else(|| {
+            error::Error::FieldNotMatch(field, &self.to_string(), &other.to_string(), None)
+        })
+    }
+
+    /// The value if it's a string
+    pub fn as_str(&self) -> Option<&str> { self.0.as_str() }
+
+    /// The member `key` of an object value, the element at the index `key` of an array value, or
+    /// the length of an array value if `key` is `size`
+    pub fn member(&self, key: &str) -> Option<AttributeField> {
+        match &self.0 {
+            serde_json::Value::Object(object) => object.get(key).cloned().map(Self),
+            serde_json::Value::Array(array) if key == "size" => Some(Self(array.len().into())),
+            serde_json::Value::Array(array) => {
+                array.get(key.parse::<usize>().ok()?).cloned().map(Self)
+            },
+            _ => None,
+        }
+    }
+
+    /// The elements of an array value
+    pub fn elements(&self) -> Option<Vec<AttributeField>> {
+        self.0.as_array().map(|array| array.iter().cloned().map(Self).collect())
+    }
+
     pub fn eq_with_str(&self, value: &str, field: &str) -> RbhResult<bool> {
         if value.contains('*') && self.0.is_string() {
             let value = value.replace('*', "\\w*");
@@ -84,18 +116,21 @@ impl PartialOrd for AttributeField {
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Attributes(HashMap<String, AttributeField>);
+pub struct Attributes(HashMap<Name, AttributeField>);
+
+impl From<HashMap<Name, AttributeField>> for Attributes {
+    fn from(map: HashMap<Name, AttributeField>) -> Self { Self(map) }
+}
 
 impl From<HashMap<String, AttributeField>> for Attributes {
-    fn from(map: HashMap<String, AttributeField>) -> Self { Self(map) }
+    fn from(map: HashMap<String, AttributeField>) -> Self {
+        Self(map.into_iter().map(|(k, v)| (k.into(), v)).collect())
+    }
 }
 
 impl<K: ToString> From<HashMap<K, serde_json::Value>> for Attributes {
     fn from(map: HashMap<K, serde_json::Value>) -> Self {
-        map.into_iter()
-            .map(|(k, v)| (k.to_string(), v.into()))
-            .collect::<HashMap<String, AttributeField>>()
-            .into()
+        Self(map.into_iter().map(|(k, v)| (k.to_string().into(), v.into())).collect())
     }
 }
 
@@ -104,26 +139,36 @@ impl Attributes {
         self.0.get(field_name).ok_or_else(|| error::Error::FieldNotExist(field_name, None))
     }
 
-    pub fn cmp(&self, field: &str, other: &Self) -> Result<Ordering, error::Error> {
-        let self_field = self.get_field(field)?;
-        let other_field = other.get_field(field)?;
-        if let Some(ord) = self_field.partial_cmp(&other_field) {
-            Ok(ord)
-        } else {
-            Err(error::Error::FieldNotMatch(
-                field,
-                &self_field.to_string(),
-                &other_field.to_string(),
-                None,
-            ))
+    /// The attribute `name` as `T`, failing if it's missing or not a `T`
+    pub fn get_as<T: DeserializeOwned>(&self, name: &str) -> RbhResult<T> {
+        T::deserialize(&self.get_field(name)?.0)
+            .map_err(|err| error::Error::InvalidJson(&err, None))
+    }
+
+    /// The attribute `name` as `T`, or `None` if it's missing or `null`
+    pub fn get_opt<T: DeserializeOwned>(&self, name: &str) -> RbhResult<Option<T>> {
+        match self.0.get(name) {
+            Some(field) => Option::<T>::deserialize(&field.0)
+                .map_err(|err| error::Error::InvalidJson(&err, None)),
+            None => Ok(None),
         }
     }
 
+    /// The attributes as `T`, like all of the string attributes with `iter_as::<String>`, where
+    /// the others are skipped
+    pub fn iter_as<T: DeserializeOwned>(&self) -> impl Iterator<Item = (&Name, T)> {
+        self.0.iter().filter_map(|(name, field)| Some((name, T::deserialize(&field.0).ok()?)))
+    }
+
+    pub fn cmp(&self, field: &str, other: &Self) -> Result<Ordering, error::Error> {
+        self.get_field(field)?.cmp_with(other.get_field(field)?, field)
+    }
+
     pub fn get_json_value_map(&self) -> Result<HashMap<String, serde_json::Value>, error::Error> {
         self.0
             .iter()
             .map(|(k, v)| match serde_json::to_value(v) {
-                Ok(vv) => Ok((k.clone(), vv)),
+                Ok(vv) => Ok((k.to_string(), vv)),
                 Err(err) => Err(error::Error::InvalidJson(&err, None)),
             })
             .collect()
@@ -131,8 +176,10 @@ impl Attributes {
 
     pub fn is_empty(&self) -> bool { self.0.is_empty() }
 
+    pub fn iter(&self) -> impl Iterator<Item = (&Name, &AttributeField)> { self.0.iter() }
+
     pub fn retain(mut self, keys: &HashSet<String>) -> Self {
-        self.0.retain(|k, _| keys.contains(k));
+        self.0.retain(|k, _| keys.contains(k.as_str()));
         self
     }
 }
@@ -150,7 +197,7 @@ impl IdentifierData {
         match self {
             IdentifierData::Single(Some(data)) => vec![data.clone()],
             IdentifierData::Single(None) => Default::default(),
-            IdentifierData::Multiple(data) => data.clone(),
+            IdentifierData::Multiple(data) => data.to_vec(),
         }
     }
 }
@@ -163,7 +210,7 @@ impl Default for IdentifierData {
 #[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
 pub struct ResourceIdentifier {
     #[serde(rename = "type")]
-    pub ty: String,
+    pub ty: Name,
     pub id: String,
 }
 
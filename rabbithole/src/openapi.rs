@@ -0,0 +1,340 @@
+//! The OpenAPI 3 documents of the fetching routes, describing the resources by the JSON:API
+//! document structure, where `generate` describes the attributes and the relationships of each
+//! resource type by its schema in the `Registry`
+
+use crate::handler::OPERATIONS;
+use crate::schema::{Registry, ResourceSchema};
+use crate::JSON_API_HEADER;
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+
+/// The title and the version of the API in the `info` of the OpenAPI document
+#[derive(Debug, Clone)]
+pub struct OpenApiInfo {
+    pub title: String,
+    pub version: String,
+}
+
+/// An OpenAPI 3 document
+pub type Spec = Value;
+
+/// Where and how the resource types of `generate` are served
+#[derive(Debug, Clone)]
+pub struct OpenApiSettings {
+    pub info: OpenApiInfo,
+    /// The base URI of the resources, like `http://localhost:8080/api`
+    pub uri: String,
+    /// The paths of the resource types under `uri`, which are `/<type>` if missing
+    pub paths: HashMap<String, String>,
+}
+
+impl OpenApiSettings {
+    pub fn new(info: OpenApiInfo, uri: &str) -> Self {
+        Self { info, uri: uri.to_string(), paths: Default::default() }
+    }
+
+    pub fn resource_path(&self, ty: &str) -> String {
+        self.paths.get(ty).cloned().unwrap_or_else(|| format!("/{}", ty))
+    }
+}
+
+/// The OpenAPI 3 document of all of the fetching routes of the resource types in `registry`, where
+/// the documents of the collections and the single resources refer to the typed schemas of the
+/// resources, like `people.Resource`, and the routes of the relationships list their names. The
+/// error documents are described by `Errors` for any status other than `200` and `304`
+pub fn generate(registry: &Registry, settings: &OpenApiSettings) -> Spec {
+    let mut paths = Map::new();
+    let mut typed_schemas = Map::new();
+    for resource in registry.resources() {
+        let resource_path = settings.resource_path(&resource.ty);
+        for operation in OPERATIONS.iter() {
+            paths.insert(
+                path_of(&resource_path, operation),
+                json!({ "get": resource_operation_object(resource, operation) }),
+            );
+        }
+        typed_schemas.extend(resource_schemas(resource));
+    }
+    let mut spec = document(&settings.info, &settings.uri, paths);
+    if let Value::Object(schemas) = &mut spec["components"]["schemas"] {
+        schemas.extend(typed_schemas);
+    }
+    spec
+}
+
+/// The OpenAPI 3 document of `paths`, like the ones of `path_of` and `operation_object`, served
+/// at `uri`, with the `schemas` in the components
+pub fn document(info: &OpenApiInfo, uri: &str, paths: Map<String, Value>) -> Value {
+    json!({
+        "openapi": "3.0.3",
+        "info": { "title": info.title, "version": info.version },
+        "servers": [{ "url": uri }],
+        "paths": paths,
+        "components": { "schemas": schemas() },
+    })
+}
+
+/// Merges the paths and the components of `doc` into `spec`, like the one generated for the other
+/// routes of the server, where the ones of `spec` are kept if both have them
+pub fn merge(spec: &mut Value, doc: &Value) {
+    for key in &["paths", "components"] {
+        let (target, source) = match (spec.get_mut(*key), doc.get(*key)) {
+            (Some(Value::Object(target)), Some(Value::Object(source))) => (target, source),
+            (None, Some(source)) => {
+                spec[*key] = source.clone();
+                continue;
+            },
+            _ => continue,
+        };
+        for (name, value) in source {
+            match (target.get_mut(name), value) {
+                // Like `components.schemas`, merged by the names of the schemas
+                (Some(Value::Object(target)), Value::Object(value)) if *key == "components" => {
+                    for (name, value) in value {
+                        target.entry(name.clone()).or_insert_with(|| value.clone());
+                    }
+                },
+                (Some(_), _) => {},
+                (None, _) => {
+                    target.insert(name.clone(), value.clone());
+                },
+            }
+        }
+    }
+}
+
+/// The path of `operation` under `resource_path` like `/people`
+pub fn path_of(resource_path: &str, operation: &str) -> String {
+    match operation {
+        "fetch_collection" => resource_path.to_string(),
+        "fetch_single" => format!("{}/{{id}}", resource_path),
+        "fetch_relationship" => format!("{}/{{id}}/relationships/{{related_field}}", resource_path),
+        _ => format!("{}/{{id}}/{{related_field}}", resource_path),
+    }
+}
+
+/// The operation object of `operation` of the resource type `ty`, without `security`
+pub fn operation_object(ty: &str, operation: &str) -> Value {
+    let mut parameters = Vec::new();
+    if operation != "fetch_collection" {
+        parameters.push(path_parameter("id"));
+    }
+    if operation == "fetch_relationship" || operation == "fetch_related" {
+        parameters.push(path_parameter("related_field"));
+    }
+    if operation != "fetch_relationship" {
+        parameters.push(query_parameter("include", json!({ "type": "string" })));
+        parameters.push(deep_object_parameter("fields"));
+    }
+    if operation == "fetch_collection" || operation == "fetch_related" {
+        parameters.push(query_parameter("sort", json!({ "type": "string" })));
+        parameters.push(deep_object_parameter("page"));
+        parameters.push(deep_object_parameter("filter"));
+    }
+    let document = if operation == "fetch_relationship" { "Relationship" } else { "Document" };
+    json!({
+        "operationId": format!("{}_{}", operation, ty),
+        "tags": [ty],
+        "parameters": parameters,
+        "responses": {
+            "200": content("The document", document),
+            "304": { "description": "The document is not modified since the `ETag`" },
+            "default": content("The errors", "Errors"),
+        },
+    })
+}
+
+/// The operation object of `operation` of `resource`, refining the one of `operation_object` by
+/// the attributes and the relationships of the resource type
+fn resource_operation_object(resource: &ResourceSchema, operation: &str) -> Value {
+    let ty = &resource.ty;
+    let mut op = operation_object(ty, operation);
+    let relationships: Vec<&str> = resource.relationships().collect();
+    let attributes: Vec<&str> = resource.attributes.iter().map(|(name, _)| name.as_str()).collect();
+    if let Value::Array(parameters) = &mut op["parameters"] {
+        for parameter in parameters.iter_mut() {
+            match parameter["name"].as_str() {
+                Some("related_field") if !relationships.is_empty() => {
+                    parameter["schema"]["enum"] = json!(relationships);
+                },
+                Some("include") if operation != "fetch_related" => {
+                    parameter["description"] = json!(format!(
+                        "The comma-separated relationships of `{}` to include, of: {}",
+                        ty,
+                        relationships.join(", ")
+                    ));
+                },
+                Some("sort") if operation == "fetch_collection" => {
+                    parameter["description"] = json!(format!(
+                        "The comma-separated attributes of `{}` to sort by, descending with the \
+                         `-` prefix, of: {}",
+                        ty,
+                        attributes.join(", ")
+                    ));
+                },
+                _ => {},
+            }
+        }
+    }
+    let document = match operation {
+        "fetch_collection" => Some(schema_name(ty, "Collection")),
+        "fetch_single" => Some(schema_name(ty, "Document")),
+        _ => None,
+    };
+    if let Some(document) = document {
+        op["responses"]["200"] = content("The document", &document);
+    }
+    for (status, description) in &[
+        ("400", "The query parameters are invalid"),
+        ("404", "The resource or the relationship is not found"),
+    ] {
+        op["responses"][*status] = content(description, "Errors");
+    }
+    op
+}
+
+/// The name of the typed schema `kind` of the resource type `ty`, like `people.Resource`, where
+/// the characters not allowed in the names of the components are replaced by `_`
+fn schema_name(ty: &str, kind: &str) -> String {
+    let ty = ty.replace(|c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_', "_");
+    format!("{}.{}", ty, kind)
+}
+
+fn schema_ref(name: &str) -> Value { json!({ "$ref": format!("#/components/schemas/{}", name) }) }
+
+/// The typed schemas of the resource object and the documents of `resource`, with the payloads
+/// creating and updating the resources, which are not answered by the fetching routes
+fn resource_schemas(resource: &ResourceSchema) -> Map<String, Value> {
+    let ty = &resource.ty;
+    let resource_ref = schema_ref(&schema_name(ty, "Resource"));
+    let mut schemas = Map::new();
+    schemas.insert(schema_name(ty, "Resource"), resource.resource_object());
+    schemas.insert(
+        schema_name(ty, "Document"),
+        typed_document(json!({ "allOf": [resource_ref.clone()], "nullable": true })),
+    );
+    schemas.insert(
+        schema_name(ty, "Collection"),
+        typed_document(json!({ "type": "array", "items": resource_ref })),
+    );
+    schemas.insert(schema_name(ty, "Create"), resource.create_payload());
+    schemas.insert(schema_name(ty, "Update"), resource.update_payload());
+    schemas
+}
+
+/// The document of the primary `data`, with any resources included
+fn typed_document(data: Value) -> Value {
+    json!({
+        "type": "object",
+        "properties": {
+            "data": data,
+            "included": { "type": "array", "items": schema_ref("Resource") },
+            "links": { "type": "object" },
+            "meta": { "type": "object" },
+        },
+    })
+}
+
+fn path_parameter(name: &str) -> Value {
+    json!({ "name": name, "in": "path", "required": true, "schema": { "type": "string" } })
+}
+
+fn query_parameter(name: &str, schema: Value) -> Value {
+    json!({ "name": name, "in": "query", "required": false, "schema": schema })
+}
+
+/// A parameter like `fields[people]=name` or `page[offset]=10`
+fn deep_object_parameter(name: &str) -> Value {
+    let mut parameter = query_parameter(
+        name,
+        json!({ "type": "object", "additionalProperties": { "type": "string" } }),
+    );
+    parameter["style"] = json!("deepObject");
+    parameter["explode"] = json!(true);
+    parameter
+}
+
+fn content(description: &str, schema: &str) -> Value {
+    json!({
+        "description": description,
+        "content": {
+            JSON_API_HEADER: { "schema": { "$ref": format!("#/components/schemas/{}", schema) } },
+        },
+    })
+}
+
+/// The schemas of the JSON:API documents referred by `operation_object`
+pub fn schemas() -> Value {
+    json!({
+        "ResourceIdentifier": {
+            "type": "object",
+            "required": ["type", "id"],
+            "properties": { "type": { "type": "string" }, "id": { "type": "string" } },
+        },
+        "Resource": {
+            "type": "object",
+            "required": ["type", "id"],
+            "properties": {
+                "type": { "type": "string" },
+                "id": { "type": "string" },
+                "attributes": { "type": "object" },
+                "relationships": { "type": "object" },
+                "links": { "type": "object" },
+                "meta": { "type": "object" },
+            },
+        },
+        "Document": {
+            "type": "object",
+            "properties": {
+                "data": {
+                    "oneOf": [
+                        { "$ref": "#/components/schemas/Resource" },
+                        { "type": "array", "items": { "$ref": "#/components/schemas/Resource" } },
+                    ],
+                    "nullable": true,
+                },
+                "included": { "type": "array", "items": { "$ref": "#/components/schemas/Resource" } },
+                "links": { "type": "object" },
+                "meta": { "type": "object" },
+            },
+        },
+        "Relationship": {
+            "type": "object",
+            "properties": {
+                "data": {
+                    "oneOf": [
+                        { "$ref": "#/components/schemas/ResourceIdentifier" },
+                        {
+                            "type": "array",
+                            "items": { "$ref": "#/components/schemas/ResourceIdentifier" },
+                        },
+                    ],
+                    "nullable": true,
+                },
+                "links": { "type": "object" },
+                "meta": { "type": "object" },
+            },
+        },
+        "Errors": {
+            "type": "object",
+            "required": ["errors"],
+            "properties": {
+                "errors": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": {
+                            "id": { "type": "string" },
+                            "status": { "type": "string" },
+                            "code": { "type": "string" },
+                            "title": { "type": "string" },
+                            "detail": { "type": "string" },
+                            "source": { "type": "object" },
+                            "meta": { "type": "object" },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
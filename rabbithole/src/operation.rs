@@ -1,14 +1,115 @@
 use crate::entity::{Entity, SingleEntity};
 use crate::model::document::Document;
 use crate::model::relationship::Relationship;
+use crate::model::resource::Resource;
 
 use crate::model::error;
 use crate::model::link::RawUri;
 use crate::query::Query;
+use crate::RbhResult;
 use async_trait::async_trait;
 
+/// The request-scoped information of an operation, filled by the endpoints
+#[derive(Debug, Clone, Default)]
+pub struct OperationContext {
+    /// Headers of the incoming request
+    pub headers: http::HeaderMap,
+    /// Who sent the request, if it's authenticated by the endpoint
+    pub principal: Option<Principal>,
+    /// The tenant of the request, if `tenant` of `JsonApiSettings` is set
+    pub tenant: Option<String>,
+}
+
+impl OperationContext {
+    /// If the request is authenticated with `scope`
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.principal.as_ref().is_some_and(|principal| principal.scopes.iter().any(|s| s == scope))
+    }
+}
+
+/// An authenticated client, like a user behind a bearer token or an API key
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Principal {
+    pub id: String,
+    /// What the client is allowed to do, like the scopes of an OAuth 2.0 token
+    pub scopes: Vec<String>,
+}
+
+/// What the endpoint is going to render from the fetched items, letting services load exactly
+/// the data needed, like JOINing only the included relationships
+#[derive(Debug)]
+pub struct FetchPlan<'a> {
+    pub query: &'a Query,
+    ty: String,
+    tenant: Option<&'a str>,
+}
+
+impl<'a> FetchPlan<'a> {
+    pub fn new<E: SingleEntity>(query: &'a Query) -> Self {
+        Self { query, ty: E::ty(), tenant: None }
+    }
+
+    /// The plan of the requests of `tenant`
+    pub fn with_tenant(mut self, tenant: Option<&'a str>) -> Self {
+        self.tenant = tenant;
+        self
+    }
+
+    /// The tenant of the request, so the services only load its data
+    pub fn tenant(&self) -> Option<&'a str> { self.tenant }
+
+    /// If the related resources of the relationship `field` will be put into `included`
+    pub fn includes(&self, field: &str) -> bool {
+        self.query.include.as_ref().is_none_or(|include| include.contains(field))
+    }
+
+    /// If the attribute or relationship `field` will be rendered in the primary resources
+    pub fn selects(&self, field: &str) -> bool {
+        self.query.fields.get(&self.ty).is_none_or(|fields| fields.contains(field))
+    }
+}
+
+/// The action an endpoint is going to perform on a resource
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Action {
+    /// Returning the resource as a member of `/<ty>`
+    FetchCollection,
+    /// Returning the resource from `/<ty>/<id>`
+    FetchSingle,
+    /// Returning a relationship of the resource from `/<ty>/<id>/relationships/<related_field>`
+    FetchRelationship(String),
+    /// Returning the related resources of the resource from `/<ty>/<id>/<related_field>`
+    FetchRelated(String),
+}
+
+/// The result of `Fetching::can`
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Decision {
+    Allow,
+    /// The resource is visible, but the action is not permitted, mapping to `403 Forbidden`
+    Forbid,
+    /// The resource should look like non-existing, mapping to `404 Not Found`
+    Hide,
+}
+
+impl Decision {
+    pub fn into_result<E: SingleEntity>(self, action: &Action, item: &E) -> RbhResult<()> {
+        match self {
+            Decision::Allow => Ok(()),
+            Decision::Forbid => {
+                Err(error::Error::ActionForbidden(action, &E::ty(), &item.id(), None))
+            },
+            Decision::Hide => Err(error::Error::ResourceNotExist(&E::ty(), &item.id(), None)),
+        }
+    }
+}
+
+/// A service mapping the fetching operations of a resource type
+/// NOTICE:
+///   - The endpoints share a single service between all of the requests (like in an `Arc`), so
+///     any mutable state should be guarded by the service itself
 #[async_trait]
-pub trait Fetching {
+pub trait Fetching: Send + Sync {
     type Item: SingleEntity + Send + Sync;
 
     /// User defined `vec_to_document` function
@@ -17,20 +118,126 @@ pub trait Fetching {
     ///     - put `prev`, `next`, `first` and `last` into `links`
     ///     - put `totalPages` if `@type == PageBased`
     async fn vec_to_document(
-        items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
+        &self, items: &[Self::Item], uri: &str, query: &Query, request_path: &RawUri,
     ) -> Result<Document, error::Error> {
         Ok(items.to_document_automatically(uri, query, request_path)?)
     }
     /// Mapping to `/<ty>?<query>`
-    async fn fetch_collection(query: &Query) -> Result<Vec<Self::Item>, error::Error>;
+    async fn fetch_collection(&self, plan: &FetchPlan<'_>)
+        -> Result<Vec<Self::Item>, error::Error>;
     /// Mapping to `/<ty>/<id>?<query>`
-    async fn fetch_single(id: &str, query: &Query) -> Result<Option<Self::Item>, error::Error>;
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error>;
     /// Mapping to `/<ty>/<id>/relationships/<related_field>?<query>`
     async fn fetch_relationship(
-        id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
     ) -> Result<Relationship, error::Error>;
     /// Mapping to `/<ty>/<id>/<related_field>?<query>`
     async fn fetch_related(
-        id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
     ) -> Result<serde_json::Value, error::Error>;
+
+    /// Resource-level authorization, checked by the endpoints before a fetched resource is returned
+    /// NOTICE:
+    ///   - In collections, the resources which are not allowed are just filtered out
+    ///   - For relationship and related routes, the parent resource is checked
+    ///   - The related resources and the ones in `included` are checked by `can_include`
+    async fn can(&self, _ctx: &OperationContext, _action: &Action, _item: &Self::Item) -> Decision {
+        Decision::Allow
+    }
+
+    /// Resource-level authorization of the resources rendered along with the ones of this
+    /// service, which may be of the other types: the related resources of the related routes and
+    /// the ones in `included`, where `action` is the one of the primary resources
+    /// NOTICE:
+    ///   - The resources which are not allowed are just dropped, where a to-one related resource
+    ///     becomes `null`
+    ///   - The resources are checked after they are fetched, so a page of the related resources
+    ///     paginated by a storage may be shorter than the page size
+    async fn can_include(
+        &self, _ctx: &OperationContext, _action: &Action, _resource: &Resource,
+    ) -> Decision {
+        Decision::Allow
+    }
+}
+
+/// Retains the items of a collection which are allowed by `Fetching::can`
+pub async fn authorize_collection<T: Fetching>(
+    service: &T, ctx: &OperationContext, items: Vec<T::Item>,
+) -> Vec<T::Item> {
+    let mut allowed = Vec::with_capacity(items.len());
+    for item in items {
+        if service.can(ctx, &Action::FetchCollection, &item).await == Decision::Allow {
+            allowed.push(item);
+        }
+    }
+    allowed
+}
+
+/// Checks a single resource with `Fetching::can`, returning the 403/404 error when not allowed
+pub async fn authorize_single<T: Fetching>(
+    service: &T, ctx: &OperationContext, action: &Action, item: &T::Item,
+) -> RbhResult<()> {
+    service.can(ctx, action, item).await.into_result(action, item)
+}
+
+/// Checks the parent resource of a relationship or related route with `Fetching::can`
+/// NOTICE:
+///   - The parent resource is loaded with `Fetching::fetch_single`
+///   - If the parent does not exist, the check is skipped, leaving the error to the operation
+///     itself
+pub async fn authorize_parent<T: Fetching>(
+    service: &T, ctx: &OperationContext, id: &str, action: &Action, query: &Query,
+) -> RbhResult<()> {
+    let plan = FetchPlan::new::<T::Item>(query).with_tenant(ctx.tenant.as_deref());
+    if let Some(parent) = service.fetch_single(id, &plan).await? {
+        authorize_single(service, ctx, action, &parent).await
+    } else {
+        Ok(())
+    }
+}
+
+/// Drops the resources of `resources`, a resource object or an array of them, which are not
+/// allowed by `Fetching::can_include`, where a dropped resource object becomes `null`
+pub async fn authorize_resources<T: Fetching>(
+    service: &T, ctx: &OperationContext, action: &Action, resources: &mut serde_json::Value,
+) {
+    match resources {
+        serde_json::Value::Array(items) => {
+            let mut allowed = Vec::with_capacity(items.len());
+            for item in items.drain(..) {
+                if can_include(service, ctx, action, &item).await {
+                    allowed.push(item);
+                }
+            }
+            *items = allowed;
+        },
+        serde_json::Value::Object(_) => {
+            if !can_include(service, ctx, action, resources).await {
+                *resources = serde_json::Value::Null;
+            }
+        },
+        _ => {},
+    }
+}
+
+/// Drops the resources in `included` of `doc`, the JSON of a document, which are not allowed by
+/// `Fetching::can_include`
+pub async fn authorize_included<T: Fetching>(
+    service: &T, ctx: &OperationContext, action: &Action, doc: &mut serde_json::Value,
+) {
+    if let Some(included) = doc.get_mut("included") {
+        authorize_resources(service, ctx, action, included).await;
+    }
+}
+
+/// The resources which are not valid resource objects are never allowed
+async fn can_include<T: Fetching>(
+    service: &T, ctx: &OperationContext, action: &Action, resource: &serde_json::Value,
+) -> bool {
+    match serde_json::from_value::<Resource>(resource.clone()) {
+        Ok(resource) => service.can_include(ctx, action, &resource).await == Decision::Allow,
+        Err(_) => false,
+    }
 }
@@ -0,0 +1,68 @@
+//! The documents of the large collections rendered in parallel with `rayon`, where the resources
+//! and the included resources of the entities are computed on the thread pool of `rayon`, and the
+//! included resources are merged afterwards:
+//! ```ignore
+//! let doc = parallel::to_document(&humans, uri, &query, &request_path)?;
+//! ```
+//! which is the same document as `Entity::to_document_automatically` of the entities, for the
+//! CPU-bound serialization of the entities with many relationships
+
+use crate::entity::SingleEntity;
+use crate::model::document::{Document, Included};
+use crate::model::link::{Link, RawUri};
+use crate::model::resource::Resource;
+use crate::query::{FieldsQuery, IncludeQuery, Query};
+use crate::RbhResult;
+use rayon::prelude::*;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+/// The included resources of all of `entities`, like `Entity::included` of the slice
+pub fn included<E: SingleEntity + Sync>(
+    entities: &[E], uri: &str, include_query: &Option<IncludeQuery>, fields_query: &FieldsQuery,
+) -> RbhResult<Included> {
+    entities.par_iter().map(|e| e.included(uri, include_query, fields_query)).try_reduce(
+        Included::default,
+        |mut a, mut b| {
+            // The smaller one is merged into the larger one
+            if a.len() < b.len() {
+                std::mem::swap(&mut a, &mut b);
+            }
+            a.extend(b);
+            Ok(a)
+        },
+    )
+}
+
+/// The resources of `entities`, in the same order
+pub fn resources<E: SingleEntity + Sync>(
+    entities: &[E], uri: &str, fields_query: &FieldsQuery,
+) -> Vec<Resource> {
+    entities.par_iter().filter_map(|e| e.to_resource(uri, fields_query)).collect()
+}
+
+/// The document of `entities`, like `Entity::to_document_automatically` of the slice, where the
+/// indices of the entities are sorted and paged before their resources are computed in parallel
+pub fn to_document<E: SingleEntity + Sync>(
+    entities: &[E], uri: &str, query: &Query, request_path: &RawUri,
+) -> RbhResult<Document> {
+    let indices = query.sort.sorted_indices(entities);
+    let indices = match &query.page {
+        Some(page) => page.page_indices(entities, &indices),
+        None => indices.as_slice(),
+    };
+    let (resources, included) = rayon::join(
+        || {
+            indices
+                .par_iter()
+                .filter_map(|&i| entities[i].to_resource(uri, &query.fields))
+                .collect()
+        },
+        || included(entities, uri, &query.include, &query.fields),
+    );
+    Ok(Document::multiple_resources(
+        resources,
+        included?,
+        Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())])),
+    ))
+}
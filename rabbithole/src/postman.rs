@@ -0,0 +1,246 @@
+//! The Postman collections (v2.1) and the curl commands of the fetching routes of the resource
+//! types in a `Registry`, as a ready-to-import playground of the API:
+//! ```ignore
+//! let settings = PostmanSettings::new("Pets", "http://localhost:8080/api")
+//!     .example(&humans)
+//!     .example(&dogs);
+//! std::fs::write("pets.postman_collection.json", postman::generate(&registry, &settings).to_string())?;
+//! std::fs::write("pets.sh", postman::curl(&registry, &settings))?;
+//! ```
+//! where the example entities, like the fixtures of the tests, give the ids in the requests and the
+//! bodies of their saved responses
+
+use crate::entity::SingleEntity;
+use crate::model::document::Document;
+use crate::model::resource::{IdentifierData, Resource};
+use crate::schema::{Registry, ResourceSchema};
+use crate::JSON_API_HEADER;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const SCHEMA: &str = "https://schema.getpostman.com/json/collection/v2.1.0/collection.json";
+
+/// The variable of the base URI in the URLs of the collection
+const BASE_URL: &str = "{{baseUrl}}";
+
+/// The name of the collection, where and how the resource types are served, and the examples
+#[derive(Debug, Clone)]
+pub struct PostmanSettings {
+    pub name: String,
+    /// The base URI of the resources, like `http://localhost:8080/api`, which is the `baseUrl`
+    /// variable of the collection
+    pub uri: String,
+    /// The paths of the resource types under `uri`, which are `/<type>` if missing
+    pub paths: HashMap<String, String>,
+    /// The example resources of the resource types
+    pub examples: HashMap<String, Vec<Resource>>,
+}
+
+impl PostmanSettings {
+    pub fn new(name: &str, uri: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            uri: uri.trim_end_matches('/').to_string(),
+            paths: Default::default(),
+            examples: Default::default(),
+        }
+    }
+
+    /// Adds `items` to the examples of their type
+    pub fn example<E: SingleEntity>(mut self, items: &[E]) -> Self {
+        let uri = &self.uri;
+        let resources: Vec<Resource> =
+            items.iter().filter_map(|item| item.to_resource(uri, &Default::default())).collect();
+        self.examples.entry(E::ty()).or_default().extend(resources);
+        self
+    }
+
+    pub fn resource_path(&self, ty: &str) -> String {
+        self.paths.get(ty).cloned().unwrap_or_else(|| format!("/{}", ty))
+    }
+}
+
+/// A request of a fetching route, with the body of its example response if any
+struct Request {
+    name: String,
+    /// The path under the base URI, where the id is the path variable `:id`
+    path: String,
+    id: Option<String>,
+    query: Vec<(String, String)>,
+    example: Option<Value>,
+}
+
+/// The Postman collection of all of the fetching routes of the resource types in `registry`,
+/// with a folder of each resource type
+pub fn generate(registry: &Registry, settings: &PostmanSettings) -> Value {
+    let folders: Vec<Value> = registry
+        .resources()
+        .iter()
+        .map(|resource| {
+            let items: Vec<Value> = requests(resource, settings).iter().map(request_item).collect();
+            json!({ "name": resource.name, "item": items })
+        })
+        .collect();
+    json!({
+        "info": { "name": settings.name, "schema": SCHEMA },
+        "item": folders,
+        "variable": [{ "key": "baseUrl", "value": settings.uri }],
+    })
+}
+
+/// The curl commands of all of the fetching routes of the resource types in `registry`, as a
+/// shell script
+pub fn curl(registry: &Registry, settings: &PostmanSettings) -> String {
+    let mut out = String::from("#!/bin/sh\n");
+    for resource in registry.resources().iter() {
+        for request in requests(resource, settings) {
+            let path = request.path.replace(":id", request.id.as_deref().unwrap_or("1"));
+            out.push_str(&format!(
+                "\n# {}\ncurl -H 'Accept: {header}' -H 'Content-Type: {header}' '{}{}'\n",
+                request.name,
+                settings.uri,
+                path,
+                header = JSON_API_HEADER,
+            ));
+        }
+    }
+    out
+}
+
+fn requests(resource: &ResourceSchema, settings: &PostmanSettings) -> Vec<Request> {
+    let resource_path = settings.resource_path(&resource.ty);
+    let examples = settings.examples.get(&resource.ty).map(Vec::as_slice).unwrap_or_default();
+    let example = examples.first();
+    let id = example.map(|example| example.id.id.clone());
+    let fields: Vec<&str> = resource
+        .attributes
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .chain(resource.relationships())
+        .collect();
+
+    let mut query = vec![];
+    if !resource.to_ones.is_empty() || !resource.to_manys.is_empty() {
+        query.push(("include".to_string(), resource.relationships().collect::<Vec<_>>().join(",")));
+    }
+    if !fields.is_empty() {
+        query.push((format!("fields[{}]", resource.ty), fields.join(",")));
+    }
+    if let Some((name, _)) = resource.attributes.first() {
+        query.push(("sort".to_string(), name.clone()));
+    }
+    query.push(("page[offset]".to_string(), "0".to_string()));
+    query.push(("page[limit]".to_string(), "10".to_string()));
+
+    let collection = (!examples.is_empty()).then(|| {
+        document(Document::multiple_resources(examples.to_vec(), Default::default(), None))
+    });
+    let single = example.map(|example| {
+        document(Document::single_resource(example.clone(), Default::default(), None))
+    });
+    let mut requests = vec![
+        Request {
+            name: format!("Fetch the collection of `{}`", resource.ty),
+            path: resource_path.clone(),
+            id: None,
+            query,
+            example: collection,
+        },
+        Request {
+            name: format!("Fetch a resource of `{}`", resource.ty),
+            path: format!("{}/:id", resource_path),
+            id: id.clone(),
+            query: vec![],
+            example: single,
+        },
+    ];
+    for (field, related_ty) in resource.to_ones.iter().chain(&resource.to_manys) {
+        let relationship = example.and_then(|example| example.relationships.get(field.as_str()));
+        let related = relationship.map(|relationship| {
+            let related_examples =
+                settings.examples.get(related_ty).map(Vec::as_slice).unwrap_or_default();
+            let find = |ty: &str, id: &str| {
+                related_examples.iter().find(|r| r.id.ty == ty && r.id.id == id).cloned()
+            };
+            let doc = match &relationship.data {
+                IdentifierData::Single(Some(identifier)) => {
+                    match find(&identifier.ty, &identifier.id) {
+                        Some(resource) => {
+                            Document::single_resource(resource, Default::default(), None)
+                        },
+                        None => Document::null(None),
+                    }
+                },
+                IdentifierData::Single(None) => Document::null(None),
+                IdentifierData::Multiple(identifiers) => Document::multiple_resources(
+                    identifiers
+                        .iter()
+                        .filter_map(|identifier| find(&identifier.ty, &identifier.id))
+                        .collect(),
+                    Default::default(),
+                    None,
+                ),
+            };
+            document(doc)
+        });
+        requests.push(Request {
+            name: format!("Fetch the relationship `{}` of a resource of `{}`", field, resource.ty),
+            path: format!("{}/:id/relationships/{}", resource_path, field),
+            id: id.clone(),
+            query: vec![],
+            example: relationship
+                .map(|relationship| serde_json::to_value(relationship).unwrap_or_default()),
+        });
+        requests.push(Request {
+            name: format!("Fetch the related `{}` of a resource of `{}`", field, resource.ty),
+            path: format!("{}/:id/{}", resource_path, field),
+            id: id.clone(),
+            query: vec![],
+            example: related,
+        });
+    }
+    requests
+}
+
+fn document(doc: Document) -> Value { serde_json::to_value(doc).unwrap_or_default() }
+
+/// The item of `request` in the collection, where the query parameters are disabled examples
+fn request_item(request: &Request) -> Value {
+    let headers = json!([
+        { "key": "Accept", "value": JSON_API_HEADER },
+        { "key": "Content-Type", "value": JSON_API_HEADER },
+    ]);
+    let path: Vec<&str> = request.path.split('/').filter(|segment| !segment.is_empty()).collect();
+    let mut url = json!({
+        "raw": format!("{}{}", BASE_URL, request.path),
+        "host": [BASE_URL],
+        "path": path,
+    });
+    if !request.query.is_empty() {
+        url["query"] = request
+            .query
+            .iter()
+            .map(|(key, value)| json!({ "key": key, "value": value, "disabled": true }))
+            .collect();
+    }
+    if request.path.contains(":id") {
+        url["variable"] = json!([{ "key": "id", "value": request.id.as_deref().unwrap_or("1") }]);
+    }
+    let http_request = json!({ "method": "GET", "header": headers, "url": url });
+    let responses: Vec<Value> = request
+        .example
+        .iter()
+        .map(|body| {
+            json!({
+                "name": "Example",
+                "originalRequest": http_request,
+                "status": "OK",
+                "code": 200,
+                "_postman_previewlanguage": "json",
+                "header": [{ "key": "Content-Type", "value": JSON_API_HEADER }],
+                "body": serde_json::to_string_pretty(body).unwrap_or_default(),
+            })
+        })
+        .collect();
+    json!({ "name": request.name, "request": http_request, "response": responses })
+}
@@ -1,5 +1,6 @@
 use crate::model::error;
 
+use crate::trace;
 use crate::RbhResult;
 
 use rsql_rs::ast::expr::Expr;
@@ -17,6 +18,11 @@ use rsql_rs::parser::rsql::RsqlParser;
 #[cfg(feature = "filter_rsql")]
 use rsql_rs::parser::Parser;
 
+#[cfg(feature = "filter_rsql")]
+use crate::model::resource::AttributeField;
+#[cfg(feature = "filter_rsql")]
+use regex::Regex;
+
 use crate::entity::SingleEntity;
 #[cfg(feature = "filter_rsql")]
 use std::cmp::Ordering;
@@ -60,17 +66,19 @@ impl FilterData for RsqlFilterData {
     #[cfg(feature = "filter_rsql")]
     fn filter<E: SingleEntity>(&self, mut entities: Vec<E>) -> RbhResult<Vec<E>> {
         for (ty_or_relat, expr) in &self.0 {
+            if &E::ty() != ty_or_relat {
+                if entities.is_empty() {
+                    continue;
+                }
+                return Err(error::Error::RsqlFilterOnRelatedNotImplemented(None));
+            }
+            let predicate = Self::compile::<E>(expr)?;
             entities = entities
                 .into_iter()
-                .filter_map(|r| {
-                    match (&E::ty() == ty_or_relat, Self::filter_on_attributes(expr, &r)) {
-                        (true, Ok(true)) => Some(Ok(r)),
-                        (true, Ok(false)) => None,
-                        (true, Err(err)) => Some(Err(err)),
-                        (false, _) => {
-                            Some(Err(error::Error::RsqlFilterOnRelatedNotImplemented(None)))
-                        },
-                    }
+                .filter_map(|r| match predicate(&r) {
+                    Ok(true) => Some(Ok(r)),
+                    Ok(false) => None,
+                    Err(err) => Some(Err(err)),
                 })
                 .collect::<RbhResult<Vec<E>>>()?;
         }
@@ -78,79 +86,241 @@ impl FilterData for RsqlFilterData {
     }
 }
 
+impl From<HashMap<String, Expr>> for RsqlFilterData {
+    fn from(exprs: HashMap<String, Expr>) -> Self { Self(exprs) }
+}
+
 impl RsqlFilterData {
-    #[cfg(feature = "filter_rsql")]
-    pub fn filter_on_attributes<E: SingleEntity>(expr: &Expr, entity: &E) -> RbhResult<bool> {
-        let ent: bool = match &expr {
-            Expr::Item(Constraint { selector, comparison, arguments }) => {
-                if let Ok(field) = entity.attributes().get_field(&selector) {
-                    if comparison == &comparison::EQUAL as &Comparison && arguments.0.len() == 1 {
-                        let arg: &str = arguments.0.first().unwrap();
-                        field.eq_with_str(arg, &selector)?
-                    } else if comparison == &comparison::NOT_EQUAL as &Comparison
-                        && arguments.0.len() == 1
-                    {
-                        let arg: &str = arguments.0.first().unwrap();
-                        field.eq_with_str(arg, &selector)? == false
-                    } else if comparison == &comparison::GREATER_THAN as &Comparison
-                        && arguments.0.len() == 1
-                    {
-                        let arg: &str = arguments.0.first().unwrap();
-                        field.cmp_with_str(arg, &selector)? == Ordering::Greater
-                    } else if comparison == &comparison::GREATER_THAN_OR_EQUAL as &Comparison
-                        && arguments.0.len() == 1
-                    {
-                        let arg: &str = arguments.0.first().unwrap();
-                        let res = field.cmp_with_str(arg, &selector)?;
-                        res == Ordering::Greater || res == Ordering::Equal
-                    } else if comparison == &comparison::LESS_THAN as &Comparison
-                        && arguments.0.len() == 1
-                    {
-                        let arg: &str = arguments.0.first().unwrap();
-                        let res = field.cmp_with_str(arg, &selector)?;
-                        res == Ordering::Less
-                    } else if comparison == &comparison::LESS_THAN_OR_EQUAL as &Comparison
-                        && arguments.0.len() == 1
-                    {
-                        let arg: &str = arguments.0.first().unwrap();
-                        let res = field.cmp_with_str(arg, &selector)?;
-                        res == Ordering::Less || res == Ordering::Equal
-                    } else if comparison == &comparison::IN as &Comparison {
-                        arguments
-                            .0
-                            .iter()
-                            .find(|s| field.eq_with_str(s, &selector).is_ok())
-                            .is_some()
-                    } else if comparison == &comparison::OUT as &Comparison {
-                        arguments
-                            .0
-                            .iter()
-                            .find(|s| field.eq_with_str(s, &selector).is_ok())
-                            .is_none()
-                    } else {
-                        Err(error::Error::UnsupportedRsqlComparison(
-                            &comparison.symbols,
-                            arguments.0.len(),
-                            None,
-                        ))?
-                    }
+    /// The expressions by the type or the relationship they are applied on, for the services
+    /// translating them into the queries of their storages
+    pub fn exprs(&self) -> &HashMap<String, Expr> { &self.0 }
+
+    /// Renames the expressions on the aliases of `aliases` into the ones on their types, where
+    /// the expression on the type itself is kept if both are given
+    pub fn resolve_aliases(&mut self, aliases: &HashMap<String, String>) {
+        for (alias, ty) in aliases {
+            if let Some(expr) = self.0.remove(alias) {
+                self.0.entry(ty.clone()).or_insert(expr);
+            }
+        }
+    }
+
+    /// The RSQL string of `expr`, parsed back into the same expression, where the nested
+    /// expressions are grouped and the arguments with the reserved characters are quoted
+    pub fn to_rsql(expr: &Expr) -> String {
+        match expr {
+            Expr::Item(constraint) => {
+                let symbol = constraint.comparison.symbols.first().map_or("==", String::as_str);
+                let arguments: Vec<String> =
+                    constraint.arguments.0.iter().map(|arg| rsql_argument(arg)).collect();
+                if constraint.comparison.multi_values {
+                    format!("{}{}({})", constraint.selector, symbol, arguments.join(","))
                 } else {
-                    Err(error::Error::FieldNotExist(&selector, None))?
+                    format!("{}{}{}", constraint.selector, symbol, arguments.join(","))
                 }
             },
             Expr::Node(op, left, right) => {
-                let left = Self::filter_on_attributes(left, entity)?;
-                match op {
-                    Operator::And => left && Self::filter_on_attributes(right, entity)?,
-                    Operator::Or => left || Self::filter_on_attributes(right, entity)?,
+                let group = |expr: &Expr| match expr {
+                    Expr::Node(..) => format!("({})", Self::to_rsql(expr)),
+                    Expr::Item(_) => Self::to_rsql(expr),
+                };
+                let op = match op {
+                    rsql_rs::ast::Operator::And => ";",
+                    rsql_rs::ast::Operator::Or => ",",
+                };
+                format!("{}{}{}", group(left), op, group(right))
+            },
+        }
+    }
+
+    /// Compiles `expr` into the predicate of the entities of `E`, where the comparisons are
+    /// resolved, the arguments are parsed and the wildcard patterns are built only once, and the
+    /// selectors are checked against the attributes in `E::schema`, if it has any
+    ///
+    /// The selectors may be the dotted paths into the object and the array attributes, like
+    /// `address.city` or `tags.0`, where `size` of an array is its length, like `tags.size=ge=2`.
+    /// The array values match `==` if any of the elements does, like `tags==cute`, and `=in=` if
+    /// any of the elements is in the arguments, while `!=` and `=out=` are the negations of them
+    #[cfg(feature = "filter_rsql")]
+    pub fn compile<'a, E: SingleEntity + 'a>(expr: &Expr) -> RbhResult<Predicate<'a, E>> {
+        match expr {
+            Expr::Item(Constraint { selector, comparison, arguments }) => {
+                let mut path = selector.split('.');
+                let attribute = path.next().unwrap_or_default().to_string();
+                let path: Vec<String> = path.map(ToString::to_string).collect();
+                let schema = E::schema();
+                if !schema.attributes.is_empty()
+                    && !schema.attributes.iter().any(|(name, _)| name == &attribute)
+                {
+                    return Err(error::Error::FieldNotExist(selector, None));
                 }
+                let comparison = CompiledComparison::new(comparison, arguments.0.len())?;
+                let arguments: Vec<Argument> =
+                    arguments.0.iter().map(|arg| Argument::new(arg)).collect();
+                let selector = selector.clone();
+                let predicate: Predicate<'a, E> = Box::new(move |entity: &E| {
+                    let field = entity
+                        .attribute(&attribute)
+                        .and_then(|field| {
+                            path.iter().try_fold(field, |field, key| field.member(key))
+                        })
+                        .ok_or_else(|| error::Error::FieldNotExist(&selector, None))?;
+                    if let Some(matched) = field.elements().and_then(|elements| {
+                        comparison.test_elements(&elements, &arguments, &selector)
+                    }) {
+                        return Ok(matched);
+                    }
+                    comparison.test(&field, &arguments, &selector)
+                });
+                Ok(predicate)
+            },
+            Expr::Node(op, left, right) => {
+                let left = Self::compile::<E>(left)?;
+                let right = Self::compile::<E>(right)?;
+                let predicate: Predicate<'a, E> = match op {
+                    Operator::And => {
+                        Box::new(move |entity: &E| Ok(left(entity)? && right(entity)?))
+                    },
+                    Operator::Or => Box::new(move |entity: &E| Ok(left(entity)? || right(entity)?)),
+                };
+                Ok(predicate)
             },
+        }
+    }
+
+    #[cfg(feature = "filter_rsql")]
+    pub fn filter_on_attributes<E: SingleEntity>(expr: &Expr, entity: &E) -> RbhResult<bool> {
+        Self::compile::<E>(expr)?(entity)
+    }
+}
+
+/// The predicate of a compiled RSQL expression, evaluated on each entity
+#[cfg(feature = "filter_rsql")]
+pub type Predicate<'a, E> = Box<dyn Fn(&E) -> RbhResult<bool> + Send + Sync + 'a>;
+
+#[cfg(feature = "filter_rsql")]
+#[derive(Debug, Clone, Copy)]
+enum CompiledComparison {
+    Equal,
+    NotEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+    LessThan,
+    LessThanOrEqual,
+    In,
+    Out,
+}
+
+#[cfg(feature = "filter_rsql")]
+impl CompiledComparison {
+    fn new(comparison: &Comparison, param_cnt: usize) -> RbhResult<Self> {
+        let single: [(&Comparison, CompiledComparison); 6] = [
+            (&comparison::EQUAL, CompiledComparison::Equal),
+            (&comparison::NOT_EQUAL, CompiledComparison::NotEqual),
+            (&comparison::GREATER_THAN, CompiledComparison::GreaterThan),
+            (&comparison::GREATER_THAN_OR_EQUAL, CompiledComparison::GreaterThanOrEqual),
+            (&comparison::LESS_THAN, CompiledComparison::LessThan),
+            (&comparison::LESS_THAN_OR_EQUAL, CompiledComparison::LessThanOrEqual),
+        ];
+        if let Some((_, compiled)) = single.iter().find(|(c, _)| *c == comparison) {
+            if param_cnt == 1 {
+                return Ok(*compiled);
+            }
+        } else if comparison == &comparison::IN as &Comparison {
+            return Ok(CompiledComparison::In);
+        } else if comparison == &comparison::OUT as &Comparison {
+            return Ok(CompiledComparison::Out);
+        }
+        Err(error::Error::UnsupportedRsqlComparison(&comparison.symbols, param_cnt, None))
+    }
+
+    fn test(self, field: &AttributeField, args: &[Argument], selector: &str) -> RbhResult<bool> {
+        Ok(match self {
+            CompiledComparison::Equal => args[0].eq(field, selector)?,
+            CompiledComparison::NotEqual => !args[0].eq(field, selector)?,
+            CompiledComparison::GreaterThan => args[0].cmp(field, selector)? == Ordering::Greater,
+            CompiledComparison::GreaterThanOrEqual => {
+                args[0].cmp(field, selector)? != Ordering::Less
+            },
+            CompiledComparison::LessThan => args[0].cmp(field, selector)? == Ordering::Less,
+            CompiledComparison::LessThanOrEqual => {
+                args[0].cmp(field, selector)? != Ordering::Greater
+            },
+            CompiledComparison::In => {
+                args.iter().any(|arg| matches!(arg.eq(field, selector), Ok(true)))
+            },
+            CompiledComparison::Out => {
+                !args.iter().any(|arg| matches!(arg.eq(field, selector), Ok(true)))
+            },
+        })
+    }
+
+    /// Tests the elements of an array value, where the equalities and `=in=` match if any of the
+    /// elements does, or `None` for the orderings, which can't be tested on the arrays
+    fn test_elements(
+        self, elements: &[AttributeField], args: &[Argument], selector: &str,
+    ) -> Option<bool> {
+        let any = |comparison: CompiledComparison| {
+            elements
+                .iter()
+                .any(|element| matches!(comparison.test(element, args, selector), Ok(true)))
         };
-        Ok(ent)
+        match self {
+            CompiledComparison::Equal | CompiledComparison::In => Some(any(self)),
+            CompiledComparison::NotEqual => Some(!any(CompiledComparison::Equal)),
+            CompiledComparison::Out => Some(!any(CompiledComparison::In)),
+            _ => None,
+        }
+    }
+}
+
+/// An argument of the comparisons, parsed into the attribute value, and into the pattern if it
+/// has any wildcard `*`
+#[cfg(feature = "filter_rsql")]
+struct Argument {
+    raw: String,
+    value: Option<AttributeField>,
+    pattern: Option<Regex>,
+}
+
+#[cfg(feature = "filter_rsql")]
+impl Argument {
+    fn new(raw: &str) -> Self {
+        let pattern =
+            if raw.contains('*') { raw.replace('*', "\\w*").parse::<Regex>().ok() } else { None };
+        Self { raw: raw.to_string(), value: raw.parse().ok(), pattern }
+    }
+
+    /// Like `AttributeField::eq_with_str`
+    fn eq(&self, field: &AttributeField, selector: &str) -> RbhResult<bool> {
+        if let (Some(pattern), Some(value)) = (&self.pattern, field.as_str()) {
+            return Ok(pattern.is_match(value));
+        }
+        self.cmp(field, selector).map(|o| o == Ordering::Equal)
     }
+
+    /// Like `AttributeField::cmp_with_str`, where the arguments which aren't JSON, like `Tokyo`,
+    /// are compared with the string values as they are
+    fn cmp(&self, field: &AttributeField, selector: &str) -> RbhResult<Ordering> {
+        match (&self.value, field.as_str()) {
+            (Some(value), _) => field.cmp_with(value, selector),
+            (None, Some(value)) => Ok(value.cmp(self.raw.as_str())),
+            (None, None) => field.cmp_with_str(&self.raw, selector),
+        }
+    }
+}
+
+/// `arg` quoted if it's empty or has any reserved character of RSQL
+fn rsql_argument(arg: &str) -> String {
+    let reserved = |c: char| "\"'();,=!~<> ".contains(c) || c.is_whitespace();
+    if !arg.is_empty() && !arg.contains(reserved) {
+        return arg.to_string();
+    }
+    let escaped = arg.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum FilterQuery {
     Rsql(RsqlFilterData),
 }
@@ -165,8 +335,11 @@ impl FilterQuery {
     }
 
     pub fn filter<E: SingleEntity>(&self, entities: Vec<E>) -> RbhResult<Vec<E>> {
-        match &self {
+        let span = trace::filter_span(&E::ty());
+        let entities = span.in_scope(|| match &self {
             FilterQuery::Rsql(map) => RsqlFilterData::filter(map, entities),
-        }
+        })?;
+        span.record(trace::RESULT_COUNT, entities.len() as i64);
+        Ok(entities)
     }
 }
@@ -0,0 +1,81 @@
+use crate::model::error::{self, ErrorSource};
+use crate::query::filter::FilterQuery;
+use crate::query::Query;
+use crate::RbhResult;
+
+use rsql_rs::ast::expr::Expr;
+use serde::Deserialize;
+
+/// The limits of the complexity of the queries, checked before the queries are evaluated, where
+/// `None` is unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct QueryLimits {
+    /// The most constraints in the filter expression of a type or a relationship
+    #[serde(default)]
+    pub max_filter_size: Option<usize>,
+    /// The depth of the tree of a filter expression, where a single constraint is `1`, and each
+    /// `;` or `,` adds a level to the deeper one of its operands
+    #[serde(default)]
+    pub max_filter_depth: Option<usize>,
+    /// The most relationship paths in `include`
+    #[serde(default)]
+    pub max_includes: Option<usize>,
+    /// The most fields in `sort`
+    #[serde(default)]
+    pub max_sort_keys: Option<usize>,
+    /// The most fields in a fieldset of `fields[<ty>]`
+    #[serde(default)]
+    pub max_fieldset_size: Option<usize>,
+}
+
+impl QueryLimits {
+    /// Checks `query` against the limits, failing with `400` and the query parameter over its
+    /// limit in `source.parameter`
+    pub fn check(&self, query: &Query) -> RbhResult<()> {
+        let includes = query.include.as_ref().map_or(0, |include| include.len());
+        check_limit("include", includes, self.max_includes, "relationship paths")?;
+        check_limit("sort", query.sort.fields().len(), self.max_sort_keys, "sort keys")?;
+        for (ty, sort) in &query.included_sort {
+            let parameter = format!("sort[{}]", ty);
+            check_limit(&parameter, sort.fields().len(), self.max_sort_keys, "sort keys")?;
+        }
+        for (ty, fields) in &query.fields {
+            let parameter = format!("fields[{}]", ty);
+            check_limit(&parameter, fields.len(), self.max_fieldset_size, "fields")?;
+        }
+        if let Some(FilterQuery::Rsql(filter)) = &query.filter {
+            for (ty, expr) in filter.exprs() {
+                let parameter = format!("filter[{}]", ty);
+                check_limit(&parameter, expr_size(expr), self.max_filter_size, "constraints")?;
+                check_limit(&parameter, expr_depth(expr), self.max_filter_depth, "levels")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn check_limit(parameter: &str, count: usize, limit: Option<usize>, what: &str) -> RbhResult<()> {
+    match limit {
+        Some(limit) if count > limit => {
+            let source = ErrorSource { pointer: None, parameter: Some(parameter.to_string()) };
+            Err(error::Error::QueryTooComplex(parameter, limit, what, Some(source)))
+        },
+        _ => Ok(()),
+    }
+}
+
+/// The number of the constraints in `expr`
+fn expr_size(expr: &Expr) -> usize {
+    match expr {
+        Expr::Item(_) => 1,
+        Expr::Node(_, left, right) => expr_size(left) + expr_size(right),
+    }
+}
+
+/// The depth of the tree of the operators in `expr`
+fn expr_depth(expr: &Expr) -> usize {
+    match expr {
+        Expr::Item(_) => 1,
+        Expr::Node(_, left, right) => 1 + expr_depth(left).max(expr_depth(right)),
+    }
+}
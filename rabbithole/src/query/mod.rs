@@ -1,22 +1,27 @@
 pub mod filter;
+pub mod limits;
 pub mod page;
 pub mod sort;
 
 use crate::model::error;
 
+use crate::trace;
 use crate::RbhResult;
 
-use crate::query::filter::FilterQuery;
+use crate::model::resource::AttributeField;
+use crate::query::filter::{FilterQuery, RsqlFilterData};
 use crate::query::page::PageQuery;
 use crate::query::sort::SortQuery;
 use percent_encoding::percent_decode_str;
-use regex::Regex;
+use serde_json::Value;
+use std::cmp::Ordering;
 use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 pub type IncludeQuery = HashSet<String>;
 pub type FieldsQuery = HashMap<String, HashSet<String>>;
 
-#[derive(Debug, Default)]
+#[derive(Debug, Default, PartialEq)]
 pub struct Query {
     /// When include is:
     ///   1. `None`: all included fields will be added
@@ -35,78 +40,84 @@ pub struct Query {
     ///   2. some values, but none of the values matches: like branch 1
     ///   3. some values, and some of the items matches: sorting result with the order of the matched sort-query item
     pub sort: SortQuery,
+    /// The orders of the included resources by their types, like `sort[dogs]=-age`, where the
+    /// types are in the order of the parameters
+    pub included_sort: Vec<(String, SortQuery)>,
     pub page: Option<PageQuery>,
     pub filter: Option<FilterQuery>,
 }
 
-lazy_static! {
-    static ref KEY_REGEX: Regex = Regex::new(r#"(?P<name>\w+)\[(?P<param>[\w\-_@]+)\]"#).unwrap();
+/// Splits the keys like `fields[people]` into `("fields", "people")`, where the name is a word and
+/// the param is a word with `-` and `@`
+fn split_key(key: &str) -> Option<(&str, &str)> {
+    let (name, rest) = key.split_once('[')?;
+    let param = rest.strip_suffix(']')?;
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    if name.is_empty() || !name.chars().all(is_word) {
+        return None;
+    }
+    if param.is_empty() || !param.chars().all(|c| is_word(c) || c == '-' || c == '@') {
+        return None;
+    }
+    Some((name, param))
 }
 
 impl Query {
     pub fn from_uri(uri: &http::Uri) -> RbhResult<Query> {
+        trace::parse_query_span().in_scope(|| Self::parse_uri(uri))
+    }
+
+    /// The query string is decoded once, borrowed when nothing is percent-encoded, and all of the
+    /// keys and values are the slices of it, so only the stored values are allocated
+    fn parse_uri(uri: &http::Uri) -> RbhResult<Query> {
         let mut include_query: IncludeQuery = Default::default();
         let mut include_query_exist = false;
         let mut sort_query: SortQuery = Default::default();
+        let mut included_sort: Vec<(String, SortQuery)> = Default::default();
         let mut filter_map: HashMap<String, String> = Default::default();
-        let mut filter_type: Option<String> = None;
+        let mut filter_type: Option<&str> = None;
         let mut fields_map: FieldsQuery = Default::default();
         let mut page_map: HashMap<String, String> = Default::default();
 
-        if let Some(query_str) = uri.query() {
-            let query_str = percent_decode_str(query_str)
+        let query_str = match uri.query() {
+            Some(query_str) => percent_decode_str(query_str)
                 .decode_utf8()
-                .map_err(|err| error::Error::NotUtf8String(query_str, &err, None))?;
+                .map_err(|err| error::Error::NotUtf8String(query_str, &err, None))?,
+            None => Default::default(),
+        };
 
-            for (key, value) in query_str.split('&').filter_map(|s| {
-                let kv_pair: Vec<&str> = s.splitn(2, '=').collect();
-                if kv_pair.len() == 2 && !kv_pair[0].is_empty() {
-                    Some((kv_pair[0], kv_pair[1]))
-                } else {
-                    None
-                }
-            }) {
-                if key == "include" {
-                    include_query_exist = true;
+        let pairs = query_str.split('&').filter_map(|s| s.split_once('='));
+        for (key, value) in pairs.filter(|(key, _)| !key.is_empty()) {
+            let values = || value.split(',').filter(|s| !s.is_empty()).map(ToString::to_string);
 
-                    for v in value.split(',').filter(|s| !s.is_empty()).map(ToString::to_string) {
-                        include_query.insert(v);
+            if key == "include" {
+                include_query_exist = true;
+                include_query.extend(values());
+            } else if key == "sort" {
+                sort_query.insert_raw(value)?;
+            } else if let Some((name, param)) = split_key(key) {
+                if name == "fields" {
+                    if let Some(origin_fields) = fields_map.get_mut(param) {
+                        origin_fields.extend(values());
+                    } else {
+                        fields_map.insert(param.into(), values().collect());
                     }
-                    continue;
-                }
-
-                if key == "sort" {
-                    sort_query.insert_raw(value)?;
-                    continue;
-                }
-
-                if let Some(cap) = (&KEY_REGEX as &Regex).captures(key.as_ref()) {
-                    if let (Some(name), Some(param)) = (cap.name("name"), cap.name("param")) {
-                        let name = name.as_str();
-                        let param = param.as_str();
-
-                        if name == "fields" {
-                            let values: HashSet<String> = value
-                                .split(',')
-                                .filter(|s| !s.is_empty())
-                                .map(ToString::to_string)
-                                .collect();
-                            if let Some(origin_fields) = fields_map.get_mut(param) {
-                                for v in values {
-                                    origin_fields.insert(v);
-                                }
-                            } else {
-                                fields_map.insert(param.into(), values);
-                            }
-                        } else if name == "filter" && !value.is_empty() {
-                            if param == "@type" {
-                                filter_type = Some(value.into());
-                            } else {
-                                filter_map.insert(param.into(), value.to_string());
-                            }
-                        } else if name == "page" {
-                            page_map.insert(param.into(), value.to_string());
-                        }
+                } else if name == "filter" && !value.is_empty() {
+                    if param == "@type" {
+                        filter_type = Some(value);
+                    } else {
+                        filter_map.insert(param.into(), value.into());
+                    }
+                } else if name == "page" {
+                    page_map.insert(param.into(), value.into());
+                } else if name == "sort" {
+                    match included_sort.iter_mut().find(|(ty, _)| ty == param) {
+                        Some((_, sort)) => sort.insert_raw(value)?,
+                        None => {
+                            let mut sort = SortQuery::default();
+                            sort.insert_raw(value)?;
+                            included_sort.push((param.into(), sort));
+                        },
                     }
                 }
             }
@@ -116,8 +127,115 @@ impl Query {
         let sort = sort_query;
         let page = PageQuery::new(&page_map)?;
         let filter =
-            if let Some(ty) = filter_type { FilterQuery::new(&ty, &filter_map)? } else { None };
-        let query = Query { include, fields: fields_map, sort, page, filter };
+            if let Some(ty) = filter_type { FilterQuery::new(ty, &filter_map)? } else { None };
+        let query = Query { include, fields: fields_map, sort, included_sort, page, filter };
         Ok(query)
     }
+
+    /// Renames the aliases of the resource types in `fields[<ty>]` and `filter[<ty>]` into their
+    /// types, where `aliases` maps the aliases to the types
+    pub fn resolve_aliases(&mut self, aliases: &HashMap<String, String>) {
+        for (alias, ty) in aliases {
+            if let Some(fields) = self.fields.remove(alias) {
+                self.fields.entry(ty.clone()).or_default().extend(fields);
+            }
+        }
+        if let Some(FilterQuery::Rsql(data)) = &mut self.filter {
+            data.resolve_aliases(aliases);
+        }
+        for (ty, _) in &mut self.included_sort {
+            if let Some(resolved) = aliases.get(ty.as_str()) {
+                *ty = resolved.clone();
+            }
+        }
+    }
+
+    /// Sorts `included` of the JSON of a document by `included_sort`, where the types in it come
+    /// first in their order, each sorted by its fields and then by the IDs, and the other types
+    /// are after them in the order of the types and the IDs, as they're serialized
+    pub fn sort_included(&self, doc: &mut Value) {
+        if self.included_sort.is_empty() {
+            return;
+        }
+        let included = match doc.get_mut("included") {
+            Some(Value::Array(included)) => included,
+            _ => return,
+        };
+        let sorts = &self.included_sort;
+        let mut keyed: Vec<(usize, Vec<Option<AttributeField>>, Value)> = included
+            .drain(..)
+            .map(|resource| {
+                let ty = resource["type"].as_str().unwrap_or_default();
+                let rank = sorts.iter().position(|(sorted, _)| sorted == ty).unwrap_or(sorts.len());
+                let keys = sorts.get(rank).map_or_else(Vec::new, |(_, sort)| {
+                    let attributes = &resource["attributes"];
+                    sort.fields()
+                        .iter()
+                        .map(|(field, _)| attributes.get(field).cloned().map(AttributeField::from))
+                        .collect()
+                });
+                (rank, keys, resource)
+            })
+            .collect();
+        keyed.sort_by(|(rank, keys, a), (other_rank, other_keys, b)| {
+            rank.cmp(other_rank)
+                .then_with(|| a["type"].as_str().cmp(&b["type"].as_str()))
+                .then_with(|| {
+                    sorts
+                        .get(*rank)
+                        .map_or(Ordering::Equal, |(_, sort)| sort.cmp_keys(keys, other_keys))
+                })
+                .then_with(|| a["id"].as_str().cmp(&b["id"].as_str()))
+        });
+        included.extend(keyed.into_iter().map(|(_, _, resource)| resource));
+    }
+}
+
+/// The query string, like `include=dogs&sort=-name`, where the sets and the maps are sorted.
+/// It's parsed back into the same query by `Query::from_uri` after being percent-encoded
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sorted = |values: &HashSet<String>| {
+            let mut values: Vec<&str> = values.iter().map(String::as_str).collect();
+            values.sort_unstable();
+            values.join(",")
+        };
+        let mut params: Vec<String> = vec![];
+        if let Some(include) = &self.include {
+            params.push(format!("include={}", sorted(include)));
+        }
+        let mut fields: Vec<_> = self.fields.iter().collect();
+        fields.sort_by_key(|(ty, _)| *ty);
+        for (ty, fields) in fields {
+            params.push(format!("fields[{}]={}", ty, sorted(fields)));
+        }
+        if !self.sort.is_empty() {
+            params.push(format!("sort={}", self.sort));
+        }
+        for (ty, sort) in &self.included_sort {
+            params.push(format!("sort[{}]={}", ty, sort));
+        }
+        match &self.page {
+            Some(PageQuery::OffsetBased(data)) => {
+                params.push(format!("page[offset]={}&page[limit]={}", data.offset, data.limit))
+            },
+            Some(PageQuery::PageBased(data)) => {
+                params.push(format!("page[number]={}&page[size]={}", data.number, data.size))
+            },
+            Some(PageQuery::CursorBased(data)) => {
+                let cursor = serde_json::to_string(data).map_err(|_| fmt::Error)?;
+                params.push(format!("page[cursor]={}", base64::encode(&cursor)));
+            },
+            None => {},
+        }
+        if let Some(FilterQuery::Rsql(data)) = &self.filter {
+            params.push("filter[@type]=Rsql".into());
+            let mut exprs: Vec<_> = data.exprs().iter().collect();
+            exprs.sort_by_key(|(key, _)| *key);
+            for (key, expr) in exprs {
+                params.push(format!("filter[{}]={}", key, RsqlFilterData::to_rsql(expr)));
+            }
+        }
+        f.write_str(&params.join("&"))
+    }
 }
@@ -4,14 +4,14 @@ use crate::RbhResult;
 use std::collections::HashMap;
 
 use crate::entity::SingleEntity;
-#[cfg(feature = "page_cursor")]
-use std::iter::Step;
 use std::str::FromStr;
 
 trait PageData: Sized {
     fn new(params: &HashMap<String, String>) -> RbhResult<Option<Self>>;
 
-    fn page<E: SingleEntity>(&self, entities: &[E]) -> (usize, usize);
+    /// The range of the page in the `len` sorted items, where `position` is the index of the item
+    /// with an id
+    fn page(&self, len: usize, position: impl FnOnce(&str) -> Option<usize>) -> (usize, usize);
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -44,22 +44,24 @@ impl PageData for CursorBasedData {
     }
 
     #[cfg(feature = "page_cursor")]
-    fn page<E: SingleEntity>(&self, entities: &[E]) -> (usize, usize) {
-        if let Some(tid) = entities.iter().position(|r| r.id() == self.target_id) {
+    fn page(&self, len: usize, position: impl FnOnce(&str) -> Option<usize>) -> (usize, usize) {
+        if let Some(tid) = position(&self.target_id) {
             if self.is_look_after {
-                (tid + 1, (tid + 1 + self.limit).min(entities.len()))
+                (tid + 1, (tid + 1).saturating_add(self.limit).min(len))
             } else {
-                ((tid + 1).sub_usize(self.limit).unwrap_or(0usize), tid + 1)
+                ((tid + 1).saturating_sub(self.limit), tid + 1)
             }
         } else if self.is_look_after {
-            (0, self.limit.min(entities.len()))
+            (0, self.limit.min(len))
         } else {
-            (entities.len().sub_usize(self.limit).unwrap_or(0usize), entities.len())
+            (len.saturating_sub(self.limit), len)
         }
     }
 
     #[cfg(not(feature = "page_cursor"))]
-    fn page<E: SingleEntity>(&self, _entities: &[E]) -> (usize, usize) { unimplemented!() }
+    fn page(&self, _len: usize, _position: impl FnOnce(&str) -> Option<usize>) -> (usize, usize) {
+        unimplemented!()
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -83,9 +85,9 @@ impl PageData for OffsetBasedData {
         }
     }
 
-    fn page<E: SingleEntity>(&self, entities: &[E]) -> (usize, usize) {
-        let start = self.offset.min(entities.len());
-        let end = (self.offset + self.limit).min(entities.len());
+    fn page(&self, len: usize, _position: impl FnOnce(&str) -> Option<usize>) -> (usize, usize) {
+        let start = self.offset.min(len);
+        let end = self.offset.saturating_add(self.limit).min(len);
         (start, end)
     }
 }
@@ -109,14 +111,14 @@ impl PageData for PageBasedData {
         }
     }
 
-    fn page<E: SingleEntity>(&self, entities: &[E]) -> (usize, usize) {
-        let start = (self.number * self.size).min(entities.len());
-        let end = ((self.number + 1) * self.size).min(entities.len());
+    fn page(&self, len: usize, _position: impl FnOnce(&str) -> Option<usize>) -> (usize, usize) {
+        let start = self.number.saturating_mul(self.size).min(len);
+        let end = self.number.saturating_add(1).saturating_mul(self.size).min(len);
         (start, end)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 pub enum PageQuery {
     OffsetBased(OffsetBasedData),
     PageBased(PageBasedData),
@@ -137,13 +139,33 @@ impl PageQuery {
     }
 
     pub fn page<'a, E: SingleEntity>(&'a self, entities: &'a [E]) -> &'a [E] {
+        let (start, end) =
+            self.range(entities.len(), |id| entities.iter().position(|e| e.id() == id));
+        &entities[start .. end]
+    }
+
+    /// The page of `indices`, the indices of `entities` in the sorted order like the ones of
+    /// `SortQuery::sorted_indices`, without moving or cloning the entities
+    pub fn page_indices<'a, E: SingleEntity>(
+        &self, entities: &[E], indices: &'a [usize],
+    ) -> &'a [usize] {
+        let (start, end) =
+            self.range(indices.len(), |id| indices.iter().position(|&i| entities[i].id() == id));
+        &indices[start .. end]
+    }
+
+    /// The range of the page, which is empty rather than reversed for the pages out of range,
+    /// as the parameters are given by the clients
+    pub(crate) fn range(
+        &self, len: usize, position: impl FnOnce(&str) -> Option<usize>,
+    ) -> (usize, usize) {
         let (start, end) = match self {
-            PageQuery::OffsetBased(data) => data.page(entities),
-            PageQuery::PageBased(data) => data.page(entities),
-            PageQuery::CursorBased(data) => data.page(entities),
+            PageQuery::OffsetBased(data) => data.page(len, position),
+            PageQuery::PageBased(data) => data.page(len, position),
+            PageQuery::CursorBased(data) => data.page(len, position),
         };
-
-        &entities[start .. end]
+        let start = start.min(len);
+        (start, end.clamp(start, len))
     }
 }
 
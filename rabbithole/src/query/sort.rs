@@ -1,10 +1,12 @@
 use crate::entity::SingleEntity;
 use crate::model::error;
+use crate::model::resource::AttributeField;
 use crate::RbhResult;
 use std::cmp::Ordering;
 use std::convert::TryFrom;
+use std::fmt;
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct SortQuery(Vec<(String, OrderType)>);
 
 #[derive(Debug, Serialize, Deserialize, Clone, Eq, PartialEq)]
@@ -29,6 +31,9 @@ impl TryFrom<Vec<(String, OrderType)>> for SortQuery {
 impl SortQuery {
     pub fn is_empty(&self) -> bool { self.0.is_empty() }
 
+    /// The sorted fields in their priority
+    pub fn fields(&self) -> &[(String, OrderType)] { &self.0 }
+
     pub fn insert_raw(&mut self, value: &str) -> RbhResult<()> {
         for v in value.split(',').filter(|s| !s.is_empty()).map(ToString::to_string) {
             if v.starts_with('-') {
@@ -49,23 +54,65 @@ impl SortQuery {
     }
 
     pub fn sort<E: SingleEntity>(&self, entities: &mut [E]) {
-        entities.sort_by(|a, b| Self::cmp_recur(a, b, &self.0))
+        let mut indices = self.sorted_indices(entities);
+        // Moves the entities along the cycles of the permutation, where `indices[i]` is the entity
+        // to be moved into `i`
+        for i in 0 .. indices.len() {
+            let mut current = i;
+            while indices[current] != i {
+                let next = indices[current];
+                entities.swap(current, next);
+                indices[current] = current;
+                current = next;
+            }
+            indices[current] = current;
+        }
+    }
+
+    /// The indices of `entities` in the sorted order, where the sorted attributes of each entity
+    /// are extracted once before sorting, instead of once in each comparison
+    pub fn sorted_indices<E: SingleEntity>(&self, entities: &[E]) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0 .. entities.len()).collect();
+        if self.0.is_empty() {
+            return indices;
+        }
+        let keys: Vec<Vec<Option<AttributeField>>> = entities
+            .iter()
+            .map(|e| self.0.iter().map(|(field, _)| e.attribute(field)).collect())
+            .collect();
+        indices.sort_by(|&a, &b| self.cmp_keys(&keys[a], &keys[b]));
+        indices
     }
 
-    fn cmp_recur<E: SingleEntity>(a: &E, b: &E, fields: &[(String, OrderType)]) -> Ordering {
-        if let Some((field, order)) = fields.first() {
-            let result = match order {
-                OrderType::Asc => a.cmp_field(field, b),
-                OrderType::Desc => b.cmp_field(field, a),
+    pub(crate) fn cmp_keys(
+        &self, a: &[Option<AttributeField>], b: &[Option<AttributeField>],
+    ) -> Ordering {
+        for ((_, order), (a, b)) in self.0.iter().zip(a.iter().zip(b)) {
+            let result = match (a, b, order) {
+                (Some(a), Some(b), OrderType::Asc) => a.partial_cmp(b),
+                (Some(a), Some(b), OrderType::Desc) => b.partial_cmp(a),
+                _ => None,
             }
             .unwrap_or(Ordering::Equal);
-            if result == Ordering::Equal {
-                SortQuery::cmp_recur(a, b, &fields[1 ..])
-            } else {
-                result
+            if result != Ordering::Equal {
+                return result;
             }
-        } else {
-            Ordering::Equal
         }
+        Ordering::Equal
+    }
+}
+
+/// The value of the `sort` parameter, like `-name,age`
+impl fmt::Display for SortQuery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fields: Vec<String> = self
+            .0
+            .iter()
+            .map(|(field, order)| match order {
+                OrderType::Asc => field.clone(),
+                OrderType::Desc => format!("-{}", field),
+            })
+            .collect();
+        f.write_str(&fields.join(","))
     }
 }
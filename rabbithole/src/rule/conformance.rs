@@ -0,0 +1,203 @@
+//! How closely the requests and the documents are checked against the JSON:API specification,
+//! set by `conformance` in `JsonApiSettings`:
+//!   - `strict`: the unknown query parameters and the invalid member names in the query are
+//!     rejected with `400`, reporting all of them with `source.parameter`, the JSON:API media
+//!     types are always required, and the compound documents must be fully linked
+//!   - `standard`: the checks of the other settings as they are
+//!   - `lenient`: the header rules are relaxed to `Strictness::Lenient` at most
+
+use crate::model::error::{self, ErrorSource};
+use crate::query::Query;
+use crate::rule::registry::RequestRule;
+use crate::rule::Strictness;
+use crate::RbhResult;
+use percent_encoding::percent_decode_str;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The families of the query parameters defined by the specification, like `page[offset]`, and
+/// `sort[<ty>]` of the included resources
+const FAMILIES: [&str; 4] = ["fields", "filter", "page", "sort"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConformanceLevel {
+    Strict,
+    #[default]
+    Standard,
+    Lenient,
+}
+
+impl ConformanceLevel {
+    /// The strictness of the header rules when `header_rules` is configured
+    pub fn header_rules(self, header_rules: Strictness) -> Strictness {
+        match (self, header_rules) {
+            (ConformanceLevel::Strict, _) => Strictness::Strict,
+            (ConformanceLevel::Lenient, Strictness::Strict) => Strictness::Lenient,
+            (_, header_rules) => header_rules,
+        }
+    }
+
+    /// Checks the query parameters of `query_str`, where `parameters` are the ones specific to the
+    /// implementation, and the member names in the parsed `query`, failing with all of the
+    /// offenders in the error and its `others`
+    pub fn check_query(
+        self, query_str: Option<&str>, query: &Query, parameters: &[String],
+    ) -> RbhResult<()> {
+        if self != ConformanceLevel::Strict {
+            return Ok(());
+        }
+        let errors = check_query_parameters(query_str, parameters);
+        match error::Error::from_errors(errors.into_iter().chain(check_member_names(query))) {
+            Some(err) => Err(err),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks the full linkage of the JSON of a compound document, where every included resource
+    /// is related from the primary data directly or through the other included resources, unless
+    /// the sparse fieldsets of `query` may leave the relationships out
+    pub fn check_document(self, doc: &Value, query: &Query) -> RbhResult<()> {
+        if self != ConformanceLevel::Strict || !query.fields.is_empty() {
+            return Ok(());
+        }
+        match doc.get("included").and_then(Value::as_array) {
+            Some(included) if !included.is_empty() => check_full_linkage(&doc["data"], included),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// If `name` is a valid member name, which has the letters, the digits and the non-ASCII
+/// characters, with `-`, `_` and ` ` allowed in the middle
+pub fn is_member_name(name: &str) -> bool {
+    let globally_allowed = |c: char| c.is_ascii_alphanumeric() || c >= '\u{80}';
+    name.chars().next().is_some_and(globally_allowed)
+        && name.chars().last().is_some_and(globally_allowed)
+        && name.chars().all(|c| globally_allowed(c) || matches!(c, '-' | '_' | ' '))
+}
+
+/// If `name` is a valid name of the implementation-specific query parameters, which is a member
+/// name with at least one character other than `a-z`, as the others are reserved by JSON:API
+pub fn is_implementation_specific(name: &str) -> bool {
+    is_member_name(name) && !name.chars().all(|c| c.is_ascii_lowercase())
+}
+
+/// The request rule rejecting the unknown query parameters like the `strict` conformance level,
+/// for the other levels, where `parameters` are the implementation-specific ones
+pub fn query_parameters_rule(parameters: Vec<String>) -> RequestRule {
+    RequestRule::new("query-parameters", move |req| {
+        let errors = check_query_parameters(req.uri.query(), &parameters);
+        error::Error::from_errors(errors).map_or(Ok(()), Err)
+    })
+}
+
+fn parameter_source(parameter: &str) -> Option<ErrorSource> {
+    Some(ErrorSource { pointer: None, parameter: Some(parameter.to_string()) })
+}
+
+/// The errors of the query parameters which are neither `include`, `sort`, one of `FAMILIES` nor
+/// one of the implementation-specific `parameters`, or their families like `ext[name]`, where
+/// the invalid names are told apart from the unknown ones. A parameter is only reported once
+fn check_query_parameters(query_str: Option<&str>, parameters: &[String]) -> Vec<error::Error> {
+    let mut offenders: Vec<String> = Vec::new();
+    for pair in query_str.unwrap_or_default().split('&').filter(|pair| !pair.is_empty()) {
+        // The pairs are split before decoding, as the values may have the encoded `&`
+        let key = pair.split_once('=').map_or(pair, |(key, _)| key);
+        let key = percent_decode_str(key).decode_utf8_lossy();
+        let key = key.as_ref();
+        let (name, family) = match key.split_once('[') {
+            Some((name, rest)) => (name, rest.ends_with(']')),
+            None => (key, true),
+        };
+        let known = family
+            && (key == "include"
+                || key == "sort"
+                || (FAMILIES.contains(&name) && name != key)
+                || (is_implementation_specific(name) && parameters.iter().any(|p| p == name)));
+        if !known && !offenders.iter().any(|offender| offender == key) {
+            offenders.push(key.to_string());
+        }
+    }
+    offenders
+        .iter()
+        .map(|key| {
+            let name = key.split_once('[').map_or(key.as_str(), |(name, _)| name);
+            if is_implementation_specific(name) {
+                error::Error::UnknownQueryParameter(key, parameter_source(key))
+            } else {
+                error::Error::InvalidQueryParameterName(key, parameter_source(key))
+            }
+        })
+        .collect()
+}
+
+/// The errors of the invalid member names in `include`, `fields` and `sort`
+fn check_member_names(query: &Query) -> Vec<error::Error> {
+    let invalid = |name: &str, parameter: &str| {
+        error::Error::InvalidMemberName(name, parameter, parameter_source(parameter))
+    };
+    let mut errors = Vec::new();
+    for path in query.include.iter().flatten() {
+        for name in path.split('.').filter(|name| !is_member_name(name)) {
+            errors.push(invalid(name, "include"));
+        }
+    }
+    for (ty, fields) in &query.fields {
+        let parameter = format!("fields[{}]", ty);
+        for name in std::iter::once(ty).chain(fields).filter(|name| !is_member_name(name)) {
+            errors.push(invalid(name, &parameter));
+        }
+    }
+    for (name, _) in query.sort.fields().iter().filter(|(name, _)| !is_member_name(name)) {
+        errors.push(invalid(name, "sort"));
+    }
+    for (ty, sort) in &query.included_sort {
+        let parameter = format!("sort[{}]", ty);
+        let names = std::iter::once(ty).chain(sort.fields().iter().map(|(name, _)| name));
+        for name in names.filter(|name| !is_member_name(name)) {
+            errors.push(invalid(name, &parameter));
+        }
+    }
+    errors
+}
+
+/// The type and the id of a resource or a resource identifier
+fn key(resource: &Value) -> (&str, &str) {
+    (resource["type"].as_str().unwrap_or_default(), resource["id"].as_str().unwrap_or_default())
+}
+
+/// The resource identifiers in the relationships of `resource`
+fn related_ids(resource: &Value) -> Vec<&Value> {
+    let relationships = resource.get("relationships").and_then(Value::as_object);
+    relationships
+        .into_iter()
+        .flat_map(|relationships| relationships.values())
+        .flat_map(|relationship| match &relationship["data"] {
+            Value::Array(ids) => ids.iter().collect(),
+            id @ Value::Object(_) => vec![id],
+            _ => vec![],
+        })
+        .collect()
+}
+
+fn check_full_linkage(data: &Value, included: &[Value]) -> RbhResult<()> {
+    let mut unlinked: HashMap<(&str, &str), &Value> =
+        included.iter().map(|resource| (key(resource), resource)).collect();
+    let mut resources: Vec<&Value> = match data {
+        Value::Array(data) => data.iter().collect(),
+        data => vec![data],
+    };
+    while let Some(resource) = resources.pop() {
+        for id in related_ids(resource) {
+            if let Some(inc) = unlinked.remove(&key(id)) {
+                resources.push(inc);
+            }
+        }
+    }
+    match unlinked.keys().next() {
+        Some((ty, id)) => Err(error::Error::IncompleteLinkage(ty, id, None)),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,155 @@
+use crate::encoding::Encoding;
+
+/// A media range in `Content-Type` or `Accept` headers, like
+/// `application/vnd.api+json; profile="a b"; q=0.5`
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaType {
+    /// `<type>/<subtype>` in lower case
+    pub essence: String,
+    /// Media type parameters, with lower case names and unquoted values
+    /// NOTICE:
+    ///   - The quality value `q` and the accept extensions after it are not included
+    pub params: Vec<(String, String)>,
+    /// The quality value `q`, `1.0` if not given
+    pub weight: f32,
+}
+
+impl MediaType {
+    /// Parses a single media range, returning `None` if it is malformed
+    pub fn parse(media_type: &str) -> Option<Self> {
+        let mut parts = split_unquoted(media_type, ';').into_iter();
+        let essence = parts.next()?.trim().to_ascii_lowercase();
+        let mut essence_parts = essence.split('/');
+        match (essence_parts.next(), essence_parts.next(), essence_parts.next()) {
+            (Some(ty), Some(subtype), None) if is_token(ty) && is_token(subtype) => {},
+            _ => return None,
+        }
+
+        let mut params = Vec::new();
+        let mut weight = 1.0;
+        for param in parts {
+            let param = param.trim();
+            if param.is_empty() {
+                continue;
+            }
+            let eq = param.find('=')?;
+            let name = param[.. eq].trim().to_ascii_lowercase();
+            let value = unquote(param[eq + 1 ..].trim())?;
+            if !is_token(&name) {
+                return None;
+            }
+            if name == "q" {
+                weight = value.parse::<f32>().ok().filter(|q| (0.0 ..= 1.0).contains(q))?;
+                // The rest are accept extensions, which are not parameters of the media type
+                break;
+            }
+            params.push((name, value));
+        }
+
+        Some(Self { essence, params, weight })
+    }
+
+    /// Parses a comma separated list of media ranges, skipping the malformed ones
+    pub fn parse_list(media_types: &str) -> Vec<Self> {
+        split_unquoted(media_types, ',').into_iter().filter_map(Self::parse).collect()
+    }
+
+    /// If it's the JSON:API media type, or the one of the other encodings of the documents, like
+    /// HAL, Siren, and CBOR with the `cbor` feature
+    pub fn is_json_api(&self) -> bool { Encoding::of_essence(&self.essence).is_some() }
+
+    /// If it's the plain `application/json`
+    pub fn is_json(&self) -> bool { self.essence == "application/json" }
+
+    /// If a JSON:API response satisfies this media range, ignoring the parameters
+    pub fn accepts_json_api(&self) -> bool {
+        self.weight > 0.0
+            && (self.is_json_api() || self.essence == "*/*" || self.essence == "application/*")
+    }
+
+    /// How specifically the media range matches the media type `essence` without parameters, which
+    /// is `3` for the same essence, `2` for `<type>/*` and `1` for `*/*`, where the media ranges
+    /// with parameters only match the media types with them
+    fn precedence(&self, essence: &str) -> Option<u8> {
+        if !self.params.is_empty() {
+            return None;
+        }
+        let ty = essence.split('/').next().unwrap_or_default();
+        match self.essence.split_once('/') {
+            _ if self.essence == essence => Some(3),
+            Some((range_ty, "*")) if range_ty == ty => Some(2),
+            Some(("*", "*")) => Some(1),
+            _ => None,
+        }
+    }
+
+    /// The quality value of the media type `essence` in the media ranges of an `Accept`, which is
+    /// the one of the most specific range matching it, or `0.0` if none does, so
+    /// `application/vnd.api+json; q=0, */*` excludes JSON:API while accepting everything else
+    pub fn quality(ranges: &[MediaType], essence: &str) -> f32 {
+        let mut matched: Option<(u8, f32)> = None;
+        for range in ranges {
+            if let Some(precedence) = range.precedence(essence) {
+                // The first one of the most specific ranges is taken
+                if matched.is_none_or(|(matched, _)| precedence > matched) {
+                    matched = Some((precedence, range.weight));
+                }
+            }
+        }
+        matched.map_or(0.0, |(_, weight)| weight)
+    }
+
+    pub fn param_names(&self) -> impl Iterator<Item = &str> {
+        self.params.iter().map(|(name, _)| name.as_str())
+    }
+
+    /// The URIs in the space separated `profile` parameters
+    pub fn profiles(&self) -> impl Iterator<Item = &str> {
+        self.params
+            .iter()
+            .filter(|(name, _)| name == "profile")
+            .flat_map(|(_, value)| value.split_whitespace())
+    }
+}
+
+fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.chars().all(|c| c.is_ascii_graphic() && !"()<>@,;:\\\"/[]?={}".contains(c))
+}
+
+fn unquote(value: &str) -> Option<String> {
+    if let Some(quoted) = value.strip_prefix('"') {
+        let quoted = quoted.strip_suffix('"')?;
+        let mut res = String::with_capacity(quoted.len());
+        let mut chars = quoted.chars();
+        while let Some(c) = chars.next() {
+            res.push(if c == '\\' { chars.next()? } else { c });
+        }
+        Some(res)
+    } else if is_token(value) {
+        Some(value.to_string())
+    } else {
+        None
+    }
+}
+
+/// Splits `s` by `sep`, except the ones in quoted strings
+fn split_unquoted(s: &str, sep: char) -> Vec<&str> {
+    let mut res = Vec::new();
+    let mut start = 0;
+    let mut in_quotes = false;
+    let mut escaped = false;
+    for (i, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            _ if c == sep && !in_quotes => {
+                res.push(&s[start .. i]);
+                start = i + c.len_utf8();
+            },
+            _ => {},
+        }
+    }
+    res.push(&s[start ..]);
+    res
+}
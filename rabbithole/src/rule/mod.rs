@@ -1,6 +1,12 @@
 use crate::model::error;
 use crate::model::version::JsonApiVersion;
+use crate::rule::media_type::MediaType;
+use serde::Deserialize;
 
+pub mod conformance;
+pub mod media_type;
+pub mod profile;
+pub mod registry;
 pub mod v1_0;
 pub mod v1_1;
 
@@ -30,3 +36,50 @@ rule_dispatcher! {
     ContentTypeMustBeJsonApi, Option<String>;
     AcceptHeaderShouldBeJsonApi, Option<String>;
 }
+
+/// How the header rules are applied, for the clients which cannot send the JSON:API media type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Strictness {
+    /// The rules of the JSON:API version
+    #[default]
+    Strict,
+    /// Missing headers and `application/json` are allowed as well,
+    /// while the JSON:API media types still follow the rules
+    Lenient,
+    /// No rule is checked
+    Off,
+}
+
+impl RuleDispatcher {
+    /// Checks the `Content-Type` and `Accept` headers of a request with `strictness`
+    pub fn check_headers(
+        jsonapi_version: &JsonApiVersion, strictness: Strictness, content_type: &Option<String>,
+        accept: &Option<String>,
+    ) -> Result<(), error::Error> {
+        match strictness {
+            Strictness::Off => Ok(()),
+            Strictness::Strict => {
+                RuleDispatcher::ContentTypeMustBeJsonApi(jsonapi_version, content_type)?;
+                RuleDispatcher::AcceptHeaderShouldBeJsonApi(jsonapi_version, accept)
+            },
+            Strictness::Lenient => {
+                let plain_content_type = content_type.as_deref().is_none_or(|content_type| {
+                    MediaType::parse(content_type).is_some_and(|media_type| media_type.is_json())
+                });
+                if !plain_content_type {
+                    RuleDispatcher::ContentTypeMustBeJsonApi(jsonapi_version, content_type)?;
+                }
+                let plain_accept = accept.as_deref().is_none_or(|accept| {
+                    MediaType::parse_list(accept)
+                        .iter()
+                        .any(|media_type| media_type.is_json() && media_type.weight > 0.0)
+                });
+                if !plain_accept {
+                    RuleDispatcher::AcceptHeaderShouldBeJsonApi(jsonapi_version, accept)?;
+                }
+                Ok(())
+            },
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! The profiles of JSON:API v1.1, which are the conventions the documents follow beyond the spec,
+//! like the pagination with cursors. The supported ones are advertised in the `profile` parameter
+//! of the `Content-Type` of the responses, and the required ones must be applied by the requests
+//! in the `profile` parameter of their `Content-Type` or `Accept`:
+//! ```ignore
+//! let profiles = ProfileRegistry::default()
+//!     .support(profile::TIMESTAMPS)
+//!     .require(profile::CURSOR_PAGINATION);
+//! // Content-Type: application/vnd.api+json; profile="urn:rabbithole:profile:timestamps https://..."
+//! ```
+
+use crate::model::error;
+use crate::rule::media_type::MediaType;
+use crate::JSON_API_HEADER;
+use serde::Deserialize;
+
+/// The cursor pagination of `page[size]`, `page[after]` and `page[before]`
+pub const CURSOR_PAGINATION: &str = "https://jsonapi.org/profiles/ethanresnick/cursor-pagination/";
+
+/// The `createdAt` and `updatedAt` attributes of the resources, in RFC 3339
+pub const TIMESTAMPS: &str = "urn:rabbithole:profile:timestamps";
+
+/// The profiles supported by an API, where the URIs are kept in the order they're added
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ProfileRegistry {
+    /// The URIs of the profiles applied to all of the documents
+    #[serde(default)]
+    pub supported: Vec<String>,
+    /// The URIs of the supported profiles which the requests must apply
+    #[serde(default)]
+    pub required: Vec<String>,
+}
+
+impl ProfileRegistry {
+    pub fn support(mut self, uri: &str) -> Self {
+        if !self.is_supported(uri) {
+            self.supported.push(uri.to_string());
+        }
+        self
+    }
+
+    /// Supports the profile of `uri`, and rejects the requests not applying it
+    pub fn require(mut self, uri: &str) -> Self {
+        self = self.support(uri);
+        if !self.required.iter().any(|required| required == uri) {
+            self.required.push(uri.to_string());
+        }
+        self
+    }
+
+    pub fn is_supported(&self, uri: &str) -> bool {
+        self.supported.iter().any(|supported| supported == uri)
+    }
+
+    /// The `Content-Type` of the documents, with the supported profiles in the `profile` parameter
+    pub fn content_type(&self) -> String { self.media_type(JSON_API_HEADER) }
+
+    /// The media type of `essence`, like the CBOR one, with the supported profiles
+    pub fn media_type(&self, essence: &str) -> String {
+        if self.supported.is_empty() {
+            essence.to_string()
+        } else {
+            format!(r#"{}; profile="{}""#, essence, self.supported.join(" "))
+        }
+    }
+
+    /// The profiles applied by a request, in its JSON:API `Content-Type` or in any of the JSON:API
+    /// media ranges of its `Accept`, including the ones not supported here
+    pub fn requested(content_type: &Option<String>, accept: &Option<String>) -> Vec<String> {
+        let content_type = content_type.as_deref().and_then(MediaType::parse);
+        let accept = accept.as_deref().map(MediaType::parse_list).unwrap_or_default();
+        let mut requested: Vec<String> = Vec::new();
+        for media_type in content_type.iter().chain(accept.iter().filter(|m| m.weight > 0.0)) {
+            if !media_type.is_json_api() {
+                continue;
+            }
+            for profile in media_type.profiles() {
+                if !requested.iter().any(|requested| requested == profile) {
+                    requested.push(profile.to_string());
+                }
+            }
+        }
+        requested
+    }
+
+    /// Checks that the request applies all of the required profiles
+    pub fn check(
+        &self, content_type: &Option<String>, accept: &Option<String>,
+    ) -> Result<(), error::Error> {
+        if self.required.is_empty() {
+            return Ok(());
+        }
+        let requested = Self::requested(content_type, accept);
+        match self.required.iter().find(|required| !requested.contains(required)) {
+            Some(missing) => Err(error::Error::ProfileRequired(missing, None)),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+//! The custom rules of an API, checked after the built-in rules of `RuleDispatcher`, like:
+//! ```ignore
+//! let rules = RuleRegistry::default()
+//!     .with_request_rule(RequestRule::new("sort-required", |req| match req.query.sort.is_empty() {
+//!         true => Err(error::Error::RuleViolated("sort-required", "no `sort`", None)),
+//!         false => Ok(()),
+//!     }))
+//!     .with_response_rule(
+//!         ResponseRule::new("paginated-collections", |doc, query| {
+//!             match doc["data"].is_array() && query.page.is_none() {
+//!                 true => Err(error::Error::RuleViolated("paginated-collections", "no `page`", None)),
+//!                 false => Ok(()),
+//!             }
+//!         })
+//!         .with_order(-1)
+//!         .with_versions(vec![JsonApiVersion { major: 1, minor: 1 }]),
+//!     );
+//! ```
+//! NOTICE:
+//!   - The rules are evaluated by ascending `order`, then in the order they're added, and the
+//!     first failing rule answers the request
+//!   - A rule is enabled in all of the JSON:API versions unless `versions` is given
+
+use crate::model::version::JsonApiVersion;
+use crate::query::Query;
+use crate::RbhResult;
+use http::Uri;
+use serde_json::Value;
+use std::fmt;
+use std::sync::Arc;
+
+/// What the request rules check
+#[derive(Debug)]
+pub struct RuleRequest<'a> {
+    pub content_type: &'a Option<String>,
+    pub accept: &'a Option<String>,
+    pub uri: &'a Uri,
+    pub query: &'a Query,
+}
+
+pub type RequestCheck = dyn Fn(&RuleRequest) -> RbhResult<()> + Send + Sync;
+/// Checks the JSON of a document with the query it answers
+pub type ResponseCheck = dyn Fn(&Value, &Query) -> RbhResult<()> + Send + Sync;
+
+/// A custom rule, checking the requests or the documents of the responses with `check`
+pub struct CustomRule<F: ?Sized> {
+    pub name: String,
+    /// Where the rule is evaluated, with the lower orders first
+    pub order: i32,
+    /// The JSON:API versions where the rule is enabled, which are all of them if empty
+    pub versions: Vec<JsonApiVersion>,
+    check: Arc<F>,
+}
+
+pub type RequestRule = CustomRule<RequestCheck>;
+pub type ResponseRule = CustomRule<ResponseCheck>;
+
+impl<F: ?Sized> Clone for CustomRule<F> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            order: self.order,
+            versions: self.versions.clone(),
+            check: self.check.clone(),
+        }
+    }
+}
+
+impl<F: ?Sized> fmt::Debug for CustomRule<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CustomRule")
+            .field("name", &self.name)
+            .field("order", &self.order)
+            .field("versions", &self.versions)
+            .finish()
+    }
+}
+
+impl<F: ?Sized> CustomRule<F> {
+    pub fn with_order(mut self, order: i32) -> Self {
+        self.order = order;
+        self
+    }
+
+    /// Enables the rule only in `versions`
+    pub fn with_versions(mut self, versions: Vec<JsonApiVersion>) -> Self {
+        self.versions = versions;
+        self
+    }
+
+    pub fn is_enabled(&self, version: &JsonApiVersion) -> bool {
+        self.versions.is_empty() || self.versions.contains(version)
+    }
+}
+
+impl RequestRule {
+    pub fn new(
+        name: &str, check: impl 'static + Fn(&RuleRequest) -> RbhResult<()> + Send + Sync,
+    ) -> Self {
+        Self { name: name.to_string(), order: 0, versions: vec![], check: Arc::new(check) }
+    }
+}
+
+impl ResponseRule {
+    pub fn new(
+        name: &str, check: impl 'static + Fn(&Value, &Query) -> RbhResult<()> + Send + Sync,
+    ) -> Self {
+        Self { name: name.to_string(), order: 0, versions: vec![], check: Arc::new(check) }
+    }
+}
+
+/// The custom rules of the requests and of the responses, kept in their evaluation order
+#[derive(Debug, Clone, Default)]
+pub struct RuleRegistry {
+    request_rules: Vec<RequestRule>,
+    response_rules: Vec<ResponseRule>,
+}
+
+/// Inserts `rule` after the rules of the same or lower orders
+fn insert_ordered<F: ?Sized>(rules: &mut Vec<CustomRule<F>>, rule: CustomRule<F>) {
+    let idx = rules.iter().position(|r| r.order > rule.order).unwrap_or(rules.len());
+    rules.insert(idx, rule);
+}
+
+impl RuleRegistry {
+    pub fn with_request_rule(mut self, rule: RequestRule) -> Self {
+        insert_ordered(&mut self.request_rules, rule);
+        self
+    }
+
+    pub fn with_response_rule(mut self, rule: ResponseRule) -> Self {
+        insert_ordered(&mut self.response_rules, rule);
+        self
+    }
+
+    /// Removes the rules named `name`
+    pub fn without(mut self, name: &str) -> Self {
+        self.request_rules.retain(|rule| rule.name != name);
+        self.response_rules.retain(|rule| rule.name != name);
+        self
+    }
+
+    pub fn request_rules(&self) -> &[RequestRule] { &self.request_rules }
+
+    pub fn response_rules(&self) -> &[ResponseRule] { &self.response_rules }
+
+    pub fn is_empty(&self) -> bool {
+        self.request_rules.is_empty() && self.response_rules.is_empty()
+    }
+
+    /// Checks `req` with the request rules enabled in `version`
+    pub fn check_request(&self, version: &JsonApiVersion, req: &RuleRequest) -> RbhResult<()> {
+        self.request_rules
+            .iter()
+            .filter(|rule| rule.is_enabled(version))
+            .try_for_each(|rule| (rule.check)(req))
+    }
+
+    /// Checks the JSON of the document answering `query` with the response rules enabled in
+    /// `version`
+    pub fn check_response(
+        &self, version: &JsonApiVersion, doc: &Value, query: &Query,
+    ) -> RbhResult<()> {
+        self.response_rules
+            .iter()
+            .filter(|rule| rule.is_enabled(version))
+            .try_for_each(|rule| (rule.check)(doc, query))
+    }
+}
@@ -1,12 +1,13 @@
 use crate::model::error;
+use crate::rule::media_type::MediaType;
 use crate::rule::Rule;
 use crate::JSON_API_HEADER;
 
 pub(crate) struct ContentTypeMustBeJsonApi;
 impl Rule<Option<String>> for ContentTypeMustBeJsonApi {
     fn check(content_type: &Option<String>) -> Result<(), error::Error> {
-        if let Some(content_type) = content_type {
-            if content_type == JSON_API_HEADER {
+        if let Some(media_type) = content_type.as_deref().and_then(MediaType::parse) {
+            if media_type.is_json_api() && media_type.params.is_empty() {
                 return Ok(());
             }
         }
@@ -18,10 +19,18 @@ impl Rule<Option<String>> for ContentTypeMustBeJsonApi {
     }
 }
 
+/// At least one of the media ranges should accept JSON:API without any parameters, where the
+/// wildcards only accept it if no more specific range excludes it with `q=0`
 pub(crate) struct AcceptHeaderShouldBeJsonApi;
 impl Rule<Option<String>> for AcceptHeaderShouldBeJsonApi {
     fn check(accept_header: &Option<String>) -> Result<(), error::Error> {
-        if accept_header.is_some() && accept_header.as_ref().unwrap() == JSON_API_HEADER {
+        let acceptable = accept_header.as_deref().is_some_and(|accept| {
+            let media_types = MediaType::parse_list(accept);
+            media_types.iter().any(|media_type| {
+                media_type.is_json_api() && media_type.weight > 0.0 && media_type.params.is_empty()
+            }) || MediaType::quality(&media_types, JSON_API_HEADER) > 0.0
+        });
+        if acceptable {
             Ok(())
         } else {
             Err(error::Error::InvalidAccept(
@@ -1,30 +1,21 @@
 use crate::model::error;
+use crate::rule::media_type::MediaType;
 use crate::rule::Rule;
 use crate::JSON_API_HEADER;
-use std::collections::HashMap;
 
-fn extract_params_of_media_type(media_type: &str) -> HashMap<String, String> {
-    let mut params: HashMap<String, String> = Default::default();
-    for param in media_type.split(';').skip(1) {
-        let param: Vec<&str> = param.split('=').map(|s| s.trim()).collect();
-        if param.len() == 2 {
-            params.insert(param[0].into(), param[1].into());
-        }
-    }
-
-    params
-}
-
-fn has_no_param(params: &HashMap<String, String>) -> bool { params.is_empty() }
-
-fn has_only_profile_param(params: &HashMap<String, String>) -> bool {
-    params.len() == 1 && params.contains_key("profile")
+/// The only media type parameter allowed in JSON:API v1.1 here
+fn has_valid_params(media_type: &MediaType) -> bool {
+    media_type.param_names().all(|name| name == "profile")
 }
 
 pub(crate) struct ContentTypeMustBeJsonApi;
 impl Rule<Option<String>> for ContentTypeMustBeJsonApi {
     fn check(content_type: &Option<String>) -> Result<(), error::Error> {
-        if is_valid(&content_type) {
+        let valid = content_type
+            .as_deref()
+            .and_then(MediaType::parse)
+            .is_some_and(|media_type| media_type.is_json_api() && has_valid_params(&media_type));
+        if valid {
             Ok(())
         } else {
             Err(error::Error::InvalidContentType(
@@ -36,10 +27,18 @@ impl Rule<Option<String>> for ContentTypeMustBeJsonApi {
     }
 }
 
+/// At least one of the media ranges should accept JSON:API with the valid parameters, where the
+/// wildcards only accept it if no more specific range excludes it with `q=0`
 pub(crate) struct AcceptHeaderShouldBeJsonApi;
 impl Rule<Option<String>> for AcceptHeaderShouldBeJsonApi {
     fn check(accept_header: &Option<String>) -> Result<(), error::Error> {
-        if is_valid(&accept_header) {
+        let acceptable = accept_header.as_deref().is_some_and(|accept| {
+            let media_types = MediaType::parse_list(accept);
+            media_types.iter().any(|media_type| {
+                media_type.is_json_api() && media_type.weight > 0.0 && has_valid_params(media_type)
+            }) || MediaType::quality(&media_types, JSON_API_HEADER) > 0.0
+        });
+        if acceptable {
             Ok(())
         } else {
             Err(error::Error::InvalidAccept(
@@ -50,15 +49,3 @@ impl Rule<Option<String>> for AcceptHeaderShouldBeJsonApi {
         }
     }
 }
-
-fn is_valid(item: &Option<String>) -> bool {
-    if let Some(item) = item {
-        let params = extract_params_of_media_type(item);
-        if item.starts_with(JSON_API_HEADER)
-            && (has_no_param(&params) || has_only_profile_param(&params))
-        {
-            return true;
-        }
-    }
-    false
-}
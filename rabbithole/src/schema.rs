@@ -0,0 +1,313 @@
+//! The schemas of the resource types, generated by `EntityDecorator` from the fields of the
+//! entities, where the JSON schemas of the attributes are inferred from the names of their Rust
+//! types, like `Option<String>`, and the unknown types are described by the empty schema.
+//!
+//! The schemas of `ResourceSchema` are in the dialect of OpenAPI 3.0, where the nullable values
+//! are marked by `nullable`, and the ones of `json_schema` are in JSON Schema draft 7
+
+use crate::entity::SingleEntity;
+use serde_json::{json, Map, Value};
+
+/// The schema of a resource type
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ResourceSchema {
+    /// The name of the entity, like `Human`
+    pub name: String,
+    pub ty: String,
+    /// The name of the id field with the JSON schema of its type, whose values are the ids of the
+    /// resources
+    pub id: (String, Value),
+    /// The JSON schemas of the attributes, in the order of the fields
+    pub attributes: Vec<(String, Value)>,
+    /// The names of the to-one relationships with the types of the related resources
+    pub to_ones: Vec<(String, String)>,
+    /// The names of the to-many relationships with the types of the related resources
+    pub to_manys: Vec<(String, String)>,
+}
+
+impl ResourceSchema {
+    /// The schema of `ty` without any attributes or relationships, named by `ty`, whose ids are
+    /// the strings in `id`
+    pub fn new(ty: &str) -> Self {
+        Self {
+            name: ty.to_string(),
+            ty: ty.to_string(),
+            id: ("id".into(), json!({ "type": "string" })),
+            ..Default::default()
+        }
+    }
+
+    /// The names of the relationships, with the to-one ones first
+    pub fn relationships(&self) -> impl Iterator<Item = &str> {
+        self.to_ones.iter().chain(&self.to_manys).map(|(name, _)| name.as_str())
+    }
+
+    /// The attributes which are not nullable
+    pub fn required_attributes(&self) -> impl Iterator<Item = &str> {
+        self.attributes
+            .iter()
+            .filter(|(_, schema)| schema.get("nullable") != Some(&Value::Bool(true)))
+            .map(|(name, _)| name.as_str())
+    }
+
+    /// The schema of the resource objects
+    pub fn resource_object(&self) -> Value { self.object(true, true, false) }
+
+    /// The schema of the documents creating a resource, where the id is optional, for the ones
+    /// generated by the servers, and the attributes which are not nullable are required
+    pub fn create_payload(&self) -> Value { payload(self.object(false, true, true)) }
+
+    /// The schema of the documents updating a resource, where only the type and the id are
+    /// required
+    pub fn update_payload(&self) -> Value { payload(self.object(true, false, true)) }
+
+    /// The schema of a resource object, where the unknown attributes and relationships are not
+    /// allowed in the payloads, and their relationships must have `data`
+    fn object(&self, id_required: bool, attributes_required: bool, is_payload: bool) -> Value {
+        let mut relationships = Map::new();
+        for (name, related) in &self.to_ones {
+            let data = json!({ "allOf": [identifier_schema(related)], "nullable": true });
+            relationships.insert(name.clone(), relationship_schema(data, is_payload));
+        }
+        for (name, related) in &self.to_manys {
+            let data = json!({ "type": "array", "items": identifier_schema(related) });
+            relationships.insert(name.clone(), relationship_schema(data, is_payload));
+        }
+        let attributes: Map<String, Value> = self.attributes.iter().cloned().collect();
+        let mut attributes = json!({ "type": "object", "properties": attributes });
+        let required: Vec<&str> = self.required_attributes().collect();
+        if attributes_required && !required.is_empty() {
+            attributes["required"] = json!(required);
+        }
+        let mut relationships = json!({ "type": "object", "properties": relationships });
+        if is_payload {
+            attributes["additionalProperties"] = json!(false);
+            relationships["additionalProperties"] = json!(false);
+        }
+        let mut object = json!({
+            "type": "object",
+            "required": if id_required { json!(["type", "id"]) } else { json!(["type"]) },
+            "properties": {
+                "type": { "type": "string", "enum": [self.ty] },
+                "id": { "type": "string" },
+                "attributes": attributes,
+                "relationships": relationships,
+                "meta": { "type": "object" },
+            },
+        });
+        if !is_payload {
+            object["properties"]["links"] = json!({ "type": "object" });
+        }
+        object
+    }
+}
+
+/// The resource identifier of the resource type `ty`
+fn identifier_schema(ty: &str) -> Value {
+    json!({
+        "type": "object",
+        "required": ["type", "id"],
+        "properties": { "type": { "type": "string", "enum": [ty] }, "id": { "type": "string" } },
+    })
+}
+
+fn relationship_schema(data: Value, is_payload: bool) -> Value {
+    let mut relationship = json!({
+        "type": "object",
+        "properties": { "data": data, "meta": { "type": "object" } },
+    });
+    if is_payload {
+        relationship["required"] = json!(["data"]);
+    } else {
+        relationship["properties"]["links"] = json!({ "type": "object" });
+    }
+    relationship
+}
+
+fn payload(data: Value) -> Value {
+    json!({
+        "type": "object",
+        "required": ["data"],
+        "properties": { "data": data, "meta": { "type": "object" } },
+    })
+}
+
+/// The JSON schemas of the resource objects of an entity and the payloads of the requests
+/// creating and updating them, like the bodies of `POST /people` and `PATCH /people/{id}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonSchema {
+    pub resource: Value,
+    pub create: Value,
+    pub update: Value,
+}
+
+/// The JSON schemas of `E`, in JSON Schema draft 7
+pub fn json_schema<E: SingleEntity>() -> JsonSchema {
+    let schema = E::schema();
+    let draft = |title: &str, schema: Value| {
+        let mut schema = without_nullable(schema);
+        schema["$schema"] = json!("http://json-schema.org/draft-07/schema#");
+        schema["title"] = json!(title);
+        schema
+    };
+    JsonSchema {
+        resource: draft(&format!("The resource of `{}`", schema.ty), schema.resource_object()),
+        create: draft(&format!("Creating a resource of `{}`", schema.ty), schema.create_payload()),
+        update: draft(&format!("Updating a resource of `{}`", schema.ty), schema.update_payload()),
+    }
+}
+
+/// `schema` in JSON Schema, where the nullable schemas of OpenAPI are the ones accepting `null`
+fn without_nullable(schema: Value) -> Value {
+    let mut schema = match schema {
+        Value::Object(schema) => schema,
+        schema => return schema,
+    };
+    for (key, value) in schema.iter_mut() {
+        match (key.as_str(), value) {
+            ("items" | "additionalProperties", value) => *value = without_nullable(value.take()),
+            ("properties", Value::Object(properties)) => {
+                for property in properties.values_mut() {
+                    *property = without_nullable(property.take());
+                }
+            },
+            ("allOf" | "anyOf" | "oneOf", Value::Array(schemas)) => {
+                for item in schemas.iter_mut() {
+                    *item = without_nullable(item.take());
+                }
+            },
+            _ => {},
+        }
+    }
+    if schema.remove("nullable") != Some(Value::Bool(true)) || schema.is_empty() {
+        return Value::Object(schema);
+    }
+    match schema.get("type").cloned() {
+        Some(Value::String(ty)) => {
+            schema.insert("type".into(), json!([ty, "null"]));
+            Value::Object(schema)
+        },
+        _ => json!({ "anyOf": [schema, { "type": "null" }] }),
+    }
+}
+
+/// The schemas of the resource types of an API, in the order of the registration
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Registry {
+    resources: Vec<ResourceSchema>,
+}
+
+impl Registry {
+    /// Adds the schema of `E`
+    pub fn register<E: SingleEntity>(mut self) -> Self {
+        self.add(E::schema());
+        self
+    }
+
+    /// Adds `schema`, replacing the one of the same type if any
+    pub fn add(&mut self, schema: ResourceSchema) {
+        match self.resources.iter_mut().find(|r| r.ty == schema.ty) {
+            Some(resource) => *resource = schema,
+            None => self.resources.push(schema),
+        }
+    }
+
+    pub fn get(&self, ty: &str) -> Option<&ResourceSchema> {
+        self.resources.iter().find(|r| r.ty == ty)
+    }
+
+    pub fn resources(&self) -> &[ResourceSchema] { &self.resources }
+}
+
+/// The JSON schema of the Rust type named `ty`, like `stringify!(Option<Vec<String>>)`, where
+/// `Option<T>` is the nullable schema of `T`
+#[doc(hidden)]
+pub fn type_schema(ty: &str) -> Value {
+    // The spaces are only kept between the words, like the ones of `&'a mut str`
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut normalized = String::new();
+    let mut space = false;
+    for c in ty.chars() {
+        if c.is_whitespace() {
+            space = true;
+            continue;
+        }
+        if space && is_word(c) && normalized.ends_with(is_word) {
+            normalized.push(' ');
+        }
+        normalized.push(c);
+        space = false;
+    }
+    schema_of(&normalized)
+}
+
+fn schema_of(ty: &str) -> Value {
+    let mut ty = ty;
+    if let Some(referred) = ty.strip_prefix('&') {
+        // Without the lifetime and the mutability
+        let referred = match referred.strip_prefix('\'') {
+            Some(lifetime) => lifetime.split_once(' ').map_or("", |(_, ty)| ty),
+            None => referred,
+        };
+        ty = referred.strip_prefix("mut ").unwrap_or(referred);
+    }
+    let (path, args) = match ty.find('<') {
+        Some(start) if ty.ends_with('>') => {
+            (&ty[.. start], split_args(&ty[start + 1 .. ty.len() - 1]))
+        },
+        _ => (ty, vec![]),
+    };
+    let name = path.rsplit("::").next().unwrap_or(path);
+    match (name, args.as_slice()) {
+        ("String" | "str" | "char" | "Cow", _) => json!({ "type": "string" }),
+        ("Uuid", _) => json!({ "type": "string", "format": "uuid" }),
+        ("NaiveDate", _) => json!({ "type": "string", "format": "date" }),
+        ("DateTime" | "NaiveDateTime" | "SystemTime", _) => {
+            json!({ "type": "string", "format": "date-time" })
+        },
+        ("bool", _) => json!({ "type": "boolean" }),
+        ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", _) => {
+            json!({ "type": "integer", "minimum": 0 })
+        },
+        ("i8" | "i16" | "i32" | "i64" | "i128" | "isize", _) => json!({ "type": "integer" }),
+        ("f32" | "f64", _) => json!({ "type": "number" }),
+        ("Option", [inner]) => {
+            let mut schema = schema_of(inner);
+            if let Value::Object(schema) = &mut schema {
+                schema.insert("nullable".into(), Value::Bool(true));
+            }
+            schema
+        },
+        ("Box" | "Rc" | "Arc", [inner]) => schema_of(inner),
+        ("Vec" | "VecDeque" | "LinkedList", [inner]) => {
+            json!({ "type": "array", "items": schema_of(inner) })
+        },
+        ("HashSet" | "BTreeSet", [inner, ..]) => {
+            json!({ "type": "array", "items": schema_of(inner), "uniqueItems": true })
+        },
+        ("HashMap" | "BTreeMap", [_, value, ..]) => {
+            json!({ "type": "object", "additionalProperties": schema_of(value) })
+        },
+        _ => json!({}),
+    }
+}
+
+/// The generic arguments split by the commas out of the brackets
+fn split_args(args: &str) -> Vec<&str> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut parts = vec![];
+    for (i, c) in args.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&args[start .. i]);
+                start = i + 1;
+            },
+            _ => {},
+        }
+    }
+    parts.push(&args[start ..]);
+    parts.into_iter().filter(|part| !part.is_empty()).collect()
+}
@@ -0,0 +1,466 @@
+//! A service answering the fetching operations of the resource types with `http` requests and
+//! responses, for the servers with no dedicated endpoint crate. With the `tower` feature, it's a
+//! `tower::Service` for a plain `hyper` server:
+//! ```ignore
+//! let service = JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })?
+//!     .resource(HumanService)
+//!     .resource(DogService);
+//! let make_service = hyper::service::make_service_fn(move |_| {
+//!     let service = service.clone().map_response(|resp| resp.map(hyper::Body::from));
+//!     async move { Ok::<_, std::convert::Infallible>(service) }
+//! });
+//! hyper::Server::bind(&addr).serve(make_service).await?;
+//! ```
+//! where the operations of a resource type can be wrapped by the tower middlewares too, like
+//! `.layered_resource(HumanService, TimeoutLayer::new(Duration::from_secs(3)))`
+
+use crate::access_log::AccessLog;
+#[cfg(feature = "tower")]
+use crate::cache::CacheKey;
+use crate::cache::DocumentCache;
+use crate::deprecation::Deprecation;
+use crate::entity::SingleEntity;
+use crate::handler::{self, JsonApiSettings, OperationRequest, Route, RouteMatching};
+use crate::model::error;
+use crate::model::meta::MetaNamespace;
+use crate::model::version::JsonApiVersion;
+use crate::openapi::{self, OpenApiInfo, OpenApiSettings};
+use crate::operation::{Fetching, OperationContext};
+use crate::query::limits::QueryLimits;
+use crate::rule::conformance::ConformanceLevel;
+use crate::rule::profile::ProfileRegistry;
+use crate::rule::registry::RuleRegistry;
+use crate::rule::Strictness;
+use crate::schema::Registry;
+use crate::tenant::TenantSource;
+use crate::RbhResult;
+
+use async_trait::async_trait;
+use http::{header, Request, Response, Uri};
+use percent_encoding::percent_decode_str;
+use std::collections::HashMap;
+#[cfg(feature = "tower")]
+use std::convert::Infallible;
+#[cfg(feature = "tower")]
+use std::future::Future;
+#[cfg(feature = "tower")]
+use std::pin::Pin;
+use std::sync::Arc;
+#[cfg(feature = "tower")]
+use std::sync::Mutex;
+#[cfg(feature = "tower")]
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+/// Serves the routes of all of the resource types added by `resource`, which are:
+///   - `GET /<ty>`
+///   - `GET /<ty>/<id>`
+///   - `GET /<ty>/<id>/relationships/<related_field>`
+///   - `GET /<ty>/<id>/<related_field>`
+///
+/// under the path of `uri`, with `HEAD` answering the headers of `GET` without the body and
+/// `OPTIONS` the `Allow` header. The other methods are answered with `405 Method Not Allowed`, and
+/// the unknown paths with `404 Not Found`, both as error documents, while the routes of the aliases
+/// of the types are redirected to the ones of the types with `308 Permanent Redirect`. The paths
+/// written with the tolerances of `route_matching`, like `/Humans/`, are answered as the routes
+/// of the types with the canonical `self` links. The documents are answered with their `ETag` and
+/// `Last-Modified`, or `304 Not Modified` if the client has them. The bodies of the requests are
+/// ignored, and the ones of the responses are the JSON bytes, to be mapped into the body type of
+/// the server
+#[derive(Clone)]
+pub struct JsonApiService {
+    /// The base of the links, which is where the service is mounted, like `http://localhost/api`
+    pub uri: url::Url,
+    pub jsonapi: JsonApiSettings,
+    resources: HashMap<String, Arc<dyn Resource>>,
+    registry: Registry,
+    cache: Option<Arc<dyn DocumentCache>>,
+}
+
+impl JsonApiService {
+    pub fn new(uri: &str, version: JsonApiVersion) -> Result<Self, url::ParseError> {
+        Ok(Self {
+            uri: uri.parse()?,
+            jsonapi: JsonApiSettings::new(version),
+            resources: Default::default(),
+            registry: Default::default(),
+            cache: None,
+        })
+    }
+
+    pub fn with_header_rules(mut self, header_rules: Strictness) -> Self {
+        self.jsonapi.header_rules = header_rules;
+        self
+    }
+
+    /// Advertises and requires the profiles of `profiles` in JSON:API v1.1
+    pub fn with_profiles(mut self, profiles: ProfileRegistry) -> Self {
+        self.jsonapi.profiles = profiles;
+        self
+    }
+
+    /// Strips the internals of the `5xx` errors from the error documents if `redact_errors`
+    pub fn with_redacted_errors(mut self, redact_errors: bool) -> Self {
+        self.jsonapi.redact_errors = redact_errors;
+        self
+    }
+
+    /// Checks the requests and the documents against the specification with `conformance`
+    pub fn with_conformance(mut self, conformance: ConformanceLevel) -> Self {
+        self.jsonapi.conformance = conformance;
+        self
+    }
+
+    /// Allows the implementation-specific query parameters of `parameters` in the `strict`
+    /// conformance level
+    pub fn with_query_parameters(mut self, parameters: Vec<String>) -> Self {
+        self.jsonapi.query_parameters = parameters;
+        self
+    }
+
+    /// Rejects the queries over the limits of `query_limits` with `400`
+    pub fn with_query_limits(mut self, query_limits: QueryLimits) -> Self {
+        self.jsonapi.query_limits = query_limits;
+        self
+    }
+
+    /// Logs the requests with the secrets masked by `access_log`
+    pub fn with_access_log(mut self, access_log: AccessLog) -> Self {
+        self.jsonapi.access_log = Some(access_log);
+        self
+    }
+
+    /// Nests the members of `meta` added by the crate and the application under `meta_namespace`
+    pub fn with_meta_namespace(mut self, meta_namespace: MetaNamespace) -> Self {
+        self.jsonapi.meta_namespace = Some(meta_namespace);
+        self
+    }
+
+    /// Matches the paths with the tolerances of `route_matching`, like `/Humans/` for `/humans`
+    pub fn with_route_matching(mut self, route_matching: RouteMatching) -> Self {
+        self.jsonapi.route_matching = route_matching;
+        self
+    }
+
+    /// Extracts the tenants of the requests from `source`, scoping the links to them
+    pub fn with_tenant(mut self, source: TenantSource) -> Self {
+        self.jsonapi.tenant = Some(source);
+        self
+    }
+
+    /// Checks the requests and the documents with the custom rules of `rules`
+    pub fn with_rules(mut self, rules: RuleRegistry) -> Self {
+        self.jsonapi.rules = rules;
+        self
+    }
+
+    /// Answers the operations with the documents in `cache`, which is invalidated by the
+    /// `ResourceChanged` events if it's subscribed to the `EventBus` of the services
+    pub fn with_cache(mut self, cache: impl Into<Arc<dyn DocumentCache>>) -> Self {
+        self.cache = Some(cache.into());
+        self
+    }
+
+    /// Adds the resource type of `service`, replacing the one of the same type if any
+    pub fn resource<T>(mut self, service: impl Into<Arc<T>>) -> Self
+    where
+        T: 'static + Fetching,
+    {
+        let resource: Arc<dyn Resource> = Arc::new(FetchingResource {
+            service: service.into(),
+            deprecation: T::Item::deprecation(),
+        });
+        self.resources.insert(T::Item::ty(), resource);
+        self.add_type::<T::Item>();
+        self
+    }
+
+    /// Adds the resource type of `service` like `resource`, with its operations wrapped by `layer`,
+    /// like the timeout, retry or concurrency limit middlewares of `tower`, where the errors of
+    /// the middlewares are answered as `503 Service Unavailable`
+    #[cfg(feature = "tower")]
+    pub fn layered_resource<T, L>(mut self, service: impl Into<Arc<T>>, layer: L) -> Self
+    where
+        T: 'static + Fetching,
+        L: tower_layer::Layer<FetchingService<T>>,
+        L::Service: 'static
+            + Send
+            + Clone
+            + tower_service::Service<OperationCall, Response = serde_json::Value>,
+        <L::Service as tower_service::Service<OperationCall>>::Error: Into<BoxError>,
+        <L::Service as tower_service::Service<OperationCall>>::Future: Send,
+    {
+        let service = layer.layer(FetchingService(service.into()));
+        let resource: Arc<dyn Resource> = Arc::new(LayeredResource {
+            ty: T::Item::ty(),
+            deprecation: T::Item::deprecation(),
+            service: Mutex::new(service),
+        });
+        self.resources.insert(T::Item::ty(), resource);
+        self.add_type::<T::Item>();
+        self
+    }
+
+    /// Adds the schema and the aliases of the resource type `E`
+    fn add_type<E: SingleEntity>(&mut self) {
+        self.registry.add(E::schema());
+        for alias in E::aliases() {
+            self.jsonapi.type_aliases.insert(alias, E::ty());
+        }
+    }
+
+    /// The schemas of the resource types
+    pub fn registry(&self) -> &Registry { &self.registry }
+
+    /// The OpenAPI 3 document of all of the resource types
+    pub fn openapi(&self, info: &OpenApiInfo) -> openapi::Spec {
+        openapi::generate(&self.registry, &OpenApiSettings::new(info.clone(), self.uri.as_str()))
+    }
+
+    /// Answers `req`, where the errors are rendered as error documents, and the request is logged
+    /// if `access_log` is set
+    pub async fn handle<B>(&self, req: Request<B>) -> Response<Vec<u8>> {
+        let (parts, _) = req.into_parts();
+        let started = Instant::now();
+        let request = self
+            .jsonapi
+            .access_log
+            .as_ref()
+            .map(|_| (parts.method.clone(), parts.uri.clone(), parts.headers.clone()));
+        let resp = self.respond(parts).await;
+        if let Some((method, uri, headers)) = request {
+            let headers = headers
+                .iter()
+                .filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?)));
+            let status = resp.status().as_u16();
+            self.jsonapi.log_access(method.as_str(), &uri, status, started.elapsed(), headers);
+        }
+        resp
+    }
+
+    async fn respond(&self, parts: http::request::Parts) -> Response<Vec<u8>> {
+        // The routes of the `path` tenants are under the base of their links
+        let (tenant, base) = match self.jsonapi.tenant_of(&self.uri, &parts.uri, &parts.headers) {
+            Ok(tenant) => tenant,
+            Err(err) => return handler::error_response(err),
+        };
+        let matching = self.jsonapi.route_matching;
+        let prefix = base.path().trim_end_matches('/');
+        let path = matching.trim_path(parts.uri.path());
+        let segments: Vec<String> = match path.strip_prefix(prefix) {
+            Some(rest) if rest.starts_with('/') => rest[1 ..]
+                .split('/')
+                .map(|segment| percent_decode_str(segment).decode_utf8_lossy().to_string())
+                .collect(),
+            _ => vec![],
+        };
+        let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+        let (ty, resource, route) = match segments.split_first() {
+            Some((segment, rest)) => {
+                let types = self.resources.keys().map(String::as_str);
+                match (matching.find_type(segment, types), Route::from_segments(rest)) {
+                    (Some(ty), Some(route)) => (ty, &self.resources[ty], route),
+                    (None, Some(_)) => {
+                        let aliases = self.jsonapi.type_aliases.keys().map(String::as_str);
+                        match matching.find_type(segment, aliases) {
+                            Some(alias) => {
+                                let ty = &self.jsonapi.type_aliases[alias];
+                                let location =
+                                    handler::alias_location(prefix, path, parts.uri.query(), ty);
+                                return handler::redirect_response(&location);
+                            },
+                            None => {
+                                return handler::error_response(error::Error::RouteNotFound(None))
+                            },
+                        }
+                    },
+                    _ => return handler::error_response(error::Error::RouteNotFound(None)),
+                }
+            },
+            None => return handler::error_response(error::Error::RouteNotFound(None)),
+        };
+        if !handler::is_fetching(&parts.method) {
+            return handler::other_method_response(&parts.method);
+        }
+
+        let uri: Uri = parts
+            .uri
+            .path_and_query()
+            .map(|p| p.as_str())
+            .unwrap_or_else(|| parts.uri.path())
+            .parse()
+            .unwrap_or_default();
+        let uri = matching.canonical_uri(prefix, &uri, ty);
+        let query = match handler::check_request(
+            &self.jsonapi,
+            &handler::header_value(&parts.headers, header::CONTENT_TYPE),
+            &handler::header_value(&parts.headers, header::ACCEPT),
+            &uri,
+        ) {
+            Ok(query) => query,
+            Err(err) => return handler::error_response(err),
+        };
+        let req = Arc::new(OperationRequest {
+            context: OperationContext { headers: parts.headers, tenant, ..Default::default() },
+            query,
+            base: base.as_str().trim_end_matches('/').to_string(),
+            request_path: uri.into(),
+        });
+        let result =
+            resource.dispatch(self.cache.as_deref(), &route, &req).await.and_then(|mut doc| {
+                handler::check_document(&self.jsonapi, &doc, &req.query)?;
+                req.query.sort_included(&mut doc);
+                let last_modified = handler::last_modified_of(&doc);
+                Ok((self.jsonapi.namespaced(doc), last_modified))
+            });
+        let headers = &req.context.headers;
+        let mut resp = match result {
+            Ok((doc, last_modified)) => {
+                handler::conditional_response(&self.jsonapi, headers, &doc, None, last_modified)
+                    .unwrap_or_else(handler::error_response)
+            },
+            Err(err) => handler::error_response(self.jsonapi.redacted(err)),
+        };
+        handler::apply_deprecation(resource.deprecation(), resp.headers_mut());
+        self.jsonapi.apply_cache_control(ty, &mut resp);
+        handler::strip_head_body(&parts.method, &mut resp);
+        resp
+    }
+}
+
+#[cfg(feature = "tower")]
+impl<B> tower_service::Service<Request<B>> for JsonApiService
+where
+    B: 'static + Send,
+{
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = Response<Vec<u8>>;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move { Ok(service.handle(req).await) })
+    }
+}
+
+/// The operations of a resource type, without the type of its service
+#[async_trait]
+trait Resource: Send + Sync {
+    fn deprecation(&self) -> Option<&Deprecation>;
+
+    async fn dispatch(
+        &self, cache: Option<&dyn DocumentCache>, route: &Route, req: &Arc<OperationRequest>,
+    ) -> RbhResult<serde_json::Value>;
+}
+
+struct FetchingResource<T> {
+    service: Arc<T>,
+    deprecation: Option<Deprecation>,
+}
+
+#[async_trait]
+impl<T> Resource for FetchingResource<T>
+where
+    T: 'static + Fetching,
+{
+    fn deprecation(&self) -> Option<&Deprecation> { self.deprecation.as_ref() }
+
+    async fn dispatch(
+        &self, cache: Option<&dyn DocumentCache>, route: &Route, req: &Arc<OperationRequest>,
+    ) -> RbhResult<serde_json::Value> {
+        match cache {
+            Some(cache) => handler::dispatch_cached(cache, &*self.service, route, req).await,
+            None => handler::dispatch(&*self.service, route, req).await,
+        }
+    }
+}
+
+/// The errors of the tower middlewares
+#[cfg(feature = "tower")]
+pub type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// An operation on a resource type, as the request of the tower services of the operations
+#[cfg(feature = "tower")]
+#[derive(Debug, Clone)]
+pub struct OperationCall {
+    pub route: Route,
+    pub req: Arc<OperationRequest>,
+}
+
+/// The tower service running the operations of `T`, answering the JSON of their documents
+#[cfg(feature = "tower")]
+pub struct FetchingService<T>(pub Arc<T>);
+
+#[cfg(feature = "tower")]
+impl<T> Clone for FetchingService<T> {
+    fn clone(&self) -> Self { Self(self.0.clone()) }
+}
+
+#[cfg(feature = "tower")]
+impl<T> tower_service::Service<OperationCall> for FetchingService<T>
+where
+    T: 'static + Fetching,
+{
+    type Error = error::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+    type Response = serde_json::Value;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, call: OperationCall) -> Self::Future {
+        let service = self.0.clone();
+        Box::pin(async move { handler::dispatch(&*service, &call.route, &call.req).await })
+    }
+}
+
+/// The operations of a resource type wrapped by the tower middlewares, where the service is cloned
+/// for each of the calls, like the other tower clients
+#[cfg(feature = "tower")]
+struct LayeredResource<S> {
+    ty: String,
+    deprecation: Option<Deprecation>,
+    service: Mutex<S>,
+}
+
+#[cfg(feature = "tower")]
+#[async_trait]
+impl<S> Resource for LayeredResource<S>
+where
+    S: 'static + Send + Clone + tower_service::Service<OperationCall, Response = serde_json::Value>,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+{
+    fn deprecation(&self) -> Option<&Deprecation> { self.deprecation.as_ref() }
+
+    async fn dispatch(
+        &self, cache: Option<&dyn DocumentCache>, route: &Route, req: &Arc<OperationRequest>,
+    ) -> RbhResult<serde_json::Value> {
+        let key = CacheKey::new(&self.ty, route, req);
+        handler::cached(cache, key, async {
+            let mut service = match self.service.lock() {
+                Ok(service) => service.clone(),
+                Err(_) => return Err(error::Error::InternalServerError(None)),
+            };
+            std::future::poll_fn(|cx| service.poll_ready(cx)).await.map_err(middleware_error)?;
+            let call = OperationCall { route: route.clone(), req: req.clone() };
+            service.call(call).await.map_err(middleware_error)
+        })
+        .await
+    }
+}
+
+/// The error of a failed operation, or the `ServiceUnavailable` of the error of a middleware, like
+/// the elapsed timeout
+#[cfg(feature = "tower")]
+fn middleware_error(err: impl Into<BoxError>) -> error::Error {
+    match err.into().downcast::<error::Error>() {
+        Ok(err) => *err,
+        Err(err) => error::Error::ServiceUnavailable(&err.to_string(), None),
+    }
+}
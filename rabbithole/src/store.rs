@@ -0,0 +1,115 @@
+use crate::entity::{Entity, SingleEntity};
+use crate::model::document::Document;
+use crate::model::error;
+use crate::model::link::{Link, RawUri};
+use crate::model::relationship::Relationship;
+use crate::model::resource::IdentifierData;
+use crate::operation::{FetchPlan, Fetching};
+use crate::query::page::PageQuery;
+use crate::query::Query;
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+
+/// A simple read-only store, which gets a full `Fetching` implementation for free
+/// NOTICE:
+///   - `filter` is applied in `fetch_collection`, `sort` and `page` are applied in the default
+///     `Fetching::vec_to_document`
+///   - All of the query actions are done in memory, so it's only suitable for small collections
+#[async_trait]
+pub trait CollectionStore: Send + Sync {
+    type Item: SingleEntity + Send + Sync;
+
+    /// All of the items in the store
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error>;
+    /// The item with the `id`
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error>;
+}
+
+#[async_trait]
+impl<S: CollectionStore> Fetching for S {
+    type Item = S::Item;
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        let items = self.all().await?;
+        if let Some(filter) = &plan.query.filter {
+            filter.filter(items)
+        } else {
+            Ok(items)
+        }
+    }
+
+    async fn fetch_single(
+        &self, id: &str, _plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        self.get(id).await
+    }
+
+    async fn fetch_relationship(
+        &self, id: &str, related_field: &str, uri: &str, _query: &Query, _request_path: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        relationship_of(self.get(id).await?, related_field, uri)
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        related_of(self.get(id).await?, related_field, uri, query, request_path)
+    }
+}
+
+/// The relationship `related_field` of the parent `item`, for the services which load the
+/// relationships along with their items
+pub fn relationship_of<E: SingleEntity>(
+    item: Option<E>, related_field: &str, uri: &str,
+) -> Result<Relationship, error::Error> {
+    let item = item.ok_or_else(|| error::Error::ParentResourceNotExist(related_field, None))?;
+    item.relationships(uri)
+        .remove(related_field)
+        .ok_or_else(|| error::Error::FieldNotExist(related_field, None))
+}
+
+/// The document of the related resources `related_field` of the parent `item`, for the services
+/// which load the relationships along with their items
+pub fn related_of<E: SingleEntity>(
+    item: Option<E>, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+) -> Result<serde_json::Value, error::Error> {
+    let item = item.ok_or_else(|| error::Error::ParentResourceNotExist(related_field, None))?;
+    let relationship = item
+        .relationships(uri)
+        .remove(related_field)
+        .ok_or_else(|| error::Error::FieldNotExist(related_field, None))?;
+    let include_query = Some(HashSet::from_iter(vec![related_field.to_string()]));
+    let mut included = item.included(uri, &include_query, &query.fields)?;
+    let links = Some(HashMap::from_iter(vec![Link::slf(uri, request_path.clone())]));
+
+    let doc = match relationship.data {
+        IdentifierData::Single(Some(id)) => match included.remove(&id) {
+            Some(resource) => Document::single_resource(resource, Default::default(), links),
+            None => Document::null(links),
+        },
+        IdentifierData::Single(None) => Document::null(links),
+        IdentifierData::Multiple(ids) => Document::multiple_resources(
+            ids.iter().filter_map(|id| included.remove(id)).collect(),
+            Default::default(),
+            links,
+        ),
+    };
+    serde_json::to_value(doc).map_err(|err| error::Error::InvalidJson(&err, None))
+}
+
+/// The document of the `items` which are already sorted and paged by a storage, for the services
+/// running the queries in their storages, where only the cursor based pagination is still
+/// applied in memory
+pub fn storage_document<E: SingleEntity>(
+    items: &[E], uri: &str, query: &Query, request_path: &RawUri,
+) -> Result<Document, error::Error> {
+    if let Some(PageQuery::CursorBased(_)) = &query.page {
+        return items.to_document_automatically(uri, query, request_path);
+    }
+    let query =
+        Query { include: query.include.clone(), fields: query.fields.clone(), ..Default::default() };
+    items.to_document_automatically(uri, &query, request_path)
+}
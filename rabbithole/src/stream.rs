@@ -0,0 +1,186 @@
+//! The documents of the collections written incrementally, where each resource of the primary
+//! data is written as soon as it's produced, instead of building the whole `Document` first:
+//! ```ignore
+//! let mut writer = DocumentWriter::new(std::io::stdout())?;
+//! for human in store.iter() {
+//!     writer.entity(human, uri, &query)?;
+//! }
+//! writer.finish(Some(&links), None)?;
+//! ```
+//! where the included resources are collected while the primary data is written, and written
+//! afterwards, in the order of their types and ids like the ones of `Document`
+//!
+//! With the feature `async-write`, `AsyncDocumentWriter` writes the same documents into a
+//! `futures::io::AsyncWrite`
+
+use crate::entity::{ResourceView, SingleEntity};
+use crate::model::document::Included;
+use crate::model::link::Links;
+use crate::model::Meta;
+use crate::query::Query;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// The bytes of the document, shared by the sync and the async writers
+#[derive(Default)]
+struct Encoder {
+    /// Whether any resource of the primary data is written
+    has_data: bool,
+    included: Included,
+}
+
+impl Encoder {
+    fn start(buf: &mut Vec<u8>) { buf.extend_from_slice(br#"{"data":["#); }
+
+    fn resource<T: Serialize + ?Sized>(
+        &mut self, buf: &mut Vec<u8>, resource: &T,
+    ) -> io::Result<()> {
+        if self.has_data {
+            buf.push(b',');
+        }
+        self.has_data = true;
+        serde_json::to_writer(buf, resource)?;
+        Ok(())
+    }
+
+    /// Collects the included resources of `entity`, and writes its resource
+    fn entity<E: SingleEntity>(
+        &mut self, buf: &mut Vec<u8>, entity: &E, uri: &str, query: &Query,
+    ) -> io::Result<()> {
+        let included = entity
+            .included(uri, &query.include, &query.fields)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+        self.included.extend(included);
+        self.resource(buf, &ResourceView::new(entity, uri, &query.fields))
+    }
+
+    fn finish(
+        self, buf: &mut Vec<u8>, links: Option<&Links>, meta: Option<&Meta>,
+    ) -> io::Result<()> {
+        buf.push(b']');
+        if !self.included.is_empty() {
+            let mut included: Vec<_> = self.included.iter().collect();
+            included.sort_by(|(a, _), (b, _)| (&a.ty, &a.id).cmp(&(&b.ty, &b.id)));
+            buf.extend_from_slice(br#","included":"#);
+            serde_json::to_writer(
+                &mut *buf,
+                &included.into_iter().map(|(_, r)| r).collect::<Vec<_>>(),
+            )?;
+        }
+        if let Some(links) = links {
+            buf.extend_from_slice(br#","links":"#);
+            serde_json::to_writer(&mut *buf, links)?;
+        }
+        if let Some(meta) = meta {
+            buf.extend_from_slice(br#","meta":"#);
+            serde_json::to_writer(&mut *buf, meta)?;
+        }
+        buf.push(b'}');
+        Ok(())
+    }
+}
+
+/// Writes the document of a collection into `W` resource by resource
+/// NOTICE:
+///   - The document is not complete until `finish`, so a writer dropped before that leaves the
+///     JSON unfinished
+///   - The errors of the included resources, like `RelationshipPathNotSupported`, are the
+///     `InvalidInput` I/O errors wrapping the `model::error::Error`
+pub struct DocumentWriter<W: Write> {
+    writer: W,
+    encoder: Encoder,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> DocumentWriter<W> {
+    /// Starts the document, writing the beginning of the primary data
+    pub fn new(mut writer: W) -> io::Result<Self> {
+        let mut buf = vec![];
+        Encoder::start(&mut buf);
+        writer.write_all(&buf)?;
+        buf.clear();
+        Ok(Self { writer, encoder: Default::default(), buf })
+    }
+
+    /// Writes a resource of the primary data, like a `Resource` or a `ResourceView`
+    pub fn resource<T: Serialize + ?Sized>(&mut self, resource: &T) -> io::Result<()> {
+        self.encoder.resource(&mut self.buf, resource)?;
+        self.flush_buf()
+    }
+
+    /// Adds the resources to the included ones, which are written by `finish`
+    pub fn include(&mut self, included: Included) { self.encoder.included.extend(included); }
+
+    /// Writes the resource of `entity` with the sparse fieldsets of `query`, and adds its
+    /// included resources of `query`
+    pub fn entity<E: SingleEntity>(
+        &mut self, entity: &E, uri: &str, query: &Query,
+    ) -> io::Result<()> {
+        self.encoder.entity(&mut self.buf, entity, uri, query)?;
+        self.flush_buf()
+    }
+
+    /// Ends the document with the included resources, `links` and `meta`, answering the writer
+    pub fn finish(mut self, links: Option<&Links>, meta: Option<&Meta>) -> io::Result<W> {
+        self.encoder.finish(&mut self.buf, links, meta)?;
+        self.writer.write_all(&self.buf)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+
+    fn flush_buf(&mut self) -> io::Result<()> {
+        self.writer.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+/// Writes the document of a collection into an `AsyncWrite` resource by resource, like
+/// `DocumentWriter`
+#[cfg(feature = "async-write")]
+pub struct AsyncDocumentWriter<W: futures::io::AsyncWrite + Unpin> {
+    writer: W,
+    encoder: Encoder,
+    buf: Vec<u8>,
+}
+
+#[cfg(feature = "async-write")]
+impl<W: futures::io::AsyncWrite + Unpin> AsyncDocumentWriter<W> {
+    pub async fn new(mut writer: W) -> io::Result<Self> {
+        use futures::io::AsyncWriteExt;
+        let mut buf = vec![];
+        Encoder::start(&mut buf);
+        writer.write_all(&buf).await?;
+        buf.clear();
+        Ok(Self { writer, encoder: Default::default(), buf })
+    }
+
+    pub async fn resource<T: Serialize + ?Sized>(&mut self, resource: &T) -> io::Result<()> {
+        self.encoder.resource(&mut self.buf, resource)?;
+        self.flush_buf().await
+    }
+
+    pub fn include(&mut self, included: Included) { self.encoder.included.extend(included); }
+
+    pub async fn entity<E: SingleEntity>(
+        &mut self, entity: &E, uri: &str, query: &Query,
+    ) -> io::Result<()> {
+        self.encoder.entity(&mut self.buf, entity, uri, query)?;
+        self.flush_buf().await
+    }
+
+    pub async fn finish(mut self, links: Option<&Links>, meta: Option<&Meta>) -> io::Result<W> {
+        use futures::io::AsyncWriteExt;
+        self.encoder.finish(&mut self.buf, links, meta)?;
+        self.writer.write_all(&self.buf).await?;
+        self.writer.flush().await?;
+        Ok(self.writer)
+    }
+
+    async fn flush_buf(&mut self) -> io::Result<()> {
+        use futures::io::AsyncWriteExt;
+        self.writer.write_all(&self.buf).await?;
+        self.buf.clear();
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! The tenants of the multi-tenant APIs, set by `tenant` in `JsonApiSettings` like:
+//! ```toml
+//! [jsonapi.tenant]
+//! header = "X-Tenant-Id"
+//! ```
+//! or `tenant = "subdomain"` and `tenant = "path"`. The endpoints put the tenant of each request
+//! into `OperationContext::tenant` and `FetchPlan::tenant`, rejecting the requests without one
+//! with `400 Bad Request`, and scope the links of the documents to it, like
+//! `http://acme.example.com/api/people` or `http://example.com/api/acme/people`. The services
+//! partition their data by the tenants of the entities with `TenantScoped`:
+//! ```ignore
+//! async fn can(&self, ctx: &OperationContext, _: &Action, item: &Self::Item) -> Decision {
+//!     tenant::decide(ctx, item)
+//! }
+//! ```
+//! NOTICE:
+//!   - The tenants are made of the ASCII alphanumerics, `-` and `_`, so they are safe in the
+//!     hosts, the paths and the cache keys
+//!   - The `path` tenants are only routed by `JsonApiService`, as the other endpoints match the
+//!     paths with the routers of their frameworks
+
+use crate::model::error;
+use crate::operation::{Decision, OperationContext};
+use crate::RbhResult;
+
+use http::{header, HeaderMap, Uri};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TenantSource {
+    /// The subdomain of the host of the base URI, like `acme` of `acme.example.com` for
+    /// `http://example.com/api`
+    Subdomain,
+    /// The value of the header, like `X-Tenant-Id`
+    Header(String),
+    /// The first path segment after the base path, like `acme` of `/api/acme/people`
+    Path,
+}
+
+impl TenantSource {
+    /// The tenant of a request to `uri` with `headers`, for the API based at `base`
+    pub fn extract(&self, base: &url::Url, uri: &Uri, headers: &HeaderMap) -> RbhResult<String> {
+        let tenant = match self {
+            TenantSource::Subdomain => {
+                let host = uri
+                    .host()
+                    .or_else(|| headers.get(header::HOST).and_then(|host| host.to_str().ok()));
+                let host = host.and_then(|host| host.split(':').next());
+                host.zip(base.host_str())
+                    .and_then(|(host, base)| host.strip_suffix(base)?.strip_suffix('.'))
+            },
+            TenantSource::Header(name) => {
+                headers.get(name.as_str()).and_then(|value| value.to_str().ok())
+            },
+            TenantSource::Path => uri
+                .path()
+                .strip_prefix(base.path().trim_end_matches('/'))
+                .and_then(|rest| rest.strip_prefix('/'))
+                .and_then(|rest| rest.split('/').next()),
+        };
+        match tenant.map(str::trim) {
+            Some(tenant) if is_valid(tenant) => Ok(tenant.to_string()),
+            _ => Err(error::Error::InvalidTenant(&self.to_string(), None)),
+        }
+    }
+
+    /// The base of the links of the requests of `tenant`, like `http://acme.example.com/api` or
+    /// `http://example.com/api/acme`, where the host of the tenant is kept as it is
+    pub fn base(&self, base: &url::Url, tenant: &str) -> url::Url {
+        let mut base = base.clone();
+        match self {
+            TenantSource::Subdomain => {
+                let host = base.host_str().unwrap_or_default();
+                if !host.starts_with(&format!("{}.", tenant)) {
+                    let host = format!("{}.{}", tenant, host);
+                    let _ = base.set_host(Some(&host));
+                }
+            },
+            TenantSource::Header(_) => {},
+            TenantSource::Path => {
+                base.set_path(&format!("{}/{}", base.path().trim_end_matches('/'), tenant))
+            },
+        }
+        base
+    }
+}
+
+impl std::fmt::Display for TenantSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TenantSource::Subdomain => write!(f, "subdomain"),
+            TenantSource::Header(name) => write!(f, "header `{}`", name),
+            TenantSource::Path => write!(f, "path"),
+        }
+    }
+}
+
+fn is_valid(tenant: &str) -> bool {
+    !tenant.is_empty() && tenant.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// An entity belonging to a tenant
+pub trait TenantScoped {
+    fn tenant(&self) -> &str;
+}
+
+/// Hides the items of the other tenants from the request of `ctx`, as if they don't exist, to be
+/// returned by `Fetching::can`
+pub fn decide<E: TenantScoped>(ctx: &OperationContext, item: &E) -> Decision {
+    match &ctx.tenant {
+        Some(tenant) if tenant != item.tenant() => Decision::Hide,
+        _ => Decision::Allow,
+    }
+}
+
+/// The items of `tenant`, like the one of `FetchPlan::tenant`, for the services loading the data
+/// of all of the tenants at once, where all of the items are kept without a tenant
+pub fn partition<E: TenantScoped>(items: Vec<E>, tenant: Option<&str>) -> Vec<E> {
+    match tenant {
+        Some(tenant) => items.into_iter().filter(|item| item.tenant() == tenant).collect(),
+        None => items,
+    }
+}
@@ -0,0 +1,78 @@
+//! The factories of the entities in the tests, like:
+//! ```ignore
+//! let dogs = Factory::new(|n| Dog { id: format!("dog-{}", n), name: format!("Dog {}", n) });
+//! let people = Factory::new(move |n| Human {
+//!     id: format!("human-{}", n),
+//!     name: format!("Human {}", n),
+//!     dogs: dogs.build_list(2),
+//! });
+//! let alice = people.build_with(|human| human.name = "Alice".into());
+//! let body = serde_json::to_vec(&payload(&alice))?;
+//! ```
+//! where `n` is the sequence number of the factory, starting from 1, and the related entities are
+//! built by the factories of their types
+
+use crate::entity::SingleEntity;
+use crate::model::document::Document;
+use crate::model::link::Links;
+use crate::model::relationship::RelationshipLinks;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Builds the entities of `E` with the sequence numbers, which are shared by the clones of the
+/// factory
+pub struct Factory<E> {
+    build: Arc<dyn Fn(usize) -> E + Send + Sync>,
+    sequence: Arc<AtomicUsize>,
+}
+
+impl<E> Clone for Factory<E> {
+    fn clone(&self) -> Self { Self { build: self.build.clone(), sequence: self.sequence.clone() } }
+}
+
+impl<E: SingleEntity> Factory<E> {
+    /// The factory building the `n`-th entity by `build(n)`
+    pub fn new(build: impl 'static + Fn(usize) -> E + Send + Sync) -> Self {
+        Self { build: Arc::new(build), sequence: Default::default() }
+    }
+
+    /// The next sequence number
+    pub fn next_sequence(&self) -> usize { self.sequence.fetch_add(1, Ordering::SeqCst) + 1 }
+
+    /// Restarts the sequence numbers from 1
+    pub fn reset(&self) { self.sequence.store(0, Ordering::SeqCst); }
+
+    pub fn build(&self) -> E { (self.build)(self.next_sequence()) }
+
+    /// Builds the next entity, with the fields overridden by `overrides`
+    pub fn build_with(&self, overrides: impl FnOnce(&mut E)) -> E {
+        let mut entity = self.build();
+        overrides(&mut entity);
+        entity
+    }
+
+    pub fn build_list(&self, count: usize) -> Vec<E> {
+        (0 .. count).map(|_| self.build()).collect()
+    }
+
+    /// Builds the next entity with its payload, like `payload`
+    pub fn build_payload(&self) -> (E, Document) {
+        let entity = self.build();
+        let payload = payload(&entity);
+        (entity, payload)
+    }
+}
+
+/// The document of `entity` in the bodies of the requests creating or updating it, where the
+/// resource and its relationships have no links, and the related resources are not included
+pub fn payload<E: SingleEntity>(entity: &E) -> Document {
+    let mut resource = match entity.to_resource("", &Default::default()) {
+        Some(resource) => resource,
+        None => return Document::null(None),
+    };
+    resource.links = Links::default();
+    for relationship in resource.relationships.values_mut() {
+        relationship.links = RelationshipLinks::default();
+    }
+    Document::single_resource(resource, Default::default(), None)
+}
@@ -0,0 +1,6 @@
+//! The utilities of the tests of the services and the endpoints
+
+pub mod factory;
+pub mod snapshot;
+#[cfg(feature = "testing")]
+pub mod strategy;
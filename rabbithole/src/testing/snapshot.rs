@@ -0,0 +1,69 @@
+//! The snapshot tests of the documents, whose formatted canonical JSON is compared with the files:
+//! ```ignore
+//! let doc = humans.to_document_automatically(uri, &query, &request_path)?;
+//! assert_snapshot(&doc, "tests/snapshots/humans.json");
+//! ```
+//! where the missing snapshots are written by the first run, and all of them are rewritten when
+//! `UPDATE_SNAPSHOTS` is set
+
+use crate::hash;
+
+use serde::Serialize;
+use std::path::Path;
+
+/// The environment variable rewriting the snapshots instead of comparing with them
+pub const UPDATE_SNAPSHOTS: &str = "UPDATE_SNAPSHOTS";
+
+/// The pretty-printed `hash::canonical` JSON of `doc`, like a `Document` or the JSON of a response
+/// body, so the same document is always formatted the same
+pub fn formatted<T: Serialize + ?Sized>(doc: &T) -> String {
+    let mut json = serde_json::to_string_pretty(&hash::canonical(doc)).unwrap();
+    json.push('\n');
+    json
+}
+
+/// Asserts that the canonical JSON of `doc` is the snapshot in the file `path`, panicking with
+/// the different lines otherwise
+pub fn assert_snapshot<T: Serialize + ?Sized>(doc: &T, path: impl AsRef<Path>) {
+    let path = path.as_ref();
+    let actual = formatted(doc);
+    let expected = match std::fs::read_to_string(path) {
+        Ok(expected) if std::env::var_os(UPDATE_SNAPSHOTS).is_none() => expected,
+        _ => {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).expect("cannot create the snapshot directory");
+            }
+            std::fs::write(path, &actual).expect("cannot write the snapshot");
+            return;
+        },
+    };
+    if actual != expected {
+        panic!(
+            "the document is not the snapshot `{}`, which is rewritten with `{}=1`:\n{}",
+            path.display(),
+            UPDATE_SNAPSHOTS,
+            diff(&expected, &actual)
+        );
+    }
+}
+
+/// The lines of `expected` and `actual` out of their common prefix and suffix
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let prefix = expected.iter().zip(&actual).take_while(|(a, b)| a == b).count();
+    let suffix = expected[prefix ..]
+        .iter()
+        .rev()
+        .zip(actual[prefix ..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut out = format!("@@ line {} @@\n", prefix + 1);
+    for line in &expected[prefix .. expected.len() - suffix] {
+        out.push_str(&format!("- {}\n", line));
+    }
+    for line in &actual[prefix .. actual.len() - suffix] {
+        out.push_str(&format!("+ {}\n", line));
+    }
+    out
+}
@@ -0,0 +1,236 @@
+//! The `proptest` strategies of the documents, the resources, the queries and the RSQL
+//! expressions, for the round trips of their serializations:
+//! ```ignore
+//! proptest! {
+//!     #[test]
+//!     fn document_round_trip(doc in any::<Document>()) {
+//!         let json = serde_json::to_string(&doc).unwrap();
+//!         prop_assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), doc);
+//!     }
+//! }
+//! ```
+//! where the generated values are the ones which are serialized without losing anything, like
+//! the JSON values without any float, and the queries only have the filters and the cursors of
+//! the enabled features
+
+use crate::model::document::{Document, DocumentItem, Included, PrimaryDataItem};
+use crate::model::error;
+use crate::model::link::{Link, Links};
+use crate::model::relationship::Relationship;
+use crate::model::resource::{IdentifierData, Resource, ResourceIdentifier};
+use crate::model::version::JsonApiVersion;
+use crate::model::{JsonApiInfo, Meta};
+use crate::query::filter::{FilterQuery, RsqlFilterData};
+use crate::query::page::{CursorBasedData, OffsetBasedData, PageBasedData, PageQuery};
+use crate::query::sort::{OrderType, SortQuery};
+use crate::query::Query;
+use proptest::collection::{hash_map, hash_set, vec};
+use proptest::prelude::*;
+use rsql_rs::ast::comparison::{self, Comparison};
+use rsql_rs::ast::constraint::{Arguments, Constraint};
+use rsql_rs::ast::expr::Expr;
+use rsql_rs::ast::Operator;
+use serde_json::Value;
+use std::convert::TryFrom;
+
+/// The names of the types, the fields and the members
+pub fn name() -> impl Strategy<Value = String> { "[a-z][a-z_]{0,7}" }
+
+/// The JSON values without any float, nested in two levels at most
+pub fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        "[a-zA-Z0-9 ]{0,8}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(2, 16, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0 .. 4).prop_map(Value::Array),
+            hash_map(name(), inner, 0 .. 4)
+                .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+pub fn meta() -> impl Strategy<Value = Meta> { hash_map(name(), json_value(), 0 .. 3) }
+
+/// The links of the absolute paths, like `/people/1`, which are raw or objects with `meta`
+pub fn link() -> impl Strategy<Value = Link> {
+    let href = "(/[a-z0-9]{1,6}){1,3}".prop_map(|href| href.parse().unwrap());
+    prop_oneof![
+        href.clone().prop_map(Link::Raw),
+        (href, meta()).prop_map(|(href, meta)| Link::Object { href, meta }),
+    ]
+}
+
+pub fn links() -> impl Strategy<Value = Links> { hash_map(name(), link(), 0 .. 3) }
+
+pub fn resource_identifier() -> impl Strategy<Value = ResourceIdentifier> {
+    (name(), "[a-z0-9-]{1,8}").prop_map(|(ty, id)| ResourceIdentifier { ty: ty.into(), id })
+}
+
+/// The relationships with their data only
+pub fn relationship() -> impl Strategy<Value = Relationship> {
+    let data = prop_oneof![
+        proptest::option::of(resource_identifier()).prop_map(IdentifierData::Single),
+        vec(resource_identifier(), 0 .. 3).prop_map(|ids| IdentifierData::Multiple(ids.into())),
+    ];
+    (data, meta()).prop_map(|(data, meta)| Relationship { data, meta, ..Default::default() })
+}
+
+pub fn resource() -> impl Strategy<Value = Resource> {
+    let attributes = hash_map(name(), json_value(), 0 .. 4);
+    let relationships = hash_map(name(), relationship(), 0 .. 3);
+    (resource_identifier(), attributes, relationships, links(), meta()).prop_map(
+        |(id, attributes, relationships, links, meta)| Resource {
+            id,
+            attributes: attributes.into(),
+            relationships: relationships.into_iter().map(|(k, v)| (k.into(), v)).collect(),
+            links,
+            meta,
+        },
+    )
+}
+
+/// The errors without `links` and `source`
+pub fn error() -> impl Strategy<Value = error::Error> {
+    let text = || proptest::option::of("[a-zA-Z0-9 ]{1,12}");
+    (text(), "[1-5][0-9]{2}", text(), text(), text(), proptest::option::of(meta())).prop_map(
+        |(id, status, code, title, detail, meta)| error::Error {
+            id,
+            status: Some(status),
+            code,
+            title,
+            detail,
+            meta,
+            ..Default::default()
+        },
+    )
+}
+
+/// The documents of a resource, the resources or `null` with the included resources, and the
+/// ones of the errors
+pub fn document() -> impl Strategy<Value = Document> {
+    let included = vec(resource(), 0 .. 3).prop_map(|resources| {
+        resources.into_iter().map(|r| (r.id.clone(), r)).collect::<Included>()
+    });
+    let data = prop_oneof![
+        resource().prop_map(|resource| PrimaryDataItem::Single(Box::new(resource))),
+        vec(resource(), 0 .. 3).prop_map(PrimaryDataItem::Multiple),
+    ];
+    let item = prop_oneof![
+        (data, included).prop_map(|data| DocumentItem::PrimaryData(Some(data))),
+        Just(DocumentItem::PrimaryData(None)),
+        vec(error(), 1 .. 3).prop_map(|errors| DocumentItem::Errors(errors.into())),
+    ];
+    let version = (0 .. 3u8, 0 .. 3u8).prop_map(|(major, minor)| JsonApiVersion { major, minor });
+    let jsonapi = (proptest::option::of(version), proptest::option::of(meta()))
+        .prop_map(|(version, meta)| JsonApiInfo { version, meta });
+    (
+        item,
+        proptest::option::of(links()),
+        proptest::option::of(meta()),
+        proptest::option::of(jsonapi),
+    )
+        .prop_map(|(item, links, meta, jsonapi)| Document { item, links, meta, jsonapi })
+}
+
+/// The RSQL expressions of the comparisons supported by `RsqlFilterData`, nested in three levels
+/// at most
+pub fn rsql_expr() -> impl Strategy<Value = Expr> {
+    let comparisons: Vec<&'static Comparison> = vec![
+        &comparison::EQUAL,
+        &comparison::NOT_EQUAL,
+        &comparison::GREATER_THAN,
+        &comparison::GREATER_THAN_OR_EQUAL,
+        &comparison::LESS_THAN,
+        &comparison::LESS_THAN_OR_EQUAL,
+        &comparison::IN,
+        &comparison::OUT,
+    ];
+    let argument = "[a-z0-9]{1,6}|[a-z0-9][a-z0-9 ]{0,5}";
+    let constraint = (name(), proptest::sample::select(comparisons), vec(argument, 2 .. 4))
+        .prop_map(|(selector, comparison, mut arguments)| {
+            if !comparison.multi_values {
+                arguments.truncate(1);
+            }
+            Expr::Item(Constraint {
+                selector,
+                comparison: comparison.clone(),
+                arguments: Arguments(arguments),
+            })
+        });
+    constraint.prop_recursive(3, 8, 2, |inner| {
+        let op = any::<bool>().prop_map(|and| if and { Operator::And } else { Operator::Or });
+        (op, inner.clone(), inner)
+            .prop_map(|(op, left, right)| Expr::Node(op, Box::new(left), Box::new(right)))
+    })
+}
+
+pub fn page_query() -> impl Strategy<Value = PageQuery> {
+    let offset = (0 .. 100usize, 0 .. 100usize)
+        .prop_map(|(offset, limit)| PageQuery::OffsetBased(OffsetBasedData { offset, limit }));
+    let page = (0 .. 100usize, 0 .. 100usize)
+        .prop_map(|(number, size)| PageQuery::PageBased(PageBasedData { number, size }));
+    let cursor = ("[a-z0-9-]{1,8}", any::<bool>(), 0 .. 100usize).prop_map(
+        |(target_id, is_look_after, limit)| {
+            PageQuery::CursorBased(CursorBasedData { target_id, is_look_after, limit })
+        },
+    );
+    if cfg!(feature = "page_cursor") {
+        prop_oneof![offset, page, cursor].boxed()
+    } else {
+        prop_oneof![offset, page].boxed()
+    }
+}
+
+/// The queries parsed by `Query::from_uri` with the enabled features
+pub fn query() -> impl Strategy<Value = Query> {
+    let include = proptest::option::of(hash_set(name(), 0 .. 3));
+    let fields = hash_map(name(), hash_set(name(), 0 .. 3), 0 .. 3);
+    let order = prop_oneof![Just(OrderType::Asc), Just(OrderType::Desc)];
+    let sort = vec((name(), order), 0 .. 3).prop_map(|sort| SortQuery::try_from(sort).unwrap());
+    let filter = hash_map(name(), rsql_expr(), 1 .. 3)
+        .prop_map(|exprs| FilterQuery::Rsql(RsqlFilterData::from(exprs)));
+    let filter = if cfg!(feature = "filter_rsql") {
+        proptest::option::of(filter).boxed()
+    } else {
+        Just(()).prop_map(|_| None).boxed()
+    };
+    // The queries without any page are only parsed with `page_cursor`
+    let page = if cfg!(feature = "page_cursor") {
+        proptest::option::of(page_query()).boxed()
+    } else {
+        page_query().prop_map(Some).boxed()
+    };
+    (include, fields, sort, page, filter).prop_map(|(include, fields, sort, page, filter)| Query {
+        include,
+        fields,
+        sort,
+        included_sort: vec![],
+        page,
+        filter,
+    })
+}
+
+impl Arbitrary for Resource {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy { resource().boxed() }
+}
+
+impl Arbitrary for Document {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy { document().boxed() }
+}
+
+impl Arbitrary for Query {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy { query().boxed() }
+}
@@ -0,0 +1,63 @@
+//! The spans of the steps shared by all of the endpoints, recorded with the `tracing` feature, like
+//! by the OpenTelemetry layer of `rabbithole-otel`, and no-ops without it
+
+/// The resource type of a span
+pub const RESOURCE_TYPE: &str = "rabbithole.resource_type";
+/// The operation of a span, one of `handler::OPERATIONS`, recorded by the services instrumented
+/// like by `rabbithole-otel`
+pub const OPERATION: &str = "rabbithole.operation";
+/// The number of the resources answered by a step
+pub const RESULT_COUNT: &str = "rabbithole.result_count";
+
+#[cfg(feature = "tracing")]
+pub use tracing::Span;
+
+#[cfg(not(feature = "tracing"))]
+#[derive(Debug, Clone)]
+pub struct Span;
+
+#[cfg(not(feature = "tracing"))]
+impl Span {
+    pub fn in_scope<F: FnOnce() -> T, T>(&self, f: F) -> T { f() }
+
+    pub fn record<V>(&self, _field: &str, _value: V) -> &Self { self }
+}
+
+/// The span of `handler::check_headers`
+pub fn check_headers_span() -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!("rabbithole.check_headers");
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// The span of `Query::from_uri`
+pub fn parse_query_span() -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!("rabbithole.parse_query");
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
+
+/// The span of the filter of the resources of `ty`, with the number of the matched ones
+pub fn filter_span(ty: &str) -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!(
+        "rabbithole.filter",
+        rabbithole.resource_type = ty,
+        rabbithole.result_count = tracing::field::Empty,
+    );
+    #[cfg(not(feature = "tracing"))]
+    {
+        let _ = ty;
+        Span
+    }
+}
+
+/// The span of the serialization of a document into JSON
+pub fn serialize_span() -> Span {
+    #[cfg(feature = "tracing")]
+    return tracing::info_span!("rabbithole.serialize_document");
+    #[cfg(not(feature = "tracing"))]
+    Span
+}
@@ -0,0 +1,210 @@
+//! The TypeScript definitions of the resource types in a `Registry`, keeping the models of the
+//! frontends in lockstep with the entities:
+//! ```ignore
+//! let registry = Registry::default().register::<Human>().register::<Dog>();
+//! std::fs::write("web/src/api.ts", rabbithole::typescript::generate(&registry))?;
+//! ```
+//! where each entity like `Human` has the interfaces of its resource object, `Human`, its
+//! `HumanAttributes` and `HumanRelationships`, and the types of its documents, `HumanDocument` and
+//! `HumanCollectionDocument`
+
+use crate::schema::{Registry, ResourceSchema};
+use serde_json::Value;
+use std::fmt::Write;
+
+/// The definitions shared by all of the resource types, like the document wrappers
+const PRELUDE: &str = r#"// Generated by rabbithole, do not edit
+
+export type Meta = Record<string, unknown>;
+
+export type Link = string | { href: string; meta?: Meta };
+
+export type Links = Record<string, Link>;
+
+export interface ResourceIdentifier<T extends string = string> {
+  type: T;
+  id: string;
+  meta?: Meta;
+}
+
+export interface Relationship<D> {
+  data: D;
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface Resource {
+  type: string;
+  id: string;
+  attributes?: Record<string, unknown>;
+  relationships?: Record<string, Relationship<ResourceIdentifier | ResourceIdentifier[] | null>>;
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface Document<D, I = Resource> {
+  data: D;
+  included?: I[];
+  links?: Links;
+  meta?: Meta;
+}
+
+export interface JsonApiError {
+  id?: string;
+  status?: string;
+  code?: string;
+  title?: string;
+  detail?: string;
+  source?: { pointer?: string; parameter?: string };
+  meta?: Meta;
+}
+
+export interface ErrorDocument {
+  errors: JsonApiError[];
+}
+"#;
+
+/// The TypeScript definitions of all of the resource types in `registry`
+pub fn generate(registry: &Registry) -> String {
+    let mut out = PRELUDE.to_string();
+    for resource in registry.resources() {
+        out.push_str(&resource_definitions(registry, resource));
+    }
+    out
+}
+
+/// The TypeScript definitions of `resource`, where the related types are the names of the
+/// interfaces in `registry`
+fn resource_definitions(registry: &Registry, resource: &ResourceSchema) -> String {
+    let name = interface_name(&resource.name);
+    let mut out = String::new();
+
+    let attributes: Vec<String> = resource
+        .attributes
+        .iter()
+        .map(|(field, schema)| format!("{}: {};", property_name(field), type_of(schema)))
+        .collect();
+    // The empty to-one relationships are skipped in the resource objects
+    let to_ones = resource.to_ones.iter().map(|(field, related)| {
+        format!(
+            "{}?: Relationship<ResourceIdentifier<{}> | null>;",
+            property_name(field),
+            string_literal(related)
+        )
+    });
+    let to_manys = resource.to_manys.iter().map(|(field, related)| {
+        format!(
+            "{}: Relationship<ResourceIdentifier<{}>[]>;",
+            property_name(field),
+            string_literal(related)
+        )
+    });
+    let relationships: Vec<String> = to_ones.chain(to_manys).collect();
+    interface(&mut out, &format!("{}Attributes", name), &attributes);
+    interface(&mut out, &format!("{}Relationships", name), &relationships);
+
+    let mut related_names: Vec<String> = vec![];
+    for (_, related) in resource.to_ones.iter().chain(&resource.to_manys) {
+        if let Some(related) = registry.get(related) {
+            let related = interface_name(&related.name);
+            if !related_names.contains(&related) {
+                related_names.push(related);
+            }
+        }
+    }
+    let _ = write!(
+        out,
+        r#"
+export interface {name} {{
+  type: {ty};
+  id: string;
+  attributes: {name}Attributes;
+  relationships?: {name}Relationships;
+  links?: Links;
+  meta?: Meta;
+}}
+"#,
+        name = name,
+        ty = string_literal(&resource.ty),
+    );
+    // The included resources are typed by the interfaces of the related types if they're known
+    let included = if related_names.is_empty() {
+        String::new()
+    } else {
+        format!(", {}", related_names.join(" | "))
+    };
+    let _ = write!(
+        out,
+        r#"
+export type {name}Document = Document<{name} | null{included}>;
+
+export type {name}CollectionDocument = Document<{name}[]{included}>;
+"#,
+        name = name,
+        included = included,
+    );
+    out
+}
+
+fn interface(out: &mut String, name: &str, properties: &[String]) {
+    if properties.is_empty() {
+        let _ = writeln!(out, "\nexport interface {} {{}}", name);
+    } else {
+        let _ = writeln!(out, "\nexport interface {} {{\n  {}\n}}", name, properties.join("\n  "));
+    }
+}
+
+/// The TypeScript type of the JSON schema `schema`, like the ones of `schema::type_schema`
+fn type_of(schema: &Value) -> String {
+    let ty = match schema.get("type").and_then(Value::as_str) {
+        Some("string") => "string".to_string(),
+        Some("integer") | Some("number") => "number".to_string(),
+        Some("boolean") => "boolean".to_string(),
+        Some("array") => {
+            let items = type_of(schema.get("items").unwrap_or(&Value::Null));
+            if items.contains(' ') {
+                format!("({})[]", items)
+            } else {
+                format!("{}[]", items)
+            }
+        },
+        Some("object") => match schema.get("additionalProperties") {
+            Some(values @ Value::Object(_)) => format!("Record<string, {}>", type_of(values)),
+            _ => "Record<string, unknown>".to_string(),
+        },
+        _ => "unknown".to_string(),
+    };
+    if schema.get("nullable") == Some(&Value::Bool(true)) && ty != "unknown" {
+        format!("{} | null", ty)
+    } else {
+        ty
+    }
+}
+
+/// `name` in PascalCase, like `BlogPosts` of `blog-posts`
+fn interface_name(name: &str) -> String {
+    let name: String = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .flat_map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_ascii_uppercase()).into_iter().chain(chars)
+        })
+        .collect();
+    if name.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", name)
+    } else {
+        name
+    }
+}
+
+fn property_name(name: &str) -> String {
+    let is_ident = name.starts_with(|c: char| c.is_alphabetic() || c == '_' || c == '$')
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '$');
+    if is_ident {
+        name.to_string()
+    } else {
+        string_literal(name)
+    }
+}
+
+fn string_literal(s: &str) -> String { Value::String(s.to_string()).to_string() }
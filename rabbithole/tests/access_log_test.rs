@@ -0,0 +1,60 @@
+use rabbithole::access_log::{AccessLog, AccessRecord, MASK};
+use std::time::Duration;
+
+fn record<'a>(uri: &'a http::Uri, headers: Vec<(&'a str, &'a str)>) -> AccessRecord<'a> {
+    AccessRecord { method: "GET", uri, status: 200, elapsed: Duration::from_millis(12), headers }
+}
+
+#[test]
+fn format_test() {
+    let uri: http::Uri =
+        "/api/people?filter[people]=password==secret;name==alice&sort=name".parse().unwrap();
+    let access_log = AccessLog::default().mask_attribute("password");
+    assert_eq!(
+        access_log.format(&record(&uri, vec![("Authorization", "Bearer token")])),
+        r#"method=GET path=/api/people query="filter[people]=password==***;name==alice&sort=name" status=200 elapsed_ms=12"#
+    );
+
+    // The percent-encoded filters are masked as well
+    let uri: http::Uri = "/api/people?filter%5Bpeople%5D=password%3D%3Dsecret".parse().unwrap();
+    let line = access_log.format(&record(&uri, vec![]));
+    assert!(line.contains(&format!("password=={}", MASK)));
+    assert!(!line.contains("secret"));
+
+    // The encoded `&` in the arguments doesn't split the pairs
+    let uri: http::Uri = "/api/people?filter[people]=password=='a%26b'".parse().unwrap();
+    let line = access_log.format(&record(&uri, vec![]));
+    assert!(line.contains(&format!("password=={}", MASK)), "{}", line);
+    assert!(!line.contains("b'"), "{}", line);
+
+    // The nested fields of the masked attributes are masked, but not the similar ones
+    let uri: http::Uri =
+        "/api/people?filter[people]=password.hash==x;passwords==y".parse().unwrap();
+    let line = access_log.format(&record(&uri, vec![]));
+    assert!(line.contains(&format!("password.hash=={};passwords==y", MASK)), "{}", line);
+
+    let uri: http::Uri = "/api/people".parse().unwrap();
+    assert_eq!(
+        AccessLog::default().format(&record(&uri, vec![])),
+        "method=GET path=/api/people status=200 elapsed_ms=12"
+    );
+}
+
+#[test]
+fn headers_test() {
+    let uri: http::Uri = "/api/people".parse().unwrap();
+    let headers = vec![
+        ("Authorization", "Bearer token"),
+        ("Cookie", "session=1"),
+        ("X-Tenant", "acme"),
+        ("Accept", "application/vnd.api+json"),
+    ];
+    let access_log = AccessLog::default().with_headers().mask_header("X-Tenant");
+    assert_eq!(
+        access_log.format(&record(&uri, headers)),
+        r#"method=GET path=/api/people status=200 elapsed_ms=12 authorization="***" cookie="***" x-tenant="***" accept="application/vnd.api+json""#
+    );
+
+    let access_log: AccessLog = serde_json::from_str(r#"{"log_headers": true}"#).unwrap();
+    assert_eq!(access_log, AccessLog::default().with_headers());
+}
@@ -0,0 +1,150 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use rabbithole::cache::{CacheKey, DocumentCache, LruDocumentCache};
+use rabbithole::event::{ChangeKind, EventBus, EventPublisher, ResourceChanged};
+use rabbithole::handler::{self, OperationRequest, Route};
+use rabbithole::model::error;
+use rabbithole::operation::{OperationContext, Principal};
+use rabbithole::query::Query;
+use rabbithole::store::CollectionStore;
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(Default)]
+struct HumanStore {
+    fetched: AtomicUsize,
+}
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        self.fetched.fetch_add(1, Ordering::SeqCst);
+        Ok(vec![Human {
+            id: "bob".into(),
+            name: "Bob".into(),
+            dogs: vec![Dog { id: "a".into(), name: "1".into(), age: 3 }],
+        }])
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.all().await?.into_iter().find(|human| human.id == id))
+    }
+}
+
+fn request(path: &str, principal: Option<&str>) -> OperationRequest {
+    let uri: http::Uri = path.parse().unwrap();
+    OperationRequest {
+        context: OperationContext {
+            principal: principal.map(|id| Principal { id: id.into(), scopes: vec![] }),
+            ..Default::default()
+        },
+        query: Query::from_uri(&uri).unwrap(),
+        base: "http://localhost".into(),
+        request_path: uri.into(),
+    }
+}
+
+fn key(ty: &str, request: &str) -> CacheKey {
+    CacheKey {
+        ty: ty.into(),
+        id: None,
+        base: "http://localhost".into(),
+        request: request.into(),
+        principal: None,
+        tenant: None,
+    }
+}
+
+#[test]
+fn lru_test() {
+    let cache = LruDocumentCache::new(2);
+    block_on(cache.put(key("people", "/people?a"), serde_json::json!({ "data": [] })));
+    block_on(cache.put(key("people", "/people?b"), serde_json::json!({ "data": [] })));
+    assert!(block_on(cache.get(&key("people", "/people?a"))).is_some());
+    block_on(cache.put(key("people", "/people?c"), serde_json::json!({ "data": [] })));
+    assert_eq!(cache.len(), 2);
+    assert!(block_on(cache.get(&key("people", "/people?a"))).is_some());
+    assert!(block_on(cache.get(&key("people", "/people?b"))).is_none());
+    assert!(block_on(cache.get(&key("people", "/people?c"))).is_some());
+
+    let doc = serde_json::json!({
+        "data": { "type": "people", "id": "bob" },
+        "included": [{ "type": "dogs", "id": "a" }],
+    });
+    block_on(cache.put(key("people", "/people/bob"), doc));
+    block_on(cache.invalidate("dogs"));
+    assert!(block_on(cache.get(&key("people", "/people/bob"))).is_none());
+    assert!(block_on(cache.get(&key("people", "/people?c"))).is_some());
+    block_on(cache.invalidate("people"));
+    assert!(cache.is_empty());
+}
+
+#[test]
+fn dispatch_cached_test() {
+    let cache = LruDocumentCache::new(8);
+    let store = HumanStore::default();
+    let route = Route::Single("bob".into());
+
+    let req = request("/people/bob?fields[people]=name", None);
+    let doc = block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap();
+    assert_eq!(doc["data"]["attributes"]["name"], "Bob");
+    let cached = block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap();
+    assert_eq!(cached, doc);
+    assert_eq!(store.fetched.load(Ordering::SeqCst), 1);
+
+    let req = request("/people/bob?fields[people]=name", Some("alice"));
+    block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap();
+    let req = request("/people/bob", None);
+    block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap();
+    assert_eq!(store.fetched.load(Ordering::SeqCst), 3);
+
+    block_on(cache.invalidate("dogs"));
+    block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap();
+    assert_eq!(store.fetched.load(Ordering::SeqCst), 4);
+
+    let req = request("/people/bob/relationships/cats", None);
+    let route = Route::Relationship("bob".into(), "cats".into());
+    block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap_err();
+    let fetched = store.fetched.load(Ordering::SeqCst);
+    block_on(handler::dispatch_cached(&cache, &store, &route, &req)).unwrap_err();
+    assert!(store.fetched.load(Ordering::SeqCst) > fetched);
+}
+
+#[test]
+fn event_bus_test() {
+    let cache: Arc<dyn DocumentCache> = Arc::new(LruDocumentCache::new(8));
+    let events = EventBus::new().subscribe(cache.clone());
+    let store = HumanStore::default();
+    let route = Route::Single("bob".into());
+    let req = request("/people/bob", None);
+    block_on(handler::dispatch_cached(&*cache, &store, &route, &req)).unwrap();
+
+    let dog = Dog { id: "a".into(), name: "2".into(), age: 3 };
+    block_on(events.publish(&ResourceChanged::new(ChangeKind::Updated, &dog, "http://localhost")));
+    block_on(handler::dispatch_cached(&*cache, &store, &route, &req)).unwrap();
+    assert_eq!(store.fetched.load(Ordering::SeqCst), 2);
+
+    let mut forwarded = request("/people/bob", None);
+    forwarded.base = "https://example.com".into();
+    let doc = block_on(handler::dispatch_cached(&*cache, &store, &route, &forwarded)).unwrap();
+    assert_eq!(doc["data"]["links"]["self"], "https://example.com/people/bob");
+    assert_eq!(store.fetched.load(Ordering::SeqCst), 3);
+}
@@ -0,0 +1,136 @@
+use rabbithole::handler;
+use rabbithole::query::Query;
+use rabbithole::rule::conformance::{self, is_member_name, ConformanceLevel};
+use rabbithole::rule::registry::RuleRegistry;
+use rabbithole::rule::Strictness;
+use serde_json::json;
+
+/// The codes and the parameters of all of the errors
+fn check_query(level: ConformanceLevel, query_str: &str) -> Result<(), Vec<(String, String)>> {
+    let uri = format!("/people?{}", query_str).parse().unwrap();
+    let query = Query::from_uri(&uri).unwrap();
+    let parameters = vec!["camelCase".to_string()];
+    level.check_query(uri.query(), &query, &parameters).map_err(|err| {
+        err.into_errors()
+            .into_iter()
+            .map(|err| (err.code.unwrap(), err.source.parameter.unwrap()))
+            .collect()
+    })
+}
+
+fn offender(code: &str, parameter: &str) -> (String, String) {
+    (code.to_string(), parameter.to_string())
+}
+
+#[test]
+fn header_rules_test() {
+    let strict = ConformanceLevel::Strict;
+    assert_eq!(strict.header_rules(Strictness::Lenient), Strictness::Strict);
+    assert_eq!(ConformanceLevel::Standard.header_rules(Strictness::Lenient), Strictness::Lenient);
+    assert_eq!(ConformanceLevel::Lenient.header_rules(Strictness::Strict), Strictness::Lenient);
+}
+
+#[test]
+fn member_name_test() {
+    for name in &["name", "first-name", "first_name", "first name", "名字", "a"] {
+        assert!(is_member_name(name), "{}", name);
+    }
+    for name in &["", "-name", "name_", " name", "na+me", "na.me"] {
+        assert!(!is_member_name(name), "{}", name);
+    }
+}
+
+#[test]
+fn check_query_test() {
+    let query_str =
+        "include=owner.friends&fields[people]=name,age&sort=-age&page[offset]=0&page[limit]=1";
+    assert!(check_query(ConformanceLevel::Strict, query_str).is_ok());
+    assert!(check_query(ConformanceLevel::Strict, "filter[people][rsql]=name==a").is_ok());
+    // The encoded `&` in the values and the encoded keys don't make up any parameters
+    assert!(check_query(ConformanceLevel::Strict, "filter[people]=name=='a%26b'").is_ok());
+    assert!(check_query(ConformanceLevel::Strict, "fields%5Bpeople%5D=name").is_ok());
+
+    // The unknown query parameters and the invalid member names are only rejected in `strict`
+    for level in &[ConformanceLevel::Standard, ConformanceLevel::Lenient] {
+        assert!(check_query(*level, "foo=1&fields[people]=-name").is_ok());
+    }
+    let strict = ConformanceLevel::Strict;
+    assert!(check_query(strict, "camelCase=1&camelCase[a]=2").is_ok());
+    assert_eq!(
+        check_query(strict, "fields[people]=_name"),
+        Err(vec![offender("RBH-0108", "fields[people]")])
+    );
+    assert_eq!(
+        check_query(strict, "include=owner.-friends"),
+        Err(vec![offender("RBH-0108", "include")])
+    );
+
+    // The names of JSON:API, which are only `a-z`, are told apart from the unknown ones
+    assert_eq!(
+        check_query(strict, "foo=1&pages[offset]=1&otherCase=1&foo=2&fields=a&include=b"),
+        Err(vec![
+            offender("RBH-0109", "foo"),
+            offender("RBH-0109", "pages[offset]"),
+            offender("RBH-0107", "otherCase"),
+            offender("RBH-0109", "fields"),
+        ])
+    );
+    assert_eq!(
+        check_query(strict, "camel_case=1&sort=-_age"),
+        Err(vec![offender("RBH-0107", "camel_case"), offender("RBH-0108", "sort")])
+    );
+}
+
+#[test]
+fn query_parameters_rule_test() {
+    assert!(conformance::is_implementation_specific("camelCase"));
+    assert!(conformance::is_implementation_specific("snake_case"));
+    assert!(!conformance::is_implementation_specific("lowercase"));
+    assert!(!conformance::is_implementation_specific("-dashed"));
+
+    // The rule rejects the unknown query parameters in the other conformance levels
+    let mut settings = handler::JsonApiSettings::new("1.0".parse().unwrap());
+    settings.header_rules = Strictness::Off;
+    settings.rules = RuleRegistry::default()
+        .with_request_rule(conformance::query_parameters_rule(vec!["camelCase".into()]));
+    let uri = "/people?camelCase=1&foo=1&barBaz=1".parse().unwrap();
+    let err = handler::check_request(&settings, &None, &None, &uri).unwrap_err();
+    let body: serde_json::Value =
+        serde_json::from_slice(handler::error_response(err).body()).unwrap();
+    assert_eq!(body["errors"].as_array().unwrap().len(), 2);
+    assert_eq!(body["errors"][0]["code"], "RBH-0109");
+    assert_eq!(body["errors"][1]["source"]["parameter"], "barBaz");
+
+    let uri = "/people?camelCase=1&include=dogs".parse().unwrap();
+    assert!(handler::check_request(&settings, &None, &None, &uri).is_ok());
+}
+
+#[test]
+fn full_linkage_test() {
+    let doc = json!({
+        "data": [{
+            "type": "people", "id": "1",
+            "relationships": { "dogs": { "data": [{ "type": "dogs", "id": "1" }] } }
+        }],
+        "included": [
+            {
+                "type": "dogs", "id": "1",
+                "relationships": { "owner": { "data": { "type": "people", "id": "2" } } }
+            },
+            { "type": "people", "id": "2" }
+        ]
+    });
+    let strict = ConformanceLevel::Strict;
+    assert!(strict.check_document(&doc, &Default::default()).is_ok());
+
+    let mut unlinked = doc.clone();
+    unlinked["included"].as_array_mut().unwrap().push(json!({ "type": "dogs", "id": "2" }));
+    let err = strict.check_document(&unlinked, &Default::default()).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0406"));
+    assert!(ConformanceLevel::Standard.check_document(&unlinked, &Default::default()).is_ok());
+
+    // The sparse fieldsets may leave the relationships out
+    let uri = "/people?fields[people]=name".parse().unwrap();
+    let query = Query::from_uri(&uri).unwrap();
+    assert!(strict.check_document(&unlinked, &query).is_ok());
+}
@@ -0,0 +1,107 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::deprecation::Deprecation;
+use rabbithole::entity::{ResourceView, SingleEntity};
+use rabbithole::query::FieldsQuery;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "owners")]
+#[entity(deprecated(since = "2023-01-01", sunset = "2024-03-01", note = "use `people`"))]
+pub struct Owner {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(deprecated(note = "use `birthday`"))]
+    pub age: i32,
+    #[entity(to_many, deprecated)]
+    pub dogs: Vec<Dog>,
+}
+
+fn owner() -> Owner {
+    Owner {
+        id: "1".into(),
+        name: "Alice".into(),
+        age: 30,
+        dogs: vec![Dog { id: "dog-1".into(), name: "Dog 1".into(), age: 3 }],
+    }
+}
+
+#[test]
+fn derive_test() {
+    let deprecation = Owner::deprecation().unwrap();
+    assert_eq!(deprecation.note.as_deref(), Some("use `people`"));
+    assert_eq!(Dog::deprecation(), None);
+    assert!(Dog::deprecated_fields().is_empty());
+
+    let fields = Owner::deprecated_fields();
+    assert_eq!(fields.len(), 2);
+    assert_eq!(fields["dogs"], Deprecation::default());
+
+    let resource = owner().to_resource("", &Default::default()).unwrap();
+    assert_eq!(
+        resource.meta["deprecated"],
+        json!({ "age": { "note": "use `birthday`" }, "dogs": {} })
+    );
+
+    // Only the deprecated fields in the resource are noted
+    let fields: FieldsQuery = HashMap::from_iter(vec![(
+        "owners".to_string(),
+        vec!["name".to_string()].into_iter().collect(),
+    )]);
+    let resource = owner().to_resource("", &fields).unwrap();
+    assert!(resource.meta.is_empty());
+
+    // The deprecated relationships are noted by their names
+    let fields: FieldsQuery = HashMap::from_iter(vec![(
+        "owners".to_string(),
+        vec!["dogs".to_string()].into_iter().collect(),
+    )]);
+    let resource = owner().to_resource("", &fields).unwrap();
+    assert_eq!(resource.meta["deprecated"], json!({ "dogs": {} }));
+}
+
+#[test]
+fn resource_view_test() {
+    let owner = owner();
+    for fields in [vec![], vec!["name"], vec!["age"], vec!["dogs"]] {
+        let query: FieldsQuery = if fields.is_empty() {
+            Default::default()
+        } else {
+            HashMap::from_iter(vec![(
+                "owners".to_string(),
+                fields.into_iter().map(ToString::to_string).collect(),
+            )])
+        };
+        let view = serde_json::to_value(ResourceView::new(&owner, "", &query)).unwrap();
+        let resource = serde_json::to_value(owner.to_resource("", &query).unwrap()).unwrap();
+        assert_eq!(view, resource);
+    }
+}
+
+#[test]
+fn headers_test() {
+    let deprecation = Owner::deprecation().unwrap();
+    assert_eq!(deprecation.deprecation_header(), "@1672531200");
+    assert_eq!(deprecation.sunset_header().unwrap(), "Fri, 01 Mar 2024 00:00:00 GMT");
+    let headers = deprecation.headers();
+    assert_eq!(headers.len(), 2);
+    assert_eq!(headers[0].0, "deprecation");
+    assert_eq!(headers[1].0, "sunset");
+
+    let deprecation = Deprecation {
+        since: Some("Sun, 06 Nov 1994 08:49:37 GMT".into()),
+        sunset: Some("someday".into()),
+        note: None,
+    };
+    assert_eq!(deprecation.deprecation_header(), "@784111777");
+    assert_eq!(deprecation.sunset_header(), None);
+    assert_eq!(deprecation.headers().len(), 1);
+    assert_eq!(Deprecation::default().deprecation_header(), "true");
+}
@@ -0,0 +1,64 @@
+pub mod common;
+
+use common::Dog;
+use rabbithole::encoding::Encoding;
+use rabbithole::entity::Entity;
+use rabbithole::model::document::Document;
+use rabbithole::query::Query;
+
+fn accept(s: &str) -> Option<String> { Some(s.to_string()) }
+
+#[test]
+fn json_test() {
+    let dogs: Vec<Dog> =
+        (1 ..= 3).map(|i| Dog { id: i.to_string(), name: format!("Dog {}", i), age: i }).collect();
+    let uri = "http://localhost:8080/api";
+    let doc = dogs.to_document_automatically(uri, &Query::default(), &"/dogs".parse().unwrap());
+    let doc = doc.unwrap();
+    let content = Encoding::Json.encode(&doc).unwrap();
+    let decoded: Document = Encoding::Json.decode(&content).unwrap();
+    assert_eq!(decoded.content_hash(), doc.content_hash());
+
+    assert_eq!(Encoding::negotiate(&None), Encoding::Json);
+    assert_eq!(Encoding::negotiate(&accept("application/vnd.api+json")), Encoding::Json);
+    assert_eq!(
+        Encoding::negotiate(&accept(
+            "*/*, application/vnd.api+json; q=0, application/hal+json; q=0.1"
+        )),
+        Encoding::Hal
+    );
+    assert_eq!(Encoding::of_content_type(&accept("application/vnd.api+cbor")), Encoding::Cbor);
+}
+
+#[cfg(feature = "cbor")]
+#[test]
+fn cbor_test() {
+    let dogs: Vec<Dog> =
+        (1 ..= 3).map(|i| Dog { id: i.to_string(), name: format!("Dog {}", i), age: i }).collect();
+    let uri = "http://localhost:8080/api";
+    let doc = dogs.to_document_automatically(uri, &Query::default(), &"/dogs".parse().unwrap());
+    let doc = doc.unwrap();
+    let content = Encoding::Cbor.encode(&doc).unwrap();
+    assert!(content.len() < Encoding::Json.encode(&doc).unwrap().len());
+    let decoded: Document = Encoding::Cbor.decode(&content).unwrap();
+    assert_eq!(decoded.content_hash(), doc.content_hash());
+
+    assert_eq!(Encoding::negotiate(&accept("application/vnd.api+cbor")), Encoding::Cbor);
+    assert_eq!(
+        Encoding::negotiate(&accept("application/vnd.api+json, application/vnd.api+cbor")),
+        Encoding::Json
+    );
+    assert_eq!(
+        Encoding::negotiate(&accept("application/vnd.api+json; q=0.5, application/vnd.api+cbor")),
+        Encoding::Cbor
+    );
+    assert_eq!(Encoding::negotiate(&accept("application/vnd.api+cbor; q=0, */*")), Encoding::Json);
+}
+
+#[cfg(not(feature = "cbor"))]
+#[test]
+fn cbor_not_implemented_test() {
+    let err = Encoding::Cbor.encode(&serde_json::json!({ "data": [] })).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-9904"));
+    assert_eq!(Encoding::negotiate(&accept("application/vnd.api+cbor")), Encoding::Json);
+}
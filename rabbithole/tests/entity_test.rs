@@ -0,0 +1,168 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::{
+    dyn_document, DocumentView, DynEntity, Entity, EntityIter, ResourceView, SingleEntity,
+};
+use rabbithole::query::FieldsQuery;
+use rabbithole::query::Query;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+use std::sync::Arc;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+fn alice() -> Human {
+    Human {
+        id: "1".into(),
+        name: "Alice".into(),
+        nickname: None,
+        dogs: vec![Dog { id: "dog-1".into(), name: "Dog 1".into(), age: 3 }],
+    }
+}
+
+fn fields(ty: &str, fields: &[&str]) -> FieldsQuery {
+    HashMap::from_iter(vec![(ty.to_string(), fields.iter().map(|f| f.to_string()).collect())])
+}
+
+#[test]
+fn resource_view_test() {
+    let uri = "http://localhost:8080/api";
+    let alice = alice();
+    for query in [
+        FieldsQuery::default(),
+        fields("people", &["name", "dogs"]),
+        fields("people", &["dogs"]),
+        fields("people", &[]),
+        fields("dogs", &["name"]),
+    ] {
+        let view = serde_json::to_value(ResourceView::new(&alice, uri, &query)).unwrap();
+        let resource = serde_json::to_value(alice.to_resource(uri, &query).unwrap()).unwrap();
+        assert_eq!(view, resource);
+    }
+
+    let query = fields("people", &["nickname"]);
+    let view = serde_json::to_value(ResourceView::new(&alice, uri, &query)).unwrap();
+    assert_eq!(view["attributes"], json!({ "nickname": null }));
+    assert!(view.get("relationships").is_none());
+}
+
+#[test]
+fn document_view_test() {
+    let uri = "http://localhost:8080/api";
+    let humans = vec![alice(), Human { id: "2".into(), name: "Bob".into(), ..alice() }];
+    let request_path = "/people".parse().unwrap();
+    for query in
+        &["", "include=dogs&sort=-name", "fields[people]=dogs&page[offset]=1&page[limit]=1"]
+    {
+        let query = Query::from_uri(&format!("/people?{}", query).parse().unwrap()).unwrap();
+        let view = DocumentView::collection(&humans, uri, &query, &request_path).unwrap();
+        let doc = humans.to_document_automatically(uri, &query, &request_path).unwrap();
+        assert_eq!(serde_json::to_value(view).unwrap(), serde_json::to_value(doc).unwrap());
+
+        let view = DocumentView::single(humans.first(), uri, &query, &request_path).unwrap();
+        let single = humans.first().cloned();
+        let doc = Entity::to_document_automatically(&single, uri, &query, &request_path);
+        assert_eq!(
+            serde_json::to_value(view).unwrap(),
+            serde_json::to_value(doc.unwrap()).unwrap()
+        );
+    }
+    let query = Query::default();
+    let view = DocumentView::<Human>::single(None, uri, &query, &request_path).unwrap();
+    assert_eq!(serde_json::to_value(view).unwrap(), json!({ "data": null }));
+}
+
+#[test]
+fn attribute_test() {
+    let alice = alice();
+    assert_eq!(alice.attribute("name").unwrap().to_string(), "\"Alice\"");
+    assert_eq!(alice.attribute("nickname").unwrap().to_string(), "null");
+    assert!(alice.attribute("dogs").is_none());
+    assert!(alice.attribute("id").is_none());
+
+    let bob = Human { id: "2".into(), name: "Bob".into(), ..alice.clone() };
+    assert_eq!(alice.cmp_field("name", &bob).unwrap(), Ordering::Less);
+    assert!(alice.cmp_field("unknown", &bob).is_err());
+    assert!(alice.cmp_field("nickname", &bob).is_err());
+}
+
+#[test]
+fn dyn_entity_test() {
+    let uri = "http://localhost:8080/api";
+    let request_path = "/things".parse().unwrap();
+    let dog = Dog { id: "dog-2".into(), name: "Dog 2".into(), age: 1 };
+    let entities: Vec<Box<dyn DynEntity>> = vec![Box::new(alice()), Box::new(dog.clone())];
+    assert_eq!(entities[0].dyn_ty(), "people");
+    assert_eq!(entities[1].dyn_id(), "dog-2");
+    assert_eq!(entities[1].downcast_ref::<Dog>().map(|dog| dog.age), Some(1));
+    assert!(entities[1].downcast_ref::<Human>().is_none());
+
+    let query = Query::from_uri(&"/things?include=dogs".parse().unwrap()).unwrap();
+    let doc = dyn_document(&entities.clone(), uri, &query, &request_path).unwrap();
+    let doc = serde_json::to_value(doc).unwrap();
+    assert_eq!(doc["data"][0]["id"], "1");
+    assert_eq!(doc["data"][1], serde_json::to_value(dog.to_resource(uri, &query.fields)).unwrap());
+    assert_eq!(doc["included"].as_array().unwrap().len(), 1);
+
+    let query = Query::from_uri(&"/things?page[offset]=1&page[limit]=1".parse().unwrap()).unwrap();
+    let doc = serde_json::to_value(dyn_document(&entities, uri, &query, &request_path).unwrap());
+    assert_eq!(doc.unwrap()["data"].as_array().unwrap().len(), 1);
+
+    let query = Query::default();
+    let doc = entities[0].to_document(uri, &query, &request_path).unwrap();
+    let expected = SingleEntity::to_document_automatically(&alice(), uri, &query, &request_path);
+    assert_eq!(
+        serde_json::to_value(doc).unwrap(),
+        serde_json::to_value(expected.unwrap()).unwrap()
+    );
+}
+
+#[test]
+fn wrapper_test() {
+    let uri = "http://localhost:8080/api";
+    let request_path = "/people".parse().unwrap();
+    let humans = vec![alice(), Human { id: "2".into(), name: "Bob".into(), ..alice() }];
+    let query = Query::from_uri(&"/people?include=dogs&sort=-name".parse().unwrap()).unwrap();
+    let expected = humans.to_document_automatically(uri, &query, &request_path).unwrap();
+    let expected = serde_json::to_value(expected).unwrap();
+
+    // The filtered entities are rendered by their references
+    let refs: Vec<&Human> = humans.iter().collect();
+    let doc = refs.to_document_automatically(uri, &query, &request_path).unwrap();
+    assert_eq!(serde_json::to_value(doc).unwrap(), expected);
+
+    let shared: Arc<[Human]> = humans.clone().into();
+    let doc = shared.to_document_automatically(uri, &query, &request_path).unwrap();
+    assert_eq!(serde_json::to_value(doc).unwrap(), expected);
+
+    // The filtered entities are rendered by an iterator without being collected
+    let iter = EntityIter(humans.iter().filter(|human| !human.name.is_empty()));
+    let doc = iter.to_document_automatically(uri, &query, &request_path).unwrap();
+    assert_eq!(serde_json::to_value(doc).unwrap(), expected);
+    assert_eq!(
+        iter.included(uri, &query.include, &query.fields).unwrap(),
+        humans.included(uri, &query.include, &query.fields).unwrap()
+    );
+    assert_eq!(serde_json::to_value(&iter).unwrap(), serde_json::to_value(&humans).unwrap());
+
+    let shared: Vec<Arc<Human>> = humans.into_iter().map(Arc::new).collect();
+    let doc = shared.to_document_automatically(uri, &query, &request_path).unwrap();
+    assert_eq!(serde_json::to_value(doc).unwrap(), expected);
+    assert_eq!(<Arc<Human> as SingleEntity>::ty(), "people");
+    assert_eq!(shared[0].to_resource(uri, &query.fields), alice().to_resource(uri, &query.fields));
+}
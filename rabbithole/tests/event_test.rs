@@ -0,0 +1,54 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use rabbithole::event::{ChangeKind, EventBus, EventPublisher, EventSubscriber, ResourceChanged};
+use std::sync::{Arc, Mutex};
+
+/// Records the events it receives, tagged with its name
+struct Recorder(&'static str, Arc<Mutex<Vec<String>>>);
+
+#[async_trait]
+impl EventSubscriber for Recorder {
+    async fn on_change(&self, event: &ResourceChanged) {
+        let event = format!("{}: {}.{} {}", self.0, event.ty, event.kind.as_str(), event.id);
+        self.1.lock().unwrap().push(event);
+    }
+}
+
+fn dog() -> Dog { Dog { id: "a".into(), name: "1".into(), age: 3 } }
+
+#[test]
+fn resource_changed_test() {
+    let event = ResourceChanged::new(ChangeKind::Updated, &dog(), "http://localhost/api");
+    assert_eq!((event.ty.as_str(), event.id.as_str()), ("dogs", "a"));
+    let resource = event.resource.unwrap();
+    assert_eq!(
+        serde_json::to_value(&resource.links["self"]).unwrap(),
+        "http://localhost/api/dogs/a"
+    );
+
+    let event = ResourceChanged::new(ChangeKind::Deleted, &dog(), "http://localhost/api");
+    assert_eq!(event.kind, ChangeKind::Deleted);
+    assert!(event.resource.is_none());
+    assert_eq!(serde_json::to_value(event.kind).unwrap(), "deleted");
+}
+
+#[test]
+fn event_bus_test() {
+    let events = Arc::new(Mutex::new(vec![]));
+    let bus = EventBus::new()
+        .subscribe(Recorder("first", events.clone()))
+        .subscribe(Arc::new(Recorder("second", events.clone())));
+    assert_eq!(bus.len(), 2);
+
+    block_on(bus.publish(&ResourceChanged::new(ChangeKind::Created, &dog(), "")));
+    assert_eq!(*events.lock().unwrap(), vec![
+        "first: dogs.created a".to_string(),
+        "second: dogs.created a".to_string()
+    ]);
+    assert!(EventBus::new().is_empty());
+}
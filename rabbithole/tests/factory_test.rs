@@ -0,0 +1,62 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::testing::factory::{payload, Factory};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[test]
+fn factory_test() {
+    let dogs =
+        Factory::new(|n| Dog { id: format!("dog-{}", n), name: format!("Dog {}", n), age: 1 });
+    let people = Factory::new({
+        let dogs = dogs.clone();
+        move |n| Human {
+            id: format!("human-{}", n),
+            name: format!("Human {}", n),
+            dogs: dogs.build_list(2),
+        }
+    });
+
+    let alice = people.build_with(|human| human.name = "Alice".into());
+    assert_eq!(alice.id, "human-1");
+    assert_eq!(alice.name, "Alice");
+    let dog_ids: Vec<&str> = alice.dogs.iter().map(|dog| dog.id.as_str()).collect();
+    assert_eq!(dog_ids, vec!["dog-1", "dog-2"]);
+
+    // The clones share the sequence numbers
+    assert_eq!(dogs.build().id, "dog-3");
+    let ids: Vec<String> = people.build_list(2).into_iter().map(|human| human.id).collect();
+    assert_eq!(ids, vec!["human-2", "human-3"]);
+    people.reset();
+    assert_eq!(people.next_sequence(), 1);
+
+    let (bob, doc) = people.build_payload();
+    assert_eq!(bob.id, "human-2");
+    assert_eq!(
+        serde_json::to_value(&doc).unwrap(),
+        json!({
+            "data": {
+                "type": "people",
+                "id": "human-2",
+                "attributes": { "name": "Human 2" },
+                "relationships": {
+                    "dogs": { "data": [{ "type": "dogs", "id": "dog-8" }, { "type": "dogs", "id": "dog-9" }] },
+                },
+            },
+        })
+    );
+    assert_eq!(payload(&bob), doc);
+}
@@ -1,10 +1,11 @@
 #[macro_use]
 extern crate lazy_static;
+extern crate rabbithole_derive as rbh_derive;
 
 pub mod common;
 
 use common::Dog;
-
+use serde::{Deserialize, Serialize};
 
 #[cfg(feature = "filter_rsql")]
 use rabbithole::query::filter::FilterData;
@@ -23,6 +24,30 @@ lazy_static! {
     ];
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Address {
+    pub city: String,
+    pub zip: Option<String>,
+}
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "kennels")]
+pub struct Kennel {
+    #[entity(id)]
+    pub id: String,
+    pub tags: Vec<String>,
+    pub address: Address,
+}
+
+#[cfg(feature = "filter_rsql")]
+fn kennel(id: &str, tags: &[&str], city: &str) -> Kennel {
+    Kennel {
+        id: id.into(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        address: Address { city: city.into(), zip: None },
+    }
+}
+
 #[test]
 #[cfg(feature = "filter_rsql")]
 fn rsql_test() {
@@ -44,3 +69,73 @@ fn rsql_test() {
             .unwrap();
     assert_eq!(rsql_data.filter(DOGS.clone()).unwrap().len(), 2);
 }
+
+#[test]
+#[cfg(feature = "filter_rsql")]
+fn compile_test() {
+    use rsql_rs::parser::rsql::RsqlParser;
+    use rsql_rs::parser::Parser;
+
+    let expr = RsqlParser::parse_to_node("name==12*;age=gt=2").unwrap();
+    let predicate = RsqlFilterData::compile::<Dog>(&expr).unwrap();
+    let dogs: Vec<&Dog> = DOGS.iter().filter(|dog| predicate(dog).unwrap()).collect();
+    assert_eq!(dogs.len(), 1);
+    assert_eq!(dogs[0].id, "a");
+
+    let expr = RsqlParser::parse_to_node("name==123,color==red").unwrap();
+    assert!(RsqlFilterData::compile::<Dog>(&expr).is_err());
+}
+
+#[test]
+#[cfg(feature = "filter_rsql")]
+fn nested_test() {
+    use rsql_rs::parser::rsql::RsqlParser;
+    use rsql_rs::parser::Parser;
+
+    let kennels = [
+        kennel("a", &["cute", "small"], "Tokyo"),
+        kennel("b", &["big"], "Osaka"),
+        kennel("c", &[], "Tokyo"),
+    ];
+    let ids = |rsql: &str| -> Vec<String> {
+        let expr = RsqlParser::parse_to_node(rsql).unwrap();
+        let predicate = RsqlFilterData::compile::<Kennel>(&expr).unwrap();
+        kennels.iter().filter(|k| predicate(k).unwrap()).map(|k| k.id.clone()).collect()
+    };
+    assert_eq!(ids("tags==cute"), vec!["a"]);
+    assert_eq!(ids("tags==sm*"), vec!["a"]);
+    assert_eq!(ids("tags!=cute"), vec!["b", "c"]);
+    assert_eq!(ids("tags=in=(big,small)"), vec!["a", "b"]);
+    assert_eq!(ids("tags=out=(big,small)"), vec!["c"]);
+    assert_eq!(ids("tags.size=ge=1"), vec!["a", "b"]);
+    assert_eq!(ids("tags.size==0"), vec!["c"]);
+    assert_eq!(ids("address.city==Tokyo;tags.size>1"), vec!["a"]);
+
+    // The selectors are checked by their attributes, and the missing paths are errors
+    let expr = RsqlParser::parse_to_node("owner.name==alice").unwrap();
+    assert!(RsqlFilterData::compile::<Kennel>(&expr).is_err());
+    let expr = RsqlParser::parse_to_node("tags.0==cute").unwrap();
+    let predicate = RsqlFilterData::compile::<Kennel>(&expr).unwrap();
+    assert!(predicate(&kennels[0]).unwrap());
+    assert_eq!(predicate(&kennels[2]).unwrap_err().code.as_deref(), Some("RBH-0401"));
+    // The orderings can't be tested on the arrays
+    let expr = RsqlParser::parse_to_node("tags>a").unwrap();
+    let predicate = RsqlFilterData::compile::<Kennel>(&expr).unwrap();
+    assert!(predicate(&kennels[0]).is_err());
+}
+
+#[test]
+#[cfg(feature = "filter_rsql")]
+fn in_test() {
+    let rsql_data =
+        RsqlFilterData::new(&HashMap::from_iter(vec![("dogs".into(), "name=in=(123,321)".into())]))
+            .unwrap()
+            .unwrap();
+    assert_eq!(rsql_data.filter(DOGS.clone()).unwrap().len(), 2);
+
+    let rsql_data =
+        RsqlFilterData::new(&HashMap::from_iter(vec![("dogs".into(), "age=out=(1,2)".into())]))
+            .unwrap()
+            .unwrap();
+    assert_eq!(rsql_data.filter(DOGS.clone()).unwrap().len(), 1);
+}
@@ -0,0 +1,205 @@
+use http::{header, HeaderMap, HeaderValue, Method, StatusCode};
+use rabbithole::cache_control::CachePolicy;
+use rabbithole::deprecation::Deprecation;
+use rabbithole::handler::{self, JsonApiSettings, Route};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::profile::{self, ProfileRegistry};
+use rabbithole::rule::Strictness;
+use rabbithole::JSON_API_HEADER;
+use std::time::{Duration, UNIX_EPOCH};
+
+#[test]
+fn route_test() {
+    assert_eq!(Route::from_segments(&[]), Some(Route::Collection));
+    assert_eq!(Route::from_segments(&["1"]), Some(Route::Single("1".into())));
+    assert_eq!(
+        Route::from_segments(&["1", "relationships", "dogs"]),
+        Some(Route::Relationship("1".into(), "dogs".into()))
+    );
+    assert_eq!(
+        Route::from_segments(&["1", "dogs"]),
+        Some(Route::Related("1".into(), "dogs".into()))
+    );
+    assert_eq!(Route::from_segments(&["1", "dogs", "2"]), None);
+}
+
+#[test]
+fn check_request_test() {
+    let mut settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 0 });
+    let json_api = Some(JSON_API_HEADER.to_string());
+    let uri = "/people?sort=-name".parse().unwrap();
+    let query = handler::check_request(&settings, &json_api, &json_api, &uri).unwrap();
+    assert!(!query.sort.is_empty());
+
+    let err = handler::check_request(&settings, &None, &json_api, &uri).unwrap_err();
+    assert_eq!(handler::error_status(&err), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    settings.header_rules = Strictness::Off;
+    assert!(handler::check_request(&settings, &None, &None, &uri).is_ok());
+}
+
+#[test]
+fn response_test() {
+    let resp = handler::error_response(error::Error::RouteNotFound(None));
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], JSON_API_HEADER);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["errors"][0]["code"], "RBH-0008");
+
+    let resp = handler::method_not_allowed(&Method::DELETE, "GET, HEAD");
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()[header::ALLOW], "GET, HEAD");
+
+    assert!(handler::is_fetching(&Method::HEAD));
+    let resp = handler::other_method_response(&Method::OPTIONS);
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()[header::ALLOW], handler::ALLOWED_METHODS);
+    let resp = handler::other_method_response(&Method::POST);
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+}
+
+#[test]
+fn profiles_test() {
+    let mut settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 1 });
+    settings.profiles =
+        ProfileRegistry::default().support(profile::TIMESTAMPS).require(profile::CURSOR_PAGINATION);
+    assert_eq!(
+        settings.content_type(),
+        format!(
+            r#"{}; profile="{} {}""#,
+            JSON_API_HEADER,
+            profile::TIMESTAMPS,
+            profile::CURSOR_PAGINATION
+        )
+    );
+
+    let plain = Some(JSON_API_HEADER.to_string());
+    let err = handler::check_headers(&settings, &plain, &plain).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0305"));
+    let with_profile =
+        Some(format!(r#"{}; profile="{}""#, JSON_API_HEADER, profile::CURSOR_PAGINATION));
+    assert!(handler::check_headers(&settings, &plain, &with_profile).is_ok());
+
+    let doc = serde_json::json!({ "data": [] });
+    let resp = handler::document_response(&settings, &plain, &doc).unwrap();
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], settings.content_type().as_str());
+
+    // The parameters are not allowed in JSON:API v1.0
+    settings.version = JsonApiVersion { major: 1, minor: 0 };
+    assert_eq!(settings.content_type(), JSON_API_HEADER);
+    assert!(handler::check_headers(&settings, &plain, &plain).is_ok());
+}
+
+#[test]
+fn last_modified_test() {
+    let last_modified = UNIX_EPOCH + Duration::from_millis(784_111_777_500);
+    assert_eq!(handler::http_date(last_modified), "Sun, 06 Nov 1994 08:49:37 GMT");
+
+    let since = |date: &str| Some(date.to_string());
+    assert!(!handler::modified_since(last_modified, &since("Sun, 06 Nov 1994 08:49:37 GMT")));
+    assert!(!handler::modified_since(last_modified, &since("Mon, 07 Nov 1994 00:00:00 GMT")));
+    assert!(handler::modified_since(last_modified, &since("Sun, 06 Nov 1994 08:49:36 GMT")));
+    assert!(handler::modified_since(last_modified, &since("yesterday")));
+    assert!(handler::modified_since(last_modified, &None));
+}
+
+#[test]
+fn conditional_response_test() {
+    let settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 0 });
+    let last_modified = UNIX_EPOCH + Duration::from_secs(784_111_777);
+    let meta = serde_json::json!({ "lastModified": handler::http_date(last_modified) });
+    let doc = serde_json::json!({ "data": { "type": "people", "id": "1", "meta": meta } });
+    assert_eq!(handler::last_modified_of(&doc), Some(last_modified));
+
+    let mut headers = HeaderMap::new();
+    let resp = handler::conditional_response(&settings, &headers, &doc, None, Some(last_modified))
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::LAST_MODIFIED], "Sun, 06 Nov 1994 08:49:37 GMT");
+    let etag = resp.headers()[header::ETAG].clone();
+
+    headers.insert(header::IF_NONE_MATCH, etag.clone());
+    let resp = handler::conditional_response(&settings, &headers, &doc, None, None).unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()[header::ETAG], etag);
+    assert!(resp.body().is_empty());
+
+    // `If-Modified-Since` is ignored if there is `If-None-Match`
+    headers.insert(header::IF_NONE_MATCH, HeaderValue::from_static("\"other\""));
+    headers.insert(
+        header::IF_MODIFIED_SINCE,
+        HeaderValue::from_static("Mon, 07 Nov 1994 00:00:00 GMT"),
+    );
+    assert!(!handler::is_fresh(&headers, Some("\"1\""), Some(last_modified)));
+    headers.remove(header::IF_NONE_MATCH);
+    assert!(handler::is_fresh(&headers, Some("\"1\""), Some(last_modified)));
+    assert!(!handler::is_fresh(&headers, Some("\"1\""), None));
+}
+
+#[test]
+fn deprecation_test() {
+    let deprecation = Deprecation { sunset: Some("2024-03-01".into()), ..Default::default() };
+    let mut headers = HeaderMap::new();
+    handler::apply_deprecation(None, &mut headers);
+    assert!(headers.is_empty());
+    handler::apply_deprecation(Some(&deprecation), &mut headers);
+    assert_eq!(headers["deprecation"], "true");
+    assert_eq!(headers["sunset"], "Fri, 01 Mar 2024 00:00:00 GMT");
+}
+
+#[test]
+fn redact_test() {
+    let mut settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 0 });
+    let storage_failed = || {
+        let mut err = error::Error::StorageFailed("connection refused at 10.0.0.1:5432", None);
+        err.meta = Some(vec![("file".to_string(), "src/db.rs:42".into())].into_iter().collect());
+        err
+    };
+    assert_eq!(settings.redacted(storage_failed()).meta.unwrap()["file"], "src/db.rs:42");
+
+    settings.redact_errors = true;
+    let err = settings.redacted(storage_failed());
+    assert_eq!(err.code.as_deref(), Some("RBH-0009"));
+    assert_eq!(err.status.as_deref(), Some("500"));
+    assert!(err.detail.is_none() && err.params.is_empty());
+    let meta = err.meta.unwrap();
+    assert_eq!(meta.len(), 1);
+    assert!(meta[handler::CORRELATION_ID].is_string());
+
+    let mut err = storage_failed();
+    err.meta.as_mut().unwrap().insert(handler::CORRELATION_ID.into(), "request-1".into());
+    assert_eq!(settings.redacted(err).meta.unwrap()[handler::CORRELATION_ID], "request-1");
+
+    // The client errors are left as they are
+    let err = settings.redacted(error::Error::ResourceNotExist("people", "1", None));
+    assert_eq!(err.detail.as_deref(), Some("The resource `people/1` does not exist"));
+}
+
+#[test]
+fn cache_control_test() {
+    for (policy, value) in &[
+        (CachePolicy::public(60), "public, max-age=60"),
+        (CachePolicy::private(10).with_must_revalidate(), "private, max-age=10, must-revalidate"),
+        (CachePolicy::public(60).with_s_maxage(600), "public, max-age=60, s-maxage=600"),
+        (CachePolicy { no_store: true, ..CachePolicy::public(60) }, "no-store"),
+        (CachePolicy::default(), "no-cache"),
+    ] {
+        assert_eq!(policy.header_value(), *value);
+    }
+
+    let mut settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 0 });
+    settings.cache_control.insert("people".into(), CachePolicy::no_store());
+    let mut resp = http::Response::new(());
+    resp.headers_mut().insert(header::ETAG, http::HeaderValue::from_static("\"1\""));
+    settings.apply_cache_control("people", &mut resp);
+    assert_eq!(resp.headers()[header::CACHE_CONTROL], "no-store");
+    assert!(!resp.headers().contains_key(header::ETAG));
+
+    // The errors and the other types are left as they are
+    let mut resp = http::Response::new(());
+    *resp.status_mut() = StatusCode::NOT_FOUND;
+    settings.apply_cache_control("people", &mut resp);
+    assert!(settings.cache_policy("dogs", StatusCode::OK).is_none());
+    assert!(!resp.headers().contains_key(header::CACHE_CONTROL));
+}
@@ -0,0 +1,47 @@
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::{Entity, SingleEntity};
+use rabbithole::hash::{canonical, content_hash};
+use rabbithole::model::document::Document;
+use rabbithole::query::Query;
+use serde_json::json;
+
+#[test]
+fn content_hash_test() {
+    let dog = Dog { id: "1".into(), name: "Dog 1".into(), age: 3 };
+    assert_eq!(dog.content_hash(), dog.clone().content_hash());
+    assert_ne!(dog.content_hash(), Dog { age: 4, ..dog.clone() }.content_hash());
+
+    assert_eq!(
+        content_hash(&json!({ "a": 1, "b": [1, 2] })),
+        content_hash(&json!({ "b": [1, 2], "a": 1 }))
+    );
+    assert_ne!(content_hash(&json!({ "b": [1, 2] })), content_hash(&json!({ "b": [2, 1] })));
+
+    let dogs: Vec<Dog> =
+        (1 ..= 5).map(|i| Dog { id: i.to_string(), name: format!("Dog {}", i), age: i }).collect();
+    let uri = "http://localhost:8080/api";
+    let doc = dogs.to_document_automatically(uri, &Query::default(), &"/dogs".parse().unwrap());
+    let doc = doc.unwrap();
+    let json = serde_json::to_string(&doc).unwrap();
+    let parsed: Document = serde_json::from_str(&json).unwrap();
+    assert_eq!(doc.content_hash(), parsed.content_hash());
+    assert_eq!(doc.content_hash(), content_hash(&serde_json::to_value(&doc).unwrap()));
+    assert_eq!(doc.content_hash().len(), 40);
+}
+
+#[test]
+fn canonical_test() {
+    let value = json!({
+        "data": { "type": "people", "id": "1", "meta": { "included": ["b", "a"] } },
+        "included": [{ "type": "people", "id": "3" }, { "type": "dogs", "id": "9" }],
+    });
+    let value = canonical(&value);
+    assert_eq!(
+        value["included"],
+        json!([{ "type": "dogs", "id": "9" }, { "type": "people", "id": "3" }])
+    );
+    // Only the included resources of the document are sorted
+    assert_eq!(value["data"]["meta"]["included"], json!(["b", "a"]));
+}
@@ -0,0 +1,110 @@
+use rabbithole::encoding::Encoding;
+use rabbithole::hypermedia::{to_hal, to_siren};
+use serde_json::json;
+
+fn doc() -> serde_json::Value {
+    json!({
+        "data": [{
+            "type": "people",
+            "id": "1",
+            "attributes": { "name": "Alice" },
+            "relationships": {
+                "dogs": {
+                    "data": [{ "type": "dogs", "id": "a" }, { "type": "dogs", "id": "b" }],
+                    "links": { "related": "/people/1/dogs" }
+                }
+            },
+            "links": { "self": "/people/1" }
+        }],
+        "included": [{
+            "type": "dogs",
+            "id": "a",
+            "attributes": { "name": "Lucky" },
+            "links": { "self": { "href": "/dogs/a" } }
+        }],
+        "links": { "self": "/people", "next": "/people?page[offset]=1" },
+        "meta": { "total": 2 }
+    })
+}
+
+#[test]
+fn hal_test() {
+    assert_eq!(
+        to_hal(&doc()),
+        json!({
+            "_embedded": {
+                "people": [{
+                    "id": "1",
+                    "name": "Alice",
+                    "_links": {
+                        "self": { "href": "/people/1" },
+                        "dogs": { "href": "/people/1/dogs" }
+                    },
+                    "_embedded": {
+                        "dogs": [{
+                            "id": "a",
+                            "name": "Lucky",
+                            "_links": { "self": { "href": "/dogs/a" } }
+                        }]
+                    }
+                }]
+            },
+            "_links": {
+                "self": { "href": "/people" },
+                "next": { "href": "/people?page[offset]=1" }
+            },
+            "meta": { "total": 2 }
+        })
+    );
+
+    let errors = json!({ "errors": [{ "status": "404" }] });
+    assert_eq!(to_hal(&errors), errors);
+}
+
+#[test]
+fn siren_test() {
+    let siren = to_siren(&doc());
+    assert_eq!(siren["class"], json!(["collection"]));
+    assert_eq!(siren["properties"], json!({ "meta": { "total": 2 } }));
+    let next = json!({ "rel": ["next"], "href": "/people?page[offset]=1" });
+    assert!(siren["links"].as_array().unwrap().contains(&next));
+
+    let person = &siren["entities"][0];
+    assert_eq!(person["class"], json!(["people"]));
+    assert_eq!(person["rel"], json!(["item"]));
+    assert_eq!(person["properties"], json!({ "id": "1", "name": "Alice" }));
+    assert_eq!(
+        person["links"],
+        json!([
+            { "rel": ["self"], "href": "/people/1" },
+            { "rel": ["dogs"], "href": "/people/1/dogs" }
+        ])
+    );
+    assert_eq!(
+        person["entities"],
+        json!([
+            {
+                "class": ["dogs"],
+                "properties": { "id": "a", "name": "Lucky" },
+                "links": [{ "rel": ["self"], "href": "/dogs/a" }],
+                "rel": ["dogs"]
+            },
+            { "class": ["dogs"], "properties": { "id": "b" }, "rel": ["dogs"] }
+        ])
+    );
+}
+
+#[test]
+fn negotiate_test() {
+    let accept = |s: &str| Some(s.to_string());
+    assert_eq!(Encoding::negotiate(&accept("application/hal+json")), Encoding::Hal);
+    assert_eq!(
+        Encoding::negotiate(&accept("application/hal+json; q=0.5, application/vnd.siren+json")),
+        Encoding::Siren
+    );
+
+    let content = Encoding::Hal.encode(&doc()).unwrap();
+    let hal: serde_json::Value = serde_json::from_slice(&content).unwrap();
+    assert_eq!(hal, to_hal(&doc()));
+    assert!(Encoding::Hal.decode::<serde_json::Value>(&content).is_err());
+}
@@ -0,0 +1,52 @@
+use rabbithole::i18n::{self, Localization, Message};
+use rabbithole::model::error;
+
+fn localization() -> Localization {
+    Localization::new("en")
+        .with_messages("de", vec![
+            ("RBH-0405", Message::new("Ressource fehlt", Some("`{ty}/{id}` fehlt".into()))),
+            ("RBH-0105", Message::new("Seitenparameter fehlen", Some("{params}".into()))),
+        ])
+        .with_messages("de-CH", vec![("RBH-0405", Message::new("Ressource fehlt!", None))])
+}
+
+#[test]
+fn builtin_test() {
+    let message = i18n::builtin("RBH-0105").unwrap();
+    assert_eq!(message.title, "Lack of Pagination Item");
+
+    // The built-in templates render the same details as the errors
+    for err in &[
+        error::Error::ResourceNotExist("people", "1", None),
+        error::Error::LackOfPaginationParams("OffsetBased", &["offset", "limit"], None),
+        error::Error::RouteNotFound(None),
+    ] {
+        let message = i18n::builtin(err.code.as_deref().unwrap()).unwrap();
+        assert_eq!(message.render_detail(&err.params), err.detail);
+    }
+    assert!(i18n::builtin("RBH-0000").is_none());
+}
+
+#[test]
+fn translate_test() {
+    let localization = localization();
+    let mut err = error::Error::ResourceNotExist("people", "1", None);
+    localization.translate(&mut err, "de");
+    assert_eq!(err.title.as_deref(), Some("Ressource fehlt"));
+    assert_eq!(err.detail.as_deref(), Some("`people/1` fehlt"));
+
+    let mut err = error::Error::LackOfPaginationParams("OffsetBased", &["offset", "limit"], None);
+    localization.translate(&mut err, "de-ch");
+    assert_eq!(err.title.as_deref(), Some("Seitenparameter fehlen"));
+    assert_eq!(err.detail.as_deref(), Some(r#"["offset", "limit"]"#));
+
+    // The regional messages override the ones of the language, keeping the detail if not given
+    let mut err = error::Error::ResourceNotExist("people", "1", None);
+    localization.translate(&mut err, "de-ch");
+    assert_eq!(err.title.as_deref(), Some("Ressource fehlt!"));
+    assert_eq!(err.detail.as_deref(), Some("The resource `people/1` does not exist"));
+
+    let mut err = error::Error::ResourceNotExist("people", "1", None);
+    localization.translate(&mut err, "en");
+    assert_eq!(err.title.as_deref(), Some("Resource Not Exist"));
+}
@@ -0,0 +1,92 @@
+use rabbithole::model::json_patch::JsonPatch;
+use rabbithole::model::patch::PatchData;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::model::resource::{IdentifierData, Resource, ResourceIdentifier};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn person() -> Resource {
+    let attributes: HashMap<&str, serde_json::Value> =
+        vec![("name", json!("Alice")), ("nickname", json!("Al")), ("tags", json!(["a", "b"]))]
+            .into_iter()
+            .collect();
+    let dogs = Relationship {
+        data: IdentifierData::Multiple(vec![ResourceIdentifier::new("dogs", "1")].into()),
+        ..Default::default()
+    };
+    Resource {
+        id: ResourceIdentifier::new("people", "1"),
+        attributes: attributes.into(),
+        relationships: vec![("dogs".into(), dogs)].into_iter().collect(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn to_patches_test() {
+    let json_patch: JsonPatch = serde_json::from_value(json!([
+        { "op": "test", "path": "/data/attributes/name", "value": "Alice" },
+        { "op": "replace", "path": "/data/attributes/name", "value": "Bob" },
+        { "op": "remove", "path": "/data/attributes/nickname" },
+        { "op": "add", "path": "/data/attributes/tags/1", "value": "c" },
+        {
+            "op": "add",
+            "path": "/data/relationships/dogs/data/-",
+            "value": { "type": "dogs", "id": "2" }
+        }
+    ]))
+    .unwrap();
+    let patches = json_patch.to_patches(&person()).unwrap();
+    assert_eq!(patches.len(), 2);
+    assert_eq!(patches[0].resource_type, "people");
+    assert_eq!(patches[0].resource_id, "1");
+    match &patches[0].item {
+        PatchData::Attributes(attributes) => {
+            let attributes = attributes.get_json_value_map().unwrap();
+            assert_eq!(attributes.len(), 3);
+            assert_eq!(attributes["name"], json!("Bob"));
+            assert_eq!(attributes["nickname"], json!(null));
+            assert_eq!(attributes["tags"], json!(["a", "c", "b"]));
+        },
+        item => panic!("{:?}", item),
+    }
+    match &patches[1].item {
+        PatchData::Relationships(relationships) => {
+            let ids = relationships["dogs"].data.data();
+            assert_eq!(ids, vec![
+                ResourceIdentifier::new("dogs", "1"),
+                ResourceIdentifier::new("dogs", "2")
+            ]);
+        },
+        item => panic!("{:?}", item),
+    }
+}
+
+#[test]
+fn invalid_test() {
+    let code = |operations: serde_json::Value| {
+        let json_patch: JsonPatch = serde_json::from_value(operations).unwrap();
+        json_patch.to_patches(&person()).unwrap_err().code.unwrap()
+    };
+    assert_eq!(
+        code(json!([{ "op": "test", "path": "/data/attributes/name", "value": "Bob" }])),
+        "RBH-0203"
+    );
+    assert_eq!(code(json!([{ "op": "remove", "path": "/data/id" }])), "RBH-0202");
+    assert_eq!(code(json!([{ "op": "remove", "path": "/data/attributes/age" }])), "RBH-0202");
+    assert_eq!(
+        code(json!([{ "op": "add", "path": "/data/attributes/tags/5", "value": "c" }])),
+        "RBH-0202"
+    );
+    assert_eq!(code(json!([{ "op": "remove", "path": "/data/relationships/dogs" }])), "RBH-0202");
+
+    let json_patch: JsonPatch = serde_json::from_value(json!([
+        { "op": "copy", "from": "/data/attributes/name", "path": "/data/attributes/nickname" },
+        { "op": "move", "from": "/data/attributes/tags", "path": "/data/attributes/labels" }
+    ]))
+    .unwrap();
+    let updated = json_patch.apply(&person()).unwrap().attributes.get_json_value_map().unwrap();
+    assert_eq!(updated["nickname"], json!("Alice"));
+    assert_eq!(updated["labels"], json!(["a", "b"]));
+    assert!(!updated.contains_key("tags"));
+}
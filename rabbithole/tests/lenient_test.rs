@@ -0,0 +1,76 @@
+use rabbithole::model::document::Document;
+use rabbithole::model::lenient;
+use serde_json::json;
+
+fn problems(doc: serde_json::Value) -> Vec<(String, String)> {
+    let err = lenient::from_value(doc).unwrap_err();
+    err.into_errors()
+        .into_iter()
+        .map(|err| {
+            assert_eq!(err.code.as_deref(), Some("RBH-0204"));
+            (err.source.pointer.unwrap().to_string(), err.detail.unwrap())
+        })
+        .collect()
+}
+
+#[test]
+fn valid_test() {
+    let doc = json!({
+        "data": [{
+            "type": "people",
+            "id": "1",
+            "attributes": { "name": "Alice" },
+            "relationships": { "dogs": { "data": [{ "type": "dogs", "id": "a" }] } },
+            "links": { "self": "http://localhost/people/1" }
+        }],
+        "included": [{ "type": "dogs", "id": "a" }],
+        "meta": { "total": 1 }
+    });
+    let expected: Document = serde_json::from_value(doc.clone()).unwrap();
+    assert_eq!(lenient::from_value(doc).unwrap(), expected);
+    assert_eq!(lenient::from_slice(br#"{ "data": null }"#).unwrap(), Document::default());
+    assert_eq!(lenient::from_slice(b"{").unwrap_err().code.as_deref(), Some("RBH-0003"));
+}
+
+#[test]
+fn problems_test() {
+    let doc = json!({
+        "data": [
+            { "type": "people", "id": 1, "attributes": [] },
+            {
+                "id": "2",
+                "relationships": {
+                    "dogs": { "data": [{ "type": "dogs" }, "b"] },
+                    "owner": { "links": {} }
+                }
+            }
+        ],
+        "links": { "next": 2 },
+        "meta": "none"
+    });
+    let mut problems = problems(doc);
+    problems.sort();
+    let pointers: Vec<&str> = problems.iter().map(|(pointer, _)| pointer.as_str()).collect();
+    assert_eq!(pointers, vec![
+        "/data/0/attributes",
+        "/data/0/id",
+        "/data/1",
+        "/data/1/relationships/dogs/data/0",
+        "/data/1/relationships/dogs/data/1",
+        "/data/1/relationships/owner",
+        "/links/next",
+        "/meta",
+    ]);
+    assert_eq!(
+        problems[1].1,
+        "The member `/data/0/id` of the document is invalid: expected a string, but a number found"
+    );
+    assert!(problems[2].1.ends_with("the member `type` is missing"));
+}
+
+#[test]
+fn document_test() {
+    let doc = json!({ "errors": [{ "status": 400 }], "data": null, "included": {} });
+    let pointers: Vec<String> = problems(doc).into_iter().map(|(pointer, _)| pointer).collect();
+    assert_eq!(pointers, vec!["/included", "/errors", "/errors"]);
+}
@@ -0,0 +1,63 @@
+use rabbithole::model::link::{Link, Links, UriTemplate};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[test]
+fn expand_test() {
+    let template: UriTemplate =
+        "http://localhost/people/{id}/dogs{?page[limit],page[offset]}".parse().unwrap();
+    assert_eq!(template.variables(), vec!["id", "page[limit]", "page[offset]"]);
+
+    let mut variables = HashMap::new();
+    variables.insert("id", "1 2".to_string());
+    assert_eq!(template.expand(&variables), "http://localhost/people/1%202/dogs");
+    variables.insert("page[limit]", "10".to_string());
+    assert_eq!(template.expand(&variables), "http://localhost/people/1%202/dogs?page[limit]=10");
+    variables.insert("page[offset]", "".to_string());
+    assert_eq!(
+        template.expand(&variables),
+        "http://localhost/people/1%202/dogs?page[limit]=10&page[offset]="
+    );
+
+    let template: UriTemplate = "{+base}{/type}{.ext}{#section}{;v}{&sort}".parse().unwrap();
+    let variables: HashMap<&str, String> = vec![
+        ("base", "http://localhost/api"),
+        ("type", "dogs"),
+        ("ext", "json"),
+        ("section", "a/b"),
+        ("v", "1"),
+        ("sort", "-name,age"),
+    ]
+    .into_iter()
+    .map(|(k, v)| (k, v.to_string()))
+    .collect();
+    assert_eq!(
+        template.expand(&variables),
+        "http://localhost/api/dogs.json#a/b;v=1&sort=-name%2Cage"
+    );
+}
+
+#[test]
+fn invalid_test() {
+    for template in &["/people/{id", "/people/id}", "/people/{{id}}", "/people/{??id}", "/{id:3}"] {
+        assert!(template.parse::<UriTemplate>().is_err(), "{}", template);
+    }
+}
+
+#[test]
+fn serde_test() {
+    let template: UriTemplate = "/people/1/dogs{?page[limit]}".parse().unwrap();
+    let mut links = Links::new();
+    links.insert("related".into(), Link::template(template));
+    let json = json!({
+        "related": {
+            "href": "/people/1/dogs{?page[limit]}",
+            "meta": { "templated": true, "variables": ["page[limit]"] }
+        }
+    });
+    assert_eq!(serde_json::to_value(&links).unwrap(), json);
+
+    let links: Links = serde_json::from_value(json).unwrap();
+    assert!(matches!(&links["related"], Link::Template { .. }));
+    assert_eq!(links["related"].href(), "/people/1/dogs");
+}
@@ -0,0 +1,55 @@
+use rabbithole::model::merge_patch::MergePatch;
+use rabbithole::model::patch::PatchData;
+use rabbithole::model::resource::{Resource, ResourceIdentifier};
+use serde_json::json;
+use std::collections::HashMap;
+
+fn person() -> Resource {
+    let attributes: HashMap<&str, serde_json::Value> = vec![
+        ("name", json!("Alice")),
+        ("nickname", json!("Al")),
+        ("address", json!({ "city": "Paris", "zip": "75001" })),
+        ("tags", json!(["a", "b"])),
+    ]
+    .into_iter()
+    .collect();
+    Resource {
+        id: ResourceIdentifier::new("people", "1"),
+        attributes: attributes.into(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn to_patch_test() {
+    let merge_patch: MergePatch = r#"{
+        "name": "Alice",
+        "nickname": null,
+        "age": null,
+        "address": { "city": "Lyon", "zip": null, "country": "FR" },
+        "tags": ["c"]
+    }"#
+    .parse()
+    .unwrap();
+    let patch = merge_patch.to_patch(&person()).unwrap().unwrap();
+    assert_eq!(patch.resource_type, "people");
+    assert_eq!(patch.resource_id, "1");
+    match &patch.item {
+        PatchData::Attributes(attributes) => {
+            let attributes = attributes.get_json_value_map().unwrap();
+            assert_eq!(attributes.len(), 3);
+            assert_eq!(attributes["nickname"], json!(null));
+            assert_eq!(attributes["address"], json!({ "city": "Lyon", "country": "FR" }));
+            assert_eq!(attributes["tags"], json!(["c"]));
+        },
+        item => panic!("{:?}", item),
+    }
+
+    let merged = merge_patch.apply(&person().attributes).unwrap().get_json_value_map().unwrap();
+    assert_eq!(merged.len(), 3);
+    assert!(!merged.contains_key("nickname"));
+
+    let unchanged: MergePatch = r#"{ "name": "Alice", "age": null }"#.parse().unwrap();
+    assert_eq!(unchanged.to_patch(&person()).unwrap(), None);
+    assert!("[]".parse::<MergePatch>().is_err());
+}
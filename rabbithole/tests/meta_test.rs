@@ -0,0 +1,43 @@
+use rabbithole::handler::{JsonApiSettings, CORRELATION_ID};
+use rabbithole::model::error;
+use rabbithole::model::meta::MetaNamespace;
+use rabbithole::model::version::JsonApiVersion;
+use serde_json::json;
+
+#[test]
+fn apply_test() {
+    let namespace = MetaNamespace::new("rabbithole").with_member("total");
+    let mut doc = json!({
+        "data": [{
+            "type": "people",
+            "id": "1",
+            "meta": { "lastModified": "Sun, 06 Nov 1994 08:49:37 GMT", "owner": "alice" }
+        }],
+        "included": [{ "type": "dogs", "id": "a", "meta": { "owner": "bob" } }],
+        "meta": { "total": 1, "rabbithole": { "aliases": ["person"] } }
+    });
+    namespace.apply(&mut doc);
+    assert_eq!(
+        doc["data"][0]["meta"],
+        json!({ "owner": "alice", "rabbithole": { "lastModified": "Sun, 06 Nov 1994 08:49:37 GMT" } })
+    );
+    // The meta without the namespaced members is left as it is
+    assert_eq!(doc["included"][0]["meta"], json!({ "owner": "bob" }));
+    // The members are merged into the namespace if it's there
+    assert_eq!(doc["meta"], json!({ "rabbithole": { "aliases": ["person"], "total": 1 } }));
+}
+
+#[test]
+fn settings_test() {
+    let mut settings = JsonApiSettings::new(JsonApiVersion { major: 1, minor: 0 });
+    let mut err = error::Error::InternalServerError(None);
+    err.meta = Some(std::iter::once((CORRELATION_ID.to_string(), json!("abc"))).collect());
+    assert!(settings.redacted(err.clone()).meta.unwrap().contains_key(CORRELATION_ID));
+
+    settings.meta_namespace = Some(MetaNamespace::new("rabbithole"));
+    let meta = settings.redacted(err).meta.unwrap();
+    assert_eq!(meta["rabbithole"], json!({ CORRELATION_ID: "abc" }));
+
+    let doc = settings.namespaced(json!({ "data": null, "meta": { "rateLimits": {} } }));
+    assert_eq!(doc["meta"], json!({ "rabbithole": { "rateLimits": {} } }));
+}
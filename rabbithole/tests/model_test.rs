@@ -270,7 +270,7 @@ fn it_does_not_omit_an_empty_primary_data() {
 #[test]
 fn it_omits_empty_error_keys() {
     let error = Error { id: Some("error_id".to_string()), ..Default::default() };
-    let doc = Document { item: DocumentItem::Errors(vec![error]), ..Default::default() };
+    let doc = Document { item: DocumentItem::Errors(vec![error].into()), ..Default::default() };
     assert_eq!(serde_json::to_string(&doc).unwrap(), r#"{"errors":[{"id":"error_id"}]}"#);
 }
 
@@ -287,3 +287,27 @@ fn it_allows_for_optional_attributes() {
         unreachable!("err: {:?}", err);
     }
 }
+
+#[test]
+fn typed_attributes_test() {
+    let attributes: Attributes = serde_json::from_value(serde_json::json!({
+        "name": "Alice",
+        "nickname": null,
+        "age": 16,
+        "tags": ["a", "b"]
+    }))
+    .unwrap();
+    assert_eq!(attributes.get_as::<String>("name").unwrap(), "Alice");
+    assert_eq!(attributes.get_as::<Vec<String>>("tags").unwrap(), vec!["a", "b"]);
+    assert!(attributes.get_as::<u8>("name").is_err());
+    assert!(attributes.get_as::<String>("unknown").is_err());
+
+    assert_eq!(attributes.get_opt::<u8>("age").unwrap(), Some(16));
+    assert_eq!(attributes.get_opt::<String>("nickname").unwrap(), None);
+    assert_eq!(attributes.get_opt::<String>("unknown").unwrap(), None);
+    assert!(attributes.get_opt::<String>("age").is_err());
+
+    let numbers: Vec<(String, u8)> =
+        attributes.iter_as::<u8>().map(|(name, age)| (name.to_string(), age)).collect();
+    assert_eq!(numbers, vec![("age".to_string(), 16)]);
+}
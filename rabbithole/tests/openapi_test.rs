@@ -0,0 +1,133 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::SingleEntity;
+use rabbithole::openapi::{generate, OpenApiInfo, OpenApiSettings};
+use rabbithole::schema::{Registry, ResourceSchema};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub scores: HashMap<String, Vec<u8>>,
+    pub gender: Gender,
+    #[entity(to_one)]
+    pub best_friend: Option<Box<Human>>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub enum Gender {
+    Male,
+    Female,
+}
+
+#[test]
+fn schema_test() {
+    assert_eq!(Human::schema(), ResourceSchema {
+        name: "Human".into(),
+        ty: "people".into(),
+        id: ("id".into(), json!({ "type": "string" })),
+        attributes: vec![
+            ("name".into(), json!({ "type": "string" })),
+            ("nickname".into(), json!({ "type": "string", "nullable": true })),
+            (
+                "scores".into(),
+                json!({
+                    "type": "object",
+                    "additionalProperties": {
+                        "type": "array",
+                        "items": { "type": "integer", "minimum": 0 },
+                    },
+                }),
+            ),
+            ("gender".into(), json!({})),
+        ],
+        to_ones: vec![("best_friend".into(), "people".into())],
+        to_manys: vec![("dogs".into(), "dogs".into())],
+    });
+    let schema = Human::schema();
+    let required: Vec<&str> = schema.required_attributes().collect();
+    assert_eq!(required, vec!["name", "scores", "gender"]);
+    assert_eq!(rabbithole::schema::type_schema("& 'a mut str"), json!({ "type": "string" }));
+}
+
+#[test]
+fn generate_test() {
+    let registry = Registry::default().register::<Human>().register::<Dog>();
+    let info = OpenApiInfo { title: "Pets".into(), version: "1.0.0".into() };
+    let mut settings = OpenApiSettings::new(info, "http://localhost:8080/api");
+    settings.paths.insert("people".into(), "/humans".into());
+    let spec = generate(&registry, &settings);
+
+    assert_eq!(spec["info"]["title"], "Pets");
+    assert_eq!(spec["servers"][0]["url"], "http://localhost:8080/api");
+    let mut paths: Vec<&String> = spec["paths"].as_object().unwrap().keys().collect();
+    paths.sort();
+    assert_eq!(paths, vec![
+        "/dogs",
+        "/dogs/{id}",
+        "/dogs/{id}/relationships/{related_field}",
+        "/dogs/{id}/{related_field}",
+        "/humans",
+        "/humans/{id}",
+        "/humans/{id}/relationships/{related_field}",
+        "/humans/{id}/{related_field}",
+    ]);
+
+    let collection = &spec["paths"]["/humans"]["get"];
+    assert_eq!(collection["operationId"], "fetch_collection_people");
+    let parameters: Vec<&str> = collection["parameters"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|parameter| parameter["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(parameters, vec!["include", "fields", "sort", "page", "filter"]);
+    assert!(collection["parameters"][2]["description"]
+        .as_str()
+        .unwrap()
+        .ends_with("name, nickname, scores, gender"));
+    assert_eq!(
+        collection["responses"]["200"]["content"][rabbithole::JSON_API_HEADER]["schema"]["$ref"],
+        "#/components/schemas/people.Collection"
+    );
+    assert_eq!(
+        collection["responses"]["400"]["content"][rabbithole::JSON_API_HEADER]["schema"]["$ref"],
+        "#/components/schemas/Errors"
+    );
+    let related = &spec["paths"]["/humans/{id}/{related_field}"]["get"]["parameters"][1];
+    assert_eq!(related["schema"]["enum"], json!(["best_friend", "dogs"]));
+    assert!(spec["paths"]["/dogs/{id}/{related_field}"]["get"]["parameters"][1]["schema"]
+        .get("enum")
+        .is_none());
+
+    let schemas = &spec["components"]["schemas"];
+    let human = &schemas["people.Resource"]["properties"];
+    assert_eq!(human["type"]["enum"], json!(["people"]));
+    assert_eq!(human["attributes"]["properties"]["age"], json!(null));
+    assert_eq!(human["attributes"]["required"], json!(["name", "scores", "gender"]));
+    assert_eq!(
+        human["relationships"]["properties"]["dogs"]["properties"]["data"]["items"]["properties"]
+            ["type"]["enum"],
+        json!(["dogs"])
+    );
+    assert_eq!(
+        schemas["dogs.Resource"]["properties"]["attributes"]["properties"]["age"],
+        json!({ "type": "integer" })
+    );
+    assert_eq!(
+        schemas["people.Document"]["properties"]["data"]["allOf"][0]["$ref"],
+        "#/components/schemas/people.Resource"
+    );
+    assert!(schemas["Errors"].is_object());
+}
@@ -0,0 +1,214 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+#[macro_use]
+extern crate lazy_static;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use rabbithole::entity::SingleEntity;
+use rabbithole::handler::{self, OperationRequest, Route};
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::model::resource::Resource;
+use rabbithole::operation::*;
+use rabbithole::query::Query;
+use rabbithole::store;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::iter::FromIterator;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+lazy_static! {
+    pub static ref DOGS: Vec<Dog> = vec![
+        Dog { id: "a".into(), name: "public".into(), age: 3 },
+        Dog { id: "b".into(), name: "forbidden".into(), age: 2 },
+        Dog { id: "c".into(), name: "hidden".into(), age: 1 },
+    ];
+}
+
+struct DogService;
+
+#[async_trait]
+impl Fetching for DogService {
+    type Item = Dog;
+
+    async fn fetch_collection(&self, _: &FetchPlan<'_>) -> Result<Vec<Self::Item>, error::Error> {
+        Ok(DOGS.clone())
+    }
+
+    async fn fetch_single(
+        &self, id: &str, _: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        Ok(DOGS.iter().find(|d| d.id == id).cloned())
+    }
+
+    async fn fetch_relationship(
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        Err(error::Error::FieldNotExist(related_field, None))
+    }
+
+    async fn fetch_related(
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        Err(error::Error::FieldNotExist(related_field, None))
+    }
+
+    async fn can(&self, _: &OperationContext, _: &Action, item: &Self::Item) -> Decision {
+        match item.name.as_str() {
+            "forbidden" => Decision::Forbid,
+            "hidden" => Decision::Hide,
+            _ => Decision::Allow,
+        }
+    }
+}
+
+/// The humans with all of the dogs, where only the public dogs are rendered along with them
+struct HumanService;
+
+#[async_trait]
+impl Fetching for HumanService {
+    type Item = Human;
+
+    async fn fetch_collection(&self, _: &FetchPlan<'_>) -> Result<Vec<Self::Item>, error::Error> {
+        Ok(vec![Human { id: "master".into(), dogs: DOGS.clone() }])
+    }
+
+    async fn fetch_single(
+        &self, id: &str, plan: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.fetch_collection(plan).await?.into_iter().find(|h| h.id == id))
+    }
+
+    async fn fetch_relationship(
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        Err(error::Error::FieldNotExist(related_field, None))
+    }
+
+    async fn fetch_related(
+        &self, id: &str, related_field: &str, uri: &str, query: &Query, request_path: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        let plan = FetchPlan::new::<Human>(query);
+        let item = self.fetch_single(id, &plan).await?;
+        store::related_of(item, related_field, uri, query, request_path)
+    }
+
+    async fn can_include(&self, _: &OperationContext, _: &Action, resource: &Resource) -> Decision {
+        match resource.attributes.get_as::<String>("name") {
+            Ok(name) if name == "public" => Decision::Allow,
+            _ => Decision::Hide,
+        }
+    }
+}
+
+fn request(path: &str) -> OperationRequest {
+    let uri: http::Uri = path.parse().unwrap();
+    OperationRequest {
+        context: Default::default(),
+        query: Query::from_uri(&uri).unwrap(),
+        base: "http://localhost/api".into(),
+        request_path: uri.into(),
+    }
+}
+
+fn ids(resources: &serde_json::Value) -> Vec<&str> {
+    resources.as_array().unwrap().iter().map(|r| r["id"].as_str().unwrap()).collect()
+}
+
+#[test]
+fn authorize_included_test() {
+    let route = Route::Related("master".into(), "dogs".into());
+    let doc = block_on(handler::dispatch(&HumanService, &route, &request("/people/master/dogs")));
+    assert_eq!(ids(&doc.unwrap()["data"]), vec!["a"]);
+
+    let req = request("/people/master?include=dogs");
+    let doc = block_on(handler::dispatch(&HumanService, &Route::Single("master".into()), &req));
+    assert_eq!(ids(&doc.unwrap()["included"]), vec!["a"]);
+    let doc = block_on(handler::dispatch(&HumanService, &Route::Collection, &req)).unwrap();
+    assert_eq!(ids(&doc["included"]), vec!["a"]);
+
+    let mut dog = serde_json::json!({ "type": "dogs", "id": "c", "attributes": { "name": "c" } });
+    let (ctx, action) = (OperationContext::default(), Action::FetchSingle);
+    block_on(authorize_resources(&HumanService, &ctx, &action, &mut dog));
+    assert!(dog.is_null());
+}
+
+#[test]
+fn authorize_collection_test() {
+    let ctx = OperationContext::default();
+    let dogs = block_on(authorize_collection(&DogService, &ctx, DOGS.clone()));
+    assert_eq!(dogs.len(), 1);
+    assert_eq!(dogs[0].id(), "a");
+}
+
+#[test]
+fn authorize_single_test() {
+    let ctx = OperationContext::default();
+    let action = Action::FetchSingle;
+    assert!(block_on(authorize_single(&DogService, &ctx, &action, &DOGS[0])).is_ok());
+
+    let err = block_on(authorize_single(&DogService, &ctx, &action, &DOGS[1])).unwrap_err();
+    assert_eq!(err.status, Some("403".into()));
+    assert_eq!(err.code, Some("RBH-0403".into()));
+
+    let err = block_on(authorize_single(&DogService, &ctx, &action, &DOGS[2])).unwrap_err();
+    assert_eq!(err.status, Some("404".into()));
+    assert_eq!(err.code, Some("RBH-0405".into()));
+}
+
+#[test]
+fn authorize_parent_test() {
+    let ctx = OperationContext::default();
+    let query = Query::default();
+    let action = Action::FetchRelated("fleas".into());
+    assert!(block_on(authorize_parent(&DogService, &ctx, "a", &action, &query)).is_ok());
+    assert!(block_on(authorize_parent(&DogService, &ctx, "b", &action, &query)).is_err());
+    assert!(block_on(authorize_parent(&DogService, &ctx, "none", &action, &query)).is_ok());
+}
+
+#[test]
+fn operation_context_scope_test() {
+    let mut ctx = OperationContext::default();
+    assert!(!ctx.has_scope("read"));
+    ctx.principal = Some(Principal { id: "alice".into(), scopes: vec!["read".into()] });
+    assert!(ctx.has_scope("read"));
+    assert!(!ctx.has_scope("write"));
+}
+
+#[test]
+fn fetch_plan_test() {
+    let query = Query::default();
+    let plan = FetchPlan::new::<Dog>(&query);
+    assert!(plan.includes("fleas"));
+    assert!(plan.selects("name"));
+
+    let query = Query {
+        include: Some(HashSet::from_iter(vec!["fleas".to_string()])),
+        fields: HashMap::from_iter(vec![(
+            "dogs".to_string(),
+            HashSet::from_iter(vec!["age".to_string()]),
+        )]),
+        ..Default::default()
+    };
+    let plan = FetchPlan::new::<Dog>(&query);
+    assert!(plan.includes("fleas"));
+    assert!(!plan.includes("master"));
+    assert!(plan.selects("age"));
+    assert!(!plan.selects("name"));
+
+    let query = Query { include: Some(Default::default()), ..Default::default() };
+    assert!(!FetchPlan::new::<Dog>(&query).includes("fleas"));
+}
@@ -83,6 +83,27 @@ fn overflow_page_based_page_test() {
     assert_eq!(slice.len(), 0);
 }
 
+#[test]
+fn out_of_range_page_test() {
+    let dogs = DOGS.clone();
+    for page in vec![
+        PageQuery::OffsetBased(OffsetBasedData { offset: usize::MAX, limit: 1 }),
+        PageQuery::PageBased(PageBasedData { number: usize::MAX, size: 2 }),
+        PageQuery::PageBased(PageBasedData { number: usize::MAX / 2, size: 2 }),
+    ] {
+        assert!(page.page(&dogs).is_empty(), "{:?}", page);
+    }
+
+    let page = PageQuery::OffsetBased(OffsetBasedData { offset: 1, limit: usize::MAX });
+    assert_eq!(page.page(&dogs).len(), 2);
+    let page = PageQuery::CursorBased(CursorBasedData {
+        target_id: "a".to_string(),
+        is_look_after: true,
+        limit: usize::MAX,
+    });
+    assert_eq!(page.page(&dogs).len(), 2);
+}
+
 #[test]
 fn cursor_based_test() {
     let dogs = DOGS.clone();
@@ -0,0 +1,55 @@
+#![cfg(feature = "parallel")]
+
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::Entity;
+use rabbithole::model::link::RawUri;
+use rabbithole::parallel;
+use rabbithole::query::Query;
+use serde::{Deserialize, Serialize};
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+const URI: &str = "http://localhost:8080/api";
+
+#[test]
+fn to_document_test() {
+    let humans: Vec<Human> = (0 .. 200)
+        .map(|n| Human {
+            id: n.to_string(),
+            name: format!("Human {}", n % 17),
+            dogs: (n .. n + 3)
+                .map(|d| Dog { id: format!("dog-{}", d), name: format!("Dog {}", d), age: d })
+                .collect(),
+        })
+        .collect();
+    let request_path: RawUri = "/people".parse().unwrap();
+    for query in &[
+        "page[offset]=0&page[limit]=50",
+        "include=dogs&sort=-name&page[offset]=20&page[limit]=30",
+        "include=&fields[people]=name&page[number]=2&page[size]=20",
+        "include=dogs&fields[dogs]=age&page[offset]=190&page[limit]=30",
+    ] {
+        let uri: http::Uri = format!("/people?{}", query).parse().unwrap();
+        let query = Query::from_uri(&uri).unwrap();
+        let expected = humans.to_document_automatically(URI, &query, &request_path).unwrap();
+        let actual = parallel::to_document(&humans, URI, &query, &request_path).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    let uri: http::Uri =
+        "/people?include=dogs.master&page[offset]=0&page[limit]=1".parse().unwrap();
+    let query = Query::from_uri(&uri).unwrap();
+    assert!(parallel::to_document(&humans, URI, &query, &request_path).is_err());
+}
@@ -0,0 +1,89 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::postman::{curl, generate, PostmanSettings};
+use rabbithole::schema::Registry;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+fn settings() -> PostmanSettings {
+    let dogs = vec![Dog { id: "dog-1".into(), name: "Dog 1".into(), age: 1 }, Dog {
+        id: "dog-2".into(),
+        name: "Dog 2".into(),
+        age: 2,
+    }];
+    let humans = vec![Human { id: "human-1".into(), name: "Alice".into(), dogs: dogs.clone() }];
+    PostmanSettings::new("Pets", "http://localhost:8080/api/").example(&humans).example(&dogs)
+}
+
+#[test]
+fn collection_test() {
+    let registry = Registry::default().register::<Human>().register::<Dog>();
+    let collection = generate(&registry, &settings());
+    assert_eq!(collection["info"]["name"], "Pets");
+    assert_eq!(collection["variable"][0]["value"], "http://localhost:8080/api");
+
+    let folders = collection["item"].as_array().unwrap();
+    let names: Vec<&str> = folders.iter().map(|folder| folder["name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["Human", "Dog"]);
+
+    let people = folders[0]["item"].as_array().unwrap();
+    let raws: Vec<&str> =
+        people.iter().map(|item| item["request"]["url"]["raw"].as_str().unwrap()).collect();
+    assert_eq!(raws, vec![
+        "{{baseUrl}}/people",
+        "{{baseUrl}}/people/:id",
+        "{{baseUrl}}/people/:id/relationships/dogs",
+        "{{baseUrl}}/people/:id/dogs",
+    ]);
+
+    let query = &people[0]["request"]["url"]["query"];
+    let keys: Vec<&str> =
+        query.as_array().unwrap().iter().map(|q| q["key"].as_str().unwrap()).collect();
+    assert_eq!(keys, vec!["include", "fields[people]", "sort", "page[offset]", "page[limit]"]);
+    assert_eq!(query[0]["value"], "dogs");
+    assert_eq!(query[0]["disabled"], true);
+    assert_eq!(people[1]["request"]["url"]["variable"][0]["value"], "human-1");
+    assert_eq!(people[1]["request"]["header"][0]["value"], "application/vnd.api+json");
+
+    // The saved responses are the documents of the examples
+    let body = |item: &Value| -> Value {
+        serde_json::from_str(item["response"][0]["body"].as_str().unwrap()).unwrap()
+    };
+    assert_eq!(body(&people[0])["data"][0]["attributes"]["name"], "Alice");
+    assert_eq!(body(&people[1])["data"]["id"], "human-1");
+    assert_eq!(body(&people[2])["data"][1]["id"], "dog-2");
+    assert_eq!(body(&people[3])["data"][1]["attributes"]["name"], "Dog 2");
+
+    // No example of a relationship without any example resource
+    let registry = Registry::default().register::<Human>();
+    let collection =
+        generate(&registry, &PostmanSettings::new("Pets", "http://localhost:8080/api"));
+    let people = collection["item"][0]["item"].as_array().unwrap();
+    assert!(people.iter().all(|item| item["response"].as_array().unwrap().is_empty()));
+    assert_eq!(people[1]["request"]["url"]["variable"][0]["value"], "1");
+}
+
+#[test]
+fn curl_test() {
+    let registry = Registry::default().register::<Human>();
+    let script = curl(&registry, &settings());
+    assert!(script.starts_with("#!/bin/sh\n"));
+    assert!(script.contains(
+        "curl -H 'Accept: application/vnd.api+json' -H 'Content-Type: \
+         application/vnd.api+json' 'http://localhost:8080/api/people/human-1/relationships/dogs'"
+    ));
+    assert_eq!(script.matches("curl ").count(), 4);
+}
@@ -8,10 +8,16 @@ use rabbithole::entity::Entity;
 
 use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
 use rabbithole::model::document::DocumentItem;
-use rabbithole::query::page::{CursorBasedData, PageQuery};
+use rabbithole::query::filter::{FilterQuery, RsqlFilterData};
+use rabbithole::query::limits::QueryLimits;
+use rabbithole::query::page::{CursorBasedData, OffsetBasedData, PageQuery};
 use rabbithole::query::sort::OrderType;
 use rabbithole::query::Query;
+use rsql_rs::parser::rsql::RsqlParser;
+use rsql_rs::parser::Parser;
+use serde_json::json;
 use std::convert::TryInto;
+use std::collections::HashMap;
 
 lazy_static! {
     pub static ref DOGS: Vec<Dog> = vec![
@@ -30,6 +36,7 @@ fn sort_and_page_test() {
         sort: vec![("name".into(), OrderType::Desc), ("age".into(), OrderType::Desc)]
             .try_into()
             .unwrap(),
+        included_sort: vec![],
         page: Some(PageQuery::CursorBased(CursorBasedData {
             target_id: "b".to_string(),
             is_look_after: true,
@@ -52,3 +59,110 @@ fn sort_and_page_test() {
         assert_eq!(data[1].id.id, "a");
     }
 }
+
+#[test]
+fn display_test() {
+    let query = Query {
+        include: Some(vec!["dogs".to_string(), "best_friend".into()].into_iter().collect()),
+        fields: vec![("dogs".to_string(), vec!["name".to_string()].into_iter().collect())]
+            .into_iter()
+            .collect(),
+        sort: vec![("name".into(), OrderType::Desc), ("age".into(), OrderType::Asc)]
+            .try_into()
+            .unwrap(),
+        included_sort: vec![(
+            "dogs".into(),
+            vec![("age".into(), OrderType::Desc)].try_into().unwrap(),
+        )],
+        page: Some(PageQuery::OffsetBased(OffsetBasedData { offset: 10, limit: 5 })),
+        filter: None,
+    };
+    assert_eq!(
+        query.to_string(),
+        "include=best_friend,dogs&fields[dogs]=name&sort=-name,age&sort[dogs]=-age&\
+         page[offset]=10&page[limit]=5"
+    );
+    assert_eq!(Query::default().to_string(), "");
+}
+
+#[test]
+fn from_uri_test() {
+    let uri = "include=dogs,,&fields[people]=name&fields[people]=dogs&fields[]=age&page[offset]=1&\
+               page[limit]=2&fields=age&x[y]z=1&=1&sort";
+    let uri = percent_encode(uri.as_bytes(), NON_ALPHANUMERIC);
+    let query = Query::from_uri(&format!("/people?{}", uri).parse().unwrap()).unwrap();
+    assert_eq!(query.include, Some(vec!["dogs".to_string()].into_iter().collect()));
+    assert_eq!(
+        query.fields,
+        vec![("people".to_string(), vec!["name".into(), "dogs".into()].into_iter().collect())]
+            .into_iter()
+            .collect()
+    );
+    assert!(query.sort.is_empty());
+    assert_eq!(query.page, Some(PageQuery::OffsetBased(OffsetBasedData { offset: 1, limit: 2 })));
+    assert_eq!(Query::from_uri(&"/people".parse().unwrap()).unwrap(), Query::default());
+}
+
+#[test]
+fn included_sort_test() {
+    let uri = "/people?sort[dogs]=-age&sort[people]=name&sort[dogs]=name";
+    let query = Query::from_uri(&uri.parse().unwrap()).unwrap();
+    let types: Vec<String> =
+        query.included_sort.iter().map(|(ty, sort)| format!("{}:{}", ty, sort)).collect();
+    assert_eq!(types, vec!["dogs:-age,name", "people:name"]);
+
+    let resource = |ty: &str, id: &str, attributes: serde_json::Value| json!({ "type": ty, "id": id, "attributes": attributes });
+    let mut doc = json!({
+        "data": [],
+        "included": [
+            resource("cats", "1", json!({})),
+            resource("dogs", "a", json!({ "age": 2, "name": "Bo" })),
+            resource("dogs", "b", json!({ "age": 3, "name": "Max" })),
+            resource("dogs", "c", json!({ "age": 2, "name": "Ace" })),
+            resource("people", "1", json!({ "name": "Carol" })),
+            resource("people", "2", json!({ "name": "Alice" })),
+        ]
+    });
+    query.sort_included(&mut doc);
+    let ids: Vec<String> = doc["included"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| format!("{}/{}", r["type"].as_str().unwrap(), r["id"].as_str().unwrap()))
+        .collect();
+    assert_eq!(ids, vec!["dogs/b", "dogs/c", "dogs/a", "people/2", "people/1", "cats/1"]);
+}
+
+#[test]
+fn limits_test() {
+    let limits = QueryLimits {
+        max_filter_size: Some(3),
+        max_filter_depth: Some(2),
+        max_includes: Some(2),
+        max_sort_keys: Some(1),
+        max_fieldset_size: Some(2),
+    };
+    let check = |query: &str| {
+        let query = Query::from_uri(&format!("/people?{}", query).parse().unwrap()).unwrap();
+        limits.check(&query).map_err(|err| err.source.parameter.unwrap())
+    };
+    assert!(check("include=dogs,friends&sort=-name&fields[people]=name,dogs").is_ok());
+    assert!(QueryLimits::default().check(&Query::default()).is_ok());
+    assert_eq!(check("include=dogs,friends,friends.dogs"), Err("include".to_string()));
+    assert_eq!(check("sort=name,-age"), Err("sort".to_string()));
+    assert_eq!(
+        check("fields[dogs]=name&fields[people]=name,age,dogs"),
+        Err("fields[people]".into())
+    );
+
+    let filter = |rsql: &str| {
+        let expr = RsqlParser::parse_to_node(rsql).unwrap();
+        let exprs: HashMap<_, _> = vec![("dogs".to_string(), expr)].into_iter().collect();
+        Query { filter: Some(FilterQuery::Rsql(RsqlFilterData::from(exprs))), ..Default::default() }
+    };
+    assert!(limits.check(&filter("name==a;age=gt=1")).is_ok());
+    let err = limits.check(&filter("name==a,name==b,name==c,name==d")).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0110"));
+    assert_eq!(err.source.parameter.as_deref(), Some("filter[dogs]"));
+    assert!(limits.check(&filter("name==a;(age==1,(age==2;name==b))")).is_err());
+}
@@ -0,0 +1,38 @@
+#![cfg(feature = "testing")]
+
+use percent_encoding::{percent_encode, NON_ALPHANUMERIC};
+use proptest::prelude::*;
+use rabbithole::model::document::Document;
+use rabbithole::model::resource::Resource;
+use rabbithole::query::filter::RsqlFilterData;
+use rabbithole::query::Query;
+use rabbithole::testing::strategy::rsql_expr;
+use rsql_rs::parser::rsql::RsqlParser;
+use rsql_rs::parser::Parser;
+
+proptest! {
+    #[test]
+    fn document_test(doc in any::<Document>()) {
+        let json = serde_json::to_string(&doc).unwrap();
+        prop_assert_eq!(serde_json::from_str::<Document>(&json).unwrap(), doc);
+    }
+
+    #[test]
+    fn resource_test(resource in any::<Resource>()) {
+        let json = serde_json::to_value(&resource).unwrap();
+        prop_assert_eq!(serde_json::from_value::<Resource>(json).unwrap(), resource);
+    }
+
+    #[test]
+    fn query_test(query in any::<Query>()) {
+        let encoded = percent_encode(query.to_string().as_bytes(), NON_ALPHANUMERIC).to_string();
+        let uri: http::Uri = format!("/?{}", encoded).parse().unwrap();
+        prop_assert_eq!(Query::from_uri(&uri).unwrap(), query);
+    }
+
+    #[test]
+    fn rsql_test(expr in rsql_expr()) {
+        let rsql = RsqlFilterData::to_rsql(&expr);
+        prop_assert_eq!(RsqlParser::parse_to_node(&rsql).unwrap(), expr, "{}", rsql);
+    }
+}
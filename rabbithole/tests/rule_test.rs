@@ -0,0 +1,156 @@
+use rabbithole::handler::{self, JsonApiSettings};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::media_type::MediaType;
+use rabbithole::rule::registry::{RequestRule, ResponseRule, RuleRegistry};
+use rabbithole::rule::{RuleDispatcher, Strictness};
+use serde_json::json;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn media_type_parse_test() {
+    let media_type =
+        MediaType::parse(r#"Application/VND.api+JSON; Profile="a,b; c"; q=0.5; ext=x"#).unwrap();
+    assert_eq!(media_type.essence, "application/vnd.api+json");
+    assert_eq!(media_type.params, vec![("profile".to_string(), "a,b; c".to_string())]);
+    assert_eq!(media_type.weight, 0.5);
+    assert!(media_type.is_json_api());
+
+    assert!(MediaType::parse("application").is_none());
+    assert!(MediaType::parse("application/json; q=2").is_none());
+    assert!(MediaType::parse(r#"application/json; profile="unclosed"#).is_none());
+
+    let list = MediaType::parse_list(r#"text/html, application/vnd.api+json; profile="a,b", bad"#);
+    assert_eq!(list.len(), 2);
+    assert_eq!(list[1].params[0].1, "a,b");
+}
+
+#[test]
+fn accept_header_test() {
+    let v1_0 = JsonApiVersion { major: 1, minor: 0 };
+    let v1_1 = JsonApiVersion { major: 1, minor: 1 };
+    let accept = |s: &str| Some(s.to_string());
+
+    assert!(RuleDispatcher::AcceptHeaderShouldBeJsonApi(&v1_0, &accept("*/*")).is_ok());
+    assert!(RuleDispatcher::AcceptHeaderShouldBeJsonApi(&v1_0, &None).is_err());
+    assert!(RuleDispatcher::AcceptHeaderShouldBeJsonApi(
+        &v1_0,
+        &accept(r#"application/vnd.api+json; profile="p", application/vnd.api+json"#)
+    )
+    .is_ok());
+    assert!(RuleDispatcher::AcceptHeaderShouldBeJsonApi(
+        &v1_0,
+        &accept(r#"application/vnd.api+json; profile="p""#)
+    )
+    .is_err());
+    assert!(RuleDispatcher::AcceptHeaderShouldBeJsonApi(
+        &v1_1,
+        &accept(r#"application/vnd.api+json; profile="p""#)
+    )
+    .is_ok());
+}
+
+#[test]
+fn weighted_accept_test() {
+    let ranges = MediaType::parse_list(
+        "application/vnd.api+json; q=0.9, application/json; q=0.8, application/*; q=0.2, */*; q=0",
+    );
+    assert_eq!(MediaType::quality(&ranges, "application/vnd.api+json"), 0.9);
+    assert_eq!(MediaType::quality(&ranges, "application/xml"), 0.2);
+    assert_eq!(MediaType::quality(&ranges, "text/html"), 0.0);
+    let ranges = MediaType::parse_list(r#"application/vnd.api+json; profile="p", text/*"#);
+    assert_eq!(MediaType::quality(&ranges, "application/vnd.api+json"), 0.0);
+
+    let v1_0 = JsonApiVersion { major: 1, minor: 0 };
+    let v1_1 = JsonApiVersion { major: 1, minor: 1 };
+    let check = |version: &JsonApiVersion, accept: &str| {
+        RuleDispatcher::AcceptHeaderShouldBeJsonApi(version, &Some(accept.to_string())).is_ok()
+    };
+    for version in &[&v1_0, &v1_1] {
+        assert!(check(version, "application/vnd.api+json;q=0.9, application/json;q=0.8"));
+        assert!(check(version, "text/html, application/*; q=0.1"));
+        // The wildcards don't accept JSON:API excluded by a more specific range
+        assert!(!check(version, "application/vnd.api+json; q=0, */*"));
+        assert!(!check(version, "application/*; q=0, */*"));
+        assert!(check(version, "application/*; q=0.5, */*; q=0"));
+        assert!(!check(version, "application/json, application/vnd.api+json; q=0"));
+    }
+    assert!(!check(&v1_0, r#"application/vnd.api+json; profile="p", */*; q=0"#));
+    assert!(check(&v1_1, r#"application/vnd.api+json; profile="p", */*; q=0"#));
+}
+
+#[test]
+fn header_strictness_test() {
+    let v1_0 = JsonApiVersion { major: 1, minor: 0 };
+    let header = |s: &str| Some(s.to_string());
+    let json = header("application/json");
+    let json_api = header("application/vnd.api+json");
+    let with_param = header(r#"application/vnd.api+json; profile="p""#);
+
+    let check = |strictness, content_type: &Option<String>, accept: &Option<String>| {
+        RuleDispatcher::check_headers(&v1_0, strictness, content_type, accept).is_ok()
+    };
+    assert!(check(Strictness::Strict, &json_api, &json_api));
+    assert!(!check(Strictness::Strict, &json, &json_api));
+    assert!(!check(Strictness::Strict, &json_api, &None));
+
+    assert!(check(Strictness::Lenient, &json, &json));
+    assert!(check(Strictness::Lenient, &None, &None));
+    assert!(check(Strictness::Lenient, &json_api, &header("text/html, application/json")));
+    assert!(!check(Strictness::Lenient, &with_param, &json));
+    assert!(!check(Strictness::Lenient, &header("text/plain"), &json));
+    assert!(!check(Strictness::Lenient, &json, &header("application/json; q=0")));
+
+    assert!(check(Strictness::Off, &header("text/plain"), &with_param));
+}
+
+#[test]
+fn registry_test() {
+    let v1_0 = JsonApiVersion { major: 1, minor: 0 };
+    let v1_1 = JsonApiVersion { major: 1, minor: 1 };
+    let evaluated = Arc::new(Mutex::new(vec![]));
+    let rule = |name: &'static str, fail: bool| {
+        let evaluated = evaluated.clone();
+        RequestRule::new(name, move |_| {
+            evaluated.lock().unwrap().push(name);
+            match fail {
+                true => Err(error::Error::RuleViolated(name, "failed", None)),
+                false => Ok(()),
+            }
+        })
+    };
+    let paginated = ResponseRule::new("paginated", |doc, query| {
+        match doc["data"].is_array() && query.page.is_none() {
+            true => Err(error::Error::RuleViolated("paginated", "no `page`", None)),
+            false => Ok(()),
+        }
+    });
+    let rules = RuleRegistry::default()
+        .with_request_rule(rule("second", false))
+        .with_request_rule(rule("third", false).with_order(1))
+        .with_request_rule(rule("first", false).with_order(-1))
+        .with_request_rule(rule("v1.1-only", true).with_versions(vec![v1_1.clone()]))
+        .with_response_rule(paginated);
+    let names: Vec<_> = rules.request_rules().iter().map(|rule| rule.name.as_str()).collect();
+    assert_eq!(names, vec!["first", "second", "v1.1-only", "third"]);
+
+    let mut settings = JsonApiSettings::new(v1_0);
+    settings.header_rules = Strictness::Off;
+    settings.rules = rules.clone();
+    let uri = "/people".parse().unwrap();
+    let query = handler::check_request(&settings, &None, &None, &uri).unwrap();
+    assert_eq!(*evaluated.lock().unwrap(), vec!["first", "second", "third"]);
+
+    // The rules are evaluated in order until one fails
+    evaluated.lock().unwrap().clear();
+    settings.version = v1_1;
+    let err = handler::check_request(&settings, &None, &None, &uri).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0012"));
+    assert_eq!(*evaluated.lock().unwrap(), vec!["first", "second", "v1.1-only"]);
+    settings.rules = rules.without("v1.1-only");
+    assert!(handler::check_request(&settings, &None, &None, &uri).is_ok());
+
+    let err = handler::check_document(&settings, &json!({ "data": [] }), &query).unwrap_err();
+    assert_eq!(err.detail.as_deref(), Some("The rule `paginated` is violated: no `page`"));
+    assert!(handler::check_document(&settings, &json!({ "data": null }), &query).is_ok());
+}
@@ -0,0 +1,63 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::schema::json_schema;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    pub nickname: Option<String>,
+    pub tags: Vec<Option<Tag>>,
+    #[entity(to_one)]
+    pub best_friend: Option<Box<Human>>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Tag(String);
+
+#[test]
+fn json_schema_test() {
+    let schema = json_schema::<Human>();
+    let resource = &schema.resource;
+    assert_eq!(resource["$schema"], "http://json-schema.org/draft-07/schema#");
+    assert_eq!(resource["required"], json!(["type", "id"]));
+    assert_eq!(resource["properties"]["type"]["enum"], json!(["people"]));
+    let attributes = &resource["properties"]["attributes"];
+    assert_eq!(attributes["properties"]["nickname"], json!({ "type": ["string", "null"] }));
+    assert_eq!(attributes["properties"]["tags"], json!({ "type": "array", "items": {} }));
+    assert_eq!(attributes["required"], json!(["name", "tags"]));
+    assert!(attributes.get("additionalProperties").is_none());
+    let best_friend = &resource["properties"]["relationships"]["properties"]["best_friend"];
+    assert_eq!(best_friend["properties"]["data"]["anyOf"][1], json!({ "type": "null" }));
+    assert_eq!(
+        best_friend["properties"]["data"]["anyOf"][0]["allOf"][0]["properties"]["type"]["enum"],
+        json!(["people"])
+    );
+    assert!(best_friend["properties"]["links"].is_object());
+    assert!(!resource.to_string().contains("nullable"));
+
+    let create = &schema.create;
+    assert_eq!(create["required"], json!(["data"]));
+    let data = &create["properties"]["data"];
+    assert_eq!(data["required"], json!(["type"]));
+    assert_eq!(data["properties"]["attributes"]["required"], json!(["name", "tags"]));
+    assert_eq!(data["properties"]["attributes"]["additionalProperties"], json!(false));
+    let dogs = &data["properties"]["relationships"]["properties"]["dogs"];
+    assert_eq!(dogs["required"], json!(["data"]));
+    assert_eq!(dogs["properties"]["data"]["items"]["properties"]["type"]["enum"], json!(["dogs"]));
+    assert!(data["properties"].get("links").is_none());
+
+    let data = &schema.update["properties"]["data"];
+    assert_eq!(data["required"], json!(["type", "id"]));
+    assert!(data["properties"]["attributes"].get("required").is_none());
+    assert_eq!(schema.update["title"], "Updating a resource of `people`");
+}
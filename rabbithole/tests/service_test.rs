@@ -0,0 +1,307 @@
+#![cfg(feature = "tower")]
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use http::{header, Request, Response, StatusCode};
+use rabbithole::handler::RouteMatching;
+use rabbithole::model::document::{Document, DocumentItem};
+use rabbithole::model::error;
+use rabbithole::model::version::JsonApiVersion;
+use rabbithole::rule::Strictness;
+use rabbithole::service::{BoxError, JsonApiService, OperationCall};
+use rabbithole::store::CollectionStore;
+use rabbithole::tenant::TenantSource;
+use rabbithole::JSON_API_HEADER;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tower::ServiceBuilder;
+use tower_service::Service;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+#[entity(alias = "person")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+struct HumanStore;
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        Ok(vec![Human { id: "alice".into(), name: "Alice".into(), dogs: vec![] }, Human {
+            id: "bob".into(),
+            name: "Bob".into(),
+            dogs: vec![Dog { id: "a".into(), name: "1".into(), age: 3 }],
+        }])
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(self.all().await?.into_iter().find(|human| human.id == id))
+    }
+}
+
+fn service() -> JsonApiService {
+    JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })
+        .unwrap()
+        .resource(HumanStore)
+}
+
+/// Answers the humans after `0`
+struct SlowStore(Duration);
+
+#[async_trait]
+impl CollectionStore for SlowStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> {
+        tokio::time::sleep(self.0).await;
+        HumanStore.all().await
+    }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        tokio::time::sleep(self.0).await;
+        HumanStore.get(id).await
+    }
+}
+
+fn request(method: &str, path: &str) -> Request<()> {
+    Request::builder()
+        .method(method)
+        .uri(path)
+        .header(header::CONTENT_TYPE, JSON_API_HEADER)
+        .header(header::ACCEPT, JSON_API_HEADER)
+        .body(())
+        .unwrap()
+}
+
+fn call(service: &mut JsonApiService, method: &str, path: &str) -> Response<Vec<u8>> {
+    block_on(service.call(request(method, path))).unwrap()
+}
+
+fn error_code(body: &[u8]) -> Option<String> {
+    let doc: Document = serde_json::from_slice(body).unwrap();
+    match doc.item {
+        DocumentItem::Errors(errors) => errors[0].code.clone(),
+        _ => None,
+    }
+}
+
+#[test]
+fn routes_test() {
+    let mut service = service();
+
+    for (path, status) in &[
+        ("/api/people", StatusCode::OK),
+        ("/api/people/bob", StatusCode::OK),
+        ("/api/people/bob/relationships/dogs", StatusCode::OK),
+        ("/api/people/bob/dogs", StatusCode::OK),
+        ("/api/people/bob/cats", StatusCode::NOT_FOUND),
+    ] {
+        let resp = call(&mut service, "GET", path);
+        assert_eq!(resp.status(), *status, "GET {}", path);
+        assert_eq!(resp.headers()[header::CONTENT_TYPE], JSON_API_HEADER);
+    }
+
+    let resp = call(&mut service, "GET", "/api/people?sort=-name");
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["links"]["self"], "http://localhost:8080/api/people?sort=-name");
+    let names: Vec<&str> = body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| r["attributes"]["name"].as_str().unwrap())
+        .collect();
+    assert_eq!(names, vec!["Bob", "Alice"]);
+
+    let resp = call(&mut service, "GET", "/api/people/bob/dogs");
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["data"][0]["id"], "a");
+}
+
+#[test]
+fn conditional_test() {
+    let mut service = service();
+    let resp = call(&mut service, "GET", "/api/people/bob");
+    let etag = resp.headers()[header::ETAG].clone();
+
+    let mut req = request("GET", "/api/people/bob");
+    req.headers_mut().insert(header::IF_NONE_MATCH, etag.clone());
+    let resp = block_on(service.call(req)).unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+    assert_eq!(resp.headers()[header::ETAG], etag);
+    assert!(resp.body().is_empty());
+}
+
+#[test]
+fn alias_test() {
+    let mut service = service();
+
+    for (path, location) in &[
+        ("/api/person", "/api/people"),
+        ("/api/person/bob?include=dogs", "/api/people/bob?include=dogs"),
+        ("/api/person/bob/relationships/dogs", "/api/people/bob/relationships/dogs"),
+    ] {
+        let resp = call(&mut service, "GET", path);
+        assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT, "GET {}", path);
+        assert_eq!(resp.headers()[header::LOCATION], *location);
+    }
+
+    // The alias is accepted in the query, and advertised in the meta of the resources
+    let resp = call(&mut service, "GET", "/api/people/bob?fields[person]=name");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["data"]["attributes"]["name"], "Bob");
+    assert!(body["data"].get("relationships").is_none_or(|r| r.as_object().unwrap().is_empty()));
+    assert_eq!(body["data"]["meta"]["aliases"], serde_json::json!(["person"]));
+
+    let resp = call(&mut service, "GET", "/api/dog");
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+}
+
+#[test]
+fn route_matching_test() {
+    // The type segments are matched exactly by default
+    let resp = call(&mut service(), "GET", "/api/People");
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let mut service = service().with_route_matching(RouteMatching::lenient());
+    for (path, link) in &[
+        ("/api/People/", "http://localhost:8080/api/people"),
+        ("/api/PEOPLE?sort=-name", "http://localhost:8080/api/people?sort=-name"),
+        ("/api/people/bob/", "http://localhost:8080/api/people/bob"),
+        ("/api/People/bob/dogs/", "http://localhost:8080/api/people/bob/dogs"),
+    ] {
+        let resp = call(&mut service, "GET", path);
+        assert_eq!(resp.status(), StatusCode::OK, "GET {}", path);
+        let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(body["links"]["self"], *link, "GET {}", path);
+    }
+
+    // The aliases are matched with the same tolerances
+    let resp = call(&mut service, "GET", "/api/Person/bob/");
+    assert_eq!(resp.status(), StatusCode::PERMANENT_REDIRECT);
+    assert_eq!(resp.headers()[header::LOCATION], "/api/people/bob");
+
+    // Only the type segments are matched ignoring the case
+    let resp = call(&mut service, "GET", "/api/people/bob/Dogs");
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let matching: RouteMatching = serde_json::from_str(r#"{"trailing_slash": true}"#).unwrap();
+    assert_eq!(
+        matching.canonical_location("/api", "/api/people/", None, vec!["people"]),
+        Some("/api/people".into())
+    );
+    assert_eq!(matching.canonical_location("/api", "/api/People", None, vec!["people"]), None);
+}
+
+#[test]
+fn tenant_test() {
+    let mut by_path = service().with_tenant(TenantSource::Path);
+    let resp = call(&mut by_path, "GET", "/api/acme/people/bob");
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["links"]["self"], "http://localhost:8080/api/acme/people/bob");
+    assert_eq!(body["data"]["links"]["self"], "http://localhost:8080/api/acme/people/bob");
+
+    // The aliases are redirected in the tenant
+    let resp = call(&mut by_path, "GET", "/api/acme/person/bob");
+    assert_eq!(resp.headers()[header::LOCATION], "/api/acme/people/bob");
+
+    let resp = call(&mut by_path, "GET", "/api/people");
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+
+    let mut by_header = service().with_tenant(TenantSource::Header("X-Tenant-Id".into()));
+    let resp = call(&mut by_header, "GET", "/api/people");
+    assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(error_code(resp.body()), Some("RBH-0013".into()));
+}
+
+#[test]
+fn errors_test() {
+    let mut service = service();
+
+    for path in &["/api/cats", "/api", "/other/people", "/api/people/bob/relationships/dogs/a"] {
+        let resp = call(&mut service, "GET", path);
+        assert_eq!(resp.status(), StatusCode::NOT_FOUND, "GET {}", path);
+        assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0008"));
+    }
+
+    let resp = call(&mut service, "DELETE", "/api/people/bob");
+    assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(resp.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+    assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0007"));
+
+    let resp = call(&mut service, "HEAD", "/api/people/bob");
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(resp.headers()[header::CONTENT_TYPE], JSON_API_HEADER);
+    assert!(resp.headers().contains_key(header::ETAG));
+    assert!(resp.body().is_empty());
+
+    let resp = call(&mut service, "OPTIONS", "/api/people/bob");
+    assert_eq!(resp.status(), StatusCode::NO_CONTENT);
+    assert_eq!(resp.headers()[header::ALLOW], "GET, HEAD, OPTIONS");
+
+    let req = Request::get("/api/people").body(()).unwrap();
+    let resp = block_on(service.call(req)).unwrap();
+    assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let mut service = self::service().with_header_rules(Strictness::Off);
+    let req = Request::get("/api/people").body(()).unwrap();
+    let resp = block_on(service.call(req)).unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn layered_test() {
+    let mut service =
+        JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })
+            .unwrap()
+            .layered_resource(
+                SlowStore(Duration::from_millis(1)),
+                ServiceBuilder::new().concurrency_limit(1).timeout(Duration::from_secs(5)),
+            );
+    let resp = service.call(request("GET", "/api/people/bob")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let body: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(body["data"]["attributes"]["name"], "Bob");
+
+    let resp = service.call(request("GET", "/api/people/bob/cats")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::NOT_FOUND);
+    assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0401"));
+
+    let mut service =
+        JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })
+            .unwrap()
+            .layered_resource(
+                SlowStore(Duration::from_secs(5)),
+                tower::timeout::TimeoutLayer::new(Duration::from_millis(10)),
+            );
+    let resp = service.call(request("GET", "/api/people")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(error_code(resp.body()).as_deref(), Some("RBH-0010"));
+
+    let reject = tower::layer::layer_fn(|_| {
+        tower::service_fn(|_: OperationCall| async {
+            Err::<serde_json::Value, BoxError>("overloaded".into())
+        })
+    });
+    let mut service =
+        JsonApiService::new("http://localhost:8080/api", JsonApiVersion { major: 1, minor: 0 })
+            .unwrap()
+            .layered_resource(HumanStore, reject);
+    let resp = service.call(request("GET", "/api/people")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let doc: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+    assert_eq!(doc["errors"][0]["detail"], "The operation cannot be served now: overloaded");
+}
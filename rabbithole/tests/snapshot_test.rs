@@ -0,0 +1,89 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::Entity;
+use rabbithole::query::Query;
+use rabbithole::testing::snapshot::{assert_snapshot, formatted};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+fn humans() -> Vec<Human> {
+    let dogs: Vec<Dog> =
+        (1 ..= 3).map(|i| Dog { id: i.to_string(), name: format!("Dog {}", i), age: i }).collect();
+    vec![
+        Human {
+            id: "alice".into(),
+            name: "Alice".into(),
+            dogs: vec![dogs[2].clone(), dogs[0].clone()],
+        },
+        Human { id: "bob".into(), name: "Bob".into(), dogs: vec![dogs[1].clone()] },
+    ]
+}
+
+#[test]
+fn formatted_test() {
+    let value = json!({
+        "data": [{ "type": "people", "id": "2", "attributes": { "name": "B", "age": 1 } }],
+        "included": [
+            { "type": "people", "id": "3" },
+            { "type": "dogs", "id": "9" },
+            { "type": "dogs", "id": "10" },
+        ],
+        "links": { "self": "/people" },
+    });
+    let json = formatted(&value);
+    assert!(json.ends_with("}\n"));
+    let key = |key: &str| json.find(key).unwrap();
+    assert!(key("\"data\"") < key("\"included\"") && key("\"included\"") < key("\"links\""));
+    assert!(key("\"age\"") < key("\"name\""));
+    let ids: Vec<&str> =
+        json.lines().filter(|line| line.contains("\"id\"")).map(str::trim).collect();
+    assert_eq!(ids, vec![r#""id": "2","#, r#""id": "10","#, r#""id": "9","#, r#""id": "3","#]);
+}
+
+#[test]
+fn snapshot_test() {
+    let doc = humans()
+        .to_document_automatically(
+            "http://example.com/api",
+            &Query::default(),
+            &"/people".parse().unwrap(),
+        )
+        .unwrap();
+    assert_snapshot(&doc, "data/snapshots/people.json");
+
+    // The same document is always formatted the same
+    let again = humans()
+        .to_document_automatically(
+            "http://example.com/api",
+            &Query::default(),
+            &"/people".parse().unwrap(),
+        )
+        .unwrap();
+    assert_eq!(formatted(&doc), formatted(&again));
+
+    let path = std::env::temp_dir().join("rabbithole_snapshot_test.json");
+    std::fs::write(&path, formatted(&json!({ "meta": { "count": 1, "total": 2 } }))).unwrap();
+    let message = std::panic::catch_unwind(|| {
+        assert_snapshot(&json!({ "meta": { "count": 2, "total": 2 } }), &path)
+    })
+    .unwrap_err();
+    let message = message.downcast_ref::<String>().unwrap();
+    assert!(
+        message.ends_with("@@ line 3 @@\n-     \"count\": 1,\n+     \"count\": 2,\n"),
+        "{}",
+        message
+    );
+}
@@ -45,3 +45,25 @@ fn two_field_sorting_test() {
     assert_eq!(dogs[1].id(), "c");
     assert_eq!(dogs[2].id(), "a");
 }
+
+#[test]
+fn sorted_indices_test() {
+    let dogs: Vec<Dog> = (0 .. 20)
+        .map(|n| Dog { id: n.to_string(), name: (n % 3).to_string(), age: (n * 7) % 5 })
+        .collect();
+    let sort_query: SortQuery =
+        vec![("name".into(), OrderType::Desc), ("age".into(), OrderType::Asc)].try_into().unwrap();
+    let indices = sort_query.sorted_indices(&dogs);
+    let mut sorted = dogs.clone();
+    sort_query.sort(&mut sorted);
+    let ids: Vec<String> = indices.iter().map(|&i| dogs[i].id()).collect();
+    assert_eq!(ids, sorted.iter().map(SingleEntity::id).collect::<Vec<_>>());
+    for pair in sorted.windows(2) {
+        assert!(
+            (pair[0].name.as_str(), -pair[0].age) >= (pair[1].name.as_str(), -pair[1].age),
+            "{} before {}",
+            pair[0].id,
+            pair[1].id
+        );
+    }
+}
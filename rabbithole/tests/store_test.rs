@@ -0,0 +1,138 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+#[macro_use]
+extern crate lazy_static;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use rabbithole::model::document::{Document, DocumentItem, PrimaryDataItem};
+use rabbithole::model::error;
+use rabbithole::model::resource::IdentifierData;
+use rabbithole::operation::{FetchPlan, Fetching};
+use rabbithole::query::sort::OrderType;
+use rabbithole::query::Query;
+use rabbithole::store::CollectionStore;
+use serde::{Deserialize, Serialize};
+use std::convert::TryInto;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+lazy_static! {
+    pub static ref DOGS: Vec<Dog> = vec![
+        Dog { id: "a".into(), name: "1".into(), age: 3 },
+        Dog { id: "b".into(), name: "2".into(), age: 2 },
+        Dog { id: "c".into(), name: "2".into(), age: 1 },
+    ];
+    pub static ref HUMANS: Vec<Human> =
+        vec![Human { id: "master".into(), name: "master".into(), dogs: DOGS[.. 2].to_vec() }];
+}
+
+struct HumanStore;
+
+#[async_trait]
+impl CollectionStore for HumanStore {
+    type Item = Human;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(HUMANS.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(HUMANS.iter().find(|h| h.id == id).cloned())
+    }
+}
+
+struct DogStore;
+
+#[async_trait]
+impl CollectionStore for DogStore {
+    type Item = Dog;
+
+    async fn all(&self) -> Result<Vec<Self::Item>, error::Error> { Ok(DOGS.clone()) }
+
+    async fn get(&self, id: &str) -> Result<Option<Self::Item>, error::Error> {
+        Ok(DOGS.iter().find(|d| d.id == id).cloned())
+    }
+}
+
+#[test]
+fn fetch_collection_test() {
+    let query = Query {
+        sort: vec![("age".into(), OrderType::Asc)].try_into().unwrap(),
+        ..Default::default()
+    };
+    let dogs = block_on(DogStore.fetch_collection(&FetchPlan::new::<Dog>(&query))).unwrap();
+    assert_eq!(dogs.len(), 3);
+
+    let doc = block_on(DogStore.vec_to_document(
+        &dogs,
+        "http://example.com",
+        &query,
+        &"/dogs".parse().unwrap(),
+    ))
+    .unwrap();
+    if let DocumentItem::PrimaryData(Some((data, _))) = doc.item {
+        let data = data.data();
+        assert_eq!(data[0].id.id, "c");
+        assert_eq!(data[1].id.id, "b");
+        assert_eq!(data[2].id.id, "a");
+    } else {
+        unreachable!();
+    }
+}
+
+#[test]
+fn fetch_single_test() {
+    let query = Query::default();
+    let plan = FetchPlan::new::<Dog>(&query);
+    let dog = block_on(DogStore.fetch_single("b", &plan)).unwrap();
+    assert_eq!(dog.unwrap().id, "b");
+    assert!(block_on(DogStore.fetch_single("none", &plan)).unwrap().is_none());
+}
+
+#[test]
+fn fetch_relationship_test() {
+    let query = Query::default();
+    let path = "/people/master/relationships/dogs".parse().unwrap();
+    let relat =
+        block_on(HumanStore.fetch_relationship("master", "dogs", "http://a", &query, &path))
+            .unwrap();
+    if let IdentifierData::Multiple(ids) = relat.data {
+        assert_eq!(ids.len(), 2);
+    } else {
+        unreachable!();
+    }
+
+    let err = block_on(HumanStore.fetch_relationship("master", "cats", "http://a", &query, &path))
+        .unwrap_err();
+    assert_eq!(err.code, Some("RBH-0401".into()));
+    let err = block_on(HumanStore.fetch_relationship("none", "dogs", "http://a", &query, &path))
+        .unwrap_err();
+    assert_eq!(err.code, Some("RBH-0404".into()));
+}
+
+#[test]
+fn fetch_related_test() {
+    let query = Query::default();
+    let path = "/people/master/dogs".parse().unwrap();
+    let related =
+        block_on(HumanStore.fetch_related("master", "dogs", "http://a", &query, &path)).unwrap();
+    let doc: Document = serde_json::from_value(related).unwrap();
+    if let DocumentItem::PrimaryData(Some((PrimaryDataItem::Multiple(dogs), included))) = doc.item {
+        assert_eq!(dogs.len(), 2);
+        assert_eq!(dogs[0].id.id, "a");
+        assert_eq!(dogs[1].id.id, "b");
+        assert!(included.is_empty());
+    } else {
+        unreachable!();
+    }
+}
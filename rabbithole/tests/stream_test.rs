@@ -0,0 +1,99 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::entity::Entity;
+use rabbithole::model::link::{Link, RawUri};
+use rabbithole::query::Query;
+use rabbithole::stream::DocumentWriter;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::iter::FromIterator;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "people")]
+pub struct Human {
+    #[entity(id)]
+    pub id: String,
+    pub name: String,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+const URI: &str = "http://localhost:8080/api";
+
+fn humans() -> Vec<Human> {
+    let dog = |n: i32| Dog { id: format!("dog-{}", n), name: format!("Dog {}", n), age: n };
+    vec![Human { id: "1".into(), name: "Alice".into(), dogs: vec![dog(1), dog(2)] }, Human {
+        id: "2".into(),
+        name: "Bob".into(),
+        dogs: vec![dog(2), dog(3)],
+    }]
+}
+
+fn query(query: &str) -> Query {
+    let uri: http::Uri = format!("/people?{}", query).parse().unwrap();
+    Query::from_uri(&uri).unwrap()
+}
+
+#[test]
+fn document_writer_test() {
+    let humans = humans();
+    let request_path: RawUri = "/people".parse().unwrap();
+    let links = HashMap::from_iter(vec![Link::slf(URI, request_path.clone())]);
+    for query in [
+        query("page[offset]=0&page[limit]=10"),
+        query(
+            "include=dogs&fields[people]=name,dogs&fields[dogs]=age&page[offset]=0&page[limit]=10",
+        ),
+        query("include=&page[offset]=0&page[limit]=10"),
+    ] {
+        let mut writer = DocumentWriter::new(vec![]).unwrap();
+        for human in &humans {
+            writer.entity(human, URI, &query).unwrap();
+        }
+        let bytes = writer.finish(Some(&links), None).unwrap();
+        let streamed: Value = serde_json::from_slice(&bytes).unwrap();
+
+        let doc = humans.to_document_automatically(URI, &query, &request_path).unwrap();
+        assert_eq!(streamed, serde_json::to_value(&doc).unwrap());
+    }
+}
+
+#[test]
+fn empty_document_test() {
+    let bytes = DocumentWriter::new(vec![]).unwrap().finish(None, None).unwrap();
+    assert_eq!(String::from_utf8(bytes).unwrap(), r#"{"data":[]}"#);
+}
+
+#[test]
+fn nested_include_test() {
+    let query = query("include=dogs.master&page[offset]=0&page[limit]=10");
+    let mut writer = DocumentWriter::new(vec![]).unwrap();
+    let err = writer.entity(&humans()[0], URI, &query).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    let err = err.get_ref().unwrap().downcast_ref::<rabbithole::model::error::Error>().unwrap();
+    assert_eq!(err.code.as_deref(), Some("RBH-0004"));
+}
+
+#[cfg(feature = "async-write")]
+#[test]
+fn async_document_writer_test() {
+    use rabbithole::stream::AsyncDocumentWriter;
+    let query = query("include=dogs&page[offset]=0&page[limit]=10");
+    let humans = humans();
+    let bytes = futures::executor::block_on(async {
+        let mut writer = AsyncDocumentWriter::new(futures::io::Cursor::new(vec![])).await?;
+        for human in &humans {
+            writer.entity(human, URI, &query).await?;
+        }
+        writer.finish(None, None).await
+    })
+    .unwrap()
+    .into_inner();
+    let streamed: Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(streamed["data"].as_array().unwrap().len(), 2);
+    assert_eq!(streamed["included"].as_array().unwrap().len(), 3);
+}
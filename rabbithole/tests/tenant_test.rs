@@ -0,0 +1,121 @@
+pub mod common;
+
+use async_trait::async_trait;
+use common::Dog;
+use futures::executor::block_on;
+use http::{HeaderMap, HeaderValue, Uri};
+use rabbithole::handler::{self, OperationRequest, Route};
+use rabbithole::model::error;
+use rabbithole::model::link::RawUri;
+use rabbithole::model::relationship::Relationship;
+use rabbithole::operation::*;
+use rabbithole::query::Query;
+use rabbithole::tenant::{self, TenantScoped, TenantSource};
+
+/// The dogs of the tenants by their names
+struct DogService;
+
+impl TenantScoped for Dog {
+    fn tenant(&self) -> &str { &self.name }
+}
+
+fn dogs() -> Vec<Dog> {
+    vec![
+        Dog { id: "a".into(), name: "acme".into(), age: 3 },
+        Dog { id: "b".into(), name: "initech".into(), age: 2 },
+        Dog { id: "c".into(), name: "acme".into(), age: 1 },
+    ]
+}
+
+#[async_trait]
+impl Fetching for DogService {
+    type Item = Dog;
+
+    async fn fetch_collection(
+        &self, plan: &FetchPlan<'_>,
+    ) -> Result<Vec<Self::Item>, error::Error> {
+        Ok(tenant::partition(dogs(), plan.tenant()))
+    }
+
+    async fn fetch_single(
+        &self, id: &str, _: &FetchPlan<'_>,
+    ) -> Result<Option<Self::Item>, error::Error> {
+        Ok(dogs().into_iter().find(|d| d.id == id))
+    }
+
+    async fn fetch_relationship(
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+    ) -> Result<Relationship, error::Error> {
+        Err(error::Error::FieldNotExist(related_field, None))
+    }
+
+    async fn fetch_related(
+        &self, _: &str, related_field: &str, _: &str, _: &Query, _: &RawUri,
+    ) -> Result<serde_json::Value, error::Error> {
+        Err(error::Error::FieldNotExist(related_field, None))
+    }
+
+    async fn can(&self, ctx: &OperationContext, _: &Action, item: &Self::Item) -> Decision {
+        tenant::decide(ctx, item)
+    }
+}
+
+fn request(path: &str, tenant: &str) -> OperationRequest {
+    let uri: Uri = path.parse().unwrap();
+    OperationRequest {
+        context: OperationContext { tenant: Some(tenant.into()), ..Default::default() },
+        query: Query::from_uri(&uri).unwrap(),
+        base: format!("http://{}.example.com/api", tenant),
+        request_path: uri.into(),
+    }
+}
+
+#[test]
+fn extract_test() {
+    let base: url::Url = "http://example.com/api".parse().unwrap();
+    let mut headers = HeaderMap::new();
+    headers.insert("x-tenant-id", HeaderValue::from_static("acme"));
+    headers.insert(http::header::HOST, HeaderValue::from_static("initech.example.com:8080"));
+    let uri: Uri = "/api/umbrella/dogs".parse().unwrap();
+
+    let source: TenantSource = serde_json::from_str(r#"{"header": "X-Tenant-Id"}"#).unwrap();
+    assert_eq!(source.extract(&base, &uri, &headers).unwrap(), "acme");
+    assert_eq!(source.base(&base, "acme"), base);
+
+    let source: TenantSource = serde_json::from_str(r#""subdomain""#).unwrap();
+    assert_eq!(source.extract(&base, &uri, &headers).unwrap(), "initech");
+    assert_eq!(source.base(&base, "initech").as_str(), "http://initech.example.com/api");
+
+    let source: TenantSource = serde_json::from_str(r#""path""#).unwrap();
+    assert_eq!(source.extract(&base, &uri, &headers).unwrap(), "umbrella");
+    assert_eq!(source.base(&base, "umbrella").as_str(), "http://example.com/api/umbrella");
+
+    // The missing and the invalid tenants are rejected
+    let err = TenantSource::Subdomain.extract(&base, &uri, &HeaderMap::new()).unwrap_err();
+    assert_eq!(err.code.as_deref(), Some("RBH-0013"));
+    headers.insert("x-tenant-id", HeaderValue::from_static("../acme"));
+    let source = TenantSource::Header("X-Tenant-Id".into());
+    assert!(source.extract(&base, &uri, &headers).is_err());
+}
+
+#[test]
+fn partition_test() {
+    let req = request("/dogs", "acme");
+    let doc = block_on(handler::dispatch(&DogService, &Route::Collection, &req)).unwrap();
+    let ids: Vec<&str> =
+        doc["data"].as_array().unwrap().iter().map(|dog| dog["id"].as_str().unwrap()).collect();
+    assert_eq!(ids, vec!["a", "c"]);
+    assert_eq!(doc["data"][0]["links"]["self"], "http://acme.example.com/api/dogs/a");
+
+    // The dogs of the other tenants look like non-existing
+    let route = Route::Single("b".into());
+    let err = block_on(handler::dispatch(&DogService, &route, &req)).unwrap_err();
+    assert_eq!(err.status.as_deref(), Some("404"));
+    let doc = block_on(handler::dispatch(&DogService, &route, &request("/dogs/b", "initech")));
+    assert_eq!(doc.unwrap()["data"]["id"], "b");
+
+    let query = Query::default();
+    let plan = FetchPlan::new::<Dog>(&query);
+    assert_eq!(plan.tenant(), None);
+    assert_eq!(block_on(DogService.fetch_collection(&plan)).unwrap().len(), 3);
+}
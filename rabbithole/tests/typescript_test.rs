@@ -0,0 +1,65 @@
+extern crate rabbithole_derive as rbh_derive;
+
+pub mod common;
+
+use common::Dog;
+use rabbithole::schema::Registry;
+use rabbithole::typescript::generate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(rbh_derive::EntityDecorator, Serialize, Deserialize, Clone)]
+#[entity(type = "blog-posts")]
+pub struct BlogPost {
+    #[entity(id)]
+    pub id: String,
+    pub title: String,
+    pub rating: Option<f64>,
+    pub tags: Vec<Option<String>>,
+    pub counts: BTreeMap<String, u32>,
+    pub draft: bool,
+    pub extra: serde_json::Value,
+    #[entity(to_one)]
+    pub previous: Option<Box<BlogPost>>,
+    #[entity(to_many)]
+    pub dogs: Vec<Dog>,
+}
+
+#[test]
+fn generate_test() {
+    let ts = generate(&Registry::default().register::<BlogPost>().register::<Dog>());
+    assert!(ts.starts_with("// Generated by rabbithole, do not edit\n"));
+    assert!(ts.contains("export interface Document<D, I = Resource> {"));
+    assert!(ts.contains(
+        r#"
+export interface BlogPostAttributes {
+  title: string;
+  rating: number | null;
+  tags: (string | null)[];
+  counts: Record<string, number>;
+  draft: boolean;
+  extra: unknown;
+}
+
+export interface BlogPostRelationships {
+  previous?: Relationship<ResourceIdentifier<"blog-posts"> | null>;
+  dogs: Relationship<ResourceIdentifier<"dogs">[]>;
+}
+
+export interface BlogPost {
+  type: "blog-posts";
+  id: string;
+  attributes: BlogPostAttributes;
+  relationships?: BlogPostRelationships;
+"#
+    ));
+    assert!(ts.contains(
+        "export type BlogPostCollectionDocument = Document<BlogPost[], BlogPost | Dog>;\n"
+    ));
+    assert!(ts.contains("export interface DogRelationships {}\n"));
+    assert!(ts.ends_with("export type DogCollectionDocument = Document<Dog[]>;\n"));
+
+    // The related types out of the registry are only known by their resource identifiers
+    let ts = generate(&Registry::default().register::<BlogPost>());
+    assert!(ts.contains("export type BlogPostDocument = Document<BlogPost | null, BlogPost>;\n"));
+}